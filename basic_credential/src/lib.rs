@@ -12,6 +12,7 @@ use openmls_traits::{
     types::{CryptoError, SignatureScheme},
 };
 
+use ed25519_dalek::pkcs8::{DecodePrivateKey as _, EncodePublicKey as _};
 use p256::ecdsa::{signature::Signer as P256Signer, Signature, SigningKey};
 
 use rand::rngs::OsRng;
@@ -109,6 +110,81 @@ impl SignatureKeyPair {
         }
     }
 
+    /// Create a new signature key pair from the raw private key, i.e. the
+    /// 32 byte seed for Ed25519 and the 32 byte scalar for ECDSA P-256. The
+    /// public key is derived from the private key.
+    ///
+    /// Returns a [`CryptoError::KeyDecodingError`] if `private` is not a valid
+    /// key for `signature_scheme`.
+    pub fn from_raw_private_key(
+        signature_scheme: SignatureScheme,
+        private: Vec<u8>,
+    ) -> Result<Self, CryptoError> {
+        let public = match signature_scheme {
+            SignatureScheme::ECDSA_SECP256R1_SHA256 => {
+                let k = SigningKey::from_slice(&private)
+                    .map_err(|_| CryptoError::KeyDecodingError)?;
+                k.verifying_key().to_encoded_point(false).as_bytes().into()
+            }
+            SignatureScheme::ED25519 => {
+                let k = ed25519_dalek::SigningKey::try_from(private.as_slice())
+                    .map_err(|_| CryptoError::KeyDecodingError)?;
+                k.verifying_key().to_bytes().into()
+            }
+            _ => return Err(CryptoError::UnsupportedSignatureScheme),
+        };
+
+        Ok(Self::from_raw(signature_scheme, private, public))
+    }
+
+    /// Create a new signature key pair from a DER-encoded PKCS#8 private key,
+    /// e.g. as produced by `openssl genpkey`.
+    ///
+    /// Returns a [`CryptoError::KeyDecodingError`] if `pkcs8_der` can't be
+    /// decoded.
+    pub fn from_pkcs8_der(
+        signature_scheme: SignatureScheme,
+        pkcs8_der: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let private = match signature_scheme {
+            SignatureScheme::ECDSA_SECP256R1_SHA256 => {
+                let k = SigningKey::from_pkcs8_der(pkcs8_der)
+                    .map_err(|_| CryptoError::KeyDecodingError)?;
+                k.to_bytes().as_slice().into()
+            }
+            SignatureScheme::ED25519 => {
+                let k = ed25519_dalek::SigningKey::from_pkcs8_der(pkcs8_der)
+                    .map_err(|_| CryptoError::KeyDecodingError)?;
+                k.to_bytes().into()
+            }
+            _ => return Err(CryptoError::UnsupportedSignatureScheme),
+        };
+
+        Self::from_raw_private_key(signature_scheme, private)
+    }
+
+    /// Get the public key as DER-encoded SubjectPublicKeyInfo (SPKI), e.g. to
+    /// request a certificate for it.
+    pub fn public_key_spki_der(&self) -> Result<Vec<u8>, CryptoError> {
+        match self.signature_scheme {
+            SignatureScheme::ECDSA_SECP256R1_SHA256 => {
+                let k = p256::ecdsa::VerifyingKey::from_sec1_bytes(&self.public)
+                    .map_err(|_| CryptoError::InvalidPublicKey)?;
+                k.to_public_key_der()
+                    .map(|document| document.into_vec())
+                    .map_err(|_| CryptoError::CryptoLibraryError)
+            }
+            SignatureScheme::ED25519 => {
+                let k = ed25519_dalek::VerifyingKey::try_from(self.public.as_slice())
+                    .map_err(|_| CryptoError::InvalidPublicKey)?;
+                k.to_public_key_der()
+                    .map(|document| document.into_vec())
+                    .map_err(|_| CryptoError::CryptoLibraryError)
+            }
+            _ => Err(CryptoError::UnsupportedSignatureScheme),
+        }
+    }
+
     pub fn id(&self) -> StorageId {
         StorageId {
             value: id(&self.public, self.signature_scheme),
@@ -190,3 +266,88 @@ impl storage::traits::SignaturePublicKey<CURRENT_VERSION> for StorageId {}
 // Implement entity trait for the signature key pair
 impl storage::Entity<CURRENT_VERSION> for SignatureKeyPair {}
 impl storage::traits::SignatureKeyPair<CURRENT_VERSION> for SignatureKeyPair {}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::pkcs8::{DecodePublicKey as _, EncodePrivateKey as _};
+
+    use super::*;
+
+    const SIGNATURE_SCHEMES: [SignatureScheme; 2] = [
+        SignatureScheme::ED25519,
+        SignatureScheme::ECDSA_SECP256R1_SHA256,
+    ];
+
+    #[test]
+    fn raw_private_key_round_trip() {
+        for signature_scheme in SIGNATURE_SCHEMES {
+            let key_pair = SignatureKeyPair::new(signature_scheme).unwrap();
+            let imported =
+                SignatureKeyPair::from_raw_private_key(signature_scheme, key_pair.private.clone())
+                    .unwrap();
+            assert_eq!(imported.private, key_pair.private);
+            assert_eq!(imported.public, key_pair.public);
+            assert_eq!(imported.signature_scheme, signature_scheme);
+        }
+
+        assert!(matches!(
+            SignatureKeyPair::from_raw_private_key(SignatureScheme::ED25519, vec![0; 31]),
+            Err(CryptoError::KeyDecodingError)
+        ));
+        assert!(matches!(
+            SignatureKeyPair::from_raw_private_key(SignatureScheme::ED448, vec![0; 57]),
+            Err(CryptoError::UnsupportedSignatureScheme)
+        ));
+    }
+
+    #[test]
+    fn pkcs8_der_round_trip() {
+        for signature_scheme in SIGNATURE_SCHEMES {
+            let key_pair = SignatureKeyPair::new(signature_scheme).unwrap();
+            let pkcs8_der = match signature_scheme {
+                SignatureScheme::ED25519 => {
+                    ed25519_dalek::SigningKey::try_from(key_pair.private.as_slice())
+                        .unwrap()
+                        .to_pkcs8_der()
+                        .unwrap()
+                }
+                _ => SigningKey::from_slice(&key_pair.private)
+                    .unwrap()
+                    .to_pkcs8_der()
+                    .unwrap(),
+            };
+
+            let imported =
+                SignatureKeyPair::from_pkcs8_der(signature_scheme, pkcs8_der.as_bytes()).unwrap();
+            assert_eq!(imported.private, key_pair.private);
+            assert_eq!(imported.public, key_pair.public);
+        }
+
+        assert!(matches!(
+            SignatureKeyPair::from_pkcs8_der(SignatureScheme::ED25519, &[0; 16]),
+            Err(CryptoError::KeyDecodingError)
+        ));
+    }
+
+    #[test]
+    fn public_key_spki_der_round_trip() {
+        for signature_scheme in SIGNATURE_SCHEMES {
+            let key_pair = SignatureKeyPair::new(signature_scheme).unwrap();
+            let spki_der = key_pair.public_key_spki_der().unwrap();
+            let public: Vec<u8> = match signature_scheme {
+                SignatureScheme::ED25519 => {
+                    ed25519_dalek::VerifyingKey::from_public_key_der(&spki_der)
+                        .unwrap()
+                        .to_bytes()
+                        .into()
+                }
+                _ => p256::ecdsa::VerifyingKey::from_public_key_der(&spki_der)
+                    .unwrap()
+                    .to_encoded_point(false)
+                    .as_bytes()
+                    .into(),
+            };
+            assert_eq!(public, key_pair.public);
+        }
+    }
+}
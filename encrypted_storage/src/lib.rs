@@ -0,0 +1,950 @@
+//! # OpenMLS Encrypted Storage
+//!
+//! A [`StorageProvider`] that encrypts all values with an application-provided
+//! key before passing them to an inner storage, such that the secrets of
+//! OpenMLS are protected at rest with any storage backend.
+//!
+//! Each value is encrypted with the configured AEAD and a fresh random nonce.
+//! The label and the key under which a value is stored are the associated
+//! data, such that encrypted values can't be swapped between entries.
+//!
+//! The encryption doesn't protect the freshness of the values. Anyone with
+//! write access to the inner storage can replace a value with an older
+//! encrypted value of the same entry, or restore a deleted one, e.g. to revive
+//! the secrets of a past epoch or a used key package. Protecting against this
+//! needs state that the inner storage can't roll back, such as a monotonic
+//! counter in trusted hardware, and is out of scope for this crate.
+//!
+//! Keys, such as group IDs and public keys, are not encrypted, because the
+//! inner storage needs them to look up values. The references of queued
+//! proposals are stored in plain as well, since they are both keys and values.
+//!
+//! ```
+//! use openmls_encrypted_storage::EncryptedStorage;
+//! use openmls_memory_storage::MemoryStorage;
+//! use openmls_rust_crypto::RustCrypto;
+//! use openmls_traits::types::AeadType;
+//!
+//! // In practice, the key comes from the platform's key store.
+//! let key = [0x42; 32];
+//! let storage = EncryptedStorage::new(
+//!     MemoryStorage::default(),
+//!     RustCrypto::default(),
+//!     AeadType::ChaCha20Poly1305,
+//!     &key,
+//! )
+//! .unwrap();
+//! ```
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    random::OpenMlsRand,
+    storage::*,
+    types::{AeadType, CryptoError},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// A [`StorageProvider`] that encrypts all values before writing them to the
+/// `Storage`, using the `Crypto` provider for the AEAD and the nonces.
+pub struct EncryptedStorage<Storage, Crypto> {
+    inner: Storage,
+    crypto: Crypto,
+    aead: AeadType,
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl<Storage, Crypto> std::fmt::Debug for EncryptedStorage<Storage, Crypto>
+where
+    Storage: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedStorage")
+            .field("inner", &self.inner)
+            .field("aead", &self.aead)
+            .field("key", &"***")
+            .finish()
+    }
+}
+
+impl<Storage: StorageProvider<CURRENT_VERSION>, Crypto: OpenMlsCrypto + OpenMlsRand>
+    EncryptedStorage<Storage, Crypto>
+{
+    /// Create a storage that encrypts values with `key` for the `aead` before
+    /// writing them to `inner`.
+    ///
+    /// Returns [`EncryptedStorageError::InvalidKeyLength`] if the length of
+    /// the `key` doesn't match the `aead`.
+    pub fn new(
+        inner: Storage,
+        crypto: Crypto,
+        aead: AeadType,
+        key: &[u8],
+    ) -> Result<Self, EncryptedStorageError<Storage::Error>> {
+        if key.len() != aead.key_size() {
+            return Err(EncryptedStorageError::InvalidKeyLength);
+        }
+
+        Ok(Self {
+            inner,
+            crypto,
+            aead,
+            key: Zeroizing::new(key.to_vec()),
+        })
+    }
+
+    /// Returns the inner storage, which only holds encrypted values.
+    pub fn inner(&self) -> &Storage {
+        &self.inner
+    }
+
+    /// Serialize and encrypt the `value` that is stored under the `label` and
+    /// `key`.
+    fn encrypt(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+        value: &impl Serialize,
+    ) -> Result<Ciphertext, EncryptedStorageError<Storage::Error>> {
+        let aad = serde_json::to_vec(&(label, key))?;
+        let plaintext = serde_json::to_vec(value)?;
+        let nonce = self
+            .crypto
+            .random_vec(self.aead.nonce_size())
+            .map_err(|_| EncryptedStorageError::Randomness)?;
+        let ciphertext = self
+            .crypto
+            .aead_encrypt(self.aead, &self.key, &plaintext, &nonce, &aad)?;
+
+        Ok(Ciphertext { nonce, ciphertext })
+    }
+
+    /// Decrypt and deserialize the `value` that is stored under the `label`
+    /// and `key`.
+    fn decrypt<V: DeserializeOwned>(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+        value: &Ciphertext,
+    ) -> Result<V, EncryptedStorageError<Storage::Error>> {
+        let aad = serde_json::to_vec(&(label, key))?;
+        let plaintext = self.crypto.aead_decrypt(
+            self.aead,
+            &self.key,
+            &value.ciphertext,
+            &value.nonce,
+            &aad,
+        )?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+/// Errors thrown by the encrypted storage.
+#[derive(thiserror::Error, Debug)]
+pub enum EncryptedStorageError<StorageError> {
+    #[error("The key length doesn't match the AEAD.")]
+    InvalidKeyLength,
+    #[error("Error encrypting or decrypting a value: {0}")]
+    Crypto(#[from] CryptoError),
+    #[error("Error generating a nonce.")]
+    Randomness,
+    #[error("Error serializing value: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Error in the inner storage: {0}")]
+    Storage(StorageError),
+}
+
+/// An encrypted value as it is written to the inner storage.
+#[derive(Serialize, Deserialize)]
+struct Ciphertext {
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Entity<CURRENT_VERSION> for Ciphertext {}
+impl traits::QueuedProposal<CURRENT_VERSION> for Ciphertext {}
+impl traits::TreeSync<CURRENT_VERSION> for Ciphertext {}
+impl traits::GroupContext<CURRENT_VERSION> for Ciphertext {}
+impl traits::InterimTranscriptHash<CURRENT_VERSION> for Ciphertext {}
+impl traits::ConfirmationTag<CURRENT_VERSION> for Ciphertext {}
+impl traits::SignatureKeyPair<CURRENT_VERSION> for Ciphertext {}
+impl traits::PskBundle<CURRENT_VERSION> for Ciphertext {}
+impl traits::HpkeKeyPair<CURRENT_VERSION> for Ciphertext {}
+impl traits::GroupState<CURRENT_VERSION> for Ciphertext {}
+impl traits::GroupEpochSecrets<CURRENT_VERSION> for Ciphertext {}
+impl traits::LeafNodeIndex<CURRENT_VERSION> for Ciphertext {}
+impl traits::MessageSecrets<CURRENT_VERSION> for Ciphertext {}
+impl traits::ResumptionPskStore<CURRENT_VERSION> for Ciphertext {}
+impl traits::KeyPackage<CURRENT_VERSION> for Ciphertext {}
+impl traits::MlsGroupJoinConfig<CURRENT_VERSION> for Ciphertext {}
+impl traits::LeafNode<CURRENT_VERSION> for Ciphertext {}
+
+const KEY_PACKAGE_LABEL: &str = "KeyPackage";
+const PSK_LABEL: &str = "Psk";
+const ENCRYPTION_KEY_PAIR_LABEL: &str = "EncryptionKeyPair";
+const SIGNATURE_KEY_PAIR_LABEL: &str = "SignatureKeyPair";
+const EPOCH_KEY_PAIRS_LABEL: &str = "EpochKeyPairs";
+
+// related to PublicGroup
+const TREE_LABEL: &str = "Tree";
+const GROUP_CONTEXT_LABEL: &str = "GroupContext";
+const INTERIM_TRANSCRIPT_HASH_LABEL: &str = "InterimTranscriptHash";
+const CONFIRMATION_TAG_LABEL: &str = "ConfirmationTag";
+
+// related to MlsGroup
+const JOIN_CONFIG_LABEL: &str = "MlsGroupJoinConfig";
+const OWN_LEAF_NODES_LABEL: &str = "OwnLeafNodes";
+const GROUP_STATE_LABEL: &str = "GroupState";
+const QUEUED_PROPOSAL_LABEL: &str = "QueuedProposal";
+const OWN_LEAF_NODE_INDEX_LABEL: &str = "OwnLeafNodeIndex";
+const EPOCH_SECRETS_LABEL: &str = "EpochSecrets";
+const RESUMPTION_PSK_STORE_LABEL: &str = "ResumptionPsk";
+const MESSAGE_SECRETS_LABEL: &str = "MessageSecrets";
+
+impl<Storage: StorageProvider<CURRENT_VERSION>, Crypto: OpenMlsCrypto + OpenMlsRand>
+    StorageProvider<CURRENT_VERSION> for EncryptedStorage<Storage, Crypto>
+{
+    type Error = EncryptedStorageError<Storage::Error>;
+
+    fn queue_proposal<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+        QueuedProposal: traits::QueuedProposal<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        proposal_ref: &ProposalRef,
+        proposal: &QueuedProposal,
+    ) -> Result<(), Self::Error> {
+        let proposal = self.encrypt(QUEUED_PROPOSAL_LABEL, &(group_id, proposal_ref), proposal)?;
+        self.inner
+            .queue_proposal(group_id, proposal_ref, &proposal)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_tree<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        TreeSync: traits::TreeSync<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        tree: &TreeSync,
+    ) -> Result<(), Self::Error> {
+        let tree = self.encrypt(TREE_LABEL, group_id, tree)?;
+        self.inner
+            .write_tree(group_id, &tree)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_interim_transcript_hash<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        InterimTranscriptHash: traits::InterimTranscriptHash<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        interim_transcript_hash: &InterimTranscriptHash,
+    ) -> Result<(), Self::Error> {
+        let interim_transcript_hash = self.encrypt(
+            INTERIM_TRANSCRIPT_HASH_LABEL,
+            group_id,
+            interim_transcript_hash,
+        )?;
+        self.inner
+            .write_interim_transcript_hash(group_id, &interim_transcript_hash)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_context<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupContext: traits::GroupContext<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_context: &GroupContext,
+    ) -> Result<(), Self::Error> {
+        let group_context = self.encrypt(GROUP_CONTEXT_LABEL, group_id, group_context)?;
+        self.inner
+            .write_context(group_id, &group_context)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_confirmation_tag<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ConfirmationTag: traits::ConfirmationTag<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        confirmation_tag: &ConfirmationTag,
+    ) -> Result<(), Self::Error> {
+        let confirmation_tag = self.encrypt(CONFIRMATION_TAG_LABEL, group_id, confirmation_tag)?;
+        self.inner
+            .write_confirmation_tag(group_id, &confirmation_tag)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+        SignatureKeyPair: traits::SignatureKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature_key_pair: &SignatureKeyPair,
+    ) -> Result<(), Self::Error> {
+        let signature_key_pair =
+            self.encrypt(SIGNATURE_KEY_PAIR_LABEL, public_key, signature_key_pair)?;
+        self.inner
+            .write_signature_key_pair(public_key, &signature_key_pair)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn queued_proposal_refs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<ProposalRef>, Self::Error> {
+        self.inner
+            .queued_proposal_refs(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn queued_proposals<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+        QueuedProposal: traits::QueuedProposal<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<(ProposalRef, QueuedProposal)>, Self::Error> {
+        let values: Vec<(ProposalRef, Ciphertext)> = self
+            .inner
+            .queued_proposals(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        values
+            .into_iter()
+            .map(|(proposal_ref, value)| {
+                let proposal =
+                    self.decrypt(QUEUED_PROPOSAL_LABEL, &(group_id, &proposal_ref), &value)?;
+                Ok((proposal_ref, proposal))
+            })
+            .collect()
+    }
+
+    fn tree<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        TreeSync: traits::TreeSync<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<TreeSync>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .tree(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(TREE_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn group_context<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupContext: traits::GroupContext<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupContext>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .group_context(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(GROUP_CONTEXT_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn interim_transcript_hash<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        InterimTranscriptHash: traits::InterimTranscriptHash<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<InterimTranscriptHash>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .interim_transcript_hash(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(INTERIM_TRANSCRIPT_HASH_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn confirmation_tag<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ConfirmationTag: traits::ConfirmationTag<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<ConfirmationTag>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .confirmation_tag(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(CONFIRMATION_TAG_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+        SignatureKeyPair: traits::SignatureKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<Option<SignatureKeyPair>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .signature_key_pair(public_key)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(SIGNATURE_KEY_PAIR_LABEL, public_key, &value))
+            .transpose()
+    }
+
+    fn write_key_package<
+        HashReference: traits::HashReference<CURRENT_VERSION>,
+        KeyPackage: traits::KeyPackage<CURRENT_VERSION>,
+    >(
+        &self,
+        hash_ref: &HashReference,
+        key_package: &KeyPackage,
+    ) -> Result<(), Self::Error> {
+        let key_package = self.encrypt(KEY_PACKAGE_LABEL, hash_ref, key_package)?;
+        self.inner
+            .write_key_package(hash_ref, &key_package)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_psk<
+        PskId: traits::PskId<CURRENT_VERSION>,
+        PskBundle: traits::PskBundle<CURRENT_VERSION>,
+    >(
+        &self,
+        psk_id: &PskId,
+        psk: &PskBundle,
+    ) -> Result<(), Self::Error> {
+        let psk = self.encrypt(PSK_LABEL, psk_id, psk)?;
+        self.inner
+            .write_psk(psk_id, &psk)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_encryption_key_pair<
+        EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &EncryptionKey,
+        key_pair: &HpkeKeyPair,
+    ) -> Result<(), Self::Error> {
+        let key_pair = self.encrypt(ENCRYPTION_KEY_PAIR_LABEL, public_key, key_pair)?;
+        self.inner
+            .write_encryption_key_pair(public_key, &key_pair)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn key_package<
+        KeyPackageRef: traits::HashReference<CURRENT_VERSION>,
+        KeyPackage: traits::KeyPackage<CURRENT_VERSION>,
+    >(
+        &self,
+        hash_ref: &KeyPackageRef,
+    ) -> Result<Option<KeyPackage>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .key_package(hash_ref)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(KEY_PACKAGE_LABEL, hash_ref, &value))
+            .transpose()
+    }
+
+    fn psk<PskBundle: traits::PskBundle<CURRENT_VERSION>, PskId: traits::PskId<CURRENT_VERSION>>(
+        &self,
+        psk_id: &PskId,
+    ) -> Result<Option<PskBundle>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .psk(psk_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(PSK_LABEL, psk_id, &value))
+            .transpose()
+    }
+
+    fn encryption_key_pair<
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+        EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &EncryptionKey,
+    ) -> Result<Option<HpkeKeyPair>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .encryption_key_pair(public_key)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(ENCRYPTION_KEY_PAIR_LABEL, public_key, &value))
+            .transpose()
+    }
+
+    fn delete_signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_signature_key_pair(public_key)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_encryption_key_pair<EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>>(
+        &self,
+        public_key: &EncryptionKey,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_encryption_key_pair(public_key)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_key_package<KeyPackageRef: traits::HashReference<CURRENT_VERSION>>(
+        &self,
+        hash_ref: &KeyPackageRef,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_key_package(hash_ref)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_psk<PskKey: traits::PskId<CURRENT_VERSION>>(
+        &self,
+        psk_id: &PskKey,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_psk(psk_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn group_state<
+        GroupState: traits::GroupState<CURRENT_VERSION>,
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupState>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .group_state(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(GROUP_STATE_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_group_state<
+        GroupState: traits::GroupState<CURRENT_VERSION>,
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_state: &GroupState,
+    ) -> Result<(), Self::Error> {
+        let group_state = self.encrypt(GROUP_STATE_LABEL, group_id, group_state)?;
+        self.inner
+            .write_group_state(group_id, &group_state)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_group_state<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_group_state(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn message_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MessageSecrets: traits::MessageSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<MessageSecrets>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .message_secrets(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(MESSAGE_SECRETS_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_message_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MessageSecrets: traits::MessageSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        message_secrets: &MessageSecrets,
+    ) -> Result<(), Self::Error> {
+        let message_secrets = self.encrypt(MESSAGE_SECRETS_LABEL, group_id, message_secrets)?;
+        self.inner
+            .write_message_secrets(group_id, &message_secrets)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_message_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_message_secrets(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn resumption_psk_store<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ResumptionPskStore: traits::ResumptionPskStore<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<ResumptionPskStore>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .resumption_psk_store(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(RESUMPTION_PSK_STORE_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_resumption_psk_store<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ResumptionPskStore: traits::ResumptionPskStore<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        resumption_psk_store: &ResumptionPskStore,
+    ) -> Result<(), Self::Error> {
+        let resumption_psk_store =
+            self.encrypt(RESUMPTION_PSK_STORE_LABEL, group_id, resumption_psk_store)?;
+        self.inner
+            .write_resumption_psk_store(group_id, &resumption_psk_store)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_all_resumption_psk_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_all_resumption_psk_secrets(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn own_leaf_index<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNodeIndex: traits::LeafNodeIndex<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<LeafNodeIndex>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .own_leaf_index(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(OWN_LEAF_NODE_INDEX_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_own_leaf_index<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNodeIndex: traits::LeafNodeIndex<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        own_leaf_index: &LeafNodeIndex,
+    ) -> Result<(), Self::Error> {
+        let own_leaf_index = self.encrypt(OWN_LEAF_NODE_INDEX_LABEL, group_id, own_leaf_index)?;
+        self.inner
+            .write_own_leaf_index(group_id, &own_leaf_index)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_own_leaf_index<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_own_leaf_index(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn group_epoch_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupEpochSecrets: traits::GroupEpochSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupEpochSecrets>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .group_epoch_secrets(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(EPOCH_SECRETS_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_group_epoch_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupEpochSecrets: traits::GroupEpochSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_epoch_secrets: &GroupEpochSecrets,
+    ) -> Result<(), Self::Error> {
+        let group_epoch_secrets =
+            self.encrypt(EPOCH_SECRETS_LABEL, group_id, group_epoch_secrets)?;
+        self.inner
+            .write_group_epoch_secrets(group_id, &group_epoch_secrets)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_group_epoch_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_group_epoch_secrets(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn write_encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+        key_pairs: &[HpkeKeyPair],
+    ) -> Result<(), Self::Error> {
+        let key = (group_id, epoch, leaf_index);
+        let key_pairs = key_pairs
+            .iter()
+            .map(|key_pair| self.encrypt(EPOCH_KEY_PAIRS_LABEL, &key, key_pair))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.inner
+            .write_encryption_epoch_key_pairs(group_id, epoch, leaf_index, &key_pairs)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+    ) -> Result<Vec<HpkeKeyPair>, Self::Error> {
+        let values: Vec<Ciphertext> = self
+            .inner
+            .encryption_epoch_key_pairs(group_id, epoch, leaf_index)
+            .map_err(EncryptedStorageError::Storage)?;
+        let key = (group_id, epoch, leaf_index);
+        values
+            .iter()
+            .map(|value| self.decrypt(EPOCH_KEY_PAIRS_LABEL, &key, value))
+            .collect()
+    }
+
+    fn delete_encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_encryption_epoch_key_pairs(group_id, epoch, leaf_index)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn clear_proposal_queue<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .clear_proposal_queue::<GroupId, ProposalRef>(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn mls_group_join_config<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MlsGroupJoinConfig: traits::MlsGroupJoinConfig<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<MlsGroupJoinConfig>, Self::Error> {
+        let value: Option<Ciphertext> = self
+            .inner
+            .mls_group_join_config(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        value
+            .map(|value| self.decrypt(JOIN_CONFIG_LABEL, group_id, &value))
+            .transpose()
+    }
+
+    fn write_mls_join_config<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MlsGroupJoinConfig: traits::MlsGroupJoinConfig<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        config: &MlsGroupJoinConfig,
+    ) -> Result<(), Self::Error> {
+        let config = self.encrypt(JOIN_CONFIG_LABEL, group_id, config)?;
+        self.inner
+            .write_mls_join_config(group_id, &config)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn own_leaf_nodes<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNode: traits::LeafNode<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<LeafNode>, Self::Error> {
+        let values: Vec<Ciphertext> = self
+            .inner
+            .own_leaf_nodes(group_id)
+            .map_err(EncryptedStorageError::Storage)?;
+        values
+            .iter()
+            .map(|value| self.decrypt(OWN_LEAF_NODES_LABEL, group_id, value))
+            .collect()
+    }
+
+    fn append_own_leaf_node<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNode: traits::LeafNode<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        leaf_node: &LeafNode,
+    ) -> Result<(), Self::Error> {
+        let leaf_node = self.encrypt(OWN_LEAF_NODES_LABEL, group_id, leaf_node)?;
+        self.inner
+            .append_own_leaf_node(group_id, &leaf_node)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_own_leaf_nodes<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_own_leaf_nodes(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_group_config<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_group_config(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_tree<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_tree(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_confirmation_tag<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_confirmation_tag(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_context<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_context(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn delete_interim_transcript_hash<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .delete_interim_transcript_hash(group_id)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn remove_proposal<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        proposal_ref: &ProposalRef,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .remove_proposal(group_id, proposal_ref)
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        self.inner
+            .begin_transaction()
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        self.inner
+            .commit_transaction()
+            .map_err(EncryptedStorageError::Storage)
+    }
+
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        self.inner
+            .rollback_transaction()
+            .map_err(EncryptedStorageError::Storage)
+    }
+}
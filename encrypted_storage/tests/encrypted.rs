@@ -0,0 +1,137 @@
+use openmls_encrypted_storage::{EncryptedStorage, EncryptedStorageError};
+use openmls_memory_storage::MemoryStorage;
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    storage::{traits, Entity, Key, StorageProvider, CURRENT_VERSION},
+    types::AeadType,
+};
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+struct ProposalRef(usize);
+impl traits::ProposalRef<CURRENT_VERSION> for ProposalRef {}
+impl Key<CURRENT_VERSION> for ProposalRef {}
+impl Entity<CURRENT_VERSION> for ProposalRef {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct Proposal(Vec<u8>);
+impl traits::QueuedProposal<CURRENT_VERSION> for Proposal {}
+impl Entity<CURRENT_VERSION> for Proposal {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TreeSync(String);
+impl traits::TreeSync<CURRENT_VERSION> for TreeSync {}
+impl Entity<CURRENT_VERSION> for TreeSync {}
+
+/// Any value, to access the inner storage directly.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct RawValue(serde_json::Value);
+impl traits::TreeSync<CURRENT_VERSION> for RawValue {}
+impl Entity<CURRENT_VERSION> for RawValue {}
+
+const KEY: [u8; 32] = [0x42; 32];
+
+fn storage(key: &[u8]) -> EncryptedStorage<MemoryStorage, RustCrypto> {
+    EncryptedStorage::new(
+        MemoryStorage::default(),
+        RustCrypto::default(),
+        AeadType::ChaCha20Poly1305,
+        key,
+    )
+    .unwrap()
+}
+
+#[test]
+fn read_write_delete() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = storage(&KEY);
+
+    let tree = TreeSync("a very secret tree".to_string());
+    storage.write_tree(&group_id, &tree).unwrap();
+    let tree_read: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree_read, Some(tree));
+
+    // The inner storage doesn't contain the plaintext.
+    let values = storage.inner().values.read().unwrap();
+    assert!(values.values().all(|value| !value
+        .windows(b"secret".len())
+        .any(|window| window == b"secret")));
+    drop(values);
+
+    storage.delete_tree(&group_id).unwrap();
+    let tree_read: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree_read, None);
+}
+
+#[test]
+fn proposals() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = storage(&KEY);
+    let proposals = (0..3)
+        .map(|i| Proposal(format!("TestProposal{i}").as_bytes().to_vec()))
+        .collect::<Vec<_>>();
+
+    for (i, proposal) in proposals.iter().enumerate() {
+        storage
+            .queue_proposal(&group_id, &ProposalRef(i), proposal)
+            .unwrap();
+    }
+    storage.remove_proposal(&group_id, &ProposalRef(1)).unwrap();
+
+    let proposals_read: Vec<(ProposalRef, Proposal)> = storage.queued_proposals(&group_id).unwrap();
+    assert_eq!(
+        proposals_read,
+        vec![
+            (ProposalRef(0), proposals[0].clone()),
+            (ProposalRef(2), proposals[2].clone())
+        ]
+    );
+}
+
+#[test]
+fn invalid_key() {
+    let err = EncryptedStorage::new(
+        MemoryStorage::default(),
+        RustCrypto::default(),
+        AeadType::ChaCha20Poly1305,
+        &[0; 16],
+    )
+    .unwrap_err();
+    assert!(matches!(err, EncryptedStorageError::InvalidKeyLength));
+}
+
+#[test]
+fn tampering_is_detected() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let other_group_id = TestGroupId(b"OtherGroupId".to_vec());
+    let storage = storage(&KEY);
+    storage
+        .write_tree(&group_id, &TreeSync("tree".to_string()))
+        .unwrap();
+
+    // A value moved to another entry doesn't decrypt.
+    let value: Option<RawValue> = storage.inner().tree(&group_id).unwrap();
+    storage
+        .inner()
+        .write_tree(&other_group_id, &value.unwrap())
+        .unwrap();
+    let err = storage.tree::<_, TreeSync>(&other_group_id).unwrap_err();
+    assert!(matches!(err, EncryptedStorageError::Crypto(_)));
+
+    // A value doesn't decrypt with another key.
+    let other_storage = EncryptedStorage::new(
+        storage.inner().clone(),
+        RustCrypto::default(),
+        AeadType::ChaCha20Poly1305,
+        &[0x23; 32],
+    )
+    .unwrap();
+    let err = other_storage.tree::<_, TreeSync>(&group_id).unwrap_err();
+    assert!(matches!(err, EncryptedStorageError::Crypto(_)));
+}
@@ -0,0 +1,146 @@
+//! Mutates valid messages of a group and processes them.
+//!
+//! The input is read as the index of the message to mutate, followed by pairs
+//! of a two byte offset into the message and a byte that is XORed with the
+//! byte at the offset.
+//!
+//! The group and the messages are built from seeded providers and signature
+//! keys at a fixed time, such that the same input always mutates the same
+//! messages and crashes can be reproduced. The update paths of commits are
+//! encrypted with HPKE, which uses randomness of the operating system, so
+//! only commits without an update path are mutated.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use openmls::prelude::{tls_codec::*, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test_crypto::OpenMlsTestCrypto;
+
+const CIPHERSUITE: Ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+
+/// The time at which the group is created and the messages are processed,
+/// 2024-01-01T00:00:00Z.
+const NOW: u64 = 1_704_067_200;
+
+/// A [`TimeProvider`] that always returns [`NOW`].
+struct FixedTime;
+
+impl TimeProvider for FixedTime {
+    fn now(&self) -> Option<u64> {
+        Some(NOW)
+    }
+}
+
+fn new_provider(seed: u8) -> OpenMlsTestCrypto {
+    OpenMlsTestCrypto::with_seed([seed; 32]).with_time_provider(FixedTime)
+}
+
+fn new_member(identity: &[u8], seed: u8) -> (CredentialWithKey, SignatureKeyPair) {
+    let signer =
+        SignatureKeyPair::from_raw_private_key(CIPHERSUITE.signature_algorithm(), vec![seed; 32])
+            .unwrap();
+    let credential_with_key = CredentialWithKey {
+        credential: BasicCredential::new(identity.to_vec()).into(),
+        signature_key: signer.to_public_vec().into(),
+    };
+    (credential_with_key, signer)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&index, mutations)) = data.split_first() else {
+        return;
+    };
+
+    let alice_provider = new_provider(1);
+    let bob_provider = new_provider(2);
+    let charlie_provider = new_provider(3);
+    let (alice_credential, alice_signer) = new_member(b"Alice", 1);
+    let (bob_credential, bob_signer) = new_member(b"Bob", 2);
+    let (charlie_credential, charlie_signer) = new_member(b"Charlie", 3);
+    let bob_key_package = KeyPackage::builder()
+        .build(CIPHERSUITE, &bob_provider, &bob_signer, bob_credential)
+        .unwrap();
+    let charlie_key_package = KeyPackage::builder()
+        .build(
+            CIPHERSUITE,
+            &charlie_provider,
+            &charlie_signer,
+            charlie_credential,
+        )
+        .unwrap();
+
+    // Alice creates a group with Bob, who accepts both public and private
+    // messages.
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(CIPHERSUITE)
+        .with_wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .lifetime(Lifetime::new_with_time_provider(60 * 60, &FixedTime).unwrap())
+        .build(&alice_provider, &alice_signer, alice_credential)
+        .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            &alice_provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(&alice_provider).unwrap();
+    let welcome =
+        MlsMessageIn::tls_deserialize_exact(welcome.tls_serialize_detached().unwrap()).unwrap();
+    let MlsMessageBodyIn::Welcome(welcome) = welcome.extract() else {
+        unreachable!()
+    };
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        &bob_provider,
+        &MlsGroupJoinConfig::builder()
+            .wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .build(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_welcome| staged_welcome.into_group(&bob_provider))
+    .unwrap();
+
+    // The valid messages of Alice that are mutated.
+    let message = match index % 3 {
+        0 => alice_group
+            .create_message(&alice_provider, &alice_signer, b"Hello Bob")
+            .unwrap(),
+        1 => {
+            alice_group
+                .propose_self_update(&alice_provider, &alice_signer, Default::default())
+                .unwrap()
+                .0
+        }
+        _ => {
+            alice_group
+                .add_members_without_update(
+                    &alice_provider,
+                    &alice_signer,
+                    &[charlie_key_package.key_package().clone()],
+                )
+                .unwrap()
+                .0
+        }
+    };
+    let mut message = message.tls_serialize_detached().unwrap();
+
+    for mutation in mutations.chunks_exact(3) {
+        let offset = u16::from_be_bytes([mutation[0], mutation[1]]) as usize;
+        let len = message.len();
+        message[offset % len] ^= mutation[2];
+    }
+
+    let Ok(message) = MlsMessageIn::tls_deserialize_exact(&message) else {
+        return;
+    };
+    let Ok(message) = message.try_into_protocol_message() else {
+        return;
+    };
+    if let Ok(processed_message) = bob_group.process_message(&bob_provider, message) {
+        if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+            processed_message.into_content()
+        {
+            let _ = bob_group.merge_staged_commit(&bob_provider, *staged_commit);
+        }
+    }
+});
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use openmls::prelude::{tls_codec::*, *};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = PublicMessageIn::tls_deserialize_exact(data);
+});
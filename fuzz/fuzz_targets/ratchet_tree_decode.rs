@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use openmls::prelude::{tls_codec::*, *};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(ratchet_tree) = RatchetTreeIn::tls_deserialize_exact(data) else {
+        return;
+    };
+    let provider = OpenMlsRustCrypto::default();
+    let _ = ratchet_tree.into_verified(
+        Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+        provider.crypto(),
+        &GroupId::from_slice(b"fuzz"),
+    );
+});
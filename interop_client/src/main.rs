@@ -1373,77 +1373,99 @@ impl MlsClient for MlsClientImpl {
         &self,
         _: Request<CommitRequest>,
     ) -> Result<Response<CommitResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "ReInit commits are not supported by OpenMLS yet",
+        ))
     }
 
     async fn handle_pending_re_init_commit(
         &self,
         _: Request<HandlePendingCommitRequest>,
     ) -> Result<Response<HandleReInitCommitResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "ReInit commits are not supported by OpenMLS yet",
+        ))
     }
 
     async fn handle_re_init_commit(
         &self,
         _: Request<HandleCommitRequest>,
     ) -> Result<Response<HandleReInitCommitResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "ReInit commits are not supported by OpenMLS yet",
+        ))
     }
 
     async fn re_init_welcome(
         &self,
         _: Request<ReInitWelcomeRequest>,
     ) -> Result<Response<CreateSubgroupResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "ReInit welcomes are not supported by OpenMLS yet",
+        ))
     }
 
     async fn handle_re_init_welcome(
         &self,
         _: Request<HandleReInitWelcomeRequest>,
     ) -> Result<Response<JoinGroupResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "ReInit welcomes are not supported by OpenMLS yet",
+        ))
     }
 
     async fn create_branch(
         &self,
         _: Request<CreateBranchRequest>,
     ) -> Result<Response<CreateSubgroupResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "Branching is not supported by OpenMLS yet",
+        ))
     }
 
     async fn handle_branch(
         &self,
         _: Request<HandleBranchRequest>,
     ) -> Result<Response<HandleBranchResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "Branching is not supported by OpenMLS yet",
+        ))
     }
 
     async fn new_member_add_proposal(
         &self,
         _: Request<NewMemberAddProposalRequest>,
     ) -> Result<Response<NewMemberAddProposalResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "New member add proposals are not implemented by this interop client",
+        ))
     }
 
     async fn create_external_signer(
         &self,
         _: Request<CreateExternalSignerRequest>,
     ) -> Result<Response<CreateExternalSignerResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "External signers are not implemented by this interop client",
+        ))
     }
 
     async fn add_external_signer(
         &self,
         _: Request<AddExternalSignerRequest>,
     ) -> Result<Response<ProposalResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "External signers are not implemented by this interop client",
+        ))
     }
 
     async fn external_signer_proposal(
         &self,
         _: Request<ExternalSignerProposalRequest>,
     ) -> Result<Response<ProposalResponse>, Status> {
-        todo!()
+        Err(Status::unimplemented(
+            "External signer proposals are not implemented by this interop client",
+        ))
     }
 
     async fn free(&self, _request: Request<FreeRequest>) -> Result<Response<FreeResponse>, Status> {
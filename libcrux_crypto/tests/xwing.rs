@@ -0,0 +1,82 @@
+//! HPKE with the X-Wing KEM of the hybrid ciphersuite.
+
+use openmls_libcrux_crypto::Provider;
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite, OpenMlsProvider};
+
+const CIPHERSUITE: Ciphersuite = Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519;
+
+/// The length of an X-Wing public key.
+const PUBLIC_KEY_LENGTH: usize = 1216;
+
+/// The length of an X-Wing ciphertext, i.e. the KEM output.
+const CIPHERTEXT_LENGTH: usize = 1120;
+
+#[test]
+fn xwing_hpke_seal_and_open() {
+    let provider = Provider::default();
+    let crypto = provider.crypto();
+    crypto.supports(CIPHERSUITE).unwrap();
+
+    let config = CIPHERSUITE.hpke_config();
+    let key_pair = crypto.derive_hpke_keypair(config, &[0x42; 32]).unwrap();
+    assert_eq!(key_pair.public.len(), PUBLIC_KEY_LENGTH);
+
+    // Deriving the key pair is deterministic.
+    let same_key_pair = crypto.derive_hpke_keypair(config, &[0x42; 32]).unwrap();
+    assert_eq!(key_pair.public, same_key_pair.public);
+
+    let ciphertext = crypto
+        .hpke_seal(config, &key_pair.public, b"info", b"aad", b"plaintext")
+        .unwrap();
+    assert_eq!(ciphertext.kem_output.as_slice().len(), CIPHERTEXT_LENGTH);
+    let plaintext = crypto
+        .hpke_open(config, &ciphertext, &key_pair.private, b"info", b"aad")
+        .unwrap();
+    assert_eq!(plaintext, b"plaintext");
+
+    // The ciphertext can't be opened with another key or with other AAD.
+    let other_key_pair = crypto.derive_hpke_keypair(config, &[0x23; 32]).unwrap();
+    assert!(crypto
+        .hpke_open(
+            config,
+            &ciphertext,
+            &other_key_pair.private,
+            b"info",
+            b"aad"
+        )
+        .is_err());
+    assert!(crypto
+        .hpke_open(
+            config,
+            &ciphertext,
+            &key_pair.private,
+            b"info",
+            b"other aad"
+        )
+        .is_err());
+}
+
+#[test]
+fn xwing_hpke_export() {
+    let provider = Provider::default();
+    let crypto = provider.crypto();
+
+    let config = CIPHERSUITE.hpke_config();
+    let key_pair = crypto.derive_hpke_keypair(config, &[0x42; 32]).unwrap();
+    let (kem_output, sender_secret) = crypto
+        .hpke_setup_sender_and_export(config, &key_pair.public, b"info", b"context", 32)
+        .unwrap();
+    assert_eq!(kem_output.len(), CIPHERTEXT_LENGTH);
+    let receiver_secret = crypto
+        .hpke_setup_receiver_and_export(
+            config,
+            &kem_output,
+            &key_pair.private,
+            b"info",
+            b"context",
+            32,
+        )
+        .unwrap();
+    assert_eq!(&*sender_secret, &*receiver_secret);
+    assert_eq!(sender_secret.len(), 32);
+}
@@ -0,0 +1,227 @@
+//! # Asynchronous key stores
+//!
+//! The [`MemoryStorage`] can stage the values of an [`OpenMlsKeyStoreAsync`],
+//! such that the synchronous group operations of OpenMLS can be used with
+//! asynchronous backends:
+//!
+//! 1. The values needed by an operation are fetched from the key store with
+//!    the `fetch_*` functions, e.g. [`MemoryStorage::fetch_group()`].
+//! 2. The operation runs on the [`MemoryStorage`].
+//! 3. The values that have been written or deleted since they were fetched or
+//!    flushed the last time are written to the key store with
+//!    [`MemoryStorage::flush()`].
+//!
+//! Fetching keeps staged values that haven't been flushed yet, such that
+//! the changes of an operation are not lost if flushing them failed. The keys
+//! and values in the key store are the same as in the [`MemoryStorage`], see
+//! [`StorageKey`] for the encoding of the keys.
+
+use openmls_traits::{
+    key_store_async::OpenMlsKeyStoreAsync,
+    storage::{traits, CURRENT_VERSION},
+};
+
+use super::*;
+
+/// Errors thrown when staging the values of an asynchronous key store.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum AsyncStoreError<E> {
+    /// Error accessing the asynchronous key store.
+    #[error("Error accessing the asynchronous key store: {0}")]
+    Store(E),
+    /// A value fetched from the key store couldn't be deserialized.
+    #[error("Error deserializing a value of the key store.")]
+    SerializationError,
+}
+
+impl MemoryStorage {
+    /// Fetches the value stored under the storage `key` in the `store`,
+    /// replacing the staged value. Staged values that have changed since they
+    /// were fetched or flushed the last time are kept.
+    async fn fetch<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        key: StorageKey,
+    ) -> Result<Option<Vec<u8>>, AsyncStoreError<S::Error>> {
+        let key = key.encode::<CURRENT_VERSION>();
+        let value = store.get(&key).await.map_err(AsyncStoreError::Store)?;
+
+        let mut values = self.values.write().unwrap();
+        let mut synced = self.synced.write().unwrap();
+        if values.get(&key) != synced.get(&key) {
+            return Ok(values.get(&key).cloned());
+        }
+        match &value {
+            Some(value) => {
+                values.insert(key.clone(), value.clone());
+                synced.insert(key, value.clone());
+            }
+            None => {
+                values.remove(&key);
+                synced.remove(&key);
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Fetches the state of the group with the `group_id` from the `store`,
+    /// i.e. everything [`MlsGroup::load()`] reads, including the queued
+    /// proposals.
+    ///
+    /// The encryption key pairs of the group are not part of the group state
+    /// and have to be fetched with
+    /// [`fetch_encryption_key_pair()`](Self::fetch_encryption_key_pair) and
+    /// [`fetch_encryption_epoch_key_pairs()`](Self::fetch_encryption_epoch_key_pairs).
+    ///
+    /// [`MlsGroup::load()`]: https://docs.rs/openmls/latest/openmls/group/struct.MlsGroup.html#method.load
+    pub async fn fetch_group<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        group_id: &impl traits::GroupId<CURRENT_VERSION>,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let group_id =
+            serde_json::to_vec(group_id).map_err(|_| AsyncStoreError::SerializationError)?;
+        let group_keys = [
+            StorageKey::Tree,
+            StorageKey::GroupContext,
+            StorageKey::InterimTranscriptHash,
+            StorageKey::ConfirmationTag,
+            StorageKey::JoinConfig,
+            StorageKey::OwnLeafNodes,
+            StorageKey::GroupState,
+            StorageKey::OwnLeafNodeIndex,
+            StorageKey::EpochSecrets,
+            StorageKey::ResumptionPskStore,
+            StorageKey::MessageSecrets,
+        ];
+        for group_key in group_keys {
+            self.fetch(store, group_key(group_id.clone())).await?;
+        }
+
+        let Some(proposal_refs) = self
+            .fetch(store, StorageKey::ProposalQueueRefs(group_id.clone()))
+            .await?
+        else {
+            return Ok(());
+        };
+        let proposal_refs: Vec<Vec<u8>> = serde_json::from_slice(&proposal_refs)
+            .map_err(|_| AsyncStoreError::SerializationError)?;
+        for proposal_ref in proposal_refs {
+            let key = StorageKey::QueuedProposal {
+                group_id: group_id.clone(),
+                proposal_ref,
+            };
+            self.fetch(store, key).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the encryption key pair of the `public_key` from the `store`.
+    pub async fn fetch_encryption_key_pair<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        public_key: &impl traits::EncryptionKey<CURRENT_VERSION>,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let public_key =
+            serde_json::to_vec(public_key).map_err(|_| AsyncStoreError::SerializationError)?;
+        self.fetch(store, StorageKey::EncryptionKeyPair(public_key))
+            .await
+            .map(|_| ())
+    }
+
+    /// Fetches the encryption key pairs of the `epoch` of the group with the
+    /// `group_id` for the leaf with the `leaf_index` from the `store`.
+    pub async fn fetch_encryption_epoch_key_pairs<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        group_id: &impl traits::GroupId<CURRENT_VERSION>,
+        epoch: &impl traits::EpochKey<CURRENT_VERSION>,
+        leaf_index: u32,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let key = StorageKey::epoch_key_pairs(group_id, epoch, leaf_index)
+            .map_err(|_| AsyncStoreError::SerializationError)?;
+        self.fetch(store, key).await.map(|_| ())
+    }
+
+    /// Fetches the key package with the `hash_ref` from the `store`, e.g.
+    /// before joining a group with a welcome for it.
+    pub async fn fetch_key_package<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        hash_ref: &impl traits::HashReference<CURRENT_VERSION>,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let hash_ref =
+            serde_json::to_vec(hash_ref).map_err(|_| AsyncStoreError::SerializationError)?;
+        self.fetch(store, StorageKey::KeyPackage(hash_ref))
+            .await
+            .map(|_| ())
+    }
+
+    /// Fetches the signature key pair of the `public_key` from the `store`.
+    pub async fn fetch_signature_key_pair<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        public_key: &impl traits::SignaturePublicKey<CURRENT_VERSION>,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let public_key =
+            serde_json::to_vec(public_key).map_err(|_| AsyncStoreError::SerializationError)?;
+        self.fetch(store, StorageKey::SignatureKeyPair(public_key))
+            .await
+            .map(|_| ())
+    }
+
+    /// Fetches the PSK with the `psk_id` from the `store`.
+    pub async fn fetch_psk<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+        psk_id: &impl traits::PskId<CURRENT_VERSION>,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let psk_id = serde_json::to_vec(psk_id).map_err(|_| AsyncStoreError::SerializationError)?;
+        self.fetch(store, StorageKey::Psk(psk_id)).await.map(|_| ())
+    }
+
+    /// Writes all values that have been written or deleted since they were
+    /// fetched or flushed the last time to the `store`.
+    ///
+    /// Deletes of values that have neither been fetched nor written before
+    /// are not flushed. If flushing fails, the values that haven't been
+    /// written yet are flushed by the next call.
+    ///
+    /// This must not be called while a transaction is open.
+    pub async fn flush<S: OpenMlsKeyStoreAsync>(
+        &self,
+        store: &S,
+    ) -> Result<(), AsyncStoreError<S::Error>> {
+        let (inserts, deletes) = {
+            let values = self.values.read().unwrap();
+            let synced = self.synced.read().unwrap();
+            let inserts = values
+                .iter()
+                .filter(|(key, value)| synced.get(*key) != Some(value))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            let deletes = synced
+                .keys()
+                .filter(|key| !values.contains_key(*key))
+                .cloned()
+                .collect::<Vec<_>>();
+            (inserts, deletes)
+        };
+
+        for (key, value) in inserts {
+            store
+                .insert(&key, &value)
+                .await
+                .map_err(AsyncStoreError::Store)?;
+            self.synced.write().unwrap().insert(key, value);
+        }
+        for key in deletes {
+            store.delete(&key).await.map_err(AsyncStoreError::Store)?;
+            self.synced.write().unwrap().remove(&key);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! # Storage keys
+//!
+//! Every value in the [`MemoryStorage`](super::MemoryStorage) is stored under
+//! a [`StorageKey`], which names the kind of the value and holds the
+//! serialized key of the value, e.g. the group ID. The kind is part of the
+//! encoded key, such that values of different kinds can't collide, even if
+//! their keys serialize to the same bytes.
+//!
+//! A key is encoded as the label of its kind, followed by the JSON
+//! serialization of the key and the storage version as big-endian `u16`.
+//! Keys that consist of several values, e.g. the group ID, epoch and leaf
+//! index of the epoch key pairs, are serialized as a JSON array, such that
+//! they can't collide either.
+//! Key stores that receive the encoded keys, e.g. an
+//! [`OpenMlsKeyStoreAsync`](openmls_traits::key_store_async::OpenMlsKeyStoreAsync),
+//! can [`decode`](StorageKey::decode) them to index the values by kind.
+
+use openmls_traits::storage::traits;
+
+use super::MemoryStorageError;
+
+/// The key of a value in the storage.
+///
+/// The fields hold the JSON serialization of the keys passed to the
+/// [`StorageProvider`](openmls_traits::storage::StorageProvider).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StorageKey {
+    /// A key package bundle, keyed by the key package reference.
+    KeyPackage(Vec<u8>),
+    /// A PSK bundle, keyed by the PSK ID.
+    Psk(Vec<u8>),
+    /// An HPKE key pair, keyed by the public encryption key.
+    EncryptionKeyPair(Vec<u8>),
+    /// A signature key pair, keyed by the public signature key.
+    SignatureKeyPair(Vec<u8>),
+    /// The HPKE key pairs of a leaf in an epoch of a group.
+    EpochKeyPairs {
+        group_id: Vec<u8>,
+        epoch: Vec<u8>,
+        leaf_index: u32,
+    },
+    /// The ratchet tree of a group.
+    Tree(Vec<u8>),
+    /// The group context of a group.
+    GroupContext(Vec<u8>),
+    /// The interim transcript hash of a group.
+    InterimTranscriptHash(Vec<u8>),
+    /// The confirmation tag of a group.
+    ConfirmationTag(Vec<u8>),
+    /// The join config of a group.
+    JoinConfig(Vec<u8>),
+    /// The own leaf nodes of a group.
+    OwnLeafNodes(Vec<u8>),
+    /// The state of a group.
+    GroupState(Vec<u8>),
+    /// A queued proposal of a group.
+    QueuedProposal {
+        group_id: Vec<u8>,
+        proposal_ref: Vec<u8>,
+    },
+    /// The references of the queued proposals of a group.
+    ProposalQueueRefs(Vec<u8>),
+    /// The own leaf index in a group.
+    OwnLeafNodeIndex(Vec<u8>),
+    /// The epoch secrets of a group.
+    EpochSecrets(Vec<u8>),
+    /// The resumption PSK store of a group.
+    ResumptionPskStore(Vec<u8>),
+    /// The message secrets of a group.
+    MessageSecrets(Vec<u8>),
+}
+
+// The labels must not be prefixes of each other followed by a character that
+// can start a JSON value, such that keys can be decoded.
+const KEY_PACKAGE_LABEL: &[u8] = b"KeyPackage";
+const PSK_LABEL: &[u8] = b"Psk";
+const ENCRYPTION_KEY_PAIR_LABEL: &[u8] = b"EncryptionKeyPair";
+const SIGNATURE_KEY_PAIR_LABEL: &[u8] = b"SignatureKeyPair";
+const EPOCH_KEY_PAIRS_LABEL: &[u8] = b"EpochKeyPairs";
+
+// related to PublicGroup
+const TREE_LABEL: &[u8] = b"Tree";
+const GROUP_CONTEXT_LABEL: &[u8] = b"GroupContext";
+const INTERIM_TRANSCRIPT_HASH_LABEL: &[u8] = b"InterimTranscriptHash";
+const CONFIRMATION_TAG_LABEL: &[u8] = b"ConfirmationTag";
+
+// related to MlsGroup
+const JOIN_CONFIG_LABEL: &[u8] = b"MlsGroupJoinConfig";
+const OWN_LEAF_NODES_LABEL: &[u8] = b"OwnLeafNodes";
+const GROUP_STATE_LABEL: &[u8] = b"GroupState";
+const QUEUED_PROPOSAL_LABEL: &[u8] = b"QueuedProposal";
+const PROPOSAL_QUEUE_REFS_LABEL: &[u8] = b"ProposalQueueRefs";
+const OWN_LEAF_NODE_INDEX_LABEL: &[u8] = b"OwnLeafNodeIndex";
+const EPOCH_SECRETS_LABEL: &[u8] = b"EpochSecrets";
+const RESUMPTION_PSK_STORE_LABEL: &[u8] = b"ResumptionPsk";
+const MESSAGE_SECRETS_LABEL: &[u8] = b"MessageSecrets";
+
+impl StorageKey {
+    /// The key of the HPKE key pairs of the leaf with the `leaf_index` in the
+    /// `epoch` of the group with the `group_id`.
+    pub fn epoch_key_pairs<const VERSION: u16>(
+        group_id: &impl traits::GroupId<VERSION>,
+        epoch: &impl traits::EpochKey<VERSION>,
+        leaf_index: u32,
+    ) -> Result<Self, MemoryStorageError> {
+        Ok(Self::EpochKeyPairs {
+            group_id: serde_json::to_vec(group_id)?,
+            epoch: serde_json::to_vec(epoch)?,
+            leaf_index,
+        })
+    }
+
+    /// The key of the queued proposal with the `proposal_ref` in the group
+    /// with the `group_id`.
+    pub fn queued_proposal<const VERSION: u16>(
+        group_id: &impl traits::GroupId<VERSION>,
+        proposal_ref: &impl traits::ProposalRef<VERSION>,
+    ) -> Result<Self, MemoryStorageError> {
+        Ok(Self::QueuedProposal {
+            group_id: serde_json::to_vec(group_id)?,
+            proposal_ref: serde_json::to_vec(proposal_ref)?,
+        })
+    }
+
+    /// Returns the label of the kind of this key.
+    pub fn label(&self) -> &'static [u8] {
+        match self {
+            Self::KeyPackage(_) => KEY_PACKAGE_LABEL,
+            Self::Psk(_) => PSK_LABEL,
+            Self::EncryptionKeyPair(_) => ENCRYPTION_KEY_PAIR_LABEL,
+            Self::SignatureKeyPair(_) => SIGNATURE_KEY_PAIR_LABEL,
+            Self::EpochKeyPairs { .. } => EPOCH_KEY_PAIRS_LABEL,
+            Self::Tree(_) => TREE_LABEL,
+            Self::GroupContext(_) => GROUP_CONTEXT_LABEL,
+            Self::InterimTranscriptHash(_) => INTERIM_TRANSCRIPT_HASH_LABEL,
+            Self::ConfirmationTag(_) => CONFIRMATION_TAG_LABEL,
+            Self::JoinConfig(_) => JOIN_CONFIG_LABEL,
+            Self::OwnLeafNodes(_) => OWN_LEAF_NODES_LABEL,
+            Self::GroupState(_) => GROUP_STATE_LABEL,
+            Self::QueuedProposal { .. } => QUEUED_PROPOSAL_LABEL,
+            Self::ProposalQueueRefs(_) => PROPOSAL_QUEUE_REFS_LABEL,
+            Self::OwnLeafNodeIndex(_) => OWN_LEAF_NODE_INDEX_LABEL,
+            Self::EpochSecrets(_) => EPOCH_SECRETS_LABEL,
+            Self::ResumptionPskStore(_) => RESUMPTION_PSK_STORE_LABEL,
+            Self::MessageSecrets(_) => MESSAGE_SECRETS_LABEL,
+        }
+    }
+
+    /// Encodes the key for the storage `VERSION`.
+    pub fn encode<const VERSION: u16>(&self) -> Vec<u8> {
+        let mut encoded = self.label().to_vec();
+        match self {
+            Self::EpochKeyPairs {
+                group_id,
+                epoch,
+                leaf_index,
+            } => {
+                // The serialization of the tuple `(group_id, epoch, leaf_index)`.
+                encoded.push(b'[');
+                encoded.extend_from_slice(group_id);
+                encoded.push(b',');
+                encoded.extend_from_slice(epoch);
+                encoded.push(b',');
+                encoded.extend_from_slice(leaf_index.to_string().as_bytes());
+                encoded.push(b']');
+            }
+            Self::QueuedProposal {
+                group_id,
+                proposal_ref,
+            } => {
+                // The serialization of the tuple `(group_id, proposal_ref)`.
+                encoded.push(b'[');
+                encoded.extend_from_slice(group_id);
+                encoded.push(b',');
+                encoded.extend_from_slice(proposal_ref);
+                encoded.push(b']');
+            }
+            Self::KeyPackage(key)
+            | Self::Psk(key)
+            | Self::EncryptionKeyPair(key)
+            | Self::SignatureKeyPair(key)
+            | Self::Tree(key)
+            | Self::GroupContext(key)
+            | Self::InterimTranscriptHash(key)
+            | Self::ConfirmationTag(key)
+            | Self::JoinConfig(key)
+            | Self::OwnLeafNodes(key)
+            | Self::GroupState(key)
+            | Self::ProposalQueueRefs(key)
+            | Self::OwnLeafNodeIndex(key)
+            | Self::EpochSecrets(key)
+            | Self::ResumptionPskStore(key)
+            | Self::MessageSecrets(key) => encoded.extend_from_slice(key),
+        }
+        encoded.extend_from_slice(&VERSION.to_be_bytes());
+        encoded
+    }
+
+    /// Decodes an encoded key, returning the key and the storage version, or
+    /// `None` if `encoded` is not a valid key.
+    pub fn decode(encoded: &[u8]) -> Option<(Self, u16)> {
+        let (rest, version) = encoded.split_at(encoded.len().checked_sub(2)?);
+        let version = u16::from_be_bytes([version[0], version[1]]);
+
+        // Labels that are prefixes of other labels are followed by another
+        // letter in the longer label, but never in a JSON key.
+        let label = [
+            KEY_PACKAGE_LABEL,
+            PSK_LABEL,
+            ENCRYPTION_KEY_PAIR_LABEL,
+            SIGNATURE_KEY_PAIR_LABEL,
+            EPOCH_KEY_PAIRS_LABEL,
+            TREE_LABEL,
+            GROUP_CONTEXT_LABEL,
+            INTERIM_TRANSCRIPT_HASH_LABEL,
+            CONFIRMATION_TAG_LABEL,
+            JOIN_CONFIG_LABEL,
+            OWN_LEAF_NODES_LABEL,
+            GROUP_STATE_LABEL,
+            QUEUED_PROPOSAL_LABEL,
+            PROPOSAL_QUEUE_REFS_LABEL,
+            OWN_LEAF_NODE_INDEX_LABEL,
+            EPOCH_SECRETS_LABEL,
+            RESUMPTION_PSK_STORE_LABEL,
+            MESSAGE_SECRETS_LABEL,
+        ]
+        .into_iter()
+        .find(|label| {
+            rest.starts_with(label)
+                && !rest
+                    .get(label.len())
+                    .is_some_and(|next| next.is_ascii_alphabetic())
+        })?;
+        let key = &rest[label.len()..];
+
+        let key = match label {
+            KEY_PACKAGE_LABEL => Self::KeyPackage(key.to_vec()),
+            PSK_LABEL => Self::Psk(key.to_vec()),
+            ENCRYPTION_KEY_PAIR_LABEL => Self::EncryptionKeyPair(key.to_vec()),
+            SIGNATURE_KEY_PAIR_LABEL => Self::SignatureKeyPair(key.to_vec()),
+            EPOCH_KEY_PAIRS_LABEL => {
+                let key = key.strip_prefix(b"[")?.strip_suffix(b"]")?;
+                let (group_id, key) = split_json_value(key)?;
+                let (epoch, key) = split_json_value(key.strip_prefix(b",")?)?;
+                Self::EpochKeyPairs {
+                    group_id: group_id.to_vec(),
+                    epoch: epoch.to_vec(),
+                    leaf_index: serde_json::from_slice(key.strip_prefix(b",")?).ok()?,
+                }
+            }
+            TREE_LABEL => Self::Tree(key.to_vec()),
+            GROUP_CONTEXT_LABEL => Self::GroupContext(key.to_vec()),
+            INTERIM_TRANSCRIPT_HASH_LABEL => Self::InterimTranscriptHash(key.to_vec()),
+            CONFIRMATION_TAG_LABEL => Self::ConfirmationTag(key.to_vec()),
+            JOIN_CONFIG_LABEL => Self::JoinConfig(key.to_vec()),
+            OWN_LEAF_NODES_LABEL => Self::OwnLeafNodes(key.to_vec()),
+            GROUP_STATE_LABEL => Self::GroupState(key.to_vec()),
+            QUEUED_PROPOSAL_LABEL => {
+                let key = key.strip_prefix(b"[")?.strip_suffix(b"]")?;
+                let (group_id, key) = split_json_value(key)?;
+                Self::QueuedProposal {
+                    group_id: group_id.to_vec(),
+                    proposal_ref: key.strip_prefix(b",")?.to_vec(),
+                }
+            }
+            PROPOSAL_QUEUE_REFS_LABEL => Self::ProposalQueueRefs(key.to_vec()),
+            OWN_LEAF_NODE_INDEX_LABEL => Self::OwnLeafNodeIndex(key.to_vec()),
+            EPOCH_SECRETS_LABEL => Self::EpochSecrets(key.to_vec()),
+            RESUMPTION_PSK_STORE_LABEL => Self::ResumptionPskStore(key.to_vec()),
+            MESSAGE_SECRETS_LABEL => Self::MessageSecrets(key.to_vec()),
+            _ => return None,
+        };
+
+        Some((key, version))
+    }
+}
+
+/// Splits the JSON value at the start of `bytes` from the rest.
+fn split_json_value(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut values =
+        serde_json::Deserializer::from_slice(bytes).into_iter::<serde::de::IgnoredAny>();
+    values.next()?.ok()?;
+    Some(bytes.split_at(values.byte_offset()))
+}
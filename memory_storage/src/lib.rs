@@ -1,5 +1,7 @@
-use openmls_traits::storage::*;
-use serde::Serialize;
+use openmls_traits::storage::{
+    migration::{MigrationError, StorageEntry, StorageMigrations},
+    *,
+};
 use std::{collections::HashMap, sync::RwLock};
 
 #[cfg(feature = "test-utils")]
@@ -12,9 +14,26 @@ mod test_store;
 #[cfg(feature = "persistence")]
 pub mod persistence;
 
+#[cfg(feature = "async")]
+pub mod async_store;
+
+mod key;
+pub use key::StorageKey;
+
+/// The original values of the keys changed in a transaction, `None` for keys
+/// that didn't exist.
+type OriginalValues = HashMap<Vec<u8>, Option<Vec<u8>>>;
+
 #[derive(Debug, Default)]
 pub struct MemoryStorage {
     pub values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// The original values of the keys changed in each open transaction. Only
+    /// the changed keys are copied, such that beginning a transaction is cheap.
+    transactions: RwLock<Vec<OriginalValues>>,
+    /// The values as they are in the asynchronous key store, i.e. as they
+    /// were fetched or flushed the last time.
+    #[cfg(feature = "async")]
+    synced: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
 }
 
 // For testing we want to clone.
@@ -24,6 +43,9 @@ impl Clone for MemoryStorage {
         let values = self.values.read().unwrap();
         Self {
             values: RwLock::new(values.clone()),
+            transactions: RwLock::default(),
+            #[cfg(feature = "async")]
+            synced: RwLock::default(),
         }
     }
 }
@@ -80,43 +102,57 @@ impl MemoryStorage {
 
         Ok(Self {
             values: RwLock::new(map),
+            transactions: RwLock::default(),
+            #[cfg(feature = "async")]
+            synced: RwLock::default(),
         })
     }
 }
 
 impl MemoryStorage {
+    /// Records the original value of the `storage_key` in the innermost open
+    /// transaction before it's changed for the first time in it.
+    fn record_original(&self, values: &HashMap<Vec<u8>, Vec<u8>>, storage_key: &[u8]) {
+        if let Some(originals) = self.transactions.write().unwrap().last_mut() {
+            originals
+                .entry(storage_key.to_vec())
+                .or_insert_with(|| values.get(storage_key).cloned());
+        }
+    }
+
     /// Internal helper to abstract write operations.
     #[inline(always)]
     fn write<const VERSION: u16>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
         value: Vec<u8>,
     ) -> Result<(), <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let mut values = self.values.write().unwrap();
-        let storage_key = build_key_from_vec::<VERSION>(label, key.to_vec());
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  write key: {}", hex::encode(&storage_key));
         log::trace!("{}", std::backtrace::Backtrace::capture());
 
+        self.record_original(&values, &storage_key);
         values.insert(storage_key, value.to_vec());
         Ok(())
     }
 
     fn append<const VERSION: u16>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
         value: Vec<u8>,
     ) -> Result<(), <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let mut values = self.values.write().unwrap();
-        let storage_key = build_key_from_vec::<VERSION>(label, key.to_vec());
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  write key: {}", hex::encode(&storage_key));
         log::trace!("{}", std::backtrace::Backtrace::capture());
 
+        self.record_original(&values, &storage_key);
+
         // fetch value from db, falling back to an empty list if doens't exist
         let list_bytes = values.entry(storage_key).or_insert(b"[]".to_vec());
 
@@ -133,17 +169,18 @@ impl MemoryStorage {
 
     fn remove_item<const VERSION: u16>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
         value: Vec<u8>,
     ) -> Result<(), <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let mut values = self.values.write().unwrap();
-        let storage_key = build_key_from_vec::<VERSION>(label, key.to_vec());
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  write key: {}", hex::encode(&storage_key));
         log::trace!("{}", std::backtrace::Backtrace::capture());
 
+        self.record_original(&values, &storage_key);
+
         // fetch value from db, falling back to an empty list if doens't exist
         let list_bytes = values.entry(storage_key).or_insert(b"[]".to_vec());
 
@@ -164,11 +201,10 @@ impl MemoryStorage {
     #[inline(always)]
     fn read<const VERSION: u16, V: Entity<VERSION>>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
     ) -> Result<Option<V>, <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let values = self.values.read().unwrap();
-        let storage_key = build_key_from_vec::<VERSION>(label, key.to_vec());
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  read key: {}", hex::encode(&storage_key));
@@ -189,14 +225,11 @@ impl MemoryStorage {
     #[inline(always)]
     fn read_list<const VERSION: u16, V: Entity<VERSION>>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
     ) -> Result<Vec<V>, <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let values = self.values.read().unwrap();
 
-        let mut storage_key = label.to_vec();
-        storage_key.extend_from_slice(key);
-        storage_key.extend_from_slice(&u16::to_be_bytes(VERSION));
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  read list key: {}", hex::encode(&storage_key));
@@ -218,25 +251,75 @@ impl MemoryStorage {
     #[inline(always)]
     fn delete<const VERSION: u16>(
         &self,
-        label: &[u8],
-        key: &[u8],
+        key: &StorageKey,
     ) -> Result<(), <Self as StorageProvider<CURRENT_VERSION>>::Error> {
         let mut values = self.values.write().unwrap();
 
-        let mut storage_key = label.to_vec();
-        storage_key.extend_from_slice(key);
-        storage_key.extend_from_slice(&u16::to_be_bytes(VERSION));
+        let storage_key = key.encode::<VERSION>();
 
         #[cfg(feature = "test-utils")]
         log::debug!("  delete key: {}", hex::encode(&storage_key));
         log::trace!("{}", std::backtrace::Backtrace::capture());
 
+        self.record_original(&values, &storage_key);
         values.remove(&storage_key);
 
         Ok(())
     }
 }
 
+impl MemoryStorage {
+    /// Migrates all entries of older storage versions to the
+    /// [`CURRENT_VERSION`] with the `migrations`, and returns the number of
+    /// migrated entries.
+    ///
+    /// The entries are only changed if all of them could be migrated. Entries
+    /// with a label that is unknown to this release are passed to the
+    /// migrations with the complete key as label and an empty key.
+    pub fn migrate(&self, migrations: &StorageMigrations) -> Result<usize, MigrationError> {
+        let mut values = self.values.write().unwrap();
+
+        let mut migrated = Vec::new();
+        for (storage_key, value) in values.iter() {
+            let Some(rest_len) = storage_key.len().checked_sub(2) else {
+                continue;
+            };
+            let (rest, version) = storage_key.split_at(rest_len);
+            let version = u16::from_be_bytes([version[0], version[1]]);
+            if version == CURRENT_VERSION {
+                continue;
+            }
+            let label_len = StorageKey::decode(storage_key)
+                .map(|(key, _)| key.label().len())
+                .unwrap_or(rest.len());
+            let mut entry = StorageEntry {
+                label: rest[..label_len].to_vec(),
+                key: rest[label_len..].to_vec(),
+                value: value.clone(),
+            };
+            migrations.migrate(version.into(), &mut entry)?;
+            migrated.push((storage_key.clone(), entry));
+        }
+
+        // Remove all migrated entries first, such that a migrated entry can't
+        // be removed again if its new key is the old key of another one.
+        let count = migrated.len();
+        for (storage_key, _) in &migrated {
+            self.record_original(&values, storage_key);
+            values.remove(storage_key);
+        }
+        for (_, entry) in migrated {
+            let mut storage_key = entry.label;
+            storage_key.extend_from_slice(&entry.key);
+            storage_key.extend_from_slice(&CURRENT_VERSION.to_be_bytes());
+            self.record_original(&values, &storage_key);
+            values.insert(storage_key, entry.value);
+        }
+
+        Ok(count)
+    }
+}
+
 /// Errors thrown by the key store.
 #[derive(thiserror::Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MemoryStorageError {
@@ -248,31 +331,10 @@ pub enum MemoryStorageError {
     SerializationError,
     #[error("Value does not exist.")]
     None,
+    #[error("There is no open transaction.")]
+    NoTransaction,
 }
 
-const KEY_PACKAGE_LABEL: &[u8] = b"KeyPackage";
-const PSK_LABEL: &[u8] = b"Psk";
-const ENCRYPTION_KEY_PAIR_LABEL: &[u8] = b"EncryptionKeyPair";
-const SIGNATURE_KEY_PAIR_LABEL: &[u8] = b"SignatureKeyPair";
-const EPOCH_KEY_PAIRS_LABEL: &[u8] = b"EpochKeyPairs";
-
-// related to PublicGroup
-const TREE_LABEL: &[u8] = b"Tree";
-const GROUP_CONTEXT_LABEL: &[u8] = b"GroupContext";
-const INTERIM_TRANSCRIPT_HASH_LABEL: &[u8] = b"InterimTranscriptHash";
-const CONFIRMATION_TAG_LABEL: &[u8] = b"ConfirmationTag";
-
-// related to MlsGroup
-const JOIN_CONFIG_LABEL: &[u8] = b"MlsGroupJoinConfig";
-const OWN_LEAF_NODES_LABEL: &[u8] = b"OwnLeafNodes";
-const GROUP_STATE_LABEL: &[u8] = b"GroupState";
-const QUEUED_PROPOSAL_LABEL: &[u8] = b"QueuedProposal";
-const PROPOSAL_QUEUE_REFS_LABEL: &[u8] = b"ProposalQueueRefs";
-const OWN_LEAF_NODE_INDEX_LABEL: &[u8] = b"OwnLeafNodeIndex";
-const EPOCH_SECRETS_LABEL: &[u8] = b"EpochSecrets";
-const RESUMPTION_PSK_STORE_LABEL: &[u8] = b"ResumptionPsk";
-const MESSAGE_SECRETS_LABEL: &[u8] = b"MessageSecrets";
-
 impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
     type Error = MemoryStorageError;
 
@@ -287,14 +349,14 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         proposal: &QueuedProposal,
     ) -> Result<(), Self::Error> {
         // write proposal to key (group_id, proposal_ref)
-        let key = serde_json::to_vec(&(group_id, proposal_ref))?;
+        let key = StorageKey::queued_proposal(group_id, proposal_ref)?;
         let value = serde_json::to_vec(proposal)?;
-        self.write::<CURRENT_VERSION>(QUEUED_PROPOSAL_LABEL, &key, value)?;
+        self.write::<CURRENT_VERSION>(&key, value)?;
 
         // update proposal list for group_id
         let key = serde_json::to_vec(group_id)?;
         let value = serde_json::to_vec(proposal_ref)?;
-        self.append::<CURRENT_VERSION>(PROPOSAL_QUEUE_REFS_LABEL, &key, value)?;
+        self.append::<CURRENT_VERSION>(&StorageKey::ProposalQueueRefs(key), value)?;
 
         Ok(())
     }
@@ -308,8 +370,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         tree: &TreeSync,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            TREE_LABEL,
-            &serde_json::to_vec(&group_id).unwrap(),
+            &StorageKey::Tree(serde_json::to_vec(&group_id).unwrap()),
             serde_json::to_vec(&tree).unwrap(),
         )
     }
@@ -323,9 +384,11 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         interim_transcript_hash: &InterimTranscriptHash,
     ) -> Result<(), Self::Error> {
         let mut values = self.values.write().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(INTERIM_TRANSCRIPT_HASH_LABEL, group_id);
+        let key = StorageKey::InterimTranscriptHash(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
         let value = serde_json::to_vec(&interim_transcript_hash).unwrap();
 
+        self.record_original(&values, &key);
         values.insert(key, value);
         Ok(())
     }
@@ -339,9 +402,11 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_context: &GroupContext,
     ) -> Result<(), Self::Error> {
         let mut values = self.values.write().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(GROUP_CONTEXT_LABEL, group_id);
+        let key = StorageKey::GroupContext(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
         let value = serde_json::to_vec(&group_context).unwrap();
 
+        self.record_original(&values, &key);
         values.insert(key, value);
         Ok(())
     }
@@ -355,9 +420,11 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         confirmation_tag: &ConfirmationTag,
     ) -> Result<(), Self::Error> {
         let mut values = self.values.write().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(CONFIRMATION_TAG_LABEL, group_id);
+        let key = StorageKey::ConfirmationTag(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
         let value = serde_json::to_vec(&confirmation_tag).unwrap();
 
+        self.record_original(&values, &key);
         values.insert(key, value);
         Ok(())
     }
@@ -371,10 +438,11 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         signature_key_pair: &SignatureKeyPair,
     ) -> Result<(), Self::Error> {
         let mut values = self.values.write().unwrap();
-        let key =
-            build_key::<CURRENT_VERSION, &SignaturePublicKey>(SIGNATURE_KEY_PAIR_LABEL, public_key);
+        let key = StorageKey::SignatureKeyPair(serde_json::to_vec(public_key).unwrap())
+            .encode::<CURRENT_VERSION>();
         let value = serde_json::to_vec(&signature_key_pair).unwrap();
 
+        self.record_original(&values, &key);
         values.insert(key, value);
         Ok(())
     }
@@ -386,7 +454,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Vec<ProposalRef>, Self::Error> {
-        self.read_list(PROPOSAL_QUEUE_REFS_LABEL, &serde_json::to_vec(group_id)?)
+        self.read_list(&StorageKey::ProposalQueueRefs(serde_json::to_vec(
+            group_id,
+        )?))
     }
 
     fn queued_proposals<
@@ -397,15 +467,15 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Vec<(ProposalRef, QueuedProposal)>, Self::Error> {
-        let refs: Vec<ProposalRef> =
-            self.read_list(PROPOSAL_QUEUE_REFS_LABEL, &serde_json::to_vec(group_id)?)?;
+        let refs: Vec<ProposalRef> = self.read_list(&StorageKey::ProposalQueueRefs(
+            serde_json::to_vec(group_id)?,
+        ))?;
 
         refs.into_iter()
             .map(|proposal_ref| -> Result<_, _> {
-                let key = (group_id, &proposal_ref);
-                let key = serde_json::to_vec(&key)?;
+                let key = StorageKey::queued_proposal(group_id, &proposal_ref)?;
 
-                let proposal = self.read(QUEUED_PROPOSAL_LABEL, &key)?.unwrap();
+                let proposal = self.read(&key)?.unwrap();
                 Ok((proposal_ref, proposal))
             })
             .collect::<Result<Vec<_>, _>>()
@@ -419,7 +489,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_id: &GroupId,
     ) -> Result<Option<TreeSync>, Self::Error> {
         let values = self.values.read().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(TREE_LABEL, group_id);
+        let key =
+            StorageKey::Tree(serde_json::to_vec(group_id).unwrap()).encode::<CURRENT_VERSION>();
 
         let Some(value) = values.get(&key) else {
             return Ok(None);
@@ -437,7 +508,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_id: &GroupId,
     ) -> Result<Option<GroupContext>, Self::Error> {
         let values = self.values.read().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(GROUP_CONTEXT_LABEL, group_id);
+        let key = StorageKey::GroupContext(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
 
         let Some(value) = values.get(&key) else {
             return Ok(None);
@@ -455,7 +527,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_id: &GroupId,
     ) -> Result<Option<InterimTranscriptHash>, Self::Error> {
         let values = self.values.read().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(INTERIM_TRANSCRIPT_HASH_LABEL, group_id);
+        let key = StorageKey::InterimTranscriptHash(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
 
         let Some(value) = values.get(&key) else {
             return Ok(None);
@@ -473,7 +546,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_id: &GroupId,
     ) -> Result<Option<ConfirmationTag>, Self::Error> {
         let values = self.values.read().unwrap();
-        let key = build_key::<CURRENT_VERSION, &GroupId>(CONFIRMATION_TAG_LABEL, group_id);
+        let key = StorageKey::ConfirmationTag(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
 
         let Some(value) = values.get(&key) else {
             return Ok(None);
@@ -492,8 +566,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
     ) -> Result<Option<SignatureKeyPair>, Self::Error> {
         let values = self.values.read().unwrap();
 
-        let key =
-            build_key::<CURRENT_VERSION, &SignaturePublicKey>(SIGNATURE_KEY_PAIR_LABEL, public_key);
+        let key = StorageKey::SignatureKeyPair(serde_json::to_vec(public_key).unwrap())
+            .encode::<CURRENT_VERSION>();
 
         let Some(value) = values.get(&key) else {
             return Ok(None);
@@ -514,7 +588,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         let key = serde_json::to_vec(&hash_ref).unwrap();
         let value = serde_json::to_vec(&key_package).unwrap();
 
-        self.write::<CURRENT_VERSION>(KEY_PACKAGE_LABEL, &key, value)
+        self.write::<CURRENT_VERSION>(&StorageKey::KeyPackage(key), value)
             .unwrap();
 
         Ok(())
@@ -529,8 +603,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         psk: &PskBundle,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            PSK_LABEL,
-            &serde_json::to_vec(&psk_id).unwrap(),
+            &StorageKey::Psk(serde_json::to_vec(&psk_id).unwrap()),
             serde_json::to_vec(&psk).unwrap(),
         )
     }
@@ -544,8 +617,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         key_pair: &HpkeKeyPair,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            ENCRYPTION_KEY_PAIR_LABEL,
-            &serde_json::to_vec(public_key).unwrap(),
+            &StorageKey::EncryptionKeyPair(serde_json::to_vec(public_key).unwrap()),
             serde_json::to_vec(key_pair).unwrap(),
         )
     }
@@ -558,14 +630,14 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         hash_ref: &KeyPackageRef,
     ) -> Result<Option<KeyPackage>, Self::Error> {
         let key = serde_json::to_vec(&hash_ref).unwrap();
-        self.read(KEY_PACKAGE_LABEL, &key)
+        self.read(&StorageKey::KeyPackage(key))
     }
 
     fn psk<PskBundle: traits::PskBundle<CURRENT_VERSION>, PskId: traits::PskId<CURRENT_VERSION>>(
         &self,
         psk_id: &PskId,
     ) -> Result<Option<PskBundle>, Self::Error> {
-        self.read(PSK_LABEL, &serde_json::to_vec(&psk_id).unwrap())
+        self.read(&StorageKey::Psk(serde_json::to_vec(&psk_id).unwrap()))
     }
 
     fn encryption_key_pair<
@@ -575,10 +647,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         public_key: &EncryptionKey,
     ) -> Result<Option<HpkeKeyPair>, Self::Error> {
-        self.read(
-            ENCRYPTION_KEY_PAIR_LABEL,
-            &serde_json::to_vec(public_key).unwrap(),
-        )
+        self.read(&StorageKey::EncryptionKeyPair(
+            serde_json::to_vec(public_key).unwrap(),
+        ))
     }
 
     fn delete_signature_key_pair<
@@ -587,34 +658,32 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         public_key: &SignaturePublicKeuy,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(
-            SIGNATURE_KEY_PAIR_LABEL,
-            &serde_json::to_vec(public_key).unwrap(),
-        )
+        self.delete::<CURRENT_VERSION>(&StorageKey::SignatureKeyPair(
+            serde_json::to_vec(public_key).unwrap(),
+        ))
     }
 
     fn delete_encryption_key_pair<EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>>(
         &self,
         public_key: &EncryptionKey,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(
-            ENCRYPTION_KEY_PAIR_LABEL,
-            &serde_json::to_vec(&public_key).unwrap(),
-        )
+        self.delete::<CURRENT_VERSION>(&StorageKey::EncryptionKeyPair(
+            serde_json::to_vec(&public_key).unwrap(),
+        ))
     }
 
     fn delete_key_package<KeyPackageRef: traits::HashReference<CURRENT_VERSION>>(
         &self,
         hash_ref: &KeyPackageRef,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(KEY_PACKAGE_LABEL, &serde_json::to_vec(&hash_ref)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::KeyPackage(serde_json::to_vec(&hash_ref)?))
     }
 
     fn delete_psk<PskKey: traits::PskId<CURRENT_VERSION>>(
         &self,
         psk_id: &PskKey,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(PSK_LABEL, &serde_json::to_vec(&psk_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::Psk(serde_json::to_vec(&psk_id)?))
     }
 
     fn group_state<
@@ -624,7 +693,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<GroupState>, Self::Error> {
-        self.read(GROUP_STATE_LABEL, &serde_json::to_vec(&group_id)?)
+        self.read(&StorageKey::GroupState(serde_json::to_vec(&group_id)?))
     }
 
     fn write_group_state<
@@ -636,8 +705,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_state: &GroupState,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            GROUP_STATE_LABEL,
-            &serde_json::to_vec(group_id)?,
+            &StorageKey::GroupState(serde_json::to_vec(group_id)?),
             serde_json::to_vec(group_state)?,
         )
     }
@@ -646,7 +714,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(GROUP_STATE_LABEL, &serde_json::to_vec(group_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::GroupState(serde_json::to_vec(group_id)?))
     }
 
     fn message_secrets<
@@ -656,7 +724,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<MessageSecrets>, Self::Error> {
-        self.read(MESSAGE_SECRETS_LABEL, &serde_json::to_vec(group_id)?)
+        self.read(&StorageKey::MessageSecrets(serde_json::to_vec(group_id)?))
     }
 
     fn write_message_secrets<
@@ -668,8 +736,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         message_secrets: &MessageSecrets,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            MESSAGE_SECRETS_LABEL,
-            &serde_json::to_vec(group_id)?,
+            &StorageKey::MessageSecrets(serde_json::to_vec(group_id)?),
             serde_json::to_vec(message_secrets)?,
         )
     }
@@ -678,7 +745,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(MESSAGE_SECRETS_LABEL, &serde_json::to_vec(group_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::MessageSecrets(serde_json::to_vec(group_id)?))
     }
 
     fn resumption_psk_store<
@@ -688,7 +755,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<ResumptionPskStore>, Self::Error> {
-        self.read(RESUMPTION_PSK_STORE_LABEL, &serde_json::to_vec(group_id)?)
+        self.read(&StorageKey::ResumptionPskStore(serde_json::to_vec(
+            group_id,
+        )?))
     }
 
     fn write_resumption_psk_store<
@@ -700,8 +769,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         resumption_psk_store: &ResumptionPskStore,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            RESUMPTION_PSK_STORE_LABEL,
-            &serde_json::to_vec(group_id)?,
+            &StorageKey::ResumptionPskStore(serde_json::to_vec(group_id)?),
             serde_json::to_vec(resumption_psk_store)?,
         )
     }
@@ -710,7 +778,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(RESUMPTION_PSK_STORE_LABEL, &serde_json::to_vec(group_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::ResumptionPskStore(serde_json::to_vec(
+            group_id,
+        )?))
     }
 
     fn own_leaf_index<
@@ -720,7 +790,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<LeafNodeIndex>, Self::Error> {
-        self.read(OWN_LEAF_NODE_INDEX_LABEL, &serde_json::to_vec(group_id)?)
+        self.read(&StorageKey::OwnLeafNodeIndex(serde_json::to_vec(group_id)?))
     }
 
     fn write_own_leaf_index<
@@ -732,8 +802,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         own_leaf_index: &LeafNodeIndex,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            OWN_LEAF_NODE_INDEX_LABEL,
-            &serde_json::to_vec(group_id)?,
+            &StorageKey::OwnLeafNodeIndex(serde_json::to_vec(group_id)?),
             serde_json::to_vec(own_leaf_index)?,
         )
     }
@@ -742,7 +811,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(OWN_LEAF_NODE_INDEX_LABEL, &serde_json::to_vec(group_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::OwnLeafNodeIndex(serde_json::to_vec(group_id)?))
     }
 
     fn group_epoch_secrets<
@@ -752,7 +821,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<GroupEpochSecrets>, Self::Error> {
-        self.read(EPOCH_SECRETS_LABEL, &serde_json::to_vec(group_id)?)
+        self.read(&StorageKey::EpochSecrets(serde_json::to_vec(group_id)?))
     }
 
     fn write_group_epoch_secrets<
@@ -764,8 +833,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_epoch_secrets: &GroupEpochSecrets,
     ) -> Result<(), Self::Error> {
         self.write::<CURRENT_VERSION>(
-            EPOCH_SECRETS_LABEL,
-            &serde_json::to_vec(group_id)?,
+            &StorageKey::EpochSecrets(serde_json::to_vec(group_id)?),
             serde_json::to_vec(group_epoch_secrets)?,
         )
     }
@@ -774,7 +842,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(EPOCH_SECRETS_LABEL, &serde_json::to_vec(group_id)?)
+        self.delete::<CURRENT_VERSION>(&StorageKey::EpochSecrets(serde_json::to_vec(group_id)?))
     }
 
     fn write_encryption_epoch_key_pairs<
@@ -788,16 +856,16 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         leaf_index: u32,
         key_pairs: &[HpkeKeyPair],
     ) -> Result<(), Self::Error> {
-        let key = epoch_key_pairs_id(group_id, epoch, leaf_index)?;
+        let key = StorageKey::epoch_key_pairs(group_id, epoch, leaf_index)?;
         let value = serde_json::to_vec(key_pairs)?;
         log::debug!("Writing encryption epoch key pairs");
         #[cfg(feature = "test-utils")]
         {
-            log::debug!("  key: {}", hex::encode(&key));
+            log::debug!("  key: {}", hex::encode(key.encode::<CURRENT_VERSION>()));
             log::debug!("  value: {}", hex::encode(&value));
         }
 
-        self.write::<CURRENT_VERSION>(EPOCH_KEY_PAIRS_LABEL, &key, value)
+        self.write::<CURRENT_VERSION>(&key, value)
     }
 
     fn encryption_epoch_key_pairs<
@@ -810,8 +878,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         epoch: &EpochKey,
         leaf_index: u32,
     ) -> Result<Vec<HpkeKeyPair>, Self::Error> {
-        let key = epoch_key_pairs_id(group_id, epoch, leaf_index)?;
-        let storage_key = build_key_from_vec::<CURRENT_VERSION>(EPOCH_KEY_PAIRS_LABEL, key);
+        let storage_key =
+            StorageKey::epoch_key_pairs(group_id, epoch, leaf_index)?.encode::<CURRENT_VERSION>();
         log::debug!("Reading encryption epoch key pairs");
 
         let values = self.values.read().unwrap();
@@ -838,8 +906,8 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         epoch: &EpochKey,
         leaf_index: u32,
     ) -> Result<(), Self::Error> {
-        let key = epoch_key_pairs_id(group_id, epoch, leaf_index)?;
-        self.delete::<CURRENT_VERSION>(EPOCH_KEY_PAIRS_LABEL, &key)
+        let key = StorageKey::epoch_key_pairs(group_id, epoch, leaf_index)?;
+        self.delete::<CURRENT_VERSION>(&key)
     }
 
     fn clear_proposal_queue<
@@ -850,17 +918,22 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
         // Get all proposal refs for this group.
-        let proposal_refs: Vec<ProposalRef> =
-            self.read_list(PROPOSAL_QUEUE_REFS_LABEL, &serde_json::to_vec(group_id)?)?;
+        let proposal_refs: Vec<ProposalRef> = self.read_list(&StorageKey::ProposalQueueRefs(
+            serde_json::to_vec(group_id)?,
+        ))?;
         let mut values = self.values.write().unwrap();
         for proposal_ref in proposal_refs {
             // Delete all proposals.
-            let key = serde_json::to_vec(&(group_id, proposal_ref))?;
+            let key =
+                StorageKey::queued_proposal(group_id, &proposal_ref)?.encode::<CURRENT_VERSION>();
+            self.record_original(&values, &key);
             values.remove(&key);
         }
 
         // Delete the proposal refs from the store.
-        let key = build_key::<CURRENT_VERSION, &GroupId>(PROPOSAL_QUEUE_REFS_LABEL, group_id);
+        let key = StorageKey::ProposalQueueRefs(serde_json::to_vec(group_id).unwrap())
+            .encode::<CURRENT_VERSION>();
+        self.record_original(&values, &key);
         values.remove(&key);
 
         Ok(())
@@ -873,7 +946,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Option<MlsGroupJoinConfig>, Self::Error> {
-        self.read(JOIN_CONFIG_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.read(&StorageKey::JoinConfig(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn write_mls_join_config<
@@ -887,7 +962,7 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         let key = serde_json::to_vec(group_id).unwrap();
         let value = serde_json::to_vec(config).unwrap();
 
-        self.write::<CURRENT_VERSION>(JOIN_CONFIG_LABEL, &key, value)
+        self.write::<CURRENT_VERSION>(&StorageKey::JoinConfig(key), value)
     }
 
     fn own_leaf_nodes<
@@ -897,7 +972,9 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         &self,
         group_id: &GroupId,
     ) -> Result<Vec<LeafNode>, Self::Error> {
-        self.read_list(OWN_LEAF_NODES_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.read_list(&StorageKey::OwnLeafNodes(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn append_own_leaf_node<
@@ -910,55 +987,59 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
     ) -> Result<(), Self::Error> {
         let key = serde_json::to_vec(group_id)?;
         let value = serde_json::to_vec(leaf_node)?;
-        self.append::<CURRENT_VERSION>(OWN_LEAF_NODES_LABEL, &key, value)
+        self.append::<CURRENT_VERSION>(&StorageKey::OwnLeafNodes(key), value)
     }
 
     fn delete_own_leaf_nodes<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(OWN_LEAF_NODES_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.delete::<CURRENT_VERSION>(&StorageKey::OwnLeafNodes(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn delete_group_config<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(JOIN_CONFIG_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.delete::<CURRENT_VERSION>(&StorageKey::JoinConfig(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn delete_tree<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(TREE_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.delete::<CURRENT_VERSION>(&StorageKey::Tree(serde_json::to_vec(group_id).unwrap()))
     }
 
     fn delete_confirmation_tag<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(
-            CONFIRMATION_TAG_LABEL,
-            &serde_json::to_vec(group_id).unwrap(),
-        )
+        self.delete::<CURRENT_VERSION>(&StorageKey::ConfirmationTag(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn delete_context<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(GROUP_CONTEXT_LABEL, &serde_json::to_vec(group_id).unwrap())
+        self.delete::<CURRENT_VERSION>(&StorageKey::GroupContext(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn delete_interim_transcript_hash<GroupId: traits::GroupId<CURRENT_VERSION>>(
         &self,
         group_id: &GroupId,
     ) -> Result<(), Self::Error> {
-        self.delete::<CURRENT_VERSION>(
-            INTERIM_TRANSCRIPT_HASH_LABEL,
-            &serde_json::to_vec(group_id).unwrap(),
-        )
+        self.delete::<CURRENT_VERSION>(&StorageKey::InterimTranscriptHash(
+            serde_json::to_vec(group_id).unwrap(),
+        ))
     }
 
     fn remove_proposal<
@@ -972,35 +1053,54 @@ impl StorageProvider<CURRENT_VERSION> for MemoryStorage {
         let key = serde_json::to_vec(group_id).unwrap();
         let value = serde_json::to_vec(proposal_ref).unwrap();
 
-        self.remove_item::<CURRENT_VERSION>(PROPOSAL_QUEUE_REFS_LABEL, &key, value)?;
+        self.remove_item::<CURRENT_VERSION>(&StorageKey::ProposalQueueRefs(key), value)?;
 
-        let key = serde_json::to_vec(&(group_id, proposal_ref)).unwrap();
-        self.delete::<CURRENT_VERSION>(QUEUED_PROPOSAL_LABEL, &key)
+        let key = StorageKey::queued_proposal(group_id, proposal_ref)?;
+        self.delete::<CURRENT_VERSION>(&key)
     }
-}
 
-/// Build a key with version and label.
-fn build_key_from_vec<const V: u16>(label: &[u8], key: Vec<u8>) -> Vec<u8> {
-    let mut key_out = label.to_vec();
-    key_out.extend_from_slice(&key);
-    key_out.extend_from_slice(&u16::to_be_bytes(V));
-    key_out
-}
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        self.transactions.write().unwrap().push(HashMap::new());
+        Ok(())
+    }
 
-/// Build a key with version and label.
-fn build_key<const V: u16, K: Serialize>(label: &[u8], key: K) -> Vec<u8> {
-    build_key_from_vec::<V>(label, serde_json::to_vec(&key).unwrap())
-}
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        let mut transactions = self.transactions.write().unwrap();
+        let originals = transactions
+            .pop()
+            .ok_or(MemoryStorageError::NoTransaction)?;
+
+        // The changes become part of the outer transaction, which keeps the
+        // original values it recorded itself.
+        if let Some(outer) = transactions.last_mut() {
+            for (key, original) in originals {
+                outer.entry(key).or_insert(original);
+            }
+        }
+        Ok(())
+    }
 
-fn epoch_key_pairs_id(
-    group_id: &impl traits::GroupId<CURRENT_VERSION>,
-    epoch: &impl traits::EpochKey<CURRENT_VERSION>,
-    leaf_index: u32,
-) -> Result<Vec<u8>, <MemoryStorage as StorageProvider<CURRENT_VERSION>>::Error> {
-    let mut key = serde_json::to_vec(group_id)?;
-    key.extend_from_slice(&serde_json::to_vec(epoch)?);
-    key.extend_from_slice(&serde_json::to_vec(&leaf_index)?);
-    Ok(key)
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        let originals = self
+            .transactions
+            .write()
+            .unwrap()
+            .pop()
+            .ok_or(MemoryStorageError::NoTransaction)?;
+
+        let mut values = self.values.write().unwrap();
+        for (key, original) in originals {
+            match original {
+                Some(value) => values.insert(key, value),
+                None => values.remove(&key),
+            };
+        }
+        Ok(())
+    }
 }
 
 impl From<serde_json::Error> for MemoryStorageError {
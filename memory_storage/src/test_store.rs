@@ -13,8 +13,7 @@ impl StorageProvider<V_TEST> for MemoryStorage {
         key_pair: &HpkeKeyPair,
     ) -> Result<(), Self::Error> {
         self.write::<V_TEST>(
-            ENCRYPTION_KEY_PAIR_LABEL,
-            &serde_json::to_vec(&public_key).unwrap(),
+            &StorageKey::EncryptionKeyPair(serde_json::to_vec(&public_key).unwrap()),
             serde_json::to_vec(&key_pair).unwrap(),
         )
     }
@@ -37,7 +36,7 @@ impl StorageProvider<V_TEST> for MemoryStorage {
             epoch = serde_json::to_string(epoch).unwrap(),
         )
         .unwrap();
-        self.read_list(ENCRYPTION_KEY_PAIR_LABEL, &key)
+        self.read_list(&StorageKey::EncryptionKeyPair(key))
     }
 
     fn key_package<
@@ -54,7 +53,7 @@ impl StorageProvider<V_TEST> for MemoryStorage {
             "the whole store when trying to get the key package: {:?}",
             self.values.read().unwrap()
         );
-        self.read(KEY_PACKAGE_LABEL, &key)
+        self.read(&StorageKey::KeyPackage(key))
     }
 
     fn write_key_package<
@@ -69,7 +68,7 @@ impl StorageProvider<V_TEST> for MemoryStorage {
         println!("setting key package at {key:?} for version {V_TEST}");
         let value = serde_json::to_vec(&key_package).unwrap();
 
-        self.write::<V_TEST>(KEY_PACKAGE_LABEL, &key, value)
+        self.write::<V_TEST>(&StorageKey::KeyPackage(key), value)
             .unwrap();
 
         self.key_package::<HashReference, KeyPackage>(hash_ref)
@@ -0,0 +1,146 @@
+#![cfg(feature = "async")]
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use openmls_memory_storage::{async_store::AsyncStoreError, MemoryStorage};
+use openmls_traits::{
+    key_store_async::OpenMlsKeyStoreAsync,
+    storage::{traits, Entity, Key, StorageProvider, CURRENT_VERSION},
+};
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TreeSync(Vec<u8>);
+impl traits::TreeSync<CURRENT_VERSION> for TreeSync {}
+impl Entity<CURRENT_VERSION> for TreeSync {}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The key store is offline.")]
+struct Offline;
+
+/// An asynchronous key store that is backed by a map and can go offline.
+#[derive(Default)]
+struct TestStore {
+    values: RefCell<HashMap<Vec<u8>, Vec<u8>>>,
+    offline: Cell<bool>,
+}
+
+impl TestStore {
+    fn check_online(&self) -> Result<(), Offline> {
+        if self.offline.get() {
+            return Err(Offline);
+        }
+        Ok(())
+    }
+}
+
+impl OpenMlsKeyStoreAsync for TestStore {
+    type Error = Offline;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.check_online()?;
+        Ok(self.values.borrow().get(key).cloned())
+    }
+
+    async fn insert(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.check_online()?;
+        self.values
+            .borrow_mut()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<(), Self::Error> {
+        self.check_online()?;
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+/// Polls the `future` to completion. The futures of the [`TestStore`] are
+/// always ready.
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    // SAFETY: The functions of the vtable don't use the data pointer.
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Values written to one storage can be fetched into another one.
+#[test]
+fn fetch_and_flush() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let store = TestStore::default();
+
+    let storage = MemoryStorage::default();
+    storage.write_tree(&group_id, &TreeSync(vec![1])).unwrap();
+    block_on(storage.flush(&store)).unwrap();
+    assert_eq!(store.values.borrow().len(), 1);
+
+    let other_storage = MemoryStorage::default();
+    block_on(other_storage.fetch_group(&store, &group_id)).unwrap();
+    let tree: Option<TreeSync> = other_storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![1])));
+
+    // Deletes of fetched values are flushed.
+    other_storage.delete_tree(&group_id).unwrap();
+    block_on(other_storage.flush(&store)).unwrap();
+    assert!(store.values.borrow().is_empty());
+
+    // Fetching replaces values that haven't changed locally.
+    block_on(storage.fetch_group(&store, &group_id)).unwrap();
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, None);
+}
+
+/// Changes that couldn't be flushed are kept when fetching and flushed later.
+#[test]
+fn failed_flush() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let store = TestStore::default();
+    let storage = MemoryStorage::default();
+    storage.write_tree(&group_id, &TreeSync(vec![1])).unwrap();
+    block_on(storage.flush(&store)).unwrap();
+
+    storage.write_tree(&group_id, &TreeSync(vec![2])).unwrap();
+    store.offline.set(true);
+    let err = block_on(storage.flush(&store)).expect_err("flushed to an offline store");
+    assert!(matches!(err, AsyncStoreError::Store(Offline)));
+
+    store.offline.set(false);
+    block_on(storage.fetch_group(&store, &group_id)).unwrap();
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![2])));
+
+    block_on(storage.flush(&store)).unwrap();
+    let other_storage = MemoryStorage::default();
+    block_on(other_storage.fetch_group(&store, &group_id)).unwrap();
+    let tree: Option<TreeSync> = other_storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![2])));
+}
@@ -0,0 +1,118 @@
+use openmls_memory_storage::{MemoryStorage, StorageKey};
+use openmls_traits::storage::{traits, Entity, Key, StorageProvider, CURRENT_VERSION};
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+struct EpochKey(u64);
+impl traits::EpochKey<CURRENT_VERSION> for EpochKey {}
+impl Key<CURRENT_VERSION> for EpochKey {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+struct ProposalRef(usize);
+impl traits::ProposalRef<CURRENT_VERSION> for ProposalRef {}
+impl Key<CURRENT_VERSION> for ProposalRef {}
+impl Entity<CURRENT_VERSION> for ProposalRef {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct Proposal(Vec<u8>);
+impl traits::QueuedProposal<CURRENT_VERSION> for Proposal {}
+impl Entity<CURRENT_VERSION> for Proposal {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct HpkeKeyPair(Vec<u8>);
+impl traits::HpkeKeyPair<CURRENT_VERSION> for HpkeKeyPair {}
+impl Entity<CURRENT_VERSION> for HpkeKeyPair {}
+
+/// Encoded keys decode to the same key and version.
+#[test]
+fn encode_decode() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let keys = [
+        StorageKey::KeyPackage(b"[1,2,3]".to_vec()),
+        StorageKey::Psk(b"{\"id\":1}".to_vec()),
+        StorageKey::Tree(serde_json::to_vec(&group_id).unwrap()),
+        StorageKey::GroupContext(serde_json::to_vec(&group_id).unwrap()),
+        StorageKey::GroupState(serde_json::to_vec(&group_id).unwrap()),
+        StorageKey::epoch_key_pairs(&group_id, &EpochKey(12), 3).unwrap(),
+        StorageKey::queued_proposal(&group_id, &ProposalRef(7)).unwrap(),
+    ];
+
+    for key in keys {
+        let encoded = key.encode::<CURRENT_VERSION>();
+        assert_eq!(StorageKey::decode(&encoded), Some((key, CURRENT_VERSION)));
+    }
+
+    assert_eq!(StorageKey::decode(b"Unknown[1]\x00\x01"), None);
+    assert_eq!(StorageKey::decode(b"\x00"), None);
+}
+
+/// Keys of different kinds don't collide, even if their keys serialize to the
+/// same bytes.
+#[test]
+fn no_collisions() {
+    let key = b"[1,2,3]".to_vec();
+    let tree = StorageKey::Tree(key.clone()).encode::<CURRENT_VERSION>();
+    let group_state = StorageKey::GroupState(key).encode::<CURRENT_VERSION>();
+    assert_ne!(tree, group_state);
+
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let first = StorageKey::epoch_key_pairs(&group_id, &EpochKey(1), 23).unwrap();
+    let second = StorageKey::epoch_key_pairs(&group_id, &EpochKey(12), 3).unwrap();
+    assert_ne!(
+        first.encode::<CURRENT_VERSION>(),
+        second.encode::<CURRENT_VERSION>()
+    );
+}
+
+/// Epoch key pairs of different epochs and leaves are stored separately.
+#[test]
+fn epoch_key_pairs() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = MemoryStorage::default();
+
+    let first = vec![HpkeKeyPair(b"first".to_vec())];
+    let second = vec![HpkeKeyPair(b"second".to_vec())];
+    storage
+        .write_encryption_epoch_key_pairs(&group_id, &EpochKey(1), 23, &first)
+        .unwrap();
+    storage
+        .write_encryption_epoch_key_pairs(&group_id, &EpochKey(12), 3, &second)
+        .unwrap();
+
+    let read: Vec<HpkeKeyPair> = storage
+        .encryption_epoch_key_pairs(&group_id, &EpochKey(1), 23)
+        .unwrap();
+    assert_eq!(read, first);
+    let read: Vec<HpkeKeyPair> = storage
+        .encryption_epoch_key_pairs(&group_id, &EpochKey(12), 3)
+        .unwrap();
+    assert_eq!(read, second);
+}
+
+/// Clearing the proposal queue removes the queued proposals from the storage.
+#[test]
+fn clear_proposal_queue() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = MemoryStorage::default();
+
+    for i in 0..3 {
+        storage
+            .queue_proposal(&group_id, &ProposalRef(i), &Proposal(vec![i as u8]))
+            .unwrap();
+    }
+    storage
+        .clear_proposal_queue::<TestGroupId, ProposalRef>(&group_id)
+        .unwrap();
+
+    let values = storage.values.read().unwrap();
+    assert!(values.keys().all(|key| !matches!(
+        StorageKey::decode(key),
+        Some((StorageKey::QueuedProposal { .. }, _))
+    )));
+}
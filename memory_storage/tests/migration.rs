@@ -0,0 +1,132 @@
+use openmls_memory_storage::MemoryStorage;
+use openmls_traits::storage::{
+    migration::{MigrationError, StorageEntry, StorageMigrations, StorageVersion},
+    traits, Entity, Key, StorageProvider, CURRENT_VERSION,
+};
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TreeSync(Vec<u8>);
+impl traits::TreeSync<CURRENT_VERSION> for TreeSync {}
+impl Entity<CURRENT_VERSION> for TreeSync {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct GroupContext {
+    epoch: u64,
+}
+impl traits::GroupContext<CURRENT_VERSION> for GroupContext {}
+impl Entity<CURRENT_VERSION> for GroupContext {}
+
+/// Entries as they were written by a release with storage version 0, which
+/// stored the tree under the label `RatchetTree` and the epoch of the group
+/// context as a string.
+const V0_FIXTURE: &[(&[u8], &[u8])] = &[
+    (b"RatchetTree[1,2,3]\x00\x00", b"[4,5,6]"),
+    (b"GroupContext[1,2,3]\x00\x00", b"{\"epoch\":\"7\"}"),
+];
+
+fn from_v0(entry: &mut StorageEntry) -> Result<(), String> {
+    if entry.label.starts_with(b"RatchetTree") && entry.key.is_empty() {
+        // The label is unknown to this release, such that the key is part of
+        // the label.
+        entry.key = entry.label[b"RatchetTree".len()..].to_vec();
+        entry.label = b"Tree".to_vec();
+    } else if entry.label == b"GroupContext" {
+        let mut context: serde_json::Value =
+            serde_json::from_slice(&entry.value).map_err(|e| e.to_string())?;
+        let epoch: u64 = context["epoch"]
+            .as_str()
+            .ok_or("The epoch is not a string.")?
+            .parse()
+            .map_err(|_| "The epoch is not a number.")?;
+        context["epoch"] = epoch.into();
+        entry.value = serde_json::to_vec(&context).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn v0_storage() -> MemoryStorage {
+    let storage = MemoryStorage::default();
+    storage.values.write().unwrap().extend(
+        V0_FIXTURE
+            .iter()
+            .map(|(key, value)| (key.to_vec(), value.to_vec())),
+    );
+    storage
+}
+
+/// Entries of older versions are migrated with the registered migrations.
+#[test]
+fn migrate_fixture() {
+    let group_id = TestGroupId(vec![1, 2, 3]);
+    let storage = v0_storage();
+    let migrations =
+        StorageMigrations::new().register(StorageVersion::new(0), StorageVersion::CURRENT, from_v0);
+    assert!(migrations.supports(StorageVersion::new(0)));
+    assert!(migrations.supports(StorageVersion::CURRENT));
+
+    // The entries of version 0 are not visible before the migration.
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, None);
+
+    assert_eq!(storage.migrate(&migrations), Ok(2));
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![4, 5, 6])));
+    let group_context: Option<GroupContext> = storage.group_context(&group_id).unwrap();
+    assert_eq!(group_context, Some(GroupContext { epoch: 7 }));
+    assert_eq!(storage.values.read().unwrap().len(), 2);
+
+    // Migrating again doesn't change anything.
+    assert_eq!(storage.migrate(&migrations), Ok(0));
+}
+
+/// No entry is changed if the migration of one of them fails.
+#[test]
+fn migrate_errors() {
+    let storage = v0_storage();
+
+    let err = storage.migrate(&StorageMigrations::new());
+    assert_eq!(
+        err,
+        Err(MigrationError::MissingMigration(StorageVersion::new(0)))
+    );
+
+    fn failing(entry: &mut StorageEntry) -> Result<(), String> {
+        match entry.label.as_slice() {
+            b"GroupContext" => Err("Unknown group context".to_string()),
+            _ => Ok(()),
+        }
+    }
+    let migrations =
+        StorageMigrations::new().register(StorageVersion::new(0), StorageVersion::CURRENT, failing);
+    let err = storage.migrate(&migrations);
+    assert_eq!(
+        err,
+        Err(MigrationError::Failed {
+            version: StorageVersion::new(0),
+            reason: "Unknown group context".to_string(),
+        })
+    );
+    assert_eq!(
+        *storage.values.read().unwrap(),
+        v0_storage().values.into_inner().unwrap()
+    );
+
+    // Entries of newer versions can't be migrated.
+    let storage = MemoryStorage::default();
+    storage
+        .values
+        .write()
+        .unwrap()
+        .insert(b"Tree[1,2,3]\xff\xff".to_vec(), b"[]".to_vec());
+    assert_eq!(
+        storage.migrate(&StorageMigrations::new()),
+        Err(MigrationError::NewerVersion(StorageVersion::new(u16::MAX)))
+    );
+}
@@ -0,0 +1,87 @@
+use openmls_memory_storage::{MemoryStorage, MemoryStorageError};
+use openmls_traits::storage::{traits, Entity, Key, StorageProvider, CURRENT_VERSION};
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TreeSync(Vec<u8>);
+impl traits::TreeSync<CURRENT_VERSION> for TreeSync {}
+impl Entity<CURRENT_VERSION> for TreeSync {}
+
+// `MemoryStorage` implements the storage provider for several versions, so
+// the transaction functions have to be called for the current one.
+fn begin_transaction(storage: &MemoryStorage) -> Result<(), MemoryStorageError> {
+    <MemoryStorage as StorageProvider<CURRENT_VERSION>>::begin_transaction(storage)
+}
+
+fn commit_transaction(storage: &MemoryStorage) -> Result<(), MemoryStorageError> {
+    <MemoryStorage as StorageProvider<CURRENT_VERSION>>::commit_transaction(storage)
+}
+
+fn rollback_transaction(storage: &MemoryStorage) -> Result<(), MemoryStorageError> {
+    <MemoryStorage as StorageProvider<CURRENT_VERSION>>::rollback_transaction(storage)
+}
+
+/// Writes are kept on commit and reverted on rollback, also when nested.
+#[test]
+fn commit_and_rollback() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = MemoryStorage::default();
+    let tree = |storage: &MemoryStorage| -> Option<TreeSync> { storage.tree(&group_id).unwrap() };
+
+    begin_transaction(&storage).unwrap();
+    storage.write_tree(&group_id, &TreeSync(vec![1])).unwrap();
+
+    begin_transaction(&storage).unwrap();
+    storage.write_tree(&group_id, &TreeSync(vec![2])).unwrap();
+    rollback_transaction(&storage).unwrap();
+    assert_eq!(tree(&storage), Some(TreeSync(vec![1])));
+
+    commit_transaction(&storage).unwrap();
+    assert_eq!(tree(&storage), Some(TreeSync(vec![1])));
+
+    begin_transaction(&storage).unwrap();
+    storage.delete_tree(&group_id).unwrap();
+    rollback_transaction(&storage).unwrap();
+    assert_eq!(tree(&storage), Some(TreeSync(vec![1])));
+
+    assert_eq!(
+        commit_transaction(&storage),
+        Err(MemoryStorageError::NoTransaction)
+    );
+}
+
+/// Rolling back the outer transaction also reverts the writes of committed
+/// nested transactions.
+#[test]
+fn rollback_committed_nested() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = MemoryStorage::default();
+    storage.write_tree(&group_id, &TreeSync(vec![1])).unwrap();
+
+    begin_transaction(&storage).unwrap();
+    begin_transaction(&storage).unwrap();
+    storage.write_tree(&group_id, &TreeSync(vec![2])).unwrap();
+    commit_transaction(&storage).unwrap();
+    storage.delete_tree(&group_id).unwrap();
+    rollback_transaction(&storage).unwrap();
+
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![1])));
+
+    // Values that didn't exist before the transaction are removed again.
+    let other_group_id = TestGroupId(b"OtherGroupId".to_vec());
+    begin_transaction(&storage).unwrap();
+    storage
+        .write_tree(&other_group_id, &TreeSync(vec![3]))
+        .unwrap();
+    rollback_transaction(&storage).unwrap();
+
+    let tree: Option<TreeSync> = storage.tree(&other_group_id).unwrap();
+    assert_eq!(tree, None);
+}
@@ -0,0 +1,211 @@
+//! Counts the heap allocations of encrypting, decrypting and committing for
+//! every ciphersuite of the RustCrypto provider.
+//!
+//! The counts are averaged over a number of iterations and printed, such that
+//! changes to the allocations of the hot paths can be compared between
+//! revisions. Unlike the timing benchmarks, the counts don't depend on the
+//! machine.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use openmls::prelude::*;
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+
+/// The number of iterations the counts are averaged over.
+const ITERATIONS: usize = 100;
+
+/// A global allocator that counts the allocations and the allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+// SAFETY: All calls are forwarded to the system allocator.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // A reallocation is counted as a new allocation of the new size.
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The allocations of an operation.
+#[derive(Default)]
+struct Allocations {
+    count: usize,
+    bytes: usize,
+}
+
+/// Run `operation` and add its allocations to `allocations`.
+fn count<T>(allocations: &mut Allocations, operation: impl FnOnce() -> T) -> T {
+    let count = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let output = operation();
+    allocations.count += ALLOCATIONS.load(Ordering::Relaxed) - count;
+    allocations.bytes += ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+    output
+}
+
+fn report(operation: &str, ciphersuite: Ciphersuite, allocations: &Allocations) {
+    println!(
+        "{operation} ({ciphersuite}): {} allocations, {} bytes",
+        allocations.count / ITERATIONS,
+        allocations.bytes / ITERATIONS,
+    );
+}
+
+fn credential(
+    ciphersuite: Ciphersuite,
+    identity: impl Into<Vec<u8>>,
+) -> (CredentialWithKey, SignatureKeyPair) {
+    let credential = BasicCredential::new(identity.into());
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        credential: credential.into(),
+        signature_key: signer.to_public_vec().into(),
+    };
+
+    (credential_with_key, signer)
+}
+
+fn protocol_message(message: MlsMessageOut) -> ProtocolMessage {
+    let message: MlsMessageIn = message.into();
+    message
+        .try_into_protocol_message()
+        .expect("expected a protocol message")
+}
+
+/// Alice and Bob in a group of two members.
+fn two_member_group(
+    provider: &impl OpenMlsProvider,
+    ciphersuite: Ciphersuite,
+) -> (MlsGroup, SignatureKeyPair, MlsGroup) {
+    let (alice_credential_with_key, alice_signer) = credential(ciphersuite, "Alice");
+    let (bob_credential_with_key, bob_signer) = credential(ciphersuite, "Bob");
+    let bob_key_package = KeyPackage::builder()
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .expect("An unexpected error occurred.");
+
+    let config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+    let mut alice_group =
+        MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+            .expect("An unexpected error occurred.");
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome: MlsMessageIn = welcome.into();
+    let welcome = welcome
+        .into_welcome()
+        .expect("expected the message to be a welcome message");
+    let bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        config.join_config(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .unwrap()
+    .into_group(provider)
+    .unwrap();
+
+    (alice_group, alice_signer, bob_group)
+}
+
+fn application_messages(provider: &impl OpenMlsProvider, ciphersuite: Ciphersuite) {
+    const MESSAGE: &[u8] = &[0x2a; 1024];
+
+    let (mut alice_group, alice_signer, mut bob_group) = two_member_group(provider, ciphersuite);
+    let mut encrypt = Allocations::default();
+    let mut decrypt = Allocations::default();
+    for _ in 0..ITERATIONS {
+        let message = count(&mut encrypt, || {
+            alice_group
+                .create_message(provider, &alice_signer, MESSAGE)
+                .expect("Could not create the message.")
+        });
+        let message = protocol_message(message);
+        count(&mut decrypt, || {
+            bob_group
+                .process_message(provider, message)
+                .expect("Could not process the message.")
+        });
+    }
+    report("Encrypt an application message", ciphersuite, &encrypt);
+    report("Decrypt an application message", ciphersuite, &decrypt);
+}
+
+fn commits(provider: &impl OpenMlsProvider, ciphersuite: Ciphersuite) {
+    let (mut alice_group, alice_signer, mut bob_group) = two_member_group(provider, ciphersuite);
+    let mut create = Allocations::default();
+    let mut process = Allocations::default();
+    for _ in 0..ITERATIONS {
+        let commit = count(&mut create, || {
+            let (commit, _, _) = alice_group
+                .self_update(provider, &alice_signer, LeafNodeParameters::default())
+                .expect("Could not create a commit.")
+                .into_contents();
+            alice_group
+                .merge_pending_commit(provider)
+                .expect("error merging pending commit");
+            commit
+        });
+        let commit = protocol_message(commit);
+        count(&mut process, || {
+            let processed_message = bob_group
+                .process_message(provider, commit)
+                .expect("Could not process the commit.");
+            let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+                processed_message.into_content()
+            else {
+                panic!("expected a commit");
+            };
+            bob_group
+                .merge_staged_commit(provider, *staged_commit)
+                .expect("error merging staged commit");
+        });
+    }
+    report("Create and merge a commit", ciphersuite, &create);
+    report("Process and merge a commit", ciphersuite, &process);
+}
+
+fn main() {
+    let provider = &OpenMlsRustCrypto::default();
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        application_messages(provider, ciphersuite);
+        commits(provider, ciphersuite);
+    }
+}
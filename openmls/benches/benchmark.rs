@@ -100,6 +100,72 @@ fn create_welcome(c: &mut Criterion, provider: &impl OpenMlsProvider) {
     }
 }
 
+fn create_welcome_many_members(c: &mut Criterion, provider: &impl OpenMlsProvider) {
+    const NUMBER_OF_MEMBERS: usize = 100;
+
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        c.bench_function(
+            &format!(
+                "Create a welcome message for {NUMBER_OF_MEMBERS} members with ciphersuite: {ciphersuite:?}"
+            ),
+            move |b| {
+                b.iter_with_setup(
+                    || {
+                        let alice_credential = BasicCredential::new("Alice".into());
+                        let alice_signer =
+                            SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+                        let alice_credential_with_key = CredentialWithKey {
+                            credential: alice_credential.into(),
+                            signature_key: alice_signer.to_public_vec().into(),
+                        };
+
+                        let key_packages: Vec<KeyPackage> = (0..NUMBER_OF_MEMBERS)
+                            .map(|i| {
+                                let credential = BasicCredential::new(format!("Member {i}").into());
+                                let signer =
+                                    SignatureKeyPair::new(ciphersuite.signature_algorithm())
+                                        .unwrap();
+                                let credential_with_key = CredentialWithKey {
+                                    credential: credential.into(),
+                                    signature_key: signer.to_public_vec().into(),
+                                };
+                                KeyPackage::builder()
+                                    .build(ciphersuite, provider, &signer, credential_with_key)
+                                    .expect("An unexpected error occurred.")
+                                    .key_package()
+                                    .clone()
+                            })
+                            .collect();
+
+                        let mls_group_create_config = MlsGroupCreateConfig::builder()
+                            .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+                            .ciphersuite(ciphersuite)
+                            .build();
+
+                        // === Alice creates a group ===
+                        let alice_group = MlsGroup::new(
+                            provider,
+                            &alice_signer,
+                            &mls_group_create_config,
+                            alice_credential_with_key.clone(),
+                        )
+                        .expect("An unexpected error occurred.");
+
+                        (alice_signer, alice_group, key_packages)
+                    },
+                    |(alice_signer, mut alice_group, key_packages)| {
+                        let _welcome =
+                            match alice_group.add_members(provider, &alice_signer, &key_packages) {
+                                Ok((_, welcome, _)) => welcome,
+                                Err(e) => panic!("Could not add members to group: {e:?}"),
+                            };
+                    },
+                );
+            },
+        );
+    }
+}
+
 fn join_group(c: &mut Criterion, provider: &impl OpenMlsProvider) {
     for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
         c.bench_function(
@@ -279,6 +345,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     kp_bundle_rust_crypto(c);
     criterion_key_package(c, &openmls_libcrux_crypto::Provider::default());
     create_welcome(c, &openmls_libcrux_crypto::Provider::default());
+    create_welcome_many_members(c, &openmls_libcrux_crypto::Provider::default());
     join_group(c, &openmls_libcrux_crypto::Provider::default());
     create_commit(c, &openmls_libcrux_crypto::Provider::default());
 }
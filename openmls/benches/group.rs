@@ -0,0 +1,412 @@
+//! Benchmarks of the core group operations for every supported ciphersuite.
+//!
+//! The setup of every operation, e.g. the creation of the key packages of the
+//! members that are added to a group, is not part of the measurement.
+//!
+//! The HPKE encryptions of update paths and Welcome messages run in parallel
+//! with the `parallel` feature, which is enabled by default.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use openmls::prelude::*;
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+
+/// The numbers of members that are added to a group at once.
+const GROUP_SIZES: [usize; 3] = [10, 100, 1000];
+
+/// The number of members of the group in which update paths are encrypted.
+const LARGE_GROUP_SIZE: usize = 1000;
+
+fn credential(
+    ciphersuite: Ciphersuite,
+    identity: impl Into<Vec<u8>>,
+) -> (CredentialWithKey, SignatureKeyPair) {
+    let credential = BasicCredential::new(identity.into());
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        credential: credential.into(),
+        signature_key: signer.to_public_vec().into(),
+    };
+
+    (credential_with_key, signer)
+}
+
+fn key_packages(
+    provider: &impl OpenMlsProvider,
+    ciphersuite: Ciphersuite,
+    number_of_members: usize,
+) -> Vec<KeyPackage> {
+    (0..number_of_members)
+        .map(|i| {
+            let (credential_with_key, signer) = credential(ciphersuite, format!("Member {i}"));
+            KeyPackage::builder()
+                .build(ciphersuite, provider, &signer, credential_with_key)
+                .expect("An unexpected error occurred.")
+                .key_package()
+                .clone()
+        })
+        .collect()
+}
+
+fn create_config(ciphersuite: Ciphersuite) -> MlsGroupCreateConfig {
+    MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build()
+}
+
+/// Alice and Bob in a group of two members.
+struct TwoMemberGroup {
+    alice_group: MlsGroup,
+    alice_signer: SignatureKeyPair,
+    bob_group: MlsGroup,
+}
+
+impl TwoMemberGroup {
+    fn new(provider: &impl OpenMlsProvider, ciphersuite: Ciphersuite) -> Self {
+        let (alice_credential_with_key, alice_signer) = credential(ciphersuite, "Alice");
+        let (bob_credential_with_key, bob_signer) = credential(ciphersuite, "Bob");
+        let bob_key_package = KeyPackage::builder()
+            .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+            .expect("An unexpected error occurred.");
+
+        let config = create_config(ciphersuite);
+        let mut alice_group =
+            MlsGroup::new(provider, &alice_signer, &config, alice_credential_with_key)
+                .expect("An unexpected error occurred.");
+        let (_, welcome, _) = alice_group
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package.key_package().clone()],
+            )
+            .expect("Could not add member to group.");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+
+        let bob_group = join(provider, &config, welcome, &alice_group);
+
+        Self {
+            alice_group,
+            alice_signer,
+            bob_group,
+        }
+    }
+}
+
+fn join(
+    provider: &impl OpenMlsProvider,
+    config: &MlsGroupCreateConfig,
+    welcome: MlsMessageOut,
+    group: &MlsGroup,
+) -> MlsGroup {
+    let welcome: MlsMessageIn = welcome.into();
+    let welcome = welcome
+        .into_welcome()
+        .expect("expected the message to be a welcome message");
+    StagedWelcome::new_from_welcome(
+        provider,
+        config.join_config(),
+        welcome,
+        Some(group.export_ratchet_tree().into()),
+    )
+    .unwrap()
+    .into_group(provider)
+    .unwrap()
+}
+
+fn protocol_message(message: MlsMessageOut) -> ProtocolMessage {
+    let message: MlsMessageIn = message.into();
+    message
+        .try_into_protocol_message()
+        .expect("expected a protocol message")
+}
+
+fn group_creation(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    let mut group = c.benchmark_group(format!("Group creation ({provider_name})"));
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let (credential_with_key, signer) = credential(ciphersuite, "Alice");
+        let config = create_config(ciphersuite);
+        group.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter(|| {
+                MlsGroup::new(provider, &signer, &config, credential_with_key.clone())
+                    .expect("An unexpected error occurred.")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn add_members(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    let mut group = c.benchmark_group(format!("Add members ({provider_name})"));
+    group.sample_size(10);
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let (credential_with_key, signer) = credential(ciphersuite, "Alice");
+        let config = create_config(ciphersuite);
+        for number_of_members in GROUP_SIZES {
+            let key_packages = key_packages(provider, ciphersuite, number_of_members);
+            group.bench_function(
+                BenchmarkId::new(ciphersuite.to_string(), number_of_members),
+                |b| {
+                    b.iter_batched(
+                        || {
+                            MlsGroup::new(provider, &signer, &config, credential_with_key.clone())
+                                .expect("An unexpected error occurred.")
+                        },
+                        |mut alice_group| {
+                            alice_group
+                                .add_members(provider, &signer, &key_packages)
+                                .expect("Could not add members to group.");
+                            alice_group
+                                .merge_pending_commit(provider)
+                                .expect("error merging pending commit");
+                        },
+                        BatchSize::PerIteration,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn process_commit(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    let mut group = c.benchmark_group(format!("Process a commit ({provider_name})"));
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        group.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter_batched(
+                || {
+                    let mut groups = TwoMemberGroup::new(provider, ciphersuite);
+                    let (commit, _, _) = groups
+                        .alice_group
+                        .self_update(
+                            provider,
+                            &groups.alice_signer,
+                            LeafNodeParameters::default(),
+                        )
+                        .expect("Could not create a commit.")
+                        .into_contents();
+                    (groups.bob_group, protocol_message(commit))
+                },
+                |(mut bob_group, commit)| {
+                    let processed_message = bob_group
+                        .process_message(provider, commit)
+                        .expect("Could not process the commit.");
+                    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+                        processed_message.into_content()
+                    else {
+                        panic!("expected a commit");
+                    };
+                    bob_group
+                        .merge_staged_commit(provider, *staged_commit)
+                        .expect("error merging staged commit");
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn application_messages(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    const MESSAGE: &[u8] = &[0x2a; 1024];
+
+    let mut encrypt =
+        c.benchmark_group(format!("Encrypt an application message ({provider_name})"));
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let mut groups = TwoMemberGroup::new(provider, ciphersuite);
+        encrypt.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter(|| {
+                groups
+                    .alice_group
+                    .create_message(provider, &groups.alice_signer, MESSAGE)
+                    .expect("Could not create the message.")
+            })
+        });
+    }
+    encrypt.finish();
+
+    let mut decrypt =
+        c.benchmark_group(format!("Decrypt an application message ({provider_name})"));
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let TwoMemberGroup {
+            mut alice_group,
+            alice_signer,
+            mut bob_group,
+        } = TwoMemberGroup::new(provider, ciphersuite);
+        decrypt.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter_batched(
+                || {
+                    let message = alice_group
+                        .create_message(provider, &alice_signer, MESSAGE)
+                        .expect("Could not create the message.");
+                    protocol_message(message)
+                },
+                |message| {
+                    bob_group
+                        .process_message(provider, message)
+                        .expect("Could not process the message.")
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    decrypt.finish();
+}
+
+fn join_from_welcome(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    let mut group = c.benchmark_group(format!("Join from a welcome ({provider_name})"));
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let (alice_credential_with_key, alice_signer) = credential(ciphersuite, "Alice");
+        let config = create_config(ciphersuite);
+        group.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter_batched(
+                || {
+                    let key_packages = key_packages(provider, ciphersuite, 1);
+                    let mut alice_group = MlsGroup::new(
+                        provider,
+                        &alice_signer,
+                        &config,
+                        alice_credential_with_key.clone(),
+                    )
+                    .expect("An unexpected error occurred.");
+                    let (_, welcome, _) = alice_group
+                        .add_members(provider, &alice_signer, &key_packages)
+                        .expect("Could not add member to group.");
+                    alice_group
+                        .merge_pending_commit(provider)
+                        .expect("error merging pending commit");
+                    (alice_group, welcome)
+                },
+                |(alice_group, welcome)| join(provider, &config, welcome, &alice_group),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Right after the members were added, all parent nodes of the group are blank
+/// and the copath resolutions of an update path contain many leaves, each of
+/// which requires a separate HPKE encryption.
+fn update_path_large_group(
+    c: &mut Criterion,
+    provider: &impl OpenMlsProvider,
+    provider_name: &str,
+) {
+    let mut group = c.benchmark_group(format!(
+        "Update path in a group of {LARGE_GROUP_SIZE} members ({provider_name})"
+    ));
+    group.sample_size(10);
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let (credential_with_key, signer) = credential(ciphersuite, "Alice");
+        let mut alice_group = MlsGroup::new(
+            provider,
+            &signer,
+            &create_config(ciphersuite),
+            credential_with_key,
+        )
+        .expect("An unexpected error occurred.");
+        alice_group
+            .add_members(
+                provider,
+                &signer,
+                &key_packages(provider, ciphersuite, LARGE_GROUP_SIZE - 1),
+            )
+            .expect("Could not add members to group.");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+
+        group.bench_function(BenchmarkId::from_parameter(ciphersuite), |b| {
+            b.iter(|| {
+                alice_group
+                    .self_update(provider, &signer, LeafNodeParameters::default())
+                    .expect("Could not create a commit.");
+                // Discard the commit, such that every iteration encrypts to
+                // the same resolutions.
+                alice_group
+                    .clear_pending_commit(provider.storage())
+                    .expect("Could not clear the pending commit.");
+            })
+        });
+    }
+    group.finish();
+}
+
+/// The group info of a large group with the ratchet tree extension, as used
+/// to join the group, is mostly made up of the ratchet tree. Deserializing it
+/// from a byte slice shouldn't be slower than from a reader, i.e. the message
+/// must not be traversed again to find the remainder of the slice.
+fn deserialize_group_info_large_group(
+    c: &mut Criterion,
+    provider: &impl OpenMlsProvider,
+    provider_name: &str,
+) {
+    let mut group = c.benchmark_group(format!(
+        "Deserialize the group info of a group of {LARGE_GROUP_SIZE} members ({provider_name})"
+    ));
+    group.sample_size(10);
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let (credential_with_key, signer) = credential(ciphersuite, "Alice");
+        let mut alice_group = MlsGroup::new(
+            provider,
+            &signer,
+            &create_config(ciphersuite),
+            credential_with_key,
+        )
+        .expect("An unexpected error occurred.");
+        alice_group
+            .add_members(
+                provider,
+                &signer,
+                &key_packages(provider, ciphersuite, LARGE_GROUP_SIZE - 1),
+            )
+            .expect("Could not add members to group.");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+        let group_info = alice_group
+            .export_group_info(provider, &signer, true)
+            .expect("Could not export the group info.")
+            .to_bytes()
+            .expect("Could not serialize the group info.");
+
+        group.bench_function(BenchmarkId::new("Reader", ciphersuite), |b| {
+            b.iter(|| {
+                <MlsMessageIn as tls_codec::Deserialize>::tls_deserialize_exact(
+                    group_info.as_slice(),
+                )
+                .expect("Could not deserialize the group info.")
+            })
+        });
+        group.bench_function(BenchmarkId::new("Byte slice", ciphersuite), |b| {
+            b.iter(|| {
+                <MlsMessageIn as tls_codec::DeserializeBytes>::tls_deserialize_exact_bytes(
+                    &group_info,
+                )
+                .expect("Could not deserialize the group info.")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_provider(c: &mut Criterion, provider: &impl OpenMlsProvider, provider_name: &str) {
+    group_creation(c, provider, provider_name);
+    add_members(c, provider, provider_name);
+    process_commit(c, provider, provider_name);
+    application_messages(c, provider, provider_name);
+    join_from_welcome(c, provider, provider_name);
+    update_path_large_group(c, provider, provider_name);
+    deserialize_group_info_large_group(c, provider, provider_name);
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_provider(c, &OpenMlsRustCrypto::default(), "RustCrypto");
+    bench_provider(c, &openmls_libcrux_crypto::Provider::default(), "libcrux");
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -68,9 +68,14 @@ impl<L: Clone + Debug + Default, P: Clone + Debug + Default> StagedAbDiff<L, P>
     }
 
     /// Return the projected size of the tree after a merge with the diff.
-    pub(super) fn tree_size(&self) -> TreeSize {
+    pub(crate) fn tree_size(&self) -> TreeSize {
         self.size
     }
+
+    /// Return the indices of the leaves that are changed by the diff.
+    pub(crate) fn changed_leaves(&self) -> impl Iterator<Item = LeafNodeIndex> + '_ {
+        self.leaf_diff.keys().copied()
+    }
 }
 
 /// The [`AbDiff`] represents a set of differences (i.e. a "Diff") for an
@@ -21,6 +21,10 @@
 //!
 //! Any value that is invalid is represented as `null`.
 //!
+//! Test vectors of drafts that didn't require full trees are a single object
+//! with the roots of all tree sizes up to `n_leaves` in `root`. They are
+//! skipped.
+//!
 //! ## Verification:
 //! * `n_nodes` is the number of nodes in the tree with `n_leaves` leaves
 //! * `root` is the root node index of the tree
@@ -34,7 +38,7 @@
 //!   in a tree with `n_leaves` leaves
 
 #[cfg(test)]
-use crate::test_utils::*;
+use crate::test_utils::{kat_format::*, *};
 
 use super::treemath::*;
 
@@ -52,6 +56,17 @@ pub struct TreeMathTestVector {
     sibling: Vec<Option<u32>>,
 }
 
+#[cfg(test)]
+impl DraftTagged for TreeMathTestVector {
+    fn detect_draft(vector: &serde_json::Value) -> KatDraft {
+        if vector["root"].is_array() {
+            KatDraft::Draft(16)
+        } else {
+            KatDraft::Rfc9420
+        }
+    }
+}
+
 #[cfg(any(feature = "test-utils", test))]
 pub fn generate_test_vector(n_leaves: u32) -> TreeMathTestVector {
     let n_nodes = TreeSize::new(node_width(n_leaves as usize) as u32);
@@ -186,8 +201,10 @@ pub fn run_test_vector(test_vector: TreeMathTestVector) -> Result<(), TmTestVect
 
 #[test]
 fn read_test_vectors_tm() {
-    let tests: Vec<TreeMathTestVector> = read_json!("../../../test_vectors/tree-math.json");
-    for test_vector in tests {
+    let tests: TaggedVectors<TreeMathTestVector> =
+        read_json_tagged!("../../../test_vectors/tree-math.json");
+    assert!(tests.skipped.is_empty());
+    for test_vector in tests.into_vectors() {
         match run_test_vector(test_vector) {
             Ok(_) => {}
             Err(e) => panic!("Error while checking tree math test vector.\n{e:?}"),
@@ -195,6 +212,28 @@ fn read_test_vectors_tm() {
     }
 }
 
+#[test]
+fn read_test_vectors_tm_drafts() {
+    let old_vector = serde_json::json!({
+        "n_leaves": 3,
+        "root": [0, 1, 3],
+        "left": [null, 0, null, 1, null],
+        "right": [null, 2, null, 4, null],
+        "parent": [1, 3, 1, null, 3],
+        "sibling": [2, 4, 0, null, 1],
+    });
+    let rfc_vector = serde_json::to_value(generate_test_vector(4)).unwrap();
+    let data = serde_json::to_string(&[old_vector, rfc_vector]).unwrap();
+
+    let tests = parse_tagged::<TreeMathTestVector>(&data).unwrap();
+    assert_eq!(tests.skipped, vec![(0, KatDraft::Draft(16))]);
+    assert_eq!(tests.vectors.len(), 1);
+    assert_eq!(tests.vectors[0].draft, KatDraft::Rfc9420);
+    for test_vector in tests.into_vectors() {
+        run_test_vector(test_vector).expect("Error while checking tree math test vector.");
+    }
+}
+
 #[cfg(any(feature = "test-utils", test))]
 /// TreeMath test vector error
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
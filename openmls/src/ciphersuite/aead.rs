@@ -178,4 +178,26 @@ mod unit_tests {
             "xoring twice changed the original value"
         );
     }
+
+    /// Check the reuse guard XOR against a fixed nonce and reuse guard. Only the
+    /// first four bytes of the nonce must be affected.
+    #[test]
+    fn test_xor_known_answer() {
+        let reuse_guard = ReuseGuard {
+            value: [0xde, 0xad, 0xbe, 0xef],
+        };
+        let nonce = AeadNonce([
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+        ]);
+        let xored = nonce.xor_with_reuse_guard(&reuse_guard);
+        assert_eq!(
+            xored.as_slice(),
+            &[0xde, 0xac, 0xbc, 0xec, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b]
+        );
+
+        // An all-zero reuse guard must leave the nonce untouched.
+        let zero_guard = ReuseGuard { value: [0u8; 4] };
+        let nonce = AeadNonce([0xff; 12]);
+        assert_eq!(nonce.clone().xor_with_reuse_guard(&zero_guard), nonce);
+    }
 }
@@ -36,7 +36,6 @@ impl DeserializeBytes for Secret {
     {
         let mut bytes_ref = bytes;
         let secret = Secret::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[secret.tls_serialized_len()..];
-        Ok((secret, remainder))
+        Ok((secret, bytes_ref))
     }
 }
@@ -123,6 +123,42 @@ pub(crate) fn encrypt_with_label(
     Ok(cipher)
 }
 
+/// Encrypt the same plaintext to each of the HPKE keys with a label.
+///
+/// The `EncryptContext` is serialized once for all recipients and the
+/// ciphertexts are returned in the order of the `public_keys`.
+pub(crate) fn encrypt_with_label_multi(
+    public_keys: &[&[u8]],
+    label: &str,
+    context: &[u8],
+    plaintext: &[u8],
+    ciphersuite: Ciphersuite,
+    crypto: &impl OpenMlsCrypto,
+) -> Result<Vec<HpkeCiphertext>, Error> {
+    let context: EncryptContext = (label, context).into();
+    let context = context.tls_serialize_detached()?;
+
+    log_crypto!(
+        debug,
+        "HPKE Encrypt with label `{label}` and ciphersuite `{ciphersuite:?}` to {} recipients:",
+        public_keys.len()
+    );
+    log_crypto!(debug, "* context:     {context:x?}");
+    log_crypto!(debug, "* plaintext:   {plaintext:x?}");
+
+    let ciphertexts = crypto.hpke_seal_multi(
+        ciphersuite.hpke_config(),
+        public_keys,
+        &context,
+        &[],
+        plaintext,
+    )?;
+
+    log_crypto!(debug, "* ciphertexts: {:x?}", ciphertexts);
+
+    Ok(ciphertexts)
+}
+
 /// Decrypt with HPKE and label.
 pub(crate) fn decrypt_with_label(
     private_key: &[u8],
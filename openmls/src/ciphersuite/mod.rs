@@ -18,6 +18,7 @@ mod codec;
 pub(crate) mod hpke;
 mod kdf_label;
 mod mac;
+mod registry;
 mod reuse_guard;
 mod secret;
 
@@ -26,6 +27,8 @@ pub mod hash_ref;
 pub mod signable;
 pub mod signature;
 
+pub use registry::CiphersuiteRegistry;
+
 // Crate
 pub(crate) use aead::*;
 pub(crate) use mac::*;
@@ -46,11 +49,18 @@ pub use openmls_traits::types::HpkePrivateKey;
 
 /// Compare two byte slices in a way that's hopefully not optimised out by the
 /// compiler.
+///
+/// The lengths of the slices are considered public and slices of different
+/// lengths are never equal. In particular, a truncated or empty MAC doesn't
+/// match the prefix of the expected MAC.
 #[inline(never)]
 fn equal_ct(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
     let mut diff = 0u8;
     for (l, r) in a.iter().zip(b.iter()) {
         diff |= l ^ r;
     }
-    diff == 0
+    std::hint::black_box(diff) == 0
 }
@@ -0,0 +1,61 @@
+//! # Ciphersuite registry
+//!
+//! The [`CiphersuiteRegistry`] holds the ciphersuites that a crypto provider
+//! implements. OpenMLS uses it to fill in the default [`Capabilities`] of new
+//! leaf nodes and to reject groups and key packages with ciphersuites the
+//! provider can't handle, such that a provider that only implements a subset
+//! of the ciphersuites, or additional ones, is reflected accurately.
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    types::{Ciphersuite, SignatureScheme, SupportedAlgorithms, VerifiableCiphersuite},
+};
+
+use crate::treesync::node::leaf_node::Capabilities;
+
+/// The ciphersuites supported by a crypto provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiphersuiteRegistry {
+    algorithms: SupportedAlgorithms,
+}
+
+impl CiphersuiteRegistry {
+    /// Create a registry of the ciphersuites supported by `crypto`.
+    pub fn new(crypto: &impl OpenMlsCrypto) -> Self {
+        Self {
+            algorithms: crypto.supported_algorithms(),
+        }
+    }
+
+    /// Returns the supported ciphersuites, in the order of the provider's
+    /// preference.
+    pub fn ciphersuites(&self) -> &[Ciphersuite] {
+        self.algorithms.ciphersuites()
+    }
+
+    /// Returns the algorithms supported by the provider.
+    pub fn algorithms(&self) -> &SupportedAlgorithms {
+        &self.algorithms
+    }
+
+    /// Returns `true` if the `ciphersuite` is supported.
+    pub fn supports(&self, ciphersuite: impl Into<VerifiableCiphersuite>) -> bool {
+        let ciphersuite = ciphersuite.into();
+        self.ciphersuites()
+            .iter()
+            .any(|&supported| VerifiableCiphersuite::from(supported) == ciphersuite)
+    }
+
+    /// Returns `true` if the `signature_scheme` is supported.
+    pub fn supports_signature_scheme(&self, signature_scheme: SignatureScheme) -> bool {
+        self.algorithms.supports_signature_scheme(signature_scheme)
+    }
+
+    /// Returns the default [`Capabilities`], advertising the supported
+    /// ciphersuites.
+    pub fn default_capabilities(&self) -> Capabilities {
+        Capabilities::builder()
+            .ciphersuites(self.ciphersuites().to_vec())
+            .build()
+    }
+}
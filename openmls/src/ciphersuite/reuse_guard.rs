@@ -18,4 +18,10 @@ impl ReuseGuard {
                 .map_err(|_| CryptoError::InsufficientRandomness)?,
         })
     }
+
+    /// Creates a reuse guard from the given bytes.
+    #[cfg(any(feature = "test-utils", test))]
+    pub(crate) fn from_bytes(value: [u8; REUSE_GUARD_BYTES]) -> Self {
+        Self { value }
+    }
 }
@@ -76,3 +76,95 @@ fn test_hpke_seal_open() {
         CryptoError::HpkeDecryptionError
     );
 }
+
+// Encrypting to multiple recipients yields one ciphertext per recipient in the
+// order of the public keys.
+#[openmls_test::openmls_test]
+fn test_hpke_seal_multi() {
+    let plaintext = &[1, 2, 3];
+    let key_pairs = (0..3)
+        .map(|_| {
+            provider
+                .crypto()
+                .derive_hpke_keypair(
+                    ciphersuite.hpke_config(),
+                    Secret::random(ciphersuite, provider.rand())
+                        .expect("Not enough randomness.")
+                        .as_slice(),
+                )
+                .expect("error deriving hpke key pair")
+        })
+        .collect::<Vec<_>>();
+    let public_keys = key_pairs
+        .iter()
+        .map(|kp| kp.public.as_slice())
+        .collect::<Vec<_>>();
+
+    let ciphertexts = hpke::encrypt_with_label_multi(
+        &public_keys,
+        "label",
+        &[1, 2, 3],
+        plaintext,
+        ciphersuite,
+        provider.crypto(),
+    )
+    .unwrap();
+    assert_eq!(ciphertexts.len(), key_pairs.len());
+
+    for (kp, ciphertext) in key_pairs.iter().zip(ciphertexts.iter()) {
+        let decrypted_payload = hpke::decrypt_with_label(
+            &kp.private,
+            "label",
+            &[1, 2, 3],
+            ciphertext,
+            ciphersuite,
+            provider.crypto(),
+        )
+        .expect("Unexpected error while decrypting a valid ciphertext.");
+        assert_eq!(decrypted_payload, plaintext);
+    }
+
+    // Each recipient gets its own encapsulation.
+    assert_ne!(ciphertexts[0].kem_output, ciphertexts[1].kem_output);
+}
+
+// Hashing data written in parts yields the hash of the concatenated data.
+#[openmls_test::openmls_test]
+fn test_hash_writer() {
+    use std::io::Write;
+
+    let data = (0..=255).collect::<Vec<u8>>();
+    let hash_type = ciphersuite.hash_algorithm();
+
+    let mut hash_writer = provider.crypto().hash_writer(hash_type).unwrap();
+    for chunk in data.chunks(7) {
+        hash_writer.write_all(chunk).unwrap();
+    }
+    assert_eq!(
+        hash_writer.finalize().unwrap(),
+        provider.crypto().hash(hash_type, &data).unwrap()
+    );
+}
+
+#[test]
+fn mac_comparison_checks_length() {
+    let mac = Mac {
+        mac_value: vec![1, 2, 3, 4].into(),
+    };
+    let truncated = Mac {
+        mac_value: vec![1, 2].into(),
+    };
+    let empty = Mac {
+        mac_value: Vec::new().into(),
+    };
+
+    assert_eq!(mac, mac.clone());
+    assert_ne!(mac, truncated);
+    assert_ne!(truncated, mac);
+    assert_ne!(mac, empty);
+    assert_ne!(empty, mac);
+
+    let mut flipped = mac.clone();
+    flipped.flip_last_byte();
+    assert_ne!(mac, flipped);
+}
@@ -3,6 +3,7 @@
 //! This module exposes [`CredentialError`].
 
 use crate::error::LibraryError;
+use openmls_traits::types::CryptoError;
 use thiserror::Error;
 
 /// An error that occurs in methods of a [`super::Credential`].
@@ -17,6 +18,15 @@ pub enum CredentialError {
     /// Verifying the signature with this credential failed.
     #[error("Invalid signature.")]
     InvalidSignature,
+    /// The certificate chain of the credential is invalid.
+    #[error("Invalid certificate chain.")]
+    InvalidCertificateChain,
+    /// The signature key doesn't match the key of the end-entity certificate.
+    #[error("The signature key doesn't match the key of the end-entity certificate.")]
+    SignatureKeyMismatch,
+    /// The certificate validator could not validate the certificate chain.
+    #[error("The certificate chain could not be validated: {0}")]
+    CertificateValidationFailed(CryptoError),
 }
 
 /// An error that occurs in methods of a [`super::Credential`].
@@ -29,3 +39,17 @@ pub enum BasicCredentialError {
     #[error("Wrong credential type.")]
     WrongCredentialType,
 }
+
+/// An error that occurs in methods of a [`super::X509Credential`].
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum X509CredentialError {
+    /// TLS codec error
+    #[error(transparent)]
+    TlsCodecError(#[from] tls_codec::Error),
+    /// Wrong credential type
+    #[error("Wrong credential type.")]
+    WrongCredentialType,
+    /// The certificate chain is empty.
+    #[error("The certificate chain is empty.")]
+    EmptyCertificateChain,
+}
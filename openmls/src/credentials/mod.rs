@@ -19,11 +19,19 @@
 //! When receiving a credential update from another member, applications must
 //! query the Authentication Service to ensure that the new credential is valid.
 //!
-//! There are multiple [`CredentialType`]s, although OpenMLS currently only
-//! supports the [`BasicCredential`].
+//! There are multiple [`CredentialType`]s. OpenMLS supports the
+//! [`BasicCredential`] and the [`X509Credential`]. The certificate chain of an
+//! [`X509Credential`] is validated with the
+//! [`CertificateValidator`](openmls_traits::crypto::CertificateValidator) of
+//! the crypto provider if there is one, and the signature key of the leaf must
+//! be the key of the end-entity certificate.
 
 use std::io::{Read, Write};
 
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    types::{CryptoError, SignatureScheme},
+};
 use serde::{Deserialize, Serialize};
 use tls_codec::{
     Deserialize as TlsDeserializeTrait, DeserializeBytes, Error, Serialize as TlsSerializeTrait,
@@ -76,7 +84,7 @@ pub mod errors;
 pub enum CredentialType {
     /// A [`BasicCredential`]
     Basic = 1,
-    /// An X.509 [`Certificate`]
+    /// An [`X509Credential`]
     X509 = 2,
     /// Another type of credential that is not in the MLS protocol spec.
     Other(u16),
@@ -115,8 +123,7 @@ impl DeserializeBytes for CredentialType {
     {
         let mut bytes_ref = bytes;
         let credential_type = CredentialType::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[credential_type.tls_serialized_len()..];
-        Ok((credential_type, remainder))
+        Ok((credential_type, bytes_ref))
     }
 }
 
@@ -142,17 +149,42 @@ impl From<CredentialType> for u16 {
 
 /// X.509 Certificate.
 ///
-/// This struct contains an X.509 certificate chain.  Note that X.509
-/// certificates are not yet supported by OpenMLS.
+/// This struct contains a single DER encoded X.509 certificate of the chain in
+/// an [`X509Credential`].
 ///
 /// ```c
 /// struct {
 ///     opaque cert_data<V>;
 /// } Certificate;
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Serialize,
+    Deserialize,
+    TlsSize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsDeserializeBytes,
+)]
 pub struct Certificate {
-    cert_data: Vec<u8>,
+    cert_data: VLBytes,
+}
+
+impl Certificate {
+    /// Create a new certificate from its DER encoding.
+    pub fn new(cert_data: Vec<u8>) -> Self {
+        Self {
+            cert_data: cert_data.into(),
+        }
+    }
+
+    /// Get the DER encoding of this certificate.
+    pub fn cert_data(&self) -> &[u8] {
+        self.cert_data.as_slice()
+    }
 }
 
 /// Credential.
@@ -164,7 +196,9 @@ pub struct Certificate {
 /// OpenMLS provides an implementation of the [`BasicCredential`].
 ///
 /// This struct contains MLS credential data, where the data depends on the
-/// type.
+/// type. Credentials of types unknown to OpenMLS are kept as
+/// [`CredentialType::Other`] together with their opaque content, so that they
+/// are serialized again exactly as they were received.
 ///
 /// **Note:** While the credential is opaque to OpenMLS, the library must know how
 ///           to deserialize it. The implementation only works with credentials
@@ -230,6 +264,28 @@ impl Credential {
     ) -> Result<T, tls_codec::Error> {
         T::tls_deserialize_exact(&self.serialized_credential_content)
     }
+
+    /// Validate this credential for use with the given signature key.
+    ///
+    /// The certificate chain of an [`X509Credential`] is validated with the
+    /// crypto provider's
+    /// [`CertificateValidator`](openmls_traits::crypto::CertificateValidator)
+    /// and the key of its end-entity certificate must be `signature_key`. If
+    /// the provider has no validator, X.509 credentials are rejected. Other
+    /// credentials are not checked.
+    pub(crate) fn validate(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        signature_scheme: SignatureScheme,
+        signature_key: &[u8],
+    ) -> Result<(), CredentialError> {
+        match self.credential_type {
+            CredentialType::X509 => X509Credential::try_from(self.clone())
+                .map_err(|_| CredentialError::InvalidCertificateChain)?
+                .validate(crypto, signature_scheme, signature_key),
+            CredentialType::Basic | CredentialType::Other(_) => Ok(()),
+        }
+    }
 }
 
 /// Basic Credential.
@@ -288,6 +344,104 @@ impl TryFrom<Credential> for BasicCredential {
     }
 }
 
+/// X.509 Credential.
+///
+/// An `X509Credential` as defined in the MLS protocol spec. It contains a chain
+/// of X.509 [`Certificate`]s, starting with the end-entity certificate, whose
+/// public key is the signature key of the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct X509Credential {
+    certificates: Vec<Certificate>,
+}
+
+impl X509Credential {
+    /// Create a new X.509 credential from a certificate chain, starting with
+    /// the end-entity certificate.
+    ///
+    /// Errors
+    ///
+    /// Returns an [`X509CredentialError::EmptyCertificateChain`] if
+    /// `certificates` is empty.
+    pub fn new(certificates: Vec<Certificate>) -> Result<Self, X509CredentialError> {
+        if certificates.is_empty() {
+            return Err(X509CredentialError::EmptyCertificateChain);
+        }
+        Ok(Self { certificates })
+    }
+
+    /// Get the certificate chain of this credential.
+    pub fn certificates(&self) -> &[Certificate] {
+        &self.certificates
+    }
+
+    /// Get the end-entity certificate of this credential.
+    pub fn end_entity_certificate(&self) -> &Certificate {
+        // The chain is never empty.
+        &self.certificates[0]
+    }
+
+    fn validate(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        signature_scheme: SignatureScheme,
+        signature_key: &[u8],
+    ) -> Result<(), CredentialError> {
+        // Without a validator, the chain can't be trusted.
+        let validator = crypto
+            .certificate_validator()
+            .ok_or(CredentialError::UnsupportedCredentialType)?;
+        let chain: Vec<&[u8]> = self
+            .certificates
+            .iter()
+            .map(Certificate::cert_data)
+            .collect();
+        let end_entity_key = validator
+            .validate_certificate_chain(&chain, signature_scheme)
+            .map_err(|e| match e {
+                CryptoError::InvalidCertificate => CredentialError::InvalidCertificateChain,
+                e => CredentialError::CertificateValidationFailed(e),
+            })?;
+        if end_entity_key != signature_key {
+            return Err(CredentialError::SignatureKeyMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<X509Credential> for Credential {
+    type Error = tls_codec::Error;
+
+    fn try_from(credential: X509Credential) -> Result<Self, Self::Error> {
+        let mut serialized_chain = Vec::new();
+        for certificate in credential.certificates.iter() {
+            certificate.tls_serialize(&mut serialized_chain)?;
+        }
+        Ok(Credential {
+            credential_type: CredentialType::X509,
+            serialized_credential_content: serialized_chain.into(),
+        })
+    }
+}
+
+impl TryFrom<Credential> for X509Credential {
+    type Error = X509CredentialError;
+
+    fn try_from(credential: Credential) -> Result<Self, Self::Error> {
+        if credential.credential_type != CredentialType::X509 {
+            return Err(X509CredentialError::WrongCredentialType);
+        }
+        let mut serialized_chain = credential.serialized_content();
+        let mut certificates = Vec::new();
+        while !serialized_chain.is_empty() {
+            let (certificate, remainder) = Certificate::tls_deserialize_bytes(serialized_chain)?;
+            certificates.push(certificate);
+            serialized_chain = remainder;
+        }
+        X509Credential::new(certificates)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// A wrapper around a credential with a corresponding public key.
 pub struct CredentialWithKey {
@@ -364,7 +518,17 @@ mod unit_tests {
         DeserializeBytes, Serialize, TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSize,
     };
 
-    use super::{BasicCredential, Credential, CredentialType};
+    use openmls_test_crypto::OpenMlsTestCrypto;
+    use openmls_traits::{
+        crypto::CertificateValidator,
+        types::{CryptoError, SignatureScheme},
+        OpenMlsProvider,
+    };
+
+    use super::{
+        errors::{CredentialError, X509CredentialError},
+        BasicCredential, Certificate, Credential, CredentialType, X509Credential,
+    };
 
     #[test]
     fn basic_credential_identity_and_codec() {
@@ -425,4 +589,159 @@ mod unit_tests {
 
         assert_eq!(custom_credential, deserialized_custom_credential);
     }
+
+    /// Test that credentials of unknown types are passed through unchanged.
+    #[test]
+    fn unknown_credential_type_passthrough() {
+        // credential_type = 0xF0F0, content = [0xde, 0xad, 0xbe, 0xef]
+        let serialized = [0xF0, 0xF0, 0x04, 0xde, 0xad, 0xbe, 0xef];
+
+        let credential = Credential::tls_deserialize_exact_bytes(&serialized).unwrap();
+        assert_eq!(credential.credential_type(), CredentialType::Other(0xF0F0));
+        assert_eq!(credential.serialized_content(), &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(credential.tls_serialize_detached().unwrap(), serialized);
+    }
+
+    #[test]
+    fn x509_credential_codec() {
+        let certificates = vec![
+            Certificate::new(b"end-entity certificate".to_vec()),
+            Certificate::new(b"intermediate certificate".to_vec()),
+        ];
+        let x509_credential = X509Credential::new(certificates.clone()).unwrap();
+        assert_eq!(x509_credential.certificates(), certificates.as_slice());
+        assert_eq!(x509_credential.end_entity_certificate(), &certificates[0]);
+
+        // Test the encoding and decoding.
+        let credential = Credential::try_from(x509_credential.clone()).unwrap();
+        assert_eq!(credential.credential_type(), CredentialType::X509);
+        let serialized = credential.tls_serialize_detached().unwrap();
+        let deserialized = Credential::tls_deserialize_exact_bytes(&serialized).unwrap();
+        assert_eq!(credential, deserialized);
+        assert_eq!(
+            X509Credential::try_from(deserialized).unwrap(),
+            x509_credential
+        );
+
+        // An empty chain is invalid.
+        assert_eq!(
+            X509Credential::new(vec![]),
+            Err(X509CredentialError::EmptyCertificateChain)
+        );
+        assert_eq!(
+            X509Credential::try_from(Credential::new(CredentialType::X509, vec![])),
+            Err(X509CredentialError::EmptyCertificateChain)
+        );
+        // Other credential types can't be converted.
+        assert_eq!(
+            X509Credential::try_from(Credential::from(BasicCredential::new(b"id".to_vec()))),
+            Err(X509CredentialError::WrongCredentialType)
+        );
+    }
+
+    /// Accepts the chains whose end-entity certificate is `valid:<key>` and
+    /// returns the key.
+    struct TestValidator;
+
+    impl CertificateValidator for TestValidator {
+        fn validate_certificate_chain(
+            &self,
+            chain: &[&[u8]],
+            signature_scheme: SignatureScheme,
+        ) -> Result<Vec<u8>, CryptoError> {
+            if signature_scheme != SignatureScheme::ED25519 {
+                return Err(CryptoError::UnsupportedSignatureScheme);
+            }
+            match chain
+                .first()
+                .and_then(|end_entity| end_entity.strip_prefix(b"valid:"))
+            {
+                Some(key) => Ok(key.to_vec()),
+                None => Err(CryptoError::InvalidCertificate),
+            }
+        }
+    }
+
+    #[test]
+    fn x509_credential_validation() {
+        let credential = |end_entity: &[u8]| {
+            Credential::try_from(
+                X509Credential::new(vec![
+                    Certificate::new(end_entity.to_vec()),
+                    Certificate::new(b"root certificate".to_vec()),
+                ])
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        let provider = OpenMlsTestCrypto::default().with_certificate_validator(TestValidator);
+        let crypto = provider.crypto();
+
+        // A valid chain for the signature key is accepted.
+        assert_eq!(
+            credential(b"valid:key").validate(crypto, SignatureScheme::ED25519, b"key"),
+            Ok(())
+        );
+        // A valid chain for another key is rejected.
+        assert_eq!(
+            credential(b"valid:key").validate(crypto, SignatureScheme::ED25519, b"other key"),
+            Err(CredentialError::SignatureKeyMismatch)
+        );
+        // An invalid chain is rejected.
+        assert_eq!(
+            credential(b"invalid").validate(crypto, SignatureScheme::ED25519, b"key"),
+            Err(CredentialError::InvalidCertificateChain)
+        );
+        // A chain that can't be validated is rejected with the error of the
+        // validator.
+        assert_eq!(
+            credential(b"valid:key").validate(
+                crypto,
+                SignatureScheme::ECDSA_SECP256R1_SHA256,
+                b"key"
+            ),
+            Err(CredentialError::CertificateValidationFailed(
+                CryptoError::UnsupportedSignatureScheme
+            ))
+        );
+        // An empty chain is rejected.
+        assert_eq!(
+            Credential::new(CredentialType::X509, vec![]).validate(
+                crypto,
+                SignatureScheme::ED25519,
+                b"key"
+            ),
+            Err(CredentialError::InvalidCertificateChain)
+        );
+        // Other credentials are not validated.
+        assert_eq!(
+            Credential::from(BasicCredential::new(b"id".to_vec())).validate(
+                crypto,
+                SignatureScheme::ED25519,
+                b"key"
+            ),
+            Ok(())
+        );
+
+        // Without a validator, X.509 credentials are rejected, even if the
+        // chain would be valid.
+        let provider = OpenMlsTestCrypto::default();
+        assert_eq!(
+            credential(b"valid:key").validate(provider.crypto(), SignatureScheme::ED25519, b"key"),
+            Err(CredentialError::UnsupportedCredentialType)
+        );
+        assert_eq!(
+            credential(b"invalid").validate(provider.crypto(), SignatureScheme::ED25519, b"key"),
+            Err(CredentialError::UnsupportedCredentialType)
+        );
+        // Basic credentials don't need a validator.
+        assert_eq!(
+            Credential::from(BasicCredential::new(b"id".to_vec())).validate(
+                provider.crypto(),
+                SignatureScheme::ED25519,
+                b"key"
+            ),
+            Ok(())
+        );
+    }
 }
@@ -0,0 +1,159 @@
+//! # Application-defined extensions
+//!
+//! Extensions of types that OpenMLS doesn't know are carried as
+//! [`UnknownExtension`]s. The [`ApplicationExtension`] trait allows
+//! applications to define typed values for their own extension types, which
+//! can be put into and read from [`Extensions`] in key packages, leaf nodes and
+//! the group context.
+//!
+//! The [`ExtensionRegistry`] collects the application extensions an
+//! application supports, such that all of them can be parsed and validated at
+//! once. A group validates the registered extensions in the key packages and
+//! leaf nodes of proposals and commits, and in group context extensions
+//! proposals, once the registry is set with
+//! [`MlsGroup::set_extension_registry()`](crate::group::MlsGroup::set_extension_registry).
+//!
+//! Note that application extension types have to be listed in the
+//! [`Capabilities`](crate::prelude::Capabilities) of all
+//! members that use them, like any other extension type that is not a default
+//! extension.
+
+use std::collections::BTreeMap;
+
+use tls_codec::{DeserializeBytes, Serialize};
+
+use super::{
+    errors::ApplicationExtensionError, Extension, ExtensionType, Extensions, UnknownExtension,
+};
+use crate::error::LibraryError;
+
+/// An extension type defined by the application.
+///
+/// The type is (de)serialized using its TLS codec implementation and stored in
+/// an [`Extension::Unknown`] with [`ApplicationExtension::EXTENSION_TYPE`].
+pub trait ApplicationExtension: Serialize + DeserializeBytes + Sized {
+    /// The extension type id. This must not be an extension type known to
+    /// OpenMLS. Private use extension types are in the range
+    /// `0xff00 - 0xffff`.
+    const EXTENSION_TYPE: u16;
+
+    /// Validate the extension after it was deserialized. The default
+    /// implementation accepts all values.
+    fn validate(&self) -> Result<(), ApplicationExtensionError> {
+        Ok(())
+    }
+
+    /// Serialize the extension into an [`Extension`].
+    fn to_extension(&self) -> Result<Extension, ApplicationExtensionError> {
+        if !matches!(
+            ExtensionType::from(Self::EXTENSION_TYPE),
+            ExtensionType::Unknown(_)
+        ) {
+            return Err(ApplicationExtensionError::KnownExtensionType);
+        }
+        let bytes = self
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+
+        Ok(Extension::Unknown(
+            Self::EXTENSION_TYPE,
+            UnknownExtension(bytes),
+        ))
+    }
+
+    /// Deserialize and validate the extension from the raw extension data.
+    fn from_extension_data(data: &[u8]) -> Result<Self, ApplicationExtensionError> {
+        let extension = Self::tls_deserialize_exact_bytes(data)
+            .map_err(|_| ApplicationExtensionError::Malformed)?;
+        extension.validate()?;
+
+        Ok(extension)
+    }
+}
+
+impl Extensions {
+    /// Get the [`ApplicationExtension`] of type `E`, if there is any.
+    ///
+    /// Returns an error if the extension is malformed or invalid.
+    pub fn application_extension<E: ApplicationExtension>(
+        &self,
+    ) -> Result<Option<E>, ApplicationExtensionError> {
+        self.unknown(E::EXTENSION_TYPE)
+            .map(|extension| E::from_extension_data(&extension.0))
+            .transpose()
+    }
+}
+
+type ValidationHook = fn(&[u8]) -> Result<(), ApplicationExtensionError>;
+
+fn validation_hook<E: ApplicationExtension>(data: &[u8]) -> Result<(), ApplicationExtensionError> {
+    E::from_extension_data(data).map(|_| ())
+}
+
+/// A registry of the [`ApplicationExtension`]s supported by an application.
+///
+/// ```
+/// use openmls::prelude::*;
+/// use tls_codec::{TlsDeserializeBytes, TlsSerialize, TlsSize};
+///
+/// #[derive(TlsSerialize, TlsDeserializeBytes, TlsSize)]
+/// struct ChatTopic {
+///     topic: tls_codec::VLBytes,
+/// }
+///
+/// impl ApplicationExtension for ChatTopic {
+///     const EXTENSION_TYPE: u16 = 0xff01;
+/// }
+///
+/// let registry = ExtensionRegistry::new().register::<ChatTopic>();
+/// let topic = ChatTopic {
+///     topic: b"Lunch".to_vec().into(),
+/// };
+/// let extensions = Extensions::single(topic.to_extension().unwrap());
+/// registry.validate(&extensions).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(any(test, feature = "test-utils"), derive(PartialEq))]
+pub struct ExtensionRegistry {
+    hooks: BTreeMap<u16, ValidationHook>,
+}
+
+impl ExtensionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the application extension `E`. An extension that was
+    /// registered before with the same extension type is replaced.
+    pub fn register<E: ApplicationExtension>(mut self) -> Self {
+        self.hooks
+            .insert(E::EXTENSION_TYPE, validation_hook::<E> as ValidationHook);
+        self
+    }
+
+    /// Returns `true` if an application extension with the given extension
+    /// type is registered.
+    pub fn is_registered(&self, extension_type: u16) -> bool {
+        self.hooks.contains_key(&extension_type)
+    }
+
+    /// Returns the extension types of all registered application extensions.
+    pub fn extension_types(&self) -> impl Iterator<Item = ExtensionType> + '_ {
+        self.hooks
+            .keys()
+            .map(|&extension_type| extension_type.into())
+    }
+
+    /// Parse and validate all registered application extensions in
+    /// `extensions`. Unknown extensions that are not registered are ignored.
+    pub fn validate(&self, extensions: &Extensions) -> Result<(), ApplicationExtensionError> {
+        for (extension_type, extension) in extensions.unknown_extensions() {
+            if let Some(hook) = self.hooks.get(&extension_type) {
+                hook(&extension.0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
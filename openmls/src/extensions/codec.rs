@@ -132,7 +132,6 @@ impl DeserializeBytes for Extension {
     {
         let mut bytes_ref = bytes;
         let extension = Extension::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[extension.tls_serialized_len()..];
-        Ok((extension, remainder))
+        Ok((extension, bytes_ref))
     }
 }
@@ -0,0 +1,84 @@
+//! # Compressed ratchet tree extension
+//!
+//! **This extension is not part of the MLS specification.**
+//!
+//! The [`CompressedRatchetTreeExtension`] carries a DEFLATE-compressed
+//! [`RatchetTreeExtension`](super::RatchetTreeExtension) in an extension of a
+//! private use extension type. It allows deployments whose delivery service
+//! can't carry the Welcomes of large groups to still include the ratchet tree
+//! in them. Only clients built with the `ratchet-tree-compression` feature
+//! understand the extension, so it should only be used if all clients of a
+//! deployment do.
+
+use std::io::{Read, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use tls_codec::{
+    DeserializeBytes as _, Serialize as _, TlsDeserializeBytes, TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{errors::RatchetTreeError, ApplicationExtension};
+use crate::{
+    error::LibraryError,
+    treesync::{RatchetTree, RatchetTreeIn},
+};
+
+/// The private use extension type of the [`CompressedRatchetTreeExtension`].
+pub const COMPRESSED_RATCHET_TREE_EXTENSION_TYPE: u16 = 0xffa0;
+
+/// The maximum size (in bytes) of a decompressed ratchet tree. Larger ratchet
+/// trees are rejected to limit the memory used when decompressing.
+const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// # Compressed Ratchet Tree Extension.
+///
+/// A non-standard extension that contains the DEFLATE-compressed
+/// serialization of a ratchet tree.
+///
+/// ```c
+/// opaque compressed_ratchet_tree<V>;
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, TlsSerialize, TlsDeserializeBytes, TlsSize)]
+pub struct CompressedRatchetTreeExtension {
+    compressed_ratchet_tree: VLBytes,
+}
+
+impl CompressedRatchetTreeExtension {
+    /// Build a new extension by compressing the given [`RatchetTree`].
+    pub fn new(ratchet_tree: &RatchetTree) -> Result<Self, LibraryError> {
+        let serialized = ratchet_tree
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&serialized)
+            .map_err(|_| LibraryError::custom("Compressing the ratchet tree failed"))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|_| LibraryError::custom("Compressing the ratchet tree failed"))?;
+
+        Ok(Self {
+            compressed_ratchet_tree: compressed.into(),
+        })
+    }
+
+    /// Decompress the [`RatchetTreeIn`] from this extension.
+    pub fn ratchet_tree(&self) -> Result<RatchetTreeIn, RatchetTreeError> {
+        let mut serialized = Vec::new();
+        DeflateDecoder::new(self.compressed_ratchet_tree.as_slice())
+            .take(MAX_DECOMPRESSED_SIZE + 1)
+            .read_to_end(&mut serialized)
+            .map_err(|_| RatchetTreeError::Invalid)?;
+        if serialized.len() as u64 > MAX_DECOMPRESSED_SIZE {
+            return Err(RatchetTreeError::Invalid);
+        }
+
+        RatchetTreeIn::tls_deserialize_exact_bytes(&serialized)
+            .map_err(|_| RatchetTreeError::Invalid)
+    }
+}
+
+impl ApplicationExtension for CompressedRatchetTreeExtension {
+    const EXTENSION_TYPE: u16 = COMPRESSED_RATCHET_TREE_EXTENSION_TYPE;
+}
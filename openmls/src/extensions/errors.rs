@@ -103,3 +103,52 @@ pub enum InvalidExtensionError {
     )]
     IllegalInLeafNodes,
 }
+
+/// Role map error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum RoleMapError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The extension type is not an application-defined extension type.
+    #[error("The extension type is not an application-defined extension type.")]
+    InvalidExtensionType,
+    /// The role map is malformed.
+    #[error("The role map is malformed.")]
+    Malformed,
+    /// The role map belongs to a different group.
+    #[error("The role map belongs to a different group.")]
+    WrongGroup,
+    /// The version of the role map is not larger than that of the current role map.
+    #[error("The version of the role map is not larger than that of the current role map.")]
+    OutdatedVersion,
+    /// The role map is not signed by an admin or by the member that may set the
+    /// first role map.
+    #[error(
+        "The role map is not signed by an admin or by the member that may set the first role map."
+    )]
+    UnauthorizedSigner,
+    /// The signature of the role map is invalid.
+    #[error("The signature of the role map is invalid.")]
+    InvalidSignature,
+    /// Signing the role map failed.
+    #[error("Signing the role map failed.")]
+    SigningError,
+}
+
+/// Application extension error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ApplicationExtensionError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The extension type of the application extension is known to OpenMLS.
+    #[error("The extension type of the application extension is known to OpenMLS.")]
+    KnownExtensionType,
+    /// The application extension is malformed.
+    #[error("The application extension is malformed.")]
+    Malformed,
+    /// The application extension is invalid.
+    #[error("The application extension is invalid: {0}")]
+    Invalid(ErrorString),
+}
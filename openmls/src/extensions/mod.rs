@@ -18,6 +18,15 @@
 //! - [`RatchetTreeExtension`] (GroupInfo extension)
 //! - [`RequiredCapabilitiesExtension`] (GroupContext extension)
 //! - [`ExternalPubExtension`] (GroupInfo extension)
+//!
+//! With the `ratchet-tree-compression` feature, OpenMLS also supports the
+//! non-standard `CompressedRatchetTreeExtension` (GroupInfo extension).
+//!
+//! Applications can define their own extensions with the
+//! [`ApplicationExtension`] trait.
+//!
+//! In addition, the [`RoleMap`] can be carried in an application-defined
+//! GroupContext extension to assign roles to group members.
 
 use std::{
     fmt::Debug,
@@ -26,21 +35,32 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use crate::treesync::RatchetTreeIn;
+
 // Private
+mod application_extension;
 mod application_id_extension;
 mod codec;
+#[cfg(feature = "ratchet-tree-compression")]
+mod compressed_ratchet_tree_extension;
 mod external_pub_extension;
 mod external_sender_extension;
 mod last_resort;
 mod ratchet_tree_extension;
 mod required_capabilities;
+mod role_map;
 use errors::*;
 
 // Public
 pub mod errors;
 
 // Public re-exports
+pub use application_extension::{ApplicationExtension, ExtensionRegistry};
 pub use application_id_extension::ApplicationIdExtension;
+#[cfg(feature = "ratchet-tree-compression")]
+pub use compressed_ratchet_tree_extension::{
+    CompressedRatchetTreeExtension, COMPRESSED_RATCHET_TREE_EXTENSION_TYPE,
+};
 pub use external_pub_extension::ExternalPubExtension;
 pub use external_sender_extension::{
     ExternalSender, ExternalSendersExtension, SenderExtensionIndex,
@@ -48,6 +68,7 @@ pub use external_sender_extension::{
 pub use last_resort::LastResortExtension;
 pub use ratchet_tree_extension::RatchetTreeExtension;
 pub use required_capabilities::RequiredCapabilitiesExtension;
+pub use role_map::{Role, RoleAssignment, RoleMap, VerifiableRoleMap};
 use tls_codec::{
     Deserialize as TlsDeserializeTrait, DeserializeBytes, Error, Serialize as TlsSerializeTrait,
     Size, TlsSize,
@@ -117,7 +138,33 @@ impl ExtensionType {
     /// Returns whether an extension type is valid when used in leaf nodes.
     /// Returns None if validity can not be determined.
     /// This is the case for unknown extensions.
+    ///
+    /// The valid message types of the extensions are listed in the "MLS
+    /// Extension Types" registry of RFC 9420 (Section 17.3): the application id
+    /// is an extension of leaf nodes ("LN"), while the last resort extension of
+    /// [draft-ietf-mls-extensions] is an extension of key packages ("KP") and
+    /// not of their leaf nodes.
+    ///
+    /// [draft-ietf-mls-extensions]: https://datatracker.ietf.org/doc/draft-ietf-mls-extensions/
     pub(crate) fn is_valid_in_leaf_node(self) -> Option<bool> {
+        match self {
+            ExtensionType::LastResort
+            | ExtensionType::RatchetTree
+            | ExtensionType::RequiredCapabilities
+            | ExtensionType::ExternalPub
+            | ExtensionType::ExternalSenders => Some(false),
+            ExtensionType::ApplicationId => Some(true),
+            ExtensionType::Unknown(_) => None,
+        }
+    }
+
+    /// Returns whether an extension type is valid when used in key packages.
+    /// Returns None if validity can not be determined.
+    /// This is the case for unknown extensions.
+    ///
+    /// See [`is_valid_in_leaf_node()`](Self::is_valid_in_leaf_node) for the
+    /// extensions of the leaf node of a key package.
+    pub(crate) fn is_valid_in_key_package(self) -> Option<bool> {
         match self {
             ExtensionType::ApplicationId
             | ExtensionType::RatchetTree
@@ -155,8 +202,7 @@ impl DeserializeBytes for ExtensionType {
     {
         let mut bytes_ref = bytes;
         let extension_type = ExtensionType::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[extension_type.tls_serialized_len()..];
-        Ok((extension_type, remainder))
+        Ok((extension_type, bytes_ref))
     }
 }
 
@@ -235,10 +281,23 @@ pub enum Extension {
 }
 
 /// A unknown/unparsed extension represented by raw bytes.
+///
+/// Extensions of types that OpenMLS doesn't know are not rejected during
+/// deserialization. They are kept as opaque bytes and serialized again
+/// unchanged, e.g. when they are part of a signed or hashed struct. Use
+/// [`Extensions::unknown()`] to get them by their type id.
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct UnknownExtension(pub Vec<u8>);
 
 /// A list of extensions with unique extension types.
+///
+/// The extension types are compared by their value on the wire, i.e. an
+/// [`Extension::Unknown`] with the value of a known extension type conflicts
+/// with an extension of that type. Lists with duplicate extension types are
+/// rejected when they are built and when they are deserialized, e.g. as part
+/// of a key package, a leaf node or a group context.
+///
+/// The order of the extensions is preserved.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSize)]
 pub struct Extensions {
     unique: Vec<Extension>,
@@ -256,8 +315,7 @@ impl TlsDeserializeTrait for Extensions {
         Self: Sized,
     {
         let candidate: Vec<Extension> = Vec::tls_deserialize(bytes)?;
-        Extensions::try_from(candidate)
-            .map_err(|_| Error::DecodingError("Found duplicate extensions".into()))
+        Extensions::try_from(candidate).map_err(|e| Error::DecodingError(e.to_string()))
     }
 }
 
@@ -268,8 +326,7 @@ impl DeserializeBytes for Extensions {
     {
         let mut bytes_ref = bytes;
         let extensions = Extensions::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[extensions.tls_serialized_len()..];
-        Ok((extensions, remainder))
+        Ok((extensions, bytes_ref))
     }
 }
 
@@ -315,11 +372,20 @@ impl Extensions {
 
     /// Add an extension to the extension list (or replace an existing one.)
     ///
-    /// Returns the replaced extension (if any).
+    /// An existing extension is replaced in place, such that the order of the
+    /// extensions is preserved. Returns the replaced extension (if any).
     pub fn add_or_replace(&mut self, extension: Extension) -> Option<Extension> {
-        let replaced = self.remove(extension.extension_type());
-        self.unique.push(extension);
-        replaced
+        match self
+            .unique
+            .iter_mut()
+            .find(|ext| same_type(ext.extension_type(), extension.extension_type()))
+        {
+            Some(existing) => Some(std::mem::replace(existing, extension)),
+            None => {
+                self.unique.push(extension);
+                None
+            }
+        }
     }
 
     /// Remove an extension from the extension list.
@@ -330,7 +396,7 @@ impl Extensions {
         if let Some(pos) = self
             .unique
             .iter()
-            .position(|ext| ext.extension_type() == extension_type)
+            .position(|ext| same_type(ext.extension_type(), extension_type))
         {
             Some(self.unique.remove(pos))
         } else {
@@ -343,10 +409,15 @@ impl Extensions {
     pub fn contains(&self, extension_type: ExtensionType) -> bool {
         self.unique
             .iter()
-            .any(|ext| ext.extension_type() == extension_type)
+            .any(|ext| same_type(ext.extension_type(), extension_type))
     }
 }
 
+/// Returns `true` if both extension types have the same value on the wire.
+fn same_type(a: ExtensionType, b: ExtensionType) -> bool {
+    u16::from(a) == u16::from(b)
+}
+
 impl TryFrom<Vec<Extension>> for Extensions {
     type Error = InvalidExtensionError;
 
@@ -356,7 +427,7 @@ impl TryFrom<Vec<Extension>> for Extensions {
         for extension in candidate.into_iter() {
             if unique
                 .iter()
-                .any(|ext| ext.extension_type() == extension.extension_type())
+                .any(|ext| same_type(ext.extension_type(), extension.extension_type()))
             {
                 return Err(InvalidExtensionError::Duplicate);
             } else {
@@ -433,6 +504,34 @@ impl Extensions {
             _ => None,
         }
     }
+
+    /// Returns an iterator over all [`UnknownExtension`]s together with their
+    /// extension type ids.
+    pub fn unknown_extensions(&self) -> impl Iterator<Item = (u16, &UnknownExtension)> {
+        self.unique.iter().filter_map(|e| match e {
+            Extension::Unknown(extension_type_id, e) => Some((*extension_type_id, e)),
+            _ => None,
+        })
+    }
+
+    /// Returns the ratchet tree of the [`RatchetTreeExtension`] or, with the
+    /// `ratchet-tree-compression` feature, of the
+    /// `CompressedRatchetTreeExtension`, if there is any.
+    pub(crate) fn ratchet_tree_in(&self) -> Result<Option<RatchetTreeIn>, RatchetTreeError> {
+        if let Some(extension) = self.ratchet_tree() {
+            return Ok(Some(extension.ratchet_tree().clone()));
+        }
+
+        #[cfg(feature = "ratchet-tree-compression")]
+        if let Some(extension) = self
+            .application_extension::<CompressedRatchetTreeExtension>()
+            .map_err(|_| RatchetTreeError::Invalid)?
+        {
+            return extension.ratchet_tree().map(Some);
+        }
+
+        Ok(None)
+    }
 }
 
 impl Extension {
@@ -612,6 +711,49 @@ mod test {
         }
     }
 
+    #[test]
+    fn that_extension_types_are_unique_on_the_wire() {
+        let application_id = Extension::ApplicationId(ApplicationIdExtension::new(b"Test"));
+        let required_capabilities =
+            Extension::RequiredCapabilities(RequiredCapabilitiesExtension::default());
+        // An unknown extension with the extension type of the application id.
+        let unknown = Extension::Unknown(0x0001, UnknownExtension(vec![0]));
+
+        assert_eq!(
+            Extensions::from_vec(vec![application_id.clone(), unknown.clone()]),
+            Err(InvalidExtensionError::Duplicate)
+        );
+        let mut extensions = Extensions::single(application_id.clone());
+        assert_eq!(
+            extensions.add(unknown.clone()),
+            Err(InvalidExtensionError::Duplicate)
+        );
+        assert!(extensions.contains(ExtensionType::Unknown(0x0001)));
+
+        // Replacing an extension keeps the order of the extensions.
+        extensions.add(required_capabilities.clone()).unwrap();
+        assert_eq!(
+            extensions.add_or_replace(unknown.clone()),
+            Some(application_id.clone())
+        );
+        assert_eq!(
+            extensions.iter().cloned().collect::<Vec<_>>(),
+            vec![unknown, required_capabilities]
+        );
+
+        // Lists with duplicate extension types are rejected when they are
+        // deserialized.
+        let bytes = vec![application_id.clone(), application_id]
+            .tls_serialize_detached()
+            .unwrap();
+        assert_eq!(
+            Extensions::tls_deserialize_exact(bytes),
+            Err(tls_codec::Error::DecodingError(
+                InvalidExtensionError::Duplicate.to_string()
+            ))
+        );
+    }
+
     #[test]
     fn that_unknown_extensions_are_de_serialized_correctly() {
         let extension_types = [0x0000u16, 0x0A0A, 0x7A7A, 0xF100, 0xFFFF];
@@ -647,4 +789,28 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn that_unknown_extensions_are_preserved_in_extension_lists() {
+        let extensions = Extensions::from_vec(vec![
+            Extension::ApplicationId(ApplicationIdExtension::new(b"app")),
+            Extension::Unknown(0xF100, UnknownExtension(vec![1, 2, 3])),
+            Extension::Unknown(0xF101, UnknownExtension(vec![])),
+        ])
+        .unwrap();
+
+        let bytes = extensions.tls_serialize_detached().unwrap();
+        let got = Extensions::tls_deserialize_exact(&bytes).unwrap();
+        assert_eq!(extensions, got);
+        assert_eq!(bytes, got.tls_serialize_detached().unwrap());
+
+        assert_eq!(got.unknown(0xF100), Some(&UnknownExtension(vec![1, 2, 3])));
+        assert_eq!(got.unknown(0xF102), None);
+        assert_eq!(
+            got.unknown_extensions()
+                .map(|(extension_type, _)| extension_type)
+                .collect::<Vec<_>>(),
+            vec![0xF100, 0xF101]
+        );
+    }
 }
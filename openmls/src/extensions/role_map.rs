@@ -0,0 +1,382 @@
+//! # Role map
+//!
+//! A [`RoleMap`] assigns roles, such as admin or moderator, to the members of
+//! a group, identified by their signature keys. The role map is signed by an
+//! admin and carried in an application-defined group context extension, such
+//! that all members agree on it and it can only be changed through a group
+//! context extensions proposal.
+//!
+//! OpenMLS does not enforce the role map. Applications that want to restrict
+//! operations to certain roles can do so in their commit validation:
+//!
+//! - Read the new role map from a group context extensions proposal with
+//!   [`VerifiableRoleMap::from_extensions()`] and check it with
+//!   [`VerifiableRoleMap::validate()`] against the current role map of the
+//!   group. This ensures that only admins of the current role map can change
+//!   it. The first role map of a group is checked with
+//!   [`VerifiableRoleMap::validate_first()`] against the signature key of the
+//!   member the application allows to set it, e.g. the group creator.
+//! - Look up the role of the sender of a message with [`RoleMap::role()`],
+//!   using the signature key of the
+//!   [`ProcessedMessage`](crate::framing::ProcessedMessage).
+
+use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer, types::Ciphersuite};
+use serde::{Deserialize, Serialize};
+use tls_codec::{
+    DeserializeBytes as _, Serialize as _, TlsDeserialize, TlsDeserializeBytes, TlsSerialize,
+    TlsSize,
+};
+
+use super::{errors::RoleMapError, Extension, ExtensionType, Extensions, UnknownExtension};
+use crate::{
+    ciphersuite::{
+        signable::{Signable, SignedStruct, Verifiable, VerifiedStruct},
+        OpenMlsSignaturePublicKey, Signature, SignaturePublicKey,
+    },
+    error::LibraryError,
+    group::GroupId,
+};
+
+const SIGNATURE_ROLE_MAP_LABEL: &str = "RoleMapTBS";
+
+/// The role of a member in a [`RoleMap`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsDeserializeBytes,
+    TlsSize,
+)]
+#[repr(u8)]
+pub enum Role {
+    /// An admin. Admins sign changes to the role map.
+    Admin = 1,
+    /// A moderator.
+    Moderator = 2,
+}
+
+/// The assignment of a [`Role`] to the member with the given signature key.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsDeserializeBytes,
+    TlsSize,
+)]
+pub struct RoleAssignment {
+    signature_key: SignaturePublicKey,
+    role: Role,
+}
+
+impl RoleAssignment {
+    /// Create a new [`RoleAssignment`].
+    pub fn new(signature_key: SignaturePublicKey, role: Role) -> Self {
+        Self {
+            signature_key,
+            role,
+        }
+    }
+
+    /// Returns the signature key of the member.
+    pub fn signature_key(&self) -> &SignaturePublicKey {
+        &self.signature_key
+    }
+
+    /// Returns the role of the member.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+}
+
+/// RoleMap (To Be Signed)
+///
+/// ```c
+/// struct {
+///     opaque group_id<V>;
+///     uint64 version;
+///     RoleAssignment assignments<V>;
+///     opaque signer<V>;
+/// } RoleMapTBS;
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    TlsSerialize,
+    TlsDeserialize,
+    TlsDeserializeBytes,
+    TlsSize,
+)]
+struct RoleMapTbs {
+    group_id: GroupId,
+    version: u64,
+    assignments: Vec<RoleAssignment>,
+    signer: SignaturePublicKey,
+}
+
+/// A signed role map.
+///
+/// A [`RoleMap`] is either created and signed locally with [`RoleMap::new()`]
+/// or obtained by validating a [`VerifiableRoleMap`]. A serialized role map is
+/// deserialized as [`VerifiableRoleMap`] and has to be validated again.
+#[derive(Debug, Clone, PartialEq, Eq, TlsSerialize, TlsSize, Serialize)]
+pub struct RoleMap {
+    payload: RoleMapTbs,
+    signature: Signature,
+}
+
+impl RoleMap {
+    /// Create a new role map for the group with the given `group_id` and sign
+    /// it with `signer`, the public key of which is `signature_key`.
+    ///
+    /// The `version` must be larger than that of the current role map of the
+    /// group, and the signer must be an admin in the current role map. The
+    /// first role map of a group must be signed by the member the application
+    /// allows to set it, see [`VerifiableRoleMap::validate_first()`].
+    pub fn new(
+        group_id: GroupId,
+        version: u64,
+        assignments: Vec<RoleAssignment>,
+        signer: &impl Signer,
+        signature_key: SignaturePublicKey,
+    ) -> Result<Self, RoleMapError> {
+        RoleMapTbs {
+            group_id,
+            version,
+            assignments,
+            signer: signature_key,
+        }
+        .sign(signer)
+        .map_err(|_| RoleMapError::SigningError)
+    }
+
+    /// Returns the group ID of the role map.
+    pub fn group_id(&self) -> &GroupId {
+        &self.payload.group_id
+    }
+
+    /// Returns the version of the role map.
+    pub fn version(&self) -> u64 {
+        self.payload.version
+    }
+
+    /// Returns the role assignments of the role map.
+    pub fn assignments(&self) -> &[RoleAssignment] {
+        &self.payload.assignments
+    }
+
+    /// Returns the signature key of the admin that signed the role map.
+    pub fn signer(&self) -> &SignaturePublicKey {
+        &self.payload.signer
+    }
+
+    /// Returns the role of the member with the given signature key, or `None`
+    /// if the member has no role.
+    pub fn role(&self, signature_key: &SignaturePublicKey) -> Option<Role> {
+        self.payload
+            .assignments
+            .iter()
+            .find(|assignment| &assignment.signature_key == signature_key)
+            .map(|assignment| assignment.role)
+    }
+
+    /// Returns `true` if the member with the given signature key is an admin.
+    pub fn is_admin(&self, signature_key: &SignaturePublicKey) -> bool {
+        self.role(signature_key) == Some(Role::Admin)
+    }
+
+    /// Serialize the role map into an extension with the given extension type,
+    /// e.g. to include it in a group context extensions proposal.
+    ///
+    /// Returns an error if `extension_type` is not an application-defined
+    /// extension type.
+    pub fn to_extension(&self, extension_type: u16) -> Result<Extension, RoleMapError> {
+        if !matches!(
+            ExtensionType::from(extension_type),
+            ExtensionType::Unknown(_)
+        ) {
+            return Err(RoleMapError::InvalidExtensionType);
+        }
+        let bytes = self
+            .tls_serialize_detached()
+            .map_err(LibraryError::missing_bound_check)?;
+
+        Ok(Extension::Unknown(extension_type, UnknownExtension(bytes)))
+    }
+}
+
+/// A role map of which the signature has not been verified.
+///
+/// A [`VerifiableRoleMap`] is read from the extensions of a group or a
+/// proposal with [`VerifiableRoleMap::from_extensions()`] and turned into a
+/// [`RoleMap`] with [`VerifiableRoleMap::validate()`] or
+/// [`VerifiableRoleMap::validate_first()`].
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    TlsDeserialize,
+    TlsDeserializeBytes,
+    TlsSize,
+    Serialize,
+    Deserialize,
+)]
+pub struct VerifiableRoleMap {
+    payload: RoleMapTbs,
+    signature: Signature,
+}
+
+impl VerifiableRoleMap {
+    /// Read the role map from the extension with the given type in
+    /// `extensions`, e.g. the group context extensions of a group or of a group
+    /// context extensions proposal.
+    ///
+    /// Returns `None` if there is no such extension.
+    pub fn from_extensions(
+        extensions: &Extensions,
+        extension_type: u16,
+    ) -> Result<Option<Self>, RoleMapError> {
+        extensions
+            .unknown(extension_type)
+            .map(|extension| {
+                Self::tls_deserialize_exact_bytes(&extension.0).map_err(|_| RoleMapError::Malformed)
+            })
+            .transpose()
+    }
+
+    /// Validate the role map as successor of the `current` role map of the
+    /// group with the given `group_id`.
+    ///
+    /// The role map is valid if
+    /// - it belongs to the group,
+    /// - its version is larger than that of the current role map,
+    /// - it is signed by an admin of the current role map, and
+    /// - the signature is valid.
+    pub fn validate(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        group_id: &GroupId,
+        current: &RoleMap,
+    ) -> Result<RoleMap, RoleMapError> {
+        if &self.payload.group_id != group_id {
+            return Err(RoleMapError::WrongGroup);
+        }
+        if self.payload.version <= current.version() {
+            return Err(RoleMapError::OutdatedVersion);
+        }
+        let authorized = current.is_admin(&self.payload.signer);
+        self.verify_signer(crypto, ciphersuite, authorized)
+    }
+
+    /// Validate the role map as the first role map of the group with the given
+    /// `group_id`.
+    ///
+    /// Admins of the new role map can't authorize it themselves, so the
+    /// application passes the signature key of the member that may set the
+    /// first role map, e.g. the group creator or the committer of the group
+    /// context extensions proposal if the application authorizes it.
+    ///
+    /// The role map is valid if
+    /// - it belongs to the group,
+    /// - it is signed by the member with the `authorized_signer` key, and
+    /// - the signature is valid.
+    pub fn validate_first(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        group_id: &GroupId,
+        authorized_signer: &SignaturePublicKey,
+    ) -> Result<RoleMap, RoleMapError> {
+        if &self.payload.group_id != group_id {
+            return Err(RoleMapError::WrongGroup);
+        }
+        let authorized = &self.payload.signer == authorized_signer;
+        self.verify_signer(crypto, ciphersuite, authorized)
+    }
+
+    /// Checks the signature of the role map, given whether its signer is
+    /// `authorized`.
+    fn verify_signer(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        authorized: bool,
+    ) -> Result<RoleMap, RoleMapError> {
+        if !authorized {
+            return Err(RoleMapError::UnauthorizedSigner);
+        }
+
+        let signature_key = OpenMlsSignaturePublicKey::from_signature_key(
+            self.payload.signer.clone(),
+            ciphersuite.signature_algorithm(),
+        );
+        self.verify(crypto, &signature_key)
+            .map_err(|_| RoleMapError::InvalidSignature)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl Signable for RoleMapTbs {
+    type SignedOutput = RoleMap;
+
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        self.tls_serialize_detached()
+    }
+
+    fn label(&self) -> &str {
+        SIGNATURE_ROLE_MAP_LABEL
+    }
+}
+
+impl SignedStruct<RoleMapTbs> for RoleMap {
+    fn from_payload(payload: RoleMapTbs, signature: Signature) -> Self {
+        Self { payload, signature }
+    }
+}
+
+impl Verifiable for VerifiableRoleMap {
+    type VerifiedStruct = RoleMap;
+
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        self.payload.tls_serialize_detached()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn label(&self) -> &str {
+        SIGNATURE_ROLE_MAP_LABEL
+    }
+
+    fn verify(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<Self::VerifiedStruct, crate::ciphersuite::signable::SignatureError> {
+        self.verify_no_out(crypto, pk)?;
+        Ok(RoleMap {
+            payload: self.payload,
+            signature: self.signature,
+        })
+    }
+}
+
+impl VerifiedStruct for RoleMap {}
@@ -6,6 +6,7 @@ use tls_codec::{Deserialize, Serialize};
 
 use super::*;
 use crate::{
+    ciphersuite::SignaturePublicKey,
     credentials::*,
     framing::*,
     group::{errors::*, tests_and_kats::utils::generate_credential_with_key, *},
@@ -13,6 +14,7 @@ use crate::{
     messages::proposals::ProposalType,
     prelude::{Capabilities, RatchetTreeIn},
     prelude_test::HpkePublicKey,
+    treesync::errors::LeafNodeValidationError,
     versions::ProtocolVersion,
 };
 use openmls_traits::prelude::*;
@@ -33,6 +35,83 @@ fn application_id() {
     assert_eq!(&data[..], &serialized_extension_struct);
 }
 
+// Test that the extension types are valid where RFC 9420 (Section 17.3) and
+// the last resort extension allow them.
+#[test]
+fn extension_type_validity() {
+    for extension_type in [
+        ExtensionType::RatchetTree,
+        ExtensionType::RequiredCapabilities,
+        ExtensionType::ExternalPub,
+        ExtensionType::ExternalSenders,
+    ] {
+        assert_eq!(extension_type.is_valid_in_leaf_node(), Some(false));
+        assert_eq!(extension_type.is_valid_in_key_package(), Some(false));
+    }
+
+    assert_eq!(
+        ExtensionType::ApplicationId.is_valid_in_leaf_node(),
+        Some(true)
+    );
+    assert_eq!(
+        ExtensionType::ApplicationId.is_valid_in_key_package(),
+        Some(false)
+    );
+    assert_eq!(
+        ExtensionType::LastResort.is_valid_in_leaf_node(),
+        Some(false)
+    );
+    assert_eq!(
+        ExtensionType::LastResort.is_valid_in_key_package(),
+        Some(true)
+    );
+    assert_eq!(ExtensionType::Unknown(0xff00).is_valid_in_leaf_node(), None);
+    assert_eq!(
+        ExtensionType::Unknown(0xff00).is_valid_in_key_package(),
+        None
+    );
+}
+
+// Test that an application id is accepted in the leaf node of the group
+// creator, while a last resort extension is rejected.
+#[openmls_test::openmls_test]
+fn leaf_node_extensions() {
+    let alice_credential_with_key_and_signer =
+        generate_credential_with_key("Alice".into(), ciphersuite.signature_algorithm(), provider);
+
+    let application_id = Extension::ApplicationId(ApplicationIdExtension::new(b"Alice's app"));
+    let alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_leaf_node_extensions(Extensions::single(application_id))
+        .expect("application id was rejected as leaf node extension")
+        .build(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            alice_credential_with_key_and_signer.credential_with_key,
+        )
+        .expect("error creating group");
+    let own_leaf = alice_group.own_leaf().expect("own leaf is missing");
+    assert_eq!(
+        own_leaf
+            .extensions()
+            .application_id()
+            .map(|extension| extension.as_slice()),
+        Some(b"Alice's app" as &[u8])
+    );
+    own_leaf
+        .validate_locally()
+        .expect("leaf node with application id is invalid");
+
+    let last_resort = Extension::LastResort(LastResortExtension::default());
+    let err = MlsGroup::builder()
+        .with_leaf_node_extensions(Extensions::single(last_resort))
+        .expect_err("last resort extension was accepted as leaf node extension");
+    assert_eq!(
+        err,
+        crate::treesync::errors::LeafNodeValidationError::UnsupportedExtensions
+    );
+}
+
 // This tests the ratchet tree extension to deliver the public ratcheting tree
 // in-band
 #[openmls_test::openmls_test]
@@ -134,6 +213,89 @@ fn ratchet_tree_extension() {
     ));
 }
 
+// This tests fetching the ratchet tree through a callback when it is not
+// delivered in-band
+#[openmls_test::openmls_test]
+fn ratchet_tree_fetcher() {
+    let (alice_credential_with_key, alice_signature_keys) =
+        test_utils::new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential_with_key, bob_signature_keys) =
+        test_utils::new_credential(provider, b"Bob", ciphersuite.signature_algorithm());
+    let (charlie_credential_with_key, charlie_signature_keys) =
+        test_utils::new_credential(provider, b"Charlie", ciphersuite.signature_algorithm());
+
+    let bob_key_package_bundle = KeyPackageBundle::generate(
+        provider,
+        &bob_signature_keys,
+        ciphersuite,
+        bob_credential_with_key,
+    );
+    let charlie_key_package_bundle = KeyPackageBundle::generate(
+        provider,
+        &charlie_signature_keys,
+        ciphersuite,
+        charlie_credential_with_key,
+    );
+
+    // === Alice creates a group without the ratchet tree extension ===
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .use_ratchet_tree_extension(false)
+        .build(provider, &alice_signature_keys, alice_credential_with_key)
+        .expect("Error creating group.");
+
+    // === Alice adds Bob and Charlie ===
+    let (_commit, welcome, _group_info_option) = alice_group
+        .add_members(
+            provider,
+            &alice_signature_keys,
+            &[
+                bob_key_package_bundle.key_package().clone(),
+                charlie_key_package_bundle.key_package().clone(),
+            ],
+        )
+        .expect("An unexpected error occurred.");
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+
+    let config = MlsGroupJoinConfig::builder()
+        .use_ratchet_tree_extension(false)
+        .build();
+
+    // A fetcher that can't find the tree results in an error. Bob's key
+    // package is consumed in the process, so we use Charlie's afterwards.
+    let error = StagedWelcome::new_from_welcome_with_ratchet_tree_fetcher(
+        provider,
+        &config,
+        welcome.clone(),
+        |_group_id| None,
+    )
+    .err();
+    assert!(matches!(
+        error.expect("We expected an error"),
+        WelcomeError::MissingRatchetTree
+    ));
+
+    let ratchet_tree: RatchetTreeIn = alice_group.export_ratchet_tree().into();
+    let charlie_group = StagedWelcome::new_from_welcome_with_ratchet_tree_fetcher(
+        provider,
+        &config,
+        welcome,
+        |group_id| {
+            assert_eq!(group_id, alice_group.group_id());
+            Some(ratchet_tree)
+        },
+    )
+    .expect("Error staging welcome")
+    .into_group(provider)
+    .expect("Error creating group from welcome");
+
+    assert_eq!(
+        alice_group.epoch_authenticator(),
+        charlie_group.epoch_authenticator()
+    );
+}
+
 #[test]
 fn required_capabilities() {
     // A raw required capabilities extension with the default values for openmls
@@ -378,3 +540,338 @@ fn last_resort_extension() {
         .expect("error retrieving key package")
         .expect("key package does not exist");
 }
+
+#[openmls_test::openmls_test]
+fn role_map_extension() {
+    const ROLE_MAP_EXTENSION_TYPE: u16 = 0xff42;
+
+    let admin =
+        openmls_basic_credential::SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let moderator =
+        openmls_basic_credential::SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let admin_key: SignaturePublicKey = admin.to_public_vec().into();
+    let moderator_key: SignaturePublicKey = moderator.to_public_vec().into();
+    let group_id = GroupId::from_slice(b"group");
+
+    // The first role map must be signed by the member the application allows
+    // to set it, here the admin. The moderator can't appoint themselves.
+    let self_appointed = RoleMap::new(
+        group_id.clone(),
+        1,
+        vec![RoleAssignment::new(moderator_key.clone(), Role::Admin)],
+        &moderator,
+        moderator_key.clone(),
+    )
+    .unwrap();
+    let extensions = Extensions::single(
+        self_appointed
+            .to_extension(ROLE_MAP_EXTENSION_TYPE)
+            .unwrap(),
+    );
+    assert_eq!(
+        VerifiableRoleMap::from_extensions(&extensions, ROLE_MAP_EXTENSION_TYPE)
+            .unwrap()
+            .expect("role map not found")
+            .validate_first(provider.crypto(), ciphersuite, &group_id, &admin_key)
+            .unwrap_err(),
+        RoleMapError::UnauthorizedSigner
+    );
+
+    let first = RoleMap::new(
+        group_id.clone(),
+        1,
+        vec![
+            RoleAssignment::new(admin_key.clone(), Role::Admin),
+            RoleAssignment::new(moderator_key.clone(), Role::Moderator),
+        ],
+        &admin,
+        admin_key.clone(),
+    )
+    .unwrap();
+    let extensions = Extensions::single(first.to_extension(ROLE_MAP_EXTENSION_TYPE).unwrap());
+    let first = VerifiableRoleMap::from_extensions(&extensions, ROLE_MAP_EXTENSION_TYPE)
+        .unwrap()
+        .expect("role map not found")
+        .validate_first(provider.crypto(), ciphersuite, &group_id, &admin_key)
+        .unwrap();
+    assert!(first.is_admin(&admin_key));
+    assert_eq!(first.role(&moderator_key), Some(Role::Moderator));
+
+    // Known extension types can't carry a role map.
+    assert_eq!(
+        first.to_extension(0x0003).unwrap_err(),
+        RoleMapError::InvalidExtensionType
+    );
+
+    let validate = |version: u64, signer: &openmls_basic_credential::SignatureKeyPair| {
+        let role_map = RoleMap::new(
+            group_id.clone(),
+            version,
+            vec![RoleAssignment::new(moderator_key.clone(), Role::Admin)],
+            signer,
+            signer.to_public_vec().into(),
+        )
+        .unwrap();
+        let extensions =
+            Extensions::single(role_map.to_extension(ROLE_MAP_EXTENSION_TYPE).unwrap());
+        VerifiableRoleMap::from_extensions(&extensions, ROLE_MAP_EXTENSION_TYPE)
+            .unwrap()
+            .unwrap()
+            .validate(provider.crypto(), ciphersuite, &group_id, &first)
+    };
+
+    // Only admins of the current role map can change it.
+    assert_eq!(
+        validate(2, &moderator).unwrap_err(),
+        RoleMapError::UnauthorizedSigner
+    );
+    // The version has to increase.
+    assert_eq!(
+        validate(1, &admin).unwrap_err(),
+        RoleMapError::OutdatedVersion
+    );
+    let second = validate(2, &admin).unwrap();
+    assert!(second.is_admin(&moderator_key));
+    assert_eq!(second.role(&admin_key), None);
+
+    // A serialized role map is deserialized unverified and validated again.
+    let serialized = serde_json::to_vec(&second).unwrap();
+    let deserialized: VerifiableRoleMap = serde_json::from_slice(&serialized).unwrap();
+    assert_eq!(
+        deserialized
+            .validate(provider.crypto(), ciphersuite, &group_id, &first)
+            .unwrap(),
+        second
+    );
+
+    // Role maps of other groups are rejected.
+    let extensions = Extensions::single(second.to_extension(ROLE_MAP_EXTENSION_TYPE).unwrap());
+    assert_eq!(
+        VerifiableRoleMap::from_extensions(&extensions, ROLE_MAP_EXTENSION_TYPE)
+            .unwrap()
+            .unwrap()
+            .validate(
+                provider.crypto(),
+                ciphersuite,
+                &GroupId::from_slice(b"other group"),
+                &first
+            )
+            .unwrap_err(),
+        RoleMapError::WrongGroup
+    );
+}
+
+/// An application extension with a value of at most 10.
+#[derive(
+    Debug, PartialEq, tls_codec::TlsSerialize, tls_codec::TlsDeserializeBytes, tls_codec::TlsSize,
+)]
+struct Counter(u8);
+
+impl ApplicationExtension for Counter {
+    const EXTENSION_TYPE: u16 = 0xff10;
+
+    fn validate(&self) -> Result<(), ApplicationExtensionError> {
+        if self.0 > 10 {
+            return Err(ApplicationExtensionError::Invalid(
+                "counter too large".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn application_extension_registry() {
+    #[derive(tls_codec::TlsSerialize, tls_codec::TlsDeserializeBytes, tls_codec::TlsSize)]
+    struct NotApplicationDefined(u8);
+
+    impl ApplicationExtension for NotApplicationDefined {
+        const EXTENSION_TYPE: u16 = 0x0003;
+    }
+
+    let registry = ExtensionRegistry::new().register::<Counter>();
+    assert!(registry.is_registered(Counter::EXTENSION_TYPE));
+    assert!(!registry.is_registered(0xff11));
+
+    // Typed values round-trip through the extension list.
+    let extensions = Extensions::from_vec(vec![
+        Counter(3).to_extension().unwrap(),
+        Extension::Unknown(0xff11, UnknownExtension(vec![0xff, 0xff])),
+    ])
+    .unwrap();
+    let bytes = extensions.tls_serialize_detached().unwrap();
+    let extensions = Extensions::tls_deserialize(&mut bytes.as_slice()).unwrap();
+    assert_eq!(
+        extensions.application_extension::<Counter>().unwrap(),
+        Some(Counter(3))
+    );
+    // Unregistered unknown extensions are ignored.
+    registry.validate(&extensions).unwrap();
+
+    // Invalid and malformed values are rejected.
+    let invalid = Extensions::single(Counter(11).to_extension().unwrap());
+    assert!(matches!(
+        registry.validate(&invalid),
+        Err(ApplicationExtensionError::Invalid(_))
+    ));
+    let malformed = Extensions::single(Extension::Unknown(
+        Counter::EXTENSION_TYPE,
+        UnknownExtension(vec![1, 2]),
+    ));
+    assert_eq!(
+        registry.validate(&malformed),
+        Err(ApplicationExtensionError::Malformed)
+    );
+    assert_eq!(
+        malformed.application_extension::<Counter>(),
+        Err(ApplicationExtensionError::Malformed)
+    );
+
+    // Extension types known to OpenMLS can't be used.
+    assert!(matches!(
+        NotApplicationDefined(1).to_extension(),
+        Err(ApplicationExtensionError::KnownExtensionType)
+    ));
+}
+
+/// Test that the application extensions of the extension registry of a group
+/// are validated in key packages, leaf nodes and group context extensions, and
+/// that groups without a registry accept any value.
+#[openmls_test::openmls_test]
+fn application_extension_registry_in_group() {
+    let (alice_credential_with_key, alice_signer) =
+        test_utils::new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential_with_key, bob_signer) =
+        test_utils::new_credential(provider, b"Bob", ciphersuite.signature_algorithm());
+    let (charlie_credential_with_key, charlie_signer) =
+        test_utils::new_credential(provider, b"Charlie", ciphersuite.signature_algorithm());
+
+    let capabilities = Capabilities::new(
+        None,
+        None,
+        Some(&[ExtensionType::Unknown(Counter::EXTENSION_TYPE)]),
+        None,
+        None,
+    );
+    let registry = ExtensionRegistry::new().register::<Counter>();
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_capabilities(capabilities.clone())
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("Error creating group.");
+    let bob_key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities.clone())
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .unwrap()
+    .into_group(provider)
+    .unwrap();
+    bob_group.set_extension_registry(registry.clone());
+
+    // An invalid value in a key package is only accepted by Alice, who has no
+    // registry.
+    let charlie_key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities.clone())
+        .key_package_extensions(Extensions::single(Counter(11).to_extension().unwrap()))
+        .build(
+            ciphersuite,
+            provider,
+            &charlie_signer,
+            charlie_credential_with_key.clone(),
+        )
+        .unwrap();
+    let (commit, _welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_key_package.key_package().clone()],
+        )
+        .unwrap();
+    let err = bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ProcessMessageError::InvalidCommit(StageCommitError::ProposalValidationError(
+            ProposalValidationError::InvalidApplicationExtension(
+                ApplicationExtensionError::Invalid("counter too large".into())
+            )
+        ))
+    );
+    alice_group
+        .clear_pending_commit(provider.storage())
+        .unwrap();
+
+    // With the registry, Alice rejects an invalid value in a leaf node.
+    alice_group.set_extension_registry(registry);
+    let charlie_key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities)
+        .leaf_node_extensions(Extensions::single(Counter(11).to_extension().unwrap()))
+        .build(
+            ciphersuite,
+            provider,
+            &charlie_signer,
+            charlie_credential_with_key,
+        )
+        .unwrap();
+    let err = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_key_package.key_package().clone()],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
+            ProposalValidationError::LeafNodeValidation(
+                LeafNodeValidationError::InvalidApplicationExtension(
+                    ApplicationExtensionError::Invalid(_)
+                )
+            )
+        ))
+    ));
+
+    // And an invalid value in the group context extensions.
+    let required_capabilities = RequiredCapabilitiesExtension::new(
+        &[ExtensionType::Unknown(Counter::EXTENSION_TYPE)],
+        &[],
+        &[],
+    );
+    let err = alice_group
+        .update_group_context_extensions(
+            provider,
+            Extensions::from_vec(vec![
+                Extension::RequiredCapabilities(required_capabilities),
+                Counter(11).to_extension().unwrap(),
+            ])
+            .unwrap(),
+            &alice_signer,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        CreateGroupContextExtProposalError::CreateCommitError(
+            CreateCommitError::GroupContextExtensionsProposalValidationError(
+                GroupContextExtensionsProposalValidationError::InvalidApplicationExtension(
+                    ApplicationExtensionError::Invalid(_)
+                )
+            )
+        )
+    ));
+}
@@ -1,5 +1,5 @@
 use std::io::Read;
-use tls_codec::{Deserialize, Size};
+use tls_codec::Deserialize;
 
 use crate::versions::ProtocolVersion;
 
@@ -56,7 +56,6 @@ impl DeserializeBytes for MlsMessageIn {
     {
         let mut bytes_ref = bytes;
         let message = MlsMessageIn::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[message.tls_serialized_len()..];
-        Ok((message, remainder))
+        Ok((message, bytes_ref))
     }
 }
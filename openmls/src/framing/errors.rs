@@ -31,6 +31,9 @@ pub enum MessageDecryptionError {
     /// See [`SecretTreeError`] for more details.
     #[error(transparent)]
     SecretTreeError(#[from] SecretTreeError),
+    /// The message was already processed in this epoch.
+    #[error("The message was already processed in this epoch.")]
+    ReplayedMessage,
 }
 
 /// Message encryption error
@@ -0,0 +1,314 @@
+//! # Known Answer Tests for signatures on MLS structs
+//!
+//! This test file generates and reads test vectors for `SignWithLabel` on MLS
+//! structs. In contrast to the `sign_with_label` test of the crypto basics
+//! test vectors, the signed content is the serialization of actual structs,
+//! such that changes to the serialization or the signature labels of these
+//! structs are detected.
+//!
+//! Parameters:
+//! * Ciphersuite
+//!
+//! Format:
+//!
+//! ```text
+//! {
+//!   "cipher_suite": /* uint16 */,
+//!   "signature_priv": /* hex-encoded binary data */,
+//!   "signature_pub": /* hex-encoded binary data */,
+//!   "group_context": /* hex-encoded binary data */,
+//!   "framed_content": {
+//!     "group_id": /* hex-encoded binary data */,
+//!     "epoch": /* uint64 */,
+//!     "leaf_index": /* uint32 */,
+//!     "authenticated_data": /* hex-encoded binary data */,
+//!     "application_data": /* hex-encoded binary data */,
+//!     "framed_content_tbs": /* hex-encoded binary data */,
+//!     "signature": /* hex-encoded binary data */,
+//!   },
+//!   "group_info": {
+//!     "confirmation_tag": /* hex-encoded binary data */,
+//!     "signer": /* uint32 */,
+//!     "external_pub": /* hex-encoded binary data */,
+//!     "group_info_tbs": /* hex-encoded binary data */,
+//!     "signature": /* hex-encoded binary data */,
+//!   }
+//! }
+//! ```
+//!
+//! The framed content is an application message of a member in a public
+//! message, i.e. the `FramedContentTBS` includes the group context. The group
+//! info has a single `external_pub` extension with the given HPKE public key,
+//! as exported for external commits. The derivation of the key from the
+//! external secret is covered by the key schedule test vectors.
+//!
+//! Verification:
+//! * `framed_content_tbs` and `group_info_tbs` are equal to the serialization
+//!   of the structs built from the other values
+//! * `VerifyWithLabel(signature_pub, label, tbs, signature) == true`
+//! * `VerifyWithLabel(signature_pub, label, tbs, SignWithLabel(signature_priv, label, tbs)) == true`
+//! * The `external_pub` extension of the deserialized group info is equal to
+//!   `external_pub`
+
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{crypto::OpenMlsCrypto, random::OpenMlsRand, types::Ciphersuite};
+use serde::{self, Deserialize, Serialize};
+use tls_codec::{Deserialize as _, Serialize as _};
+
+use crate::{
+    binary_tree::LeafNodeIndex,
+    ciphersuite::{
+        signable::{Signable, Verifiable},
+        HpkePublicKey, Mac, OpenMlsSignaturePublicKey, SignContent, Signature,
+    },
+    extensions::{Extension, Extensions, ExternalPubExtension},
+    framing::{mls_content::FramedContentTbs, FramedContentBody, Sender, WireFormat},
+    group::{GroupContext, GroupId},
+    messages::{
+        group_info::{GroupInfoTBS, VerifiableGroupInfo},
+        ConfirmationTag,
+    },
+    test_utils::*,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct FramedContentVector {
+    group_id: String,
+    epoch: u64,
+    leaf_index: u32,
+    authenticated_data: String,
+    application_data: String,
+    framed_content_tbs: String,
+    signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct GroupInfoVector {
+    confirmation_tag: String,
+    signer: u32,
+    external_pub: String,
+    group_info_tbs: String,
+    signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignaturesTestVector {
+    cipher_suite: u16,
+    signature_priv: String,
+    signature_pub: String,
+    group_context: String,
+    framed_content: FramedContentVector,
+    group_info: GroupInfoVector,
+}
+
+fn framed_content_tbs(
+    framed_content: &FramedContentVector,
+    serialized_context: Vec<u8>,
+) -> FramedContentTbs {
+    FramedContentTbs::new(
+        WireFormat::PublicMessage,
+        GroupId::from_slice(&hex_to_bytes(&framed_content.group_id)),
+        framed_content.epoch,
+        Sender::build_member(LeafNodeIndex::new(framed_content.leaf_index)),
+        hex_to_bytes(&framed_content.authenticated_data).into(),
+        FramedContentBody::Application(hex_to_bytes(&framed_content.application_data).into()),
+    )
+    .with_context(serialized_context)
+}
+
+fn group_info_tbs(group_info: &GroupInfoVector, group_context: GroupContext) -> GroupInfoTBS {
+    GroupInfoTBS::new(
+        group_context,
+        Extensions::single(Extension::ExternalPub(ExternalPubExtension::new(
+            HpkePublicKey::from(hex_to_bytes(&group_info.external_pub)),
+        ))),
+        ConfirmationTag(Mac {
+            mac_value: hex_to_bytes(&group_info.confirmation_tag).into(),
+        }),
+        LeafNodeIndex::new(group_info.signer),
+    )
+}
+
+fn generate_test_vector(
+    ciphersuite: Ciphersuite,
+    provider: &OpenMlsRustCrypto,
+) -> SignaturesTestVector {
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+
+    let group_id = GroupId::random(provider.rand());
+    let epoch = 42;
+    let group_context = GroupContext::new(
+        ciphersuite,
+        group_id.clone(),
+        epoch,
+        provider
+            .rand()
+            .random_vec(ciphersuite.hash_length())
+            .unwrap(),
+        provider
+            .rand()
+            .random_vec(ciphersuite.hash_length())
+            .unwrap(),
+        Extensions::empty(),
+    );
+    let serialized_context = group_context.tls_serialize_detached().unwrap();
+
+    let mut framed_content = FramedContentVector {
+        group_id: bytes_to_hex(group_id.as_slice()),
+        epoch,
+        leaf_index: 3,
+        authenticated_data: bytes_to_hex(b"authenticated data"),
+        application_data: bytes_to_hex(b"application data"),
+        framed_content_tbs: String::new(),
+        signature: String::new(),
+    };
+    let tbs = framed_content_tbs(&framed_content, serialized_context.clone());
+    framed_content.framed_content_tbs = bytes_to_hex(&tbs.tls_serialize_detached().unwrap());
+    framed_content.signature = bytes_to_hex(tbs.sign(&signer).unwrap().signature().as_slice());
+
+    let mut group_info = GroupInfoVector {
+        confirmation_tag: bytes_to_hex(
+            &provider
+                .rand()
+                .random_vec(ciphersuite.hash_length())
+                .unwrap(),
+        ),
+        signer: 3,
+        external_pub: bytes_to_hex(
+            &provider
+                .crypto()
+                .derive_hpke_keypair(
+                    ciphersuite.hpke_config(),
+                    &provider
+                        .rand()
+                        .random_vec(ciphersuite.hash_length())
+                        .unwrap(),
+                )
+                .unwrap()
+                .public,
+        ),
+        group_info_tbs: String::new(),
+        signature: String::new(),
+    };
+    let tbs = group_info_tbs(&group_info, group_context);
+    group_info.group_info_tbs = bytes_to_hex(&tbs.tls_serialize_detached().unwrap());
+    group_info.signature = bytes_to_hex(tbs.sign(&signer).unwrap().signature().as_slice());
+
+    SignaturesTestVector {
+        cipher_suite: ciphersuite as u16,
+        signature_priv: bytes_to_hex(signer.private()),
+        signature_pub: bytes_to_hex(signer.public()),
+        group_context: bytes_to_hex(&serialized_context),
+        framed_content,
+        group_info,
+    }
+}
+
+#[test]
+fn write_test_vectors() {
+    let mut tests = Vec::new();
+    let provider = OpenMlsRustCrypto::default();
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        tests.push(generate_test_vector(ciphersuite, &provider));
+    }
+    write("test_vectors/signatures-new.json", &tests);
+}
+
+fn run_test_vector(test: SignaturesTestVector, provider: &OpenMlsRustCrypto) -> Result<(), String> {
+    let ciphersuite = Ciphersuite::try_from(test.cipher_suite).unwrap();
+    if !provider
+        .crypto()
+        .supported_ciphersuites()
+        .contains(&ciphersuite)
+    {
+        log::debug!("Unsupported ciphersuite {ciphersuite:?} ...");
+        return Ok(());
+    }
+    log::debug!("Testing test vector for ciphersuite {ciphersuite:?}");
+
+    let private = hex_to_bytes(&test.signature_priv);
+    let public = hex_to_bytes(&test.signature_pub);
+    let signer =
+        SignatureKeyPair::from_raw(ciphersuite.signature_algorithm(), private, public.clone());
+    let public_key =
+        OpenMlsSignaturePublicKey::new(public.into(), ciphersuite.signature_algorithm()).unwrap();
+    let verify = |label: &str, tbs: &[u8], signature: &Signature| {
+        public_key.verify_with_label(
+            provider.crypto(),
+            signature,
+            &SignContent::new(label, tbs.into()),
+        )
+    };
+
+    let serialized_context = hex_to_bytes(&test.group_context);
+    let group_context = GroupContext::tls_deserialize_exact(&serialized_context)
+        .map_err(|e| format!("Invalid group context: {e:?}"))?;
+
+    // framed content
+    {
+        let tbs = framed_content_tbs(&test.framed_content, serialized_context);
+        let serialized_tbs = tbs.tls_serialize_detached().unwrap();
+        if serialized_tbs != hex_to_bytes(&test.framed_content.framed_content_tbs) {
+            return Err("FramedContentTBS mismatch".to_string());
+        }
+
+        let signature = hex_to_bytes(&test.framed_content.signature).into();
+        verify(tbs.label(), &serialized_tbs, &signature)
+            .map_err(|e| format!("Invalid FramedContentTBS signature: {e:?}"))?;
+
+        let label = tbs.label().to_owned();
+        let authenticated_content = tbs.sign(&signer).unwrap();
+        verify(&label, &serialized_tbs, authenticated_content.signature())
+            .map_err(|e| format!("Invalid own FramedContentTBS signature: {e:?}"))?;
+    }
+
+    // group info
+    {
+        let tbs = group_info_tbs(&test.group_info, group_context);
+        let serialized_tbs = tbs.tls_serialize_detached().unwrap();
+        if serialized_tbs != hex_to_bytes(&test.group_info.group_info_tbs) {
+            return Err("GroupInfoTBS mismatch".to_string());
+        }
+
+        let signature = hex_to_bytes(&test.group_info.signature).into();
+        verify(tbs.label(), &serialized_tbs, &signature)
+            .map_err(|e| format!("Invalid GroupInfoTBS signature: {e:?}"))?;
+
+        let label = tbs.label().to_owned();
+        let group_info = tbs.sign(&signer).unwrap();
+        verify(&label, &serialized_tbs, group_info.signature())
+            .map_err(|e| format!("Invalid own GroupInfoTBS signature: {e:?}"))?;
+
+        // Parse the group info like a joiner would.
+        let serialized_group_info =
+            [serialized_tbs, signature.tls_serialize_detached().unwrap()].concat();
+        let group_info = VerifiableGroupInfo::tls_deserialize_exact(&serialized_group_info)
+            .map_err(|e| format!("Invalid group info: {e:?}"))?
+            .verify(provider.crypto(), &public_key)
+            .map_err(|e| format!("Invalid group info signature: {e:?}"))?;
+        let external_pub = group_info
+            .extensions()
+            .external_pub()
+            .ok_or_else(|| "Missing external_pub extension".to_string())?;
+        if external_pub.external_pub().as_slice() != hex_to_bytes(&test.group_info.external_pub) {
+            return Err("external_pub mismatch".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn read_test_vectors_signatures() {
+    let _ = pretty_env_logger::try_init();
+
+    let provider = OpenMlsRustCrypto::default();
+
+    let tests: Vec<SignaturesTestVector> = read_json!("../../test_vectors/signatures.json");
+    for test in tests {
+        match run_test_vector(test, &provider) {
+            Ok(_) => {}
+            Err(e) => panic!("Error while checking signatures test vector.\n{e:?}"),
+        }
+    }
+}
@@ -94,6 +94,11 @@ pub enum MlsMessageBodyIn {
 }
 
 impl MlsMessageIn {
+    /// Returns the protocol version of this message.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
     /// Returns the wire format.
     pub fn wire_format(&self) -> WireFormat {
         match self.body {
@@ -105,6 +110,48 @@ impl MlsMessageIn {
         }
     }
 
+    /// Returns the group ID of this message without processing it, or `None`
+    /// if the message does not belong to a group.
+    ///
+    /// Note: This value is unauthenticated. It should only be used to decide
+    /// which group the message gets passed to.
+    pub fn group_id(&self) -> Option<&GroupId> {
+        match &self.body {
+            MlsMessageBodyIn::PublicMessage(m) => Some(m.group_id()),
+            MlsMessageBodyIn::PrivateMessage(m) => Some(m.group_id()),
+            MlsMessageBodyIn::GroupInfo(group_info) => Some(group_info.group_id()),
+            MlsMessageBodyIn::Welcome(_) | MlsMessageBodyIn::KeyPackage(_) => None,
+        }
+    }
+
+    /// Returns the epoch of this message without processing it, or `None` if
+    /// the message does not belong to a group.
+    ///
+    /// Note: This value is unauthenticated.
+    pub fn epoch(&self) -> Option<GroupEpoch> {
+        match &self.body {
+            MlsMessageBodyIn::PublicMessage(m) => Some(m.epoch()),
+            MlsMessageBodyIn::PrivateMessage(m) => Some(m.epoch()),
+            MlsMessageBodyIn::GroupInfo(group_info) => Some(group_info.epoch()),
+            MlsMessageBodyIn::Welcome(_) | MlsMessageBodyIn::KeyPackage(_) => None,
+        }
+    }
+
+    /// Returns the content type of this message without processing it, or
+    /// `None` if the message is neither a [`PublicMessage`] nor a
+    /// [`PrivateMessage`].
+    ///
+    /// Note: This value is unauthenticated.
+    pub fn content_type(&self) -> Option<ContentType> {
+        match &self.body {
+            MlsMessageBodyIn::PublicMessage(m) => Some(m.content_type()),
+            MlsMessageBodyIn::PrivateMessage(m) => Some(m.content_type()),
+            MlsMessageBodyIn::Welcome(_)
+            | MlsMessageBodyIn::GroupInfo(_)
+            | MlsMessageBodyIn::KeyPackage(_) => None,
+        }
+    }
+
     /// Extract the content of an [`MlsMessageIn`] after deserialization for use
     /// with the [`MlsGroup`] API.
     pub fn extract(self) -> MlsMessageBodyIn {
@@ -160,6 +160,27 @@ impl MlsMessageOut {
     pub fn body(&self) -> &MlsMessageBodyOut {
         &self.body
     }
+
+    /// Extract the contents of this [`MlsMessageOut`].
+    pub fn extract(self) -> MlsMessageBodyOut {
+        self.body
+    }
+
+    /// Returns the protocol version of this message.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Returns the wire format.
+    pub fn wire_format(&self) -> WireFormat {
+        match self.body {
+            MlsMessageBodyOut::PrivateMessage(_) => WireFormat::PrivateMessage,
+            MlsMessageBodyOut::PublicMessage(_) => WireFormat::PublicMessage,
+            MlsMessageBodyOut::Welcome(_) => WireFormat::Welcome,
+            MlsMessageBodyOut::GroupInfo(_) => WireFormat::GroupInfo,
+            MlsMessageBodyOut::KeyPackage(_) => WireFormat::KeyPackage,
+        }
+    }
 }
 
 // Convenience functions for tests and test-utils
@@ -7,7 +7,7 @@
 //! signatures are verified before the content of an MLS [`PrivateMessageIn`] or
 //! [`PublicMessageIn`] can be accessed by processing functions of OpenMLS.
 
-use std::io::Read;
+use std::{io::Read, sync::Arc};
 
 use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
 use tls_codec::Serialize as TlsSerializeTrait;
@@ -117,7 +117,7 @@ impl VerifiableAuthenticatedContentIn {
     pub(crate) fn new(
         wire_format: WireFormat,
         content: FramedContentIn,
-        serialized_context: impl Into<Option<Vec<u8>>>,
+        serialized_context: impl Into<Option<Arc<[u8]>>>,
         auth: FramedContentAuthData,
     ) -> Self {
         let tbs = FramedContentTbsIn {
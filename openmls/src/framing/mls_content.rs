@@ -9,7 +9,7 @@ use crate::{
     versions::ProtocolVersion,
 };
 
-use std::io::Write;
+use std::{io::Write, sync::Arc};
 
 use super::{
     mls_auth_content::{AuthenticatedContent, FramedContentAuthData},
@@ -130,7 +130,9 @@ impl<'a> AuthenticatedContentTbm<'a> {
     }
 
     pub(crate) fn into_bytes(self) -> Result<Vec<u8>, tls_codec::Error> {
-        let mut buffer = self.tbs_payload.to_vec();
+        let mut buffer =
+            Vec::with_capacity(self.tbs_payload.len() + self.auth.tls_serialized_len());
+        buffer.extend_from_slice(self.tbs_payload);
         self.auth.tls_serialize(&mut buffer)?;
         Ok(buffer)
     }
@@ -141,7 +143,7 @@ pub(crate) struct FramedContentTbs {
     pub(super) version: ProtocolVersion,
     pub(super) wire_format: WireFormat,
     pub(super) content: FramedContent,
-    pub(super) serialized_context: Option<Vec<u8>>,
+    pub(super) serialized_context: Option<Arc<[u8]>>,
 }
 
 impl Signable for FramedContentTbs {
@@ -185,8 +187,8 @@ impl FramedContentTbs {
 
     /// Adds a serialized context to FramedContentTbs.
     /// This consumes the original struct and can be used as a builder function.
-    pub(crate) fn with_context(mut self, serialized_context: Vec<u8>) -> Self {
-        self.serialized_context = Some(serialized_context);
+    pub(crate) fn with_context(mut self, serialized_context: impl Into<Arc<[u8]>>) -> Self {
+        self.serialized_context = Some(serialized_context.into());
         self
     }
 }
@@ -198,10 +200,18 @@ pub(crate) fn framed_content_tbs_serialized_detached<'context>(
     sender: &Sender,
     serialized_context: impl Into<Option<&'context [u8]>>,
 ) -> Result<Vec<u8>, tls_codec::Error> {
-    let writer = &mut Vec::new();
+    let serialized_context = serialized_context.into();
+    // The context is only written for member senders, but reserving space for
+    // it in any case is cheaper than growing the buffer.
+    let mut writer = Vec::with_capacity(
+        version.tls_serialized_len()
+            + wire_format.tls_serialized_len()
+            + content.tls_serialized_len()
+            + serialized_context.map_or(0, <[u8]>::len),
+    );
 
     framed_content_tbs_serialized(
-        writer,
+        &mut writer,
         version,
         wire_format,
         content,
@@ -209,7 +219,7 @@ pub(crate) fn framed_content_tbs_serialized_detached<'context>(
         serialized_context,
     )?;
 
-    Ok(writer.to_vec())
+    Ok(writer)
 }
 
 pub(crate) fn framed_content_tbs_serialized<'context, W: Write>(
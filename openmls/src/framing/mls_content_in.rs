@@ -10,7 +10,10 @@ use crate::{
     versions::ProtocolVersion,
 };
 
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
 
 use super::{
     mls_auth_content_in::AuthenticatedContentIn,
@@ -176,7 +179,7 @@ pub(crate) struct FramedContentTbsIn {
     pub(super) version: ProtocolVersion,
     pub(super) wire_format: WireFormat,
     pub(super) content: FramedContentIn,
-    pub(super) serialized_context: Option<Vec<u8>>,
+    pub(super) serialized_context: Option<Arc<[u8]>>,
 }
 
 impl Signable for FramedContentTbsIn {
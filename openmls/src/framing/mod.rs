@@ -94,6 +94,8 @@ pub use validation::*;
 
 // Tests
 #[cfg(test)]
+mod kat_signatures;
+#[cfg(test)]
 pub(crate) mod tests;
 
 /// Wire format of MLS messages.
@@ -42,6 +42,17 @@ pub(crate) struct MlsMessageHeader {
     pub(crate) sender: LeafNodeIndex,
 }
 
+/// Padding of the content of a [`PrivateMessage`].
+#[derive(Debug, Clone, Copy)]
+enum Padding {
+    /// Pad the content to a multiple of the given block size.
+    BlockSize(usize),
+    /// Pad the content with exactly the given number of bytes. This is only
+    /// used for deterministic encryption in tests.
+    #[cfg(any(feature = "test-utils", test))]
+    Length(usize),
+}
+
 impl PrivateMessage {
     #[cfg(test)]
     pub(crate) fn new(
@@ -82,12 +93,40 @@ impl PrivateMessage {
         }
         Self::encrypt_content(
             crypto,
-            rand,
             None,
             public_message,
             ciphersuite,
             message_secrets,
-            padding_size,
+            Self::random_reuse_guard(rand)?,
+            Padding::BlockSize(padding_size),
+        )
+    }
+
+    /// Encrypts the `AuthenticatedContent` with the given reuse guard and
+    /// exactly `padding_length` bytes of padding. Together with a
+    /// deterministic signature scheme, this makes the resulting
+    /// `PrivateMessage` deterministic, which allows comparing it byte by byte
+    /// with the output of other implementations.
+    #[cfg(any(feature = "test-utils", test))]
+    pub(crate) fn encrypt_deterministic<T>(
+        crypto: &impl OpenMlsCrypto,
+        public_message: &AuthenticatedContent,
+        ciphersuite: Ciphersuite,
+        message_secrets: &mut MessageSecrets,
+        reuse_guard: [u8; REUSE_GUARD_BYTES],
+        padding_length: usize,
+    ) -> Result<PrivateMessage, MessageEncryptionError<T>> {
+        if public_message.wire_format() != WireFormat::PrivateMessage {
+            return Err(MessageEncryptionError::WrongWireFormat);
+        }
+        Self::encrypt_content(
+            crypto,
+            None,
+            public_message,
+            ciphersuite,
+            message_secrets,
+            ReuseGuard::from_bytes(reuse_guard),
+            Padding::Length(padding_length),
         )
     }
 
@@ -102,12 +141,12 @@ impl PrivateMessage {
     ) -> Result<PrivateMessage, MessageEncryptionError<T>> {
         Self::encrypt_content(
             crypto,
-            rand,
             None,
             public_message,
             ciphersuite,
             message_secrets,
-            padding_size,
+            Self::random_reuse_guard(rand)?,
+            Padding::BlockSize(padding_size),
         )
     }
 
@@ -123,25 +162,33 @@ impl PrivateMessage {
     ) -> Result<PrivateMessage, MessageEncryptionError<T>> {
         Self::encrypt_content(
             crypto,
-            rand,
             Some(header),
             public_message,
             ciphersuite,
             message_secrets,
-            padding_size,
+            Self::random_reuse_guard(rand)?,
+            Padding::BlockSize(padding_size),
         )
     }
 
+    /// Sample a reuse guard uniformly at random.
+    fn random_reuse_guard<T>(
+        rand: &impl OpenMlsRand,
+    ) -> Result<ReuseGuard, MessageEncryptionError<T>> {
+        ReuseGuard::try_from_random(rand)
+            .map_err(|e| LibraryError::unexpected_crypto_error(e).into())
+    }
+
     /// Internal function to encrypt content. The extra message header is only used
     /// for tests. Otherwise, the data from the given `AuthenticatedContent` is used.
     fn encrypt_content<T>(
         crypto: &impl OpenMlsCrypto,
-        rand: &impl OpenMlsRand,
         test_header: Option<MlsMessageHeader>,
         public_message: &AuthenticatedContent,
         ciphersuite: Ciphersuite,
         message_secrets: &mut MessageSecrets,
-        padding_size: usize,
+        reuse_guard: ReuseGuard,
+        padding: Padding,
     ) -> Result<PrivateMessage, MessageEncryptionError<T>> {
         let sender_index = if let Some(index) = public_message.sender().as_member() {
             index
@@ -159,7 +206,7 @@ impl PrivateMessage {
         };
         // Serialize the content AAD
         let private_message_content_aad = PrivateContentAad {
-            group_id: header.group_id.clone(),
+            group_id: &header.group_id,
             epoch: header.epoch,
             content_type: public_message.content().content_type(),
             authenticated_data: VLByteSlice(public_message.authenticated_data()),
@@ -173,9 +220,6 @@ impl PrivateMessage {
             .secret_tree_mut()
             // Even in tests we want to use the real sender index, so we have a key to encrypt.
             .secret_for_encryption(ciphersuite, crypto, sender_index, secret_type)?;
-        // Sample reuse guard uniformly at random.
-        let reuse_guard: ReuseGuard =
-            ReuseGuard::try_from_random(rand).map_err(LibraryError::unexpected_crypto_error)?;
         // Prepare the nonce by xoring with the reuse guard.
         let prepared_nonce = ratchet_nonce.xor_with_reuse_guard(&reuse_guard);
         // Encrypt the payload
@@ -189,7 +233,7 @@ impl PrivateMessage {
                 crypto,
                 &Self::encode_padded_ciphertext_content_detached(
                     public_message,
-                    padding_size,
+                    padding,
                     ciphersuite.mac_length(),
                 )
                 .map_err(LibraryError::missing_bound_check)?,
@@ -211,7 +255,7 @@ impl PrivateMessage {
         // Compute sender data nonce by xoring reuse guard and key schedule
         // nonce as per spec.
         let mls_sender_data_aad = MlsSenderDataAad::new(
-            header.group_id.clone(),
+            &header.group_id,
             header.epoch,
             public_message.content().content_type(),
         );
@@ -260,7 +304,7 @@ impl PrivateMessage {
     /// Encodes the `PrivateMessageContent` struct with padding.
     fn encode_padded_ciphertext_content_detached(
         authenticated_content: &AuthenticatedContent,
-        padding_size: usize,
+        padding: Padding,
         mac_len: usize,
     ) -> Result<Vec<u8>, tls_codec::Error> {
         let plaintext_length = authenticated_content
@@ -268,14 +312,17 @@ impl PrivateMessage {
             .serialized_len_without_type()
             + authenticated_content.auth.tls_serialized_len();
 
-        let padding_length = if padding_size > 0 {
-            // Calculate padding block size.
-            // Only the AEAD tag is added.
-            let padding_offset = plaintext_length + mac_len;
-            // Return padding block size
-            (padding_size - (padding_offset % padding_size)) % padding_size
-        } else {
-            0
+        let padding_length = match padding {
+            Padding::BlockSize(padding_size) if padding_size > 0 => {
+                // Calculate padding block size.
+                // Only the AEAD tag is added.
+                let padding_offset = plaintext_length + mac_len;
+                // Return padding block size
+                (padding_size - (padding_offset % padding_size)) % padding_size
+            }
+            Padding::BlockSize(_) => 0,
+            #[cfg(any(feature = "test-utils", test))]
+            Padding::Length(padding_length) => padding_length,
         };
 
         // Persist all initial fields manually (avoids cloning them)
@@ -305,7 +352,7 @@ impl PrivateMessage {
 
 #[derive(TlsSerialize, TlsSize)]
 pub(crate) struct PrivateContentAad<'a> {
-    pub(crate) group_id: GroupId,
+    pub(crate) group_id: &'a GroupId,
     pub(crate) epoch: GroupEpoch,
     pub(crate) content_type: ContentType,
     pub(crate) authenticated_data: VLByteSlice<'a>,
@@ -66,7 +66,7 @@ impl PrivateMessageIn {
             .map_err(LibraryError::unexpected_crypto_error)?;
         // Serialize sender data AAD
         let mls_sender_data_aad =
-            MlsSenderDataAad::new(self.group_id.clone(), self.epoch, self.content_type);
+            MlsSenderDataAad::new(&self.group_id, self.epoch, self.content_type);
         let mls_sender_data_aad_bytes = mls_sender_data_aad
             .tls_serialize_detached()
             .map_err(LibraryError::missing_bound_check)?;
@@ -103,7 +103,7 @@ impl PrivateMessageIn {
     ) -> Result<PrivateMessageContentIn, MessageDecryptionError> {
         // Serialize content AAD
         let private_message_content_aad_bytes = PrivateContentAad {
-            group_id: self.group_id.clone(),
+            group_id: &self.group_id,
             epoch: self.epoch,
             content_type: self.content_type,
             authenticated_data: VLByteSlice(self.authenticated_data.as_slice()),
@@ -154,6 +154,11 @@ impl PrivateMessageIn {
         sender_data: MlsSenderData,
     ) -> Result<VerifiableAuthenticatedContentIn, MessageDecryptionError> {
         let secret_type = SecretType::from(&self.content_type);
+        // Reject replays before touching the secret tree, which would
+        // otherwise fail with a less descriptive error.
+        if message_secrets.is_replay(sender_index, secret_type, sender_data.generation) {
+            return Err(MessageDecryptionError::ReplayedMessage);
+        }
         // Extract generation and key material for encryption
         let (ratchet_key, ratchet_nonce) = message_secrets
             .secret_tree_mut()
@@ -175,6 +180,7 @@ impl PrivateMessageIn {
         // Prepare the nonce by xoring with the reuse guard.
         let prepared_nonce = ratchet_nonce.xor_with_reuse_guard(&sender_data.reuse_guard);
         let private_message_content = self.decrypt(crypto, ratchet_key, &prepared_nonce)?;
+        message_secrets.mark_consumed(sender_index, secret_type, sender_data.generation);
 
         // Extract sender. The sender type is always of type Member for PrivateMessage.
         let sender = Sender::from_sender_data(sender_data);
@@ -193,7 +199,7 @@ impl PrivateMessageIn {
                 authenticated_data: self.authenticated_data.clone(),
                 body: private_message_content.content,
             },
-            Some(message_secrets.serialized_context().to_vec()),
+            Some(message_secrets.shared_serialized_context()),
             private_message_content.auth,
         );
         Ok(verifiable)
@@ -214,6 +220,11 @@ impl PrivateMessageIn {
         self.content_type
     }
 
+    /// Get the `authenticated_data` in the `PrivateMessage`.
+    pub(crate) fn authenticated_data(&self) -> &[u8] {
+        self.authenticated_data.as_slice()
+    }
+
     /// Set the ciphertext.
     #[cfg(test)]
     pub(crate) fn set_ciphertext(&mut self, ciphertext: Vec<u8>) {
@@ -207,16 +207,7 @@ impl ConfirmedTranscriptHashInput<'_> {
         ciphersuite: Ciphersuite,
         interim_transcript_hash: &[u8],
     ) -> Result<Vec<u8>, LibraryError> {
-        let serialized: Vec<u8> = self
-            .tls_serialize_detached()
-            .map_err(LibraryError::missing_bound_check)?;
-
-        crypto
-            .hash(
-                ciphersuite.hash_algorithm(),
-                &[interim_transcript_hash, &serialized].concat(),
-            )
-            .map_err(LibraryError::unexpected_crypto_error)
+        transcript_hash(crypto, ciphersuite, interim_transcript_hash, &self)
     }
 }
 
@@ -256,19 +247,33 @@ impl InterimTranscriptHashInput<'_> {
         ciphersuite: Ciphersuite,
         confirmed_transcript_hash: &[u8],
     ) -> Result<Vec<u8>, LibraryError> {
-        let serialized = self
-            .tls_serialize_detached()
-            .map_err(LibraryError::missing_bound_check)?;
-
-        crypto
-            .hash(
-                ciphersuite.hash_algorithm(),
-                &[confirmed_transcript_hash, &serialized].concat(),
-            )
-            .map_err(LibraryError::unexpected_crypto_error)
+        transcript_hash(crypto, ciphersuite, confirmed_transcript_hash, &self)
     }
 }
 
+/// Hashes the previous `transcript_hash` followed by the serialized `input`.
+/// The `input` is serialized directly into the hash computation, such that
+/// large commits are not copied into a separate buffer.
+fn transcript_hash(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    transcript_hash: &[u8],
+    input: &impl TlsSerializeTrait,
+) -> Result<Vec<u8>, LibraryError> {
+    let mut hash_writer = crypto
+        .hash_writer(ciphersuite.hash_algorithm())
+        .map_err(LibraryError::unexpected_crypto_error)?;
+    hash_writer
+        .write_all(transcript_hash)
+        .map_err(|_| LibraryError::custom("Writing the transcript hash failed"))?;
+    input
+        .tls_serialize(&mut hash_writer)
+        .map_err(LibraryError::missing_bound_check)?;
+    hash_writer
+        .finalize()
+        .map_err(LibraryError::unexpected_crypto_error)
+}
+
 impl<'a> TryFrom<&'a PublicMessage> for InterimTranscriptHashInput<'a> {
     type Error = &'static str;
 
@@ -14,7 +14,10 @@ use super::{
 };
 
 use openmls_traits::types::Ciphersuite;
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
 use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
 
 /// [`PublicMessageIn`] is a framing structure for MLS messages. It can contain
@@ -155,7 +158,7 @@ impl PublicMessageIn {
 
         // Verify the membership tag
         if let Some(membership_tag) = &self.membership_tag {
-            // TODO #133: make this a constant-time comparison
+            // The comparison of the MACs is constant-time.
             if membership_tag != expected_membership_tag {
                 return Err(ValidationError::InvalidMembershipTag);
             }
@@ -178,7 +181,7 @@ impl PublicMessageIn {
     /// Turn this [`PublicMessageIn`] into a [`VerifiableAuthenticatedContent`].
     pub(crate) fn into_verifiable_content(
         self,
-        serialized_context: impl Into<Option<Vec<u8>>>,
+        serialized_context: impl Into<Option<Arc<[u8]>>>,
     ) -> VerifiableAuthenticatedContentIn {
         VerifiableAuthenticatedContentIn::new(
             WireFormat::PublicMessage,
@@ -243,8 +246,7 @@ impl DeserializeBytes for PublicMessageIn {
     {
         let mut bytes_ref = bytes;
         let message = PublicMessageIn::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[message.tls_serialized_len()..];
-        Ok((message, remainder))
+        Ok((message, bytes_ref))
     }
 }
 
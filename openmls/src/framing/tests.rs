@@ -4,7 +4,7 @@ use openmls_traits::types::Ciphersuite;
 
 use mls_group::tests_and_kats::utils::{setup_alice_bob_group, setup_client};
 use signable::Verifiable;
-use tls_codec::{Deserialize, Serialize};
+use tls_codec::{Deserialize, DeserializeBytes, Serialize};
 
 use crate::{
     binary_tree::{array_representation::TreeSize, LeafNodeIndex},
@@ -18,6 +18,7 @@ use crate::{
     storage::OpenMlsProvider,
     test_utils::frankenstein::*,
     tree::{secret_tree::SecretTree, sender_ratchet::SenderRatchetConfiguration},
+    versions::ProtocolVersion,
 };
 
 /// This tests serializing/deserializing PublicMessage
@@ -566,3 +567,203 @@ fn key_package_version() {
     // Expect a decoding  error
     matches!(err, tls_codec::Error::DecodingError(_));
 }
+
+/// Test that the wire format and version of an [`MlsMessageOut`] survive a
+/// round trip through [`MlsMessageIn`].
+#[openmls_test::openmls_test]
+fn mls_message_wire_format() {
+    let (key_package, _, _) = key_package(ciphersuite, provider);
+
+    let message = MlsMessageOut::from(key_package);
+    assert_eq!(message.wire_format(), WireFormat::KeyPackage);
+    assert_eq!(message.version(), ProtocolVersion::Mls10);
+
+    let encoded = message
+        .tls_serialize_detached()
+        .expect("An unexpected error occurred.");
+    let decoded =
+        MlsMessageIn::tls_deserialize_exact(encoded).expect("An unexpected error occurred.");
+    assert_eq!(decoded.wire_format(), message.wire_format());
+    assert_eq!(decoded.version(), message.version());
+
+    assert!(matches!(
+        message.extract(),
+        MlsMessageBodyOut::KeyPackage(_)
+    ));
+    assert!(matches!(decoded.extract(), MlsMessageBodyIn::KeyPackage(_)));
+}
+
+/// Test that deserializing an [`MlsMessageIn`] from a byte slice returns the
+/// bytes following the message.
+#[openmls_test::openmls_test]
+fn mls_message_deserialize_bytes_remainder() {
+    let (key_package, _, _) = key_package(ciphersuite, provider);
+
+    let message = MlsMessageOut::from(key_package);
+    let mut encoded = message
+        .tls_serialize_detached()
+        .expect("An unexpected error occurred.");
+    let trailing_bytes = [0xAA, 0xBB, 0xCC];
+    encoded.extend_from_slice(&trailing_bytes);
+
+    let (decoded, remainder) =
+        MlsMessageIn::tls_deserialize_bytes(&encoded).expect("An unexpected error occurred.");
+    assert_eq!(remainder, trailing_bytes);
+    assert_eq!(MlsMessageOut::from(decoded), message);
+}
+
+/// Test that the group ID, epoch and content type of an [`MlsMessageIn`] can
+/// be inspected without processing the message.
+#[openmls_test::openmls_test]
+fn mls_message_preview() {
+    let (mut alice_group, alice_signature_keys, _bob_group, _bob_signature_keys, _credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let roundtrip = |message: MlsMessageOut| {
+        let encoded = message
+            .tls_serialize_detached()
+            .expect("An unexpected error occurred.");
+        MlsMessageIn::tls_deserialize_exact(encoded).expect("An unexpected error occurred.")
+    };
+
+    let application_message = roundtrip(
+        alice_group
+            .create_message(provider, &alice_signature_keys, b"Hello")
+            .expect("An unexpected error occurred."),
+    );
+    assert_eq!(application_message.group_id(), Some(alice_group.group_id()));
+    assert_eq!(application_message.epoch(), Some(alice_group.epoch()));
+    assert_eq!(
+        application_message.content_type(),
+        Some(ContentType::Application)
+    );
+    assert_eq!(
+        application_message.wire_format(),
+        WireFormat::PrivateMessage
+    );
+
+    let (commit, _welcome, group_info) = alice_group
+        .self_update(
+            provider,
+            &alice_signature_keys,
+            LeafNodeParameters::default(),
+        )
+        .expect("An unexpected error occurred.")
+        .into_contents();
+    let commit = roundtrip(commit);
+    assert_eq!(commit.group_id(), Some(alice_group.group_id()));
+    assert_eq!(commit.epoch(), Some(alice_group.epoch()));
+    assert_eq!(commit.content_type(), Some(ContentType::Commit));
+
+    if let Some(group_info) = group_info {
+        let group_info = roundtrip(group_info.into());
+        assert_eq!(group_info.group_id(), Some(alice_group.group_id()));
+        assert_eq!(group_info.content_type(), None);
+    }
+
+    let (key_package, _, _) = key_package(ciphersuite, provider);
+    let key_package = roundtrip(MlsMessageOut::from(key_package));
+    assert_eq!(key_package.group_id(), None);
+    assert_eq!(key_package.epoch(), None);
+    assert_eq!(key_package.content_type(), None);
+}
+
+/// Test that encrypting the same content with a fixed reuse guard and padding
+/// yields identical ciphertexts.
+#[openmls_test::openmls_test]
+fn deterministic_private_message() {
+    let (plaintext, _credential, _signature_keys) =
+        create_content(ciphersuite, WireFormat::PrivateMessage, provider);
+    let message_secrets =
+        MessageSecrets::random(ciphersuite, provider.rand(), LeafNodeIndex::new(0));
+
+    let encrypt = |reuse_guard: [u8; 4], padding_length: usize| {
+        PrivateMessage::encrypt_deterministic::<StorageError>(
+            provider.crypto(),
+            &plaintext,
+            ciphersuite,
+            &mut message_secrets.clone(),
+            reuse_guard,
+            padding_length,
+        )
+        .expect("Could not encrypt PublicMessage.")
+    };
+
+    let reuse_guard = [1, 2, 3, 4];
+    let ciphertext = encrypt(reuse_guard, 16);
+    assert_eq!(ciphertext, encrypt(reuse_guard, 16));
+
+    // A different reuse guard changes both the sender data and the content
+    // ciphertext.
+    let other_ciphertext = encrypt([4, 3, 2, 1], 16);
+    assert_ne!(
+        ciphertext.encrypted_sender_data,
+        other_ciphertext.encrypted_sender_data
+    );
+    assert_ne!(ciphertext.ciphertext, other_ciphertext.ciphertext);
+
+    // The padding is applied exactly.
+    let padded_ciphertext = encrypt(reuse_guard, 26);
+    assert_eq!(
+        padded_ciphertext.ciphertext.as_slice().len(),
+        ciphertext.ciphertext.as_slice().len() + 10
+    );
+}
+
+/// Test that deterministically encrypted application messages can be
+/// processed by other members.
+#[openmls_test::openmls_test]
+fn deterministic_application_message() {
+    let (mut alice_group, alice_signature_keys, mut bob_group, _bob_signature_keys, _credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let message: MlsMessageIn = alice_group
+        .create_message_deterministic(
+            provider,
+            &alice_signature_keys,
+            b"Hello, Bob!",
+            [0xAA; 4],
+            7,
+        )
+        .expect("An unexpected error occurred.")
+        .into();
+
+    let processed_message = bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("Could not process message.");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ApplicationMessage(application_message) => {
+            assert_eq!(application_message.into_bytes(), b"Hello, Bob!");
+        }
+        _ => panic!("Expected an application message."),
+    }
+}
+
+// The serialized context of an incoming message is shared, not copied, when
+// the message is converted for verification.
+#[test]
+fn incoming_tbs_shares_serialized_context() {
+    use std::sync::Arc;
+
+    use super::mls_content_in::{FramedContentBodyIn, FramedContentIn, FramedContentTbsIn};
+
+    let serialized_context: Arc<[u8]> = vec![1, 2, 3].into();
+    let tbs_in = FramedContentTbsIn {
+        version: ProtocolVersion::Mls10,
+        wire_format: WireFormat::PublicMessage,
+        content: FramedContentIn {
+            group_id: GroupId::from_slice(&[5, 5, 5]),
+            epoch: GroupEpoch::from(1),
+            sender: Sender::build_member(LeafNodeIndex::new(0)),
+            authenticated_data: vec![].into(),
+            body: FramedContentBodyIn::Application(vec![4, 5, 6].into()),
+        },
+        serialized_context: Some(serialized_context.clone()),
+    };
+
+    let tbs = FramedContentTbs::from(tbs_in);
+    assert!(Arc::ptr_eq(
+        tbs.serialized_context.as_ref().unwrap(),
+        &serialized_context
+    ));
+}
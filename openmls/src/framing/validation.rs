@@ -23,6 +23,8 @@
 //! ```
 // TODO #106/#151: Update the above diagram
 
+use std::sync::Arc;
+
 use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
 use proposal_store::QueuedProposal;
 
@@ -60,7 +62,7 @@ impl DecryptedMessage {
     pub(crate) fn from_inbound_public_message<'a>(
         public_message: PublicMessageIn,
         message_secrets_option: impl Into<Option<&'a MessageSecrets>>,
-        serialized_context: Vec<u8>,
+        serialized_context: Arc<[u8]>,
         crypto: &impl OpenMlsCrypto,
         ciphersuite: Ciphersuite,
     ) -> Result<Self, ValidationError> {
@@ -236,20 +238,24 @@ impl UnverifiedMessage {
     }
 
     /// Verify the [`UnverifiedMessage`]. Returns the [`AuthenticatedContent`]
-    /// and the internal [`Credential`].
+    /// and the [`CredentialWithKey`] that was used to verify it.
     pub(crate) fn verify(
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
         protocol_version: ProtocolVersion,
-    ) -> Result<(AuthenticatedContent, Credential), ValidationError> {
+    ) -> Result<(AuthenticatedContent, CredentialWithKey), ValidationError> {
         let content: AuthenticatedContentIn = self
             .verifiable_content
             .verify(crypto, &self.sender_pk)
             .map_err(|_| ValidationError::InvalidSignature)?;
         let content =
             content.validate(ciphersuite, crypto, self.sender_context, protocol_version)?;
-        Ok((content, self.credential))
+        let credential_with_key = CredentialWithKey {
+            credential: self.credential,
+            signature_key: self.sender_pk.into(),
+        };
+        Ok((content, credential_with_key))
     }
 
     /// Get the content type of the message.
@@ -267,6 +273,8 @@ pub struct ProcessedMessage {
     authenticated_data: Vec<u8>,
     content: ProcessedMessageContent,
     credential: Credential,
+    signature_key: SignaturePublicKey,
+    identity_changes: Vec<IdentityChanged>,
 }
 
 impl ProcessedMessage {
@@ -277,7 +285,7 @@ impl ProcessedMessage {
         sender: Sender,
         authenticated_data: Vec<u8>,
         content: ProcessedMessageContent,
-        credential: Credential,
+        credential_with_key: CredentialWithKey,
     ) -> Self {
         Self {
             group_id,
@@ -285,10 +293,17 @@ impl ProcessedMessage {
             sender,
             authenticated_data,
             content,
-            credential,
+            credential: credential_with_key.credential,
+            signature_key: credential_with_key.signature_key,
+            identity_changes: Vec::new(),
         }
     }
 
+    /// Set the identity changes contained in the message.
+    pub(crate) fn set_identity_changes(&mut self, identity_changes: Vec<IdentityChanged>) {
+        self.identity_changes = identity_changes;
+    }
+
     /// Returns the group ID of the message.
     pub fn group_id(&self) -> &GroupId {
         &self.group_id
@@ -304,6 +319,12 @@ impl ProcessedMessage {
         &self.sender
     }
 
+    /// Returns the leaf index of the sender if the sender is a member of the
+    /// group.
+    pub fn sender_leaf_index(&self) -> Option<LeafNodeIndex> {
+        self.sender.as_member()
+    }
+
     /// Returns the additional authenticated data (AAD) of the message.
     pub fn aad(&self) -> &[u8] {
         &self.authenticated_data
@@ -319,10 +340,68 @@ impl ProcessedMessage {
         self.content
     }
 
-    /// Returns the credential of the message.
+    /// Returns the credential of the sender of the message.
+    ///
+    /// This is the credential that belongs to the signature key the message
+    /// was verified with, as it was in the epoch of the message. It does not
+    /// have to be looked up in the group again, which could yield a different
+    /// credential if the sender was updated since.
     pub fn credential(&self) -> &Credential {
         &self.credential
     }
+
+    /// Returns the signature key the message was verified with.
+    pub fn signature_key(&self) -> &SignaturePublicKey {
+        &self.signature_key
+    }
+
+    /// Returns the members whose credential is changed by the Update
+    /// proposals or the update path contained in the message.
+    ///
+    /// Applications should check that the new credentials belong to the same
+    /// identity, since a silent identity swap would otherwise go unnoticed.
+    pub fn identity_changes(&self) -> &[IdentityChanged] {
+        &self.identity_changes
+    }
+}
+
+/// A change of the credential of a member by an Update proposal or the update
+/// path of a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityChanged {
+    leaf_index: LeafNodeIndex,
+    old_credential: Credential,
+    new_credential: Credential,
+}
+
+impl IdentityChanged {
+    /// Create a new [`IdentityChanged`].
+    pub(crate) fn new(
+        leaf_index: LeafNodeIndex,
+        old_credential: Credential,
+        new_credential: Credential,
+    ) -> Self {
+        Self {
+            leaf_index,
+            old_credential,
+            new_credential,
+        }
+    }
+
+    /// Returns the leaf index of the member.
+    pub fn leaf_index(&self) -> LeafNodeIndex {
+        self.leaf_index
+    }
+
+    /// Returns the current credential of the member.
+    pub fn old_credential(&self) -> &Credential {
+        &self.old_credential
+    }
+
+    /// Returns the new credential of the member.
+    pub fn new_credential(&self) -> &Credential {
+        &self.new_credential
+    }
 }
 
 /// Content of a processed message.
@@ -5,15 +5,15 @@
 use thiserror::Error;
 
 pub use super::mls_group::errors::*;
-use super::public_group::errors::CreationFromExternalError;
+use super::{public_group::errors::CreationFromExternalError, GroupEpoch};
 use crate::{
     ciphersuite::signable::SignatureError,
     error::LibraryError,
-    extensions::errors::{ExtensionError, InvalidExtensionError},
+    extensions::errors::{ApplicationExtensionError, ExtensionError, InvalidExtensionError},
     framing::errors::MessageDecryptionError,
     key_packages::errors::{KeyPackageExtensionSupportError, KeyPackageVerifyError},
     messages::{group_info::GroupInfoError, GroupSecretsError},
-    schedule::errors::PskError,
+    schedule::{errors::PskError, PreSharedKeyId},
     treesync::errors::*,
 };
 
@@ -32,6 +32,10 @@ pub enum WelcomeError<StorageError> {
     /// Ciphersuites in Welcome and key package bundle don't match.
     #[error("Ciphersuites in Welcome and key package bundle don't match.")]
     CiphersuiteMismatch,
+    /// Ciphersuites in Welcome and the group context of the group info don't
+    /// match.
+    #[error("Ciphersuites in Welcome and the group context of the group info don't match.")]
+    GroupInfoCiphersuiteMismatch,
     /// See [`GroupInfoError`] for more details.
     #[error(transparent)]
     GroupInfo(#[from] GroupInfoError),
@@ -41,6 +45,9 @@ pub enum WelcomeError<StorageError> {
     /// No ratchet tree available to build initial tree after receiving a Welcome message.
     #[error("No ratchet tree available to build initial tree after receiving a Welcome message.")]
     MissingRatchetTree,
+    /// The ratchet tree extension in the Welcome message is invalid.
+    #[error("The ratchet tree extension in the Welcome message is invalid.")]
+    InvalidRatchetTree,
     /// The computed confirmation tag does not match the expected one.
     #[error("The computed confirmation tag does not match the expected one.")]
     ConfirmationTagMismatch,
@@ -53,6 +60,12 @@ pub enum WelcomeError<StorageError> {
     /// We don't support all capabilities of the group.
     #[error("We don't support all capabilities of the group.")]
     UnsupportedCapability,
+    /// Our key package does not support all capabilities required by the
+    /// group. A key package with suitable capabilities has to be used instead.
+    /// The key package the Welcome is addressed to is not consumed in this
+    /// case.
+    #[error("Our key package does not support all capabilities required by the group: {0:?}")]
+    IncompatibleKeyPackage(MissingCapabilities),
     /// Sender not found in tree.
     #[error("Sender not found in tree.")]
     UnknownSender,
@@ -71,6 +84,11 @@ pub enum WelcomeError<StorageError> {
     /// See [`PskError`] for more details.
     #[error(transparent)]
     Psk(#[from] PskError),
+    /// The PSKs required to join the group are not available. They have to be
+    /// stored before processing the Welcome message again. The key package
+    /// the Welcome is addressed to is not consumed in this case.
+    #[error("The PSKs required to join the group are not available: {0:?}")]
+    MissingPsks(Vec<PreSharedKeyId>),
     /// No matching encryption key was found in the key store.
     #[error("No matching encryption key was found in the key store.")]
     NoMatchingEncryptionKey,
@@ -87,6 +105,24 @@ pub enum WelcomeError<StorageError> {
     /// This error indicates the leaf node is invalid. See [`LeafNodeValidationError`] for more details.
     #[error(transparent)]
     LeafNodeValidation(#[from] LeafNodeValidationError),
+    /// We are already a member of a group with the same group ID in storage.
+    /// The key package the Welcome is addressed to is not consumed in this
+    /// case.
+    #[error("We are already a member of a group with the same group ID in storage.")]
+    GroupAlreadyExists,
+    /// The lifetime of the key package the Welcome is addressed to is not
+    /// valid. The key package is not consumed in this case.
+    #[error("The lifetime of the key package the Welcome is addressed to is not valid.")]
+    KeyPackageExpired,
+    /// The epoch of the group in the Welcome is older than the minimum epoch
+    /// expected by the application.
+    #[error("The epoch {epoch:?} of the group in the Welcome is older than the expected minimum epoch {min_epoch:?}.")]
+    StaleEpoch {
+        /// The epoch of the group in the Welcome.
+        epoch: GroupEpoch,
+        /// The minimum epoch expected by the application.
+        min_epoch: GroupEpoch,
+    },
     /// This error indicates that an error occurred while reading or writing from/to storage.
     #[error("An error occurred when querying storage")]
     StorageError(StorageError),
@@ -101,6 +137,9 @@ pub enum ExternalCommitError<StorageError> {
     /// No ratchet tree available to build initial tree.
     #[error("No ratchet tree available to build initial tree.")]
     MissingRatchetTree,
+    /// The ratchet tree extension is invalid.
+    #[error("The ratchet tree extension is invalid.")]
+    InvalidRatchetTree,
     /// No external_pub extension available to join group by external commit.
     #[error("No external_pub extension available to join group by external commit.")]
     MissingExternalPub,
@@ -161,6 +200,9 @@ pub enum StageCommitError {
     /// The proposal queue is missing a proposal for the commit.
     #[error("The proposal queue is missing a proposal for the commit.")]
     MissingProposal,
+    /// The Commit contains the same proposal more than once.
+    #[error("The Commit contains the same proposal more than once.")]
+    DuplicateProposal,
     /// Missing own key to apply proposal.
     #[error("Missing own key to apply proposal.")]
     OwnKeyNotFound,
@@ -399,6 +441,10 @@ pub enum ProposalValidationError {
     /// Regular Commits may not contain ExternalInit proposals, but one was found
     #[error("Found ExternalInit proposal in regular commit")]
     ExternalInitProposalInRegularCommit,
+    /// An application extension of the key package of an add proposal is
+    /// invalid.
+    #[error("An application extension of the key package of an add proposal is invalid: {0}")]
+    InvalidApplicationExtension(ApplicationExtensionError),
 }
 
 /// External Commit validaton error
@@ -475,6 +521,9 @@ pub(crate) enum FromCommittedProposalsError {
     /// The sender of a Commit tried to remove themselves.
     #[error("The sender of a Commit tried to remove themselves.")]
     SelfRemoval,
+    /// The Commit contains the same proposal more than once.
+    #[error("The Commit contains the same proposal more than once.")]
+    DuplicateProposal,
 }
 
 /// Create group context ext proposal error
@@ -543,10 +592,26 @@ pub enum GroupContextExtensionsProposalValidationError {
     )]
     RequiredExtensionNotSupportedByAllMembers,
 
+    /// The new required capabilities contain proposal types that are not supported by all group members.
+    #[error(
+        "The new required capabilities contain proposal types that are not supported by all group members."
+    )]
+    RequiredProposalNotSupportedByAllMembers,
+
+    /// The new required capabilities contain credential types that are not supported by all group members.
+    #[error(
+        "The new required capabilities contain credential types that are not supported by all group members."
+    )]
+    RequiredCredentialNotSupportedByAllMembers,
+
     /// An extension in the group context extensions is not listed in the required capabilties'
     /// extension types.
     #[error(
         "An extension in the group context extensions is not listed in the required capabilties' extension types."
     )]
     ExtensionNotInRequiredCapabilities,
+
+    /// An application extension in the group context extensions is invalid.
+    #[error("An application extension in the group context extensions is invalid: {0}")]
+    InvalidApplicationExtension(ApplicationExtensionError),
 }
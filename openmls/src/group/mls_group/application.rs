@@ -13,39 +13,127 @@ impl MlsGroup {
     /// Returns `CreateMessageError::MlsGroupStateError::PendingProposal` if pending proposals
     /// exist. In that case `.process_pending_proposals()` must be called first
     /// and incoming messages from the DS must be processed afterwards.
+    /// Returns `CreateMessageError::MlsGroupStateError::UpdateRequired` if the
+    /// group has been imported from another device and no own commit has been
+    /// merged since.
+    /// Returns `CreateMessageError::MessageTooLarge` if the message exceeds the
+    /// maximum application message size configured for the group.
+    /// Returns `CreateMessageError::MissingAad` if the group requires AAD on
+    /// application messages and none was set with [`MlsGroup::set_aad()`].
     pub fn create_message<Provider: OpenMlsProvider>(
         &mut self,
         provider: &Provider,
         signer: &impl Signer,
         message: &[u8],
     ) -> Result<MlsMessageOut, CreateMessageError> {
+        let authenticated_content = self.application_content(signer, message)?;
+        let ciphertext = self
+            .encrypt(authenticated_content, provider)
+            // We know the application message is wellformed and we have the key material of the current epoch
+            .map_err(|_| LibraryError::custom("Malformed plaintext"))?;
+
+        self.reset_aad();
+        Ok(MlsMessageOut::from_private_message(
+            ciphertext,
+            self.version(),
+        ))
+    }
+
+    /// Creates an application message like [`MlsGroup::create_message()`], but
+    /// uses the given reuse guard and exactly `padding_length` bytes of padding
+    /// instead of a random reuse guard and the group's padding size.
+    ///
+    /// With a deterministic signature scheme (e.g. Ed25519), the resulting
+    /// ciphertext only depends on the inputs and the group state. This allows
+    /// comparing it byte by byte with the output of other implementations.
+    ///
+    /// **WARNING**: Reusing a reuse guard defeats its purpose. This must only
+    /// be used for testing.
+    #[cfg(any(feature = "test-utils", test))]
+    pub fn create_message_deterministic<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        signer: &impl Signer,
+        message: &[u8],
+        reuse_guard: [u8; 4],
+        padding_length: usize,
+    ) -> Result<MlsMessageOut, CreateMessageError> {
+        let authenticated_content = self.application_content(signer, message)?;
+        let ciphertext = PrivateMessage::encrypt_deterministic::<Provider::StorageError>(
+            provider.crypto(),
+            &authenticated_content,
+            self.ciphersuite(),
+            self.message_secrets_store.message_secrets_mut(),
+            reuse_guard,
+            padding_length,
+        )
+        .map_err(|_| LibraryError::custom("Malformed plaintext"))?;
+        provider
+            .storage()
+            .write_message_secrets(self.group_id(), &self.message_secrets_store)
+            .map_err(|_| LibraryError::custom("Error writing message secrets"))?;
+
+        self.reset_aad();
+        Ok(MlsMessageOut::from_private_message(
+            ciphertext,
+            self.version(),
+        ))
+    }
+
+    /// Checks that an application message can be sent and creates its
+    /// [`AuthenticatedContent`].
+    fn application_content(
+        &self,
+        signer: &impl Signer,
+        message: &[u8],
+    ) -> Result<AuthenticatedContent, CreateMessageError> {
         if !self.is_active() {
             return Err(CreateMessageError::GroupStateError(
                 MlsGroupStateError::UseAfterEviction,
             ));
         }
+        if matches!(self.group_state, MlsGroupState::UpdateRequired) {
+            return Err(CreateMessageError::GroupStateError(
+                MlsGroupStateError::UpdateRequired,
+            ));
+        }
         if !self.proposal_store().is_empty() {
             return Err(CreateMessageError::GroupStateError(
                 MlsGroupStateError::PendingProposal,
             ));
         }
+        if let Some(max_size) = self.mls_group_config.max_application_message_size {
+            if message.len() > max_size {
+                return Err(CreateMessageError::MessageTooLarge);
+            }
+        }
+        if self.mls_group_config.require_aad && self.aad.is_empty() {
+            return Err(CreateMessageError::MissingAad);
+        }
 
-        let authenticated_content = AuthenticatedContent::new_application(
+        Ok(AuthenticatedContent::new_application(
             self.own_leaf_index(),
             &self.aad,
             message,
             self.context(),
             signer,
-        )?;
-        let ciphertext = self
-            .encrypt(authenticated_content, provider)
-            // We know the application message is wellformed and we have the key material of the current epoch
-            .map_err(|_| LibraryError::custom("Malformed plaintext"))?;
+        )?)
+    }
 
+    /// Creates an application message with the given additional authenticated
+    /// data (AAD). This is equivalent to calling [`MlsGroup::set_aad()`]
+    /// followed by [`MlsGroup::create_message()`].
+    pub fn create_message_with_aad<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        signer: &impl Signer,
+        message: &[u8],
+        aad: Vec<u8>,
+    ) -> Result<MlsMessageOut, CreateMessageError> {
+        self.set_aad(aad);
+        let result = self.create_message(provider, signer, message);
+        // Don't let the AAD leak into the next message if creation failed.
         self.reset_aad();
-        Ok(MlsMessageOut::from_private_message(
-            ciphertext,
-            self.version(),
-        ))
+        result
     }
 }
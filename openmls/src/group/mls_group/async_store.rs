@@ -0,0 +1,110 @@
+//! # Group operations with asynchronous key stores
+//!
+//! The group operations of OpenMLS access the storage synchronously. To use
+//! an [`OpenMlsKeyStoreAsync`], e.g. a remote KMS, IndexedDB or a network HSM,
+//! without blocking the executor, the operations run on a [`MemoryStorage`]
+//! that stages the values of the key store:
+//!
+//! * [`MlsGroup::load_async()`] fetches the state of a group and its key pairs
+//!   from the key store and loads the group.
+//! * [`MlsGroup::with_async_store()`] runs any group operation, e.g.
+//!   [`MlsGroup::process_message()`] or [`MlsGroup::commit_builder()`], on the
+//!   staged values and flushes the changes to the key store afterwards.
+//!
+//! Values that are needed by other operations, e.g. the key package when
+//! joining a group from a welcome, are fetched with the `fetch_*` functions of
+//! the [`MemoryStorage`], and the changes are written to the key store with
+//! [`MemoryStorage::flush()`].
+
+use openmls_memory_storage::{async_store::AsyncStoreError, MemoryStorage};
+use openmls_traits::key_store_async::OpenMlsKeyStoreAsync;
+
+use super::{errors::AsyncOperationError, *};
+
+impl MlsGroup {
+    /// Fetches the state of the group with the `group_id` and its key pairs
+    /// from the `store` into the storage of the `provider` and loads the
+    /// group.
+    ///
+    /// Returns `None` if the group is not in the `store`.
+    pub async fn load_async<Provider, Store>(
+        provider: &Provider,
+        store: &Store,
+        group_id: &GroupId,
+    ) -> Result<Option<MlsGroup>, AsyncStoreError<Store::Error>>
+    where
+        Provider: openmls_traits::OpenMlsProvider<StorageProvider = MemoryStorage>,
+        Store: OpenMlsKeyStoreAsync,
+    {
+        let storage: &Provider::StorageProvider = provider.storage();
+        storage.fetch_group(store, group_id).await?;
+        let Some(group) =
+            MlsGroup::load(storage, group_id).map_err(|_| AsyncStoreError::SerializationError)?
+        else {
+            return Ok(None);
+        };
+        group.fetch_key_pairs(storage, store).await?;
+
+        Ok(Some(group))
+    }
+
+    /// Runs the `operation` on this group with the values of the `store`
+    /// staged in the storage of the `provider`, and flushes the values the
+    /// operation changed to the `store` afterwards, also if the operation
+    /// fails.
+    ///
+    /// The key pairs of the current epoch are fetched before the operation,
+    /// such that the group doesn't have to be loaded with
+    /// [`MlsGroup::load_async()`] again after creating or joining it. PSKs
+    /// that the operation needs have to be fetched with
+    /// [`MemoryStorage::fetch_psk()`].
+    ///
+    /// ```ignore
+    /// let processed_message = group
+    ///     .with_async_store(provider, &store, |group, provider| {
+    ///         group.process_message(provider, message)
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn with_async_store<Provider, Store, T, E>(
+        &mut self,
+        provider: &Provider,
+        store: &Store,
+        operation: impl FnOnce(&mut MlsGroup, &Provider) -> Result<T, E>,
+    ) -> Result<T, AsyncOperationError<E, Store::Error>>
+    where
+        Provider: openmls_traits::OpenMlsProvider<StorageProvider = MemoryStorage>,
+        Store: OpenMlsKeyStoreAsync,
+    {
+        let storage: &Provider::StorageProvider = provider.storage();
+        self.fetch_key_pairs(storage, store).await?;
+
+        let result = operation(self, provider);
+        storage.flush(store).await?;
+
+        result.map_err(AsyncOperationError::Operation)
+    }
+
+    /// Fetches the encryption key pairs of the own leaf nodes and of the
+    /// current epoch from the `store`.
+    async fn fetch_key_pairs<Store: OpenMlsKeyStoreAsync>(
+        &self,
+        storage: &MemoryStorage,
+        store: &Store,
+    ) -> Result<(), AsyncStoreError<Store::Error>> {
+        let own_leaf_nodes = self.own_leaf_nodes.iter().chain(self.own_leaf_node());
+        for leaf_node in own_leaf_nodes {
+            storage
+                .fetch_encryption_key_pair(store, leaf_node.encryption_key())
+                .await?;
+        }
+        storage
+            .fetch_encryption_epoch_key_pairs(
+                store,
+                self.group_id(),
+                &self.context().epoch(),
+                self.own_leaf_index().u32(),
+            )
+            .await
+    }
+}
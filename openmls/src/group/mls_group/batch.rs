@@ -0,0 +1,171 @@
+//! Batch processing of incoming messages
+//!
+//! A client that was offline receives the messages of several epochs at once.
+//! [`MlsGroup::process_messages()`] processes such a batch epoch by epoch: the
+//! proposals of an epoch first, then its application messages and finally its
+//! commits. Proposals are stored as pending proposals and the commits that the
+//! application approves are merged right away, such that the messages of the
+//! next epoch can be processed.
+//!
+//! Decrypting a private message updates the message secrets of the group.
+//! Instead of writing them to the storage after every message, they are
+//! written once with the merge of a commit and once at the end of the batch.
+
+use openmls_traits::storage::StorageProvider as _;
+
+use super::{
+    errors::{ProcessBatchError, ProcessMessageError},
+    *,
+};
+use crate::storage::OpenMlsProvider;
+
+/// A message of a batch processed with [`MlsGroup::process_messages()`].
+#[derive(Debug)]
+pub enum ProcessedBatchMessage {
+    /// An application message, a proposal or a commit that was not approved.
+    /// Proposals were already stored as pending proposals of the group. The
+    /// [`StagedCommit`] of a commit that was not approved can still be merged
+    /// with [`MlsGroup::merge_staged_commit()`].
+    Processed(Box<ProcessedMessage>),
+    /// A commit that was approved and merged into the group.
+    MergedCommit {
+        /// The sender of the commit.
+        sender: Sender,
+        /// The epoch of the group after the commit was merged.
+        epoch: GroupEpoch,
+    },
+}
+
+/// The results of the messages of a batch processed with
+/// [`MlsGroup::process_messages()`], in the order of the batch.
+pub type ProcessedBatch<StorageError> =
+    Vec<Result<ProcessedBatchMessage, ProcessBatchError<StorageError>>>;
+
+/// The position of messages of the given content type within an epoch.
+fn processing_rank(content_type: ContentType) -> u8 {
+    match content_type {
+        ContentType::Proposal => 0,
+        ContentType::Application => 1,
+        ContentType::Commit => 2,
+    }
+}
+
+impl MlsGroup {
+    /// Processes a batch of incoming messages, e.g. the messages a client
+    /// receives when it comes back online.
+    ///
+    /// The messages are processed in the order of their epochs. Within an
+    /// epoch, the proposals are processed first, then the application messages
+    /// and finally the commits, each in the order of the batch. Proposals are
+    /// stored as pending proposals of the group. `approve_commit` is called
+    /// for every valid commit before it is merged, such that the application
+    /// can inspect it like with [`MlsGroup::process_message()`]. The first
+    /// approved commit of an epoch is merged and further commits of the same
+    /// epoch fail. If a commit is not approved, the group stays in its epoch
+    /// and the messages of the later epochs fail.
+    ///
+    /// Returns the result of every message in the order of the batch. A
+    /// message that fails doesn't stop the processing of the other messages.
+    ///
+    /// # Errors:
+    /// Returns a [`ProcessBatchError::StorageError`] together with the results
+    /// of the messages if the updated message secrets can't be written to the
+    /// storage at the end of the batch. The secrets used to decrypt the
+    /// messages are consumed in the group nevertheless, such that the results
+    /// can't be obtained again by processing the messages another time.
+    #[allow(clippy::type_complexity)]
+    pub fn process_messages<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        messages: impl IntoIterator<Item = MlsMessageIn>,
+        mut approve_commit: impl FnMut(&StagedCommit) -> bool,
+    ) -> Result<
+        ProcessedBatch<Provider::StorageError>,
+        (
+            ProcessedBatch<Provider::StorageError>,
+            ProcessBatchError<Provider::StorageError>,
+        ),
+    > {
+        let mut results = Vec::new();
+        let mut batch = Vec::new();
+        for (index, message) in messages.into_iter().enumerate() {
+            match message.try_into_protocol_message() {
+                Ok(message) => batch.push((index, message)),
+                Err(_) => results.push((index, Err(ProcessBatchError::NotAProtocolMessage))),
+            }
+        }
+
+        // The sort is stable and keeps the order of the batch within an epoch
+        // and content type.
+        batch
+            .sort_by_key(|(_, message)| (message.epoch(), processing_rank(message.content_type())));
+
+        let mut message_secrets_changed = false;
+        for (index, message) in batch {
+            message_secrets_changed |= matches!(message, ProtocolMessage::PrivateMessage(_));
+            let result = self.process_batch_message(provider, message, &mut approve_commit);
+            if matches!(result, Ok(ProcessedBatchMessage::MergedCommit { .. })) {
+                // Merging wrote the message secrets.
+                message_secrets_changed = false;
+            }
+            results.push((index, result));
+        }
+
+        results.sort_by_key(|(index, _)| *index);
+        let results = results.into_iter().map(|(_, result)| result).collect();
+
+        if message_secrets_changed {
+            if let Err(e) = provider
+                .storage()
+                .write_message_secrets(self.group_id(), &self.message_secrets_store)
+            {
+                return Err((results, ProcessBatchError::StorageError(e)));
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Processes a single message of a batch and stores proposals or merges
+    /// approved commits.
+    fn process_batch_message<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        message: ProtocolMessage,
+        approve_commit: &mut impl FnMut(&StagedCommit) -> bool,
+    ) -> Result<ProcessedBatchMessage, ProcessBatchError<Provider::StorageError>> {
+        let processed_message = self.process_protocol_message(provider, message, false, None)?;
+
+        match processed_message.content() {
+            ProcessedMessageContent::ApplicationMessage(_) => {}
+            ProcessedMessageContent::ProposalMessage(proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(proposal) => {
+                self.store_pending_proposal(provider.storage(), (**proposal).clone())
+                    .map_err(ProcessBatchError::StorageError)?;
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit)
+                if approve_commit(staged_commit) =>
+            {
+                let sender = processed_message.sender().clone();
+                let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+                    processed_message.into_content()
+                else {
+                    return Err(ProcessMessageError::from(LibraryError::custom(
+                        "Expected a staged commit",
+                    ))
+                    .into());
+                };
+                self.merge_staged_commit(provider, *staged_commit)?;
+                return Ok(ProcessedBatchMessage::MergedCommit {
+                    sender,
+                    epoch: self.epoch(),
+                });
+            }
+            ProcessedMessageContent::StagedCommitMessage(_) => {}
+        }
+
+        Ok(ProcessedBatchMessage::Processed(Box::new(
+            processed_message,
+        )))
+    }
+}
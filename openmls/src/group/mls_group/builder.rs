@@ -3,6 +3,7 @@ use tls_codec::Serialize;
 
 use crate::{
     binary_tree::array_representation::TreeSize,
+    ciphersuite::CiphersuiteRegistry,
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::{errors::InvalidExtensionError, Extensions},
@@ -10,7 +11,7 @@ use crate::{
         public_group::errors::PublicGroupBuildError, GroupId, MlsGroupCreateConfig,
         MlsGroupCreateConfigBuilder, NewGroupError, PublicGroup, WireFormatPolicy,
     },
-    key_packages::Lifetime,
+    key_packages::{Lifetime, LifetimePolicy},
     prelude::LeafNodeIndex,
     schedule::{
         psk::{load_psks, store::ResumptionPskStore, PskSecret},
@@ -70,6 +71,15 @@ impl MlsGroupBuilder {
             .unwrap_or_else(|| GroupId::random(provider.rand()));
         let ciphersuite = mls_group_create_config.ciphersuite;
 
+        let registry = CiphersuiteRegistry::new(provider.crypto());
+        if !registry.supports(ciphersuite) {
+            return Err(NewGroupError::UnsupportedCiphersuite);
+        }
+        let capabilities = mls_group_create_config
+            .capabilities
+            .clone()
+            .unwrap_or_else(|| registry.default_capabilities());
+
         let (public_group_builder, commit_secret, leaf_keypair) =
             PublicGroup::builder(group_id, ciphersuite, credential_with_key)
                 .with_group_context_extensions(
@@ -77,7 +87,7 @@ impl MlsGroupBuilder {
                 )?
                 .with_leaf_node_extensions(mls_group_create_config.leaf_node_extensions.clone())?
                 .with_lifetime(*mls_group_create_config.lifetime())
-                .with_capabilities(mls_group_create_config.capabilities.clone())
+                .with_capabilities(capabilities)
                 .get_secrets(provider, signer)
                 .map_err(|e| match e {
                     PublicGroupBuildError::LibraryError(e) => NewGroupError::LibraryError(e),
@@ -139,9 +149,10 @@ impl MlsGroupBuilder {
             message_secrets,
         );
 
-        let public_group = public_group_builder
+        let mut public_group = public_group_builder
             .with_confirmation_tag(initial_confirmation_tag)
             .build(provider.crypto())?;
+        public_group.set_lifetime_policy(mls_group_create_config.join_config.lifetime_policy);
 
         // We already add a resumption PSK for epoch 0 to make things more unified.
         let resumption_psk = group_epoch_secrets.resumption_psk();
@@ -204,6 +215,61 @@ impl MlsGroupBuilder {
         self
     }
 
+    /// Sets the `max_application_message_size` property of the MlsGroup.
+    pub fn max_application_message_size(mut self, max_application_message_size: usize) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .max_application_message_size(max_application_message_size);
+        self
+    }
+
+    /// Sets the `require_aad` property of the MlsGroup.
+    pub fn require_aad(mut self, require_aad: bool) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .require_aad(require_aad);
+        self
+    }
+
+    /// Sets the `reject_identity_changes` property of the MlsGroup.
+    pub fn reject_identity_changes(mut self, reject_identity_changes: bool) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .reject_identity_changes(reject_identity_changes);
+        self
+    }
+
+    /// Sets the `lifetime_policy` property of the MlsGroup.
+    pub fn lifetime_policy(mut self, lifetime_policy: LifetimePolicy) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .lifetime_policy(lifetime_policy);
+        self
+    }
+
+    /// Sets the `max_ratchet_tree_extension_size` property of the MlsGroup.
+    pub fn max_ratchet_tree_extension_size(
+        mut self,
+        max_ratchet_tree_extension_size: usize,
+    ) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .max_ratchet_tree_extension_size(max_ratchet_tree_extension_size);
+        self
+    }
+
+    /// Sets the `compress_ratchet_tree_extension` property of the MlsGroup.
+    #[cfg(feature = "ratchet-tree-compression")]
+    pub fn compress_ratchet_tree_extension(
+        mut self,
+        compress_ratchet_tree_extension: bool,
+    ) -> Self {
+        self.mls_group_create_config_builder = self
+            .mls_group_create_config_builder
+            .compress_ratchet_tree_extension(compress_ratchet_tree_extension);
+        self
+    }
+
     /// Sets the `number_of_resumption_psks` property of the MlsGroup.
     pub fn number_of_resumption_psks(mut self, number_of_resumption_psks: usize) -> Self {
         self.mls_group_create_config_builder = self
@@ -0,0 +1,302 @@
+//! Chunking of application messages
+//!
+//! Delivery services often impose a limit on the size of the messages they
+//! relay. This module contains helpers to split a large application payload
+//! into multiple application messages and to reassemble it on the receiving
+//! side.
+//!
+//! Each chunk is carried as the application data of a regular application
+//! message and is encoded as follows:
+//!
+//! ```text
+//! struct {
+//!     uint64 message_id;
+//!     uint16 index;
+//!     uint16 count;
+//!     opaque data<V>;
+//! } ApplicationChunk;
+//! ```
+//!
+//! The `message_id` is chosen randomly by the sender and is shared by all
+//! chunks of the same payload. Chunks are reassembled per sender and message
+//! id with a [`ChunkReassembler`].
+//!
+//! Because the chunks of a message may never all arrive, the reassembler
+//! bounds the number of incomplete messages and the bytes they hold. Once a
+//! bound is reached, the incomplete messages that were started first are
+//! evicted.
+
+use std::collections::{BTreeMap, HashMap};
+
+use openmls_traits::{random::OpenMlsRand, signatures::Signer};
+use tls_codec::{
+    DeserializeBytes as _, Serialize as _, TlsDeserializeBytes, TlsSerialize, TlsSize, VLBytes,
+};
+
+use super::{
+    errors::{ChunkReassemblyError, CreateMessageError},
+    *,
+};
+use crate::storage::OpenMlsProvider;
+
+/// A single chunk of a chunked application message.
+#[derive(Debug, Clone, PartialEq, Eq, TlsSerialize, TlsDeserializeBytes, TlsSize)]
+pub(crate) struct ApplicationChunk {
+    pub(crate) message_id: u64,
+    pub(crate) index: u16,
+    pub(crate) count: u16,
+    pub(crate) data: VLBytes,
+}
+
+impl MlsGroup {
+    /// Splits `message` into chunks of at most `chunk_size` bytes and creates
+    /// one application message per chunk. The chunks can be reassembled by the
+    /// receivers using a [`ChunkReassembler`].
+    ///
+    /// Note that `chunk_size` only covers the payload of each chunk. Every chunk
+    /// carries an additional 13 to 16 bytes of framing that count towards the
+    /// maximum application message size of the group.
+    ///
+    /// The additional authenticated data set with [`MlsGroup::set_aad()`] is
+    /// included in every chunk.
+    ///
+    /// Returns `CreateMessageError::InvalidChunkSize` if `chunk_size` is zero
+    /// and `CreateMessageError::TooManyChunks` if the message would require
+    /// more than `u16::MAX` chunks. Otherwise, the same errors as
+    /// [`MlsGroup::create_message()`] can be returned.
+    pub fn create_chunked_message<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        signer: &impl Signer,
+        message: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<MlsMessageOut>, CreateMessageError> {
+        if chunk_size == 0 {
+            return Err(CreateMessageError::InvalidChunkSize);
+        }
+        // An empty message is sent as a single, empty chunk.
+        let count = message.len().div_ceil(chunk_size).max(1);
+        let count = u16::try_from(count).map_err(|_| CreateMessageError::TooManyChunks)?;
+        let message_id = u64::from_be_bytes(
+            provider
+                .rand()
+                .random_array()
+                .map_err(|_| LibraryError::custom("Not enough randomness."))?,
+        );
+
+        let aad = self.aad.clone();
+        let mut messages = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let start = index as usize * chunk_size;
+            let end = (start + chunk_size).min(message.len());
+            let chunk = ApplicationChunk {
+                message_id,
+                index,
+                count,
+                data: VLBytes::from(&message[start..end]),
+            };
+            let chunk = chunk
+                .tls_serialize_detached()
+                .map_err(LibraryError::missing_bound_check)?;
+            self.set_aad(aad.clone());
+            messages.push(self.create_message(provider, signer, &chunk)?);
+        }
+
+        Ok(messages)
+    }
+}
+
+#[derive(Debug)]
+struct PartialMessage {
+    count: u16,
+    size: usize,
+    /// The order in which the first chunks of the pending messages arrived.
+    sequence: u64,
+    chunks: BTreeMap<u16, Vec<u8>>,
+}
+
+/// Reassembles application messages created with
+/// [`MlsGroup::create_chunked_message()`].
+///
+/// Chunks are collected per sender and message. Once all chunks of a message
+/// have been received, the reassembled message is returned.
+///
+/// At most [`DEFAULT_MAX_PENDING_MESSAGES`](Self::DEFAULT_MAX_PENDING_MESSAGES)
+/// incomplete messages holding at most
+/// [`DEFAULT_MAX_PENDING_BYTES`](Self::DEFAULT_MAX_PENDING_BYTES) are kept by
+/// default. If a chunk exceeds
+/// one of these bounds, the incomplete messages that were started first are
+/// evicted.
+#[derive(Debug)]
+pub struct ChunkReassembler {
+    max_message_size: usize,
+    max_pending_messages: usize,
+    max_pending_bytes: usize,
+    pending_bytes: usize,
+    next_sequence: u64,
+    pending: HashMap<(LeafNodeIndex, u64), PartialMessage>,
+}
+
+impl ChunkReassembler {
+    /// The default of [`with_max_pending_messages()`](Self::with_max_pending_messages).
+    pub const DEFAULT_MAX_PENDING_MESSAGES: usize = 64;
+
+    /// The default of [`with_max_pending_bytes()`](Self::with_max_pending_bytes),
+    /// 16 MiB.
+    pub const DEFAULT_MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+
+    /// Creates a new [`ChunkReassembler`] that accepts reassembled messages of
+    /// at most `max_message_size` bytes.
+    pub fn new(max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            max_pending_messages: Self::DEFAULT_MAX_PENDING_MESSAGES,
+            max_pending_bytes: Self::DEFAULT_MAX_PENDING_BYTES,
+            pending_bytes: 0,
+            next_sequence: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Sets the maximum number of incomplete messages. A chunk of a new
+    /// message evicts the oldest incomplete message if there are as many
+    /// already. The limit is at least one.
+    pub fn with_max_pending_messages(mut self, max_pending_messages: usize) -> Self {
+        self.max_pending_messages = max_pending_messages.max(1);
+        self
+    }
+
+    /// Sets the maximum number of bytes held by incomplete messages. A chunk
+    /// that exceeds it evicts the oldest incomplete messages until the bytes
+    /// fit. A message that doesn't fit on its own is rejected like a message
+    /// that exceeds the maximum message size.
+    pub fn with_max_pending_bytes(mut self, max_pending_bytes: usize) -> Self {
+        self.max_pending_bytes = max_pending_bytes;
+        self
+    }
+
+    /// Processes the application data of a chunk sent by the member at leaf
+    /// index `sender`.
+    ///
+    /// Returns `Ok(Some(message))` if this was the last missing chunk of a
+    /// message and `Ok(None)` if more chunks are needed. If an error is
+    /// returned, all chunks received so far for the affected message are
+    /// discarded.
+    pub fn process_chunk(
+        &mut self,
+        sender: LeafNodeIndex,
+        application_data: &[u8],
+    ) -> Result<Option<Vec<u8>>, ChunkReassemblyError> {
+        let (chunk, remainder) = ApplicationChunk::tls_deserialize_bytes(application_data)
+            .map_err(|_| ChunkReassemblyError::MalformedChunk)?;
+        if !remainder.is_empty() || chunk.count == 0 || chunk.index >= chunk.count {
+            return Err(ChunkReassemblyError::MalformedChunk);
+        }
+
+        let key = (sender, chunk.message_id);
+        if !self.pending.contains_key(&key) {
+            while self.pending.len() >= self.max_pending_messages {
+                self.evict_oldest(key);
+            }
+            self.pending.insert(
+                key,
+                PartialMessage {
+                    count: chunk.count,
+                    size: 0,
+                    sequence: self.next_sequence,
+                    chunks: BTreeMap::new(),
+                },
+            );
+            self.next_sequence += 1;
+        }
+        let partial = self
+            .pending
+            .get_mut(&key)
+            .ok_or(ChunkReassemblyError::InconsistentChunk)?;
+
+        let max_message_size = self.max_message_size.min(self.max_pending_bytes);
+        match Self::add_chunk(partial, chunk, max_message_size) {
+            Ok(size) => self.pending_bytes += size,
+            Err(e) => {
+                self.remove(&key);
+                return Err(e);
+            }
+        }
+
+        if self.pending[&key].chunks.len() == self.pending[&key].count as usize {
+            let partial = self
+                .remove(&key)
+                .ok_or(ChunkReassemblyError::InconsistentChunk)?;
+            return Ok(Some(partial.chunks.into_values().flatten().collect()));
+        }
+
+        while self.pending_bytes > self.max_pending_bytes && self.evict_oldest(key) {}
+
+        Ok(None)
+    }
+
+    /// Discards all incomplete messages of the member at leaf index `sender`,
+    /// e.g. after the member was removed from the group.
+    pub fn discard_sender(&mut self, sender: LeafNodeIndex) {
+        self.pending
+            .retain(|(leaf_index, _), _| *leaf_index != sender);
+        self.pending_bytes = self.pending.values().map(|partial| partial.size).sum();
+    }
+
+    /// Returns the number of messages for which chunks are missing.
+    pub fn incomplete_messages(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the number of bytes held by the messages for which chunks are
+    /// missing.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    /// Removes the partial message and its bytes.
+    fn remove(&mut self, key: &(LeafNodeIndex, u64)) -> Option<PartialMessage> {
+        let partial = self.pending.remove(key)?;
+        self.pending_bytes -= partial.size;
+        Some(partial)
+    }
+
+    /// Evicts the oldest partial message other than the one with the given
+    /// `key`. Returns `false` if there is none.
+    fn evict_oldest(&mut self, key: (LeafNodeIndex, u64)) -> bool {
+        let oldest = self
+            .pending
+            .iter()
+            .filter(|(pending_key, _)| **pending_key != key)
+            .min_by_key(|(_, partial)| partial.sequence)
+            .map(|(pending_key, _)| *pending_key);
+        match oldest {
+            Some(oldest) => self.remove(&oldest).is_some(),
+            None => false,
+        }
+    }
+
+    /// Adds the chunk to the partial message. Returns the number of bytes
+    /// that were added.
+    fn add_chunk(
+        partial: &mut PartialMessage,
+        chunk: ApplicationChunk,
+        max_message_size: usize,
+    ) -> Result<usize, ChunkReassemblyError> {
+        if partial.count != chunk.count {
+            return Err(ChunkReassemblyError::InconsistentChunk);
+        }
+        if partial.chunks.contains_key(&chunk.index) {
+            return Err(ChunkReassemblyError::DuplicateChunk);
+        }
+        let data = chunk.data.as_slice().to_vec();
+        if partial.size + data.len() > max_message_size {
+            return Err(ChunkReassemblyError::MessageTooLarge);
+        }
+        let size = data.len();
+        partial.size += size;
+        partial.chunks.insert(chunk.index, data);
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,409 @@
+//! # Client state export
+//!
+//! To move a client to a new device, the complete MLS state of the client,
+//! i.e. its groups, signature key pairs and key packages, can be exported into
+//! a single encrypted bundle with [`ClientStateExport`] and imported on the
+//! new device with [`ClientStateImport::import()`].
+//!
+//! The bundle is encrypted with ChaCha20-Poly1305 under a 32 byte key supplied
+//! by the application, e.g. derived from a passphrase with a password hashing
+//! function. It starts with a header of the magic bytes `OMCS` and the format
+//! version as big-endian `u16`, followed by the random nonce and the
+//! ciphertext. The header is authenticated as additional data.
+//!
+//! **The old device must not send any messages to the exported groups after
+//! the export.** The new device continues with the same leaf, the same sender
+//! ratchets and the same reuse guards, so both devices would encrypt with the
+//! same key and nonce. The exported groups are therefore marked inactive in the
+//! storage of the old device, see [`MlsGroup::is_active()`], and the imported
+//! groups can't create application messages until the new device has merged an
+//! own commit, see [`MlsGroupState::UpdateRequired`].
+//!
+//! **Moving the state to another device invalidates the forward secrecy of all
+//! exported groups.** The bundle contains the secrets of the current epochs
+//! and the secrets kept for past epochs, and the old device keeps its copy
+//! until it deletes it. Applications should let the new device commit an
+//! update in every group right after the import, see
+//! [`ClientStateImport::forward_secrecy_invalidated()`].
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    random::OpenMlsRand,
+    storage::{traits, Entity, Key, StorageProvider as _, CURRENT_VERSION},
+    types::AeadType,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{errors::ClientStateError, *};
+use crate::{ciphersuite::hash_ref::KeyPackageRef, treesync::node::encryption_keys::EncryptionKey};
+
+/// The magic bytes at the start of a client state bundle.
+const MAGIC: &[u8; 4] = b"OMCS";
+
+/// The format version of the client state bundles written by
+/// [`ClientStateExport::export()`].
+pub const CLIENT_STATE_FORMAT_VERSION: u16 = 1;
+
+/// The AEAD algorithm the bundle is encrypted with.
+const AEAD: AeadType = AeadType::ChaCha20Poly1305;
+
+/// The length of the header, i.e. the magic bytes and the format version.
+const HEADER_LENGTH: usize = MAGIC.len() + 2;
+
+/// A value from the storage that is moved without interpreting it, such that
+/// signature public keys and key pairs of any credential type can be
+/// exported.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct RawValue(serde_json::Value);
+
+impl Key<CURRENT_VERSION> for RawValue {}
+impl Entity<CURRENT_VERSION> for RawValue {}
+impl traits::SignaturePublicKey<CURRENT_VERSION> for RawValue {}
+impl traits::SignatureKeyPair<CURRENT_VERSION> for RawValue {}
+
+/// The plaintext of a client state bundle.
+#[derive(Serialize, Deserialize)]
+struct ClientStatePayload {
+    /// The group state snapshots, see [`MlsGroup::save_state()`].
+    groups: Vec<Vec<u8>>,
+    signature_key_pairs: Vec<(RawValue, RawValue)>,
+    key_packages: Vec<(KeyPackageRef, KeyPackageBundle)>,
+}
+
+/// Exports the MLS state of a client into an encrypted bundle.
+///
+/// ```
+/// use openmls::{prelude::*, treesync::LeafNodeParameters};
+/// use openmls_basic_credential::SignatureKeyPair;
+/// use openmls_rust_crypto::OpenMlsRustCrypto;
+///
+/// let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+/// let provider = OpenMlsRustCrypto::default();
+/// let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+/// signer.store(provider.storage()).unwrap();
+/// let credential_with_key = CredentialWithKey {
+///     credential: BasicCredential::new(b"Alice".to_vec()).into(),
+///     signature_key: signer.public().into(),
+/// };
+/// let mut group = MlsGroup::builder()
+///     .ciphersuite(ciphersuite)
+///     .build(&provider, &signer, credential_with_key)
+///     .unwrap();
+///
+/// // Export the state on the old device, which deactivates the group there.
+/// let key = [0x42; 32];
+/// let bundle = ClientStateExport::new()
+///     .group(&mut group)
+///     .signature_key_pair(&signer.id())
+///     .export(&provider, &key)
+///     .unwrap();
+/// assert!(!group.is_active());
+///
+/// // Import the state on the new device and restore forward secrecy.
+/// let new_provider = OpenMlsRustCrypto::default();
+/// let mut import = ClientStateImport::import(&new_provider, &key, &bundle).unwrap();
+/// assert!(import.forward_secrecy_invalidated());
+/// for group in import.groups_mut() {
+///     group
+///         .self_update(&new_provider, &signer, LeafNodeParameters::default())
+///         .unwrap();
+///     group.merge_pending_commit(&new_provider).unwrap();
+/// }
+/// assert!(!import.forward_secrecy_invalidated());
+/// ```
+#[derive(Default)]
+pub struct ClientStateExport<'a> {
+    groups: Vec<&'a mut MlsGroup>,
+    signature_public_keys: Vec<serde_json::Result<serde_json::Value>>,
+    key_package_refs: Vec<KeyPackageRef>,
+}
+
+impl<'a> ClientStateExport<'a> {
+    /// Create an empty export.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the `group` to the export. The group is marked inactive once the
+    /// export succeeded.
+    pub fn group(mut self, group: &'a mut MlsGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Add the `groups` to the export. The groups are marked inactive once the
+    /// export succeeded.
+    pub fn groups(mut self, groups: impl IntoIterator<Item = &'a mut MlsGroup>) -> Self {
+        self.groups.extend(groups);
+        self
+    }
+
+    /// Add the signature key pair stored under `public_key` to the export.
+    ///
+    /// The key pair is exported as it is in the storage, such that it is
+    /// imported under the same key.
+    pub fn signature_key_pair(
+        mut self,
+        public_key: &impl traits::SignaturePublicKey<CURRENT_VERSION>,
+    ) -> Self {
+        self.signature_public_keys
+            .push(serde_json::to_value(public_key));
+        self
+    }
+
+    /// Add the key package bundle stored under `key_package_ref` to the
+    /// export.
+    pub fn key_package(mut self, key_package_ref: KeyPackageRef) -> Self {
+        self.key_package_refs.push(key_package_ref);
+        self
+    }
+
+    /// Reads the state added to the export from the storage of the `provider`
+    /// and returns the bundle encrypted under the 32 byte `key`.
+    ///
+    /// The exported groups are marked inactive afterwards, such that this
+    /// device can't send messages to them anymore.
+    ///
+    /// **The bundle contains all secrets of the client and invalidates the
+    /// forward secrecy of the exported groups, see
+    /// [`ClientStateImport::forward_secrecy_invalidated()`].**
+    pub fn export<Provider: OpenMlsProvider>(
+        self,
+        provider: &Provider,
+        key: &[u8],
+    ) -> Result<Vec<u8>, ClientStateError<Provider::StorageError>> {
+        if key.len() != AEAD.key_size() {
+            return Err(ClientStateError::InvalidKeyLength);
+        }
+        let storage = provider.storage();
+
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                let mut snapshot = Vec::new();
+                group.save_state(storage, &mut snapshot)?;
+                Ok(snapshot)
+            })
+            .collect::<Result<Vec<_>, ClientStateError<_>>>()?;
+        let signature_key_pairs = self
+            .signature_public_keys
+            .into_iter()
+            .map(|public_key| {
+                let public_key = RawValue(public_key.map_err(|_| ClientStateError::Serialization)?);
+                let key_pair: RawValue = storage
+                    .signature_key_pair(&public_key)
+                    .map_err(ClientStateError::StorageError)?
+                    .ok_or(ClientStateError::MissingSignatureKeyPair)?;
+                Ok((public_key, key_pair))
+            })
+            .collect::<Result<Vec<_>, ClientStateError<_>>>()?;
+        let key_packages = self
+            .key_package_refs
+            .into_iter()
+            .map(|key_package_ref| {
+                let bundle: KeyPackageBundle = storage
+                    .key_package(&key_package_ref)
+                    .map_err(ClientStateError::StorageError)?
+                    .ok_or(ClientStateError::MissingKeyPackage)?;
+                Ok((key_package_ref, bundle))
+            })
+            .collect::<Result<Vec<_>, ClientStateError<_>>>()?;
+
+        let payload = serde_json::to_vec(&ClientStatePayload {
+            groups,
+            signature_key_pairs,
+            key_packages,
+        })
+        .map_err(|_| ClientStateError::Serialization)?;
+
+        let mut bundle = Vec::with_capacity(HEADER_LENGTH);
+        bundle.extend_from_slice(MAGIC);
+        bundle.extend_from_slice(&CLIENT_STATE_FORMAT_VERSION.to_be_bytes());
+        let nonce = provider
+            .rand()
+            .random_vec(AEAD.nonce_size())
+            .map_err(|_| ClientStateError::Randomness)?;
+        let ciphertext = provider
+            .crypto()
+            .aead_encrypt(AEAD, key, &payload, &nonce, &bundle)
+            .map_err(ClientStateError::Crypto)?;
+        bundle.extend_from_slice(&nonce);
+        bundle.extend_from_slice(&ciphertext);
+
+        for group in self.groups {
+            group.group_state = MlsGroupState::Inactive;
+            storage
+                .write_group_state(group.group_id(), &group.group_state)
+                .map_err(ClientStateError::StorageError)?;
+        }
+
+        Ok(bundle)
+    }
+}
+
+/// The result of importing a client state bundle.
+#[derive(Debug)]
+pub struct ClientStateImport {
+    groups: Vec<MlsGroup>,
+    /// The encryption keys of the own leaves of the `groups` when they were
+    /// imported.
+    imported_encryption_keys: Vec<Option<EncryptionKey>>,
+    key_package_refs: Vec<KeyPackageRef>,
+    signature_key_pairs: usize,
+}
+
+impl ClientStateImport {
+    /// Decrypts the `bundle` written by [`ClientStateExport::export()`] with
+    /// the 32 byte `key` and writes the contained state to the storage of the
+    /// `provider`.
+    ///
+    /// The state is written in a single storage transaction, such that a
+    /// failing import doesn't leave parts of the state in the storage.
+    /// Operational groups are imported in the [`MlsGroupState::UpdateRequired`]
+    /// state.
+    ///
+    /// Returns [`ClientStateError::Crypto`] if the `key` is wrong or the bundle
+    /// has been modified.
+    pub fn import<Provider: OpenMlsProvider>(
+        provider: &Provider,
+        key: &[u8],
+        bundle: &[u8],
+    ) -> Result<Self, ClientStateError<Provider::StorageError>> {
+        if key.len() != AEAD.key_size() {
+            return Err(ClientStateError::InvalidKeyLength);
+        }
+        if bundle.len() < HEADER_LENGTH + AEAD.nonce_size() || &bundle[..MAGIC.len()] != MAGIC {
+            return Err(ClientStateError::InvalidHeader);
+        }
+        let (header, rest) = bundle.split_at(HEADER_LENGTH);
+        let version = u16::from_be_bytes([header[4], header[5]]);
+        if version != CLIENT_STATE_FORMAT_VERSION {
+            return Err(ClientStateError::UnsupportedVersion(version));
+        }
+        let (nonce, ciphertext) = rest.split_at(AEAD.nonce_size());
+        let payload = provider
+            .crypto()
+            .aead_decrypt(AEAD, key, ciphertext, nonce, header)
+            .map_err(ClientStateError::Crypto)?;
+        let payload: ClientStatePayload =
+            serde_json::from_slice(&payload).map_err(|_| ClientStateError::Malformed)?;
+
+        let storage = provider.storage();
+        storage
+            .begin_transaction()
+            .map_err(ClientStateError::StorageError)?;
+        match Self::write_payload(storage, payload) {
+            Ok(import) => {
+                storage
+                    .commit_transaction()
+                    .map_err(ClientStateError::StorageError)?;
+                Ok(import)
+            }
+            Err(e) => {
+                // The error of the import is more relevant than a failing
+                // rollback.
+                let _ = storage.rollback_transaction();
+                Err(e)
+            }
+        }
+    }
+
+    fn write_payload<Storage: StorageProvider>(
+        storage: &Storage,
+        payload: ClientStatePayload,
+    ) -> Result<Self, ClientStateError<Storage::Error>> {
+        let signature_key_pairs = payload.signature_key_pairs.len();
+        for (public_key, key_pair) in &payload.signature_key_pairs {
+            storage
+                .write_signature_key_pair(public_key, key_pair)
+                .map_err(ClientStateError::StorageError)?;
+        }
+        let key_package_refs = payload
+            .key_packages
+            .into_iter()
+            .map(|(key_package_ref, bundle)| {
+                storage
+                    .write_key_package(&key_package_ref, &bundle)
+                    .map_err(ClientStateError::StorageError)?;
+                Ok(key_package_ref)
+            })
+            .collect::<Result<Vec<_>, ClientStateError<_>>>()?;
+        let groups = payload
+            .groups
+            .iter()
+            .map(|snapshot| {
+                let mut group = MlsGroup::load_state(storage, &mut snapshot.as_slice())?;
+                if matches!(group.group_state, MlsGroupState::Operational) {
+                    group.group_state = MlsGroupState::UpdateRequired;
+                    storage
+                        .write_group_state(group.group_id(), &group.group_state)
+                        .map_err(ClientStateError::StorageError)?;
+                }
+                Ok(group)
+            })
+            .collect::<Result<Vec<MlsGroup>, ClientStateError<_>>>()?;
+        let imported_encryption_keys = groups.iter().map(own_encryption_key).collect();
+
+        Ok(Self {
+            groups,
+            imported_encryption_keys,
+            key_package_refs,
+            signature_key_pairs,
+        })
+    }
+
+    /// Returns the imported groups.
+    pub fn groups(&self) -> &[MlsGroup] {
+        &self.groups
+    }
+
+    /// Returns the imported groups, e.g. to commit an update in each of them.
+    pub fn groups_mut(&mut self) -> &mut [MlsGroup] {
+        &mut self.groups
+    }
+
+    /// Returns the imported groups, e.g. to continue using them.
+    pub fn into_groups(self) -> Vec<MlsGroup> {
+        self.groups
+    }
+
+    /// Returns the references of the imported key packages.
+    pub fn key_package_refs(&self) -> &[KeyPackageRef] {
+        &self.key_package_refs
+    }
+
+    /// Returns the number of imported signature key pairs.
+    pub fn signature_key_pairs(&self) -> usize {
+        self.signature_key_pairs
+    }
+
+    /// Returns `true` if the forward secrecy of one of the imported groups is
+    /// invalidated, i.e. if the own leaf of an active group still has the
+    /// encryption key it had when the state was exported. The private key of
+    /// this leaf and the group secrets are known to both devices and passed
+    /// through the bundle.
+    ///
+    /// The forward secrecy of a group is restored once the new device has
+    /// committed an update, e.g. with [`MlsGroup::self_update()`], on the
+    /// group returned by [`groups_mut()`](Self::groups_mut). The old device
+    /// and the bundle should be deleted nevertheless. The imported key
+    /// packages are not covered and should be replaced by new ones.
+    pub fn forward_secrecy_invalidated(&self) -> bool {
+        self.groups.iter().zip(&self.imported_encryption_keys).any(
+            |(group, imported_encryption_key)| {
+                group.is_active()
+                    && imported_encryption_key.is_some()
+                    && own_encryption_key(group) == *imported_encryption_key
+            },
+        )
+    }
+}
+
+/// Returns the encryption key of the own leaf of the `group`.
+fn own_encryption_key(group: &MlsGroup) -> Option<EncryptionKey> {
+    group
+        .own_leaf()
+        .map(|leaf_node| leaf_node.encryption_key().clone())
+}
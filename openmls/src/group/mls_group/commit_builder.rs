@@ -12,7 +12,7 @@ use crate::{
     group::{
         create_commit::CommitType, diff::compute_path::PathComputationResult,
         CommitBuilderStageError, CreateCommitError, Extension, Extensions, ExternalPubExtension,
-        ProposalQueue, ProposalQueueError, QueuedProposal, RatchetTreeExtension, StagedCommit,
+        ProposalQueue, ProposalQueueError, QueuedProposal, StagedCommit,
     },
     key_packages::KeyPackage,
     messages::{
@@ -32,7 +32,7 @@ use super::{
     mls_auth_content::AuthenticatedContent,
     staged_commit::{MemberStagedCommitState, StagedCommitState},
     AddProposal, CreateCommitResult, GroupContextExtensionProposal, MlsGroup, MlsGroupState,
-    MlsMessageOut, PendingCommitState, Proposal, RemoveProposal, Sender,
+    MlsMessageOut, NewSignerBundle, PendingCommitState, Proposal, RemoveProposal, Sender,
 };
 
 /// This stage is for populating the builder.
@@ -271,6 +271,42 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
         crypto: &impl OpenMlsCrypto,
         signer: &impl Signer,
         f: impl FnMut(&QueuedProposal) -> bool,
+    ) -> Result<CommitBuilder<'a, Complete>, CreateCommitError> {
+        self.build_internal(rand, crypto, signer, signer, f)
+    }
+
+    /// Like [`CommitBuilder::build()`], but rotates the own signature key. The
+    /// own leaf node gets the credential and signature key of `new_signer`.
+    ///
+    /// The commit is signed with `old_signer`, while the new leaf node and the
+    /// group info are signed with the signer in `new_signer`. The new
+    /// signature key takes effect when the commit is merged with
+    /// [`MlsGroup::merge_pending_commit()`], see
+    /// [`MlsGroup::pending_signature_key()`].
+    pub fn build_with_new_signer<S: Signer>(
+        mut self,
+        rand: &impl OpenMlsRand,
+        crypto: &impl OpenMlsCrypto,
+        old_signer: &impl Signer,
+        new_signer: NewSignerBundle<'_, S>,
+        f: impl FnMut(&QueuedProposal) -> bool,
+    ) -> Result<CommitBuilder<'a, Complete>, CreateCommitError> {
+        self.stage
+            .leaf_node_parameters
+            .set_credential_with_key(new_signer.credential_with_key);
+        self.build_internal(rand, crypto, old_signer, new_signer.signer, f)
+    }
+
+    /// Builds the commit. The commit itself is signed with `signer`, while the
+    /// own leaf node in the update path and the group info are signed with
+    /// `leaf_node_signer`.
+    fn build_internal(
+        self,
+        rand: &impl OpenMlsRand,
+        crypto: &impl OpenMlsCrypto,
+        signer: &impl Signer,
+        leaf_node_signer: &impl Signer,
+        f: impl FnMut(&QueuedProposal) -> bool,
     ) -> Result<CommitBuilder<'a, Complete>, CreateCommitError> {
         let ciphersuite = self.group.ciphersuite();
         let sender = Sender::build_member(self.group.own_leaf_index());
@@ -333,7 +369,7 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
         builder
             .group
             .public_group
-            .validate_add_proposals(&proposal_queue)?;
+            .validate_add_proposals(crypto, &proposal_queue)?;
         // ValSem106
         // ValSem109
         builder
@@ -354,10 +390,11 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
         // ValSem110
         // ValSem111
         // ValSem112
-        builder
-            .group
-            .public_group
-            .validate_update_proposals(&proposal_queue, builder.group.own_leaf_index())?;
+        builder.group.public_group.validate_update_proposals(
+            crypto,
+            &proposal_queue,
+            builder.group.own_leaf_index(),
+        )?;
 
         // ValSem208
         // ValSem209
@@ -375,7 +412,7 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
 
         // Apply proposals to tree
         let apply_proposals_values =
-            diff.apply_proposals(&proposal_queue, builder.group.own_leaf_index())?;
+            diff.apply_proposals(&proposal_queue, builder.group.own_leaf_index(), crypto)?;
         if apply_proposals_values.self_removed {
             return Err(CreateCommitError::CannotRemoveSelf);
         }
@@ -397,7 +434,7 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
                     apply_proposals_values.exclusion_list(),
                     &CommitType::Member,
                     &cur_stage.leaf_node_parameters,
-                    signer,
+                    leaf_node_signer,
                     apply_proposals_values.extensions.clone()
                 )?
             } else {
@@ -501,15 +538,21 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
                 Extension::ExternalPub(ExternalPubExtension::new(external_pub.into()));
 
             // Create the ratchet tree extension if necessary
-            let extensions: Extensions = if builder.group.configuration().use_ratchet_tree_extension
+            let ratchet_tree_extension = if builder.group.configuration().use_ratchet_tree_extension
             {
-                Extensions::from_vec(vec![
-                    Extension::RatchetTree(RatchetTreeExtension::new(diff.export_ratchet_tree())),
-                    external_pub_extension,
-                ])?
+                builder
+                    .group
+                    .configuration()
+                    .ratchet_tree_extension(diff.export_ratchet_tree())?
             } else {
-                Extensions::single(external_pub_extension)
+                None
             };
+            let extensions = Extensions::from_vec(
+                ratchet_tree_extension
+                    .into_iter()
+                    .chain([external_pub_extension])
+                    .collect(),
+            )?;
 
             // Create to-be-signed group info.
             let group_info_tbs = {
@@ -521,7 +564,7 @@ impl<'a> CommitBuilder<'a, LoadedPsks> {
                 )
             };
             // Sign to-be-signed group info.
-            Some(group_info_tbs.sign(signer)?)
+            Some(group_info_tbs.sign(leaf_node_signer)?)
         };
 
         let welcome_option = if !needs_welcome {
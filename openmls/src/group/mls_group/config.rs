@@ -30,11 +30,13 @@
 use super::*;
 use crate::{
     extensions::errors::InvalidExtensionError,
-    key_packages::Lifetime,
+    group::errors::WireFormatPolicyError,
+    key_packages::{Lifetime, LifetimePolicy},
     tree::sender_ratchet::SenderRatchetConfiguration,
     treesync::{errors::LeafNodeValidationError, node::leaf_node::Capabilities},
 };
 use serde::{Deserialize, Serialize};
+use tls_codec::Size as _;
 
 /// The [`MlsGroupJoinConfig`] contains all configuration parameters that are
 /// relevant to group operation at runtime. It is used to configure the group's
@@ -56,6 +58,34 @@ pub struct MlsGroupJoinConfig {
     pub(crate) use_ratchet_tree_extension: bool,
     /// Sender ratchet configuration
     pub(crate) sender_ratchet_configuration: SenderRatchetConfiguration,
+    /// Maximum size of the plaintext of outgoing application messages. The
+    /// default is `None`, i.e. no limit.
+    #[serde(default)]
+    pub(crate) max_application_message_size: Option<usize>,
+    /// Flag to indicate that application messages must carry non-empty
+    /// additional authenticated data (AAD)
+    #[serde(default)]
+    pub(crate) require_aad: bool,
+    /// Policy for validating the lifetimes of key packages and leaf nodes
+    #[serde(default)]
+    pub(crate) lifetime_policy: LifetimePolicy,
+    /// Flag to indicate that Update proposals and commits that change the
+    /// credential of a member are rejected
+    #[serde(default)]
+    pub(crate) reject_identity_changes: bool,
+    /// Maximum size (in bytes) of the ratchet tree extension in Welcomes and
+    /// group infos. The default is `None`, i.e. no limit.
+    #[serde(default)]
+    pub(crate) max_ratchet_tree_extension_size: Option<usize>,
+    /// Ciphersuites that ReInit proposals may move the group to. The default
+    /// is `None`, i.e. all ciphersuites are accepted.
+    #[serde(default)]
+    pub(crate) reinit_ciphersuites: Option<Vec<Ciphersuite>>,
+    /// Flag to indicate that the non-standard compressed ratchet tree extension
+    /// should be used for ratchet trees that exceed the size limit
+    #[cfg(feature = "ratchet-tree-compression")]
+    #[serde(default)]
+    pub(crate) compress_ratchet_tree_extension: bool,
 }
 
 impl MlsGroupJoinConfig {
@@ -78,6 +108,84 @@ impl MlsGroupJoinConfig {
     pub fn sender_ratchet_configuration(&self) -> &SenderRatchetConfiguration {
         &self.sender_ratchet_configuration
     }
+
+    /// Returns the maximum application message size set in this [`MlsGroupJoinConfig`].
+    pub fn max_application_message_size(&self) -> Option<usize> {
+        self.max_application_message_size
+    }
+
+    /// Returns whether this [`MlsGroupJoinConfig`] requires non-empty AAD on
+    /// application messages.
+    pub fn require_aad(&self) -> bool {
+        self.require_aad
+    }
+
+    /// Returns the [`LifetimePolicy`] set in this [`MlsGroupJoinConfig`].
+    pub fn lifetime_policy(&self) -> &LifetimePolicy {
+        &self.lifetime_policy
+    }
+
+    /// Returns whether this [`MlsGroupJoinConfig`] rejects updates that change
+    /// the credential of a member.
+    pub fn reject_identity_changes(&self) -> bool {
+        self.reject_identity_changes
+    }
+
+    /// Returns the maximum size of the ratchet tree extension set in this
+    /// [`MlsGroupJoinConfig`].
+    pub fn max_ratchet_tree_extension_size(&self) -> Option<usize> {
+        self.max_ratchet_tree_extension_size
+    }
+
+    /// Returns the ciphersuites that ReInit proposals may move the group to,
+    /// if restricted in this [`MlsGroupJoinConfig`].
+    pub fn reinit_ciphersuites(&self) -> Option<&[Ciphersuite]> {
+        self.reinit_ciphersuites.as_deref()
+    }
+
+    /// Returns whether this [`MlsGroupJoinConfig`] uses the compressed ratchet
+    /// tree extension.
+    #[cfg(feature = "ratchet-tree-compression")]
+    pub fn compress_ratchet_tree_extension(&self) -> bool {
+        self.compress_ratchet_tree_extension
+    }
+
+    /// Returns the extension that carries the given ratchet tree in Welcomes
+    /// and group infos, or `None` if the extension would exceed the maximum
+    /// size.
+    pub(crate) fn ratchet_tree_extension(
+        &self,
+        ratchet_tree: RatchetTree,
+    ) -> Result<Option<Extension>, LibraryError> {
+        let fits = |extension: &Extension| {
+            let fits = self
+                .max_ratchet_tree_extension_size
+                .is_none_or(|max| extension.tls_serialized_len() <= max);
+            if !fits {
+                log::warn!("The ratchet tree extension exceeds the maximum size.");
+            }
+            fits
+        };
+
+        #[cfg(feature = "ratchet-tree-compression")]
+        if self.compress_ratchet_tree_extension {
+            // If there is a limit, only ratchet trees that exceed it are compressed.
+            if let Some(max) = self.max_ratchet_tree_extension_size {
+                let extension =
+                    Extension::RatchetTree(RatchetTreeExtension::new(ratchet_tree.clone()));
+                if extension.tls_serialized_len() <= max {
+                    return Ok(Some(extension));
+                }
+            }
+            let extension = crate::extensions::CompressedRatchetTreeExtension::new(&ratchet_tree)?;
+            let extension = crate::extensions::ApplicationExtension::to_extension(&extension)
+                .map_err(|_| LibraryError::custom("Invalid compressed ratchet tree extension"))?;
+            return Ok(fits(&extension).then_some(extension));
+        }
+
+        let extension = Extension::RatchetTree(RatchetTreeExtension::new(ratchet_tree));
+        Ok(fits(&extension).then_some(extension))
+    }
 }
 
 /// Specifies configuration for the creation of an [`MlsGroup`]. Refer to the
@@ -85,8 +193,14 @@ impl MlsGroupJoinConfig {
 /// more information about the different configuration values.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MlsGroupCreateConfig {
-    /// Capabilities advertised in the creator's leaf node
-    pub(crate) capabilities: Capabilities,
+    /// Capabilities advertised in the creator's leaf node. If not set, the
+    /// default capabilities of the provider's
+    /// [`CiphersuiteRegistry`](crate::ciphersuite::CiphersuiteRegistry) are used.
+    ///
+    /// Set capabilities are serialized like before they were optional, and
+    /// configs without capabilities are read as unset.
+    #[serde(default)]
+    pub(crate) capabilities: Option<Capabilities>,
     /// Lifetime of the own leaf node
     pub(crate) lifetime: Lifetime,
     /// Ciphersuite and protocol version
@@ -102,7 +216,7 @@ pub struct MlsGroupCreateConfig {
 impl Default for MlsGroupCreateConfig {
     fn default() -> Self {
         Self {
-            capabilities: Capabilities::default(),
+            capabilities: None,
             lifetime: Lifetime::default(),
             ciphersuite: Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
             join_config: MlsGroupJoinConfig::default(),
@@ -165,6 +279,55 @@ impl MlsGroupJoinConfigBuilder {
         self
     }
 
+    /// Sets the `max_application_message_size` property of the [`MlsGroupJoinConfig`].
+    pub fn max_application_message_size(mut self, max_application_message_size: usize) -> Self {
+        self.join_config.max_application_message_size = Some(max_application_message_size);
+        self
+    }
+
+    /// Sets the `require_aad` property of the [`MlsGroupJoinConfig`].
+    pub fn require_aad(mut self, require_aad: bool) -> Self {
+        self.join_config.require_aad = require_aad;
+        self
+    }
+
+    /// Sets the `reject_identity_changes` property of the [`MlsGroupJoinConfig`].
+    pub fn reject_identity_changes(mut self, reject_identity_changes: bool) -> Self {
+        self.join_config.reject_identity_changes = reject_identity_changes;
+        self
+    }
+
+    /// Sets the `max_ratchet_tree_extension_size` property of the [`MlsGroupJoinConfig`].
+    pub fn max_ratchet_tree_extension_size(
+        mut self,
+        max_ratchet_tree_extension_size: usize,
+    ) -> Self {
+        self.join_config.max_ratchet_tree_extension_size = Some(max_ratchet_tree_extension_size);
+        self
+    }
+
+    /// Sets the `reinit_ciphersuites` property of the [`MlsGroupJoinConfig`].
+    pub fn reinit_ciphersuites(mut self, reinit_ciphersuites: Vec<Ciphersuite>) -> Self {
+        self.join_config.reinit_ciphersuites = Some(reinit_ciphersuites);
+        self
+    }
+
+    /// Sets the `compress_ratchet_tree_extension` property of the [`MlsGroupJoinConfig`].
+    #[cfg(feature = "ratchet-tree-compression")]
+    pub fn compress_ratchet_tree_extension(
+        mut self,
+        compress_ratchet_tree_extension: bool,
+    ) -> Self {
+        self.join_config.compress_ratchet_tree_extension = compress_ratchet_tree_extension;
+        self
+    }
+
+    /// Sets the `lifetime_policy` property of the [`MlsGroupJoinConfig`].
+    pub fn lifetime_policy(mut self, lifetime_policy: LifetimePolicy) -> Self {
+        self.join_config.lifetime_policy = lifetime_policy;
+        self
+    }
+
     /// Finalizes the builder and returns an [`MlsGroupJoinConfig`].
     pub fn build(self) -> MlsGroupJoinConfig {
         self.join_config
@@ -207,6 +370,47 @@ impl MlsGroupCreateConfig {
         &self.join_config.sender_ratchet_configuration
     }
 
+    /// Returns the [`MlsGroupCreateConfig`] maximum application message size.
+    pub fn max_application_message_size(&self) -> Option<usize> {
+        self.join_config.max_application_message_size
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] boolean flag that indicates whether
+    /// application messages must carry non-empty AAD.
+    pub fn require_aad(&self) -> bool {
+        self.join_config.require_aad
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] boolean flag that indicates whether
+    /// updates that change the credential of a member are rejected.
+    pub fn reject_identity_changes(&self) -> bool {
+        self.join_config.reject_identity_changes
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] [`LifetimePolicy`].
+    pub fn lifetime_policy(&self) -> &LifetimePolicy {
+        &self.join_config.lifetime_policy
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] maximum size of the ratchet tree
+    /// extension.
+    pub fn max_ratchet_tree_extension_size(&self) -> Option<usize> {
+        self.join_config.max_ratchet_tree_extension_size
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] ciphersuites that ReInit proposals
+    /// may move the group to, if restricted.
+    pub fn reinit_ciphersuites(&self) -> Option<&[Ciphersuite]> {
+        self.join_config.reinit_ciphersuites()
+    }
+
+    /// Returns the [`MlsGroupCreateConfig`] boolean flag that indicates whether
+    /// the compressed ratchet tree extension is used.
+    #[cfg(feature = "ratchet-tree-compression")]
+    pub fn compress_ratchet_tree_extension(&self) -> bool {
+        self.join_config.compress_ratchet_tree_extension
+    }
+
     /// Returns the [`Extensions`] set as the initial group context.
     /// This does not contain the initial group context extensions
     /// added from builder calls to `external_senders` or `required_capabilities`.
@@ -294,9 +498,11 @@ impl MlsGroupCreateConfigBuilder {
         self
     }
 
-    /// Sets the `capabilities` of the group creator's leaf node.
+    /// Sets the `capabilities` of the group creator's leaf node. If not set,
+    /// the default capabilities of the provider's
+    /// [`CiphersuiteRegistry`](crate::ciphersuite::CiphersuiteRegistry) are used.
     pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
-        self.config.capabilities = capabilities;
+        self.config.capabilities = Some(capabilities);
         self
     }
 
@@ -310,6 +516,85 @@ impl MlsGroupCreateConfigBuilder {
         self
     }
 
+    /// Sets the `max_application_message_size` property of the MlsGroupCreateConfig.
+    /// Application messages with a larger plaintext are rejected by
+    /// [`MlsGroup::create_message()`].
+    pub fn max_application_message_size(mut self, max_application_message_size: usize) -> Self {
+        self.config.join_config.max_application_message_size = Some(max_application_message_size);
+        self
+    }
+
+    /// Sets the `require_aad` property of the MlsGroupCreateConfig.
+    /// If set, outgoing and incoming application messages must carry non-empty
+    /// additional authenticated data (AAD), e.g. routing metadata set with
+    /// [`MlsGroup::set_aad()`].
+    pub fn require_aad(mut self, require_aad: bool) -> Self {
+        self.config.join_config.require_aad = require_aad;
+        self
+    }
+
+    /// Sets the `reject_identity_changes` property of the MlsGroupCreateConfig.
+    /// If set, Update proposals and commits that change the credential of a
+    /// member are rejected when processing them. Otherwise, the changes are
+    /// reported by [`ProcessedMessage::identity_changes()`].
+    ///
+    /// [`ProcessedMessage::identity_changes()`]: crate::framing::ProcessedMessage::identity_changes
+    pub fn reject_identity_changes(mut self, reject_identity_changes: bool) -> Self {
+        self.config.join_config.reject_identity_changes = reject_identity_changes;
+        self
+    }
+
+    /// Sets the `lifetime_policy` property of the MlsGroupCreateConfig. The
+    /// policy is used to validate the lifetimes of the leaf nodes of new
+    /// members, of the leaf nodes in the ratchet tree when joining, and of the
+    /// own key package when processing a Welcome. The default is
+    /// [`LifetimePolicy::default()`].
+    pub fn lifetime_policy(mut self, lifetime_policy: LifetimePolicy) -> Self {
+        self.config.join_config.lifetime_policy = lifetime_policy;
+        self
+    }
+
+    /// Sets the `max_ratchet_tree_extension_size` property of the
+    /// MlsGroupCreateConfig. If the ratchet tree extension would be larger than
+    /// `max_ratchet_tree_extension_size` bytes, it is not included in Welcomes
+    /// and the ratchet tree has to be provided to new members out of band.
+    /// Exporting a group info with the ratchet tree fails in this case, see
+    /// [`MlsGroup::export_group_info()`].
+    pub fn max_ratchet_tree_extension_size(
+        mut self,
+        max_ratchet_tree_extension_size: usize,
+    ) -> Self {
+        self.config.join_config.max_ratchet_tree_extension_size =
+            Some(max_ratchet_tree_extension_size);
+        self
+    }
+
+    /// Sets the `reinit_ciphersuites` property of the MlsGroupCreateConfig.
+    /// If set, ReInit proposals that move the group to a ciphersuite that is
+    /// not in `reinit_ciphersuites` are rejected when processing them, such
+    /// that a member can't downgrade the group to a weaker ciphersuite.
+    pub fn reinit_ciphersuites(mut self, reinit_ciphersuites: Vec<Ciphersuite>) -> Self {
+        self.config.join_config.reinit_ciphersuites = Some(reinit_ciphersuites);
+        self
+    }
+
+    /// Sets the `compress_ratchet_tree_extension` property of the
+    /// MlsGroupCreateConfig. If set, ratchet trees that exceed the
+    /// `max_ratchet_tree_extension_size` (or all ratchet trees if there is no
+    /// limit) are included in the non-standard
+    /// [`CompressedRatchetTreeExtension`] instead of the ratchet tree
+    /// extension. Only use this if all clients support the extension.
+    ///
+    /// [`CompressedRatchetTreeExtension`]: crate::extensions::CompressedRatchetTreeExtension
+    #[cfg(feature = "ratchet-tree-compression")]
+    pub fn compress_ratchet_tree_extension(
+        mut self,
+        compress_ratchet_tree_extension: bool,
+    ) -> Self {
+        self.config.join_config.compress_ratchet_tree_extension = compress_ratchet_tree_extension;
+        self
+    }
+
     /// Sets the `lifetime` property of the MlsGroupCreateConfig.
     pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
         self.config.lifetime = lifetime;
@@ -342,20 +627,26 @@ impl MlsGroupCreateConfigBuilder {
         mut self,
         extensions: Extensions,
     ) -> Result<Self, LeafNodeValidationError> {
-        // None of the default extensions are leaf node extensions, so only
-        // unknown extensions can be leaf node extensions.
+        // Only the application id and unknown extensions can be leaf node
+        // extensions.
         let is_valid_in_leaf_node = extensions
             .iter()
-            .all(|e| matches!(e.extension_type(), ExtensionType::Unknown(_)));
+            .all(|e| e.extension_type().is_valid_in_leaf_node() != Some(false));
         if !is_valid_in_leaf_node {
-            log::error!("Leaf node extensions must be unknown extensions.");
+            log::error!("Leaf node extensions must be valid in leaf nodes.");
             return Err(LeafNodeValidationError::UnsupportedExtensions);
         }
 
         // Make sure that the extension type is supported in this context.
         // This means that the leaf node needs to have support listed in the
         // the capabilities (https://validation.openmls.tech/#valn0107).
-        if !self.config.capabilities.contains_extensions(&extensions) {
+        let supports_extensions = match &self.config.capabilities {
+            Some(capabilities) => capabilities.contains_extensions(&extensions),
+            // The default capabilities of the registry only differ from
+            // the default ones in the ciphersuites.
+            None => Capabilities::default().contains_extensions(&extensions),
+        };
+        if !supports_extensions {
             return Err(LeafNodeValidationError::ExtensionsNotInCapabilities);
         }
 
@@ -423,6 +714,22 @@ impl WireFormatPolicy {
         Self { outgoing, incoming }
     }
 
+    /// Creates a new wire format policy from an [`OutgoingWireFormatPolicy`]
+    /// and an [`IncomingWireFormatPolicy`].
+    ///
+    /// Returns [`WireFormatPolicyError::IncompatiblePolicies`] if the incoming
+    /// policy does not accept the wire format of the outgoing handshake
+    /// messages, since the group couldn't process its own commits then.
+    pub fn try_new(
+        outgoing: OutgoingWireFormatPolicy,
+        incoming: IncomingWireFormatPolicy,
+    ) -> Result<Self, WireFormatPolicyError> {
+        if !incoming.is_compatible_with(outgoing.into()) {
+            return Err(WireFormatPolicyError::IncompatiblePolicies);
+        }
+        Ok(Self { outgoing, incoming })
+    }
+
     /// Returns a reference to the wire format policy's outgoing wire format policy.
     pub fn outgoing(&self) -> OutgoingWireFormatPolicy {
         self.outgoing
@@ -136,7 +136,8 @@ impl MlsGroup {
         self.public_group
             .validate_key_uniqueness(&proposal_queue, None)?;
         // ValSem105
-        self.public_group.validate_add_proposals(&proposal_queue)?;
+        self.public_group
+            .validate_add_proposals(provider.crypto(), &proposal_queue)?;
         // ValSem106
         // ValSem109
         self.public_group.validate_capabilities(&proposal_queue)?;
@@ -151,8 +152,11 @@ impl MlsGroup {
             // ValSem110
             // ValSem111
             // ValSem112
-            self.public_group
-                .validate_update_proposals(&proposal_queue, *sender_index)?;
+            self.public_group.validate_update_proposals(
+                provider.crypto(),
+                &proposal_queue,
+                *sender_index,
+            )?;
         }
 
         // ValSem208
@@ -165,7 +169,7 @@ impl MlsGroup {
 
         // Apply proposals to tree
         let apply_proposals_values =
-            diff.apply_proposals(&proposal_queue, self.own_leaf_index())?;
+            diff.apply_proposals(&proposal_queue, self.own_leaf_index(), provider.crypto())?;
 
         let path_computation_result =
             // If path is needed, compute path values
@@ -292,14 +296,18 @@ impl MlsGroup {
                 .public;
             let external_pub_extension =
                 Extension::ExternalPub(ExternalPubExtension::new(external_pub.into()));
-            let other_extensions: Extensions = if self.configuration().use_ratchet_tree_extension {
-                Extensions::from_vec(vec![
-                    Extension::RatchetTree(RatchetTreeExtension::new(diff.export_ratchet_tree())),
-                    external_pub_extension,
-                ])?
+            let ratchet_tree_extension = if self.configuration().use_ratchet_tree_extension {
+                self.configuration()
+                    .ratchet_tree_extension(diff.export_ratchet_tree())?
             } else {
-                Extensions::single(external_pub_extension)
+                None
             };
+            let other_extensions = Extensions::from_vec(
+                ratchet_tree_extension
+                    .into_iter()
+                    .chain([external_pub_extension])
+                    .collect(),
+            )?;
 
             // Create to-be-signed group info.
             let group_info_tbs = {
@@ -3,14 +3,16 @@ use openmls_traits::{signatures::Signer, storage::StorageProvider as StorageProv
 
 use super::{builder::MlsGroupBuilder, *};
 use crate::{
+    ciphersuite::CiphersuiteRegistry,
     credentials::CredentialWithKey,
     group::errors::{ExternalCommitError, WelcomeError},
+    key_packages::time_provider,
     messages::{
         group_info::{GroupInfo, VerifiableGroupInfo},
         Welcome,
     },
     schedule::{
-        psk::{store::ResumptionPskStore, PreSharedKeyId},
+        psk::{missing_psks, store::ResumptionPskStore, PreSharedKeyId},
         EpochSecrets, InitSecret,
     },
     storage::OpenMlsProvider,
@@ -98,13 +100,25 @@ impl MlsGroup {
         credential_with_key: CredentialWithKey,
     ) -> Result<(Self, MlsMessageOut, Option<GroupInfo>), ExternalCommitError<Provider::StorageError>>
     {
+        // Reject groups with ciphersuites we can't handle before processing
+        // the group info.
+        if !CiphersuiteRegistry::new(provider.crypto())
+            .supports(verifiable_group_info.ciphersuite())
+        {
+            return Err(ExternalCommitError::UnsupportedCiphersuite);
+        }
+
         // Prepare the commit parameters
         let framing_parameters = FramingParameters::new(aad, WireFormat::PublicMessage);
 
-        let leaf_node_parameters = LeafNodeParameters::builder()
-            .with_capabilities(capabilities.unwrap_or_default())
-            .with_extensions(extensions.unwrap_or_default())
-            .build();
+        // Without explicit capabilities, the defaults of the provider are used
+        // when computing the path.
+        let mut leaf_node_parameters =
+            LeafNodeParameters::builder().with_extensions(extensions.unwrap_or_default());
+        if let Some(capabilities) = capabilities {
+            leaf_node_parameters = leaf_node_parameters.with_capabilities(capabilities);
+        }
+        let leaf_node_parameters = leaf_node_parameters.build();
         let mut params = CreateCommitParams::builder()
             .external_commit(credential_with_key, framing_parameters)
             .leaf_node_parameters(leaf_node_parameters)
@@ -116,21 +130,26 @@ impl MlsGroup {
         // If we got a ratchet tree extension in the welcome, we enable it for
         // this group. Note that this is not strictly necessary. But there's
         // currently no other mechanism to enable the extension.
-        let ratchet_tree = match verifiable_group_info.extensions().ratchet_tree() {
-            Some(extension) => extension.ratchet_tree().clone(),
+        let ratchet_tree = match verifiable_group_info
+            .extensions()
+            .ratchet_tree_in()
+            .map_err(|_| ExternalCommitError::InvalidRatchetTree)?
+        {
+            Some(ratchet_tree) => ratchet_tree,
             None => match ratchet_tree {
                 Some(ratchet_tree) => ratchet_tree,
                 None => return Err(ExternalCommitError::MissingRatchetTree),
             },
         };
 
-        let (public_group, group_info) = PublicGroup::from_external(
+        let (public_group, group_info) = PublicGroup::from_external_with_lifetime_policy(
             provider.crypto(),
             provider.storage(),
             ratchet_tree,
             verifiable_group_info,
             // Existing proposals are discarded when joining by external commit.
             ProposalStore::new(),
+            mls_group_config.lifetime_policy,
         )?;
         let group_context = public_group.group_context();
 
@@ -176,11 +195,8 @@ impl MlsGroup {
         // If there is a group member in the group with the same identity as us,
         // commit a remove proposal.
         let signature_key = params.credential_with_key().signature_key.as_slice();
-        if let Some(us) = public_group
-            .members()
-            .find(|member| member.signature_key == signature_key)
-        {
-            let remove_proposal = Proposal::Remove(RemoveProposal { removed: us.index });
+        if let Some(us) = public_group.member_index_by_signature_key(signature_key) {
+            let remove_proposal = Proposal::Remove(RemoveProposal { removed: us });
             inline_proposals.push(remove_proposal);
         };
 
@@ -230,74 +246,94 @@ impl ProcessedWelcome {
     ///
     /// This does not require a ratchet tree yet.
     ///
+    /// The key store is searched for a key package bundle matching any of the
+    /// key package references in the [`Welcome`], so a client can publish
+    /// multiple key packages at once. The matching bundle is deleted from the
+    /// key store, unless its key package is a last resort key package.
+    ///
+    /// Returns an error ([`WelcomeError::GroupAlreadyExists`]) if we are
+    /// still a member of a group with the same group ID in storage, using the
+    /// same signature key.
+    ///
+    /// Returns an error ([`WelcomeError::KeyPackageExpired`]) if the lifetime
+    /// of the key package is not valid according to the
+    /// [`LifetimePolicy`](crate::key_packages::LifetimePolicy) of
+    /// the [`MlsGroupJoinConfig`].
+    ///
     /// [`Welcome`]: crate::messages::Welcome
     pub fn new_from_welcome<Provider: OpenMlsProvider>(
         provider: &Provider,
         mls_group_config: &MlsGroupJoinConfig,
         welcome: Welcome,
     ) -> Result<Self, WelcomeError<Provider::StorageError>> {
-        let (resumption_psk_store, key_package_bundle) =
-            keys_for_welcome(mls_group_config, &welcome, provider)?;
+        let resumption_psk_store =
+            ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
+        let key_package_bundle = key_package_bundle_for_welcome(&welcome, provider)?;
+
+        // Some KATs use key packages that are expired by now. The check is
+        // skipped for them like for the leaf nodes in the ratchet tree.
+        if !crate::skip_validation::is_disabled::leaf_node_lifetime()
+            && mls_group_config
+                .lifetime_policy
+                .validate_with_time_provider(
+                    key_package_bundle.key_package().life_time(),
+                    time_provider(provider.crypto()),
+                )
+                .is_err()
+        {
+            return Err(WelcomeError::KeyPackageExpired);
+        }
 
         let ciphersuite = welcome.ciphersuite();
-        let Some(egs) = welcome.find_encrypted_group_secret(
-            key_package_bundle
-                .key_package()
-                .hash_ref(provider.crypto())?,
-        ) else {
-            return Err(WelcomeError::JoinerSecretNotFound);
-        };
-        if ciphersuite != key_package_bundle.key_package().ciphersuite() {
-            let e = WelcomeError::CiphersuiteMismatch;
-            log::debug!("new_from_welcome {:?}", e);
-            return Err(e);
-        }
-        let group_secrets = GroupSecrets::try_from_ciphertext(
-            key_package_bundle.init_private_key(),
-            egs.encrypted_group_secrets(),
-            welcome.encrypted_group_info(),
-            ciphersuite,
-            provider.crypto(),
+        let (group_secrets, key_schedule, verifiable_group_info) = decrypt_welcome(
+            provider,
+            &resumption_psk_store,
+            &welcome,
+            &key_package_bundle,
         )?;
-        let psk_secret = {
-            let psks = load_psks(
-                provider.storage(),
-                &resumption_psk_store,
-                &group_secrets.psks,
-            )?;
 
-            PskSecret::new(provider.crypto(), ciphersuite, psks)?
-        };
-        let key_schedule = KeySchedule::init(
-            ciphersuite,
-            provider.crypto(),
-            &group_secrets.joiner_secret,
-            psk_secret,
-        )?;
-        let (welcome_key, welcome_nonce) = key_schedule
-            .welcome(provider.crypto(), ciphersuite)
-            .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?
-            .derive_welcome_key_nonce(provider.crypto(), ciphersuite)
-            .map_err(LibraryError::unexpected_crypto_error)?;
-        let verifiable_group_info = VerifiableGroupInfo::try_from_ciphertext(
-            &welcome_key,
-            &welcome_nonce,
-            welcome.encrypted_group_info(),
-            &[],
-            provider.crypto(),
-        )?;
-        if let Some(required_capabilities) =
-            verifiable_group_info.extensions().required_capabilities()
+        // Also check that our key package actually supports the required
+        // capabilities and the group context extensions. As per the spec, the
+        // sender must have checked this. But you never know.
+        let group_context_extensions = verifiable_group_info.group_context_extensions();
+        let missing_capabilities = key_package_bundle
+            .key_package()
+            .leaf_node()
+            .capabilities()
+            .missing_capabilities(
+                group_context_extensions.required_capabilities(),
+                group_context_extensions,
+            );
+        if !missing_capabilities.is_empty() {
+            return Err(WelcomeError::IncompatibleKeyPackage(missing_capabilities));
+        }
+
+        // If we are still a member of this group with the same signature key,
+        // joining again would overwrite that state with a divergent one. The
+        // state of a group we were removed from can be replaced. The state is
+        // only ours if we hold the private key of its own leaf, which isn't
+        // the case if e.g. other members of the group share the storage.
+        if let Some(existing_group) =
+            MlsGroup::load(provider.storage(), verifiable_group_info.group_id())
+                .map_err(WelcomeError::StorageError)?
         {
-            // Also check that our key package actually supports the extensions.
-            // As per the spec, the sender must have checked this. But you never know.
-            key_package_bundle
-                .key_package()
-                .leaf_node()
-                .capabilities()
-                .supports_required_capabilities(required_capabilities)?;
+            let signature_key = key_package_bundle.key_package().leaf_node().signature_key();
+            let is_own_state = |leaf_node: &LeafNode| {
+                leaf_node.signature_key() == signature_key
+                    && existing_group
+                        .read_epoch_keypairs(provider.storage())
+                        .iter()
+                        .any(|keypair| keypair.public_key() == leaf_node.encryption_key())
+            };
+            if existing_group.is_active()
+                && existing_group.own_leaf_node().is_some_and(is_own_state)
+            {
+                return Err(WelcomeError::GroupAlreadyExists);
+            }
         }
 
+        consume_key_package(provider, &key_package_bundle)?;
+
         Ok(Self {
             mls_group_config: mls_group_config.clone(),
             ciphersuite,
@@ -323,6 +359,32 @@ impl ProcessedWelcome {
         &self.group_secrets.psks
     }
 
+    /// Returns `true` if this Welcome message was addressed to a last resort
+    /// key package. See [`StagedWelcome::used_last_resort_key_package()`].
+    pub fn used_last_resort_key_package(&self) -> bool {
+        self.key_package_bundle.key_package().last_resort()
+    }
+
+    /// Check that the epoch of the group in this Welcome message is at least
+    /// `min_epoch`.
+    ///
+    /// The epoch in a Welcome can not be related to a point in time by itself.
+    /// Applications that know which epoch a group had reached at a given time,
+    /// e.g. from their delivery service, can use this to reject Welcomes that
+    /// are implausibly old, such as replayed ones.
+    ///
+    /// Returns [`WelcomeError::StaleEpoch`] if the epoch is older.
+    pub fn check_epoch<StorageError>(
+        &self,
+        min_epoch: GroupEpoch,
+    ) -> Result<(), WelcomeError<StorageError>> {
+        let epoch = self.verifiable_group_info.epoch();
+        if epoch < min_epoch {
+            return Err(WelcomeError::StaleEpoch { epoch, min_epoch });
+        }
+        Ok(())
+    }
+
     /// Consume the `ProcessedWelcome` and combine it witht he ratchet tree into
     /// a `StagedWelcome`.
     pub fn into_staged_welcome<Provider: OpenMlsProvider>(
@@ -336,8 +398,13 @@ impl ProcessedWelcome {
         // If we got a ratchet tree extension in the welcome, we enable it for
         // this group. Note that this is not strictly necessary. But there's
         // currently no other mechanism to enable the extension.
-        let ratchet_tree = match self.verifiable_group_info.extensions().ratchet_tree() {
-            Some(extension) => extension.ratchet_tree().clone(),
+        let ratchet_tree = match self
+            .verifiable_group_info
+            .extensions()
+            .ratchet_tree_in()
+            .map_err(|_| WelcomeError::InvalidRatchetTree)?
+        {
+            Some(ratchet_tree) => ratchet_tree,
             None => match ratchet_tree {
                 Some(ratchet_tree) => ratchet_tree,
                 None => return Err(WelcomeError::MissingRatchetTree),
@@ -346,31 +413,25 @@ impl ProcessedWelcome {
 
         // Since there is currently only the external pub extension, there is no
         // group info extension of interest here.
-        let (public_group, _group_info_extensions) = PublicGroup::from_external(
-            provider.crypto(),
-            provider.storage(),
-            ratchet_tree,
-            self.verifiable_group_info.clone(),
-            ProposalStore::new(),
-        )?;
+        let (public_group, _group_info_extensions) =
+            PublicGroup::from_external_with_lifetime_policy(
+                provider.crypto(),
+                provider.storage(),
+                ratchet_tree,
+                self.verifiable_group_info.clone(),
+                ProposalStore::new(),
+                self.mls_group_config.lifetime_policy,
+            )?;
 
         // Find our own leaf in the tree.
         let own_leaf_index = public_group
-            .members()
-            .find_map(|m| {
-                if m.signature_key
-                    == self
-                        .key_package_bundle
-                        .key_package()
-                        .leaf_node()
-                        .signature_key()
-                        .as_slice()
-                {
-                    Some(m.index)
-                } else {
-                    None
-                }
-            })
+            .member_index_by_signature_key(
+                self.key_package_bundle
+                    .key_package()
+                    .leaf_node()
+                    .signature_key()
+                    .as_slice(),
+            )
             .ok_or(WelcomeError::PublicTreeError(
                 PublicTreeError::MalformedTree,
             ))?;
@@ -465,6 +526,84 @@ impl ProcessedWelcome {
     }
 }
 
+impl WelcomePreview {
+    /// Creates a preview of the group the [`Welcome`] message invites us to,
+    /// by decrypting the group secrets and the group info.
+    ///
+    /// The key package the [`Welcome`] is addressed to is looked up in the key
+    /// store, but not deleted. Returns an error
+    /// ([`WelcomeError::NoMatchingKeyPackage`]) if no [`KeyPackage`] can be
+    /// found.
+    ///
+    /// If the ratchet tree is not delivered in the [`Welcome`], it can be
+    /// passed as `ratchet_tree` to obtain the members of the group.
+    ///
+    /// [`Welcome`]: crate::messages::Welcome
+    pub fn new_from_welcome<Provider: OpenMlsProvider>(
+        provider: &Provider,
+        welcome: &Welcome,
+        ratchet_tree: Option<&RatchetTreeIn>,
+    ) -> Result<Self, WelcomeError<Provider::StorageError>> {
+        let key_package_bundle = key_package_bundle_for_welcome(welcome, provider)?;
+        // A new member doesn't have any resumption PSKs of the group yet.
+        let resumption_psk_store = ResumptionPskStore::new(0);
+        let (_group_secrets, _key_schedule, verifiable_group_info) = decrypt_welcome(
+            provider,
+            &resumption_psk_store,
+            welcome,
+            &key_package_bundle,
+        )?;
+
+        let extension_ratchet_tree = verifiable_group_info
+            .extensions()
+            .ratchet_tree_in()
+            .map_err(|_| WelcomeError::InvalidRatchetTree)?;
+        let members = extension_ratchet_tree
+            .as_ref()
+            .or(ratchet_tree)
+            .map(RatchetTreeIn::unverified_members);
+
+        Ok(Self {
+            ciphersuite: welcome.ciphersuite(),
+            verifiable_group_info,
+            members,
+        })
+    }
+
+    /// Returns the ciphersuite of the group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    /// Returns the (unverified) ID of the group.
+    pub fn group_id(&self) -> &GroupId {
+        self.verifiable_group_info.group_id()
+    }
+
+    /// Returns the (unverified) epoch of the group.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.verifiable_group_info.epoch()
+    }
+
+    /// Returns the (unverified) group context extensions of the group.
+    pub fn group_context_extensions(&self) -> &Extensions {
+        self.verifiable_group_info.group_context_extensions()
+    }
+
+    /// Returns the (unverified) extensions of the group info.
+    pub fn group_info_extensions(&self) -> &Extensions {
+        self.verifiable_group_info.extensions()
+    }
+
+    /// Returns the (unverified) members of the group, or `None` if the ratchet
+    /// tree is neither part of the [`Welcome`] nor was it passed in.
+    ///
+    /// [`Welcome`]: crate::messages::Welcome
+    pub fn members(&self) -> Option<&[Member]> {
+        self.members.as_deref()
+    }
+}
+
 impl StagedWelcome {
     /// Creates a new staged welcome from a [`Welcome`] message. Returns an error
     /// ([`WelcomeError::NoMatchingKeyPackage`]) if no [`KeyPackage`]
@@ -485,6 +624,48 @@ impl StagedWelcome {
         processed_welcome.into_staged_welcome(provider, ratchet_tree)
     }
 
+    /// Creates a new staged welcome from a [`Welcome`] message, fetching the
+    /// ratchet tree on demand.
+    ///
+    /// If the [`Welcome`] does not contain a ratchet tree extension,
+    /// `fetch_ratchet_tree` is called with the ID of the group that is being
+    /// joined. If it returns `None`, [`WelcomeError::MissingRatchetTree`] is
+    /// returned. The callback is not called if the ratchet tree extension is
+    /// present.
+    ///
+    /// If the ratchet tree has to be fetched asynchronously, use
+    /// [`ProcessedWelcome::new_from_welcome()`] instead, fetch the tree using
+    /// the group ID from [`ProcessedWelcome::unverified_group_info()`] and pass
+    /// it to [`ProcessedWelcome::into_staged_welcome()`].
+    ///
+    /// Note: calling this function will consume the key material for decrypting the [`Welcome`]
+    /// message, even if the caller does not turn the [`StagedWelcome`] into an [`MlsGroup`].
+    ///
+    /// [`Welcome`]: crate::messages::Welcome
+    pub fn new_from_welcome_with_ratchet_tree_fetcher<Provider: OpenMlsProvider>(
+        provider: &Provider,
+        mls_group_config: &MlsGroupJoinConfig,
+        welcome: Welcome,
+        fetch_ratchet_tree: impl FnOnce(&GroupId) -> Option<RatchetTreeIn>,
+    ) -> Result<Self, WelcomeError<Provider::StorageError>> {
+        let processed_welcome =
+            ProcessedWelcome::new_from_welcome(provider, mls_group_config, welcome)?;
+
+        let group_info = processed_welcome.unverified_group_info();
+        let ratchet_tree = if group_info
+            .extensions()
+            .ratchet_tree_in()
+            .map_err(|_| WelcomeError::InvalidRatchetTree)?
+            .is_some()
+        {
+            None
+        } else {
+            fetch_ratchet_tree(group_info.group_id())
+        };
+
+        processed_welcome.into_staged_welcome(provider, ratchet_tree)
+    }
+
     /// Returns the [`LeafNodeIndex`] of the group member that authored the [`Welcome`] message.
     ///
     /// [`Welcome`]: crate::messages::Welcome
@@ -514,6 +695,19 @@ impl StagedWelcome {
         self.public_group.members()
     }
 
+    /// Returns `true` if the [`Welcome`] was addressed to a last resort key
+    /// package.
+    ///
+    /// Last resort key packages are not deleted from the key store when they
+    /// are used, since the delivery service may hand them out repeatedly. The
+    /// application should publish fresh key packages and eventually replace
+    /// the last resort key package when this returns `true`.
+    ///
+    /// [`Welcome`]: crate::messages::Welcome
+    pub fn used_last_resort_key_package(&self) -> bool {
+        self.key_package_bundle.key_package().last_resort()
+    }
+
     /// Consumes the [`StagedWelcome`] and returns the respective [`MlsGroup`].
     pub fn into_group<Provider: OpenMlsProvider>(
         self,
@@ -554,16 +748,106 @@ impl StagedWelcome {
     }
 }
 
-fn keys_for_welcome<Provider: OpenMlsProvider>(
-    mls_group_config: &MlsGroupJoinConfig,
-    welcome: &Welcome,
+/// Decrypts the [`GroupSecrets`] and the [`VerifiableGroupInfo`] of the
+/// [`Welcome`] using the given key package bundle.
+fn decrypt_welcome<Provider: OpenMlsProvider>(
     provider: &Provider,
+    resumption_psk_store: &ResumptionPskStore,
+    welcome: &Welcome,
+    key_package_bundle: &KeyPackageBundle,
 ) -> Result<
-    (ResumptionPskStore, KeyPackageBundle),
+    (GroupSecrets, KeySchedule, VerifiableGroupInfo),
     WelcomeError<<Provider as OpenMlsProvider>::StorageError>,
 > {
-    let resumption_psk_store = ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
-    let key_package_bundle: KeyPackageBundle = welcome
+    let ciphersuite = welcome.ciphersuite();
+    let Some(egs) = welcome.find_encrypted_group_secret(
+        key_package_bundle
+            .key_package()
+            .hash_ref(provider.crypto())?,
+    ) else {
+        return Err(WelcomeError::JoinerSecretNotFound);
+    };
+    if ciphersuite != key_package_bundle.key_package().ciphersuite() {
+        let e = WelcomeError::CiphersuiteMismatch;
+        log::debug!("new_from_welcome {:?}", e);
+        return Err(e);
+    }
+    let group_secrets = GroupSecrets::try_from_ciphertext(
+        key_package_bundle.init_private_key(),
+        egs.encrypted_group_secrets(),
+        welcome.encrypted_group_info(),
+        ciphersuite,
+        provider.crypto(),
+    )?;
+    let psk_secret = {
+        let missing_psks = missing_psks(
+            provider.storage(),
+            resumption_psk_store,
+            &group_secrets.psks,
+        )
+        .map_err(WelcomeError::StorageError)?;
+        if !missing_psks.is_empty() {
+            return Err(WelcomeError::MissingPsks(missing_psks));
+        }
+
+        let psks = load_psks(
+            provider.storage(),
+            resumption_psk_store,
+            &group_secrets.psks,
+        )?;
+
+        PskSecret::new(provider.crypto(), ciphersuite, psks)?
+    };
+    let key_schedule = KeySchedule::init(
+        ciphersuite,
+        provider.crypto(),
+        &group_secrets.joiner_secret,
+        psk_secret,
+    )?;
+    let (welcome_key, welcome_nonce) = key_schedule
+        .welcome(provider.crypto(), ciphersuite)
+        .map_err(|_| LibraryError::custom("Using the key schedule in the wrong state"))?
+        .derive_welcome_key_nonce(provider.crypto(), ciphersuite)
+        .map_err(LibraryError::unexpected_crypto_error)?;
+    let verifiable_group_info = VerifiableGroupInfo::try_from_ciphertext(
+        &welcome_key,
+        &welcome_nonce,
+        welcome.encrypted_group_info(),
+        &[],
+        provider.crypto(),
+    )?;
+    // The group must not be of another ciphersuite than the one the Welcome was
+    // encrypted with.
+    if verifiable_group_info.ciphersuite() != ciphersuite {
+        return Err(WelcomeError::GroupInfoCiphersuiteMismatch);
+    }
+
+    Ok((group_secrets, key_schedule, verifiable_group_info))
+}
+
+/// Deletes the key package bundle used to join a group from storage, unless it
+/// is a last resort key package.
+fn consume_key_package<Provider: OpenMlsProvider>(
+    provider: &Provider,
+    key_package_bundle: &KeyPackageBundle,
+) -> Result<(), WelcomeError<<Provider as OpenMlsProvider>::StorageError>> {
+    if !key_package_bundle.key_package().last_resort() {
+        provider
+            .storage()
+            .delete_key_package(&key_package_bundle.key_package.hash_ref(provider.crypto())?)
+            .map_err(WelcomeError::StorageError)?;
+    } else {
+        log::debug!("Key package has last resort extension, not deleting");
+    }
+    Ok(())
+}
+
+/// Loads the key package bundle the [`Welcome`] is addressed to from storage.
+fn key_package_bundle_for_welcome<Provider: OpenMlsProvider>(
+    welcome: &Welcome,
+    provider: &Provider,
+) -> Result<KeyPackageBundle, WelcomeError<<Provider as OpenMlsProvider>::StorageError>> {
+    welcome
         .secrets()
         .iter()
         .find_map(|egs| {
@@ -575,14 +859,5 @@ fn keys_for_welcome<Provider: OpenMlsProvider>(
                 .map_err(WelcomeError::StorageError)
                 .transpose()
         })
-        .ok_or(WelcomeError::NoMatchingKeyPackage)??;
-    if !key_package_bundle.key_package().last_resort() {
-        provider
-            .storage()
-            .delete_key_package(&key_package_bundle.key_package.hash_ref(provider.crypto())?)
-            .map_err(WelcomeError::StorageError)?;
-    } else {
-        log::debug!("Key package has last resort extension, not deleting");
-    }
-    Ok((resumption_psk_store, key_package_bundle))
+        .ok_or(WelcomeError::NoMatchingKeyPackage)?
 }
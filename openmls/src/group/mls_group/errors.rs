@@ -5,6 +5,7 @@
 
 // These errors are exposed through `crate::group::errors`.
 
+use openmls_traits::types::Ciphersuite;
 use thiserror::Error;
 
 use crate::{
@@ -15,8 +16,9 @@ use crate::{
             CreateAddProposalError, CreateCommitError, MergeCommitError, StageCommitError,
             ValidationError,
         },
-        CommitBuilderStageError, CreateGroupContextExtProposalError,
+        CommitBuilderStageError, CreateGroupContextExtProposalError, GroupSnapshot,
     },
+    key_packages::errors::KeyPackagePoolError,
     schedule::errors::PskError,
     treesync::{
         errors::{LeafNodeValidationError, PublicTreeError},
@@ -45,6 +47,9 @@ pub enum NewGroupError<StorageError> {
     /// Invalid extensions set in configuration
     #[error("Invalid extensions set in configuration")]
     InvalidExtensions(#[from] InvalidExtensionError),
+    /// The ciphersuite is not supported by the crypto provider.
+    #[error("The ciphersuite is not supported by the crypto provider.")]
+    UnsupportedCiphersuite,
 }
 
 /// EmptyInput error
@@ -79,6 +84,10 @@ pub enum MlsGroupStateError {
     /// Requested pending proposal hasn't been found in local pending proposals
     #[error("Requested pending proposal hasn't been found in local pending proposals.")]
     PendingProposalNotFound,
+    /// Can't create message because the group has been imported from another
+    /// device and the own leaf hasn't been updated yet.
+    #[error("Can't create message because the group has been imported from another device and the own leaf hasn't been updated yet.")]
+    UpdateRequired,
 }
 
 /// Error merging pending commit
@@ -94,13 +103,16 @@ pub enum MergePendingCommitError<StorageError> {
 
 /// Process message error
 #[derive(Error, Debug, PartialEq, Clone)]
-pub enum ProcessMessageError {
+pub enum ProcessMessageError<StorageError> {
     /// See [`LibraryError`] for more details.
     #[error(transparent)]
     LibraryError(#[from] LibraryError),
     /// The message's wire format is incompatible with the group's wire format policy.
     #[error("The message's wire format is incompatible with the group's wire format policy.")]
     IncompatibleWireFormat,
+    /// The group requires non-empty AAD on application messages, but the message has none.
+    #[error("The group requires non-empty AAD on application messages, but the message has none.")]
+    MissingAad,
     /// See [`ValidationError`] for more details.
     #[error(transparent)]
     ValidationError(#[from] ValidationError),
@@ -116,6 +128,37 @@ pub enum ProcessMessageError {
     /// The proposal is invalid for the Sender of type [External](crate::prelude::Sender::External)
     #[error("The proposal is invalid for the Sender of type External")]
     UnsupportedProposalType,
+    /// The identity validator rejected the credential of a new or updated member.
+    #[error("The identity validator rejected the credential of a new or updated member.")]
+    InvalidIdentity,
+    /// The message changes the credential of a member, which the group rejects.
+    #[error("The message changes the credential of a member, which the group rejects.")]
+    IdentityChanged,
+    /// The message contains a ReInit proposal to a ciphersuite that is not
+    /// approved by the group.
+    #[error("The message contains a ReInit proposal to the unapproved ciphersuite {0:?}.")]
+    UnapprovedReInitCiphersuite(Ciphersuite),
+    /// The updated secret tree could not be written to the storage.
+    #[error("The updated secret tree could not be written to the storage.")]
+    StorageError(StorageError),
+}
+
+/// Error processing a batch of messages with
+/// [`MlsGroup::process_messages()`](super::MlsGroup::process_messages()).
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ProcessBatchError<StorageError> {
+    /// The message is not a public or private message.
+    #[error("The message is not a public or private message.")]
+    NotAProtocolMessage,
+    /// See [`ProcessMessageError`] for more details.
+    #[error(transparent)]
+    ProcessMessageError(#[from] ProcessMessageError<StorageError>),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<StorageError>),
+    /// Error writing a proposal or the updated message secrets to storage.
+    #[error("Error writing to storage.")]
+    StorageError(StorageError),
 }
 
 /// Create message error
@@ -127,6 +170,18 @@ pub enum CreateMessageError {
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
+    /// The message exceeds the maximum application message size of the group.
+    #[error("The message exceeds the maximum application message size of the group.")]
+    MessageTooLarge,
+    /// The chunk size must be larger than zero.
+    #[error("The chunk size must be larger than zero.")]
+    InvalidChunkSize,
+    /// The message would have to be split into too many chunks.
+    #[error("The message would have to be split into too many chunks.")]
+    TooManyChunks,
+    /// The group requires non-empty AAD on application messages, but no AAD was set.
+    #[error("The group requires non-empty AAD on application messages, but no AAD was set.")]
+    MissingAad,
 }
 
 /// Add members error
@@ -147,6 +202,9 @@ pub enum AddMembersError<StorageError> {
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
+    /// See [`LeafNodeValidationError`] for more details.
+    #[error(transparent)]
+    LeafNodeValidation(#[from] LeafNodeValidationError),
     /// Error writing to storage.
     #[error("Error writing to storage")]
     StorageError(StorageError),
@@ -299,6 +357,10 @@ pub enum ExportGroupInfoError {
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
+    /// The ratchet tree extension exceeds the maximum size of the group
+    /// configuration.
+    #[error("The ratchet tree extension exceeds the maximum size of the group configuration.")]
+    RatchetTreeTooLarge,
 }
 
 /// Export secret error
@@ -371,3 +433,161 @@ pub enum RemoveProposalError<StorageError> {
     #[error("error writing proposal to storage")]
     Storage(StorageError),
 }
+
+/// Wire format policy error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum WireFormatPolicyError {
+    /// The incoming wire format policy does not accept the wire format of
+    /// outgoing handshake messages.
+    #[error(
+        "The incoming wire format policy does not accept the wire format of outgoing handshake messages."
+    )]
+    IncompatiblePolicies,
+}
+
+/// Chunk reassembly error
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ChunkReassemblyError {
+    /// The application message could not be decoded as a chunk.
+    #[error("The application message could not be decoded as a chunk.")]
+    MalformedChunk,
+    /// The chunk does not match previously received chunks of the same message.
+    #[error("The chunk does not match previously received chunks of the same message.")]
+    InconsistentChunk,
+    /// A chunk with the same index was already received.
+    #[error("A chunk with the same index was already received.")]
+    DuplicateChunk,
+    /// The reassembled message would exceed the maximum message size.
+    #[error("The reassembled message would exceed the maximum message size.")]
+    MessageTooLarge,
+}
+
+/// Group state snapshot error
+#[cfg(feature = "json")]
+#[derive(Error, Debug)]
+pub enum GroupStateError<StorageError> {
+    /// Reading or writing the snapshot failed.
+    #[error("Reading or writing the snapshot failed: {0}")]
+    Io(#[from] std::io::Error),
+    /// The snapshot doesn't start with the group state snapshot header.
+    #[error("The snapshot doesn't start with the group state snapshot header.")]
+    InvalidHeader,
+    /// The format version of the snapshot is newer than the supported one.
+    #[error("The format version {0} of the snapshot is not supported.")]
+    UnsupportedVersion(u16),
+    /// The snapshot couldn't be deserialized.
+    #[error("The snapshot couldn't be deserialized.")]
+    Malformed,
+    /// The migration of the snapshot from an older format version failed.
+    #[error("The migration of the snapshot failed: {0}")]
+    Migration(String),
+    /// A group with the same group ID already exists in the storage.
+    #[error("A group with the same group ID already exists in the storage.")]
+    GroupAlreadyExists,
+    /// The state of the group is incomplete in the storage.
+    #[error("The state of the group is incomplete in the storage.")]
+    MissingState,
+    /// Error accessing the storage.
+    #[error("Error accessing the storage.")]
+    StorageError(StorageError),
+}
+
+/// Recorded message processing error
+#[cfg(feature = "json")]
+#[derive(Error, Debug, Clone)]
+pub enum RecordedProcessError<StorageError> {
+    /// See [`MlsMessageError`](crate::framing::errors::MlsMessageError) for
+    /// more details.
+    #[error(transparent)]
+    MlsMessageError(#[from] crate::framing::errors::MlsMessageError),
+    /// See [`ProtocolMessageError`](crate::framing::errors::ProtocolMessageError)
+    /// for more details.
+    #[error(transparent)]
+    ProtocolMessageError(#[from] crate::framing::errors::ProtocolMessageError),
+    /// See [`ProcessMessageError`] for more details.
+    #[error(transparent)]
+    ProcessMessageError(#[from] ProcessMessageError<StorageError>),
+}
+
+/// Client state export and import error
+#[cfg(feature = "json")]
+#[derive(Error, Debug)]
+pub enum ClientStateError<StorageError> {
+    /// The key doesn't have the length of a ChaCha20-Poly1305 key.
+    #[error("The key doesn't have the length of a ChaCha20-Poly1305 key.")]
+    InvalidKeyLength,
+    /// The bundle doesn't start with the client state bundle header.
+    #[error("The bundle doesn't start with the client state bundle header.")]
+    InvalidHeader,
+    /// The format version of the bundle is not supported.
+    #[error("The format version {0} of the bundle is not supported.")]
+    UnsupportedVersion(u16),
+    /// Encrypting or decrypting the bundle failed, e.g. because of a wrong key.
+    #[error("Encrypting or decrypting the bundle failed: {0:?}")]
+    Crypto(openmls_traits::types::CryptoError),
+    /// Generating the nonce failed.
+    #[error("Generating the nonce failed.")]
+    Randomness,
+    /// The state couldn't be serialized.
+    #[error("The state couldn't be serialized.")]
+    Serialization,
+    /// The decrypted bundle couldn't be deserialized.
+    #[error("The decrypted bundle couldn't be deserialized.")]
+    Malformed,
+    /// A signature key pair added to the export is not in the storage.
+    #[error("A signature key pair added to the export is not in the storage.")]
+    MissingSignatureKeyPair,
+    /// A key package added to the export is not in the storage.
+    #[error("A key package added to the export is not in the storage.")]
+    MissingKeyPackage,
+    /// See [`GroupStateError`] for more details.
+    #[error(transparent)]
+    GroupState(#[from] GroupStateError<StorageError>),
+    /// Error accessing the storage.
+    #[error("Error accessing the storage.")]
+    StorageError(StorageError),
+}
+
+/// Error of a group operation with an asynchronous key store
+#[cfg(feature = "async")]
+#[derive(Error, Debug)]
+pub enum AsyncOperationError<OperationError, StoreError> {
+    /// The group operation failed.
+    #[error("The group operation failed.")]
+    Operation(OperationError),
+    /// See [`AsyncStoreError`](openmls_memory_storage::async_store::AsyncStoreError) for more details.
+    #[error("Error staging the values of the asynchronous key store.")]
+    Store(#[from] openmls_memory_storage::async_store::AsyncStoreError<StoreError>),
+}
+
+/// Group snapshot error
+#[derive(Error, Debug)]
+pub enum SnapshotRollbackError<StorageError> {
+    /// The snapshot was taken of another group. It is returned, such that it
+    /// can be released or rolled back on its group.
+    #[error("The snapshot was taken of another group.")]
+    WrongGroup(GroupSnapshot),
+    /// The storage doesn't implement transactions, which are required to
+    /// restore a snapshot.
+    #[error(
+        "The storage doesn't implement transactions, which are required to restore a snapshot."
+    )]
+    TransactionsUnsupported,
+    /// The state of the group is incomplete in the storage.
+    #[error("The state of the group is incomplete in the storage.")]
+    MissingState,
+    /// Accessing storage failed.
+    #[error("Accessing storage failed.")]
+    StorageError(StorageError),
+}
+
+/// Key store maintenance error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum KeyStoreMaintenanceError<StorageError> {
+    /// See [`KeyPackagePoolError`] for more details.
+    #[error(transparent)]
+    KeyPackagePoolError(#[from] KeyPackagePoolError<StorageError>),
+    /// Accessing storage failed.
+    #[error("Accessing storage failed.")]
+    StorageError(StorageError),
+}
@@ -61,6 +61,17 @@ impl MlsGroup {
     }
 
     /// Export a group info object for this group.
+    ///
+    /// The group info is signed with `signer`, which must be the signer of the
+    /// own leaf, and contains an external public key extension, so that it can
+    /// be published for outsiders to join the group through an external commit.
+    /// If `with_ratchet_tree` is `true`, a ratchet tree extension is included
+    /// as well. Otherwise, the ratchet tree has to be distributed out of band.
+    ///
+    /// # Errors:
+    /// Returns an [`ExportGroupInfoError::RatchetTreeTooLarge`] if
+    /// `with_ratchet_tree` is `true` and the ratchet tree extension exceeds
+    /// the `max_ratchet_tree_extension_size` of the group configuration.
     pub fn export_group_info<Provider: OpenMlsProvider>(
         &self,
         provider: &Provider,
@@ -68,12 +79,6 @@ impl MlsGroup {
         with_ratchet_tree: bool,
     ) -> Result<MlsMessageOut, ExportGroupInfoError> {
         let extensions = {
-            let ratchet_tree_extension = || {
-                Extension::RatchetTree(RatchetTreeExtension::new(
-                    self.public_group().export_ratchet_tree(),
-                ))
-            };
-
             let external_pub_extension = || -> Result<Extension, ExportGroupInfoError> {
                 let external_pub = self
                     .group_epoch_secrets()
@@ -86,16 +91,24 @@ impl MlsGroup {
                 )))
             };
 
-            if with_ratchet_tree {
-                Extensions::from_vec(vec![ratchet_tree_extension(), external_pub_extension()?])
-                    .map_err(|_| {
-                        LibraryError::custom(
-                            "There should not have been duplicate extensions here.",
-                        )
-                    })?
+            let ratchet_tree_extension = if with_ratchet_tree {
+                let extension = self
+                    .configuration()
+                    .ratchet_tree_extension(self.public_group().export_ratchet_tree())?
+                    .ok_or(ExportGroupInfoError::RatchetTreeTooLarge)?;
+                Some(extension)
             } else {
-                Extensions::single(external_pub_extension()?)
-            }
+                None
+            };
+            Extensions::from_vec(
+                ratchet_tree_extension
+                    .into_iter()
+                    .chain([external_pub_extension()?])
+                    .collect(),
+            )
+            .map_err(|_| {
+                LibraryError::custom("There should not have been duplicate extensions here.")
+            })?
         };
 
         // Create to-be-signed group info.
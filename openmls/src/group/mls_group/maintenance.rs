@@ -0,0 +1,146 @@
+//! # Key store maintenance
+//!
+//! This module contains the [`KeyStoreMaintenance`] routine, which deletes
+//! key material that is no longer needed from the storage.
+
+use openmls_traits::storage::StorageProvider as _;
+
+use super::{errors::KeyStoreMaintenanceError, *};
+use crate::{ciphersuite::hash_ref::KeyPackageRef, key_packages::KeyPackagePool};
+
+/// A routine that deletes key material which is no longer needed from the
+/// storage.
+///
+/// Forward secrecy depends on old secrets actually being deleted. Running the
+/// routine regularly
+/// - forgets key packages of a [`KeyPackagePool`] that were consumed, and
+///   deletes the ones that expire soon (see [`KeyPackagePool::prune()`]), and
+/// - deletes the secrets of groups from epochs that are older than the
+///   retention window (see [`MlsGroup::prune_past_epochs()`]).
+///
+/// The removed material is reported in a [`MaintenanceReport`].
+///
+/// ```
+/// use openmls::prelude::*;
+/// use openmls_rust_crypto::OpenMlsRustCrypto;
+///
+/// let provider = OpenMlsRustCrypto::default();
+/// let mut groups: Vec<MlsGroup> = Vec::new();
+///
+/// let report = KeyStoreMaintenance::new(2)
+///     .run(&provider, None, groups.iter_mut())
+///     .unwrap();
+/// assert!(report.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStoreMaintenance {
+    retained_epochs: usize,
+}
+
+impl KeyStoreMaintenance {
+    /// Create a routine that keeps the secrets of the last `retained_epochs`
+    /// past epochs of each group.
+    pub fn new(retained_epochs: usize) -> Self {
+        Self { retained_epochs }
+    }
+
+    /// Returns the number of past epochs of which the secrets are kept.
+    pub fn retained_epochs(&self) -> usize {
+        self.retained_epochs
+    }
+
+    /// Prune the `key_package_pool`, if any, and the past epochs of the
+    /// `groups`.
+    pub fn run<'a, Provider: OpenMlsProvider>(
+        &self,
+        provider: &Provider,
+        key_package_pool: Option<&mut KeyPackagePool>,
+        groups: impl IntoIterator<Item = &'a mut MlsGroup>,
+    ) -> Result<MaintenanceReport, KeyStoreMaintenanceError<Provider::StorageError>> {
+        let mut report = MaintenanceReport::default();
+
+        if let Some(key_package_pool) = key_package_pool {
+            report.removed_key_packages = key_package_pool.prune(provider)?;
+        }
+
+        for group in groups {
+            let pruned_epochs = group
+                .prune_past_epochs(provider, self.retained_epochs)
+                .map_err(KeyStoreMaintenanceError::StorageError)?;
+            if !pruned_epochs.is_empty() {
+                report
+                    .pruned_epochs
+                    .push((group.group_id().clone(), pruned_epochs));
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// The key material removed by [`KeyStoreMaintenance::run()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    removed_key_packages: Vec<KeyPackageRef>,
+    pruned_epochs: Vec<(GroupId, Vec<GroupEpoch>)>,
+}
+
+impl MaintenanceReport {
+    /// Returns the references of the key packages that were removed from the
+    /// key package pool. Published ones among them should also be removed from
+    /// the delivery service.
+    pub fn removed_key_packages(&self) -> &[KeyPackageRef] {
+        &self.removed_key_packages
+    }
+
+    /// Returns the groups of which secrets were deleted, together with the
+    /// epochs of the deleted secrets.
+    pub fn pruned_epochs(&self) -> &[(GroupId, Vec<GroupEpoch>)] {
+        &self.pruned_epochs
+    }
+
+    /// Returns `true` if nothing was removed.
+    pub fn is_empty(&self) -> bool {
+        self.removed_key_packages.is_empty() && self.pruned_epochs.is_empty()
+    }
+}
+
+impl MlsGroup {
+    /// Delete the message secrets and resumption PSKs of all epochs that are
+    /// more than `retained_epochs` epochs in the past from this group and the
+    /// storage.
+    ///
+    /// Messages from the deleted epochs can no longer be decrypted. Returns
+    /// the epochs of which secrets were deleted.
+    pub fn prune_past_epochs<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        retained_epochs: usize,
+    ) -> Result<Vec<GroupEpoch>, Provider::StorageError> {
+        let oldest_epoch = GroupEpoch::from(
+            self.context()
+                .epoch()
+                .as_u64()
+                .saturating_sub(retained_epochs as u64),
+        );
+
+        let mut pruned_epochs = self.message_secrets_store.prune_before(oldest_epoch);
+        let pruned_psk_epochs = self.resumption_psk_store.prune_before(oldest_epoch);
+        let psks_pruned = !pruned_psk_epochs.is_empty();
+        if !pruned_epochs.is_empty() {
+            provider
+                .storage()
+                .write_message_secrets(self.group_id(), &self.message_secrets_store)?;
+        }
+        if psks_pruned {
+            provider
+                .storage()
+                .write_resumption_psk_store(self.group_id(), &self.resumption_psk_store)?;
+        }
+
+        pruned_epochs.extend(pruned_psk_epochs);
+        pruned_epochs.sort();
+        pruned_epochs.dedup();
+        Ok(pruned_epochs)
+    }
+}
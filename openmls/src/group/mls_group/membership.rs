@@ -11,8 +11,9 @@ use super::{
     *,
 };
 use crate::{
-    binary_tree::array_representation::LeafNodeIndex, key_packages::KeyPackage,
-    messages::group_info::GroupInfo, storage::OpenMlsProvider, treesync::LeafNode,
+    binary_tree::array_representation::LeafNodeIndex, ciphersuite::hash_ref::KeyPackageRef,
+    key_packages::KeyPackage, messages::group_info::GroupInfo, storage::OpenMlsProvider,
+    treesync::LeafNode,
 };
 
 impl MlsGroup {
@@ -29,7 +30,8 @@ impl MlsGroup {
     /// contains the commit, the second one the [`Welcome`] and the third an optional [GroupInfo] that
     /// will be [Some] if the group has the `use_ratchet_tree_extension` flag set.
     ///
-    /// Returns an error if there is a pending commit.
+    /// Returns an error if there is a pending commit or if one of the key
+    /// packages doesn't support all extensions in the group context.
     ///
     /// [`Welcome`]: crate::messages::Welcome
     // FIXME: #1217
@@ -62,7 +64,8 @@ impl MlsGroup {
     /// an optional [GroupInfo] that will be [Some] if the group has the
     /// `use_ratchet_tree_extension` flag set.
     ///
-    /// Returns an error if there is a pending commit.
+    /// Returns an error if there is a pending commit or if one of the key
+    /// packages doesn't support all extensions in the group context.
     ///
     /// [`Welcome`]: crate::messages::Welcome
     // FIXME: #1217
@@ -96,6 +99,13 @@ impl MlsGroup {
             return Err(AddMembersError::EmptyInput(EmptyInputError::AddMembers));
         }
 
+        for key_package in key_packages {
+            key_package
+                .leaf_node()
+                .capabilities()
+                .supports_group_context_extensions(self.context().extensions())?;
+        }
+
         let bundle = self
             .commit_builder()
             .propose_adds(key_packages.iter().cloned())
@@ -238,8 +248,23 @@ impl MlsGroup {
                     leaf_node.signature_key().as_slice().to_vec(),
                     leaf_node.credential().clone(),
                 )
+                .with_application_id(leaf_node.application_id())
             })
     }
+
+    /// Returns the leaf index of the member that was added to the group with
+    /// the key package with the given [`KeyPackageRef`].
+    ///
+    /// Returns `None` if there is no such member, or if the member was not
+    /// added by a commit that this client merged, e.g. because it was already
+    /// in the group when this client joined.
+    pub fn member_index_by_key_package_ref(
+        &self,
+        key_package_ref: &KeyPackageRef,
+    ) -> Option<LeafNodeIndex> {
+        self.public_group()
+            .member_index_by_key_package_ref(key_package_ref)
+    }
 }
 
 /// Helper `enum` that classifies the kind of remove operation. This can be used to
@@ -0,0 +1,341 @@
+//! Memory usage of a group
+//!
+//! [`MlsGroup::memory_stats()`] reports the approximate number of bytes held
+//! by the different parts of the group state, such that applications can tune
+//! e.g. the number of past epochs to keep against real numbers.
+//!
+//! The sizes are the number of bytes of the data in the group state, as they
+//! are counted when serializing it. The overhead of the allocations and of the
+//! collections is not included.
+
+use std::fmt;
+
+use serde::{ser, Serialize};
+
+use super::MlsGroup;
+
+/// The approximate memory usage of an [`MlsGroup`] in bytes, as returned by
+/// [`MlsGroup::memory_stats()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// The nodes of the ratchet tree, including the cached tree hashes.
+    pub treesync_bytes: usize,
+    /// The secret tree and the sender ratchets of the current epoch.
+    pub secret_tree_bytes: usize,
+    /// The message secrets and members of the past epochs that are kept for
+    /// the decryption of late messages, see the `max_past_epochs` of the
+    /// group configuration.
+    pub past_epochs_bytes: usize,
+    /// The proposals in the proposal store.
+    pub pending_proposals_bytes: usize,
+}
+
+impl MemoryStats {
+    /// Returns the sum of all sizes.
+    pub fn total_bytes(&self) -> usize {
+        self.treesync_bytes
+            + self.secret_tree_bytes
+            + self.past_epochs_bytes
+            + self.pending_proposals_bytes
+    }
+}
+
+impl MlsGroup {
+    /// Returns the approximate number of bytes held by the different parts of
+    /// the group state.
+    ///
+    /// This iterates over the whole group state and should be used for
+    /// instrumentation only.
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            treesync_bytes: self.public_group.tree_memory_size(),
+            secret_tree_bytes: self.message_secrets_store.secret_tree_memory_size(),
+            past_epochs_bytes: self.message_secrets_store.past_epochs_memory_size(),
+            pending_proposals_bytes: approximate_size(self.proposal_store()),
+        }
+    }
+}
+
+/// Returns the number of bytes of the data in `value`.
+pub(crate) fn approximate_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    // Counting never fails, but serializing a value can fail on its own.
+    match value.serialize(&mut counter) {
+        Ok(()) => counter.0,
+        Err(fmt::Error) => 0,
+    }
+}
+
+/// A [`ser::Serializer`] that only counts the bytes of the serialized
+/// primitive values.
+struct ByteCounter(usize);
+
+impl ByteCounter {
+    fn count(&mut self, bytes: usize) -> Result<(), fmt::Error> {
+        self.0 += bytes;
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), fmt::Error> {
+        self.count(1)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), fmt::Error> {
+        self.count(1)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), fmt::Error> {
+        self.count(2)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), fmt::Error> {
+        self.count(4)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), fmt::Error> {
+        self.count(8)
+    }
+
+    fn serialize_i128(self, _v: i128) -> Result<(), fmt::Error> {
+        self.count(16)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), fmt::Error> {
+        self.count(1)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), fmt::Error> {
+        self.count(2)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), fmt::Error> {
+        self.count(4)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), fmt::Error> {
+        self.count(8)
+    }
+
+    fn serialize_u128(self, _v: u128) -> Result<(), fmt::Error> {
+        self.count(16)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), fmt::Error> {
+        self.count(4)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), fmt::Error> {
+        self.count(8)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), fmt::Error> {
+        self.count(4)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), fmt::Error> {
+        self.count(v.len())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), fmt::Error> {
+        self.count(v.len())
+    }
+
+    fn serialize_none(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), fmt::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), fmt::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, fmt::Error> {
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), fmt::Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut ByteCounter {
+    type Ok = ();
+    type Error = fmt::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), fmt::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), fmt::Error> {
+        Ok(())
+    }
+}
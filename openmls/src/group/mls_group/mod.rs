@@ -19,9 +19,10 @@ use super::{
 };
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
-    ciphersuite::{hash_ref::ProposalRef, signable::Signable},
-    credentials::Credential,
+    ciphersuite::{hash_ref::ProposalRef, signable::Signable, SignaturePublicKey},
+    credentials::{Credential, CredentialWithKey},
     error::LibraryError,
+    extensions::ExtensionRegistry,
     framing::{mls_auth_content::AuthenticatedContent, *},
     group::{
         CreateCommitError, CreateGroupContextExtProposalError, Extension, ExtensionType,
@@ -59,16 +60,30 @@ mod updates;
 use config::*;
 
 // Crate
+#[cfg(feature = "async")]
+pub(crate) mod async_store;
+pub(crate) mod batch;
+pub(crate) mod chunking;
+#[cfg(feature = "json")]
+pub(crate) mod client_state;
 pub(crate) mod commit_builder;
 pub(crate) mod config;
 pub(crate) mod create_commit;
 pub(crate) mod errors;
+pub(crate) mod maintenance;
 pub(crate) mod membership;
+#[cfg(feature = "memory-stats")]
+pub(crate) mod memory_stats;
 pub(crate) mod past_secrets;
+#[cfg(feature = "json")]
+pub(crate) mod persistence;
 pub(crate) mod processing;
 pub(crate) mod proposal;
 pub(crate) mod proposal_store;
+pub(crate) mod snapshot;
 pub(crate) mod staged_commit;
+#[cfg(feature = "json")]
+pub(crate) mod trace;
 
 // Tests
 #[cfg(test)]
@@ -93,10 +108,18 @@ pub struct Member {
     pub encryption_key: Vec<u8>,
     /// The member's public signature key.
     pub signature_key: Vec<u8>,
+    /// The application id of the member's leaf node, if any.
+    #[serde(default)]
+    application_id: Option<Vec<u8>>,
 }
 
 impl Member {
-    /// Create new member.
+    /// Create a new member from the values of its leaf node.
+    ///
+    /// The application id of the new member is `None`. Since the application
+    /// id is a private field, this is the only way to construct a [`Member`]
+    /// outside of OpenMLS; the members of a group are returned by
+    /// [`MlsGroup::members()`].
     pub fn new(
         index: LeafNodeIndex,
         encryption_key: Vec<u8>,
@@ -108,8 +131,35 @@ impl Member {
             encryption_key,
             signature_key,
             credential,
+            application_id: None,
         }
     }
+
+    /// Returns the application id of the member's leaf node, if any.
+    pub fn application_id(&self) -> Option<&[u8]> {
+        self.application_id.as_deref()
+    }
+
+    /// Set the application id of the member.
+    pub(crate) fn with_application_id(mut self, application_id: Option<&[u8]>) -> Self {
+        self.application_id = application_id.map(<[u8]>::to_vec);
+        self
+    }
+}
+
+/// A new signer together with the credential and signature key it belongs
+/// to. Used to rotate the own signature key in a self update.
+///
+/// The commit or proposal carrying the update is still signed with the old
+/// signer, since other members verify it against the current leaf node. The
+/// new leaf node and, in the case of a commit, the [`GroupInfo`] are signed
+/// with the new signer.
+#[derive(Debug)]
+pub struct NewSignerBundle<'a, S: Signer> {
+    /// The signer for the new signature key.
+    pub signer: &'a S,
+    /// The credential and signature key of the new leaf node.
+    pub credential_with_key: CredentialWithKey,
 }
 
 /// Pending Commit state. Differentiates between Commits issued by group members
@@ -157,11 +207,19 @@ impl From<PendingCommitState> for StagedCommit {
 ///   when this client creates a commit.
 ///
 /// * [`MlsGroupState::Inactive`]: A group can enter this state from any other
-///   state when it processes a commit that removes this client from the group.
+///   state when it processes a commit that removes this client from the group,
+///   or when it is exported to another device with `ClientStateExport`.
 ///   This is a terminal state that the group can not exit from. If the clients
 ///   wants to re-join the group, it can either be added by a group member or it
 ///   can join via external commit.
 ///
+/// * [`MlsGroupState::UpdateRequired`]: An operational group enters this state
+///   when it is imported on a new device with `ClientStateImport`. It allows
+///   the same operations as the [`MlsGroupState::Operational`] state, except
+///   that no application messages can be created. Once the client has merged
+///   an own commit, e.g. one created with [`MlsGroup::self_update()`], the
+///   group transitions to the [`MlsGroupState::Operational`] state.
+///
 /// * [`MlsGroupState::PendingCommit`]: This state is split into two possible
 ///   sub-states, one for each Commit type:
 ///   [`PendingCommitState::Member`] and [`PendingCommitState::External`]:
@@ -194,8 +252,12 @@ pub enum MlsGroupState {
     PendingCommit(Box<PendingCommitState>),
     /// The group state is in an opertaional state, where new messages and Commits can be created.
     Operational,
-    /// The group is inactive because the member has been removed.
+    /// The group is inactive because the member has been removed or the group
+    /// has been exported to another device.
     Inactive,
+    /// The group has been imported from another device and requires an own
+    /// commit before application messages can be created.
+    UpdateRequired,
 }
 
 /// A `MlsGroup` represents an MLS group with a high-level API. The API exposes
@@ -270,9 +332,21 @@ impl MlsGroup {
         mls_group_config: &MlsGroupJoinConfig,
     ) -> Result<(), Storage::Error> {
         self.mls_group_config = mls_group_config.clone();
+        self.public_group
+            .set_lifetime_policy(mls_group_config.lifetime_policy);
         storage.write_mls_join_config(self.group_id(), mls_group_config)
     }
 
+    /// Sets the [`ExtensionRegistry`] whose application extensions are
+    /// validated in the leaf nodes and key packages of incoming proposals and
+    /// commits, and in group context extensions proposals.
+    ///
+    /// The registry is not persisted, so it has to be set again after the
+    /// group is loaded.
+    pub fn set_extension_registry(&mut self, extension_registry: ExtensionRegistry) {
+        self.public_group.set_extension_registry(extension_registry);
+    }
+
     /// Sets the additional authenticated data (AAD) for the next outgoing
     /// message. This is ephemeral and will be reset by every API call that
     /// successfully returns an [`MlsMessageOut`].
@@ -346,6 +420,26 @@ impl MlsGroup {
             }
             MlsGroupState::Operational => None,
             MlsGroupState::Inactive => None,
+            MlsGroupState::UpdateRequired => None,
+        }
+    }
+
+    /// Returns the signature key that the pending commit rotates the own leaf
+    /// node to, e.g. after [`MlsGroup::self_update_with_new_signer()`]. Returns
+    /// `None` if there is no pending commit or if it keeps the signature key.
+    ///
+    /// The new signature key is staged with the pending commit. It becomes
+    /// the signature key of the own leaf node once the commit is merged with
+    /// [`MlsGroup::merge_pending_commit()`]. Until then, the old signer must be
+    /// used, and afterwards only the signer of the new key.
+    pub fn pending_signature_key(&self) -> Option<&SignaturePublicKey> {
+        let signature_key = self
+            .pending_commit()?
+            .update_path_leaf_node()?
+            .signature_key();
+        match self.own_leaf_node() {
+            Some(own_leaf_node) if own_leaf_node.signature_key() == signature_key => None,
+            _ => Some(signature_key),
         }
     }
 
@@ -373,7 +467,9 @@ impl MlsGroup {
                     Ok(())
                 }
             }
-            MlsGroupState::Operational | MlsGroupState::Inactive => Ok(()),
+            MlsGroupState::Operational
+            | MlsGroupState::Inactive
+            | MlsGroupState::UpdateRequired => Ok(()),
         }
     }
 
@@ -429,13 +525,17 @@ impl MlsGroup {
         let group_state = storage.group_state(group_id)?;
 
         let build = || -> Option<Self> {
+            let mut public_group = public_group?;
+            let mls_group_config: MlsGroupJoinConfig = mls_group_config?;
+            public_group.set_lifetime_policy(mls_group_config.lifetime_policy);
+
             Some(Self {
-                public_group: public_group?,
+                public_group,
                 group_epoch_secrets: group_epoch_secrets?,
                 own_leaf_index: own_leaf_index?,
                 message_secrets_store: message_secrets_store?,
                 resumption_psk_store: resumption_psk_store?,
-                mls_group_config: mls_group_config?,
+                mls_group_config,
                 own_leaf_nodes,
                 aad: vec![],
                 group_state: group_state?,
@@ -577,9 +677,9 @@ impl MlsGroup {
                 .supports_required_capabilities(required_capabilities)?;
 
             // Ensure that all other leaf nodes support all the required
-            // extensions as well.
+            // extension, proposal and credential types as well.
             self.public_group()
-                .check_extension_support(required_capabilities.extension_types())?;
+                .check_required_capabilities_support(required_capabilities)?;
         }
         let proposal = GroupContextExtensionProposal::new(extensions);
         let proposal = Proposal::GroupContextExtensions(proposal);
@@ -765,7 +865,7 @@ impl MlsGroup {
         match self.group_state {
             MlsGroupState::PendingCommit(_) => Err(MlsGroupStateError::PendingCommit),
             MlsGroupState::Inactive => Err(MlsGroupStateError::UseAfterEviction),
-            MlsGroupState::Operational => Ok(()),
+            MlsGroupState::Operational | MlsGroupState::UpdateRequired => Ok(()),
         }
     }
 }
@@ -873,3 +973,18 @@ pub struct ProcessedWelcome {
     resumption_psk_store: crate::schedule::psk::store::ResumptionPskStore,
     key_package_bundle: KeyPackageBundle,
 }
+
+/// A preview of the group a `Welcome` message invites us to.
+///
+/// Unlike a [`ProcessedWelcome`], creating a preview does not delete the key
+/// package the `Welcome` is addressed to from the key store. It can be used to
+/// ask the user whether they want to join the group before actually joining.
+///
+/// **NOTE:** All values are **unverified**, since the group info can only be
+/// verified once the ratchet tree is available. Use with caution.
+#[derive(Debug, Clone)]
+pub struct WelcomePreview {
+    ciphersuite: Ciphersuite,
+    verifiable_group_info: VerifiableGroupInfo,
+    members: Option<Vec<Member>>,
+}
@@ -1,5 +1,7 @@
 use std::collections::VecDeque;
 
+#[cfg(feature = "memory-stats")]
+use super::memory_stats::approximate_size;
 use crate::schedule::message_secrets::MessageSecrets;
 
 use super::*;
@@ -50,6 +52,19 @@ impl MessageSecretsStore {
         }
     }
 
+    /// Returns the approximate number of bytes held by the secret tree of the
+    /// current epoch.
+    #[cfg(feature = "memory-stats")]
+    pub(crate) fn secret_tree_memory_size(&self) -> usize {
+        approximate_size(self.message_secrets.secret_tree())
+    }
+
+    /// Returns the approximate number of bytes held by the past epochs.
+    #[cfg(feature = "memory-stats")]
+    pub(crate) fn past_epochs_memory_size(&self) -> usize {
+        self.past_epoch_trees.iter().map(approximate_size).sum()
+    }
+
     /// Resize the store.
     pub(crate) fn resize(&mut self, max_past_epochs: usize) {
         let old_size = self.max_epochs;
@@ -91,6 +106,20 @@ impl MessageSecretsStore {
         );
     }
 
+    /// Remove the secrets of all past epochs before `oldest_epoch` and return
+    /// the removed epochs.
+    pub(crate) fn prune_before(&mut self, oldest_epoch: GroupEpoch) -> Vec<GroupEpoch> {
+        let mut removed = Vec::new();
+        self.past_epoch_trees.retain(|epoch_tree| {
+            let keep = epoch_tree.epoch >= oldest_epoch.as_u64();
+            if !keep {
+                removed.push(epoch_tree.epoch.into());
+            }
+            keep
+        });
+        removed
+    }
+
     /// Get a mutable reference to a secret tree for a given epoch `group_epoch`.
     /// If no message secrets are found for that epoch, `None` is returned.
     pub(crate) fn secrets_for_epoch_mut(
@@ -148,18 +177,29 @@ impl MessageSecretsStore {
         &[]
     }
 
+    /// Return the [`Member`] at `leaf_index` in the `group_epoch`, or `None`
+    /// if the epoch is unknown or the leaf was blank in that epoch.
+    pub(crate) fn leaf_for_epoch(
+        &self,
+        group_epoch: impl Into<GroupEpoch>,
+        leaf_index: LeafNodeIndex,
+    ) -> Option<&Member> {
+        let leaves = self.leaves_for_epoch(group_epoch);
+        // The leaves are stored in the order of their indices, but blank
+        // leaves are skipped.
+        leaves
+            .binary_search_by_key(&leaf_index, |member| member.index)
+            .ok()
+            .map(|position| &leaves[position])
+    }
+
     /// Check if the provided epoch contains a leaf index.
     pub(crate) fn epoch_has_leaf(
         &self,
         group_epoch: GroupEpoch,
         leaf_index: LeafNodeIndex,
     ) -> bool {
-        self.past_epoch_trees.iter().any(|t| {
-            t.epoch == group_epoch.0
-                && t.leaves
-                    .iter()
-                    .any(|Member { index, .. }| *index == leaf_index)
-        })
+        self.leaf_for_epoch(group_epoch, leaf_index).is_some()
     }
 
     /// Get a mutable reference to the message secrets of the current epoch.
@@ -0,0 +1,256 @@
+//! # Group state snapshots
+//!
+//! The state of an [`MlsGroup`] is persisted through the
+//! [`StorageProvider`](crate::storage::StorageProvider) after every operation.
+//! This module allows exporting the complete state of a group from the
+//! storage into a single snapshot with [`MlsGroup::save_state()`], e.g. to
+//! move it to another storage, and importing it again with
+//! [`MlsGroup::load_state()`].
+//!
+//! A snapshot starts with a header of the magic bytes `OMGS` and the format
+//! version as big-endian `u16`, followed by the JSON serialization of the
+//! state. Snapshots of older format versions can be migrated while loading
+//! them with [`MlsGroup::load_state_with_migration()`].
+//!
+//! **The snapshot contains all secrets of the group, including the private
+//! keys of the current epoch, and has to be protected accordingly.**
+
+use std::io::{Read, Write};
+
+use openmls_traits::storage::CURRENT_VERSION;
+use serde::{Deserialize, Serialize};
+
+use super::{errors::GroupStateError, *};
+use crate::{
+    group::{public_group::InterimTranscriptHash, GroupContext},
+    messages::ConfirmationTag,
+    treesync::TreeSync,
+};
+
+/// The magic bytes at the start of a group state snapshot.
+const MAGIC: &[u8; 4] = b"OMGS";
+
+/// The format version of the group state snapshots written by
+/// [`MlsGroup::save_state()`].
+pub const GROUP_STATE_FORMAT_VERSION: u16 = 1;
+
+/// The state of the group as written by [`MlsGroup::save_state()`].
+#[derive(Serialize)]
+struct GroupStateSnapshotRef<'a> {
+    group_id: &'a GroupId,
+    mls_group_config: &'a MlsGroupJoinConfig,
+    tree: &'a TreeSync,
+    group_context: &'a GroupContext,
+    interim_transcript_hash: &'a InterimTranscriptHash,
+    confirmation_tag: &'a ConfirmationTag,
+    queued_proposals: Vec<&'a QueuedProposal>,
+    group_epoch_secrets: &'a GroupEpochSecrets,
+    own_leaf_index: &'a LeafNodeIndex,
+    message_secrets_store: &'a MessageSecretsStore,
+    resumption_psk_store: &'a ResumptionPskStore,
+    own_leaf_nodes: &'a [LeafNode],
+    group_state: &'a MlsGroupState,
+    epoch_key_pairs: &'a [EncryptionKeyPair],
+    leaf_node_key_pairs: &'a [EncryptionKeyPair],
+}
+
+/// The state of the group as read by [`MlsGroup::load_state()`].
+#[derive(Deserialize)]
+struct GroupStateSnapshot {
+    group_id: GroupId,
+    mls_group_config: MlsGroupJoinConfig,
+    tree: TreeSync,
+    group_context: GroupContext,
+    interim_transcript_hash: InterimTranscriptHash,
+    confirmation_tag: ConfirmationTag,
+    queued_proposals: Vec<QueuedProposal>,
+    group_epoch_secrets: GroupEpochSecrets,
+    own_leaf_index: LeafNodeIndex,
+    message_secrets_store: MessageSecretsStore,
+    resumption_psk_store: ResumptionPskStore,
+    own_leaf_nodes: Vec<LeafNode>,
+    group_state: MlsGroupState,
+    epoch_key_pairs: Vec<EncryptionKeyPair>,
+    leaf_node_key_pairs: Vec<EncryptionKeyPair>,
+}
+
+impl MlsGroup {
+    /// Writes a snapshot of the complete state of this group in `storage` to
+    /// the `writer`, e.g. to move the group to another storage.
+    ///
+    /// The snapshot starts with the magic bytes `OMGS` and the format version
+    /// [`GROUP_STATE_FORMAT_VERSION`] as big-endian `u16`, followed by the JSON
+    /// serialization of the state.
+    ///
+    /// **The snapshot contains all secrets of the group, including the private
+    /// keys of the current epoch, and has to be protected accordingly.**
+    ///
+    /// Returns an error if the state of the group is not in the storage.
+    pub fn save_state<Storage: StorageProvider>(
+        &self,
+        storage: &Storage,
+        writer: &mut impl Write,
+    ) -> Result<(), GroupStateError<Storage::Error>> {
+        let group_id = self.group_id();
+        let tree: TreeSync = storage
+            .tree(group_id)
+            .map_err(GroupStateError::StorageError)?
+            .ok_or(GroupStateError::MissingState)?;
+        let interim_transcript_hash: InterimTranscriptHash = storage
+            .interim_transcript_hash(group_id)
+            .map_err(GroupStateError::StorageError)?
+            .ok_or(GroupStateError::MissingState)?;
+        // A failing read must fail the export instead of writing a snapshot
+        // without the keys of the epoch.
+        let epoch_key_pairs: Vec<EncryptionKeyPair> = storage
+            .encryption_epoch_key_pairs(
+                group_id,
+                &self.context().epoch(),
+                self.own_leaf_index().u32(),
+            )
+            .map_err(GroupStateError::StorageError)?;
+        let leaf_node_key_pairs = self
+            .own_leaf_nodes
+            .iter()
+            .map(|leaf_node| {
+                storage
+                    .encryption_key_pair(leaf_node.encryption_key())
+                    .map_err(GroupStateError::StorageError)?
+                    .ok_or(GroupStateError::MissingState)
+            })
+            .collect::<Result<Vec<EncryptionKeyPair>, _>>()?;
+
+        let snapshot = GroupStateSnapshotRef {
+            group_id,
+            mls_group_config: &self.mls_group_config,
+            tree: &tree,
+            group_context: self.context(),
+            interim_transcript_hash: &interim_transcript_hash,
+            confirmation_tag: self.public_group.confirmation_tag(),
+            queued_proposals: self.proposal_store().proposals().collect(),
+            group_epoch_secrets: &self.group_epoch_secrets,
+            own_leaf_index: &self.own_leaf_index,
+            message_secrets_store: &self.message_secrets_store,
+            resumption_psk_store: &self.resumption_psk_store,
+            own_leaf_nodes: &self.own_leaf_nodes,
+            group_state: &self.group_state,
+            epoch_key_pairs: &epoch_key_pairs,
+            leaf_node_key_pairs: &leaf_node_key_pairs,
+        };
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&GROUP_STATE_FORMAT_VERSION.to_be_bytes())?;
+        serde_json::to_writer(writer, &snapshot).map_err(|e| GroupStateError::Io(e.into()))?;
+
+        Ok(())
+    }
+
+    /// Reads a snapshot written by [`MlsGroup::save_state()`] from the
+    /// `reader`, writes the state to `storage` and returns the group.
+    ///
+    /// Returns [`GroupStateError::UnsupportedVersion`] for snapshots of other
+    /// format versions than [`GROUP_STATE_FORMAT_VERSION`], and
+    /// [`GroupStateError::GroupAlreadyExists`] if a group with the same group
+    /// ID is in the storage already.
+    pub fn load_state<Storage: StorageProvider>(
+        storage: &Storage,
+        reader: &mut impl Read,
+    ) -> Result<MlsGroup, GroupStateError<Storage::Error>> {
+        Self::load_state_with_migration(storage, reader, |version, _| {
+            Err(format!("No migration from format version {version}"))
+        })
+    }
+
+    /// Like [`MlsGroup::load_state()`], but snapshots of older format versions
+    /// are passed to `migrate` together with their format version. The
+    /// migration has to return the JSON value of the snapshot in the format of
+    /// [`GROUP_STATE_FORMAT_VERSION`].
+    pub fn load_state_with_migration<Storage: StorageProvider>(
+        storage: &Storage,
+        reader: &mut impl Read,
+        migrate: impl FnOnce(u16, serde_json::Value) -> Result<serde_json::Value, String>,
+    ) -> Result<MlsGroup, GroupStateError<Storage::Error>> {
+        let mut header = [0u8; 6];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(GroupStateError::InvalidHeader);
+        }
+        let version = u16::from_be_bytes([header[4], header[5]]);
+        if version > GROUP_STATE_FORMAT_VERSION {
+            return Err(GroupStateError::UnsupportedVersion(version));
+        }
+
+        let mut value: serde_json::Value =
+            serde_json::from_reader(reader).map_err(|_| GroupStateError::Malformed)?;
+        if version < GROUP_STATE_FORMAT_VERSION {
+            value = migrate(version, value).map_err(GroupStateError::Migration)?;
+        }
+        let snapshot: GroupStateSnapshot =
+            serde_json::from_value(value).map_err(|_| GroupStateError::Malformed)?;
+
+        let group_id = &snapshot.group_id;
+        if MlsGroup::load(storage, group_id)
+            .map_err(GroupStateError::StorageError)?
+            .is_some()
+        {
+            return Err(GroupStateError::GroupAlreadyExists);
+        }
+
+        // All values of the group are written in one transaction, such that a
+        // failing write doesn't leave a partial group in the storage.
+        storage
+            .begin_transaction()
+            .map_err(GroupStateError::StorageError)?;
+        if let Err(e) = Self::write_snapshot(storage, &snapshot) {
+            // The error of the write is more relevant than a failing rollback.
+            let _ = storage.rollback_transaction();
+            return Err(GroupStateError::StorageError(e));
+        }
+        storage
+            .commit_transaction()
+            .map_err(GroupStateError::StorageError)?;
+
+        MlsGroup::load(storage, group_id)
+            .map_err(GroupStateError::StorageError)?
+            .ok_or(GroupStateError::MissingState)
+    }
+
+    /// Writes all values of the `snapshot` to the `storage`.
+    fn write_snapshot<Storage: StorageProvider>(
+        storage: &Storage,
+        snapshot: &GroupStateSnapshot,
+    ) -> Result<(), <Storage as openmls_traits::storage::StorageProvider<CURRENT_VERSION>>::Error>
+    {
+        let group_id = &snapshot.group_id;
+        storage.write_mls_join_config(group_id, &snapshot.mls_group_config)?;
+        storage.write_tree(group_id, &snapshot.tree)?;
+        storage.write_context(group_id, &snapshot.group_context)?;
+        storage.write_interim_transcript_hash(group_id, &snapshot.interim_transcript_hash)?;
+        storage.write_confirmation_tag(group_id, &snapshot.confirmation_tag)?;
+        for queued_proposal in &snapshot.queued_proposals {
+            storage.queue_proposal(
+                group_id,
+                &queued_proposal.proposal_reference(),
+                queued_proposal,
+            )?;
+        }
+        storage.write_group_epoch_secrets(group_id, &snapshot.group_epoch_secrets)?;
+        storage.write_own_leaf_index(group_id, &snapshot.own_leaf_index)?;
+        storage.write_message_secrets(group_id, &snapshot.message_secrets_store)?;
+        storage.write_resumption_psk_store(group_id, &snapshot.resumption_psk_store)?;
+        for leaf_node in &snapshot.own_leaf_nodes {
+            storage.append_own_leaf_node(group_id, leaf_node)?;
+        }
+        storage.write_group_state(group_id, &snapshot.group_state)?;
+        storage.write_encryption_epoch_key_pairs(
+            group_id,
+            &snapshot.group_context.epoch(),
+            snapshot.own_leaf_index.u32(),
+            &snapshot.epoch_key_pairs,
+        )?;
+        for key_pair in &snapshot.leaf_node_key_pairs {
+            key_pair.write(storage)?;
+        }
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use errors::{CommitToPendingProposalsError, MergePendingCommitError};
 use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer, storage::StorageProvider as _};
 
 use crate::{
+    credentials::CredentialWithKey,
     framing::mls_content::FramedContentBody,
     group::{errors::MergeCommitError, StageCommitError, ValidationError},
     messages::group_info::GroupInfo,
@@ -29,14 +30,29 @@ impl MlsGroup {
         &mut self,
         provider: &Provider,
         message: impl Into<ProtocolMessage>,
-    ) -> Result<ProcessedMessage, ProcessMessageError> {
+    ) -> Result<ProcessedMessage, ProcessMessageError<Provider::StorageError>> {
+        self.process_protocol_message(provider, message.into(), true, None)
+    }
+
+    /// Processes the `message` like [`MlsGroup::process_message()`]. The
+    /// message secrets that decrypting a private message updates are only
+    /// written to the storage if `write_message_secrets` is set. If
+    /// `validate_identity` is given, it is called for the credentials of new
+    /// and updated members, see
+    /// [`MlsGroup::process_message_with_identity_validator()`].
+    pub(super) fn process_protocol_message<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        message: ProtocolMessage,
+        write_message_secrets: bool,
+        validate_identity: Option<&dyn Fn(&CredentialWithKey) -> bool>,
+    ) -> Result<ProcessedMessage, ProcessMessageError<Provider::StorageError>> {
         // Make sure we are still a member of the group
         if !self.is_active() {
             return Err(ProcessMessageError::GroupStateError(
                 MlsGroupStateError::UseAfterEviction,
             ));
         }
-        let message = message.into();
 
         // Check that handshake messages are compatible with the incoming wire format policy
         if !message.is_external()
@@ -50,6 +66,20 @@ impl MlsGroup {
             return Err(ProcessMessageError::IncompatibleWireFormat);
         }
 
+        // Check that application messages carry AAD if the group requires it.
+        // The AAD is not encrypted, such that the message can be rejected
+        // before it is decrypted.
+        if let ProtocolMessage::PrivateMessage(private_message) = &message {
+            if self.configuration().require_aad()
+                && private_message.content_type() == ContentType::Application
+                && private_message.authenticated_data().is_empty()
+            {
+                return Err(ProcessMessageError::MissingAad);
+            }
+        }
+
+        let is_private_message = matches!(message, ProtocolMessage::PrivateMessage(_));
+
         // Parse the message
         let sender_ratchet_configuration =
             self.configuration().sender_ratchet_configuration().clone();
@@ -62,6 +92,16 @@ impl MlsGroup {
         let decrypted_message =
             self.decrypt_message(provider.crypto(), message, &sender_ratchet_configuration)?;
 
+        // Decrypting a private message ratchets the secret tree forward and
+        // deletes the used secrets. Persist this right away, such that the
+        // stored state never lags behind the state in memory.
+        if is_private_message && write_message_secrets {
+            provider
+                .storage()
+                .write_message_secrets(self.group_id(), &self.message_secrets_store)
+                .map_err(ProcessMessageError::StorageError)?;
+        }
+
         let unverified_message = self
             .public_group
             .parse_message(decrypted_message, &self.message_secrets_store)
@@ -75,12 +115,140 @@ impl MlsGroup {
                 (vec![], vec![])
             };
 
-        self.process_unverified_message(
+        let mut processed_message = self.process_unverified_message(
             provider,
             unverified_message,
             old_epoch_keypairs,
             leaf_node_keypairs,
-        )
+        )?;
+
+        // The policy of the group can only be checked once the message is
+        // decrypted. A rejected message has consumed its secrets all the same,
+        // such that it can't be decrypted again.
+        self.check_message_policy(&mut processed_message, validate_identity)?;
+
+        Ok(processed_message)
+    }
+
+    /// Checks the processed message against the policy of the group and sets
+    /// the identity changes of the message.
+    fn check_message_policy<StorageError>(
+        &self,
+        processed_message: &mut ProcessedMessage,
+        validate_identity: Option<&dyn Fn(&CredentialWithKey) -> bool>,
+    ) -> Result<(), ProcessMessageError<StorageError>> {
+        // Check the credentials of new and updated members
+        if let Some(validate_identity) = validate_identity {
+            if !new_leaf_nodes(processed_message)
+                .into_iter()
+                .all(|leaf_node| validate_identity(&CredentialWithKey::from(leaf_node)))
+            {
+                return Err(ProcessMessageError::InvalidIdentity);
+            }
+        }
+
+        // Check whether the message changes the credential of a member
+        let identity_changes = self.identity_changes(processed_message);
+        if !identity_changes.is_empty() && self.configuration().reject_identity_changes() {
+            return Err(ProcessMessageError::IdentityChanged);
+        }
+        processed_message.set_identity_changes(identity_changes);
+
+        // Check that ReInit proposals only move to approved ciphersuites
+        if let Some(ciphersuite) = self.unapproved_reinit_ciphersuite(processed_message) {
+            return Err(ProcessMessageError::UnapprovedReInitCiphersuite(
+                ciphersuite,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ciphersuite of a ReInit proposal in the processed message
+    /// that is not in the `reinit_ciphersuites` of the group configuration.
+    fn unapproved_reinit_ciphersuite(
+        &self,
+        processed_message: &ProcessedMessage,
+    ) -> Option<Ciphersuite> {
+        let approved = self.configuration().reinit_ciphersuites()?;
+        let proposals: Vec<&Proposal> = match processed_message.content() {
+            ProcessedMessageContent::ProposalMessage(queued_proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                vec![queued_proposal.proposal()]
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => staged_commit
+                .queued_proposals()
+                .map(|queued_proposal| queued_proposal.proposal())
+                .collect(),
+            ProcessedMessageContent::ApplicationMessage(_) => vec![],
+        };
+
+        proposals.into_iter().find_map(|proposal| match proposal {
+            Proposal::ReInit(reinit) if !approved.contains(&reinit.ciphersuite()) => {
+                Some(reinit.ciphersuite())
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the credential changes of the members that issued the Update
+    /// proposals or the update path in the processed message.
+    fn identity_changes(&self, processed_message: &ProcessedMessage) -> Vec<IdentityChanged> {
+        let updated_leaf_nodes: Vec<(LeafNodeIndex, &LeafNode)> = match processed_message.content()
+        {
+            ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+                update_leaf_node(queued_proposal).into_iter().collect()
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                let committer = match processed_message.sender() {
+                    Sender::Member(leaf_index) => Some(*leaf_index),
+                    _ => None,
+                };
+                staged_commit
+                    .queued_proposals()
+                    .filter_map(update_leaf_node)
+                    .chain(committer.zip(staged_commit.update_path_leaf_node()))
+                    .collect()
+            }
+            ProcessedMessageContent::ApplicationMessage(_)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(_) => vec![],
+        };
+
+        updated_leaf_nodes
+            .into_iter()
+            .filter_map(|(leaf_index, leaf_node)| {
+                let old_credential = self.public_group().leaf(leaf_index)?.credential();
+                (old_credential != leaf_node.credential()).then(|| {
+                    IdentityChanged::new(
+                        leaf_index,
+                        old_credential.clone(),
+                        leaf_node.credential().clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Parses incoming messages like [`MlsGroup::process_message()`] and
+    /// additionally calls `validate_identity` for the credential of every new
+    /// or updated member, e.g. to check it against an authentication service.
+    ///
+    /// The validator is called for the leaf nodes of the key packages in Add
+    /// proposals, the leaf nodes in Update proposals and the leaf node in the
+    /// update path of a commit. It returns `true` if the credential is valid.
+    ///
+    /// # Errors:
+    /// Returns a [`ProcessMessageError::InvalidIdentity`] if the validator
+    /// rejects a credential, or any other [`ProcessMessageError`] returned by
+    /// [`MlsGroup::process_message()`]. The secrets to decrypt a rejected
+    /// private message are consumed, i.e. it can't be processed again.
+    pub fn process_message_with_identity_validator<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        message: impl Into<ProtocolMessage>,
+        validate_identity: impl Fn(&CredentialWithKey) -> bool,
+    ) -> Result<ProcessedMessage, ProcessMessageError<Provider::StorageError>> {
+        self.process_protocol_message(provider, message.into(), true, Some(&validate_identity))
     }
 
     /// Stores a standalone proposal in the internal [ProposalStore]
@@ -138,10 +306,36 @@ impl MlsGroup {
 
     /// Merge a [StagedCommit] into the group after inspection. As this advances
     /// the epoch of the group, it also clears any pending commits.
+    ///
+    /// The new state of the group is written in one storage transaction. If
+    /// merging fails, the transaction is rolled back and the group should be
+    /// loaded from the storage again.
     pub fn merge_staged_commit<Provider: OpenMlsProvider>(
         &mut self,
         provider: &Provider,
         staged_commit: StagedCommit,
+    ) -> Result<(), MergeCommitError<Provider::StorageError>> {
+        let storage = provider.storage();
+        storage
+            .begin_transaction()
+            .map_err(MergeCommitError::StorageError)?;
+        match self.merge_staged_commit_in_transaction(provider, staged_commit) {
+            Ok(()) => storage
+                .commit_transaction()
+                .map_err(MergeCommitError::StorageError),
+            Err(e) => {
+                // The error of the merge is more relevant than a failing
+                // rollback.
+                let _ = storage.rollback_transaction();
+                Err(e)
+            }
+        }
+    }
+
+    fn merge_staged_commit_in_transaction<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        staged_commit: StagedCommit,
     ) -> Result<(), MergeCommitError<Provider::StorageError>> {
         // Check if we were removed from the group
         if staged_commit.self_removed() {
@@ -180,6 +374,18 @@ impl MlsGroup {
 
     /// Merges the pending [`StagedCommit`] if there is one, and
     /// clears the field by setting it to `None`.
+    ///
+    /// The secrets of the next epoch and the tree of the next epoch, including
+    /// its hashes, are computed when the commit is created. Merging the pending
+    /// commit therefore doesn't derive any secrets, it mostly writes the new
+    /// state of the group to the storage.
+    ///
+    /// If merging fails and the storage supports transactions, the group is
+    /// restored from the storage, such that the commit is still pending and
+    /// the own leaf node, including its signature key, is the one of the
+    /// current epoch. A signature key rotated by the pending commit, see
+    /// [`MlsGroup::pending_signature_key()`], therefore only takes effect once
+    /// this function returns successfully.
     pub fn merge_pending_commit<Provider: OpenMlsProvider>(
         &mut self,
         provider: &Provider,
@@ -188,12 +394,38 @@ impl MlsGroup {
             MlsGroupState::PendingCommit(_) => {
                 let old_state = mem::replace(&mut self.group_state, MlsGroupState::Operational);
                 if let MlsGroupState::PendingCommit(pending_commit_state) = old_state {
-                    self.merge_staged_commit(provider, (*pending_commit_state).into())?;
+                    if let Err(e) =
+                        self.merge_staged_commit(provider, (*pending_commit_state).into())
+                    {
+                        self.restore_after_failed_merge(provider.storage());
+                        return Err(e.into());
+                    }
                 }
                 Ok(())
             }
             MlsGroupState::Inactive => Err(MlsGroupStateError::UseAfterEviction)?,
-            MlsGroupState::Operational => Ok(()),
+            MlsGroupState::Operational | MlsGroupState::UpdateRequired => Ok(()),
+        }
+    }
+
+    /// Replaces the in-memory state of the group, which a failed merge may
+    /// have changed partially, with the state in the storage. The storage was
+    /// rolled back to the state before the merge, so the pending commit is
+    /// restored with it.
+    ///
+    /// Without transactions, the storage may contain a partially merged
+    /// state as well, and the group is left as it is.
+    fn restore_after_failed_merge<Storage: StorageProvider>(&mut self, storage: &Storage) {
+        if !storage.supports_transactions() {
+            return;
+        }
+        if let Ok(Some(mut group)) = MlsGroup::load(storage, self.group_id()) {
+            // The extension registry and the AAD are not persisted.
+            group
+                .public_group
+                .set_extension_registry(self.public_group.extension_registry().clone());
+            group.aad = mem::take(&mut self.aad);
+            *self = group;
         }
     }
 
@@ -254,17 +486,18 @@ impl MlsGroup {
         unverified_message: UnverifiedMessage,
         old_epoch_keypairs: Vec<EncryptionKeyPair>,
         leaf_node_keypairs: Vec<EncryptionKeyPair>,
-    ) -> Result<ProcessedMessage, ProcessMessageError> {
+    ) -> Result<ProcessedMessage, ProcessMessageError<Provider::StorageError>> {
         // Checks the following semantic validation:
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
         //  - https://validation.openmls.tech/#valn1203
-        let (content, credential) =
+        let (content, credential_with_key) =
             unverified_message.verify(self.ciphersuite(), provider.crypto(), self.version())?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
                 let sender = content.sender().clone();
+                let epoch = content.epoch();
                 let authenticated_data = content.authenticated_data().to_owned();
 
                 let content = match content.content() {
@@ -299,15 +532,16 @@ impl MlsGroup {
 
                 Ok(ProcessedMessage::new(
                     self.group_id().clone(),
-                    self.context().epoch(),
+                    epoch,
                     sender,
                     authenticated_data,
                     content,
-                    credential,
+                    credential_with_key,
                 ))
             }
             Sender::External(_) => {
                 let sender = content.sender().clone();
+                let epoch = content.epoch();
                 let data = content.authenticated_data().to_owned();
                 match content.content() {
                     FramedContentBody::Application(_) => {
@@ -323,11 +557,11 @@ impl MlsGroup {
                         ));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
-                            self.context().epoch(),
+                            epoch,
                             sender,
                             data,
                             content,
-                            credential,
+                            credential_with_key,
                         ))
                     }
                     // TODO #151/#106
@@ -381,7 +615,7 @@ impl MlsGroup {
                 DecryptedMessage::from_inbound_public_message(
                     *public_message,
                     message_secrets,
-                    message_secrets.serialized_context().to_vec(),
+                    message_secrets.shared_serialized_context(),
                     crypto,
                     self.ciphersuite(),
                 )
@@ -398,3 +632,41 @@ impl MlsGroup {
         }
     }
 }
+
+/// Returns the sender and the new leaf node of an Update proposal.
+fn update_leaf_node(queued_proposal: &QueuedProposal) -> Option<(LeafNodeIndex, &LeafNode)> {
+    match (queued_proposal.proposal(), queued_proposal.sender()) {
+        (Proposal::Update(update_proposal), Sender::Member(leaf_index)) => {
+            Some((*leaf_index, update_proposal.leaf_node()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the leaf nodes of the new and updated members in the processed
+/// message.
+fn new_leaf_nodes(processed_message: &ProcessedMessage) -> Vec<&LeafNode> {
+    match processed_message.content() {
+        ProcessedMessageContent::ProposalMessage(queued_proposal)
+        | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+            proposal_leaf_node(queued_proposal.proposal())
+                .into_iter()
+                .collect()
+        }
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => staged_commit
+            .queued_proposals()
+            .filter_map(|queued_proposal| proposal_leaf_node(queued_proposal.proposal()))
+            .chain(staged_commit.update_path_leaf_node())
+            .collect(),
+        ProcessedMessageContent::ApplicationMessage(_) => vec![],
+    }
+}
+
+/// Returns the new leaf node of Add and Update proposals.
+fn proposal_leaf_node(proposal: &Proposal) -> Option<&LeafNode> {
+    match proposal {
+        Proposal::Add(add_proposal) => Some(add_proposal.key_package().leaf_node()),
+        Proposal::Update(update_proposal) => Some(update_proposal.leaf_node()),
+        _ => None,
+    }
+}
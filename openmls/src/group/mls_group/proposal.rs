@@ -225,7 +225,8 @@ impl MlsGroup {
 
     /// Creates proposals to add members to the group.
     ///
-    /// Returns an error if there is a pending commit.
+    /// Returns an error if there is a pending commit or if the key package
+    /// doesn't support all extensions in the group context.
     pub fn propose_add_member<Provider: OpenMlsProvider>(
         &mut self,
         provider: &Provider,
@@ -450,6 +451,12 @@ impl MlsGroup {
                 .capabilities()
                 .supports_required_capabilities(required_capabilities)?;
         }
+        // Every member has to support the group context extensions, so check
+        // this here instead of failing when the commit is validated.
+        joiner_key_package
+            .leaf_node()
+            .capabilities()
+            .supports_group_context_extensions(self.context().extensions())?;
         let add_proposal = AddProposal {
             key_package: joiner_key_package,
         };
@@ -269,6 +269,16 @@ impl ProposalQueue {
                     }
                 }
             };
+            // A proposal that is committed more than once would apply to the
+            // same leaf or key package more than once, which RFC 9420,
+            // Section 12.2 forbids. The queue would silently drop the duplicate
+            // and hide it from the validation.
+            if proposal_queue
+                .queued_proposals
+                .contains_key(&queued_proposal.proposal_reference())
+            {
+                return Err(FromCommittedProposalsError::DuplicateProposal);
+            }
             proposal_queue.add(queued_proposal);
         }
 
@@ -0,0 +1,154 @@
+//! # Group snapshots
+//!
+//! To process a batch of messages optimistically, e.g. before the application
+//! has decided whether it accepts the batch, a [`GroupSnapshot`] of the group
+//! can be taken with [`MlsGroup::snapshot()`]. Afterwards the batch is
+//! processed as usual and either kept with [`MlsGroup::release_snapshot()`]
+//! or discarded with [`MlsGroup::rollback_to_snapshot()`].
+//!
+//! A snapshot is a copy of the stored state of the group, including the
+//! encryption keys of its epoch. The rollback replaces the stored state of the
+//! group with the copy and leaves the rest of the storage, e.g. the state of
+//! other groups, untouched.
+//!
+//! The stored state of the group is replaced in one storage transaction, such
+//! that a crash can't leave a partially restored group behind. Snapshots
+//! therefore require a [`StorageProvider`] that implements transactions, like
+//! the memory and SQLite storages, see
+//! [`StorageProvider::supports_transactions()`](openmls_traits::storage::StorageProvider::supports_transactions()).
+
+use openmls_traits::storage::StorageProvider as _;
+
+use super::{errors::SnapshotRollbackError, *};
+
+/// A snapshot of the state of a group, see [`MlsGroup::snapshot()`].
+#[derive(Debug)]
+pub struct GroupSnapshot {
+    group: Box<MlsGroup>,
+    epoch_keypairs: Vec<EncryptionKeyPair>,
+}
+
+impl GroupSnapshot {
+    /// Returns the group ID of the group the snapshot was taken of.
+    pub fn group_id(&self) -> &GroupId {
+        self.group.group_id()
+    }
+
+    /// Returns the epoch of the group when the snapshot was taken.
+    pub fn epoch(&self) -> GroupEpoch {
+        self.group.epoch()
+    }
+}
+
+impl MlsGroup {
+    /// Takes a snapshot of the state of the group before processing messages
+    /// optimistically.
+    ///
+    /// The snapshot can be released with
+    /// [`release_snapshot()`](Self::release_snapshot) or rolled back with
+    /// [`rollback_to_snapshot()`](Self::rollback_to_snapshot).
+    ///
+    /// # Errors:
+    /// Returns a [`SnapshotRollbackError::TransactionsUnsupported`] if the
+    /// storage doesn't implement transactions.
+    pub fn snapshot<Provider: OpenMlsProvider>(
+        &self,
+        provider: &Provider,
+    ) -> Result<GroupSnapshot, SnapshotRollbackError<Provider::StorageError>> {
+        let storage = provider.storage();
+        if !storage.supports_transactions() {
+            return Err(SnapshotRollbackError::TransactionsUnsupported);
+        }
+
+        let group = MlsGroup::load(storage, self.group_id())
+            .map_err(SnapshotRollbackError::StorageError)?
+            .ok_or(SnapshotRollbackError::MissingState)?;
+        let epoch_keypairs = self.read_epoch_keypairs(storage);
+
+        Ok(GroupSnapshot {
+            group: Box::new(group),
+            epoch_keypairs,
+        })
+    }
+
+    /// Keeps the changes to the group since the `snapshot` was taken.
+    pub fn release_snapshot<Provider: OpenMlsProvider>(
+        &self,
+        _provider: &Provider,
+        snapshot: GroupSnapshot,
+    ) -> Result<(), SnapshotRollbackError<Provider::StorageError>> {
+        if snapshot.group_id() != self.group_id() {
+            return Err(SnapshotRollbackError::WrongGroup(snapshot));
+        }
+
+        Ok(())
+    }
+
+    /// Discards the changes to the group since the `snapshot` was taken and
+    /// restores the group to its state at that time. The stored state of
+    /// other groups is not changed.
+    ///
+    /// The additional authenticated data set with
+    /// [`set_aad()`](Self::set_aad) is cleared.
+    ///
+    /// # Errors:
+    /// Returns a [`SnapshotRollbackError::TransactionsUnsupported`] if the
+    /// storage doesn't implement transactions. The group is not changed in
+    /// this case.
+    pub fn rollback_to_snapshot<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        snapshot: GroupSnapshot,
+    ) -> Result<(), SnapshotRollbackError<Provider::StorageError>> {
+        if snapshot.group_id() != self.group_id() {
+            return Err(SnapshotRollbackError::WrongGroup(snapshot));
+        }
+        let storage = provider.storage();
+        if !storage.supports_transactions() {
+            return Err(SnapshotRollbackError::TransactionsUnsupported);
+        }
+
+        storage
+            .begin_transaction()
+            .map_err(SnapshotRollbackError::StorageError)?;
+        match self.restore_snapshot(storage, &snapshot) {
+            Ok(()) => storage
+                .commit_transaction()
+                .map_err(SnapshotRollbackError::StorageError)?,
+            Err(e) => {
+                // The error of the restore is more relevant than a failing
+                // rollback.
+                let _ = storage.rollback_transaction();
+                return Err(SnapshotRollbackError::StorageError(e));
+            }
+        }
+
+        *self = *snapshot.group;
+
+        Ok(())
+    }
+
+    /// Replaces the stored state of the group with the state in the
+    /// `snapshot`.
+    fn restore_snapshot<Storage: StorageProvider>(
+        &mut self,
+        storage: &Storage,
+        snapshot: &GroupSnapshot,
+    ) -> Result<(), Storage::Error> {
+        self.delete(storage)?;
+
+        let group = &snapshot.group;
+        group.store(storage)?;
+        for leaf_node in &group.own_leaf_nodes {
+            storage.append_own_leaf_node(group.group_id(), leaf_node)?;
+        }
+        for queued_proposal in group.proposal_store().proposals() {
+            storage.queue_proposal(
+                group.group_id(),
+                &queued_proposal.proposal_reference(),
+                queued_proposal,
+            )?;
+        }
+        group.store_epoch_keypairs(storage, &snapshot.epoch_keypairs)
+    }
+}
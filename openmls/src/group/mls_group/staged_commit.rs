@@ -159,7 +159,7 @@ impl MlsGroup {
         let mut diff = self.public_group.empty_diff();
 
         let apply_proposals_values =
-            diff.apply_proposals(&proposal_queue, self.own_leaf_index())?;
+            diff.apply_proposals(&proposal_queue, self.own_leaf_index(), provider.crypto())?;
 
         // Determine if Commit has a path
         let (commit_secret, new_keypairs, new_leaf_keypair_option, update_path_leaf_node) =
@@ -46,6 +46,13 @@ const NUM_TESTS: usize = 25;
 ///         /* serialized MLSMessage (PublicMessage or PrivateMessage) */,
 ///       ],
 ///       "commit": /* serialized MLSMessage (PublicMessage or PrivateMessage) */,
+///       /* optional, not part of the vectors of the MLS working group */
+///       "application_messages": [
+///         {
+///           "message": /* serialized MLSMessage (PrivateMessage) */,
+///           "plaintext": /* hex-encoded binary data */,
+///         },
+///       ],
 ///       "epoch_authenticator": /* hex-encoded binary data */,
 ///     },
 ///     // ...
@@ -90,6 +97,9 @@ pub struct TestEpoch {
     proposals: Vec<TestProposal>,
     #[serde(with = "hex::serde")]
     commit: Vec<u8>,
+    /// Application messages sent in the epoch after the commit.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    application_messages: Vec<TestApplicationMessage>,
     #[serde(with = "hex::serde")]
     epoch_authenticator: Vec<u8>,
 }
@@ -97,6 +107,14 @@ pub struct TestEpoch {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct TestProposal(#[serde(with = "hex::serde")] Vec<u8>);
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TestApplicationMessage {
+    #[serde(with = "hex::serde")]
+    message: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    plaintext: Vec<u8>,
+}
+
 #[test]
 fn test_read_vectors() {
     crate::skip_validation::checks::leaf_node_lifetime::handle().with_disabled(|| {
@@ -113,6 +131,22 @@ fn test_read_vectors() {
     })
 }
 
+#[test]
+fn test_generated_vectors() {
+    for &ciphersuite in OpenMlsRustCrypto::default()
+        .crypto()
+        .supported_ciphersuites()
+        .iter()
+    {
+        let test_vector = generate_test_vector(ciphersuite);
+        assert!(test_vector
+            .epochs
+            .iter()
+            .any(|epoch| !epoch.application_messages.is_empty()));
+        run_test_vector(test_vector);
+    }
+}
+
 pub fn run_test_vector(test_vector: PassiveClientWelcomeTestVector) {
     let _ = pretty_env_logger::try_init();
 
@@ -174,6 +208,16 @@ pub fn run_test_vector(test_vector: PassiveClientWelcomeTestVector) {
         debug!("Commit: {message:#?}");
         passive_client.process_message(message);
 
+        for application_message in epoch.application_messages {
+            let message =
+                MlsMessageIn::tls_deserialize_exact(&application_message.message).unwrap();
+            debug!("Application message: {message:?}");
+            assert_eq!(
+                passive_client.process_message(message),
+                Some(application_message.plaintext)
+            );
+        }
+
         assert_eq!(
             epoch.epoch_authenticator,
             passive_client.epoch_authenticator()
@@ -290,7 +334,9 @@ impl PassiveClient {
         self.group = Some(group);
     }
 
-    fn process_message(&mut self, message: MlsMessageIn) {
+    /// Processes the message and returns the plaintext of application
+    /// messages.
+    fn process_message(&mut self, message: MlsMessageIn) -> Option<Vec<u8>> {
         println!("{:#?}", message);
         let processed_message = self
             .group
@@ -314,8 +360,13 @@ impl PassiveClient {
                     .merge_staged_commit(&self.provider, *staged_commit)
                     .unwrap();
             }
+            ProcessedMessageContent::ApplicationMessage(application_message) => {
+                return Some(application_message.into_bytes());
+            }
             _ => unimplemented!(),
         }
+
+        None
     }
 
     fn epoch_authenticator(&self) -> Vec<u8> {
@@ -371,7 +422,11 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
 
     let initial_epoch_authenticator = creator_group.epoch_authenticator().as_slice().to_vec();
 
-    let epoch1 = update_inline(&creator_provider, &creator, &mut creator_group);
+    let mut epoch1 = update_inline(&creator_provider, &creator, &mut creator_group);
+    epoch1.application_messages = vec![
+        application_message(&creator_provider, &creator, &mut creator_group, b"Hello"),
+        application_message(&creator_provider, &creator, &mut creator_group, b"Bob"),
+    ];
 
     let epoch2 = {
         let proposals = vec![propose_add(
@@ -389,6 +444,7 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
         TestEpoch {
             proposals,
             commit,
+            application_messages: vec![],
             epoch_authenticator,
         }
     };
@@ -408,6 +464,7 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
         TestEpoch {
             proposals,
             commit,
+            application_messages: vec![],
             epoch_authenticator,
         }
     };
@@ -437,6 +494,7 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
         TestEpoch {
             proposals,
             commit,
+            application_messages: vec![],
             epoch_authenticator,
         }
     };
@@ -460,6 +518,12 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
         TestEpoch {
             proposals,
             commit,
+            application_messages: vec![application_message(
+                &creator_provider,
+                &creator,
+                &mut creator_group,
+                b"Welcome Fardi",
+            )],
             epoch_authenticator,
         }
     };
@@ -477,6 +541,7 @@ pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PassiveClientWelcomeTes
         TestEpoch {
             proposals,
             commit,
+            application_messages: vec![],
             epoch_authenticator,
         }
     };
@@ -521,7 +586,7 @@ fn propose_add(
         false,
     );
 
-    let mls_message_out_proposal = group
+    let (mls_message_out_proposal, _proposal_ref) = group
         .propose_add_member(
             provider,
             &candidate.signature_keypair,
@@ -545,7 +610,7 @@ fn propose_remove(
         .unwrap()
         .index;
 
-    let mls_message_out_proposal = group
+    let (mls_message_out_proposal, _proposal_ref) = group
         .propose_remove_member(provider, &candidate.signature_keypair, remove)
         .unwrap();
 
@@ -585,6 +650,23 @@ fn update_inline(
     TestEpoch {
         proposals,
         commit,
+        application_messages: vec![],
         epoch_authenticator,
     }
 }
+
+fn application_message(
+    provider: &OpenMlsRustCrypto,
+    candidate: &GroupCandidate,
+    group: &mut MlsGroup,
+    plaintext: &[u8],
+) -> TestApplicationMessage {
+    let mls_message_out = group
+        .create_message(provider, &candidate.signature_keypair, plaintext)
+        .unwrap();
+
+    TestApplicationMessage {
+        message: mls_message_out.tls_serialize_detached().unwrap(),
+        plaintext: plaintext.to_vec(),
+    }
+}
@@ -27,8 +27,11 @@ use tls_codec::{Deserialize as TlsDeserialize, Serialize as TlsSerialize};
 use crate::{
     binary_tree::{array_representation::TreeSize, LeafNodeIndex},
     ciphersuite::signable::Verifiable,
-    framing::{MlsMessageBodyIn, MlsMessageIn},
-    group::{HpkePrivateKey, OpenMlsSignaturePublicKey, SignaturePublicKey},
+    framing::{MlsMessageBodyIn, MlsMessageIn, MlsMessageOut},
+    group::{
+        HpkePrivateKey, MlsGroup, MlsGroupCreateConfig, OpenMlsSignaturePublicKey,
+        SignaturePublicKey,
+    },
     key_packages::*,
     messages::*,
     prelude::group_info::{GroupInfo, VerifiableGroupInfo},
@@ -40,9 +43,8 @@ use crate::{
 };
 
 const TEST_VECTOR_PATH_READ: &str = "test_vectors/welcome.json";
-// TODO(#1279)
-// const TEST_VECTOR_PATH_WRITE: &str = "test_vectors/welcome-new.json";
-// const NUM_TESTS: usize = 100;
+const TEST_VECTOR_PATH_WRITE: &str = "test_vectors/welcome-new.json";
+const NUM_TESTS: usize = 100;
 
 /// ```json
 /// {
@@ -81,28 +83,74 @@ fn test_read_vectors() {
     }
 }
 
-// # TODO(#1279)
-// #[test]
-// fn test_write_vectors() {
-//     let mut tests = Vec::new();
-//
-//     for &ciphersuite in OpenMlsRustCrypto::default()
-//         .crypto()
-//         .supported_ciphersuites()
-//         .iter()
-//     {
-//         for _ in 0..NUM_TESTS {
-//             let test = generate_test_vector(ciphersuite);
-//             tests.push(test);
-//         }
-//     }
-//
-//     write(TEST_VECTOR_PATH_WRITE, &tests);
-// }
-//
-// pub fn generate_test_vector(_ciphersuite: Ciphersuite) -> WelcomeTestVector {
-//     unimplemented!()
-// }
+#[test]
+fn test_write_vectors() {
+    let mut tests = Vec::new();
+
+    for &ciphersuite in OpenMlsRustCrypto::default()
+        .crypto()
+        .supported_ciphersuites()
+        .iter()
+    {
+        for _ in 0..NUM_TESTS {
+            let test = generate_test_vector(ciphersuite);
+            // Make sure the generated test vector can be verified.
+            run_test_vector(test.clone()).expect("Error while checking generated test vector.");
+            tests.push(test);
+        }
+    }
+
+    write(TEST_VECTOR_PATH_WRITE, &tests);
+}
+
+pub fn generate_test_vector(ciphersuite: Ciphersuite) -> WelcomeTestVector {
+    let group_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let creator_provider = OpenMlsRustCrypto::default();
+
+    let creator =
+        generate_group_candidate(b"Alice (Creator)", ciphersuite, &creator_provider, true);
+
+    let mut creator_group = MlsGroup::new(
+        &creator_provider,
+        &creator.signature_keypair,
+        &group_config,
+        creator
+            .credential_with_key_and_signer
+            .credential_with_key
+            .clone(),
+    )
+    .unwrap();
+
+    let joiner = generate_group_candidate(
+        b"Bob (Joiner)",
+        ciphersuite,
+        &OpenMlsRustCrypto::default(),
+        false,
+    );
+
+    let (_, mls_message_welcome, _) = creator_group
+        .add_members(
+            &creator_provider,
+            &creator.signature_keypair,
+            &[joiner.key_package.key_package().clone()],
+        )
+        .unwrap();
+
+    let init_priv = joiner.key_package.init_private_key().to_vec();
+
+    WelcomeTestVector {
+        cipher_suite: ciphersuite.into(),
+        init_priv,
+        signer_pub: creator.signature_keypair.to_public_vec(),
+        key_package: MlsMessageOut::from(joiner.key_package)
+            .tls_serialize_detached()
+            .unwrap(),
+        welcome: mls_message_welcome.tls_serialize_detached().unwrap(),
+    }
+}
 
 pub fn run_test_vector(test_vector: WelcomeTestVector) -> Result<(), &'static str> {
     let _ = pretty_env_logger::formatted_builder()
@@ -0,0 +1,229 @@
+//! This module contains tests for the batch processing of messages.
+
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test::openmls_test;
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider as _};
+
+use crate::{
+    credentials::test_utils::new_credential,
+    framing::*,
+    group::{errors::*, *},
+    key_packages::*,
+    treesync::LeafNodeParameters,
+};
+
+fn application_message(
+    result: Result<ProcessedBatchMessage, ProcessBatchError<impl std::fmt::Debug>>,
+) -> Vec<u8> {
+    let processed_message = match result {
+        Ok(ProcessedBatchMessage::Processed(processed_message)) => processed_message,
+        result => panic!("expected a processed message, got {result:?}"),
+    };
+    let ProcessedMessageContent::ApplicationMessage(message) = processed_message.into_content()
+    else {
+        panic!("expected an application message");
+    };
+    message.into_bytes()
+}
+
+/// Creates a group of Alice and Bob with the given wire format policy.
+fn alice_and_bob_group<Provider: crate::storage::OpenMlsProvider>(
+    ciphersuite: Ciphersuite,
+    alice_provider: &Provider,
+    bob_provider: &Provider,
+    wire_format_policy: WireFormatPolicy,
+) -> (MlsGroup, SignatureKeyPair, MlsGroup) {
+    let (alice_credential, alice_signer) =
+        new_credential(alice_provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential, bob_signer) =
+        new_credential(bob_provider, b"Bob", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle =
+        KeyPackageBundle::generate(bob_provider, &bob_signer, ciphersuite, bob_credential);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_wire_format_policy(wire_format_policy)
+        .build(alice_provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::builder()
+            .wire_format_policy(wire_format_policy)
+            .build(),
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_join| staged_join.into_group(bob_provider))
+    .expect("error joining the group");
+
+    (alice_group, alice_signer, bob_group)
+}
+
+// A batch of the messages of two epochs is processed in the order of the
+// epochs, independent of the order of the batch.
+#[openmls_test]
+fn process_batch_out_of_order() {
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+
+    let (mut alice_group, alice_signer, mut bob_group) = alice_and_bob_group(
+        ciphersuite,
+        alice_provider,
+        bob_provider,
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+    );
+    let (charlie_credential, charlie_signer) = new_credential(
+        alice_provider,
+        b"Charlie",
+        ciphersuite.signature_algorithm(),
+    );
+    let charlie_key_package_bundle = KeyPackageBundle::generate(
+        alice_provider,
+        &charlie_signer,
+        ciphersuite,
+        charlie_credential,
+    );
+
+    // Alice sends a message, proposes to add Charlie and commits the proposal,
+    // and sends another message in the new epoch.
+    let first_message = alice_group
+        .create_message(alice_provider, &alice_signer, b"first")
+        .expect("error creating application message");
+    let (proposal, _proposal_ref) = alice_group
+        .propose_add_member(
+            alice_provider,
+            &alice_signer,
+            charlie_key_package_bundle.key_package(),
+        )
+        .expect("error proposing to add Charlie");
+    let (commit, welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(alice_provider, &alice_signer)
+        .expect("error committing to pending proposals");
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let second_message = alice_group
+        .create_message(alice_provider, &alice_signer, b"second")
+        .expect("error creating application message");
+
+    let batch: [MlsMessageIn; 5] = [
+        second_message.into(),
+        commit.into(),
+        welcome.expect("expected a welcome").into(),
+        first_message.into(),
+        proposal.into(),
+    ];
+    let mut results = bob_group
+        .process_messages(bob_provider, batch, |_| true)
+        .expect("error processing the batch")
+        .into_iter();
+
+    assert_eq!(application_message(results.next().unwrap()), b"second");
+    assert!(matches!(
+        results.next().unwrap(),
+        Ok(ProcessedBatchMessage::MergedCommit { epoch, .. }) if epoch == alice_group.epoch()
+    ));
+    assert!(matches!(
+        results.next().unwrap(),
+        Err(ProcessBatchError::NotAProtocolMessage)
+    ));
+    assert_eq!(application_message(results.next().unwrap()), b"first");
+    let Some(Ok(ProcessedBatchMessage::Processed(processed_message))) = results.next() else {
+        panic!("expected a processed proposal");
+    };
+    assert!(matches!(
+        processed_message.content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+    assert!(results.next().is_none());
+
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+    assert_eq!(bob_group.members().count(), 3);
+    assert_eq!(
+        bob_group.epoch_authenticator().as_slice(),
+        alice_group.epoch_authenticator().as_slice()
+    );
+}
+
+// The message secrets that decrypting a batch of private messages updates are
+// written to the storage, also if a commit is not approved and merged.
+#[openmls_test]
+fn process_batch_private_messages() {
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+
+    let (mut alice_group, alice_signer, mut bob_group) = alice_and_bob_group(
+        ciphersuite,
+        alice_provider,
+        bob_provider,
+        PURE_CIPHERTEXT_WIRE_FORMAT_POLICY,
+    );
+
+    // Alice sends a message and a commit, and another message in the new
+    // epoch.
+    let first_message = alice_group
+        .create_message(alice_provider, &alice_signer, b"first")
+        .expect("error creating application message");
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(alice_provider, &alice_signer, LeafNodeParameters::default())
+        .expect("error creating self-update commit")
+        .into_messages();
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let second_message = alice_group
+        .create_message(alice_provider, &alice_signer, b"second")
+        .expect("error creating application message");
+
+    // Bob doesn't approve the commit, such that the second message can't be
+    // processed.
+    let batch: [MlsMessageIn; 3] = [
+        first_message.clone().into(),
+        commit.into(),
+        second_message.into(),
+    ];
+    let mut results = bob_group
+        .process_messages(bob_provider, batch, |_| false)
+        .expect("error processing the batch")
+        .into_iter();
+
+    assert_eq!(application_message(results.next().unwrap()), b"first");
+    let Some(Ok(ProcessedBatchMessage::Processed(processed_message))) = results.next() else {
+        panic!("expected a processed commit");
+    };
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a staged commit");
+    };
+    assert!(results.next().unwrap().is_err());
+    assert!(results.next().is_none());
+    assert_eq!(bob_group.epoch().as_u64() + 1, alice_group.epoch().as_u64());
+
+    // The stored group has used the key of the first message.
+    let mut loaded_bob_group = MlsGroup::load(bob_provider.storage(), bob_group.group_id())
+        .expect("error loading group")
+        .expect("no group in the storage");
+    loaded_bob_group
+        .process_message(bob_provider, first_message.into_protocol_message().unwrap())
+        .expect_err("processed the first message twice");
+
+    // The commit can still be merged.
+    bob_group
+        .merge_staged_commit(bob_provider, *staged_commit)
+        .expect("error merging staged commit");
+    assert_eq!(
+        bob_group.epoch_authenticator().as_slice(),
+        alice_group.epoch_authenticator().as_slice()
+    );
+}
@@ -0,0 +1,154 @@
+//! This module contains tests for the client state export and import.
+
+use mls_group::tests_and_kats::utils::setup_alice_bob_group;
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test::openmls_test;
+use openmls_traits::OpenMlsProvider as _;
+
+use crate::{
+    framing::*,
+    group::{errors::*, *},
+    key_packages::*,
+    treesync::LeafNodeParameters,
+};
+
+#[openmls_test]
+fn client_state_export_import() {
+    let (mut alice_group, alice_signer, mut bob_group, bob_signer, bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+    let key_package_bundle = KeyPackage::builder()
+        .build(ciphersuite, provider, &bob_signer, bob_credential)
+        .expect("error building key package");
+    let key_package_ref = key_package_bundle
+        .key_package()
+        .hash_ref(provider.crypto())
+        .unwrap();
+
+    // Keys of the wrong length are rejected, and the group stays active.
+    let key = [0x42; 32];
+    let err = ClientStateExport::new()
+        .group(&mut bob_group)
+        .export(provider, &key[..16])
+        .expect_err("exported with a short key");
+    assert!(matches!(err, ClientStateError::InvalidKeyLength));
+    assert!(bob_group.is_active());
+
+    let bundle = ClientStateExport::new()
+        .group(&mut bob_group)
+        .signature_key_pair(&bob_signer.id())
+        .key_package(key_package_ref.clone())
+        .export(provider, &key)
+        .expect("error exporting the client state");
+    assert_eq!(&bundle[..4], b"OMCS");
+    assert_eq!(bundle[4..6], CLIENT_STATE_FORMAT_VERSION.to_be_bytes());
+
+    // The old device can't send messages to the exported group anymore, also
+    // after loading it from the storage again.
+    assert!(!bob_group.is_active());
+    let err = bob_group
+        .create_message(provider, &bob_signer, b"Hello Alice")
+        .expect_err("created a message in an exported group");
+    assert_eq!(
+        err,
+        CreateMessageError::GroupStateError(MlsGroupStateError::UseAfterEviction)
+    );
+    let stored_bob_group = MlsGroup::load(provider.storage(), bob_group.group_id())
+        .unwrap()
+        .unwrap();
+    assert!(!stored_bob_group.is_active());
+
+    // The bundle can only be imported with the same key.
+    let err = ClientStateImport::import(&Provider::default(), &[0x23; 32], &bundle)
+        .expect_err("imported with a wrong key");
+    assert!(matches!(err, ClientStateError::Crypto(_)));
+
+    // Modified bundles are rejected.
+    let mut modified = bundle.clone();
+    *modified.last_mut().unwrap() ^= 0xff;
+    let err = ClientStateImport::import(&Provider::default(), &key, &modified)
+        .expect_err("imported a modified bundle");
+    assert!(matches!(err, ClientStateError::Crypto(_)));
+
+    // Import the state on a new device.
+    let bob_provider = Provider::default();
+    let mut import = ClientStateImport::import(&bob_provider, &key, &bundle)
+        .expect("error importing the client state");
+    assert!(import.forward_secrecy_invalidated());
+    assert_eq!(import.signature_key_pairs(), 1);
+    assert_eq!(
+        import.key_package_refs(),
+        std::slice::from_ref(&key_package_ref)
+    );
+    assert!(SignatureKeyPair::read(
+        bob_provider.storage(),
+        bob_signer.public(),
+        bob_signer.signature_scheme(),
+    )
+    .is_some());
+    let imported_bundle: Option<KeyPackageBundle> = bob_provider
+        .storage()
+        .key_package(&key_package_ref)
+        .unwrap();
+    assert!(imported_bundle.is_some());
+
+    assert_eq!(import.groups().len(), 1);
+    let restored_bob_group = &mut import.groups_mut()[0];
+    assert_eq!(restored_bob_group.group_id(), bob_group.group_id());
+    assert_eq!(restored_bob_group.epoch(), bob_group.epoch());
+
+    // The new device can't send application messages before it updated its
+    // leaf.
+    let err = restored_bob_group
+        .create_message(&bob_provider, &bob_signer, b"Hello Alice")
+        .expect_err("created a message before updating the own leaf");
+    assert_eq!(
+        err,
+        CreateMessageError::GroupStateError(MlsGroupStateError::UpdateRequired)
+    );
+
+    // The new device restores forward secrecy by committing an update, and
+    // Alice processes it.
+    let (commit, _welcome, _group_info) = restored_bob_group
+        .self_update(&bob_provider, &bob_signer, LeafNodeParameters::default())
+        .expect("error creating self update")
+        .into_contents();
+    restored_bob_group
+        .merge_pending_commit(&bob_provider)
+        .expect("error merging pending commit");
+    assert!(!import.forward_secrecy_invalidated());
+    let mut restored_bob_group = import.into_groups().pop().unwrap();
+    let processed_message = alice_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a staged commit");
+    };
+    alice_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging staged commit");
+    assert_eq!(alice_group.epoch(), restored_bob_group.epoch());
+
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message");
+    let processed_message = restored_bob_group
+        .process_message(&bob_provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+
+    let message = restored_bob_group
+        .create_message(&bob_provider, &bob_signer, b"Hello Alice")
+        .expect("error creating application message");
+    let processed_message = alice_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+}
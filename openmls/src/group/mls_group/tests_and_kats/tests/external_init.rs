@@ -1,3 +1,5 @@
+use openmls_traits::{types::VerifiableCiphersuite, OpenMlsProvider as _};
+
 use crate::{
     group::{
         errors::ExternalCommitError,
@@ -6,6 +8,7 @@ use crate::{
         MlsGroup, MlsGroupJoinConfig,
     },
     storage::OpenMlsProvider,
+    treesync::node::leaf_node::Capabilities,
 };
 
 #[openmls_test::openmls_test]
@@ -46,3 +49,56 @@ fn test_external_init_broken_signature() {
         )
     ));
 }
+
+// Test that an external commit advertises the ciphersuites of the provider
+// without explicit capabilities, and the explicit ones otherwise.
+#[openmls_test::openmls_test]
+fn test_external_init_default_capabilities() {
+    let (group_alice, alice_signer, _group_bob, _bob_signer, _bob_credential_with_key) =
+        setup_alice_bob_group(ciphersuite, provider);
+    let (charlie_credential, _charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    let join = |capabilities: Option<Capabilities>| {
+        let verifiable_group_info = group_alice
+            .export_group_info(provider, &alice_signer, true)
+            .unwrap()
+            .into_verifiable_group_info()
+            .unwrap();
+        let (mut group_charlie, _commit, _group_info) = MlsGroup::join_by_external_commit(
+            provider,
+            &charlie_signer,
+            None,
+            verifiable_group_info,
+            &MlsGroupJoinConfig::default(),
+            capabilities,
+            None,
+            &[],
+            charlie_credential.clone(),
+        )
+        .expect("error joining by external commit");
+        group_charlie
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+        group_charlie
+            .own_leaf_node()
+            .unwrap()
+            .capabilities()
+            .ciphersuites()
+            .to_vec()
+    };
+
+    let supported_ciphersuites: Vec<VerifiableCiphersuite> = provider
+        .crypto()
+        .supported_algorithms()
+        .ciphersuites()
+        .iter()
+        .map(|&ciphersuite| ciphersuite.into())
+        .collect();
+    assert_eq!(join(None), supported_ciphersuites);
+
+    let capabilities = Capabilities::builder()
+        .ciphersuites(vec![ciphersuite])
+        .build();
+    assert_eq!(join(Some(capabilities)), vec![ciphersuite.into()]);
+}
@@ -0,0 +1,57 @@
+//! This module contains tests for the memory usage report of a group.
+
+use openmls_test::openmls_test;
+
+use crate::{
+    credentials::test_utils::new_credential, group::*, key_packages::*, prelude::LeafNodeParameters,
+};
+
+// The memory usage of every part of the group state grows with what the
+// group holds.
+#[openmls_test]
+fn memory_stats() {
+    let (alice_credential, alice_signer) =
+        new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential, bob_signer) =
+        new_credential(provider, b"Bob", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle =
+        KeyPackageBundle::generate(provider, &bob_signer, ciphersuite, bob_credential);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .max_past_epochs(2)
+        .build(provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let initial_stats = alice_group.memory_stats();
+    assert!(initial_stats.treesync_bytes > 0);
+    assert!(initial_stats.secret_tree_bytes > 0);
+    assert_eq!(initial_stats.past_epochs_bytes, 0);
+    assert_eq!(initial_stats.pending_proposals_bytes, 0);
+
+    alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let stats = alice_group.memory_stats();
+    assert!(stats.treesync_bytes > initial_stats.treesync_bytes);
+    assert!(stats.past_epochs_bytes > 0);
+
+    alice_group
+        .propose_self_update(provider, &alice_signer, LeafNodeParameters::default())
+        .expect("error proposing self update");
+    let stats_with_proposal = alice_group.memory_stats();
+    assert!(stats_with_proposal.pending_proposals_bytes > 0);
+    assert_eq!(
+        stats_with_proposal.total_bytes(),
+        stats_with_proposal.treesync_bytes
+            + stats_with_proposal.secret_tree_bytes
+            + stats_with_proposal.past_epochs_bytes
+            + stats_with_proposal.pending_proposals_bytes
+    );
+}
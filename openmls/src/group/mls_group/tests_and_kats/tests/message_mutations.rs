@@ -0,0 +1,145 @@
+//! This module tests that corrupted messages of a malicious peer are rejected
+//! with the expected error.
+
+use openmls_test::openmls_test;
+use openmls_traits::OpenMlsProvider as _;
+use tls_codec::{Deserialize as _, Serialize as _};
+
+use crate::{
+    credentials::test_utils::new_credential,
+    framing::*,
+    group::{errors::*, *},
+    key_packages::*,
+    test_utils::message_mutator::{
+        assert_rejected, assert_replay_rejected, Mutation, Reauthenticator,
+    },
+};
+
+#[openmls_test]
+fn message_mutations() {
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+    let charlie_provider = &Provider::default();
+
+    let (alice_credential, alice_signer) =
+        new_credential(alice_provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential, bob_signer) =
+        new_credential(bob_provider, b"Bob", ciphersuite.signature_algorithm());
+    let (charlie_credential, charlie_signer) = new_credential(
+        charlie_provider,
+        b"Charlie",
+        ciphersuite.signature_algorithm(),
+    );
+    let (dave_credential, dave_signer) =
+        new_credential(alice_provider, b"Dave", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle =
+        KeyPackageBundle::generate(bob_provider, &bob_signer, ciphersuite, bob_credential);
+    let charlie_key_package_bundle = KeyPackageBundle::generate(
+        charlie_provider,
+        &charlie_signer,
+        ciphersuite,
+        charlie_credential,
+    );
+    let dave_key_package_bundle =
+        KeyPackageBundle::generate(alice_provider, &dave_signer, ciphersuite, dave_credential);
+
+    // Alice creates a group with Bob and Charlie.
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .build(alice_provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[
+                bob_key_package_bundle.key_package().clone(),
+                charlie_key_package_bundle.key_package().clone(),
+            ],
+        )
+        .expect("error adding Bob and Charlie");
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::builder()
+            .wire_format_policy(MIXED_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .build(),
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_join| staged_join.into_group(bob_provider))
+    .expect("error joining the group");
+
+    // Application messages can't be replayed.
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message")
+        .tls_serialize_detached()
+        .unwrap();
+    assert_replay_rejected(&mut bob_group, bob_provider, &message);
+
+    // Alice proposes to add Dave.
+    let (proposal, _proposal_ref) = alice_group
+        .propose_add_member(
+            alice_provider,
+            &alice_signer,
+            dave_key_package_bundle.key_package(),
+        )
+        .expect("error proposing to add Dave");
+    let proposal = proposal.tls_serialize_detached().unwrap();
+    let reauthenticator = Reauthenticator::new(alice_provider, &alice_signer, &bob_group);
+
+    let mutations = [
+        (
+            Mutation::FlipSignatureBit,
+            ValidationError::InvalidSignature,
+        ),
+        (
+            Mutation::FlipMembershipTagBit,
+            ValidationError::InvalidMembershipTag,
+        ),
+        (Mutation::SwapSender(2), ValidationError::InvalidSignature),
+    ];
+    for (mutation, expected) in mutations {
+        assert_rejected(
+            &mut bob_group,
+            bob_provider,
+            &mutation.apply(&proposal, &reauthenticator),
+            ProcessMessageError::ValidationError(expected),
+        );
+    }
+
+    // The unmodified proposal is accepted.
+    let processed_message = bob_group
+        .process_message(
+            bob_provider,
+            MlsMessageIn::tls_deserialize_exact(&proposal)
+                .unwrap()
+                .try_into_protocol_message()
+                .unwrap(),
+        )
+        .expect("error processing the proposal");
+    let ProcessedMessageContent::ProposalMessage(queued_proposal) =
+        processed_message.into_content()
+    else {
+        panic!("expected a proposal");
+    };
+    bob_group
+        .store_pending_proposal(bob_provider.storage(), *queued_proposal)
+        .unwrap();
+
+    // Alice commits to the proposal, which can't be committed twice.
+    let (commit, _welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(alice_provider, &alice_signer)
+        .expect("error committing to the proposal");
+    let commit = commit.tls_serialize_detached().unwrap();
+    assert_rejected(
+        &mut bob_group,
+        bob_provider,
+        &Mutation::DuplicateProposal.apply(&commit, &reauthenticator),
+        ProcessMessageError::InvalidCommit(StageCommitError::DuplicateProposal),
+    );
+}
@@ -10,7 +10,7 @@ use tls_codec::{Deserialize, Serialize};
 
 use crate::{
     binary_tree::LeafNodeIndex,
-    credentials::test_utils::new_credential,
+    credentials::{test_utils::new_credential, CredentialWithKey},
     framing::*,
     group::{errors::*, *},
     key_packages::*,
@@ -28,7 +28,7 @@ use crate::{
     },
     tree::sender_ratchet::SenderRatchetConfiguration,
     treesync::{
-        errors::{ApplyUpdatePathError, LeafNodeValidationError},
+        errors::{ApplyUpdatePathError, LeafNodeValidationError, LifetimeError},
         node::leaf_node::Capabilities,
         LeafNodeParameters,
     },
@@ -1295,9 +1295,7 @@ fn builder_pattern() {
 
     // Make sure that building with an invalid leaf node extension fails
     let invalid_leaf_extensions =
-        Extensions::single(Extension::ApplicationId(ApplicationIdExtension::new(&[
-            0x00, 0x01, 0x02,
-        ])));
+        Extensions::single(Extension::LastResort(LastResortExtension::default()));
 
     let builder_err = MlsGroup::builder()
         .with_leaf_node_extensions(invalid_leaf_extensions)
@@ -1305,6 +1303,47 @@ fn builder_pattern() {
     assert_eq!(builder_err, LeafNodeValidationError::UnsupportedExtensions);
 }
 
+// Test that explicitly set capabilities are advertised unchanged, even if they
+// are the default ones, and that the provider's default capabilities are
+// advertised otherwise.
+#[openmls_test]
+fn builder_default_capabilities() {
+    let (alice_credential, alice_signer) =
+        new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+
+    let explicit_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_capabilities(Capabilities::default())
+        .build(provider, &alice_signer, alice_credential.clone())
+        .expect("error creating group");
+    let own_leaf = explicit_group.own_leaf_node().unwrap();
+    assert_eq!(own_leaf.capabilities(), &Capabilities::default());
+
+    let default_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .build(provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let own_leaf = default_group.own_leaf_node().unwrap();
+    assert_eq!(
+        own_leaf.capabilities(),
+        &crate::ciphersuite::CiphersuiteRegistry::new(provider.crypto()).default_capabilities()
+    );
+
+    // Set capabilities are serialized as they are, and unset ones are read
+    // back as unset.
+    let config = MlsGroupCreateConfig::builder()
+        .capabilities(Capabilities::default())
+        .build();
+    let mut serialized = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        serialized["capabilities"],
+        serde_json::to_value(Capabilities::default()).unwrap()
+    );
+    serialized.as_object_mut().unwrap().remove("capabilities");
+    let deserialized: MlsGroupCreateConfig = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized, MlsGroupCreateConfig::default());
+}
+
 // Test the successful update of Group Context Extension with type Extension::Unknown(0xff11)
 #[openmls_test]
 fn update_group_context_with_unknown_extension<Provider: OpenMlsProvider + Default>() {
@@ -1951,6 +1990,73 @@ fn unknown_extensions() {
     .expect("Error creating group from staged join");
 }
 
+#[openmls_test]
+fn add_member_without_group_context_extension_support() {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // The group uses an unknown group context extension, which is not required
+    // through the required capabilities extension.
+    let unknown_extension_type = ExtensionType::Unknown(0xff00);
+    let capabilities = Capabilities::new(None, None, Some(&[unknown_extension_type]), None, None);
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_capabilities(capabilities.clone())
+        .with_group_context_extensions(Extensions::single(Extension::Unknown(
+            0xff00,
+            UnknownExtension(vec![0, 1, 2, 3]),
+        )))
+        .expect("error adding unknown extension to builder")
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group using builder");
+
+    // Bob's key package doesn't support the extension.
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            ciphersuite,
+            provider,
+            &bob_signer,
+            bob_credential_with_key.clone(),
+        )
+        .expect("error building key package");
+    let expected_error =
+        LeafNodeValidationError::UnsupportedGroupContextExtension(unknown_extension_type);
+
+    let err = alice_group
+        .propose_add_member(provider, &alice_signer, bob_key_package.key_package())
+        .expect_err("proposed to add a member that lacks an extension");
+    assert_eq!(
+        err,
+        ProposeAddMemberError::LeafNodeValidation(expected_error.clone())
+    );
+
+    let err = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .expect_err("added a member that lacks an extension");
+    assert_eq!(err, AddMembersError::LeafNodeValidation(expected_error));
+    assert_eq!(alice_group.pending_proposals().count(), 0);
+    assert!(alice_group.pending_commit().is_none());
+
+    // A key package that supports the extension can be added.
+    let bob_key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities)
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .expect("error building key package");
+    alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .expect("error adding member");
+}
+
 #[openmls_test]
 fn join_multiple_groups_last_resort_extension(
     ciphersuite: Ciphersuite,
@@ -2007,15 +2113,26 @@ fn join_multiple_groups_last_resort_extension(
         .into_welcome()
         .expect("expected message to be a welcome");
 
-    StagedWelcome::new_from_welcome(
+    let staged_welcome = StagedWelcome::new_from_welcome(
         provider,
         &MlsGroupJoinConfig::default(),
         alice_welcome,
         None,
     )
-    .expect("error creating staged join from welcome")
-    .into_group(provider)
-    .expect("error creating group from staged join");
+    .expect("error creating staged join from welcome");
+    // charlie is told that the last resort key package should be replaced
+    assert!(staged_welcome.used_last_resort_key_package());
+    staged_welcome
+        .into_group(provider)
+        .expect("error creating group from staged join");
+
+    // the last resort key package is still in the key store
+    let hash_ref = charlie_keypkg
+        .key_package()
+        .hash_ref(provider.crypto())
+        .unwrap();
+    let stored: Option<KeyPackageBundle> = provider.storage().key_package(&hash_ref).unwrap();
+    assert!(stored.is_some());
 
     // bob calls add_members(...) with charlie's KeyPackage; produces Commit and Welcome messages
     let (_, bob_welcome, _) = bob_group
@@ -2334,6 +2451,78 @@ fn psks() {
         .into_contents();
 }
 
+// Test that joining a group fails with a dedicated error if a PSK is missing
+// and succeeds once the PSK is available.
+#[openmls_test::openmls_test]
+fn psks_missing_on_join() {
+    let (alice_credential_with_key, alice_signature_keys, bob_key_package_bundle, _) =
+        setup_alice_bob(ciphersuite, provider);
+
+    let secret = Secret::random(ciphersuite, provider.rand()).expect("Not enough randomness.");
+    let preshared_key_id = PreSharedKeyId::new(
+        ciphersuite,
+        provider.rand(),
+        Psk::External(ExternalPsk::new(vec![1u8, 2, 3])),
+    )
+    .expect("An unexpected error occured.");
+    preshared_key_id.store(provider, secret.as_slice()).unwrap();
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .build(provider, &alice_signature_keys, alice_credential_with_key)
+        .expect("Error creating group.");
+
+    // === Alice adds Bob and commits to a PSK proposal ===
+    alice_group
+        .propose_external_psk(provider, &alice_signature_keys, preshared_key_id.clone())
+        .expect("Could not create PSK proposal");
+    let (_commit, welcome, _group_info_option) = alice_group
+        .add_members(
+            provider,
+            &alice_signature_keys,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("Could not create commit");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("Could not merge commit");
+    let welcome = welcome.into_welcome().unwrap();
+
+    // Bob doesn't have the PSK.
+    provider
+        .storage()
+        .delete_psk(preshared_key_id.psk())
+        .unwrap();
+    let err = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.clone(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect_err("Joined the group without the PSK");
+    assert_eq!(
+        err,
+        WelcomeError::MissingPsks(vec![preshared_key_id.clone()])
+    );
+
+    // Once Bob stored the PSK, he can join with the same key package.
+    preshared_key_id.store(provider, secret.as_slice()).unwrap();
+    let bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("Could not stage welcome")
+    .into_group(provider)
+    .expect("Could not create group from welcome");
+    assert_eq!(
+        alice_group.epoch_authenticator(),
+        bob_group.epoch_authenticator()
+    );
+}
+
 // Test several scenarios when PSKs are used in a group
 #[openmls_test::openmls_test]
 fn staged_commit_creation(
@@ -2786,3 +2975,597 @@ fn proposal_application_after_self_was_removed_ref(
     let bob_next_id = member.credential.serialized_content();
     assert_eq!(bob_next_id, b"Charlie");
 }
+
+// Test that application ids survive group creation, joining and updates.
+#[openmls_test]
+fn application_id_round_trip() {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let alice_application_id = b"alice's device".to_vec();
+    let bob_application_id = b"bob's device".to_vec();
+
+    // === Alice creates a group with an application id ===
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .use_ratchet_tree_extension(true)
+        .with_leaf_node_extensions(Extensions::single(Extension::ApplicationId(
+            ApplicationIdExtension::new(&alice_application_id),
+        )))
+        .expect("error adding application id to builder")
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group using builder");
+
+    // === Bob generates a key package with an application id ===
+    let bob_key_package = KeyPackage::builder()
+        .leaf_node_extensions(Extensions::single(Extension::ApplicationId(
+            ApplicationIdExtension::new(&bob_application_id),
+        )))
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .expect("error creating key package")
+        .key_package()
+        .clone();
+    assert_eq!(
+        bob_key_package.application_id(),
+        Some(bob_application_id.as_slice())
+    );
+
+    // === Alice adds Bob ===
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome: MlsMessageIn = welcome.into();
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.into_welcome().expect("expected a welcome"),
+        None,
+    )
+    .expect("error processing welcome")
+    .into_group(provider)
+    .expect("error creating group from welcome");
+
+    // === Bob updates and the application ids are preserved ===
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(provider, &bob_signer, LeafNodeParameters::default())
+        .expect("error creating self-update commit")
+        .into_messages();
+    bob_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let processed_message = alice_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a staged commit");
+    };
+    alice_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging staged commit");
+
+    for group in [&alice_group, &bob_group] {
+        let application_ids: Vec<_> = group
+            .members()
+            .map(|member| member.application_id().map(<[u8]>::to_vec))
+            .collect();
+        assert_eq!(
+            application_ids,
+            vec![
+                Some(alice_application_id.clone()),
+                Some(bob_application_id.clone())
+            ]
+        );
+        assert_eq!(
+            group
+                .member_at(LeafNodeIndex::new(1))
+                .and_then(|member| member.application_id().map(<[u8]>::to_vec)),
+            Some(bob_application_id.clone())
+        );
+    }
+}
+
+// Test that the identity validator is called for new members and can reject
+// commits.
+#[openmls_test]
+fn identity_validator() {
+    let (mut alice_group, alice_signer, mut bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+    let (_charlie_credential_with_key, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    // Encrypt the commit, such that processing it uses the secret tree.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY)
+        .build();
+    for group in [&mut alice_group, &mut bob_group] {
+        group
+            .set_configuration(provider.storage(), &join_config)
+            .expect("error setting configuration");
+    }
+
+    let (commit, _welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect("error adding Charlie");
+    let commit = commit.into_protocol_message().unwrap();
+
+    let is_not_charlie = |credential_with_key: &CredentialWithKey| {
+        credential_with_key.credential.serialized_content() != b"Charlie"
+    };
+    let err = bob_group
+        .process_message_with_identity_validator(provider, commit.clone(), is_not_charlie)
+        .expect_err("the commit adding Charlie should be rejected");
+    assert_eq!(err, ProcessMessageError::InvalidIdentity);
+
+    // The rejected commit consumed the secrets to decrypt it, so it can't be
+    // processed again.
+    let err = bob_group
+        .process_message_with_identity_validator(provider, commit, |_| true)
+        .expect_err("the rejected commit was decrypted again");
+    assert_eq!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+            MessageDecryptionError::ReplayedMessage
+        ))
+    );
+}
+
+// Test that a member can rotate its signature key in a self update.
+#[openmls_test]
+fn self_update_with_new_signer() {
+    let (mut alice_group, alice_signer, mut bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+    let (new_credential_with_key, new_signer) =
+        new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let self_update_with_new_signer = |alice_group: &mut MlsGroup| {
+        alice_group
+            .self_update_with_new_signer(
+                provider,
+                &alice_signer,
+                NewSignerBundle {
+                    signer: &new_signer,
+                    credential_with_key: new_credential_with_key.clone(),
+                },
+                LeafNodeParameters::default(),
+            )
+            .expect("error creating self update with new signer")
+            .into_messages()
+    };
+
+    // The new signature key is staged with the pending commit. When the
+    // pending commit is cleared, the old signer is still valid.
+    self_update_with_new_signer(&mut alice_group);
+    assert_eq!(
+        alice_group.pending_signature_key(),
+        Some(&new_credential_with_key.signature_key)
+    );
+    assert_eq!(
+        alice_group
+            .own_leaf_node()
+            .unwrap()
+            .signature_key()
+            .as_slice(),
+        alice_signer.public()
+    );
+    alice_group
+        .clear_pending_commit(provider.storage())
+        .expect("error clearing pending commit");
+    assert!(alice_group.pending_signature_key().is_none());
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message");
+    bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+
+    // Merging the pending commit swaps in the new signature key.
+    let (commit, _welcome, _group_info) = self_update_with_new_signer(&mut alice_group);
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    assert!(alice_group.pending_signature_key().is_none());
+    assert_eq!(
+        alice_group.own_leaf_node().unwrap().signature_key(),
+        &new_credential_with_key.signature_key
+    );
+
+    let processed_message = bob_group
+        .process_message(provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a staged commit");
+    };
+    bob_group
+        .merge_staged_commit(provider, *staged_commit)
+        .expect("error merging staged commit");
+
+    let alice_member = bob_group
+        .member_at(alice_group.own_leaf_index())
+        .expect("Alice should still be a member");
+    assert_eq!(
+        alice_member.signature_key,
+        new_credential_with_key.signature_key.as_slice()
+    );
+
+    // Alice is now using the new signer.
+    let message = alice_group
+        .create_message(provider, &new_signer, b"Hello Bob")
+        .expect("error creating application message");
+    let processed_message = bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+}
+
+// Test that credential changes in Update proposals are reported or rejected.
+#[openmls_test]
+fn identity_change_detection() {
+    let (mut alice_group, alice_signer, mut bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+    let (mut new_credential_with_key, _mallory_signer) =
+        new_credential(provider, b"Mallory", ciphersuite.signature_algorithm());
+    new_credential_with_key.signature_key = alice_signer.public().into();
+
+    let (proposal, _proposal_ref) = alice_group
+        .propose_self_update(
+            provider,
+            &alice_signer,
+            LeafNodeParameters::builder()
+                .with_credential_with_key(new_credential_with_key.clone())
+                .build(),
+        )
+        .expect("error creating update proposal");
+    let proposal = proposal.into_protocol_message().unwrap();
+
+    // By default, the change is reported.
+    let processed_message = bob_group
+        .process_message(provider, proposal.clone())
+        .expect("error processing proposal");
+    let identity_changes = processed_message.identity_changes();
+    assert_eq!(identity_changes.len(), 1);
+    assert_eq!(
+        identity_changes[0].leaf_index(),
+        alice_group.own_leaf_index()
+    );
+    assert_eq!(
+        identity_changes[0].old_credential().serialized_content(),
+        b"Alice"
+    );
+    assert_eq!(
+        identity_changes[0].new_credential(),
+        &new_credential_with_key.credential
+    );
+
+    // If configured, the change is rejected.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .reject_identity_changes(true)
+        .build();
+    bob_group
+        .set_configuration(provider.storage(), &join_config)
+        .expect("error setting configuration");
+    let err = bob_group
+        .process_message(provider, proposal)
+        .expect_err("the identity change should be rejected");
+    assert_eq!(err, ProcessMessageError::IdentityChanged);
+}
+
+// Test that processed messages expose the verified sender.
+#[openmls_test]
+fn processed_message_sender() {
+    let (mut alice_group, alice_signer, mut bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message");
+    let processed_message = bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+
+    assert_eq!(
+        processed_message.sender_leaf_index(),
+        Some(alice_group.own_leaf_index())
+    );
+    assert_eq!(processed_message.epoch(), alice_group.epoch());
+    assert_eq!(
+        processed_message.credential().serialized_content(),
+        b"Alice"
+    );
+    assert_eq!(
+        processed_message.signature_key().as_slice(),
+        alice_signer.public()
+    );
+}
+
+#[openmls_test]
+fn ratchet_tree_extension_size_limit() {
+    let (alice_credential_with_key, alice_signer, bob_key_package_bundle, _) =
+        setup_alice_bob(ciphersuite, provider);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .use_ratchet_tree_extension(true)
+        .max_ratchet_tree_extension_size(16)
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group");
+
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let welcome = welcome.into_welcome().unwrap();
+
+    // The ratchet tree exceeds the limit and is not in the Welcome. A failed
+    // join consumes the key package, so it is tried with another provider.
+    let other_provider = Provider::default();
+    other_provider
+        .storage()
+        .write_key_package(
+            &bob_key_package_bundle
+                .key_package()
+                .hash_ref(provider.crypto())
+                .unwrap(),
+            &bob_key_package_bundle,
+        )
+        .unwrap();
+    let err = StagedWelcome::new_from_welcome(
+        &other_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.clone(),
+        None,
+    )
+    .expect_err("joined without a ratchet tree");
+    assert_eq!(err, WelcomeError::MissingRatchetTree);
+
+    // Bob can join with the ratchet tree delivered out of band.
+    let bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("error processing welcome")
+    .into_group(provider)
+    .expect("error creating group from welcome");
+    assert_eq!(
+        alice_group.epoch_authenticator(),
+        bob_group.epoch_authenticator()
+    );
+
+    // Group infos can't be exported with the ratchet tree, only without it.
+    let err = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect_err("exported a group info with a too large ratchet tree");
+    assert_eq!(err, ExportGroupInfoError::RatchetTreeTooLarge);
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .unwrap();
+    assert!(group_info.extensions().ratchet_tree().is_none());
+    assert!(group_info.extensions().external_pub().is_some());
+}
+
+#[cfg(feature = "ratchet-tree-compression")]
+#[openmls_test]
+fn compressed_ratchet_tree_extension() {
+    let (alice_credential_with_key, alice_signer, bob_key_package_bundle, _) =
+        setup_alice_bob(ciphersuite, provider);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .use_ratchet_tree_extension(true)
+        .compress_ratchet_tree_extension(true)
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group");
+
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // Bob joins with the compressed ratchet tree from the Welcome.
+    let bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.into_welcome().unwrap(),
+        None,
+    )
+    .expect("error processing welcome")
+    .into_group(provider)
+    .expect("error creating group from welcome");
+    assert_eq!(
+        alice_group.epoch_authenticator(),
+        bob_group.epoch_authenticator()
+    );
+
+    let group_info = alice_group
+        .export_group_info(provider, &alice_signer, true)
+        .expect("error exporting group info")
+        .into_verifiable_group_info()
+        .unwrap();
+    assert!(group_info.extensions().ratchet_tree().is_none());
+    let compressed = group_info
+        .extensions()
+        .application_extension::<crate::extensions::CompressedRatchetTreeExtension>()
+        .unwrap()
+        .expect("missing compressed ratchet tree extension");
+    assert_eq!(
+        compressed.ratchet_tree().unwrap(),
+        crate::treesync::RatchetTreeIn::from(alice_group.export_ratchet_tree())
+    );
+}
+
+#[openmls_test]
+fn lifetime_policy_for_adds() {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // Bob's key package expires in an hour.
+    let bob_key_package_bundle = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new(60 * 60))
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .expect("error building key package");
+    let bob_key_package = bob_key_package_bundle.key_package().clone();
+
+    // Alice only accepts key packages that are valid for another day.
+    let lifetime_policy = LifetimePolicy::new().with_min_remaining(60 * 60 * 24);
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .lifetime_policy(lifetime_policy)
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("error creating group");
+    assert_eq!(
+        alice_group.configuration().lifetime_policy(),
+        &lifetime_policy
+    );
+
+    let err = alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+        .expect_err("added a key package that expires too soon");
+    assert!(matches!(
+        err,
+        AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
+            ProposalValidationError::LeafNodeValidation(LeafNodeValidationError::Lifetime(
+                LifetimeError::ExpiresTooSoon
+            ))
+        ))
+    ));
+
+    // The policy is stored with the group configuration and applies after
+    // loading the group.
+    let mut alice_group = MlsGroup::load(provider.storage(), alice_group.group_id())
+        .expect("error loading group")
+        .expect("group not found");
+    assert_eq!(
+        alice_group.configuration().lifetime_policy(),
+        &lifetime_policy
+    );
+    let err = alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+        .expect_err("added a key package that expires too soon after loading");
+    assert!(matches!(
+        err,
+        AddMembersError::CreateCommitError(CreateCommitError::ProposalValidationError(
+            ProposalValidationError::LeafNodeValidation(LeafNodeValidationError::Lifetime(
+                LifetimeError::ExpiresTooSoon
+            ))
+        ))
+    ));
+
+    // With the default policy, the key package is accepted.
+    alice_group
+        .set_configuration(provider.storage(), &MlsGroupJoinConfig::default())
+        .expect("error setting configuration");
+    alice_group
+        .add_members(provider, &alice_signer, &[bob_key_package])
+        .expect("error adding Bob");
+}
+
+// Test that the ratcheted secret tree is persisted when processing a message,
+// such that a reloaded group can't decrypt the same message again.
+#[openmls_test]
+fn processing_persists_secret_tree() {
+    let (mut alice_group, alice_signer, mut bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message")
+        .into_protocol_message()
+        .unwrap();
+    bob_group
+        .process_message(provider, message.clone())
+        .expect("error processing application message");
+
+    let mut reloaded_bob_group = MlsGroup::load(provider.storage(), bob_group.group_id())
+        .expect("error loading group")
+        .expect("group not found");
+    let err = reloaded_bob_group
+        .process_message(provider, message)
+        .expect_err("processed the same message twice");
+    assert!(matches!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(_))
+    ));
+}
+
+// Test that the key store maintenance deletes secrets of old epochs.
+#[openmls_test]
+fn key_store_maintenance_prunes_past_epochs() {
+    let (alice_credential, alice_signer) =
+        new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .max_past_epochs(5)
+        .number_of_resumption_psks(5)
+        .build(provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+
+    // Advance the group to epoch 4.
+    for _ in 0..4 {
+        alice_group
+            .self_update(provider, &alice_signer, LeafNodeParameters::default())
+            .expect("error creating self update");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+    }
+
+    let report = KeyStoreMaintenance::new(1)
+        .run(provider, None, [&mut alice_group])
+        .expect("error running maintenance");
+    assert!(report.removed_key_packages().is_empty());
+    assert_eq!(
+        report.pruned_epochs(),
+        &[(
+            alice_group.group_id().clone(),
+            vec![
+                GroupEpoch::from(0),
+                GroupEpoch::from(1),
+                GroupEpoch::from(2)
+            ]
+        )]
+    );
+
+    // The stored group doesn't have the secrets either, and a second run
+    // has nothing left to remove.
+    let mut loaded_group = MlsGroup::load(provider.storage(), alice_group.group_id())
+        .expect("error loading group")
+        .expect("group not found");
+    let report = KeyStoreMaintenance::new(1)
+        .run(provider, None, [&mut loaded_group])
+        .expect("error running maintenance");
+    assert!(report.is_empty());
+}
@@ -1,6 +1,17 @@
 //! Test and Known Answer Test (KAT) modules for the MLS group.
 
+mod batch;
+#[cfg(feature = "json")]
+mod client_state;
 mod external_init;
+#[cfg(feature = "memory-stats")]
+mod memory_stats;
+mod message_mutations;
 mod mls_group;
 mod past_secrets;
+#[cfg(feature = "json")]
+mod persistence;
 mod proposals;
+mod snapshot;
+#[cfg(feature = "json")]
+mod trace;
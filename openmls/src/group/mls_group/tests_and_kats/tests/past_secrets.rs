@@ -1,8 +1,11 @@
 //! This module contains tests regarding the use of [`MessageSecretsStore`]
 
 use crate::{
-    binary_tree::LeafNodeIndex, group::past_secrets::MessageSecretsStore,
-    schedule::message_secrets::MessageSecrets, test_utils::*,
+    binary_tree::LeafNodeIndex,
+    credentials::BasicCredential,
+    group::{past_secrets::MessageSecretsStore, Member},
+    schedule::message_secrets::MessageSecrets,
+    test_utils::*,
 };
 
 #[openmls_test::openmls_test]
@@ -62,3 +65,38 @@ fn test_empty_secret_tree_store() {
     // Make sure we cannot access the message secrets we just stored
     assert!(message_secrets_store.secrets_for_epoch_mut(0).is_none());
 }
+
+// The members of past epochs are looked up by their leaf index, also when
+// there are blank leaves before them.
+#[openmls_test::openmls_test]
+fn test_leaf_for_epoch() {
+    let mut message_secrets_store = MessageSecretsStore::new_with_secret(
+        1,
+        MessageSecrets::random(ciphersuite, provider.rand(), LeafNodeIndex::new(0)),
+    );
+
+    let member = |index: u32, identity: &[u8]| {
+        Member::new(
+            LeafNodeIndex::new(index),
+            vec![index as u8],
+            vec![index as u8],
+            BasicCredential::new(identity.to_vec()).into(),
+        )
+    };
+    message_secrets_store.add(
+        0,
+        MessageSecrets::random(ciphersuite, provider.rand(), LeafNodeIndex::new(0)),
+        vec![member(0, b"Alice"), member(2, b"Charlie")],
+    );
+
+    assert_eq!(
+        message_secrets_store.leaf_for_epoch(0, LeafNodeIndex::new(2)),
+        Some(&member(2, b"Charlie"))
+    );
+    assert!(message_secrets_store.epoch_has_leaf(0.into(), LeafNodeIndex::new(0)));
+    assert!(!message_secrets_store.epoch_has_leaf(0.into(), LeafNodeIndex::new(1)));
+    assert_eq!(
+        message_secrets_store.leaf_for_epoch(1, LeafNodeIndex::new(0)),
+        None
+    );
+}
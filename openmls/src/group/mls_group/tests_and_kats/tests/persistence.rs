@@ -0,0 +1,121 @@
+//! This module contains tests for the group state snapshots.
+
+use mls_group::tests_and_kats::utils::setup_alice_bob_group;
+use openmls_test::openmls_test;
+use openmls_traits::OpenMlsProvider as _;
+
+use crate::{framing::*, group::*, treesync::LeafNodeParameters};
+
+#[openmls_test]
+fn group_state_snapshot() {
+    let (mut alice_group, alice_signer, bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let mut snapshot = Vec::new();
+    bob_group
+        .save_state(provider.storage(), &mut snapshot)
+        .expect("error saving the group state");
+    assert_eq!(&snapshot[..4], b"OMGS");
+    assert_eq!(snapshot[4..6], GROUP_STATE_FORMAT_VERSION.to_be_bytes());
+
+    // The group can't be loaded into a storage that already contains it.
+    let err = MlsGroup::load_state(provider.storage(), &mut snapshot.as_slice())
+        .expect_err("loaded a group that already exists");
+    assert!(matches!(err, GroupStateError::GroupAlreadyExists));
+
+    // Load the group into a fresh storage.
+    let bob_provider = Provider::default();
+    let mut restored_bob_group =
+        MlsGroup::load_state(bob_provider.storage(), &mut snapshot.as_slice())
+            .expect("error loading the group state");
+    assert_eq!(restored_bob_group.group_id(), bob_group.group_id());
+    assert_eq!(restored_bob_group.epoch(), bob_group.epoch());
+    assert_eq!(
+        restored_bob_group.export_ratchet_tree(),
+        bob_group.export_ratchet_tree()
+    );
+
+    // The restored group can decrypt the path of a commit and messages of the
+    // new epoch.
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer, LeafNodeParameters::default())
+        .expect("error creating self update")
+        .into_contents();
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let processed_message = restored_bob_group
+        .process_message(&bob_provider, commit.into_protocol_message().unwrap())
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a staged commit");
+    };
+    restored_bob_group
+        .merge_staged_commit(&bob_provider, *staged_commit)
+        .expect("error merging staged commit");
+
+    let message = alice_group
+        .create_message(provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message");
+    let processed_message = restored_bob_group
+        .process_message(&bob_provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+}
+
+#[openmls_test]
+fn group_state_snapshot_versions() {
+    let (_alice_group, _alice_signer, bob_group, _bob_signer, _bob_credential) =
+        setup_alice_bob_group(ciphersuite, provider);
+
+    let mut snapshot = Vec::new();
+    bob_group
+        .save_state(provider.storage(), &mut snapshot)
+        .expect("error saving the group state");
+
+    // Invalid magic bytes
+    let mut invalid = snapshot.clone();
+    invalid[0] ^= 0xff;
+    let err = MlsGroup::load_state(Provider::default().storage(), &mut invalid.as_slice())
+        .expect_err("loaded a snapshot with an invalid header");
+    assert!(matches!(err, GroupStateError::InvalidHeader));
+
+    // Newer format version
+    let mut newer = snapshot.clone();
+    newer[4..6].copy_from_slice(&(GROUP_STATE_FORMAT_VERSION + 1).to_be_bytes());
+    let err = MlsGroup::load_state(Provider::default().storage(), &mut newer.as_slice())
+        .expect_err("loaded a snapshot with a newer format version");
+    assert!(
+        matches!(err, GroupStateError::UnsupportedVersion(version) if version == GROUP_STATE_FORMAT_VERSION + 1)
+    );
+
+    // Older format versions need a migration.
+    let mut older = snapshot.clone();
+    older[4..6].copy_from_slice(&(GROUP_STATE_FORMAT_VERSION - 1).to_be_bytes());
+    let err = MlsGroup::load_state(Provider::default().storage(), &mut older.as_slice())
+        .expect_err("loaded a snapshot of an older format version without migration");
+    assert!(matches!(err, GroupStateError::Migration(_)));
+
+    let bob_provider = Provider::default();
+    let restored_bob_group = MlsGroup::load_state_with_migration(
+        bob_provider.storage(),
+        &mut older.as_slice(),
+        |version, value| {
+            assert_eq!(version, GROUP_STATE_FORMAT_VERSION - 1);
+            Ok(value)
+        },
+    )
+    .expect("error loading the migrated group state");
+    assert_eq!(restored_bob_group.epoch(), bob_group.epoch());
+
+    // Truncated snapshot
+    let truncated = &snapshot[..snapshot.len() / 2];
+    let err = MlsGroup::load_state(Provider::default().storage(), &mut &truncated[..])
+        .expect_err("loaded a truncated snapshot");
+    assert!(matches!(err, GroupStateError::Malformed));
+}
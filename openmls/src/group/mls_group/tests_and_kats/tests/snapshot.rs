@@ -0,0 +1,146 @@
+//! This module contains tests for the group snapshots.
+
+use openmls_test::openmls_test;
+use openmls_traits::OpenMlsProvider as _;
+
+use crate::{
+    credentials::test_utils::new_credential,
+    framing::*,
+    group::{errors::*, *},
+    key_packages::*,
+    treesync::LeafNodeParameters,
+};
+
+#[openmls_test]
+fn snapshot_rollback() {
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+
+    let (alice_credential, alice_signer) =
+        new_credential(alice_provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential, bob_signer) =
+        new_credential(bob_provider, b"Bob", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle =
+        KeyPackageBundle::generate(bob_provider, &bob_signer, ciphersuite, bob_credential);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .build(alice_provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::builder()
+            .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+            .build(),
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_join| staged_join.into_group(bob_provider))
+    .expect("error joining the group");
+
+    // Alice sends a batch of a message and a commit.
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello Bob")
+        .expect("error creating application message");
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(alice_provider, &alice_signer, LeafNodeParameters::default())
+        .expect("error creating self update")
+        .into_contents();
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let batch = [
+        message.into_protocol_message().unwrap(),
+        commit.into_protocol_message().unwrap(),
+    ];
+
+    let process_batch = |bob_group: &mut MlsGroup| {
+        for message in batch.clone() {
+            let processed_message = bob_group
+                .process_message(bob_provider, message)
+                .expect("error processing message");
+            if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+                processed_message.into_content()
+            {
+                bob_group
+                    .merge_staged_commit(bob_provider, *staged_commit)
+                    .expect("error merging staged commit");
+            }
+        }
+    };
+
+    // Bob processes the batch optimistically and rejects it.
+    let epoch = bob_group.epoch();
+    let snapshot = bob_group
+        .snapshot(bob_provider)
+        .expect("error taking snapshot");
+    assert_eq!(snapshot.epoch(), epoch);
+    process_batch(&mut bob_group);
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+
+    // Another group is created in the meantime.
+    let (other_group, _, _, _) =
+        mls_group::tests_and_kats::utils::setup_alice_group(ciphersuite, bob_provider);
+
+    bob_group
+        .rollback_to_snapshot(bob_provider, snapshot)
+        .expect("error rolling back");
+    assert_eq!(bob_group.epoch(), epoch);
+    let loaded_bob_group = MlsGroup::load(bob_provider.storage(), bob_group.group_id())
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded_bob_group.epoch(), epoch);
+
+    // The rollback only restored the state of Bob's group.
+    assert!(
+        MlsGroup::load(bob_provider.storage(), other_group.group_id())
+            .unwrap()
+            .is_some()
+    );
+
+    // The rolled back group can process the batch again and keep it.
+    let snapshot = bob_group
+        .snapshot(bob_provider)
+        .expect("error taking snapshot");
+    process_batch(&mut bob_group);
+    bob_group
+        .release_snapshot(bob_provider, snapshot)
+        .expect("error releasing snapshot");
+    assert_eq!(bob_group.epoch(), alice_group.epoch());
+
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello again")
+        .expect("error creating application message");
+    let processed_message = bob_group
+        .process_message(bob_provider, message.into_protocol_message().unwrap())
+        .expect("error processing application message");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ApplicationMessage(_)
+    ));
+
+    // Snapshots can only be rolled back on the group they were taken of.
+    let snapshot = other_group
+        .snapshot(bob_provider)
+        .expect("error taking snapshot");
+    let err = bob_group
+        .rollback_to_snapshot(bob_provider, snapshot)
+        .expect_err("rolled back the snapshot of another group");
+    let SnapshotRollbackError::WrongGroup(snapshot) = err else {
+        panic!("expected the snapshot to be returned");
+    };
+    other_group
+        .release_snapshot(bob_provider, snapshot)
+        .expect("error releasing snapshot");
+}
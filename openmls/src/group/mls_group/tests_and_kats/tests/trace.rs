@@ -0,0 +1,147 @@
+//! This module contains tests for recording and replaying group traces.
+
+use openmls_test::openmls_test;
+use openmls_traits::OpenMlsProvider as _;
+use tls_codec::Serialize as _;
+
+use crate::{
+    credentials::test_utils::new_credential, framing::*, group::*, key_packages::*,
+    test_utils::replay::*, treesync::LeafNodeParameters,
+};
+
+#[openmls_test]
+fn record_and_replay_trace() {
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+
+    let (alice_credential, alice_signer) =
+        new_credential(alice_provider, b"Alice", ciphersuite.signature_algorithm());
+    let (bob_credential, bob_signer) =
+        new_credential(bob_provider, b"Bob", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle =
+        KeyPackageBundle::generate(bob_provider, &bob_signer, ciphersuite, bob_credential);
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .build(alice_provider, &alice_signer, alice_credential)
+        .expect("error creating group");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("error adding Bob");
+    alice_group
+        .merge_pending_commit(alice_provider)
+        .expect("error merging pending commit");
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_join| staged_join.into_group(bob_provider))
+    .expect("error joining the group");
+
+    let mut recorder =
+        GroupRecorder::new(&bob_group, bob_provider.storage()).expect("error starting recorder");
+
+    // Bob receives a message, a commit and a malformed message.
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello Bob")
+        .unwrap();
+    recorder
+        .process_message(
+            &mut bob_group,
+            bob_provider,
+            &message.tls_serialize_detached().unwrap(),
+        )
+        .expect("error processing message");
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(alice_provider, &alice_signer, LeafNodeParameters::default())
+        .unwrap()
+        .into_contents();
+    alice_group.merge_pending_commit(alice_provider).unwrap();
+    let processed_message = recorder
+        .process_message(
+            &mut bob_group,
+            bob_provider,
+            &commit.tls_serialize_detached().unwrap(),
+        )
+        .expect("error processing commit");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    bob_group
+        .merge_staged_commit(bob_provider, *staged_commit)
+        .unwrap();
+    recorder
+        .process_message(&mut bob_group, bob_provider, &[0, 1])
+        .expect_err("processed a malformed message");
+
+    // Bob commits an update.
+    let (commit, _welcome, _group_info) = bob_group
+        .self_update(bob_provider, &bob_signer, LeafNodeParameters::default())
+        .unwrap()
+        .into_contents();
+    recorder.record_outbound(&bob_group, &commit).unwrap();
+    bob_group.merge_pending_commit(bob_provider).unwrap();
+    recorder
+        .checkpoint(&bob_group, bob_provider.storage())
+        .unwrap();
+    let processed_message = alice_group
+        .process_message(alice_provider, commit.into_protocol_message().unwrap())
+        .unwrap();
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        panic!("expected a commit");
+    };
+    alice_group
+        .merge_staged_commit(alice_provider, *staged_commit)
+        .unwrap();
+
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello again")
+        .unwrap();
+    recorder
+        .process_message(
+            &mut bob_group,
+            bob_provider,
+            &message.tls_serialize_detached().unwrap(),
+        )
+        .expect("error processing message");
+
+    let mut trace_file = Vec::new();
+    recorder.into_trace().write(&mut trace_file).unwrap();
+    let trace = GroupTrace::read(&mut trace_file.as_slice()).unwrap();
+    assert_eq!(trace.events().len(), 6);
+
+    // The replayed group is in the same state as the recorded one.
+    let replay_provider = &Provider::default();
+    let replayed_group = replay(replay_provider, &trace).expect("error replaying trace");
+    assert_eq!(replayed_group.epoch(), bob_group.epoch());
+    assert_eq!(
+        replayed_group.epoch_authenticator(),
+        bob_group.epoch_authenticator()
+    );
+    assert_eq!(
+        replayed_group
+            .export_secret(replay_provider, "test", &[], 32)
+            .unwrap(),
+        bob_group
+            .export_secret(bob_provider, "test", &[], 32)
+            .unwrap()
+    );
+
+    // A replay reports the first event that diverges from the trace.
+    let mut trace_value = serde_json::to_value(&trace).unwrap();
+    trace_value["events"][2]["Inbound"]["result"] = serde_json::json!({ "Ok": null });
+    let diverging_trace: GroupTrace = serde_json::from_value(trace_value).unwrap();
+    let err =
+        replay(&Provider::default(), &diverging_trace).expect_err("replayed a diverging trace");
+    assert!(matches!(err, ReplayError::ResultMismatch { event: 2, .. }));
+}
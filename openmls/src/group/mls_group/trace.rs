@@ -0,0 +1,190 @@
+//! # Group session traces
+//!
+//! To reproduce a divergence of the group state reported by a user, a
+//! [`GroupRecorder`] records the messages a group sends and receives into a
+//! [`GroupTrace`]. The trace starts with a snapshot of the group state, see
+//! [`MlsGroup::save_state()`], and contains every recorded message together
+//! with the epoch of the group at that time and the result of processing it.
+//!
+//! The own commits of a group can't be processed again from the trace. After
+//! merging an own commit, or after any other change of the group state that is
+//! not caused by processing a message, the application has to record a
+//! checkpoint with [`GroupRecorder::checkpoint()`], which snapshots the group
+//! state again.
+//!
+//! The replayer in the `test-utils` reconstructs the group state from a trace.
+//! It merges all commits and stores all proposals that were processed
+//! successfully, and replaces the group state with the snapshot of every
+//! checkpoint.
+//!
+//! **The trace contains the snapshots of the group state, including all
+//! secrets of the recorded epochs, and has to be protected accordingly.**
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use tls_codec::Deserialize as _;
+
+use super::{
+    errors::{GroupStateError, RecordedProcessError},
+    *,
+};
+use crate::framing::errors::MlsMessageError;
+
+/// An event of a [`GroupTrace`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceEvent {
+    /// A message received by the group.
+    Inbound {
+        /// The epoch of the group before processing the message.
+        epoch: u64,
+        /// The serialized message.
+        message: Vec<u8>,
+        /// The result of processing the message, with the display string of
+        /// the error if it failed.
+        result: Result<(), String>,
+    },
+    /// A message sent by the group.
+    Outbound {
+        /// The epoch of the group when the message was created.
+        epoch: u64,
+        /// The serialized message.
+        message: Vec<u8>,
+    },
+    /// A snapshot of the group state.
+    Checkpoint {
+        /// The epoch of the group.
+        epoch: u64,
+        /// The snapshot written by [`MlsGroup::save_state()`].
+        state: Vec<u8>,
+    },
+}
+
+/// A replayable trace of a group session, recorded by a [`GroupRecorder`].
+///
+/// The trace contains snapshots of the group state, including all secrets of
+/// the recorded epochs, and has to be protected accordingly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupTrace {
+    initial_state: Vec<u8>,
+    events: Vec<TraceEvent>,
+}
+
+impl GroupTrace {
+    /// Returns the snapshot of the group state the trace starts with.
+    pub fn initial_state(&self) -> &[u8] {
+        &self.initial_state
+    }
+
+    /// Returns the recorded events in the order they were recorded.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Writes the JSON serialization of the trace to the `writer`.
+    pub fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Reads a trace written by [`GroupTrace::write()`] from the `reader`.
+    pub fn read(reader: &mut impl Read) -> std::io::Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Records the messages of a group into a [`GroupTrace`].
+#[derive(Debug)]
+pub struct GroupRecorder {
+    trace: GroupTrace,
+}
+
+impl GroupRecorder {
+    /// Start recording the `group` with a snapshot of its state in `storage`.
+    pub fn new<Storage: StorageProvider>(
+        group: &MlsGroup,
+        storage: &Storage,
+    ) -> Result<Self, GroupStateError<Storage::Error>> {
+        let mut initial_state = Vec::new();
+        group.save_state(storage, &mut initial_state)?;
+        Ok(Self {
+            trace: GroupTrace {
+                initial_state,
+                events: Vec::new(),
+            },
+        })
+    }
+
+    /// Deserializes and processes the `message` with the `group` like
+    /// [`MlsGroup::process_message()`] and records it together with the
+    /// result.
+    pub fn process_message<Provider: OpenMlsProvider>(
+        &mut self,
+        group: &mut MlsGroup,
+        provider: &Provider,
+        message: &[u8],
+    ) -> Result<ProcessedMessage, RecordedProcessError<Provider::StorageError>> {
+        let epoch = group.epoch().as_u64();
+        let processed_message = process_serialized_message(group, provider, message);
+        self.trace.events.push(TraceEvent::Inbound {
+            epoch,
+            message: message.to_vec(),
+            result: processed_message
+                .as_ref()
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        });
+        processed_message
+    }
+
+    /// Records the `message` created by the `group`. Own commits have to be
+    /// followed by a [`GroupRecorder::checkpoint()`] after merging them.
+    pub fn record_outbound(
+        &mut self,
+        group: &MlsGroup,
+        message: &MlsMessageOut,
+    ) -> Result<(), MlsMessageError> {
+        self.trace.events.push(TraceEvent::Outbound {
+            epoch: group.epoch().as_u64(),
+            message: message.to_bytes()?,
+        });
+        Ok(())
+    }
+
+    /// Records a snapshot of the state of the `group` in `storage`.
+    pub fn checkpoint<Storage: StorageProvider>(
+        &mut self,
+        group: &MlsGroup,
+        storage: &Storage,
+    ) -> Result<(), GroupStateError<Storage::Error>> {
+        let mut state = Vec::new();
+        group.save_state(storage, &mut state)?;
+        self.trace.events.push(TraceEvent::Checkpoint {
+            epoch: group.epoch().as_u64(),
+            state,
+        });
+        Ok(())
+    }
+
+    /// Returns the trace recorded so far.
+    pub fn trace(&self) -> &GroupTrace {
+        &self.trace
+    }
+
+    /// Stops recording and returns the trace.
+    pub fn into_trace(self) -> GroupTrace {
+        self.trace
+    }
+}
+
+/// Deserializes and processes the `message` with the `group`.
+pub(crate) fn process_serialized_message<Provider: OpenMlsProvider>(
+    group: &mut MlsGroup,
+    provider: &Provider,
+    message: &[u8],
+) -> Result<ProcessedMessage, RecordedProcessError<Provider::StorageError>> {
+    let message = MlsMessageIn::tls_deserialize_exact(message)
+        .map_err(|_| MlsMessageError::UnableToDecode)?
+        .try_into_protocol_message()?;
+    Ok(group.process_message(provider, message)?)
+}
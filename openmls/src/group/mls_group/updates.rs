@@ -43,6 +43,47 @@ impl MlsGroup {
         Ok(bundle)
     }
 
+    /// Updates the own leaf node and rotates the own signature key. The new
+    /// leaf node contains the credential and signature key of `new_signer`.
+    /// Any credential set in the `leaf_node_parameters` is ignored.
+    ///
+    /// The commit is signed with `old_signer`. The new signature key is staged
+    /// with the pending commit, see [`MlsGroup::pending_signature_key()`], and
+    /// swapped in when the commit is merged with
+    /// [`MlsGroup::merge_pending_commit()`]. Until that function returns
+    /// successfully, the old signer must be used. If the pending commit is
+    /// cleared instead, the old signer stays valid. Persisting the new
+    /// signature key pair is up to the application.
+    ///
+    /// Returns an error if there is a pending commit.
+    pub fn self_update_with_new_signer<Provider: OpenMlsProvider, S: Signer>(
+        &mut self,
+        provider: &Provider,
+        old_signer: &impl Signer,
+        new_signer: NewSignerBundle<'_, S>,
+        leaf_node_parameters: LeafNodeParameters,
+    ) -> Result<CommitMessageBundle, SelfUpdateError<Provider::StorageError>> {
+        self.is_operational()?;
+
+        let bundle = self
+            .commit_builder()
+            .leaf_node_parameters(leaf_node_parameters)
+            .consume_proposal_store(true)
+            .load_psks(provider.storage())?
+            .build_with_new_signer(
+                provider.rand(),
+                provider.crypto(),
+                old_signer,
+                new_signer,
+                |_| true,
+            )?
+            .stage_commit(provider)?;
+
+        self.reset_aad();
+
+        Ok(bundle)
+    }
+
     /// Creates a proposal to update the own leaf node. Optionally, a
     /// [`LeafNode`] can be provided to update the leaf node. Note that its
     /// private key must be manually added to the key store.
@@ -50,6 +91,7 @@ impl MlsGroup {
         &mut self,
         provider: &Provider,
         signer: &impl Signer,
+        leaf_node_signer: &impl Signer,
         leaf_node_parmeters: LeafNodeParameters,
     ) -> Result<AuthenticatedContent, ProposeSelfUpdateError<Provider::StorageError>> {
         self.is_operational()?;
@@ -67,7 +109,7 @@ impl MlsGroup {
         own_leaf.update(
             self.ciphersuite(),
             provider,
-            signer,
+            leaf_node_signer,
             self.group_id().clone(),
             self.own_leaf_index(),
             leaf_node_parmeters,
@@ -94,7 +136,43 @@ impl MlsGroup {
         signer: &impl Signer,
         leaf_node_parameters: LeafNodeParameters,
     ) -> Result<(MlsMessageOut, ProposalRef), ProposeSelfUpdateError<Provider::StorageError>> {
-        let update_proposal = self._propose_self_update(provider, signer, leaf_node_parameters)?;
+        let update_proposal =
+            self._propose_self_update(provider, signer, signer, leaf_node_parameters)?;
+        self.queue_own_update_proposal(provider, update_proposal)
+    }
+
+    /// Creates a proposal to update the own leaf node and to rotate the own
+    /// signature key. The new leaf node contains the credential and signature
+    /// key of `new_signer`. Any credential set in the `leaf_node_parameters` is
+    /// ignored.
+    ///
+    /// The proposal is signed with `old_signer`, which must also be used until
+    /// a commit covering the proposal was merged. After that, only the new
+    /// signer must be used in this group.
+    pub fn propose_self_update_with_new_signer<Provider: OpenMlsProvider, S: Signer>(
+        &mut self,
+        provider: &Provider,
+        old_signer: &impl Signer,
+        new_signer: NewSignerBundle<'_, S>,
+        mut leaf_node_parameters: LeafNodeParameters,
+    ) -> Result<(MlsMessageOut, ProposalRef), ProposeSelfUpdateError<Provider::StorageError>> {
+        leaf_node_parameters.set_credential_with_key(new_signer.credential_with_key);
+        let update_proposal = self._propose_self_update(
+            provider,
+            old_signer,
+            new_signer.signer,
+            leaf_node_parameters,
+        )?;
+        self.queue_own_update_proposal(provider, update_proposal)
+    }
+
+    /// Stores an own update proposal in the proposal store and returns it as
+    /// a message to send to the group.
+    fn queue_own_update_proposal<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        update_proposal: AuthenticatedContent,
+    ) -> Result<(MlsMessageOut, ProposalRef), ProposeSelfUpdateError<Provider::StorageError>> {
         let proposal = QueuedProposal::from_authenticated_content_by_ref(
             self.ciphersuite(),
             provider.crypto(),
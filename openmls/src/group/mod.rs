@@ -23,10 +23,22 @@ pub(crate) mod public_group;
 // Public
 pub use errors::*;
 pub use group_context::GroupContext;
+pub use mls_group::batch::{ProcessedBatch, ProcessedBatchMessage};
+pub use mls_group::chunking::ChunkReassembler;
+#[cfg(feature = "json")]
+pub use mls_group::client_state::*;
 pub use mls_group::config::*;
+pub use mls_group::maintenance::*;
 pub use mls_group::membership::*;
+#[cfg(feature = "memory-stats")]
+pub use mls_group::memory_stats::MemoryStats;
+#[cfg(feature = "json")]
+pub use mls_group::persistence::GROUP_STATE_FORMAT_VERSION;
 pub use mls_group::proposal_store::*;
+pub use mls_group::snapshot::GroupSnapshot;
 pub use mls_group::staged_commit::StagedCommit;
+#[cfg(feature = "json")]
+pub use mls_group::trace::{GroupRecorder, GroupTrace, TraceEvent};
 pub use mls_group::{Member, *};
 pub use public_group::*;
 
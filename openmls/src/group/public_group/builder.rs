@@ -5,7 +5,7 @@ use crate::{
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::{errors::InvalidExtensionError, Extensions},
-    group::{ExtensionType, GroupContext, GroupId},
+    group::{GroupContext, GroupId},
     key_packages::Lifetime,
     messages::ConfirmationTag,
     schedule::CommitSecret,
@@ -57,11 +57,11 @@ impl TempBuilderPG1 {
         mut self,
         extensions: Extensions,
     ) -> Result<Self, InvalidExtensionError> {
-        // None of the default extensions are leaf node extensions, so only
-        // unknown extensions can be leaf node extensions.
+        // Only the application id and unknown extensions can be leaf node
+        // extensions.
         let is_valid_in_leaf_node = extensions
             .iter()
-            .all(|e| matches!(e.extension_type(), ExtensionType::Unknown(_)));
+            .all(|e| e.extension_type().is_valid_in_leaf_node() != Some(false));
         if !is_valid_in_leaf_node {
             return Err(InvalidExtensionError::IllegalInLeafNodes);
         }
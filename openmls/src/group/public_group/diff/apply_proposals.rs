@@ -54,6 +54,7 @@ impl PublicGroupDiff<'_> {
         &mut self,
         proposal_queue: &ProposalQueue,
         own_leaf_index: impl Into<Option<LeafNodeIndex>>,
+        crypto: &impl OpenMlsCrypto,
     ) -> Result<ApplyProposalsValues, LibraryError> {
         log::debug!("Applying proposal");
         let mut self_removed = false;
@@ -127,6 +128,8 @@ impl PublicGroupDiff<'_> {
                 .add_leaf(leaf_node.clone())
                 // TODO #810
                 .map_err(|_| LibraryError::custom("Tree full: cannot add more members"))?;
+            self.diff
+                .set_key_package_ref(leaf_index, add_proposal.key_package.hash_ref(crypto)?);
             invitation_list.push((leaf_index, add_proposal.clone()))
         }
 
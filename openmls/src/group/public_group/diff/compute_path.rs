@@ -5,6 +5,7 @@ use tls_codec::Serialize;
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    ciphersuite::CiphersuiteRegistry,
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::Extensions,
@@ -13,7 +14,7 @@ use crate::{
     treesync::{
         node::{
             encryption_keys::EncryptionKeyPair,
-            leaf_node::{Capabilities, LeafNodeParameters, UpdateLeafNodeParams},
+            leaf_node::{LeafNodeParameters, UpdateLeafNodeParams},
             parent_node::PlainUpdatePathNode,
         },
         treekem::UpdatePath,
@@ -50,7 +51,7 @@ impl PublicGroupDiff<'_> {
         let leaf_node_params = if let CommitType::External(credential_with_key) = commit_type {
             let capabilities = match leaf_node_params.capabilities() {
                 Some(c) => c.to_owned(),
-                None => Capabilities::default(),
+                None => CiphersuiteRegistry::new(crypto).default_capabilities(),
             };
 
             let extensions = match leaf_node_params.extensions() {
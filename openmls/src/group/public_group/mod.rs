@@ -29,10 +29,14 @@ use super::{
 use crate::treesync::{node::parent_node::PlainUpdatePathNode, treekem::UpdatePathNode};
 use crate::{
     binary_tree::{array_representation::TreeSize, LeafNodeIndex},
-    ciphersuite::{hash_ref::ProposalRef, signable::Verifiable},
+    ciphersuite::{
+        hash_ref::{KeyPackageRef, ProposalRef},
+        signable::Verifiable,
+    },
     error::LibraryError,
-    extensions::RequiredCapabilitiesExtension,
+    extensions::{ExtensionRegistry, RequiredCapabilitiesExtension},
     framing::InterimTranscriptHashInput,
+    key_packages::LifetimePolicy,
     messages::{
         group_info::{GroupInfo, VerifiableGroupInfo},
         proposals::{Proposal, ProposalOrRefType, ProposalType},
@@ -72,6 +76,13 @@ pub struct PublicGroup {
     interim_transcript_hash: Vec<u8>,
     // Most recent confirmation tag. Kept here for verification purposes.
     confirmation_tag: ConfirmationTag,
+    // The policy for validating the lifetimes of leaf nodes. It is persisted
+    // as part of the `MlsGroupJoinConfig` and restored when an `MlsGroup` is
+    // loaded. Public groups on their own always use the default policy.
+    lifetime_policy: LifetimePolicy,
+    // The application extensions that are validated in leaf nodes, key
+    // packages and group context extensions proposals. It is not persisted.
+    extension_registry: ExtensionRegistry,
 }
 
 /// This is a wrapper type, because we can't implement the storage traits on `Vec<u8>`.
@@ -103,6 +114,8 @@ impl PublicGroup {
             group_context,
             interim_transcript_hash,
             confirmation_tag: initial_confirmation_tag,
+            lifetime_policy: LifetimePolicy::default(),
+            extension_registry: ExtensionRegistry::default(),
         })
     }
 
@@ -117,6 +130,26 @@ impl PublicGroup {
         ratchet_tree: RatchetTreeIn,
         verifiable_group_info: VerifiableGroupInfo,
         proposal_store: ProposalStore,
+    ) -> Result<(Self, GroupInfo), CreationFromExternalError<StorageProvider::PublicError>> {
+        Self::from_external_with_lifetime_policy(
+            crypto,
+            storage,
+            ratchet_tree,
+            verifiable_group_info,
+            proposal_store,
+            LifetimePolicy::default(),
+        )
+    }
+
+    /// Like [`PublicGroup::from_external()`], but validates the lifetimes of
+    /// the leaf nodes in the ratchet tree against the given `lifetime_policy`.
+    pub(crate) fn from_external_with_lifetime_policy<StorageProvider: PublicStorageProvider>(
+        crypto: &impl OpenMlsCrypto,
+        storage: &StorageProvider,
+        ratchet_tree: RatchetTreeIn,
+        verifiable_group_info: VerifiableGroupInfo,
+        proposal_store: ProposalStore,
+        lifetime_policy: LifetimePolicy,
     ) -> Result<(Self, GroupInfo), CreationFromExternalError<StorageProvider::PublicError>> {
         let ciphersuite = verifiable_group_info.ciphersuite();
 
@@ -180,13 +213,15 @@ impl PublicGroup {
             interim_transcript_hash,
             confirmation_tag: group_info.confirmation_tag().clone(),
             proposal_store,
+            lifetime_policy,
+            extension_registry: ExtensionRegistry::default(),
         };
 
         // Fully check that the leaf nodes in the ratchet tree are valid
         public_group
             .treesync
             .full_leaves()
-            .try_for_each(|leaf_node| public_group.validate_leaf_node(leaf_node))?;
+            .try_for_each(|leaf_node| public_group.validate_leaf_node(crypto, leaf_node))?;
 
         public_group
             .store(storage)
@@ -377,6 +412,31 @@ impl PublicGroup {
         self.treesync().leaf(leaf_index)
     }
 
+    /// Return the index of the member with the given `signature_key` or
+    /// `None` if there is no such member.
+    pub(crate) fn member_index_by_signature_key(
+        &self,
+        signature_key: &[u8],
+    ) -> Option<LeafNodeIndex> {
+        self.treesync().leaf_index_by_signature_key(signature_key)
+    }
+
+    /// Returns the approximate number of bytes held by the tree.
+    #[cfg(feature = "memory-stats")]
+    pub(crate) fn tree_memory_size(&self) -> usize {
+        crate::group::mls_group::memory_stats::approximate_size(self.treesync())
+    }
+
+    /// Return the index of the member that was added with the key package
+    /// with the given `key_package_ref` or `None` if there is no such member.
+    pub(crate) fn member_index_by_key_package_ref(
+        &self,
+        key_package_ref: &KeyPackageRef,
+    ) -> Option<LeafNodeIndex> {
+        self.treesync()
+            .leaf_index_by_key_package_ref(key_package_ref)
+    }
+
     /// Returns the tree size
     pub(crate) fn tree_size(&self) -> TreeSize {
         self.treesync().tree_size()
@@ -448,6 +508,8 @@ impl PublicGroup {
                 group_context: group_context?,
                 interim_transcript_hash: interim_transcript_hash?.0,
                 confirmation_tag: confirmation_tag?,
+                lifetime_policy: LifetimePolicy::default(),
+                extension_registry: ExtensionRegistry::default(),
             })
         };
 
@@ -463,6 +525,28 @@ impl PublicGroup {
     pub(crate) fn proposal_store_mut(&mut self) -> &mut ProposalStore {
         &mut self.proposal_store
     }
+
+    /// Sets the [`LifetimePolicy`] used to validate leaf nodes.
+    pub(crate) fn set_lifetime_policy(&mut self, lifetime_policy: LifetimePolicy) {
+        self.lifetime_policy = lifetime_policy;
+    }
+
+    /// Returns the [`ExtensionRegistry`] set with
+    /// [`PublicGroup::set_extension_registry()`].
+    pub(crate) fn extension_registry(&self) -> &ExtensionRegistry {
+        &self.extension_registry
+    }
+
+    /// Sets the [`ExtensionRegistry`] whose application extensions are
+    /// validated in the leaf nodes and key packages of incoming proposals and
+    /// commits, and in group context extensions proposals.
+    ///
+    /// The registry is not persisted, so it has to be set again after the
+    /// group is loaded. The ratchet tree a group is joined with is validated
+    /// before the registry can be set.
+    pub fn set_extension_registry(&mut self, extension_registry: ExtensionRegistry) {
+        self.extension_registry = extension_registry;
+    }
 }
 
 // Test functions
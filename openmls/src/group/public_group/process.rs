@@ -1,6 +1,8 @@
 //! This module contains the implementation of the processing functions for
 //! public groups.
 
+use std::convert::Infallible;
+
 use openmls_traits::crypto::OpenMlsCrypto;
 use tls_codec::Serialize;
 
@@ -62,8 +64,7 @@ impl PublicGroup {
                     .map(CredentialWithKey::from)
             } else if let Some(store) = message_secrets_store_option {
                 store
-                    .leaves_for_epoch(message_epoch)
-                    .get(leaf_node_index.u32() as usize)
+                    .leaf_for_epoch(message_epoch, leaf_node_index)
                     .map(CredentialWithKey::from)
             } else {
                 None
@@ -149,7 +150,7 @@ impl PublicGroup {
         &self,
         crypto: &impl OpenMlsCrypto,
         message: impl Into<ProtocolMessage>,
-    ) -> Result<ProcessedMessage, ProcessMessageError> {
+    ) -> Result<ProcessedMessage, ProcessMessageError<Infallible>> {
         let protocol_message = message.into();
         // Checks the following semantic validation:
         //  - ValSem002
@@ -166,7 +167,8 @@ impl PublicGroup {
                     None,
                     self.group_context()
                         .tls_serialize_detached()
-                        .map_err(LibraryError::missing_bound_check)?,
+                        .map_err(LibraryError::missing_bound_check)?
+                        .into(),
                     crypto,
                     self.ciphersuite(),
                 )?
@@ -211,17 +213,18 @@ impl PublicGroup {
         &self,
         crypto: &impl OpenMlsCrypto,
         unverified_message: UnverifiedMessage,
-    ) -> Result<ProcessedMessage, ProcessMessageError> {
+    ) -> Result<ProcessedMessage, ProcessMessageError<Infallible>> {
         // Checks the following semantic validation:
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
         //  - https://validation.openmls.tech/#valn1203
-        let (content, credential) =
+        let (content, credential_with_key) =
             unverified_message.verify(self.ciphersuite(), crypto, self.version())?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
                 let sender = content.sender().clone();
+                let epoch = content.epoch();
                 let authenticated_data = content.authenticated_data().to_owned();
 
                 let content = match content.content() {
@@ -250,15 +253,16 @@ impl PublicGroup {
 
                 Ok(ProcessedMessage::new(
                     self.group_id().clone(),
-                    self.group_context().epoch(),
+                    epoch,
                     sender,
                     authenticated_data,
                     content,
-                    credential,
+                    credential_with_key,
                 ))
             }
             Sender::External(_) => {
                 let sender = content.sender().clone();
+                let epoch = content.epoch();
                 let data = content.authenticated_data().to_owned();
                 match content.content() {
                     FramedContentBody::Application(_) => {
@@ -274,11 +278,11 @@ impl PublicGroup {
                         ));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
-                            self.group_context().epoch(),
+                            epoch,
                             sender,
                             data,
                             content,
-                            credential,
+                            credential_with_key,
                         ))
                     }
                     // TODO #151/#106
@@ -92,12 +92,15 @@ impl PublicGroup {
                 FromCommittedProposalsError::LibraryError(e) => StageCommitError::LibraryError(e),
                 FromCommittedProposalsError::ProposalNotFound => StageCommitError::MissingProposal,
                 FromCommittedProposalsError::SelfRemoval => StageCommitError::AttemptedSelfRemoval,
+                FromCommittedProposalsError::DuplicateProposal => {
+                    StageCommitError::DuplicateProposal
+                }
             }
         })?;
 
         // https://validation.openmls.tech/#valn1207
         if let Some(update_path) = &commit.path {
-            self.validate_leaf_node(update_path.leaf_node())?;
+            self.validate_leaf_node(crypto, update_path.leaf_node())?;
         }
 
         // Validate the staged proposals. This implements https://validation.openmls.tech/#valn1204.
@@ -109,7 +112,7 @@ impl PublicGroup {
         // ValSem104
         self.validate_key_uniqueness(&proposal_queue, Some(commit))?;
         // ValSem105
-        self.validate_add_proposals(&proposal_queue)?;
+        self.validate_add_proposals(crypto, &proposal_queue)?;
         // ValSem106
         // ValSem109
         self.validate_capabilities(&proposal_queue)?;
@@ -132,7 +135,7 @@ impl PublicGroup {
                 // ValSem110
                 // ValSem111
                 // ValSem112
-                self.validate_update_proposals(&proposal_queue, *leaf_index)?;
+                self.validate_update_proposals(crypto, &proposal_queue, *leaf_index)?;
 
                 self.validate_no_external_init_proposals(&proposal_queue)?;
             }
@@ -255,7 +258,7 @@ impl PublicGroup {
         let ciphersuite = self.ciphersuite();
         let mut diff = self.empty_diff();
 
-        let apply_proposals_values = diff.apply_proposals(proposal_queue, None)?;
+        let apply_proposals_values = diff.apply_proposals(proposal_queue, None, crypto)?;
 
         let commit = match mls_content.content() {
             FramedContentBody::Commit(commit) => commit,
@@ -4,13 +4,16 @@ use crate::{
     binary_tree::LeafNodeIndex,
     framing::{
         public_message_in::PublicMessageIn, MlsMessageIn, MlsMessageOut, ProcessedMessage,
-        ProcessedMessageContent, ProtocolMessage, Sender,
+        ProcessedMessageContent, ProtocolMessage, PublicMessage, Sender,
     },
     group::{
-        mls_group::tests_and_kats::utils::setup_client, proposal_store::ProposalStore, GroupId,
-        MlsGroup, MlsGroupCreateConfig, StagedCommit, PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        errors::{ProcessMessageError, ValidationError},
+        mls_group::tests_and_kats::utils::setup_client,
+        proposal_store::ProposalStore,
+        GroupId, MlsGroup, MlsGroupCreateConfig, StagedCommit, PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
     },
     messages::proposals::Proposal,
+    treesync::LeafNodeParameters,
 };
 
 use super::{super::mls_group::StagedWelcome, PublicGroup};
@@ -309,3 +312,75 @@ fn extract_staged_commit(ppm: ProcessedMessage) -> StagedCommit {
         ProcessedMessageContent::StagedCommitMessage(staged_content) => *staged_content,
     }
 }
+
+/// Test that a public group rejects member commits without membership tag or
+/// confirmation tag with the corresponding validation error.
+#[openmls_test::openmls_test]
+fn public_group_missing_tags<Provider: OpenMlsProvider>(
+    ciphersuite: Ciphersuite,
+    provider: &Provider,
+) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let verifiable_group_info = alice_group
+        .export_group_info(provider, &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let ratchet_tree = alice_group.export_ratchet_tree();
+    let (public_group, _extensions) = PublicGroup::from_external(
+        provider.crypto(),
+        provider.storage(),
+        ratchet_tree.into(),
+        verifiable_group_info,
+        ProposalStore::new(),
+    )
+    .unwrap();
+
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_signer, LeafNodeParameters::default())
+        .expect("Could not self-update.")
+        .into_contents();
+    let commit: PublicMessage = into_public_message(commit).into();
+
+    // Strip the membership tag.
+    let mut franken_commit = commit.clone();
+    franken_commit.unset_membership_tag();
+    let err = public_group
+        .process_message(provider.crypto(), franken_commit)
+        .expect_err("Could process commit without membership tag.");
+    assert!(matches!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::MissingMembershipTag)
+    ));
+
+    // Strip the confirmation tag.
+    let mut franken_commit = commit.clone();
+    franken_commit.set_confirmation_tag(None);
+    let err = public_group
+        .process_message(provider.crypto(), franken_commit)
+        .expect_err("Could process commit without confirmation tag.");
+    assert!(matches!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::MissingConfirmationTag)
+    ));
+
+    // Positive case
+    public_group
+        .process_message(provider.crypto(), commit)
+        .expect("Unexpected error.");
+}
@@ -3,7 +3,7 @@
 
 use std::collections::{BTreeSet, HashSet};
 
-use openmls_traits::types::VerifiableCiphersuite;
+use openmls_traits::{crypto::OpenMlsCrypto, types::VerifiableCiphersuite};
 
 use super::PublicGroup;
 use crate::extensions::RequiredCapabilitiesExtension;
@@ -20,8 +20,8 @@ use crate::{
     group::{
         errors::{ExternalCommitValidationError, ProposalValidationError, ValidationError},
         past_secrets::MessageSecretsStore,
-        Member,
     },
+    key_packages::time_provider,
     messages::{
         proposals::{Proposal, ProposalOrRefType, ProposalType},
         Commit,
@@ -29,8 +29,6 @@ use crate::{
     schedule::errors::PskError,
 };
 
-use crate::treesync::errors::LifetimeError;
-
 impl PublicGroup {
     // === Messages ===
 
@@ -190,20 +188,19 @@ impl PublicGroup {
                 .map(|remove_proposal| remove_proposal.remove_proposal().removed),
         );
 
-        // Initialize the sets with the current members, filtered by the
-        // remove proposals.
-        for Member {
-            index,
-            encryption_key,
-            signature_key,
-            ..
-        } in self.treesync().full_leave_members()
-        {
-            if !remove_proposals.contains(&index) {
-                signature_key_set.insert(signature_key);
-                encryption_key_set.insert(encryption_key);
-            }
-        }
+        // The keys of the current members, filtered by the remove proposals,
+        // are looked up in the member index of the tree. The sets only
+        // contain the new keys.
+        let is_member_signature_key = |signature_key: &[u8]| {
+            self.treesync()
+                .leaf_index_by_signature_key(signature_key)
+                .is_some_and(|index| !remove_proposals.contains(&index))
+        };
+        let is_member_encryption_key = |encryption_key: &[u8]| {
+            self.treesync()
+                .leaf_index_by_encryption_key(encryption_key)
+                .is_some_and(|index| !remove_proposals.contains(&index))
+        };
 
         // Collect signature keys from add proposals
         let signature_keys = proposal_queue.add_proposals().map(|add_proposal| {
@@ -273,7 +270,7 @@ impl PublicGroup {
         //  - https://validation.openmls.tech/#valn0111
         //  - https://validation.openmls.tech/#valn0305
         for signature_key in signature_keys {
-            if !signature_key_set.insert(signature_key) {
+            if is_member_signature_key(&signature_key) || !signature_key_set.insert(signature_key) {
                 return Err(ProposalValidationError::DuplicateSignatureKey);
             }
         }
@@ -289,7 +286,9 @@ impl PublicGroup {
             if init_key_set.contains(&encryption_key) {
                 return Err(ProposalValidationError::InitEncryptionKeyCollision);
             }
-            if !encryption_key_set.insert(encryption_key) {
+            if is_member_encryption_key(&encryption_key)
+                || !encryption_key_set.insert(encryption_key)
+            {
                 return Err(ProposalValidationError::DuplicateEncryptionKey);
             }
         }
@@ -298,7 +297,7 @@ impl PublicGroup {
         //  - ValSem102
         //  - ValSem104
         for init_key in init_keys {
-            if encryption_key_set.contains(&init_key) {
+            if is_member_encryption_key(&init_key) || encryption_key_set.contains(&init_key) {
                 return Err(ProposalValidationError::InitEncryptionKeyCollision);
             }
             if !init_key_set.insert(init_key) {
@@ -348,6 +347,7 @@ impl PublicGroup {
     ///  - ValSem105: Add Proposal: Ciphersuite & protocol version must match the group
     pub(crate) fn validate_add_proposals(
         &self,
+        crypto: &impl OpenMlsCrypto,
         proposal_queue: &ProposalQueue,
     ) -> Result<(), ProposalValidationError> {
         let add_proposals = proposal_queue.add_proposals();
@@ -364,7 +364,14 @@ impl PublicGroup {
             }
 
             // https://validation.openmls.tech/#valn0202
-            self.validate_leaf_node(add_proposal.add_proposal().key_package().leaf_node())?;
+            self.validate_leaf_node(
+                crypto,
+                add_proposal.add_proposal().key_package().leaf_node(),
+            )?;
+
+            self.extension_registry
+                .validate(add_proposal.add_proposal().key_package().extensions())
+                .map_err(ProposalValidationError::InvalidApplicationExtension)?;
         }
         Ok(())
     }
@@ -425,6 +432,7 @@ impl PublicGroup {
     /// TODO: #133 This validation must be updated according to Sec. 13.2
     pub(crate) fn validate_update_proposals(
         &self,
+        crypto: &impl OpenMlsCrypto,
         proposal_queue: &ProposalQueue,
         committer: LeafNodeIndex,
     ) -> Result<(), ProposalValidationError> {
@@ -445,7 +453,7 @@ impl PublicGroup {
             }
 
             // https://validation.openmls.tech/#valn0601
-            self.validate_leaf_node(update_proposal.update_proposal().leaf_node())?;
+            self.validate_leaf_node(crypto, update_proposal.update_proposal().leaf_node())?;
         }
         Ok(())
     }
@@ -562,6 +570,13 @@ impl PublicGroup {
                             // If a group context extensions proposal updates the required capabilities, we
                             // need to check that these are satisfied for all existing members of the group.
                             self.check_extension_support(required_capabilities_new.extension_types()).map_err(|_| GroupContextExtensionsProposalValidationError::RequiredExtensionNotSupportedByAllMembers)?;
+                            // This includes the required proposal and credential types.
+                            self.check_required_capabilities_support(required_capabilities_new)
+                                .map_err(|e| match e {
+                                    LeafNodeValidationError::UnsupportedProposals => GroupContextExtensionsProposalValidationError::RequiredProposalNotSupportedByAllMembers,
+                                    LeafNodeValidationError::UnsupportedCredentials => GroupContextExtensionsProposalValidationError::RequiredCredentialNotSupportedByAllMembers,
+                                    _ => GroupContextExtensionsProposalValidationError::RequiredExtensionNotSupportedByAllMembers,
+                                })?;
                             required_capabilities_new
                         }
                         None => &default_required_capabilities,
@@ -581,6 +596,12 @@ impl PublicGroup {
                     if !all_extensions_are_in_required_capabilities {
                         return Err(GroupContextExtensionsProposalValidationError::ExtensionNotInRequiredCapabilities);
                     }
+
+                    self.extension_registry
+                        .validate(extensions.extensions())
+                        .map_err(
+                        GroupContextExtensionsProposalValidationError::InvalidApplicationExtension,
+                    )?;
                 }
                 _ => {
                     return Err(GroupContextExtensionsProposalValidationError::LibraryError(
@@ -644,6 +665,7 @@ impl PublicGroup {
 
     pub(crate) fn validate_leaf_node(
         &self,
+        crypto: &impl OpenMlsCrypto,
         leaf_node: &crate::treesync::LeafNode,
     ) -> Result<(), LeafNodeValidationError> {
         // https://validation.openmls.tech/#valn0103
@@ -653,6 +675,10 @@ impl PublicGroup {
 
         // https://validation.openmls.tech/#valn0105 is done when sending
 
+        self.extension_registry
+            .validate(leaf_node.extensions())
+            .map_err(LeafNodeValidationError::InvalidApplicationExtension)?;
+
         // https://validation.openmls.tech/#valn0106
         //
         // Only leaf nodes in key packages contain lifetimes, so this will return None for other
@@ -662,10 +688,12 @@ impl PublicGroup {
         // provide a way to turn off this check.
         if !crate::skip_validation::is_disabled::leaf_node_lifetime() {
             if let Some(lifetime) = leaf_node.life_time() {
-                if !lifetime.is_valid() {
-                    log::warn!("offending lifetime: {lifetime:?}");
-                    return Err(LeafNodeValidationError::Lifetime(LifetimeError::NotCurrent));
-                }
+                self.lifetime_policy
+                    .validate_with_time_provider(lifetime, time_provider(crypto))
+                    .map_err(|e| {
+                        log::warn!("offending lifetime: {lifetime:?}");
+                        LeafNodeValidationError::Lifetime(e)
+                    })?;
             }
         }
 
@@ -695,4 +723,18 @@ impl PublicGroup {
         }
         Ok(())
     }
+
+    /// Returns a [`LeafNodeValidationError`] if a leaf in this tree doesn't
+    /// support the extension, proposal or credential types in
+    /// `required_capabilities`.
+    pub(crate) fn check_required_capabilities_support(
+        &self,
+        required_capabilities: &RequiredCapabilitiesExtension,
+    ) -> Result<(), LeafNodeValidationError> {
+        for leaf in self.treesync().full_leaves() {
+            leaf.capabilities()
+                .supports_required_capabilities(required_capabilities)?;
+        }
+        Ok(())
+    }
 }
@@ -3,6 +3,9 @@
 //!
 //! See <https://github.com/mlswg/mls-implementations/blob/master/test-vectors.md>
 //! for more description on the test vectors.
+//!
+//! Test vectors of drafts before `MLSPlaintext` and `MLSCiphertext` were
+//! renamed to `PublicMessage` and `PrivateMessage` are skipped.
 
 use frankenstein::{FrankenFramedContentBody, FrankenPublicMessage};
 use openmls_traits::{random::OpenMlsRand, types::SignatureScheme, OpenMlsProvider};
@@ -25,7 +28,7 @@ use crate::{
     },
     prelude::{CredentialType, LeafNode},
     schedule::psk::*,
-    test_utils::*,
+    test_utils::{kat_format::*, *},
     treesync::node::{
         leaf_node::{Capabilities, TreeInfoTbs, TreePosition},
         NodeIn,
@@ -115,6 +118,16 @@ pub struct MessagesTestVector {
     private_message: Vec<u8>,
 }
 
+impl DraftTagged for MessagesTestVector {
+    fn detect_draft(vector: &serde_json::Value) -> KatDraft {
+        if vector.get("mls_ciphertext").is_some() || vector.get("mls_plaintext_commit").is_some() {
+            KatDraft::Draft(16)
+        } else {
+            KatDraft::Rfc9420
+        }
+    }
+}
+
 pub fn generate_test_vector(ciphersuite: Ciphersuite) -> MessagesTestVector {
     let provider = OpenMlsRustCrypto::default();
 
@@ -429,7 +442,6 @@ pub fn run_test_vector(tv: MessagesTestVector) -> Result<(), EncodingMismatch> {
         return Err(EncodingMismatch::AddProposal);
     }
 
-    //update_proposal: String,         /* serialized Update */
     // UpdateProposal
     let tv_update_proposal = tv.update_proposal;
     let my_update_proposal = UpdateProposalIn::tls_deserialize_exact(&tv_update_proposal)
@@ -445,7 +457,6 @@ pub fn run_test_vector(tv: MessagesTestVector) -> Result<(), EncodingMismatch> {
         }
         return Err(EncodingMismatch::UpdateProposal);
     }
-    //remove_proposal: String,         /* serialized Remove */
     // RemoveProposal
     let tv_remove_proposal = tv.remove_proposal;
     let my_remove_proposal = RemoveProposal::tls_deserialize_exact(&tv_remove_proposal)
@@ -479,7 +490,55 @@ pub fn run_test_vector(tv: MessagesTestVector) -> Result<(), EncodingMismatch> {
         return Err(EncodingMismatch::PreSharedKeyProposal);
     }
 
-    // Re-Init, External Init and App-Ack Proposals go here...
+    // ReInitProposal
+    let tv_re_init_proposal = tv.re_init_proposal;
+    let my_re_init_proposal = ReInitProposal::tls_deserialize_exact(&tv_re_init_proposal)
+        .unwrap()
+        .tls_serialize_detached()
+        .unwrap();
+    if tv_re_init_proposal != my_re_init_proposal {
+        log::error!("  ReInitProposal encoding mismatch");
+        log::debug!("    Encoded: {:x?}", my_re_init_proposal);
+        log::debug!("    Expected: {:x?}", tv_re_init_proposal);
+        if cfg!(test) {
+            panic!("ReInitProposal encoding mismatch");
+        }
+        return Err(EncodingMismatch::ReInitProposal);
+    }
+
+    // ExternalInitProposal
+    let tv_external_init_proposal = tv.external_init_proposal;
+    let my_external_init_proposal =
+        ExternalInitProposal::tls_deserialize_exact(&tv_external_init_proposal)
+            .unwrap()
+            .tls_serialize_detached()
+            .unwrap();
+    if tv_external_init_proposal != my_external_init_proposal {
+        log::error!("  ExternalInitProposal encoding mismatch");
+        log::debug!("    Encoded: {:x?}", my_external_init_proposal);
+        log::debug!("    Expected: {:x?}", tv_external_init_proposal);
+        if cfg!(test) {
+            panic!("ExternalInitProposal encoding mismatch");
+        }
+        return Err(EncodingMismatch::ExternalInitProposal);
+    }
+
+    // GroupContextExtensionProposal
+    let tv_group_context_extensions_proposal = tv.group_context_extensions_proposal;
+    let my_group_context_extensions_proposal =
+        GroupContextExtensionProposal::tls_deserialize_exact(&tv_group_context_extensions_proposal)
+            .unwrap()
+            .tls_serialize_detached()
+            .unwrap();
+    if tv_group_context_extensions_proposal != my_group_context_extensions_proposal {
+        log::error!("  GroupContextExtensionProposal encoding mismatch");
+        log::debug!("    Encoded: {:x?}", my_group_context_extensions_proposal);
+        log::debug!("    Expected: {:x?}", tv_group_context_extensions_proposal);
+        if cfg!(test) {
+            panic!("GroupContextExtensionProposal encoding mismatch");
+        }
+        return Err(EncodingMismatch::GroupContextExtensionProposal);
+    }
 
     // Commit
     let tv_commit = tv.commit;
@@ -566,11 +625,26 @@ pub fn run_test_vector(tv: MessagesTestVector) -> Result<(), EncodingMismatch> {
     Ok(())
 }
 
+#[test]
+fn generated_test_vectors_messages() {
+    use openmls_traits::crypto::OpenMlsCrypto;
+
+    let provider = OpenMlsRustCrypto::default();
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        let test_vector = generate_test_vector(ciphersuite);
+        let test_vector: MessagesTestVector =
+            serde_json::from_value(serde_json::to_value(test_vector).unwrap()).unwrap();
+        run_test_vector(test_vector).expect("Error while checking messages test vector.");
+    }
+}
+
 #[test]
 fn read_test_vectors_messages() {
-    let tests: Vec<MessagesTestVector> = read_json!("../../../../test_vectors/messages.json");
+    let tests: TaggedVectors<MessagesTestVector> =
+        read_json_tagged!("../../../../test_vectors/messages.json");
+    assert!(tests.skipped.is_empty());
 
-    for test_vector in tests {
+    for test_vector in tests.into_vectors() {
         match run_test_vector(test_vector) {
             Ok(_) => {}
             Err(e) => panic!("Error while checking messages test vector.\n{e:?}"),
@@ -608,6 +682,15 @@ pub enum EncodingMismatch {
     /// PreSharedKeyProposal encodings don't match.
     #[error("PreSharedKeyProposal encodings don't match.")]
     PreSharedKeyProposal,
+    /// ReInitProposal encodings don't match.
+    #[error("ReInitProposal encodings don't match.")]
+    ReInitProposal,
+    /// ExternalInitProposal encodings don't match.
+    #[error("ExternalInitProposal encodings don't match.")]
+    ExternalInitProposal,
+    /// GroupContextExtensionProposal encodings don't match.
+    #[error("GroupContextExtensionProposal encodings don't match.")]
+    GroupContextExtensionProposal,
     /// RemoveProposal encodings don't match.
     #[error("RemoveProposal encodings don't match.")]
     RemoveProposal,
@@ -1,4 +1,6 @@
 // Import necessary modules and dependencies
+use openmls_traits::OpenMlsProvider as _;
+
 use crate::{
     binary_tree::LeafNodeIndex,
     framing::*,
@@ -189,3 +191,115 @@ fn test_add_member_with_aad(
         assert_eq!(bob_processed_message.aad(), &aad);
     }
 }
+
+// Tests that a group requiring AAD rejects outgoing and incoming application
+// messages without AAD.
+#[openmls_test::openmls_test]
+fn test_require_aad() {
+    let alice_credential_with_key_and_signer =
+        generate_credential_with_key("Alice".into(), ciphersuite.signature_algorithm(), provider);
+    let bob_credential_with_key_and_signer =
+        generate_credential_with_key("Bob".into(), ciphersuite.signature_algorithm(), provider);
+    let bob_key_package = generate_key_package(
+        ciphersuite,
+        Extensions::empty(),
+        provider,
+        bob_credential_with_key_and_signer.clone(),
+    );
+
+    // Only Bob requires AAD, so that Alice can send messages without it.
+    let alice_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+    let bob_join_config = MlsGroupJoinConfig::builder().require_aad(true).build();
+    assert!(bob_join_config.require_aad());
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_credential_with_key_and_signer.signer,
+        &alice_config,
+        alice_credential_with_key_and_signer
+            .credential_with_key
+            .clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_message, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .expect("An unexpected error occurred.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome: MlsMessageIn = welcome.into();
+    let welcome = welcome
+        .into_welcome()
+        .expect("expected message to be a welcome");
+
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        &bob_join_config,
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("Error creating staged join from Welcome")
+    .into_group(provider)
+    .expect("Error creating group from staged join");
+
+    // Bob can't send messages without AAD.
+    let err = bob_group
+        .create_message(
+            provider,
+            &bob_credential_with_key_and_signer.signer,
+            b"Hello, World!",
+        )
+        .expect_err("Created a message without AAD.");
+    assert_eq!(err, CreateMessageError::MissingAad);
+
+    // Bob rejects messages from Alice without AAD ...
+    let message: MlsMessageIn = alice_group
+        .create_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            b"Hello, World!",
+        )
+        .expect("Error creating message")
+        .into();
+    let err = bob_group
+        .process_message(provider, message.clone().into_protocol_message().unwrap())
+        .expect_err("Processed a message without AAD.");
+    assert_eq!(err, ProcessMessageError::MissingAad);
+
+    // ... before decrypting them, such that the message can still be processed
+    // once AAD is no longer required.
+    bob_group
+        .set_configuration(provider.storage(), &MlsGroupJoinConfig::default())
+        .expect("Error setting the configuration");
+    bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("Error processing message");
+    bob_group
+        .set_configuration(provider.storage(), &bob_join_config)
+        .expect("Error setting the configuration");
+
+    // Bob accepts messages with AAD.
+    let aad = b"routing metadata".to_vec();
+    let message: MlsMessageIn = alice_group
+        .create_message_with_aad(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            b"Hello, World!",
+            aad.clone(),
+        )
+        .expect("Error creating message")
+        .into();
+    assert!(alice_group.aad().is_empty());
+    let processed_message = bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("Error processing message");
+    assert_eq!(processed_message.aad(), &aad);
+}
@@ -0,0 +1,293 @@
+use crate::{
+    binary_tree::LeafNodeIndex,
+    framing::*,
+    group::{
+        tests_and_kats::utils::{generate_credential_with_key, generate_key_package},
+        *,
+    },
+};
+
+// Tests that the maximum application message size is enforced and that large
+// messages can be sent in chunks and reassembled.
+#[openmls_test::openmls_test]
+fn application_message_chunking() {
+    let alice_credential_with_key_and_signer =
+        generate_credential_with_key("Alice".into(), ciphersuite.signature_algorithm(), provider);
+    let bob_credential_with_key_and_signer =
+        generate_credential_with_key("Bob".into(), ciphersuite.signature_algorithm(), provider);
+    let bob_key_package = generate_key_package(
+        ciphersuite,
+        Extensions::empty(),
+        provider,
+        bob_credential_with_key_and_signer,
+    );
+
+    let max_application_message_size = 100;
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .max_application_message_size(max_application_message_size)
+        .build();
+    assert_eq!(
+        mls_group_create_config.max_application_message_size(),
+        Some(max_application_message_size)
+    );
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_credential_with_key_and_signer.signer,
+        &mls_group_create_config,
+        alice_credential_with_key_and_signer
+            .credential_with_key
+            .clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_message, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .expect("An unexpected error occurred.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome: MlsMessageIn = welcome.into();
+    let welcome = welcome
+        .into_welcome()
+        .expect("expected message to be a welcome");
+
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("Error creating staged join from Welcome")
+    .into_group(provider)
+    .expect("Error creating group from staged join");
+
+    // A message that exceeds the limit is rejected ...
+    let message: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    let err = alice_group
+        .create_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message,
+        )
+        .expect_err("Message exceeding the maximum size was accepted.");
+    assert_eq!(err, CreateMessageError::MessageTooLarge);
+
+    // ... while a message at the limit is accepted.
+    alice_group
+        .create_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message[..max_application_message_size],
+        )
+        .expect("Error creating message");
+
+    // Invalid chunk sizes are rejected.
+    let err = alice_group
+        .create_chunked_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message,
+            0,
+        )
+        .expect_err("A chunk size of zero was accepted.");
+    assert_eq!(err, CreateMessageError::InvalidChunkSize);
+    let err = alice_group
+        .create_chunked_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message,
+            max_application_message_size,
+        )
+        .expect_err("Chunks without room for the framing were accepted.");
+    assert_eq!(err, CreateMessageError::MessageTooLarge);
+
+    // The message is sent in chunks that fit the limit.
+    let chunks = alice_group
+        .create_chunked_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message,
+            max_application_message_size - 16,
+        )
+        .expect("Error creating chunked message");
+    assert_eq!(
+        chunks.len(),
+        message.len().div_ceil(max_application_message_size - 16)
+    );
+
+    let mut reassembler = ChunkReassembler::new(message.len());
+    let mut reassembled = None;
+    // Swap the first two chunks to check that the order does not matter.
+    let mut chunks = chunks;
+    chunks.swap(0, 1);
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let chunk: MlsMessageIn = chunk.into();
+        let processed_message = bob_group
+            .process_message(provider, chunk.into_protocol_message().unwrap())
+            .expect("Error processing message");
+        let Sender::Member(sender) = *processed_message.sender() else {
+            panic!("Expected a member sender");
+        };
+        let ProcessedMessageContent::ApplicationMessage(application_message) =
+            processed_message.into_content()
+        else {
+            panic!("Expected an application message");
+        };
+        let application_data = application_message.into_bytes();
+
+        assert!(reassembled.is_none(), "Message was reassembled too early");
+        reassembled = reassembler
+            .process_chunk(sender, &application_data)
+            .expect("Error processing chunk");
+        if reassembled.is_none() {
+            assert_eq!(reassembler.incomplete_messages(), 1);
+
+            // Processing the same chunk again is an error and discards the
+            // message.
+            if i == 0 {
+                let mut other_reassembler = ChunkReassembler::new(message.len());
+                other_reassembler
+                    .process_chunk(sender, &application_data)
+                    .unwrap();
+                assert_eq!(
+                    other_reassembler.process_chunk(sender, &application_data),
+                    Err(ChunkReassemblyError::DuplicateChunk)
+                );
+                assert_eq!(other_reassembler.incomplete_messages(), 0);
+            }
+        }
+    }
+    assert_eq!(reassembled, Some(message.clone()));
+    assert_eq!(reassembler.incomplete_messages(), 0);
+
+    // Messages that exceed the size of the reassembler are rejected.
+    let chunks = alice_group
+        .create_chunked_message(
+            provider,
+            &alice_credential_with_key_and_signer.signer,
+            &message,
+            max_application_message_size - 16,
+        )
+        .expect("Error creating chunked message");
+    let mut reassembler = ChunkReassembler::new(message.len() - 1);
+    let mut result = Ok(None);
+    for chunk in chunks {
+        let chunk: MlsMessageIn = chunk.into();
+        let processed_message = bob_group
+            .process_message(provider, chunk.into_protocol_message().unwrap())
+            .expect("Error processing message");
+        let ProcessedMessageContent::ApplicationMessage(application_message) =
+            processed_message.into_content()
+        else {
+            panic!("Expected an application message");
+        };
+        result = reassembler.process_chunk(
+            alice_group.own_leaf_index(),
+            &application_message.into_bytes(),
+        );
+        if result.is_err() {
+            break;
+        }
+    }
+    assert_eq!(result, Err(ChunkReassemblyError::MessageTooLarge));
+    assert_eq!(reassembler.incomplete_messages(), 0);
+
+    // Data that isn't a chunk is rejected.
+    assert_eq!(
+        reassembler.process_chunk(alice_group.own_leaf_index(), b"not a chunk"),
+        Err(ChunkReassemblyError::MalformedChunk)
+    );
+}
+
+fn chunk(message_id: u64, index: u16, count: u16, data: &[u8]) -> Vec<u8> {
+    use tls_codec::Serialize as _;
+
+    crate::group::mls_group::chunking::ApplicationChunk {
+        message_id,
+        index,
+        count,
+        data: data.into(),
+    }
+    .tls_serialize_detached()
+    .unwrap()
+}
+
+// Tests that the number of incomplete messages is bounded and that the oldest
+// incomplete messages are evicted first.
+#[test]
+fn chunk_reassembler_max_pending_messages() {
+    let sender = LeafNodeIndex::new(0);
+    let mut reassembler = ChunkReassembler::new(100).with_max_pending_messages(2);
+
+    for message_id in 0..3 {
+        assert_eq!(
+            reassembler.process_chunk(sender, &chunk(message_id, 0, 2, b"first")),
+            Ok(None)
+        );
+    }
+    assert_eq!(reassembler.incomplete_messages(), 2);
+    assert_eq!(reassembler.pending_bytes(), 10);
+
+    // The first message was evicted, the chunk starts a new message and
+    // evicts the second one.
+    assert_eq!(
+        reassembler.process_chunk(sender, &chunk(0, 1, 2, b"second")),
+        Ok(None)
+    );
+    assert_eq!(reassembler.incomplete_messages(), 2);
+
+    // The third message is still complete.
+    assert_eq!(
+        reassembler.process_chunk(sender, &chunk(2, 1, 2, b"second")),
+        Ok(Some(b"firstsecond".to_vec()))
+    );
+    assert_eq!(reassembler.incomplete_messages(), 1);
+    assert_eq!(reassembler.pending_bytes(), 6);
+}
+
+// Tests that the bytes of incomplete messages are bounded and that the oldest
+// incomplete messages are evicted first.
+#[test]
+fn chunk_reassembler_max_pending_bytes() {
+    let alice = LeafNodeIndex::new(0);
+    let bob = LeafNodeIndex::new(1);
+    let mut reassembler = ChunkReassembler::new(100).with_max_pending_bytes(12);
+
+    assert_eq!(
+        reassembler.process_chunk(alice, &chunk(0, 0, 2, b"alice")),
+        Ok(None)
+    );
+    assert_eq!(
+        reassembler.process_chunk(bob, &chunk(0, 0, 3, b"bob")),
+        Ok(None)
+    );
+    assert_eq!(reassembler.pending_bytes(), 8);
+
+    // The chunk exceeds the bound and evicts the message of Alice.
+    assert_eq!(
+        reassembler.process_chunk(bob, &chunk(0, 1, 3, b"chunk")),
+        Ok(None)
+    );
+    assert_eq!(reassembler.incomplete_messages(), 1);
+    assert_eq!(reassembler.pending_bytes(), 8);
+    assert_eq!(
+        reassembler.process_chunk(bob, &chunk(0, 2, 3, b"-")),
+        Ok(Some(b"bobchunk-".to_vec()))
+    );
+    assert_eq!(reassembler.pending_bytes(), 0);
+
+    // A message that exceeds the bound on its own is rejected.
+    assert_eq!(
+        reassembler.process_chunk(alice, &chunk(1, 0, 2, b"a long message")),
+        Err(ChunkReassemblyError::MessageTooLarge)
+    );
+    assert_eq!(reassembler.incomplete_messages(), 0);
+    assert_eq!(reassembler.pending_bytes(), 0);
+}
@@ -534,7 +534,7 @@ fn test_valsem246() {
     let decrypted_message = DecryptedMessage::from_inbound_public_message(
         public_message_commit.clone().into(),
         alice_group.message_secrets(),
-        alice_group.message_secrets().serialized_context().to_vec(),
+        alice_group.message_secrets().shared_serialized_context(),
         provider.crypto(),
         ciphersuite,
     )
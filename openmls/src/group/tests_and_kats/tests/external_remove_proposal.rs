@@ -321,6 +321,105 @@ fn external_remove_proposal_should_fail_when_invalid_signature() {
     ));
 }
 
+#[openmls_test]
+fn external_remove_proposal_should_fail_when_impersonating_other_external_sender() {
+    // Two delivery services are allowed external senders of the group
+    let ds_credential_with_key = generate_credential_with_key(
+        "delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let other_ds_credential_with_key = generate_credential_with_key(
+        "other-delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+
+    let (mut alice_group, _alice_credential) = validation_test_setup(
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        ciphersuite,
+        provider,
+        vec![
+            ExternalSender::new(
+                ds_credential_with_key
+                    .credential_with_key
+                    .signature_key
+                    .clone(),
+                ds_credential_with_key
+                    .credential_with_key
+                    .credential
+                    .clone(),
+            ),
+            ExternalSender::new(
+                other_ds_credential_with_key
+                    .credential_with_key
+                    .signature_key
+                    .clone(),
+                other_ds_credential_with_key
+                    .credential_with_key
+                    .credential
+                    .clone(),
+            ),
+        ],
+    );
+
+    // get Bob's index
+    let bob_index = alice_group
+        .members()
+        .find(|member| member.credential.serialized_content() == b"Bob")
+        .map(|member| member.index)
+        .unwrap();
+
+    // The second delivery service signs the proposal, but claims to be the
+    // first one.
+    let forged_remove_proposal: MlsMessageIn = ExternalProposal::new_remove::<Provider>(
+        bob_index,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &other_ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+
+    let error = alice_group
+        .process_message(
+            provider,
+            forged_remove_proposal.try_into_protocol_message().unwrap(),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ProcessMessageError::ValidationError(ValidationError::InvalidSignature)
+    ));
+
+    // Using its own index, the proposal is accepted.
+    let remove_proposal: MlsMessageIn = ExternalProposal::new_remove::<Provider>(
+        bob_index,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &other_ds_credential_with_key.signer,
+        SenderExtensionIndex::new(1),
+    )
+    .unwrap()
+    .into();
+
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            remove_proposal.try_into_protocol_message().unwrap(),
+        )
+        .unwrap();
+    assert!(matches!(
+        processed_message.sender(),
+        Sender::External(index) if index.index() == 1
+    ));
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+}
+
 #[openmls_test]
 fn external_remove_proposal_should_fail_when_no_external_senders() {
     let (mut alice_group, _) = validation_test_setup(
@@ -18,6 +18,7 @@ use crate::{
         secret_tree::SecretTree, secret_tree::SecretType,
         sender_ratchet::SenderRatchetConfiguration,
     },
+    treesync::LeafNodeParameters,
 };
 
 #[openmls_test::openmls_test]
@@ -97,6 +98,63 @@ fn padding(provider: &impl crate::storage::OpenMlsProvider) {
     }
 }
 
+/// Check that encrypted handshake messages are padded to the configured block
+/// size as well.
+#[openmls_test::openmls_test]
+fn handshake_padding() {
+    let alice_credential_with_keys = generate_credential_with_key(
+        b"Alice".to_vec(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+
+    for padding_size in [1, 2, 16, 33, 64, 100, 255] {
+        let mut alice_group = MlsGroup::builder()
+            .ciphersuite(ciphersuite)
+            .with_wire_format_policy(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY)
+            .padding_size(padding_size)
+            .build(
+                provider,
+                &alice_credential_with_keys.signer,
+                alice_credential_with_keys.credential_with_key.clone(),
+            )
+            .expect("An unexpected error occurred.");
+
+        let (proposal, _proposal_ref) = alice_group
+            .propose_self_update(
+                provider,
+                &alice_credential_with_keys.signer,
+                LeafNodeParameters::default(),
+            )
+            .expect("An unexpected error occurred.");
+        alice_group
+            .clear_pending_proposals(provider.storage())
+            .expect("An unexpected error occurred.");
+        let (commit, _welcome, _group_info) = alice_group
+            .self_update(
+                provider,
+                &alice_credential_with_keys.signer,
+                LeafNodeParameters::default(),
+            )
+            .expect("An unexpected error occurred.")
+            .into_contents();
+
+        for message in [proposal, commit] {
+            let private_message = match message.body() {
+                MlsMessageBodyOut::PrivateMessage(pm) => pm,
+                _ => panic!("Handshake message was not encrypted."),
+            };
+            assert!(private_message.is_handshake_message());
+            let length = private_message.ciphertext().len();
+            assert_eq!(
+                length % padding_size,
+                0,
+                "message length: {length}, padding block size: {padding_size}"
+            );
+        }
+    }
+}
+
 /// Check that PrivateMessageContent's padding field is verified to be all-zero.
 #[openmls_test::openmls_test]
 fn bad_padding() {
@@ -193,7 +251,7 @@ fn bad_padding() {
 
             let private_message_content_aad_bytes = {
                 let private_message_content_aad = PrivateContentAad {
-                    group_id: group_id.clone(),
+                    group_id: &group_id,
                     epoch,
                     content_type: plaintext.content().content_type(),
                     authenticated_data: VLByteSlice(plaintext.authenticated_data()),
@@ -289,11 +347,8 @@ fn bad_padding() {
             // Compute sender data nonce by xoring reuse guard and key schedule
             // nonce as per spec.
 
-            let mls_sender_data_aad = MlsSenderDataAad::test_new(
-                group_id.clone(),
-                epoch,
-                plaintext.content().content_type(),
-            );
+            let mls_sender_data_aad =
+                MlsSenderDataAad::test_new(&group_id, epoch, plaintext.content().content_type());
             // Serialize the sender data AAD
             let mls_sender_data_aad_bytes = mls_sender_data_aad.tls_serialize_detached().unwrap();
             let sender_data = MlsSenderData::from_sender(leaf_index, generation, reuse_guard);
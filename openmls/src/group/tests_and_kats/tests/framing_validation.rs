@@ -666,3 +666,52 @@ fn test_valsem010() {
         .process_message(provider, ProtocolMessage::from(original_message))
         .expect("Unexpected error.");
 }
+
+// Replayed application messages are rejected with a dedicated error.
+#[openmls_test::openmls_test]
+fn test_replayed_ciphertext() {
+    let ValidationTestSetup {
+        mut alice_group,
+        mut bob_group,
+        _alice_credential,
+        _bob_credential: _,
+        _alice_key_package: _,
+        _bob_key_package: _,
+    } = validation_test_setup(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY, ciphersuite, provider);
+
+    let message = alice_group
+        .create_message(provider, &_alice_credential.signer, &[1, 2, 3])
+        .expect("An unexpected error occurred.");
+
+    let serialized_message = message
+        .tls_serialize_detached()
+        .expect("Could not serialize message.");
+
+    let ciphertext = MlsMessageIn::tls_deserialize(&mut serialized_message.as_slice())
+        .expect("Could not deserialize message.")
+        .into_ciphertext()
+        .expect("Message was not a ciphertext.");
+
+    bob_group
+        .process_message(provider, ProtocolMessage::from(ciphertext.clone()))
+        .expect("Unexpected error.");
+
+    let err = bob_group
+        .process_message(provider, ProtocolMessage::from(ciphertext))
+        .expect_err("Could process replayed message.");
+
+    assert!(matches!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+            MessageDecryptionError::ReplayedMessage
+        ))
+    ));
+
+    // Later messages can still be processed.
+    let message = alice_group
+        .create_message(provider, &_alice_credential.signer, &[4, 5, 6])
+        .expect("An unexpected error occurred.");
+    bob_group
+        .process_message(provider, message.into_protocol_message().unwrap())
+        .expect("Unexpected error.");
+}
@@ -7,11 +7,11 @@ use tls_codec::{Deserialize as _, Serialize as _};
 
 use crate::{
     ciphersuite::hash_ref::ProposalRef,
-    credentials::CredentialWithKey,
+    credentials::{CredentialType, CredentialWithKey},
     framing::*,
     group::*,
     key_packages::{errors::KeyPackageVerifyError, *},
-    messages::group_info::GroupInfo,
+    messages::{group_info::GroupInfo, proposals::ProposalType},
     test_utils::frankenstein::{self, FrankenMlsMessage},
     treesync::{
         errors::LeafNodeValidationError, node::leaf_node::Capabilities, LeafNodeParameters,
@@ -237,7 +237,10 @@ impl<Provider: crate::storage::OpenMlsProvider> MemberState<Provider> {
     }
 
     /// This wrapper that expects [`MlsGroup::process_message`] to return an error.
-    fn fail_processing(&mut self, msg: MlsMessageIn) -> ProcessMessageError {
+    fn fail_processing(
+        &mut self,
+        msg: MlsMessageIn,
+    ) -> ProcessMessageError<Provider::StorageError> {
         let msg = msg.into_protocol_message().unwrap();
         let err_msg = format!(
             "expected an error when processing message at {}",
@@ -1135,3 +1138,64 @@ fn proposal() {
         )
         .expect_err("expected an error building GCE proposal with bad required_capabilities");
 }
+
+// Test that the proposal and credential types in new required capabilities
+// must be supported by all members, not only the extension types.
+#[openmls_test]
+fn fail_unsupported_required_proposal_and_credential_types_valn1001() {
+    let alice_party = PartyState::<Provider>::generate("alice", ciphersuite);
+    let bob_party = PartyState::<Provider>::generate("bob", ciphersuite);
+
+    let alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .with_capabilities(
+            Capabilities::builder()
+                .proposals(vec![ProposalType::Custom(0xf0f0)])
+                .credentials(vec![CredentialType::Basic, CredentialType::X509])
+                .build(),
+        )
+        .build(
+            &alice_party.provider,
+            &alice_party.signer,
+            alice_party.credential_with_key.clone(),
+        )
+        .expect("error creating group using builder");
+    let mut alice = MemberState {
+        party: alice_party,
+        group: alice_group,
+    };
+
+    // Bob supports neither the custom proposal type nor X.509 credentials.
+    let bob_key_package = bob_party.key_package(ciphersuite, |builder| builder);
+    alice.propose_add_member(bob_key_package.key_package());
+    alice.commit_and_merge_pending();
+
+    for (required_capabilities, expected) in [
+        (
+            RequiredCapabilitiesExtension::new(&[], &[ProposalType::Custom(0xf0f0)], &[]),
+            LeafNodeValidationError::UnsupportedProposals,
+        ),
+        (
+            RequiredCapabilitiesExtension::new(&[], &[], &[CredentialType::X509]),
+            LeafNodeValidationError::UnsupportedCredentials,
+        ),
+    ] {
+        let err = alice
+            .group
+            .propose_group_context_extensions(
+                &alice.party.provider,
+                Extensions::single(Extension::RequiredCapabilities(required_capabilities)),
+                &alice.party.signer,
+            )
+            .expect_err("expected an error building GCE proposal with bad required_capabilities");
+        assert!(
+            matches!(
+                err,
+                ProposalError::CreateGroupContextExtProposalError(
+                    CreateGroupContextExtProposalError::LeafNodeValidation(ref e)
+                ) if *e == expected
+            ),
+            "expected different error. got {err:?}"
+        );
+    }
+}
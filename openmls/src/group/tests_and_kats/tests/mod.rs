@@ -1,6 +1,7 @@
 //! Unit tests for the core group
 
 mod aad;
+mod chunking;
 mod commit_validation;
 mod encoding;
 mod external_add_proposal;
@@ -21,15 +21,16 @@ use crate::{
     ciphersuite::hash_ref::ProposalRef,
     credentials::*,
     framing::{
-        mls_content::FramedContentBody, validation::ProcessedMessageContent, AuthenticatedContent,
-        FramedContent, MlsMessageIn, MlsMessageOut, ProtocolMessage, PublicMessage, Sender,
+        errors::MessageDecryptionError, mls_content::FramedContentBody,
+        validation::ProcessedMessageContent, AuthenticatedContent, FramedContent, MlsMessageIn,
+        MlsMessageOut, PrivateMessageIn, ProtocolMessage, PublicMessage, Sender,
     },
     group::*,
     key_packages::{errors::*, *},
     messages::{
         proposals::{
-            AddProposal, CustomProposal, Proposal, ProposalOrRef, ProposalType, RemoveProposal,
-            UpdateProposal,
+            AddProposal, CustomProposal, Proposal, ProposalOrRef, ProposalType, ReInitProposal,
+            RemoveProposal, UpdateProposal,
         },
         Commit, Welcome,
     },
@@ -2285,7 +2286,7 @@ fn test_valsem401_valsem402() {
     let bob_provider = Provider::default();
 
     // TODO(#1354): This is currently not tested because we can't easily create invalid commits.
-    let bad_psks: [(Vec<PreSharedKeyId>, ProcessMessageError); 0] = [
+    let bad_psks: [(Vec<PreSharedKeyId>, ProcessMessageError<_>); 0] = [
         // // ValSem401
         // (
         //     vec![PreSharedKeyId::external(
@@ -2420,3 +2421,250 @@ fn test_valsem401_valsem402() {
         bob_group.clear_pending_commit(provider.storage()).unwrap();
     }
 }
+
+/// ReInit proposals to ciphersuites that are not in the `reinit_ciphersuites`
+/// of the group configuration are rejected.
+#[openmls_test::openmls_test]
+fn reinit_ciphersuite_policy() {
+    let ProposalValidationTestSetup {
+        mut alice_group,
+        mut bob_group,
+        bob_credential_with_key_and_signer,
+        ..
+    } = validation_test_setup(PURE_PLAINTEXT_WIRE_FORMAT_POLICY, ciphersuite, provider);
+
+    let other_ciphersuite =
+        if ciphersuite == Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519 {
+            Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256
+        } else {
+            Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519
+        };
+
+    // Bob proposes to re-initialize the group with another ciphersuite. There
+    // is no API for ReInit proposals, so an Update proposal is turned into one.
+    let (update_proposal, _) = bob_group
+        .propose_self_update(
+            provider,
+            &bob_credential_with_key_and_signer.signer,
+            LeafNodeParameters::default(),
+        )
+        .expect("error while creating update proposal");
+    let franken_message = FrankenMlsMessage::from(update_proposal);
+    let FrankenMlsMessageBody::PublicMessage(public_message) = franken_message.body else {
+        panic!("Unexpected message type");
+    };
+    let mut content = public_message.content;
+    content.body =
+        FrankenFramedContentBody::Proposal(FrankenProposal::ReInit(FrankenReInitProposal {
+            group_id: bob_group.group_id().as_slice().to_vec().into(),
+            version: 1,
+            ciphersuite: other_ciphersuite.into(),
+            extensions: vec![],
+        }));
+    let reinit_proposal = FrankenPublicMessage::auth(
+        provider,
+        ciphersuite,
+        &bob_credential_with_key_and_signer.signer,
+        content,
+        Some(&bob_group.export_group_context().clone().into()),
+        Some(bob_group.message_secrets().membership_key().as_slice()),
+        None,
+    );
+
+    // Alice only approves the current ciphersuite.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .reinit_ciphersuites(vec![ciphersuite])
+        .build();
+    alice_group
+        .set_configuration(provider.storage(), &join_config)
+        .unwrap();
+    let err = alice_group
+        .process_message(
+            provider,
+            ProtocolMessage::from(PublicMessage::from(reinit_proposal.clone())),
+        )
+        .expect_err("processed a ReInit proposal to an unapproved ciphersuite");
+    assert_eq!(
+        err,
+        ProcessMessageError::UnapprovedReInitCiphersuite(other_ciphersuite)
+    );
+
+    // Once the other ciphersuite is approved, the proposal is accepted.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .reinit_ciphersuites(vec![ciphersuite, other_ciphersuite])
+        .build();
+    alice_group
+        .set_configuration(provider.storage(), &join_config)
+        .unwrap();
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            ProtocolMessage::from(PublicMessage::from(reinit_proposal)),
+        )
+        .expect("error processing ReInit proposal");
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+}
+
+/// A ReInit proposal in a private message that the policy rejects consumes
+/// the secrets to decrypt it all the same.
+#[openmls_test::openmls_test]
+fn reinit_ciphersuite_policy_private_message() {
+    let ProposalValidationTestSetup {
+        mut alice_group,
+        mut bob_group,
+        bob_credential_with_key_and_signer,
+        ..
+    } = validation_test_setup(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY, ciphersuite, provider);
+
+    let other_ciphersuite =
+        if ciphersuite == Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519 {
+            Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256
+        } else {
+            Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519
+        };
+
+    // Bob proposes to re-initialize the group with another ciphersuite.
+    let reinit_proposal = Proposal::ReInit(ReInitProposal {
+        group_id: bob_group.group_id().clone(),
+        version: ProtocolVersion::default(),
+        ciphersuite: other_ciphersuite,
+        extensions: Extensions::empty(),
+    });
+    let content = AuthenticatedContent::member_proposal(
+        bob_group.framing_parameters(),
+        bob_group.own_leaf_index(),
+        reinit_proposal,
+        bob_group.context(),
+        &bob_credential_with_key_and_signer.signer,
+    )
+    .expect("error creating ReInit proposal");
+    let private_message = bob_group
+        .encrypt(content, provider)
+        .expect("error encrypting ReInit proposal");
+    let message = ProtocolMessage::from(PrivateMessageIn::from(private_message));
+
+    // Alice only approves the current ciphersuite.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY)
+        .reinit_ciphersuites(vec![ciphersuite])
+        .build();
+    alice_group
+        .set_configuration(provider.storage(), &join_config)
+        .unwrap();
+    let err = alice_group
+        .process_message(provider, message.clone())
+        .expect_err("processed a ReInit proposal to an unapproved ciphersuite");
+    assert_eq!(
+        err,
+        ProcessMessageError::UnapprovedReInitCiphersuite(other_ciphersuite)
+    );
+
+    // Once the other ciphersuite is approved, the rejected proposal still
+    // can't be decrypted again.
+    let join_config = MlsGroupJoinConfig::builder()
+        .wire_format_policy(PURE_CIPHERTEXT_WIRE_FORMAT_POLICY)
+        .reinit_ciphersuites(vec![ciphersuite, other_ciphersuite])
+        .build();
+    alice_group
+        .set_configuration(provider.storage(), &join_config)
+        .unwrap();
+    let err = alice_group
+        .process_message(provider, message)
+        .expect_err("the rejected ReInit proposal was decrypted again");
+    assert_eq!(
+        err,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+            MessageDecryptionError::ReplayedMessage
+        ))
+    );
+}
+
+/// A commit that covers the same proposal more than once is rejected, both if
+/// the proposal is inline and if it is a reference (RFC 9420, Section 12.2).
+#[openmls_test::openmls_test]
+fn duplicate_proposals() {
+    for by_reference in [false, true] {
+        let ProposalValidationTestSetup {
+            mut alice_group,
+            alice_credential_with_key_and_signer,
+            mut bob_group,
+            ..
+        } = validation_test_setup(PURE_PLAINTEXT_WIRE_FORMAT_POLICY, ciphersuite, provider);
+        let (_charlie_credential_with_key, charlie_key_package) =
+            generate_credential_with_key_and_key_package("Charlie".into(), ciphersuite, provider);
+
+        let commit = if by_reference {
+            let (proposal, _proposal_ref) = alice_group
+                .propose_add_member(
+                    provider,
+                    &alice_credential_with_key_and_signer.signer,
+                    charlie_key_package.key_package(),
+                )
+                .unwrap();
+            let processed_message = bob_group
+                .process_message(provider, proposal.into_protocol_message().unwrap())
+                .unwrap();
+            let ProcessedMessageContent::ProposalMessage(queued_proposal) =
+                processed_message.into_content()
+            else {
+                panic!("Expected a proposal.");
+            };
+            bob_group
+                .store_pending_proposal(provider.storage(), *queued_proposal)
+                .unwrap();
+            alice_group
+                .commit_to_pending_proposals(provider, &alice_credential_with_key_and_signer.signer)
+                .unwrap()
+                .0
+        } else {
+            alice_group
+                .add_members(
+                    provider,
+                    &alice_credential_with_key_and_signer.signer,
+                    &[charlie_key_package.key_package().clone()],
+                )
+                .unwrap()
+                .0
+        };
+        let plaintext = MlsMessageIn::from(commit)
+            .into_plaintext()
+            .expect("Message was not a plaintext.");
+        let original_plaintext = plaintext.clone();
+
+        let FramedContentBody::Commit(commit_content) = plaintext.content() else {
+            panic!("Unexpected content type.");
+        };
+        let proposal_or_ref = commit_content.proposals[0].clone();
+        assert_eq!(
+            matches!(proposal_or_ref, ProposalOrRef::Reference(_)),
+            by_reference
+        );
+        let duplicate_commit = insert_proposal_and_resign(
+            provider,
+            ciphersuite,
+            vec![proposal_or_ref],
+            plaintext,
+            &original_plaintext,
+            &alice_group,
+            &alice_credential_with_key_and_signer.signer,
+        );
+
+        let err = bob_group
+            .process_message(provider, ProtocolMessage::from(duplicate_commit))
+            .expect_err("Processed a commit with a duplicate proposal.");
+        assert_eq!(
+            err,
+            ProcessMessageError::InvalidCommit(StageCommitError::DuplicateProposal)
+        );
+
+        // Positive case
+        bob_group
+            .process_message(provider, ProtocolMessage::from(original_plaintext))
+            .expect("Unexpected error.");
+    }
+}
@@ -2,7 +2,11 @@
 
 use openmls_traits::{signatures::Signer, types::Ciphersuite};
 
-use crate::{framing::*, group::*, treesync::LeafNodeParameters};
+use crate::{
+    framing::*,
+    group::{errors::WireFormatPolicyError, *},
+    treesync::LeafNodeParameters,
+};
 
 use crate::group::tests_and_kats::utils::{
     generate_credential_with_key, generate_key_package, CredentialWithKeyAndSigner,
@@ -149,3 +153,30 @@ fn test_wire_policy_negative(
         assert!(matches!(err, ProcessMessageError::IncompatibleWireFormat));
     }
 }
+
+// Test that only the valid policy combinations can be constructed publicly
+#[test]
+fn test_wire_policy_try_new() {
+    for wire_format_policy in WIRE_FORMAT_POLICIES.iter() {
+        let policy =
+            WireFormatPolicy::try_new(wire_format_policy.outgoing(), wire_format_policy.incoming())
+                .expect("valid wire format policy was rejected");
+        assert_eq!(&policy, wire_format_policy);
+    }
+
+    for (outgoing, incoming) in [
+        (
+            OutgoingWireFormatPolicy::AlwaysPlaintext,
+            IncomingWireFormatPolicy::AlwaysCiphertext,
+        ),
+        (
+            OutgoingWireFormatPolicy::AlwaysCiphertext,
+            IncomingWireFormatPolicy::AlwaysPlaintext,
+        ),
+    ] {
+        assert_eq!(
+            WireFormatPolicy::try_new(outgoing, incoming),
+            Err(WireFormatPolicyError::IncompatiblePolicies)
+        );
+    }
+}
@@ -4,7 +4,10 @@
 
 use thiserror::Error;
 
-use crate::{ciphersuite::signable::SignatureError, error::LibraryError};
+use crate::{
+    ciphersuite::signable::SignatureError, error::LibraryError, extensions::ExtensionType,
+    treesync::errors::LeafNodeValidationError,
+};
 
 /// KeyPackage verify error
 #[derive(Error, Debug, PartialEq, Clone)]
@@ -36,6 +39,21 @@ pub enum KeyPackageVerifyError {
     /// The protocol version is not valid.
     #[error("The protocol version is not valid.")]
     InvalidProtocolVersion,
+    /// The ciphersuite is not supported.
+    #[error("The ciphersuite is not supported.")]
+    UnsupportedCiphersuite,
+    /// The ciphersuite is not included in the leaf's capabilities.
+    #[error("The ciphersuite is not included in the leaf's capabilities.")]
+    CiphersuiteNotInCapabilities,
+    /// The lifetime range of the leaf node is longer than acceptable.
+    #[error("The lifetime range of the leaf node is longer than acceptable.")]
+    InvalidLifetimeRange,
+    /// A key package extension is not valid in key packages.
+    #[error("A key package extension is not valid in key packages.")]
+    InvalidExtension(ExtensionType),
+    /// See [`LeafNodeValidationError`] for more details.
+    #[error(transparent)]
+    LeafNodeValidation(#[from] LeafNodeValidationError),
 }
 
 /// KeyPackage extension support error
@@ -61,4 +79,43 @@ pub enum KeyPackageNewError {
     /// See [`SignatureError`] for more details.
     #[error(transparent)]
     SignatureError(#[from] SignatureError),
+    /// No ciphersuite was set.
+    #[error("No ciphersuite was set.")]
+    MissingCiphersuite,
+    /// No credential was set.
+    #[error("No credential was set.")]
+    MissingCredential,
+    /// The ciphersuite is not included in the capabilities.
+    #[error("The ciphersuite is not included in the capabilities.")]
+    CiphersuiteNotInCapabilities,
+    /// The ciphersuite is not supported by the crypto provider.
+    #[error("The ciphersuite is not supported by the crypto provider.")]
+    UnsupportedCiphersuite,
+    /// The lifetime range is longer than acceptable.
+    #[error("The lifetime range is longer than acceptable.")]
+    InvalidLifetime,
+    /// The current time is not available to create the lifetime.
+    #[error("The current time is not available to create the lifetime.")]
+    TimeUnavailable,
+    /// An extension is not valid in the key package or the leaf node it was
+    /// added to.
+    #[error("An extension is not valid in the key package or the leaf node it was added to.")]
+    InvalidExtension(ExtensionType),
+    /// An extension is not included in the capabilities.
+    #[error("An extension is not included in the capabilities.")]
+    ExtensionNotInCapabilities(ExtensionType),
+}
+
+/// Key package pool error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum KeyPackagePoolError<StorageError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`KeyPackageNewError`] for more details.
+    #[error(transparent)]
+    KeyPackageNewError(#[from] KeyPackageNewError),
+    /// Accessing storage failed.
+    #[error("Accessing storage failed.")]
+    StorageError(StorageError),
 }
@@ -15,7 +15,8 @@ use tls_codec::{
 };
 
 use super::{
-    errors::KeyPackageVerifyError, InitKey, KeyPackage, KeyPackageTbs, SIGNATURE_KEY_PACKAGE_LABEL,
+    errors::KeyPackageVerifyError, time_provider, InitKey, KeyPackage, KeyPackageTbs, TimeProvider,
+    SIGNATURE_KEY_PACKAGE_LABEL,
 };
 
 #[cfg(any(feature = "test-utils", test))]
@@ -55,10 +56,7 @@ impl Verifiable for VerifiableKeyPackage {
     ) -> Result<Self::VerifiedStruct, SignatureError> {
         self.verify_no_out(crypto, pk)?;
 
-        Ok(KeyPackage {
-            payload: self.payload,
-            signature: self.signature,
-        })
+        Ok(KeyPackage::from_payload(self.payload, self.signature))
     }
 }
 
@@ -122,11 +120,28 @@ impl KeyPackageIn {
         }
     }
 
+    /// Deserialize a key package from JSON created with
+    /// [`KeyPackage::to_json()`].
+    ///
+    /// The key package has to be validated with [`KeyPackageIn::validate()`]
+    /// before it can be used.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the (unverified) ciphersuite of this key package.
+    pub(crate) fn ciphersuite(&self) -> Ciphersuite {
+        self.payload.ciphersuite
+    }
+
     /// Verify that this key package is valid:
     /// * verify that the signature on this key package is valid
     /// * verify that the signature on the leaf node is valid
     /// * verify that all extensions are supported by the leaf node
-    /// * make sure that the lifetime is valid
+    /// * make sure that the lifetime is valid at the current time of the
+    ///   crypto provider's [`TimeProvider`], or of the system clock if it has
+    ///   none
     /// * make sure that the init key and the encryption key are different
     /// * make sure that the protocol version is valid
     ///
@@ -136,6 +151,18 @@ impl KeyPackageIn {
         self,
         crypto: &impl OpenMlsCrypto,
         protocol_version: ProtocolVersion,
+    ) -> Result<KeyPackage, KeyPackageVerifyError> {
+        self.validate_with_time_provider(crypto, protocol_version, time_provider(crypto))
+    }
+
+    /// Verify that this key package is valid, like [`KeyPackageIn::validate()`],
+    /// but check the lifetime against the current time of the given
+    /// [`TimeProvider`].
+    pub fn validate_with_time_provider(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        protocol_version: ProtocolVersion,
+        time_provider: &(impl TimeProvider + ?Sized),
     ) -> Result<KeyPackage, KeyPackageVerifyError> {
         // We first need to verify the LeafNode inside the KeyPackage
         let leaf_node = self.payload.leaf_node.clone().into_verifiable_leaf_node();
@@ -193,7 +220,7 @@ impl KeyPackageIn {
 
         // Ensure validity of the life time extension in the leaf node.
         if let Some(life_time) = key_package.payload.leaf_node.life_time() {
-            if !life_time.is_valid() {
+            if !life_time.is_valid_with_time_provider(time_provider) {
                 return Err(KeyPackageVerifyError::InvalidLifetime);
             }
         } else {
@@ -262,6 +289,7 @@ impl From<KeyPackageIn> for KeyPackage {
         Self {
             payload: value.payload.into(),
             signature: value.signature,
+            hash_ref: std::sync::OnceLock::new(),
         }
     }
 }
@@ -3,12 +3,16 @@ use fluvio_wasm_timer::{SystemTime, UNIX_EPOCH};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use openmls_traits::crypto::OpenMlsCrypto;
+pub use openmls_traits::crypto::TimeProvider;
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSize};
 
+use crate::treesync::errors::LifetimeError;
+
 /// This value is used as the default lifetime if no default  lifetime is configured.
 /// The value is in seconds and amounts to 3 * 28 Days, i.e. about 3 months.
-const DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS: u64 = 60 * 60 * 24 * 28 * 3;
+pub(crate) const DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS: u64 = 60 * 60 * 24 * 28 * 3;
 
 /// This value is used as the default amount of time (in seconds) the lifetime
 /// of a `KeyPackage` is extended into the past to allow for skewed clocks. The
@@ -55,35 +59,106 @@ pub struct Lifetime {
     not_after: u64,
 }
 
+/// A [`TimeProvider`] that uses the system clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTimeProvider;
+
+impl TimeProvider for SystemTimeProvider {
+    fn now(&self) -> Option<u64> {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => Some(duration.as_secs()),
+            Err(_) => {
+                log::error!("SystemTime before UNIX EPOCH.");
+                None
+            }
+        }
+    }
+}
+
+/// Returns the [`TimeProvider`] of the `crypto` provider, or the
+/// [`SystemTimeProvider`] if it has none.
+pub(crate) fn time_provider(crypto: &impl OpenMlsCrypto) -> &dyn TimeProvider {
+    crypto.time_provider().unwrap_or(&SystemTimeProvider)
+}
+
 impl Lifetime {
     /// Create a new lifetime with lifetime `t` (in seconds).
     /// Note that the lifetime is extended 1h into the past to adapt to skewed
     /// clocks, i.e. `not_before` is set to now - 1h.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system time is before the Unix epoch.
     pub fn new(t: u64) -> Self {
+        Self::new_with_time_provider(t, &SystemTimeProvider).expect("SystemTime before UNIX EPOCH!")
+    }
+
+    /// Create a new lifetime with lifetime `t` (in seconds), using the current
+    /// time of the given [`TimeProvider`].
+    /// Note that the lifetime is extended 1h into the past to adapt to skewed
+    /// clocks, i.e. `not_before` is set to now - 1h.
+    ///
+    /// Returns [`LifetimeError::TimeUnavailable`] if the time provider doesn't
+    /// return the current time.
+    pub fn new_with_time_provider(
+        t: u64,
+        time_provider: &(impl TimeProvider + ?Sized),
+    ) -> Result<Self, LifetimeError> {
         let lifetime_margin: u64 = DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN_SECONDS;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("SystemTime before UNIX EPOCH!")
-            .as_secs();
-        let not_before = now - lifetime_margin;
-        let not_after = now + t;
-        Self {
+        let now = time_provider.now().ok_or(LifetimeError::TimeUnavailable)?;
+        let not_before = now.saturating_sub(lifetime_margin);
+        let not_after = now.saturating_add(t);
+        Ok(Self {
             not_before,
             not_after,
-        }
+        })
     }
 
-    /// Returns true if this lifetime is valid.
+    /// Returns true if this lifetime is valid at the current time of the
+    /// system clock.
     pub fn is_valid(&self) -> bool {
-        match SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|duration| duration.as_secs())
-        {
-            Ok(elapsed) => self.not_before < elapsed && elapsed < self.not_after,
-            Err(_) => {
-                log::error!("SystemTime before UNIX EPOCH.");
-                false
-            }
+        self.is_valid_with_time_provider(&SystemTimeProvider)
+    }
+
+    /// Returns true if this lifetime is valid at the current time of the
+    /// given [`TimeProvider`]. If the current time is not available, the
+    /// lifetime is not valid.
+    pub fn is_valid_with_time_provider(
+        &self,
+        time_provider: &(impl TimeProvider + ?Sized),
+    ) -> bool {
+        match time_provider.now() {
+            Some(now) => self.not_before < now && now < self.not_after,
+            None => false,
+        }
+    }
+
+    /// Returns true if this lifetime is valid at the given time (in seconds
+    /// since the Unix epoch), allowing for `skew` seconds of clock skew in
+    /// either direction.
+    pub fn is_valid_at(&self, timestamp: u64, skew: u64) -> bool {
+        self.not_before.saturating_sub(skew) <= timestamp
+            && timestamp <= self.not_after.saturating_add(skew)
+    }
+
+    /// Returns true if this lifetime ends within the next `seconds` seconds of
+    /// the system clock or has already ended.
+    pub fn expires_within(&self, seconds: u64) -> bool {
+        self.expires_within_with_time_provider(seconds, &SystemTimeProvider)
+    }
+
+    /// Returns true if this lifetime ends within the next `seconds` seconds
+    /// after the current time of the given [`TimeProvider`] or has already
+    /// ended. If the current time is not available, the lifetime is
+    /// considered to have ended.
+    pub fn expires_within_with_time_provider(
+        &self,
+        seconds: u64,
+        time_provider: &(impl TimeProvider + ?Sized),
+    ) -> bool {
+        match time_provider.now() {
+            Some(now) => self.not_after <= now.saturating_add(seconds),
+            None => true,
         }
     }
 
@@ -111,11 +186,121 @@ impl Default for Lifetime {
     }
 }
 
+/// The policy for validating the [`Lifetime`]s of key packages and leaf nodes.
+///
+/// The policy is configured per group in the
+/// [`MlsGroupJoinConfig`](crate::group::MlsGroupJoinConfig) and applied to the
+/// leaf nodes of new members in Add proposals and commits, to the leaf nodes in
+/// the ratchet tree when joining a group, and to the own key package when
+/// processing a [`Welcome`](crate::messages::Welcome).
+///
+/// The current time is the time of the crypto provider's [`TimeProvider`], see
+/// [`OpenMlsCrypto::time_provider()`], or of the system clock if it has none.
+///
+/// The default policy accepts all lifetimes that cover the current time, like
+/// [`Lifetime::is_valid()`], without limiting their range and without a margin
+/// or clock skew. Key packages with a range longer than about three months are
+/// rejected when they are received, regardless of the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifetimePolicy {
+    max_range: u64,
+    min_remaining: u64,
+    clock_skew: u64,
+}
+
+impl Default for LifetimePolicy {
+    fn default() -> Self {
+        Self {
+            max_range: u64::MAX,
+            min_remaining: 0,
+            clock_skew: 0,
+        }
+    }
+}
+
+impl LifetimePolicy {
+    /// Create the default [`LifetimePolicy`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum range (in seconds) between `not_before` and `not_after`
+    /// of accepted lifetimes.
+    ///
+    /// Note that key packages with a range longer than about three months are
+    /// rejected when they are received, regardless of this policy.
+    pub fn with_max_range(mut self, seconds: u64) -> Self {
+        self.max_range = seconds;
+        self
+    }
+
+    /// Set the amount of time (in seconds) an accepted lifetime has to remain
+    /// valid for, e.g. to reject key packages that are about to expire.
+    pub fn with_min_remaining(mut self, seconds: u64) -> Self {
+        self.min_remaining = seconds;
+        self
+    }
+
+    /// Set the clock skew (in seconds) that is tolerated in either direction
+    /// when checking that a lifetime covers the current time.
+    pub fn with_clock_skew(mut self, seconds: u64) -> Self {
+        self.clock_skew = seconds;
+        self
+    }
+
+    /// Returns the maximum range (in seconds) of accepted lifetimes.
+    pub fn max_range(&self) -> u64 {
+        self.max_range
+    }
+
+    /// Returns the amount of time (in seconds) an accepted lifetime has to
+    /// remain valid for.
+    pub fn min_remaining(&self) -> u64 {
+        self.min_remaining
+    }
+
+    /// Returns the tolerated clock skew (in seconds).
+    pub fn clock_skew(&self) -> u64 {
+        self.clock_skew
+    }
+
+    /// Validate the `lifetime` against this policy at the current time of the
+    /// system clock.
+    pub fn validate(&self, lifetime: &Lifetime) -> Result<(), LifetimeError> {
+        self.validate_with_time_provider(lifetime, &SystemTimeProvider)
+    }
+
+    /// Validate the `lifetime` against this policy at the current time of the
+    /// given [`TimeProvider`]. If the current time is not available, the
+    /// lifetime is not valid.
+    pub fn validate_with_time_provider(
+        &self,
+        lifetime: &Lifetime,
+        time_provider: &(impl TimeProvider + ?Sized),
+    ) -> Result<(), LifetimeError> {
+        if lifetime.not_after.saturating_sub(lifetime.not_before) > self.max_range {
+            return Err(LifetimeError::RangeTooBig);
+        }
+        let now = time_provider.now().ok_or(LifetimeError::NotCurrent)?;
+        if !lifetime.is_valid_at(now, self.clock_skew) {
+            return Err(LifetimeError::NotCurrent);
+        }
+        if lifetime.not_after.saturating_add(self.clock_skew)
+            < now.saturating_add(self.min_remaining)
+        {
+            return Err(LifetimeError::ExpiresTooSoon);
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use tls_codec::{Deserialize, Serialize};
 
-    use super::Lifetime;
+    use super::{Lifetime, LifetimePolicy};
+    use crate::{test_utils::FixedTimeProvider, treesync::errors::LifetimeError};
 
     #[test]
     fn lifetime() {
@@ -136,4 +321,99 @@ mod tests {
             .expect("Error deserializing lifetime");
         assert!(!ext_deserialized.is_valid());
     }
+
+    #[test]
+    fn lifetime_with_skew() {
+        let lifetime = Lifetime {
+            not_before: 1000,
+            not_after: 2000,
+        };
+        assert!(lifetime.is_valid_at(1500, 0));
+        assert!(!lifetime.is_valid_at(999, 0));
+        assert!(!lifetime.is_valid_at(2001, 0));
+        assert!(lifetime.is_valid_at(900, 100));
+        assert!(lifetime.is_valid_at(2100, 100));
+        assert!(!lifetime.is_valid_at(2101, 100));
+        assert!(lifetime.is_valid_at(0, u64::MAX));
+        assert!(lifetime.is_valid_at(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn lifetime_with_time_provider() {
+        let lifetime =
+            Lifetime::new_with_time_provider(100, &FixedTimeProvider(Some(10_000))).unwrap();
+        assert_eq!(lifetime.not_before(), 10_000 - 60 * 60);
+        assert_eq!(lifetime.not_after(), 10_100);
+
+        assert!(lifetime.is_valid_with_time_provider(&FixedTimeProvider(Some(10_050))));
+        assert!(!lifetime.is_valid_with_time_provider(&FixedTimeProvider(Some(10_100))));
+        assert!(!lifetime.is_valid_with_time_provider(&FixedTimeProvider(None)));
+
+        // The lifetime is long expired when using the system clock.
+        assert!(!lifetime.is_valid());
+
+        assert_eq!(
+            Lifetime::new_with_time_provider(100, &FixedTimeProvider(None)),
+            Err(LifetimeError::TimeUnavailable)
+        );
+    }
+
+    #[test]
+    fn lifetime_policy() {
+        let lifetime = Lifetime {
+            not_before: 1000,
+            not_after: 2000,
+        };
+        let at = |now| FixedTimeProvider(Some(now));
+
+        let policy = LifetimePolicy::default();
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(1500)),
+            Ok(())
+        );
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(2001)),
+            Err(LifetimeError::NotCurrent)
+        );
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &FixedTimeProvider(None)),
+            Err(LifetimeError::NotCurrent)
+        );
+
+        let policy = LifetimePolicy::new().with_clock_skew(100);
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(2100)),
+            Ok(())
+        );
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(900)),
+            Ok(())
+        );
+
+        let policy = LifetimePolicy::new().with_min_remaining(600);
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(1400)),
+            Ok(())
+        );
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(1401)),
+            Err(LifetimeError::ExpiresTooSoon)
+        );
+
+        // The default policy doesn't limit the range.
+        let long_lifetime = Lifetime {
+            not_before: 0,
+            not_after: u64::MAX,
+        };
+        assert_eq!(
+            LifetimePolicy::default().validate_with_time_provider(&long_lifetime, &at(1500)),
+            Ok(())
+        );
+
+        let policy = LifetimePolicy::new().with_max_range(999);
+        assert_eq!(
+            policy.validate_with_time_provider(&lifetime, &at(1500)),
+            Err(LifetimeError::RangeTooBig)
+        );
+    }
 }
@@ -54,6 +54,10 @@
 //!     .unwrap();
 //! ```
 //!
+//! Alternatively, [`KeyPackageBundle::builder()`] configures all parameters of
+//! the key package, including the ciphersuite and credential, and validates
+//! them when building.
+//!
 //! See [`KeyPackage`] for more details and other ways to create key packages.
 //!
 //! ## Loading key packages
@@ -88,6 +92,12 @@
 //!     .expect("Invalid KeyPackage");
 //! ```
 //!
+//! With the `json` feature, key packages can also be (de)serialized as JSON,
+//! e.g. for storing them in a JSON database. Like their TLS encoding, the JSON
+//! encoding of a [`KeyPackage`] is deserialized into a [`KeyPackageIn`] that
+//! has to be validated. Note that the JSON encoding is not canonical and must
+//! not be used on the wire.
+//!
 //! See [`KeyPackage`] for more details on how to use key packages.
 
 use crate::{
@@ -98,7 +108,9 @@ use crate::{
     },
     credentials::*,
     error::LibraryError,
-    extensions::{Extension, ExtensionType, Extensions, LastResortExtension},
+    extensions::{
+        ApplicationIdExtension, Extension, ExtensionType, Extensions, LastResortExtension,
+    },
     storage::OpenMlsProvider,
     treesync::{
         node::{
@@ -110,9 +122,11 @@ use crate::{
     versions::ProtocolVersion,
 };
 use openmls_traits::{
-    crypto::OpenMlsCrypto, signatures::Signer, storage::StorageProvider, types::Ciphersuite,
+    crypto::OpenMlsCrypto, random::OpenMlsRand, signatures::Signer, storage::StorageProvider,
+    types::Ciphersuite,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use tls_codec::{
     Serialize as TlsSerializeTrait, TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSize,
 };
@@ -125,6 +139,7 @@ pub mod errors;
 pub mod key_package_in;
 
 mod lifetime;
+mod pool;
 
 // Tests
 #[cfg(test)]
@@ -132,7 +147,9 @@ pub(crate) mod tests;
 
 // Public types
 pub use key_package_in::KeyPackageIn;
-pub use lifetime::Lifetime;
+pub(crate) use lifetime::{time_provider, DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS};
+pub use lifetime::{Lifetime, LifetimePolicy, SystemTimeProvider, TimeProvider};
+pub use pool::KeyPackagePool;
 
 /// The unsigned payload of a key package.
 /// Any modification must happen on this unsigned struct. Use `sign` to get a
@@ -179,6 +196,10 @@ impl From<KeyPackage> for KeyPackageTbs {
 pub struct KeyPackage {
     payload: KeyPackageTbs,
     signature: Signature,
+    /// The [`KeyPackageRef`] of this key package, computed on first use.
+    #[tls_codec(skip)]
+    #[serde(skip)]
+    hash_ref: OnceLock<KeyPackageRef>,
 }
 
 impl PartialEq for KeyPackage {
@@ -191,7 +212,11 @@ impl PartialEq for KeyPackage {
 
 impl SignedStruct<KeyPackageTbs> for KeyPackage {
     fn from_payload(payload: KeyPackageTbs, signature: Signature) -> Self {
-        Self { payload, signature }
+        Self {
+            payload,
+            signature,
+            hash_ref: OnceLock::new(),
+        }
     }
 }
 
@@ -204,6 +229,51 @@ pub(crate) struct KeyPackageCreationResult {
     pub init_private_key: HpkePrivateKey,
 }
 
+/// The input keying material for the init key and the encryption key of a new
+/// [`KeyPackage`].
+pub(crate) struct KeyPackageIkm {
+    init: Secret,
+    encryption: Secret,
+}
+
+impl KeyPackageIkm {
+    /// Sample fresh input keying material.
+    fn random(
+        ciphersuite: Ciphersuite,
+        rand: &impl OpenMlsRand,
+    ) -> Result<Self, KeyPackageNewError> {
+        Ok(Self {
+            init: Secret::random(ciphersuite, rand)
+                .map_err(LibraryError::unexpected_crypto_error)?,
+            encryption: Secret::random(ciphersuite, rand)
+                .map_err(LibraryError::unexpected_crypto_error)?,
+        })
+    }
+
+    /// Derive the input keying material deterministically from `seed`.
+    #[cfg(feature = "test-utils")]
+    fn from_seed(
+        ciphersuite: Ciphersuite,
+        crypto: &impl OpenMlsCrypto,
+        seed: &[u8],
+    ) -> Result<Self, KeyPackageNewError> {
+        let hash_type = ciphersuite.hash_algorithm();
+        let prk = crypto
+            .hkdf_extract(hash_type, b"", seed)
+            .map_err(LibraryError::unexpected_crypto_error)?;
+        let expand = |label: &[u8]| {
+            crypto
+                .hkdf_expand(hash_type, prk.as_slice(), label, ciphersuite.hash_length())
+                .map(|okm| Secret::from_slice(okm.as_slice()))
+                .map_err(LibraryError::unexpected_crypto_error)
+        };
+        Ok(Self {
+            init: expand(b"init key")?,
+            encryption: expand(b"encryption key")?,
+        })
+    }
+}
+
 /// Init key for HPKE.
 #[derive(
     Debug,
@@ -266,23 +336,26 @@ impl KeyPackage {
         extensions: Extensions,
         leaf_node_capabilities: Capabilities,
         leaf_node_extensions: Extensions,
+        ikm: KeyPackageIkm,
     ) -> Result<KeyPackageCreationResult, KeyPackageNewError> {
         if ciphersuite.signature_algorithm() != signer.signature_scheme() {
             return Err(KeyPackageNewError::CiphersuiteSignatureSchemeMismatch);
         }
+        if !CiphersuiteRegistry::new(provider.crypto()).supports(ciphersuite) {
+            return Err(KeyPackageNewError::UnsupportedCiphersuite);
+        }
 
         // Create a new HPKE key pair
-        let ikm = Secret::random(ciphersuite, provider.rand())
-            .map_err(LibraryError::unexpected_crypto_error)?;
         let init_key = provider
             .crypto()
-            .derive_hpke_keypair(ciphersuite.hpke_config(), ikm.as_slice())
+            .derive_hpke_keypair(ciphersuite.hpke_config(), ikm.init.as_slice())
             .map_err(|e| {
                 KeyPackageNewError::LibraryError(LibraryError::unexpected_crypto_error(e))
             })?;
-        let (key_package, encryption_keypair) = Self::new_from_keys(
+        let encryption_keypair =
+            EncryptionKeyPair::derive(provider.crypto(), ciphersuite, &ikm.encryption)?;
+        let key_package = Self::new_from_keys(
             ciphersuite,
-            provider,
             signer,
             credential_with_key,
             lifetime,
@@ -290,6 +363,7 @@ impl KeyPackage {
             leaf_node_capabilities,
             leaf_node_extensions,
             init_key.public.into(),
+            &encryption_keypair,
         )?;
 
         Ok(KeyPackageCreationResult {
@@ -301,17 +375,14 @@ impl KeyPackage {
 
     /// Create a new key package for the given `ciphersuite` and `identity`.
     ///
-    /// The HPKE init key must have been generated before and the private part
-    /// has to be stored in the key store.
-    ///
-    /// This function returns the new [`KeyPackage`] as well as the
-    /// encryption key ([`HpkeKeyPair`]) of the leaf node.
+    /// The HPKE init key and the encryption key of the leaf node must have been
+    /// generated before and the private parts have to be stored in the key
+    /// store.
     ///
     /// The caller is responsible for storing the new values.
     #[allow(clippy::too_many_arguments)]
     fn new_from_keys(
         ciphersuite: Ciphersuite,
-        provider: &impl OpenMlsProvider,
         signer: &impl Signer,
         credential_with_key: CredentialWithKey,
         lifetime: Lifetime,
@@ -319,7 +390,8 @@ impl KeyPackage {
         capabilities: Capabilities,
         leaf_node_extensions: Extensions,
         init_key: InitKey,
-    ) -> Result<(Self, EncryptionKeyPair), KeyPackageNewError> {
+        encryption_key_pair: &EncryptionKeyPair,
+    ) -> Result<Self, KeyPackageNewError> {
         // We don't need the private key here. It's stored in the key store for
         // use later when creating a group with this key package.
 
@@ -332,8 +404,11 @@ impl KeyPackage {
             tree_info_tbs: TreeInfoTbs::KeyPackage,
         };
 
-        let (leaf_node, encryption_key_pair) =
-            LeafNode::new(provider, signer, new_leaf_node_params)?;
+        let leaf_node = LeafNode::new_with_encryption_key_pair(
+            signer,
+            new_leaf_node_params,
+            encryption_key_pair,
+        )?;
 
         let key_package_tbs = KeyPackageTbs {
             protocol_version: ProtocolVersion::default(),
@@ -345,7 +420,7 @@ impl KeyPackage {
 
         let key_package = key_package_tbs.sign(signer)?;
 
-        Ok((key_package, encryption_key_pair))
+        Ok(key_package)
     }
 
     /// Get a reference to the extensions of this key package.
@@ -371,15 +446,93 @@ impl KeyPackage {
     /// Compute the [`KeyPackageRef`] of this [`KeyPackage`].
     /// The [`KeyPackageRef`] is used to identify a new member that should get
     /// added to a group.
+    ///
+    /// The reference is computed on the first call and cached afterwards.
     pub fn hash_ref(&self, crypto: &impl OpenMlsCrypto) -> Result<KeyPackageRef, LibraryError> {
-        make_key_package_ref(
+        if let Some(hash_ref) = self.hash_ref.get() {
+            return Ok(hash_ref.clone());
+        }
+        let hash_ref = make_key_package_ref(
             &self
                 .tls_serialize_detached()
                 .map_err(LibraryError::missing_bound_check)?,
             self.payload.ciphersuite,
             crypto,
         )
-        .map_err(LibraryError::unexpected_crypto_error)
+        .map_err(LibraryError::unexpected_crypto_error)?;
+        Ok(self.hash_ref.get_or_init(|| hash_ref).clone())
+    }
+
+    /// Verify a key package without any group context, e.g. when a delivery
+    /// service vets key packages uploaded by clients.
+    ///
+    /// In addition to the checks done by [`KeyPackageIn::validate()`], this
+    /// checks that
+    /// * the ciphersuite is supported by `crypto` and included in the leaf's
+    ///   capabilities
+    /// * the lifetime range is acceptable
+    /// * the leaf node only uses extensions that are valid in leaf nodes and
+    ///   included in its capabilities, and its credential type is included in
+    ///   its capabilities
+    /// * the key package only uses extensions that are valid in key packages
+    ///
+    /// Returns the verified [`KeyPackage`] or a [`KeyPackageVerifyError`].
+    pub fn verify_standalone(
+        key_package: KeyPackageIn,
+        crypto: &impl OpenMlsCrypto,
+    ) -> Result<KeyPackage, KeyPackageVerifyError> {
+        let ciphersuite = key_package.ciphersuite();
+        crypto
+            .supports(ciphersuite)
+            .map_err(|_| KeyPackageVerifyError::UnsupportedCiphersuite)?;
+
+        let key_package = key_package.validate(crypto, ProtocolVersion::Mls10)?;
+
+        let leaf_node = key_package.leaf_node();
+        if !leaf_node
+            .capabilities()
+            .contains_ciphersuite(ciphersuite.into())
+        {
+            return Err(KeyPackageVerifyError::CiphersuiteNotInCapabilities);
+        }
+        if !key_package.life_time().has_acceptable_range() {
+            return Err(KeyPackageVerifyError::InvalidLifetimeRange);
+        }
+        leaf_node.validate_locally()?;
+        if let Some(extension) = key_package
+            .extensions()
+            .iter()
+            .find(|extension| extension.extension_type().is_valid_in_key_package() == Some(false))
+        {
+            return Err(KeyPackageVerifyError::InvalidExtension(
+                extension.extension_type(),
+            ));
+        }
+
+        Ok(key_package)
+    }
+
+    /// Returns the [`Capabilities`] that are supported by all of the given key
+    /// packages, or `None` if `key_packages` is empty.
+    ///
+    /// A group creator can use this to choose a ciphersuite and the required
+    /// capabilities of a group such that all of its intended members support
+    /// them. The result keeps the preference order of the first key package.
+    /// See [`Capabilities::intersection()`] for details.
+    pub fn common_capabilities<'a>(
+        key_packages: impl IntoIterator<Item = &'a KeyPackage>,
+    ) -> Option<Capabilities> {
+        Capabilities::common(
+            key_packages
+                .into_iter()
+                .map(|key_package| key_package.leaf_node().capabilities()),
+        )
+    }
+
+    /// Returns the application id of the key package's leaf node if it has an
+    /// [`ApplicationIdExtension`].
+    pub fn application_id(&self) -> Option<&[u8]> {
+        self.payload.leaf_node.application_id()
     }
 
     /// Get the [`Ciphersuite`].
@@ -423,10 +576,13 @@ impl KeyPackage {
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct KeyPackageBuilder {
     key_package_lifetime: Option<Lifetime>,
+    #[serde(default)]
+    key_package_lifetime_duration: Option<u64>,
     key_package_extensions: Option<Extensions>,
     leaf_node_capabilities: Option<Capabilities>,
     leaf_node_extensions: Option<Extensions>,
     last_resort: bool,
+    grease: bool,
 }
 
 impl KeyPackageBuilder {
@@ -434,10 +590,12 @@ impl KeyPackageBuilder {
     pub fn new() -> Self {
         Self {
             key_package_lifetime: None,
+            key_package_lifetime_duration: None,
             key_package_extensions: None,
             leaf_node_capabilities: None,
             leaf_node_extensions: None,
             last_resort: false,
+            grease: false,
         }
     }
 
@@ -447,6 +605,31 @@ impl KeyPackageBuilder {
         self
     }
 
+    /// Set the duration (in seconds) of the lifetime of the key packages,
+    /// starting when they are built. This is ignored if a lifetime is set with
+    /// [`KeyPackageBuilder::key_package_lifetime()`]. If neither is set, the
+    /// lifetime lasts about three months.
+    pub fn key_package_lifetime_duration(mut self, seconds: u64) -> Self {
+        self.key_package_lifetime_duration.replace(seconds);
+        self
+    }
+
+    /// Returns the lifetime of a key package built now, i.e. at the current
+    /// time of the `crypto` provider's [`TimeProvider`].
+    fn lifetime(&self, crypto: &impl OpenMlsCrypto) -> Result<Lifetime, KeyPackageNewError> {
+        match (
+            self.key_package_lifetime,
+            self.key_package_lifetime_duration,
+        ) {
+            (Some(lifetime), _) => Ok(lifetime),
+            (None, seconds) => Lifetime::new_with_time_provider(
+                seconds.unwrap_or(DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS),
+                time_provider(crypto),
+            )
+            .map_err(|_| KeyPackageNewError::TimeUnavailable),
+        }
+    }
+
     /// Set the key package extensions.
     pub fn key_package_extensions(mut self, extensions: Extensions) -> Self {
         self.key_package_extensions.replace(extensions);
@@ -454,12 +637,28 @@ impl KeyPackageBuilder {
     }
 
     /// Mark the key package as a last-resort key package via a [`LastResortExtension`].
+    ///
+    /// The [`ExtensionType::LastResort`] is added to the leaf node capabilities
+    /// if necessary, since extensions in a key package must be supported by
+    /// its leaf node.
     pub fn mark_as_last_resort(mut self) -> Self {
         self.last_resort = true;
         self
     }
 
-    /// Set the leaf node capabilities.
+    /// Add GREASE values (see RFC 9420, Section 13.5) to the leaf node
+    /// capabilities of the key package.
+    ///
+    /// This helps to ensure that other implementations correctly ignore
+    /// unknown values.
+    pub fn with_grease(mut self) -> Self {
+        self.grease = true;
+        self
+    }
+
+    /// Set the leaf node capabilities. If not set, the
+    /// [`CiphersuiteRegistry::default_capabilities()`] of the provider are
+    /// used.
     pub fn leaf_node_capabilities(mut self, capabilities: Capabilities) -> Self {
         self.leaf_node_capabilities.replace(capabilities);
         self
@@ -484,6 +683,29 @@ impl KeyPackageBuilder {
         }
     }
 
+    /// Returns the leaf node capabilities, with the last resort extension
+    /// type added if the `last_resort` flag is set and GREASE values added if
+    /// the `grease` flag is set.
+    fn capabilities(
+        &mut self,
+        provider: &impl OpenMlsProvider,
+    ) -> Result<Capabilities, KeyPackageNewError> {
+        let mut capabilities = self
+            .leaf_node_capabilities
+            .take()
+            .unwrap_or_else(|| CiphersuiteRegistry::new(provider.crypto()).default_capabilities());
+        if self.last_resort {
+            capabilities = capabilities.with_extension(ExtensionType::LastResort);
+        }
+        if !self.grease {
+            return Ok(capabilities);
+        }
+        let capabilities = capabilities
+            .with_grease(provider.rand())
+            .map_err(LibraryError::unexpected_crypto_error)?;
+        Ok(capabilities)
+    }
+
     #[cfg(test)]
     pub(crate) fn build_without_storage(
         mut self,
@@ -493,27 +715,80 @@ impl KeyPackageBuilder {
         credential_with_key: CredentialWithKey,
     ) -> Result<KeyPackageCreationResult, KeyPackageNewError> {
         self.ensure_last_resort();
+        let capabilities = self.capabilities(provider)?;
         KeyPackage::create(
             ciphersuite,
             provider,
             signer,
             credential_with_key,
-            self.key_package_lifetime.unwrap_or_default(),
+            self.lifetime(provider.crypto())?,
             self.key_package_extensions.unwrap_or_default(),
-            self.leaf_node_capabilities.unwrap_or_default(),
+            capabilities,
             self.leaf_node_extensions.unwrap_or_default(),
+            KeyPackageIkm::random(ciphersuite, provider.rand())?,
         )
     }
 
     /// Finalize and build the key package.
     pub fn build(
+        self,
+        ciphersuite: Ciphersuite,
+        provider: &impl OpenMlsProvider,
+        signer: &impl Signer,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<KeyPackageBundle, KeyPackageNewError> {
+        let ikm = KeyPackageIkm::random(ciphersuite, provider.rand())?;
+        self.build_with_ikm(ciphersuite, provider, signer, credential_with_key, ikm)
+    }
+
+    /// Build the key package deterministically from `seed`, e.g. to reproduce
+    /// exact key packages in interop tests or fuzzing harnesses.
+    ///
+    /// The init key, the encryption key and the signature key are derived from
+    /// `seed` using HKDF, so that the same seed always yields the same key
+    /// package bundle and signer. For this to hold, the lifetime must be set
+    /// explicitly (e.g. with [`Lifetime::new_with_time_provider()`] and a fixed
+    /// [`TimeProvider`]) and GREASE must not be enabled.
+    ///
+    /// **WARNING:** This must never be used outside of tests.
+    #[cfg(feature = "test-utils")]
+    pub fn build_from_seed(
+        self,
+        ciphersuite: Ciphersuite,
+        provider: &impl OpenMlsProvider,
+        credential: Credential,
+        seed: &[u8],
+    ) -> Result<(KeyPackageBundle, openmls_basic_credential::SignatureKeyPair), KeyPackageNewError>
+    {
+        let signer = crate::test_utils::signature_key_pair_from_seed(
+            provider.crypto(),
+            ciphersuite.signature_algorithm(),
+            seed,
+        )
+        .map_err(LibraryError::unexpected_crypto_error)?;
+        let credential_with_key = CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        };
+        let ikm = KeyPackageIkm::from_seed(ciphersuite, provider.crypto(), seed)?;
+        let key_package_bundle =
+            self.build_with_ikm(ciphersuite, provider, &signer, credential_with_key, ikm)?;
+
+        Ok((key_package_bundle, signer))
+    }
+
+    /// Build the key package from the given input keying material and store
+    /// it.
+    fn build_with_ikm(
         mut self,
         ciphersuite: Ciphersuite,
         provider: &impl OpenMlsProvider,
         signer: &impl Signer,
         credential_with_key: CredentialWithKey,
+        ikm: KeyPackageIkm,
     ) -> Result<KeyPackageBundle, KeyPackageNewError> {
         self.ensure_last_resort();
+        let capabilities = self.capabilities(provider)?;
         let KeyPackageCreationResult {
             key_package,
             encryption_keypair,
@@ -523,10 +798,11 @@ impl KeyPackageBuilder {
             provider,
             signer,
             credential_with_key,
-            self.key_package_lifetime.unwrap_or_default(),
+            self.lifetime(provider.crypto())?,
             self.key_package_extensions.unwrap_or_default(),
-            self.leaf_node_capabilities.unwrap_or_default(),
+            capabilities,
             self.leaf_node_extensions.unwrap_or_default(),
+            ikm,
         )?;
 
         // Store the key package in the key store with the hash reference as id
@@ -545,6 +821,214 @@ impl KeyPackageBuilder {
     }
 }
 
+/// Builder that helps creating (and configuring) a [`KeyPackageBundle`].
+///
+/// In contrast to the [`KeyPackageBuilder`], all parameters of the key package
+/// are set on the builder and checked when calling [`build()`].
+///
+/// ```
+/// use openmls::prelude::*;
+/// use openmls_basic_credential::SignatureKeyPair;
+/// use openmls_rust_crypto::OpenMlsRustCrypto;
+///
+/// let provider = OpenMlsRustCrypto::default();
+/// let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+/// let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+/// let credential_with_key = CredentialWithKey {
+///     credential: BasicCredential::new(b"Alice".to_vec()).into(),
+///     signature_key: signer.public().into(),
+/// };
+///
+/// let key_package_bundle = KeyPackageBundle::builder()
+///     .ciphersuite(ciphersuite)
+///     .credential_with_key(credential_with_key)
+///     .application_id(b"device 1")
+///     .build(&provider, &signer)
+///     .unwrap();
+/// ```
+///
+/// [`build()`]: KeyPackageBundleBuilder::build
+#[derive(Default, Debug, Clone)]
+pub struct KeyPackageBundleBuilder {
+    ciphersuite: Option<Ciphersuite>,
+    credential_with_key: Option<CredentialWithKey>,
+    lifetime: Option<Lifetime>,
+    capabilities: Option<Capabilities>,
+    key_package_extensions: Extensions,
+    leaf_node_extensions: Extensions,
+    last_resort: bool,
+}
+
+impl KeyPackageBundleBuilder {
+    /// Create a key package bundle builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the ciphersuite of the key package. This is required.
+    pub fn ciphersuite(mut self, ciphersuite: Ciphersuite) -> Self {
+        self.ciphersuite = Some(ciphersuite);
+        self
+    }
+
+    /// Set the credential and signature key of the key package. This is
+    /// required.
+    pub fn credential_with_key(mut self, credential_with_key: CredentialWithKey) -> Self {
+        self.credential_with_key = Some(credential_with_key);
+        self
+    }
+
+    /// Set the leaf node capabilities. If not set, the
+    /// [`CiphersuiteRegistry::default_capabilities()`] of the provider are
+    /// used.
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Set the key package lifetime. If not set, the default lifetime is used.
+    pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
+        self.lifetime = Some(lifetime);
+        self
+    }
+
+    /// Set the application id of the leaf node, replacing any previously set
+    /// application id.
+    pub fn application_id(mut self, application_id: &[u8]) -> Self {
+        self.leaf_node_extensions
+            .add_or_replace(Extension::ApplicationId(ApplicationIdExtension::new(
+                application_id,
+            )));
+        self
+    }
+
+    /// Add an extension to the key package, replacing any previously added
+    /// extension of the same type.
+    pub fn key_package_extension(mut self, extension: Extension) -> Self {
+        self.key_package_extensions.add_or_replace(extension);
+        self
+    }
+
+    /// Add an extension to the leaf node, replacing any previously added
+    /// extension of the same type.
+    pub fn leaf_node_extension(mut self, extension: Extension) -> Self {
+        self.leaf_node_extensions.add_or_replace(extension);
+        self
+    }
+
+    /// Mark the key package as a last-resort key package via a
+    /// [`LastResortExtension`]. See [`KeyPackageBuilder::mark_as_last_resort()`].
+    pub fn mark_as_last_resort(mut self) -> Self {
+        self.last_resort = true;
+        self
+    }
+
+    /// Check that the configured parameters form a valid key package.
+    fn validate(
+        &self,
+        ciphersuite: Ciphersuite,
+        registry: &CiphersuiteRegistry,
+    ) -> Result<(), KeyPackageNewError> {
+        if !registry.supports(ciphersuite) {
+            return Err(KeyPackageNewError::UnsupportedCiphersuite);
+        }
+
+        let mut capabilities = self
+            .capabilities
+            .clone()
+            .unwrap_or_else(|| registry.default_capabilities());
+        if self.last_resort {
+            capabilities = capabilities.with_extension(ExtensionType::LastResort);
+        }
+
+        if !capabilities.contains_ciphersuite(ciphersuite.into()) {
+            return Err(KeyPackageNewError::CiphersuiteNotInCapabilities);
+        }
+
+        if let Some(lifetime) = &self.lifetime {
+            if !lifetime.has_acceptable_range() {
+                return Err(KeyPackageNewError::InvalidLifetime);
+            }
+        }
+
+        let key_package_extension_types = self.key_package_extensions.iter().map(|extension| {
+            (
+                extension.extension_type(),
+                extension.extension_type().is_valid_in_key_package(),
+            )
+        });
+        let leaf_node_extension_types = self.leaf_node_extensions.iter().map(|extension| {
+            (
+                extension.extension_type(),
+                extension.extension_type().is_valid_in_leaf_node(),
+            )
+        });
+        for (extension_type, is_valid) in
+            key_package_extension_types.chain(leaf_node_extension_types)
+        {
+            if is_valid == Some(false) {
+                return Err(KeyPackageNewError::InvalidExtension(extension_type));
+            }
+            if !capabilities.contains_extension(extension_type) {
+                return Err(KeyPackageNewError::ExtensionNotInCapabilities(
+                    extension_type,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize and build the key package bundle.
+    ///
+    /// The key package bundle is stored in the provider's storage, so that it
+    /// can be used when processing a [`Welcome`](crate::messages::Welcome).
+    ///
+    /// Returns an error if the ciphersuite or the credential are not set, if
+    /// the ciphersuite is not supported by the provider or not included in
+    /// the capabilities, if the lifetime
+    /// range is too long, or if an extension is not valid where it was added
+    /// or is not included in the capabilities.
+    pub fn build(
+        self,
+        provider: &impl OpenMlsProvider,
+        signer: &impl Signer,
+    ) -> Result<KeyPackageBundle, KeyPackageNewError> {
+        let ciphersuite = self
+            .ciphersuite
+            .ok_or(KeyPackageNewError::MissingCiphersuite)?;
+        self.validate(ciphersuite, &CiphersuiteRegistry::new(provider.crypto()))?;
+        let credential_with_key = self
+            .credential_with_key
+            .ok_or(KeyPackageNewError::MissingCredential)?;
+
+        let mut builder = KeyPackage::builder()
+            .key_package_extensions(self.key_package_extensions)
+            .leaf_node_extensions(self.leaf_node_extensions);
+        if let Some(lifetime) = self.lifetime {
+            builder = builder.key_package_lifetime(lifetime);
+        }
+        if let Some(capabilities) = self.capabilities {
+            builder = builder.leaf_node_capabilities(capabilities);
+        }
+        if self.last_resort {
+            builder = builder.mark_as_last_resort();
+        }
+
+        builder.build(ciphersuite, provider, signer, credential_with_key)
+    }
+}
+
+#[cfg(feature = "json")]
+impl KeyPackage {
+    /// Serialize this key package as JSON.
+    ///
+    /// Use [`KeyPackageIn::from_json()`] to deserialize it again.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
 /// A [`KeyPackageBundle`] contains a [`KeyPackage`] and the init and encryption
 /// private key.
 ///
@@ -559,6 +1043,12 @@ pub struct KeyPackageBundle {
 
 // Public `KeyPackageBundle` functions.
 impl KeyPackageBundle {
+    /// Create a [`KeyPackageBundleBuilder`] to configure and build a new key
+    /// package bundle.
+    pub fn builder() -> KeyPackageBundleBuilder {
+        KeyPackageBundleBuilder::new()
+    }
+
     /// Get a reference to the public part of this bundle, i.e. the [`KeyPackage`].
     pub fn key_package(&self) -> &KeyPackage {
         &self.key_package
@@ -578,6 +1068,26 @@ impl KeyPackageBundle {
     }
 }
 
+#[cfg(feature = "json")]
+impl KeyPackageBundle {
+    /// Serialize this key package bundle as JSON.
+    ///
+    /// **WARNING:** The JSON contains the private keys of the bundle and must
+    /// be stored as securely as the provider's storage.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserialize a key package bundle from JSON created with
+    /// [`KeyPackageBundle::to_json()`].
+    ///
+    /// The key package is not validated again, so this must only be used with
+    /// key package bundles from a trusted source.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
 #[cfg(any(test, feature = "test-utils"))]
 impl KeyPackageBundle {
     /// Generate a new key package bundle with the private key.
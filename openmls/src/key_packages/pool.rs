@@ -0,0 +1,240 @@
+//! # Key package pool
+//!
+//! This module contains the [`KeyPackagePool`].
+
+use openmls_traits::{signatures::Signer, storage::StorageProvider as _, types::Ciphersuite};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    errors::{KeyPackageNewError, KeyPackagePoolError},
+    time_provider, KeyPackage, KeyPackageBuilder, KeyPackageBundle, Lifetime,
+    DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS,
+};
+use crate::{
+    ciphersuite::hash_ref::KeyPackageRef, credentials::CredentialWithKey, storage::OpenMlsProvider,
+};
+
+/// The default amount of time (in seconds) before the end of their lifetime at
+/// which key packages are rotated. The value amounts to 7 days.
+const DEFAULT_ROTATION_MARGIN_SECONDS: u64 = 60 * 60 * 24 * 7;
+
+/// A key package tracked by the [`KeyPackagePool`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PoolEntry {
+    hash_ref: KeyPackageRef,
+    lifetime: Lifetime,
+    published: bool,
+}
+
+/// A pool of key packages of a single client.
+///
+/// Clients usually publish a number of key packages to the delivery service
+/// ahead of time, so that others can add them to groups while they are
+/// offline. The [`KeyPackagePool`] keeps track of these key packages:
+///
+/// - [`KeyPackagePool::replenish()`] generates and stores new key packages
+///   until the configured number of key packages is available,
+/// - [`KeyPackagePool::take_unpublished()`] returns the key packages that
+///   still have to be uploaded to the delivery service and marks them as
+///   published,
+/// - key packages that were consumed when joining a group from a
+///   [`Welcome`](crate::messages::Welcome) are forgotten, and key packages
+///   that are about to expire are deleted from the key store and replaced.
+///
+/// The key packages themselves, including their private keys, are kept in the
+/// provider's storage. The pool only tracks their references and publication
+/// status. It can be serialized to persist it alongside the storage.
+///
+/// ```
+/// use openmls::prelude::*;
+/// use openmls_basic_credential::SignatureKeyPair;
+/// use openmls_rust_crypto::OpenMlsRustCrypto;
+///
+/// let provider = OpenMlsRustCrypto::default();
+/// let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+/// let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+/// let credential_with_key = CredentialWithKey {
+///     credential: BasicCredential::new(b"Alice".to_vec()).into(),
+///     signature_key: signer.public().into(),
+/// };
+///
+/// let mut pool = KeyPackagePool::new(ciphersuite, credential_with_key);
+/// pool.replenish(&provider, &signer, 10).unwrap();
+/// let key_packages = pool.take_unpublished(&provider).unwrap();
+/// assert_eq!(key_packages.len(), 10);
+/// // Upload the key packages to the delivery service ...
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyPackagePool {
+    ciphersuite: Ciphersuite,
+    credential_with_key: CredentialWithKey,
+    key_package_builder: KeyPackageBuilder,
+    lifetime_seconds: Option<u64>,
+    rotation_margin_seconds: u64,
+    entries: Vec<PoolEntry>,
+}
+
+impl KeyPackagePool {
+    /// Create a new, empty pool for key packages with the given ciphersuite
+    /// and credential.
+    pub fn new(ciphersuite: Ciphersuite, credential_with_key: CredentialWithKey) -> Self {
+        Self {
+            ciphersuite,
+            credential_with_key,
+            key_package_builder: KeyPackageBuilder::new(),
+            lifetime_seconds: None,
+            rotation_margin_seconds: DEFAULT_ROTATION_MARGIN_SECONDS,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Use the given [`KeyPackageBuilder`] as a template for new key packages,
+    /// e.g. to set capabilities or extensions.
+    ///
+    /// The lifetime set on the builder is ignored. Use
+    /// [`KeyPackagePool::with_lifetime()`] instead.
+    pub fn with_key_package_builder(mut self, key_package_builder: KeyPackageBuilder) -> Self {
+        self.key_package_builder = key_package_builder;
+        self
+    }
+
+    /// Set the lifetime (in seconds) of new key packages. If not set, the
+    /// default lifetime of key packages is used.
+    pub fn with_lifetime(mut self, seconds: u64) -> Self {
+        self.lifetime_seconds = Some(seconds);
+        self
+    }
+
+    /// Set the amount of time (in seconds) before the end of their lifetime at
+    /// which key packages are rotated. The default is 7 days.
+    pub fn with_rotation_margin(mut self, seconds: u64) -> Self {
+        self.rotation_margin_seconds = seconds;
+        self
+    }
+
+    /// Returns the number of key packages in the pool.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no key packages in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of key packages in the pool that have not been taken
+    /// with [`KeyPackagePool::take_unpublished()`] yet.
+    pub fn unpublished_len(&self) -> usize {
+        self.entries.iter().filter(|entry| !entry.published).count()
+    }
+
+    /// Forget key packages that have been consumed and rotate key packages
+    /// that are about to expire.
+    ///
+    /// A key package is considered consumed if it is no longer in the key
+    /// store, which is the case after a [`Welcome`](crate::messages::Welcome)
+    /// addressed to it was processed. Key packages that expire within the
+    /// rotation margin are deleted from the key store.
+    ///
+    /// Returns the references of all key packages that were removed from the
+    /// pool. Published ones among them should also be removed from the
+    /// delivery service.
+    pub fn prune<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+    ) -> Result<Vec<KeyPackageRef>, KeyPackagePoolError<Provider::StorageError>> {
+        let mut removed = Vec::new();
+        let mut index = 0;
+        while index < self.entries.len() {
+            let entry = &self.entries[index];
+            let stored: Option<KeyPackageBundle> = provider
+                .storage()
+                .key_package(&entry.hash_ref)
+                .map_err(KeyPackagePoolError::StorageError)?;
+            let consumed = stored.is_none();
+            if !consumed
+                && !entry.lifetime.expires_within_with_time_provider(
+                    self.rotation_margin_seconds,
+                    time_provider(provider.crypto()),
+                )
+            {
+                index += 1;
+                continue;
+            }
+            if !consumed {
+                provider
+                    .storage()
+                    .delete_key_package(&entry.hash_ref)
+                    .map_err(KeyPackagePoolError::StorageError)?;
+            }
+            removed.push(self.entries.remove(index).hash_ref);
+        }
+
+        Ok(removed)
+    }
+
+    /// Prune the pool (see [`KeyPackagePool::prune()`]) and generate new key
+    /// packages until it contains `n` key packages.
+    ///
+    /// New key packages are stored in the key store and are unpublished.
+    /// Returns the number of key packages that were generated.
+    pub fn replenish<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+        signer: &impl Signer,
+        n: usize,
+    ) -> Result<usize, KeyPackagePoolError<Provider::StorageError>> {
+        self.prune(provider)?;
+
+        let missing = n.saturating_sub(self.entries.len());
+        for _ in 0..missing {
+            let lifetime = Lifetime::new_with_time_provider(
+                self.lifetime_seconds
+                    .unwrap_or(DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS),
+                time_provider(provider.crypto()),
+            )
+            .map_err(|_| KeyPackageNewError::TimeUnavailable)?;
+            let key_package_bundle = self
+                .key_package_builder
+                .clone()
+                .key_package_lifetime(lifetime)
+                .build(
+                    self.ciphersuite,
+                    provider,
+                    signer,
+                    self.credential_with_key.clone(),
+                )?;
+            self.entries.push(PoolEntry {
+                hash_ref: key_package_bundle
+                    .key_package()
+                    .hash_ref(provider.crypto())?,
+                lifetime,
+                published: false,
+            });
+        }
+
+        Ok(missing)
+    }
+
+    /// Returns all key packages that have not been published yet and marks
+    /// them as published.
+    pub fn take_unpublished<Provider: OpenMlsProvider>(
+        &mut self,
+        provider: &Provider,
+    ) -> Result<Vec<KeyPackage>, KeyPackagePoolError<Provider::StorageError>> {
+        let mut key_packages = Vec::new();
+        for entry in self.entries.iter_mut().filter(|entry| !entry.published) {
+            let stored: Option<KeyPackageBundle> = provider
+                .storage()
+                .key_package(&entry.hash_ref)
+                .map_err(KeyPackagePoolError::StorageError)?;
+            // Key packages that are gone are forgotten with the next prune.
+            if let Some(key_package_bundle) = stored {
+                key_packages.push(key_package_bundle.key_package);
+                entry.published = true;
+            }
+        }
+
+        Ok(key_packages)
+    }
+}
@@ -1,10 +1,13 @@
 use crate::test_utils::*;
 use openmls_basic_credential::SignatureKeyPair;
-use openmls_traits::prelude::*;
+use openmls_traits::{prelude::*, storage::CURRENT_VERSION, types::VerifiableCiphersuite};
 
-use tls_codec::Deserialize;
+use tls_codec::{Deserialize, Serialize};
 
-use crate::{extensions::*, key_packages::*, storage::OpenMlsProvider};
+use crate::{
+    ciphersuite::CiphersuiteRegistry, extensions::*, group::MlsGroup, key_packages::*,
+    storage::OpenMlsProvider,
+};
 
 /// Helper function to generate key packages
 pub(crate) fn key_package(
@@ -54,6 +57,31 @@ fn serialization() {
             .expect("An unexpected error occurred."),
     );
     assert_eq!(key_package.key_package(), &decoded_key_package);
+
+    // The cached hash reference is not part of the encoding and matches the
+    // one computed for the decoded key package.
+    let hash_ref = key_package
+        .key_package()
+        .hash_ref(provider.crypto())
+        .unwrap();
+    assert_eq!(
+        key_package
+            .key_package()
+            .tls_serialize_detached()
+            .expect("An unexpected error occurred."),
+        encoded
+    );
+    assert_eq!(
+        decoded_key_package.hash_ref(provider.crypto()).unwrap(),
+        hash_ref
+    );
+    assert_eq!(
+        key_package
+            .key_package()
+            .hash_ref(provider.crypto())
+            .unwrap(),
+        hash_ref
+    );
 }
 
 #[openmls_test::openmls_test]
@@ -135,6 +163,33 @@ fn key_package_validation() {
     assert_eq!(err, KeyPackageVerifyError::InitKeyEqualsEncryptionKey);
 }
 
+/// Test that key packages with duplicate extension types are rejected when
+/// they are deserialized, both in the key package and in its leaf node.
+#[openmls_test::openmls_test]
+fn key_package_duplicate_extensions() {
+    let (key_package_orig, _, _) = key_package(ciphersuite, provider);
+
+    let mut franken_key_package =
+        frankenstein::FrankenKeyPackage::from(key_package_orig.key_package().clone());
+    franken_key_package.extensions = vec![
+        frankenstein::FrankenExtension::LastResort,
+        frankenstein::FrankenExtension::LastResort,
+    ];
+    let serialized = franken_key_package.tls_serialize_detached().unwrap();
+    KeyPackageIn::tls_deserialize_exact(serialized)
+        .expect_err("deserialized a key package with duplicate extension types");
+
+    let mut franken_key_package =
+        frankenstein::FrankenKeyPackage::from(key_package_orig.key_package().clone());
+    franken_key_package.leaf_node.extensions = vec![
+        frankenstein::FrankenExtension::Unknown(0xff00, vec![0].into()),
+        frankenstein::FrankenExtension::Unknown(0xff00, vec![1].into()),
+    ];
+    let serialized = franken_key_package.tls_serialize_detached().unwrap();
+    KeyPackageIn::tls_deserialize_exact(serialized)
+        .expect_err("deserialized a leaf node with duplicate extension types");
+}
+
 /// Test that a key package is correctly built with a last resort extension when
 /// the last resort flag is set during the build process.
 #[openmls_test::openmls_test]
@@ -157,6 +212,18 @@ fn last_resort_key_package() {
         .expect("An unexpected error occurred.");
     assert!(key_package.key_package().last_resort());
 
+    // the last resort extension type is added to the capabilities, so that the
+    // key package is valid
+    assert!(key_package
+        .key_package()
+        .leaf_node()
+        .capabilities()
+        .extensions()
+        .contains(&ExtensionType::LastResort));
+    let kpi = KeyPackageIn::from(key_package.key_package().clone());
+    kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+        .expect("The last resort key package must be valid.");
+
     // build with empty extensions
     let key_package = KeyPackage::builder()
         .key_package_extensions(Extensions::empty())
@@ -192,3 +259,598 @@ fn last_resort_key_package() {
         .expect("An unexpected error occurred.");
     assert!(key_package.key_package().last_resort());
 }
+
+/// Test that a key package with GREASE values in its capabilities is valid and
+/// can be added to a group.
+#[openmls_test::openmls_test]
+fn grease_key_package() {
+    let credential = Credential::from(BasicCredential::new(b"Sasha".to_vec()));
+    let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+
+    let key_package = KeyPackage::builder()
+        .with_grease()
+        .build(
+            ciphersuite,
+            provider,
+            &signature_keys,
+            CredentialWithKey {
+                signature_key: signature_keys.to_public_vec().into(),
+                credential,
+            },
+        )
+        .expect("An unexpected error occurred.");
+
+    let capabilities = key_package.key_package().leaf_node().capabilities();
+    assert!(capabilities
+        .extensions()
+        .iter()
+        .any(|&extension_type| matches!(extension_type, ExtensionType::Unknown(_))));
+
+    let kpi = KeyPackageIn::from(key_package.key_package().clone());
+    kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+        .expect("GREASE values must be ignored during validation");
+
+    // Add the key package to a group.
+    let (alice_credential_with_key, alice_signer) = crate::credentials::test_utils::new_credential(
+        provider,
+        b"Alice",
+        ciphersuite.signature_algorithm(),
+    );
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .build(provider, &alice_signer, alice_credential_with_key)
+        .expect("An unexpected error occurred.");
+    alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[key_package.key_package().clone()],
+        )
+        .expect("Could not add member with GREASE capabilities.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+}
+
+/// Test that the [`KeyPackageBundleBuilder`] builds valid key packages with the
+/// configured parameters, and rejects invalid configurations.
+#[openmls_test::openmls_test]
+fn key_package_bundle_builder() {
+    let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        signature_key: signature_keys.to_public_vec().into(),
+        credential: BasicCredential::new(b"Sasha".to_vec()).into(),
+    };
+    let capabilities = Capabilities::new(
+        None,
+        None,
+        Some(&[ExtensionType::LastResort, ExtensionType::Unknown(0xff00)]),
+        None,
+        None,
+    );
+    let lifetime = Lifetime::new(60 * 60 * 24);
+
+    let key_package_bundle = KeyPackageBundle::builder()
+        .ciphersuite(ciphersuite)
+        .credential_with_key(credential_with_key.clone())
+        .capabilities(capabilities.clone())
+        .lifetime(lifetime)
+        .application_id(b"application id")
+        .leaf_node_extension(Extension::Unknown(0xff00, UnknownExtension(vec![1])))
+        .mark_as_last_resort()
+        .build(provider, &signature_keys)
+        .expect("An unexpected error occurred.");
+
+    let key_package = key_package_bundle.key_package();
+    assert_eq!(key_package.ciphersuite(), ciphersuite);
+    assert_eq!(key_package.life_time(), &lifetime);
+    assert!(key_package.last_resort());
+    assert_eq!(key_package.leaf_node().capabilities(), &capabilities);
+    assert_eq!(
+        key_package.leaf_node().credential(),
+        &credential_with_key.credential
+    );
+    assert_eq!(
+        key_package
+            .leaf_node()
+            .extensions()
+            .application_id()
+            .map(|e| e.as_slice()),
+        Some(b"application id" as &[u8])
+    );
+    assert!(key_package
+        .leaf_node()
+        .extensions()
+        .unknown(0xff00)
+        .is_some());
+
+    let kpi = KeyPackageIn::from(key_package.clone());
+    kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+        .expect("The key package must be valid.");
+
+    // The key package bundle is stored.
+    let stored: Option<KeyPackageBundle> = provider
+        .storage()
+        .key_package(&key_package.hash_ref(provider.crypto()).unwrap())
+        .unwrap();
+    assert!(stored.is_some());
+
+    // Missing parameters
+    let err = KeyPackageBundle::builder()
+        .credential_with_key(credential_with_key.clone())
+        .build(provider, &signature_keys)
+        .expect_err("Built a key package without a ciphersuite.");
+    assert_eq!(err, KeyPackageNewError::MissingCiphersuite);
+    let err = KeyPackageBundle::builder()
+        .ciphersuite(ciphersuite)
+        .build(provider, &signature_keys)
+        .expect_err("Built a key package without a credential.");
+    assert_eq!(err, KeyPackageNewError::MissingCredential);
+
+    // Extensions that are not valid where they are added
+    let err = KeyPackageBundle::builder()
+        .ciphersuite(ciphersuite)
+        .credential_with_key(credential_with_key.clone())
+        .key_package_extension(Extension::ApplicationId(ApplicationIdExtension::new(
+            b"application id",
+        )))
+        .build(provider, &signature_keys)
+        .expect_err("Built a key package with an application id key package extension.");
+    assert_eq!(
+        err,
+        KeyPackageNewError::InvalidExtension(ExtensionType::ApplicationId)
+    );
+
+    // Extensions that are not in the capabilities
+    let err = KeyPackageBundle::builder()
+        .ciphersuite(ciphersuite)
+        .credential_with_key(credential_with_key.clone())
+        .leaf_node_extension(Extension::Unknown(0xff01, UnknownExtension(vec![1])))
+        .build(provider, &signature_keys)
+        .expect_err("Built a key package with an extension not in the capabilities.");
+    assert_eq!(
+        err,
+        KeyPackageNewError::ExtensionNotInCapabilities(ExtensionType::Unknown(0xff01))
+    );
+
+    // Ciphersuite that is not in the capabilities
+    let other_ciphersuite = match ciphersuite {
+        Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519 => {
+            Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519
+        }
+        _ => Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+    };
+    let capabilities = Capabilities::new(None, Some(&[other_ciphersuite]), None, None, None);
+    let err = KeyPackageBundle::builder()
+        .ciphersuite(ciphersuite)
+        .credential_with_key(credential_with_key)
+        .capabilities(capabilities)
+        .build(provider, &signature_keys)
+        .expect_err("Built a key package with a ciphersuite not in the capabilities.");
+    assert_eq!(err, KeyPackageNewError::CiphersuiteNotInCapabilities);
+}
+
+/// Test that the [`KeyPackagePool`] replenishes consumed and expiring key
+/// packages and tracks which key packages have been published.
+#[openmls_test::openmls_test]
+fn key_package_pool() {
+    let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        signature_key: signature_keys.to_public_vec().into(),
+        credential: BasicCredential::new(b"Sasha".to_vec()).into(),
+    };
+
+    let mut pool = KeyPackagePool::new(ciphersuite, credential_with_key.clone());
+    assert!(pool.is_empty());
+    assert_eq!(pool.replenish(provider, &signature_keys, 3).unwrap(), 3);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.unpublished_len(), 3);
+
+    let published = pool.take_unpublished(provider).unwrap();
+    assert_eq!(published.len(), 3);
+    assert_eq!(pool.unpublished_len(), 0);
+    assert!(pool.take_unpublished(provider).unwrap().is_empty());
+    for key_package in &published {
+        assert_eq!(key_package.ciphersuite(), ciphersuite);
+        let kpi = KeyPackageIn::from(key_package.clone());
+        kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+            .expect("The key package must be valid.");
+    }
+
+    // The pool is full.
+    assert_eq!(pool.replenish(provider, &signature_keys, 3).unwrap(), 0);
+
+    // A key package is consumed, e.g. when joining a group.
+    let consumed = published[0].hash_ref(provider.crypto()).unwrap();
+    openmls_traits::storage::StorageProvider::<CURRENT_VERSION>::delete_key_package(
+        provider.storage(),
+        &consumed,
+    )
+    .unwrap();
+    assert_eq!(pool.replenish(provider, &signature_keys, 3).unwrap(), 1);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.unpublished_len(), 1);
+    let new_key_packages = pool.take_unpublished(provider).unwrap();
+    assert_eq!(new_key_packages.len(), 1);
+    assert!(!published.contains(&new_key_packages[0]));
+
+    // Key packages that expire within the rotation margin are rotated.
+    let mut pool = KeyPackagePool::new(ciphersuite, credential_with_key)
+        .with_lifetime(60)
+        .with_rotation_margin(60 * 60);
+    assert_eq!(pool.replenish(provider, &signature_keys, 2).unwrap(), 2);
+    let expiring = pool.take_unpublished(provider).unwrap();
+    let removed = pool.prune(provider).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert!(pool.is_empty());
+    for key_package in expiring {
+        let hash_ref = key_package.hash_ref(provider.crypto()).unwrap();
+        assert!(removed.contains(&hash_ref));
+        let stored: Option<KeyPackageBundle> = provider.storage().key_package(&hash_ref).unwrap();
+        assert!(stored.is_none());
+    }
+}
+
+/// Test that key packages can be created and validated with a custom
+/// [`TimeProvider`].
+#[openmls_test::openmls_test]
+fn key_package_time_provider() {
+    let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let now = SystemTimeProvider.now().unwrap();
+    let key_package = KeyPackage::builder()
+        .key_package_lifetime(
+            Lifetime::new_with_time_provider(60, &FixedTimeProvider(Some(now))).unwrap(),
+        )
+        .build(
+            ciphersuite,
+            provider,
+            &signature_keys,
+            CredentialWithKey {
+                signature_key: signature_keys.to_public_vec().into(),
+                credential: BasicCredential::new(b"Sasha".to_vec()).into(),
+            },
+        )
+        .expect("An unexpected error occurred.");
+
+    let kpi = KeyPackageIn::from(key_package.key_package().clone());
+    kpi.clone()
+        .validate_with_time_provider(
+            provider.crypto(),
+            ProtocolVersion::Mls10,
+            &FixedTimeProvider(Some(now + 30)),
+        )
+        .expect("The key package must be valid.");
+    let err = kpi
+        .validate_with_time_provider(
+            provider.crypto(),
+            ProtocolVersion::Mls10,
+            &FixedTimeProvider(Some(now + 120)),
+        )
+        .expect_err("The key package must be expired.");
+    assert_eq!(err, KeyPackageVerifyError::InvalidLifetime);
+}
+
+/// A clock for the [`TimeProvider`] of a provider that can be moved forward.
+#[derive(Debug, Clone, Default)]
+struct TestClock(std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+impl TestClock {
+    fn set(&self, now: u64) {
+        self.0.store(now, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+impl TimeProvider for TestClock {
+    fn now(&self) -> Option<u64> {
+        Some(self.0.load(std::sync::atomic::Ordering::Relaxed))
+    }
+}
+
+/// Test that key packages are created, validated and rotated at the time of
+/// the [`TimeProvider`] of the provider rather than the system clock.
+#[test]
+fn provider_time_provider() {
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let now = 1_000_000_000;
+    let clock = TestClock::default();
+    clock.set(now);
+    let provider =
+        &openmls_test_crypto::OpenMlsTestCrypto::default().with_time_provider(clock.clone());
+    let system_provider = &openmls_test_crypto::OpenMlsTestCrypto::default();
+
+    let (alice_credential, alice_signer) = crate::credentials::test_utils::new_credential(
+        provider,
+        b"Alice",
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential, bob_signer) = crate::credentials::test_utils::new_credential(
+        provider,
+        b"Bob",
+        ciphersuite.signature_algorithm(),
+    );
+
+    // The lifetime of a new key package starts 1h before the time of the
+    // provider.
+    let bob_key_package = KeyPackage::builder()
+        .key_package_lifetime_duration(60 * 60 * 24)
+        .build(ciphersuite, provider, &bob_signer, bob_credential.clone())
+        .unwrap();
+    let lifetime = bob_key_package.key_package().life_time();
+    assert_eq!(lifetime.not_before(), now - 60 * 60);
+    assert_eq!(lifetime.not_after(), now + 60 * 60 * 24);
+
+    // The key package is valid at the time of the provider, but not at the
+    // time of the system clock.
+    let kpi = KeyPackageIn::from(bob_key_package.key_package().clone());
+    kpi.clone()
+        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .expect("The key package must be valid.");
+    assert_eq!(
+        kpi.validate(system_provider.crypto(), ProtocolVersion::Mls10),
+        Err(KeyPackageVerifyError::InvalidLifetime)
+    );
+
+    // Group validation uses the time of the provider as well.
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .build(provider, &alice_signer, alice_credential.clone())
+        .unwrap();
+    alice_group
+        .propose_add_member(provider, &alice_signer, bob_key_package.key_package())
+        .expect("The key package must be valid at the time of the provider.");
+    clock.set(now + 60 * 60 * 24 * 2);
+    assert!(matches!(
+        alice_group
+            .commit_to_pending_proposals(provider, &alice_signer)
+            .expect_err("The key package must be expired."),
+        crate::group::CommitToPendingProposalsError::CreateCommitError(
+            crate::group::CreateCommitError::ProposalValidationError(
+                crate::group::ProposalValidationError::LeafNodeValidation(
+                    crate::treesync::errors::LeafNodeValidationError::Lifetime(_)
+                )
+            )
+        )
+    ));
+
+    // The key package pool rotates key packages that expire at the time of
+    // the provider.
+    let mut pool = KeyPackagePool::new(ciphersuite, bob_credential)
+        .with_lifetime(60 * 60 * 24)
+        .with_rotation_margin(60 * 60);
+    assert_eq!(pool.replenish(provider, &bob_signer, 1).unwrap(), 1);
+    assert!(pool.prune(provider).unwrap().is_empty());
+    clock.set(now + 60 * 60 * 24 * 3);
+    assert_eq!(pool.prune(provider).unwrap().len(), 1);
+    assert!(pool.is_empty());
+}
+
+/// Test that the duration of the lifetime of new key packages can be set.
+#[openmls_test::openmls_test]
+fn key_package_lifetime_duration() {
+    let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        signature_key: signature_keys.to_public_vec().into(),
+        credential: BasicCredential::new(b"Sasha".to_vec()).into(),
+    };
+    let lifetime = |builder: KeyPackageBuilder| {
+        *builder
+            .build(
+                ciphersuite,
+                provider,
+                &signature_keys,
+                credential_with_key.clone(),
+            )
+            .expect("An unexpected error occurred.")
+            .key_package()
+            .life_time()
+    };
+
+    // The lifetime starts 1h in the past.
+    let short = lifetime(KeyPackage::builder().key_package_lifetime_duration(60));
+    assert_eq!(short.not_after() - short.not_before(), 60 + 60 * 60);
+    assert!(short.is_valid());
+
+    // An explicit lifetime takes precedence.
+    let explicit =
+        Lifetime::new_with_time_provider(60, &FixedTimeProvider(Some(1_000_000))).unwrap();
+    let built = lifetime(
+        KeyPackage::builder()
+            .key_package_lifetime(explicit)
+            .key_package_lifetime_duration(120),
+    );
+    assert_eq!(built, explicit);
+}
+
+/// Test that key packages can be verified without any group context.
+#[openmls_test::openmls_test]
+fn verify_standalone() {
+    let (key_package_bundle, credential, signer) = key_package(ciphersuite, provider);
+    let key_package = key_package_bundle.key_package().clone();
+
+    let verified = KeyPackage::verify_standalone(key_package.clone().into(), provider.crypto())
+        .expect("The key package must be valid.");
+    assert_eq!(verified, key_package);
+
+    // A lifetime range that is too long
+    let key_package = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new(60 * 60 * 24 * 365 * 10))
+        .build(
+            ciphersuite,
+            provider,
+            &signer,
+            CredentialWithKey {
+                credential,
+                signature_key: signer.to_public_vec().into(),
+            },
+        )
+        .expect("An unexpected error occurred.")
+        .key_package()
+        .clone();
+    let err = KeyPackage::verify_standalone(key_package.into(), provider.crypto())
+        .expect_err("A key package with a too long lifetime range must be rejected.");
+    assert_eq!(err, KeyPackageVerifyError::InvalidLifetimeRange);
+}
+
+/// Test that the common capabilities of key packages are computed.
+#[openmls_test::openmls_test]
+fn common_capabilities() {
+    let (_, credential, signer) = key_package(ciphersuite, provider);
+    let key_package_with_extensions = |extensions: &[ExtensionType]| {
+        KeyPackage::builder()
+            .leaf_node_capabilities(Capabilities::new(
+                None,
+                Some(&[ciphersuite]),
+                Some(extensions),
+                None,
+                None,
+            ))
+            .build(
+                ciphersuite,
+                provider,
+                &signer,
+                CredentialWithKey {
+                    credential: credential.clone(),
+                    signature_key: signer.to_public_vec().into(),
+                },
+            )
+            .expect("An unexpected error occurred.")
+            .key_package()
+            .clone()
+    };
+    let alice_key_package =
+        key_package_with_extensions(&[ExtensionType::Unknown(0xff00), ExtensionType::LastResort]);
+    let bob_key_package = key_package_with_extensions(&[ExtensionType::LastResort]);
+
+    let common = KeyPackage::common_capabilities([&alice_key_package, &bob_key_package])
+        .expect("There must be common capabilities.");
+    assert_eq!(common.ciphersuites(), &[ciphersuite.into()]);
+    assert_eq!(common.extensions(), &[ExtensionType::LastResort]);
+    assert_eq!(common.credentials(), &[CredentialType::Basic]);
+
+    assert_eq!(KeyPackage::common_capabilities(&[]), None);
+}
+
+#[openmls_test::openmls_test]
+fn ciphersuite_registry() {
+    let registry = CiphersuiteRegistry::new(provider.crypto());
+    assert_eq!(
+        registry.ciphersuites(),
+        provider.crypto().supported_ciphersuites().as_slice()
+    );
+    assert!(registry.supports(ciphersuite));
+    assert!(registry.supports_signature_scheme(ciphersuite.signature_algorithm()));
+    assert!(registry
+        .algorithms()
+        .hpke_kems()
+        .contains(&ciphersuite.hpke_kem_algorithm()));
+
+    // Without explicit capabilities, exactly the ciphersuites of the provider
+    // are advertised.
+    let (key_package, credential, signer) = key_package(ciphersuite, provider);
+    let advertised = key_package
+        .key_package()
+        .leaf_node()
+        .capabilities()
+        .ciphersuites()
+        .to_vec();
+    let supported = registry
+        .ciphersuites()
+        .iter()
+        .map(|&ciphersuite| VerifiableCiphersuite::from(ciphersuite))
+        .collect::<Vec<_>>();
+    assert_eq!(advertised, supported);
+
+    // Ciphersuites the provider doesn't implement are rejected.
+    let unsupported = Ciphersuite::MLS_256_DHKEMX448_AES256GCM_SHA512_Ed448;
+    if registry.supports(unsupported) {
+        return;
+    }
+    let err = KeyPackageBundle::builder()
+        .ciphersuite(unsupported)
+        .credential_with_key(CredentialWithKey {
+            credential,
+            signature_key: signer.to_public_vec().into(),
+        })
+        .build(provider, &signer)
+        .expect_err("built a key package with an unsupported ciphersuite");
+    assert_eq!(err, KeyPackageNewError::UnsupportedCiphersuite);
+}
+
+#[cfg(feature = "json")]
+#[openmls_test::openmls_test]
+fn json_serialization() {
+    let (key_package_bundle, _, _) = key_package(ciphersuite, provider);
+    let key_package = key_package_bundle.key_package();
+
+    let json = key_package
+        .to_json()
+        .expect("Could not serialize key package.");
+    let key_package_in =
+        KeyPackageIn::from_json(&json).expect("Could not deserialize key package.");
+    let decoded = key_package_in
+        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .expect("The key package must be valid.");
+    assert_eq!(&decoded, key_package);
+    assert_eq!(
+        decoded.hash_ref(provider.crypto()).unwrap(),
+        key_package.hash_ref(provider.crypto()).unwrap()
+    );
+
+    let json = key_package_bundle
+        .to_json()
+        .expect("Could not serialize key package bundle.");
+    let decoded =
+        KeyPackageBundle::from_json(&json).expect("Could not deserialize key package bundle.");
+    assert_eq!(decoded.key_package(), key_package);
+    assert_eq!(
+        decoded.init_private_key(),
+        key_package_bundle.init_private_key()
+    );
+}
+
+#[cfg(feature = "test-utils")]
+#[openmls_test::openmls_test]
+fn key_package_from_seed() {
+    let build = |seed: &[u8]| {
+        KeyPackage::builder()
+            .key_package_lifetime(
+                Lifetime::new_with_time_provider(
+                    60 * 60 * 24,
+                    &FixedTimeProvider(Some(1_700_000_000)),
+                )
+                .unwrap(),
+            )
+            .build_from_seed(
+                ciphersuite,
+                provider,
+                BasicCredential::new(b"Sasha".to_vec()).into(),
+                seed,
+            )
+            .expect("An unexpected error occurred.")
+    };
+
+    let (key_package_bundle, signer) = build(b"seed");
+    let (key_package_bundle_again, signer_again) = build(b"seed");
+    assert_eq!(
+        key_package_bundle
+            .key_package()
+            .tls_serialize_detached()
+            .unwrap(),
+        key_package_bundle_again
+            .key_package()
+            .tls_serialize_detached()
+            .unwrap()
+    );
+    assert_eq!(
+        key_package_bundle.init_private_key(),
+        key_package_bundle_again.init_private_key()
+    );
+    assert_eq!(signer.public(), signer_again.public());
+
+    let (other_key_package_bundle, other_signer) = build(b"other seed");
+    assert_ne!(
+        key_package_bundle.key_package().hpke_init_key(),
+        other_key_package_bundle.key_package().hpke_init_key()
+    );
+    assert_ne!(signer.public(), other_signer.public());
+}
@@ -133,7 +133,6 @@ impl DeserializeBytes for ProposalIn {
     {
         let mut bytes_ref = bytes;
         let proposal = ProposalIn::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[proposal.tls_serialized_len()..];
-        Ok((proposal, remainder))
+        Ok((proposal, bytes_ref))
     }
 }
@@ -14,9 +14,13 @@ use crate::{
         signable::{Signable, SignedStruct, Verifiable, VerifiedStruct},
         AeadKey, AeadNonce, Signature,
     },
-    extensions::Extensions,
+    extensions::{
+        errors::RatchetTreeError, Extensions, ExternalPubExtension, ExternalSendersExtension,
+        UnknownExtension,
+    },
     group::{GroupContext, GroupEpoch, GroupId},
     messages::ConfirmationTag,
+    treesync::RatchetTreeIn,
 };
 
 const SIGNATURE_GROUP_INFO_LABEL: &str = "GroupInfoTBS";
@@ -122,6 +126,37 @@ impl VerifiableGroupInfo {
     pub fn epoch(&self) -> GroupEpoch {
         self.payload.group_context.epoch()
     }
+
+    /// Get (unverified) group context extensions of the verifiable group info.
+    pub(crate) fn group_context_extensions(&self) -> &Extensions {
+        self.payload.group_context.extensions()
+    }
+
+    /// Get the (unverified) ratchet tree of the verifiable group info, if it
+    /// carries one in its extensions.
+    ///
+    /// Returns an error if the ratchet tree extension can't be decoded.
+    pub fn ratchet_tree(&self) -> Result<Option<RatchetTreeIn>, RatchetTreeError> {
+        self.payload.extensions.ratchet_tree_in()
+    }
+
+    /// Get the (unverified) [`ExternalPubExtension`] of the verifiable group
+    /// info, if there is any.
+    pub fn external_pub(&self) -> Option<&ExternalPubExtension> {
+        self.payload.extensions.external_pub()
+    }
+
+    /// Get the (unverified) [`ExternalSendersExtension`] of the group context
+    /// of the verifiable group info, if there is any.
+    pub fn external_senders(&self) -> Option<&ExternalSendersExtension> {
+        self.group_context_extensions().external_senders()
+    }
+
+    /// Get the (unverified) [`UnknownExtension`] of the verifiable group info
+    /// with the given type id, if there is any.
+    pub fn unknown_extension(&self, extension_type_id: u16) -> Option<&UnknownExtension> {
+        self.payload.extensions.unknown(extension_type_id)
+    }
 }
 
 #[cfg(test)]
@@ -185,6 +220,32 @@ impl GroupInfo {
         &self.signature
     }
 
+    /// Returns the ratchet tree carried in the [`GroupInfo`] extensions, if
+    /// there is any.
+    ///
+    /// Returns an error if the ratchet tree extension can't be decoded.
+    pub fn ratchet_tree(&self) -> Result<Option<RatchetTreeIn>, RatchetTreeError> {
+        self.payload.extensions.ratchet_tree_in()
+    }
+
+    /// Returns the [`ExternalPubExtension`] of the [`GroupInfo`], if there is
+    /// any.
+    pub fn external_pub(&self) -> Option<&ExternalPubExtension> {
+        self.payload.extensions.external_pub()
+    }
+
+    /// Returns the [`ExternalSendersExtension`] of the group context, if there
+    /// is any.
+    pub fn external_senders(&self) -> Option<&ExternalSendersExtension> {
+        self.payload.group_context.extensions().external_senders()
+    }
+
+    /// Returns the [`UnknownExtension`] of the [`GroupInfo`] with the given
+    /// type id, if there is any.
+    pub fn unknown_extension(&self, extension_type_id: u16) -> Option<&UnknownExtension> {
+        self.payload.extensions.unknown(extension_type_id)
+    }
+
     /// Returns the confirmation tag.
     pub(crate) fn confirmation_tag(&self) -> &ConfirmationTag {
         &self.payload.confirmation_tag
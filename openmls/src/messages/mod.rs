@@ -355,16 +355,17 @@ impl PathSecret {
         Ok(Self { path_secret })
     }
 
-    /// Encrypt the path secret under the given `HpkePublicKey` using the given
-    /// `group_context`.
+    /// Encrypt the path secret under each of the given `public_keys` using the
+    /// given `group_context`.
     pub(crate) fn encrypt(
         &self,
         crypto: &impl OpenMlsCrypto,
         ciphersuite: Ciphersuite,
-        public_key: &EncryptionKey,
+        public_keys: &[EncryptionKey],
         group_context: &[u8],
-    ) -> Result<HpkeCiphertext, LibraryError> {
-        public_key.encrypt(
+    ) -> Result<Vec<HpkeCiphertext>, LibraryError> {
+        EncryptionKey::encrypt_to_all(
+            public_keys,
             crypto,
             ciphersuite,
             group_context,
@@ -131,8 +131,7 @@ impl DeserializeBytes for ProposalType {
     {
         let mut bytes_ref = bytes;
         let proposal_type = ProposalType::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[proposal_type.tls_serialized_len()..];
-        Ok((proposal_type, remainder))
+        Ok((proposal_type, bytes_ref))
     }
 }
 
@@ -398,6 +397,13 @@ pub struct ReInitProposal {
     pub(crate) extensions: Extensions,
 }
 
+impl ReInitProposal {
+    /// Returns the ciphersuite of the new group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+}
+
 /// ExternalInit Proposal.
 ///
 /// An ExternalInit proposal is used by new members that want to join a group by using an external
@@ -4,8 +4,9 @@ use crate::{
     ciphersuite::signable::Verifiable,
     group::mls_group::tests_and_kats::utils::setup_alice_group,
     messages::group_info::{GroupInfo, VerifiableGroupInfo},
-    prelude::MlsMessageBodyOut,
+    prelude::{MlsMessageBodyOut, MlsMessageIn},
     test_utils::*,
+    treesync::RatchetTreeIn,
 };
 
 /// Tests the creation of an [UnverifiedGroupInfo] and verifies it was correctly signed.
@@ -28,7 +29,47 @@ fn export_group_info() {
         VerifiableGroupInfo::tls_deserialize(&mut serialized.as_slice()).unwrap()
     };
 
-    let _: GroupInfo = verifiable_group_info
+    let group_info: GroupInfo = verifiable_group_info
         .verify(provider.crypto(), &pk)
         .expect("signature verification should succeed");
+    assert!(group_info.extensions().external_pub().is_some());
+    assert!(group_info.extensions().ratchet_tree().is_some());
+
+    // The typed accessors return the same extensions.
+    assert_eq!(
+        group_info.external_pub(),
+        group_info.extensions().external_pub()
+    );
+    let ratchet_tree = group_info
+        .ratchet_tree()
+        .expect("error decoding the ratchet tree")
+        .expect("expected a ratchet tree");
+    assert_eq!(
+        ratchet_tree,
+        RatchetTreeIn::from(group_alice.export_ratchet_tree())
+    );
+    assert!(group_info.external_senders().is_none());
+    assert!(group_info.unknown_extension(0xff00).is_none());
+
+    // Without the ratchet tree, only the external public key is included.
+    let group_info_message = group_alice
+        .export_group_info(provider, &signer, false)
+        .unwrap();
+    let verifiable_group_info = {
+        let serialized = group_info_message.tls_serialize_detached().unwrap();
+        MlsMessageIn::tls_deserialize(&mut serialized.as_slice())
+            .unwrap()
+            .into_verifiable_group_info()
+            .expect("expected a group info")
+    };
+    assert_eq!(verifiable_group_info.group_id(), group_alice.group_id());
+    assert_eq!(verifiable_group_info.epoch(), group_alice.epoch());
+    assert!(verifiable_group_info.external_pub().is_some());
+    assert_eq!(verifiable_group_info.ratchet_tree(), Ok(None));
+
+    let group_info: GroupInfo = verifiable_group_info
+        .verify(provider.crypto(), &pk)
+        .expect("signature verification should succeed");
+    assert!(group_info.extensions().external_pub().is_some());
+    assert!(group_info.extensions().ratchet_tree().is_none());
 }
@@ -7,10 +7,14 @@ use crate::{
     ciphersuite::{
         hash_ref::KeyPackageRef, hpke, signable::Signable, AeadKey, AeadNonce, Mac, Secret,
     },
-    extensions::Extensions,
+    extensions::{Extension, ExtensionType, Extensions, UnknownExtension},
     group::{
         errors::WelcomeError, mls_group::tests_and_kats::utils::setup_client, GroupContext,
-        GroupId, MlsGroup, MlsGroupCreateConfig, ProcessedWelcome, StagedWelcome,
+        GroupEpoch, GroupId, Member, MlsGroup, MlsGroupCreateConfig, MlsGroupJoinConfig,
+        ProcessedWelcome, StagedWelcome, WelcomePreview,
+    },
+    key_packages::{
+        KeyPackage, KeyPackageBundle, Lifetime, LifetimePolicy, SystemTimeProvider, TimeProvider,
     },
     messages::{
         group_info::{GroupInfoTBS, VerifiableGroupInfo},
@@ -20,7 +24,11 @@ use crate::{
         psk::{load_psks, store::ResumptionPskStore, PskSecret},
         KeySchedule,
     },
-    treesync::node::encryption_keys::EncryptionKeyPair,
+    test_utils::FixedTimeProvider,
+    treesync::{
+        errors::MissingCapabilities,
+        node::{encryption_keys::EncryptionKeyPair, leaf_node::Capabilities},
+    },
 };
 
 /// This test detects if the decryption of the encrypted group secrets fails due to a change in
@@ -371,6 +379,382 @@ fn test_welcome_processing() {
         .expect("Error creating group from a valid staged join.");
 }
 
+/// Test that a Welcome can be previewed without consuming the key package.
+#[openmls_test::openmls_test]
+fn test_welcome_preview() {
+    let group_id = GroupId::random(provider.rand());
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_signature_key) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_signature_key) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let bob_kp = bob_kpb.key_package();
+
+    // === Alice creates a group  and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        group_id,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_queued_message, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kp.clone()])
+        .expect("Could not add member to group.");
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    provider
+        .storage()
+        .write_key_package(&bob_kp.hash_ref(provider.crypto()).unwrap(), &bob_kpb)
+        .unwrap();
+
+    // Without the ratchet tree, the members are unknown.
+    let preview = WelcomePreview::new_from_welcome(provider, &welcome, None).unwrap();
+    assert_eq!(preview.group_id(), alice_group.group_id());
+    assert_eq!(preview.epoch(), alice_group.epoch());
+    assert_eq!(preview.ciphersuite(), ciphersuite);
+    assert_eq!(
+        preview.group_context_extensions(),
+        alice_group.export_group_context().extensions()
+    );
+    assert!(preview.members().is_none());
+
+    let ratchet_tree = alice_group.export_ratchet_tree().into();
+    let preview =
+        WelcomePreview::new_from_welcome(provider, &welcome, Some(&ratchet_tree)).unwrap();
+    let members: Vec<Member> = alice_group.members().collect();
+    assert_eq!(preview.members(), Some(members.as_slice()));
+
+    // The key package is still available to join the group.
+    let _group = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome,
+        Some(ratchet_tree),
+    )
+    .expect("Error creating staged join after previewing the welcome.")
+    .into_group(provider)
+    .expect("Error creating group from a valid staged join.");
+}
+
+/// Test that processing a Welcome for a group that already exists in storage
+/// fails without consuming the key package.
+#[openmls_test::openmls_test]
+fn test_welcome_for_existing_group() {
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_signature_key) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_signature_key) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let bob_kp = bob_kpb.key_package();
+
+    // === Alice creates a group  and adds Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_queued_message, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kp.clone()])
+        .expect("Could not add member to group.");
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    let _bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome.clone(),
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("Error creating staged join from a valid welcome.")
+    .into_group(provider)
+    .expect("Error creating group from a valid staged join.");
+
+    // Processing the Welcome again must not overwrite the existing group.
+    let hash_ref = bob_kp.hash_ref(provider.crypto()).unwrap();
+    provider
+        .storage()
+        .write_key_package(&hash_ref, &bob_kpb)
+        .unwrap();
+    let err = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect_err("Joined a group that already exists.");
+    assert!(matches!(err, WelcomeError::GroupAlreadyExists));
+
+    let key_package: Option<KeyPackageBundle> = provider.storage().key_package(&hash_ref).unwrap();
+    assert!(key_package.is_some());
+}
+
+/// Test that a client with multiple published key packages can join with
+/// whichever of them the Welcome is addressed to, and that only that key
+/// package is consumed.
+#[openmls_test::openmls_test]
+fn test_welcome_with_multiple_key_packages() {
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_signature_key) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_signature_key) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // Bob publishes a few more key packages.
+    let bob_key_package_bundles: Vec<KeyPackageBundle> = (0..3)
+        .map(|_| {
+            KeyPackageBundle::generate(
+                provider,
+                &bob_signer,
+                ciphersuite,
+                bob_credential_with_key.clone(),
+            )
+        })
+        .collect();
+    let hash_refs: Vec<KeyPackageRef> = bob_key_package_bundles
+        .iter()
+        .map(|kpb| kpb.key_package().hash_ref(provider.crypto()).unwrap())
+        .collect();
+
+    // === Alice creates a group and adds Bob using one of his key packages ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_queued_message, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package_bundles[1].key_package().clone()],
+        )
+        .expect("Could not add member to group.");
+
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    let bob_group = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect("Error creating staged join from a valid welcome.")
+    .into_group(provider)
+    .expect("Error creating group from a valid staged join.");
+    assert_eq!(
+        bob_group.own_leaf_node().unwrap().encryption_key(),
+        bob_key_package_bundles[1]
+            .key_package()
+            .leaf_node()
+            .encryption_key()
+    );
+
+    // Only the key package that was used is deleted.
+    for (i, hash_ref) in hash_refs.iter().enumerate() {
+        let key_package: Option<KeyPackageBundle> =
+            provider.storage().key_package(hash_ref).unwrap();
+        assert_eq!(key_package.is_some(), i != 1);
+    }
+}
+
+/// Test that a Welcome for a group with group context extensions our key
+/// package doesn't support is rejected with a report of the missing
+/// capabilities, and that the key package is not consumed.
+#[openmls_test::openmls_test]
+fn test_welcome_with_incompatible_key_package() {
+    const UNKNOWN_EXTENSION_TYPE: u16 = 0xff11;
+    let gc_extensions = Extensions::single(Extension::Unknown(
+        UNKNOWN_EXTENSION_TYPE,
+        UnknownExtension(vec![1, 2]),
+    ));
+    let capabilities = Capabilities::new(
+        None,
+        None,
+        Some(&[ExtensionType::Unknown(UNKNOWN_EXTENSION_TYPE)]),
+        None,
+        None,
+    );
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .with_group_context_extensions(gc_extensions)
+        .expect("error adding unknown extension to config")
+        .capabilities(capabilities)
+        .ciphersuite(ciphersuite)
+        .build();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_signature_key) =
+        setup_client("Alice", ciphersuite, provider);
+    // Bob's key package doesn't list the unknown extension in its capabilities.
+    let (_bob_credential, bob_kpb, _bob_signer, _bob_signature_key) =
+        setup_client("Bob", ciphersuite, provider);
+    let bob_kp = bob_kpb.key_package();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    // `add_members` refuses to add Bob, so the Welcome is built with the
+    // commit builder, like a client that doesn't check this would.
+    let welcome = alice_group
+        .commit_builder()
+        .propose_adds(Some(bob_kp.clone()))
+        .load_psks(provider.storage())
+        .expect("error loading psks")
+        .build(provider.rand(), provider.crypto(), &alice_signer, |_| true)
+        .expect("error building commit")
+        .stage_commit(provider)
+        .expect("error staging commit")
+        .to_welcome_msg()
+        .expect("no welcome for an add");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    let err = StagedWelcome::new_from_welcome(
+        provider,
+        mls_group_create_config.join_config(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .expect_err("Joined a group with an incompatible key package.");
+    assert_eq!(
+        err,
+        WelcomeError::IncompatibleKeyPackage(MissingCapabilities {
+            extension_types: vec![ExtensionType::Unknown(UNKNOWN_EXTENSION_TYPE)],
+            proposal_types: vec![],
+            credential_types: vec![],
+        })
+    );
+
+    let hash_ref = bob_kp.hash_ref(provider.crypto()).unwrap();
+    let key_package: Option<KeyPackageBundle> = provider.storage().key_package(&hash_ref).unwrap();
+    assert!(key_package.is_some());
+}
+
+/// Test that Welcomes addressed to an expired key package are rejected unless
+/// the lifetime policy of the join config tolerates enough clock skew, and
+/// that a Welcome with an old epoch can be rejected by the application.
+#[openmls_test::openmls_test]
+fn test_welcome_freshness() {
+    // Alice tolerates a minute of clock skew.
+    let mls_group_create_config = MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .lifetime_policy(LifetimePolicy::new().with_clock_skew(60))
+        .build();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_signature_key) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_signature_key) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // Bob's key package expired half a minute ago.
+    let now = SystemTimeProvider.now().unwrap();
+    let lifetime = Lifetime::new_with_time_provider(1, &FixedTimeProvider(Some(now - 31))).unwrap();
+    let bob_kpb = KeyPackage::builder()
+        .key_package_lifetime(lifetime)
+        .build(ciphersuite, provider, &bob_signer, bob_credential_with_key)
+        .expect("error building key package");
+    let bob_kp = bob_kpb.key_package();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_create_config,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_queued_message, welcome, _group_info) = alice_group
+        .add_members(provider, &alice_signer, &[bob_kp.clone()])
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    let strict_join_config = MlsGroupJoinConfig::builder()
+        .lifetime_policy(LifetimePolicy::default())
+        .build();
+    let Err(err) =
+        ProcessedWelcome::new_from_welcome(provider, &strict_join_config, welcome.clone())
+    else {
+        panic!("Processed a Welcome for an expired key package.");
+    };
+    assert_eq!(err, WelcomeError::KeyPackageExpired);
+
+    // The key package was not consumed.
+    let hash_ref = bob_kp.hash_ref(provider.crypto()).unwrap();
+    let key_package: Option<KeyPackageBundle> = provider.storage().key_package(&hash_ref).unwrap();
+    assert!(key_package.is_some());
+
+    // With enough skew, the key package is accepted.
+    let lenient_join_config = MlsGroupJoinConfig::builder()
+        .lifetime_policy(LifetimePolicy::new().with_clock_skew(60))
+        .build();
+    let processed_welcome =
+        ProcessedWelcome::new_from_welcome(provider, &lenient_join_config, welcome)
+            .expect("Error processing a Welcome within the allowed skew.");
+
+    let epoch = alice_group.epoch();
+    processed_welcome
+        .check_epoch::<()>(epoch)
+        .expect("The epoch of the Welcome is current.");
+    let err = processed_welcome
+        .check_epoch::<()>(GroupEpoch::from(epoch.as_u64() + 1))
+        .expect_err("The epoch of the Welcome is too old.");
+    assert_eq!(
+        err,
+        WelcomeError::StaleEpoch {
+            epoch,
+            min_epoch: GroupEpoch::from(epoch.as_u64() + 1),
+        }
+    );
+
+    let _bob_group = processed_welcome
+        .into_staged_welcome(provider, Some(alice_group.export_ratchet_tree().into()))
+        .expect("Error creating staged join from a valid welcome.")
+        .into_group(provider)
+        .expect("Error creating group from a valid staged join.");
+}
+
 #[test]
 fn invalid_welcomes() {
     // An almost good welcome message.
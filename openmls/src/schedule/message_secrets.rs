@@ -1,6 +1,9 @@
 //! This module defines the [`MessageSecrets`] struct that can be used for message decryption & verification
 
+use std::sync::Arc;
+
 use super::*;
+use crate::tree::{replay_cache::ReplayCache, secret_tree::SecretType, sender_ratchet::Generation};
 
 /// Combined message secrets that need to be stored for later decryption/verification
 #[derive(Serialize, Deserialize)]
@@ -10,8 +13,10 @@ pub(crate) struct MessageSecrets {
     sender_data_secret: SenderDataSecret,
     membership_key: MembershipKey,
     confirmation_key: ConfirmationKey,
-    serialized_context: Vec<u8>,
+    serialized_context: Arc<[u8]>,
     secret_tree: SecretTree,
+    #[serde(default)]
+    replay_cache: ReplayCache,
 }
 
 #[cfg(not(feature = "crypto-debug"))]
@@ -23,6 +28,7 @@ impl core::fmt::Debug for MessageSecrets {
             .field("confirmation_key", &"***")
             .field("serialized_context", &"***")
             .field("secret_tree", &"***")
+            .field("replay_cache", &self.replay_cache)
             .finish()
     }
 }
@@ -41,8 +47,9 @@ impl MessageSecrets {
             sender_data_secret,
             membership_key,
             confirmation_key,
-            serialized_context,
+            serialized_context: serialized_context.into(),
             secret_tree,
+            replay_cache: ReplayCache::default(),
         }
     }
 
@@ -66,10 +73,43 @@ impl MessageSecrets {
         self.serialized_context.as_ref()
     }
 
+    /// Get a shared reference to the message secrets's serialized context,
+    /// e.g. to keep it with an incoming message without copying it.
+    pub(crate) fn shared_serialized_context(&self) -> Arc<[u8]> {
+        self.serialized_context.clone()
+    }
+
+    /// Get a reference to the message secrets's secret tree.
+    #[cfg(feature = "memory-stats")]
+    pub(crate) fn secret_tree(&self) -> &SecretTree {
+        &self.secret_tree
+    }
+
     /// Get a mutable reference to the message secrets's secret tree.
     pub(crate) fn secret_tree_mut(&mut self) -> &mut SecretTree {
         &mut self.secret_tree
     }
+
+    /// Returns `true` if the given generation of the sender's ratchet was
+    /// already used to decrypt a message in this epoch.
+    pub(crate) fn is_replay(
+        &self,
+        sender: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: Generation,
+    ) -> bool {
+        self.replay_cache.contains(sender, secret_type, generation)
+    }
+
+    /// Marks the given generation of the sender's ratchet as consumed.
+    pub(crate) fn mark_consumed(
+        &mut self,
+        sender: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: Generation,
+    ) {
+        self.replay_cache.insert(sender, secret_type, generation)
+    }
 }
 
 // Test functions
@@ -82,7 +122,7 @@ impl MessageSecrets {
     #[cfg(test)]
     /// Update the message secrets's serialized context.
     pub(crate) fn set_serialized_context(&mut self, serialized_context: Vec<u8>) {
-        self.serialized_context = serialized_context;
+        self.serialized_context = serialized_context.into();
     }
 
     #[cfg(test)]
@@ -101,12 +141,13 @@ impl MessageSecrets {
             sender_data_secret: SenderDataSecret::random(ciphersuite, rng),
             membership_key: MembershipKey::random(ciphersuite, rng),
             confirmation_key: ConfirmationKey::random(ciphersuite, rng),
-            serialized_context: rng.random_vec(10).expect("Not enough randomness."),
+            serialized_context: rng.random_vec(10).expect("Not enough randomness.").into(),
             secret_tree: SecretTree::new(
                 EncryptionSecret::random(ciphersuite, rng),
                 TreeSize::new(10),
                 own_index,
             ),
+            replay_cache: ReplayCache::default(),
         }
     }
 
@@ -482,6 +482,33 @@ pub(crate) fn load_psks<'p, Storage: StorageProvider>(
     Ok(psk_bundles)
 }
 
+/// Returns the PSKs in `psk_ids` that are neither in the `resumption_psk_store`
+/// nor in the `storage`, or the error of the `storage` if reading a PSK fails.
+pub(crate) fn missing_psks<Storage: StorageProvider>(
+    storage: &Storage,
+    resumption_psk_store: &ResumptionPskStore,
+    psk_ids: &[PreSharedKeyId],
+) -> Result<Vec<PreSharedKeyId>, Storage::Error> {
+    let mut missing_psks = Vec::new();
+
+    for psk_id in psk_ids.iter() {
+        let available = match &psk_id.psk {
+            Psk::Resumption(resumption) => {
+                resumption_psk_store.get(resumption.psk_epoch()).is_some()
+            }
+            Psk::External(_) => {
+                let psk_bundle: Option<PskBundle> = storage.psk(psk_id.psk())?;
+                psk_bundle.is_some()
+            }
+        };
+        if !available {
+            missing_psks.push(psk_id.clone());
+        }
+    }
+
+    Ok(missing_psks)
+}
+
 /// This module contains a store that can hold a rollover list of resumption PSKs.
 pub mod store {
     use serde::{Deserialize, Serialize};
@@ -525,6 +552,22 @@ pub mod store {
             }
         }
 
+        /// Removes the entries of all epochs before `oldest_epoch` and returns
+        /// the removed epochs.
+        pub(crate) fn prune_before(&mut self, oldest_epoch: GroupEpoch) -> Vec<GroupEpoch> {
+            let (removed, mut kept): (Vec<_>, Vec<_>) = self
+                .resumption_psk
+                .drain(..)
+                .partition(|(epoch, _)| *epoch < oldest_epoch);
+            if !removed.is_empty() {
+                // Continue as if the remaining entries were added in order.
+                kept.sort_by_key(|(epoch, _)| *epoch);
+                self.cursor = kept.len();
+            }
+            self.resumption_psk = kept;
+            removed.into_iter().map(|(epoch, _)| epoch).collect()
+        }
+
         /// Searches an entry for a given epoch number and if found, returns the
         /// corresponding resumption psk.
         pub(crate) fn get(&self, epoch: GroupEpoch) -> Option<&ResumptionPskSecret> {
@@ -3,7 +3,11 @@
 //! See <https://github.com/mlswg/mls-implementations/blob/master/test-vectors.md>
 //! for more description on the test vectors.
 //!
-//! If values are not present, they are encoded as empty strings.
+//! If values are not present, they are encoded as empty strings. Vectors of
+//! other implementations may also omit them. Such vectors are checked with
+//! [`run_external_test_vector()`], for which absent secrets that are an input
+//! to the key schedule are zero and absent computed values are not checked.
+//! The vectors of OpenMLS are checked strictly with [`run_test_vector()`].
 
 use log::info;
 use openmls_traits::{random::OpenMlsRand, types::HpkeKeyPair, OpenMlsProvider};
@@ -21,6 +25,7 @@ use crate::{
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 struct Exporter {
     label: String,
     context: String,
@@ -29,6 +34,7 @@ struct Exporter {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 struct Epoch {
     // Chosen by the generator
     tree_hash: String,
@@ -274,10 +280,87 @@ fn read_test_vectors_key_schedule() {
     }
 }
 
+/// Vectors of other implementations may omit values. Computed values that are
+/// not present are only ignored for them.
+#[openmls_test::openmls_test]
+fn read_test_vectors_key_schedule_absent_values() {
+    for test_vector in key_schedule_vectors_with_absent_values() {
+        run_external_test_vector(test_vector, provider)
+            .expect("Error while checking key schedule test vector.");
+    }
+}
+
+/// The vectors of OpenMLS must not omit values.
+#[test]
+#[should_panic(expected = "Welcome secret mismatch")]
+fn read_test_vectors_key_schedule_absent_values_strict() {
+    let provider = OpenMlsRustCrypto::default();
+    for test_vector in key_schedule_vectors_with_absent_values() {
+        let _ = run_test_vector(test_vector, &provider);
+    }
+}
+
+/// Returns the key schedule vectors of OpenMLS without some of their values.
+#[cfg(test)]
+fn key_schedule_vectors_with_absent_values() -> Vec<KeyScheduleTestVector> {
+    let mut tests: serde_json::Value = read_json!("../../../../test_vectors/key-schedule.json");
+
+    for test_vector in tests.as_array_mut().unwrap() {
+        for epoch in test_vector["epochs"].as_array_mut().unwrap() {
+            let epoch = epoch.as_object_mut().unwrap();
+            epoch.remove("welcome_secret");
+            epoch.remove("exporter_secret");
+            epoch.remove("exporter");
+            epoch.insert("external_pub".to_string(), "".into());
+        }
+    }
+
+    serde_json::from_value(tests).unwrap()
+}
+
+/// Returns `true` if the `computed` value matches the `expected` hex value,
+/// or if the value is not present in the test vector and `allow_absent` is
+/// set.
+#[cfg(any(feature = "test-utils", test))]
+fn matches_expected(allow_absent: bool, expected: &str, computed: &[u8]) -> bool {
+    (allow_absent && expected.is_empty()) || hex_to_bytes(expected) == computed
+}
+
+/// Returns the secret encoded as hex. If `allow_absent` is set, the zero
+/// secret of the `ciphersuite` is returned if it is not present in the test
+/// vector.
+#[cfg(any(feature = "test-utils", test))]
+fn input_secret(allow_absent: bool, ciphersuite: Ciphersuite, hex: &str) -> Secret {
+    if allow_absent && hex.is_empty() {
+        Secret::zero(ciphersuite)
+    } else {
+        Secret::from_slice(&hex_to_bytes(hex))
+    }
+}
+
+/// Checks a test vector of OpenMLS. All values must be present.
 #[cfg(any(feature = "test-utils", test))]
 pub fn run_test_vector(
     test_vector: KeyScheduleTestVector,
     provider: &impl OpenMlsProvider,
+) -> Result<(), KsTestVectorError> {
+    check_test_vector(test_vector, provider, false)
+}
+
+/// Checks a test vector of another implementation, which may omit values.
+#[cfg(any(feature = "test-utils", test))]
+pub fn run_external_test_vector(
+    test_vector: KeyScheduleTestVector,
+    provider: &impl OpenMlsProvider,
+) -> Result<(), KsTestVectorError> {
+    check_test_vector(test_vector, provider, true)
+}
+
+#[cfg(any(feature = "test-utils", test))]
+fn check_test_vector(
+    test_vector: KeyScheduleTestVector,
+    provider: &impl OpenMlsProvider,
+    allow_absent: bool,
 ) -> Result<(), KsTestVectorError> {
     let ciphersuite = Ciphersuite::try_from(test_vector.cipher_suite).expect("Invalid ciphersuite");
     log::trace!("  {:?}", test_vector);
@@ -301,8 +384,11 @@ pub fn run_test_vector(
 
     for (epoch_ctr, epoch) in test_vector.epochs.iter().enumerate() {
         let tree_hash = hex_to_bytes(&epoch.tree_hash);
-        let secret = hex_to_bytes(&epoch.commit_secret);
-        let commit_secret = CommitSecret::from(PathSecret::from(Secret::from_slice(&secret)));
+        let commit_secret = CommitSecret::from(PathSecret::from(input_secret(
+            allow_absent,
+            ciphersuite,
+            &epoch.commit_secret,
+        )));
         log::trace!("    CommitSecret from tve {:?}", epoch.commit_secret);
 
         let confirmed_transcript_hash = hex_to_bytes(&epoch.confirmed_transcript_hash);
@@ -324,15 +410,15 @@ pub fn run_test_vector(
             &group_context.tls_serialize_detached().unwrap(),
         )
         .expect("Could not create JoinerSecret.");
-        if hex_to_bytes(&epoch.joiner_secret) != joiner_secret.as_slice() {
+        if !matches_expected(allow_absent, &epoch.joiner_secret, joiner_secret.as_slice()) {
             if cfg!(test) {
                 panic!("Joiner secret mismatch");
             }
             return Err(KsTestVectorError::JoinerSecretMismatch);
         }
 
-        let psk_secret_inner = Secret::from_slice(&hex_to_bytes(&epoch.psk_secret));
-        let psk_secret = PskSecret::from(psk_secret_inner);
+        let psk_secret =
+            PskSecret::from(input_secret(allow_absent, ciphersuite, &epoch.psk_secret));
 
         let mut key_schedule =
             KeySchedule::init(ciphersuite, provider.crypto(), &joiner_secret, psk_secret)
@@ -341,7 +427,11 @@ pub fn run_test_vector(
             .welcome(provider.crypto(), ciphersuite)
             .expect("An unexpected error occurred.");
 
-        if hex_to_bytes(&epoch.welcome_secret) != welcome_secret.as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.welcome_secret,
+            welcome_secret.as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Welcome secret mismatch");
             }
@@ -352,7 +442,11 @@ pub fn run_test_vector(
         let group_context_serialized = group_context
             .tls_serialize_detached()
             .expect("An unexpected error occurred.");
-        if group_context_serialized != expected_group_context {
+        if !matches_expected(
+            allow_absent,
+            &epoch.group_context,
+            &group_context_serialized,
+        ) {
             log::error!("  Group context mismatch");
             log::debug!("    Computed: {:x?}", group_context_serialized);
             log::debug!("    Expected: {:x?}", expected_group_context);
@@ -371,7 +465,7 @@ pub fn run_test_vector(
             .expect("An unexpected error occurred.");
 
         init_secret = epoch_secrets.init_secret().clone();
-        if hex_to_bytes(&epoch.init_secret) != init_secret.as_slice() {
+        if !matches_expected(allow_absent, &epoch.init_secret, init_secret.as_slice()) {
             log_crypto!(
                 debug,
                 "    Epoch secret mismatch: {:x?} != {:x?}",
@@ -383,52 +477,81 @@ pub fn run_test_vector(
             }
             return Err(KsTestVectorError::InitSecretMismatch);
         }
-        if hex_to_bytes(&epoch.sender_data_secret) != epoch_secrets.sender_data_secret().as_slice()
-        {
+        if !matches_expected(
+            allow_absent,
+            &epoch.sender_data_secret,
+            epoch_secrets.sender_data_secret().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Sender data secret mismatch");
             }
             return Err(KsTestVectorError::SenderDataSecretMismatch);
         }
-        if hex_to_bytes(&epoch.encryption_secret) != epoch_secrets.encryption_secret().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.encryption_secret,
+            epoch_secrets.encryption_secret().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Encryption secret mismatch");
             }
             return Err(KsTestVectorError::EncryptionSecretMismatch);
         }
-        if hex_to_bytes(&epoch.exporter_secret) != epoch_secrets.exporter_secret().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.exporter_secret,
+            epoch_secrets.exporter_secret().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Exporter secret mismatch");
             }
             return Err(KsTestVectorError::ExporterSecretMismatch);
         }
-        if hex_to_bytes(&epoch.epoch_authenticator)
-            != epoch_secrets.epoch_authenticator().as_slice()
-        {
+        if !matches_expected(
+            allow_absent,
+            &epoch.epoch_authenticator,
+            epoch_secrets.epoch_authenticator().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Epoch authenticator mismatch");
             }
             return Err(KsTestVectorError::EpochAuthenticatorMismatch);
         }
-        if hex_to_bytes(&epoch.external_secret) != epoch_secrets.external_secret().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.external_secret,
+            epoch_secrets.external_secret().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("External secret mismatch");
             }
             return Err(KsTestVectorError::ExternalSecretMismatch);
         }
-        if hex_to_bytes(&epoch.confirmation_key) != epoch_secrets.confirmation_key().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.confirmation_key,
+            epoch_secrets.confirmation_key().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Confirmation key mismatch");
             }
             return Err(KsTestVectorError::ConfirmationKeyMismatch);
         }
-        if hex_to_bytes(&epoch.membership_key) != epoch_secrets.membership_key().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.membership_key,
+            epoch_secrets.membership_key().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Membership key mismatch");
             }
             return Err(KsTestVectorError::MembershipKeyMismatch);
         }
-        if hex_to_bytes(&epoch.resumption_psk) != epoch_secrets.resumption_psk().as_slice() {
+        if !matches_expected(
+            allow_absent,
+            &epoch.resumption_psk,
+            epoch_secrets.resumption_psk().as_slice(),
+        ) {
             if cfg!(test) {
                 panic!("Resumption psk mismatch");
             }
@@ -440,7 +563,7 @@ pub fn run_test_vector(
             .external_secret()
             .derive_external_keypair(provider.crypto(), ciphersuite)
             .expect("an unexpected crypto error occurred");
-        if hex_to_bytes(&epoch.external_pub) != external_key_pair.public {
+        if !matches_expected(allow_absent, &epoch.external_pub, &external_key_pair.public) {
             log::error!("  External public key mismatch");
             log::debug!(
                 "    Computed: {:x?}",
@@ -456,6 +579,9 @@ pub fn run_test_vector(
         }
 
         // Check exported secret
+        if allow_absent && epoch.exporter.secret.is_empty() {
+            continue;
+        }
         let exported = epoch_secrets
             .exporter_secret()
             .derive_exported_secret(
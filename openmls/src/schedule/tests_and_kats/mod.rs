@@ -1,4 +1,6 @@
 #[cfg(any(feature = "test-utils", test))]
 pub mod kats;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod properties;
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,262 @@
+//! Key Schedule Property Tests
+//!
+//! Complementing the fixed test vectors, these tests derive chains of epochs
+//! from random inputs and check invariants of the key schedule.
+
+use std::collections::HashSet;
+
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite, OpenMlsProvider};
+use proptest::{
+    collection::vec,
+    option,
+    prelude::*,
+    test_runner::{Config, TestRunner},
+};
+
+use crate::{
+    ciphersuite::Secret,
+    messages::PathSecret,
+    schedule::{
+        psk::PskSecret, CommitSecret, EpochSecrets, InitSecret, JoinerSecret, KeySchedule,
+        WelcomeSecret,
+    },
+};
+
+/// The random inputs of one epoch of the key schedule.
+#[derive(Debug, Clone)]
+struct EpochInput {
+    commit_secret: Option<Vec<u8>>,
+    psk_secret: Option<Vec<u8>>,
+    group_context: Vec<u8>,
+}
+
+fn epoch_input(hash_length: usize) -> impl Strategy<Value = EpochInput> {
+    (
+        option::of(vec(any::<u8>(), hash_length)),
+        option::of(vec(any::<u8>(), hash_length)),
+        vec(any::<u8>(), 1..128),
+    )
+        .prop_map(|(commit_secret, psk_secret, group_context)| EpochInput {
+            commit_secret,
+            psk_secret,
+            group_context,
+        })
+}
+
+/// The initial init secret and the inputs of up to eight epochs.
+fn epoch_chain(ciphersuite: Ciphersuite) -> impl Strategy<Value = (Vec<u8>, Vec<EpochInput>)> {
+    let hash_length = ciphersuite.hash_length();
+    (
+        vec(any::<u8>(), hash_length),
+        vec(epoch_input(hash_length), 1..8),
+    )
+}
+
+fn runner() -> TestRunner {
+    TestRunner::new(Config {
+        cases: 32,
+        failure_persistence: None,
+        ..Config::default()
+    })
+}
+
+fn key_schedule(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    init_secret: &InitSecret,
+    input: &EpochInput,
+) -> KeySchedule {
+    let commit_secret = input
+        .commit_secret
+        .as_deref()
+        .map(|secret| CommitSecret::from(PathSecret::from(Secret::from_slice(secret))));
+    let joiner_secret = JoinerSecret::new(
+        crypto,
+        ciphersuite,
+        commit_secret,
+        init_secret,
+        &input.group_context,
+    )
+    .expect("Could not create the joiner secret.");
+    let psk_secret = PskSecret::from(
+        input
+            .psk_secret
+            .as_deref()
+            .map(Secret::from_slice)
+            .unwrap_or_else(|| Secret::zero(ciphersuite)),
+    );
+
+    KeySchedule::init(ciphersuite, crypto, &joiner_secret, psk_secret)
+        .expect("Could not create the key schedule.")
+}
+
+/// Derives the welcome secret and the epoch secrets of an epoch.
+fn derive_epoch(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    init_secret: &InitSecret,
+    input: &EpochInput,
+) -> (WelcomeSecret, EpochSecrets) {
+    let mut key_schedule = key_schedule(crypto, ciphersuite, init_secret, input);
+    let welcome_secret = key_schedule
+        .welcome(crypto, ciphersuite)
+        .expect("Could not derive the welcome secret.");
+    key_schedule
+        .add_context(crypto, &input.group_context)
+        .expect("Could not add the context.");
+    let epoch_secrets = key_schedule
+        .epoch_secrets(crypto, ciphersuite)
+        .expect("Could not derive the epoch secrets.");
+    (welcome_secret, epoch_secrets)
+}
+
+/// Derives all epochs of the chain and returns the secrets of every epoch.
+fn derive_chain(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    init_secret: &[u8],
+    inputs: &[EpochInput],
+) -> Vec<Vec<Vec<u8>>> {
+    let mut init_secret = InitSecret::from(Secret::from_slice(init_secret));
+    inputs
+        .iter()
+        .map(|input| {
+            let (welcome_secret, epoch_secrets) =
+                derive_epoch(crypto, ciphersuite, &init_secret, input);
+            init_secret = epoch_secrets.init_secret().clone();
+            all_secrets(&welcome_secret, &epoch_secrets)
+        })
+        .collect()
+}
+
+fn all_secrets(welcome_secret: &WelcomeSecret, epoch_secrets: &EpochSecrets) -> Vec<Vec<u8>> {
+    [
+        welcome_secret.as_slice(),
+        epoch_secrets.init_secret().as_slice(),
+        epoch_secrets.sender_data_secret().as_slice(),
+        epoch_secrets.encryption_secret().as_slice(),
+        epoch_secrets.exporter_secret().as_slice(),
+        epoch_secrets.epoch_authenticator().as_slice(),
+        epoch_secrets.external_secret().as_slice(),
+        epoch_secrets.confirmation_key().as_slice(),
+        epoch_secrets.membership_key().as_slice(),
+        epoch_secrets.resumption_psk().as_slice(),
+    ]
+    .into_iter()
+    .map(<[u8]>::to_vec)
+    .collect()
+}
+
+/// All secrets derived in a chain of epochs are distinct, both within an
+/// epoch and across epochs, and the derivation is deterministic.
+#[openmls_test::openmls_test]
+fn distinct_secrets_per_epoch() {
+    let crypto = provider.crypto();
+
+    runner()
+        .run(&epoch_chain(ciphersuite), |(init_secret, inputs)| {
+            let chain = derive_chain(crypto, ciphersuite, &init_secret, &inputs);
+
+            let secrets = chain.iter().flatten().collect::<HashSet<_>>();
+            prop_assert_eq!(secrets.len(), chain.len() * chain[0].len());
+            for secret in secrets {
+                prop_assert_eq!(secret.len(), ciphersuite.hash_length());
+            }
+
+            prop_assert_eq!(
+                chain,
+                derive_chain(crypto, ciphersuite, &init_secret, &inputs)
+            );
+            Ok(())
+        })
+        .expect("Key schedule property violated.");
+}
+
+/// Every epoch only depends on the init secret of the previous epoch and its
+/// own inputs. Changing the initial init secret changes every later epoch.
+#[openmls_test::openmls_test]
+fn init_secret_chaining() {
+    let crypto = provider.crypto();
+
+    let strategy = (epoch_chain(ciphersuite), any::<prop::sample::Index>());
+    runner()
+        .run(&strategy, |((init_secret, inputs), index)| {
+            let chain = derive_chain(crypto, ciphersuite, &init_secret, &inputs);
+
+            // Continuing from the init secret of an intermediate epoch yields
+            // the same remaining epochs.
+            let split = index.index(inputs.len());
+            if split > 0 {
+                let intermediate_init_secret = chain[split - 1][1].clone();
+                prop_assert_eq!(
+                    &chain[split..],
+                    &derive_chain(
+                        crypto,
+                        ciphersuite,
+                        &intermediate_init_secret,
+                        &inputs[split..]
+                    )[..]
+                );
+            }
+
+            let mut other_init_secret = init_secret.clone();
+            other_init_secret[0] ^= 0xff;
+            let other_chain = derive_chain(crypto, ciphersuite, &other_init_secret, &inputs);
+            for (epoch, other_epoch) in chain.iter().zip(other_chain.iter()) {
+                for (secret, other_secret) in epoch.iter().zip(other_epoch.iter()) {
+                    prop_assert_ne!(secret, other_secret);
+                }
+            }
+            Ok(())
+        })
+        .expect("Key schedule property violated.");
+}
+
+/// The welcome secret is derived before the group context is added to the
+/// key schedule and thus doesn't depend on it, while the epoch secrets do.
+#[openmls_test::openmls_test]
+fn welcome_secret_independent_of_context() {
+    let crypto = provider.crypto();
+    let hash_length = ciphersuite.hash_length();
+
+    let strategy = (
+        vec(any::<u8>(), hash_length),
+        epoch_input(hash_length),
+        vec(any::<u8>(), 1..128),
+    );
+    runner()
+        .run(&strategy, |(init_secret, input, other_context)| {
+            prop_assume!(input.group_context != other_context);
+            let init_secret = InitSecret::from(Secret::from_slice(&init_secret));
+
+            let (welcome_secret, epoch_secrets) =
+                derive_epoch(crypto, ciphersuite, &init_secret, &input);
+
+            // The same joiner secret and PSK with another context.
+            let mut key_schedule = key_schedule(crypto, ciphersuite, &init_secret, &input);
+            let other_welcome_secret = key_schedule
+                .welcome(crypto, ciphersuite)
+                .expect("Could not derive the welcome secret.");
+            key_schedule
+                .add_context(crypto, &other_context)
+                .expect("Could not add the context.");
+            let other_epoch_secrets = key_schedule
+                .epoch_secrets(crypto, ciphersuite)
+                .expect("Could not derive the epoch secrets.");
+
+            prop_assert_eq!(welcome_secret.as_slice(), other_welcome_secret.as_slice());
+            prop_assert_ne!(
+                epoch_secrets.epoch_authenticator().as_slice(),
+                other_epoch_secrets.epoch_authenticator().as_slice()
+            );
+            prop_assert_ne!(
+                epoch_secrets.init_secret().as_slice(),
+                other_epoch_secrets.init_secret().as_slice()
+            );
+
+            // The welcome secret can't be derived after the context was added.
+            prop_assert!(key_schedule.welcome(crypto, ciphersuite).is_err());
+            Ok(())
+        })
+        .expect("Key schedule property violated.");
+}
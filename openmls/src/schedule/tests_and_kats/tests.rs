@@ -4,7 +4,10 @@ use openmls_traits::{random::OpenMlsRand, OpenMlsProvider};
 
 use crate::{
     ciphersuite::Secret,
-    schedule::psk::{store::ResumptionPskStore, PskSecret, *},
+    schedule::{
+        psk::{store::ResumptionPskStore, PskSecret, *},
+        SenderDataSecret,
+    },
 };
 
 #[openmls_test::openmls_test]
@@ -39,3 +42,58 @@ fn test_psks() {
         PskSecret::new(provider.crypto(), ciphersuite, psks).unwrap()
     };
 }
+
+/// The sender data key and nonce are derived from a sample of the ciphertext.
+/// Make sure the derivation is deterministic, only depends on the first
+/// `hash_length` bytes of the ciphertext and uses the full ciphertext if it is
+/// shorter than that.
+#[openmls_test::openmls_test]
+fn sender_data_key_nonce_derivation() {
+    let crypto = provider.crypto();
+    let sender_data_secret = SenderDataSecret::from_slice(&[0x5a; 32]);
+    let sample_length = ciphersuite.hash_length();
+
+    let derive = |ciphertext: &[u8]| {
+        let key = sender_data_secret
+            .derive_aead_key(crypto, ciphersuite, ciphertext)
+            .expect("Error deriving sender data key.");
+        let nonce = sender_data_secret
+            .derive_aead_nonce(ciphersuite, crypto, ciphertext)
+            .expect("Error deriving sender data nonce.");
+        (key.as_slice().to_vec(), nonce.as_slice().to_vec())
+    };
+
+    let ciphertext = provider
+        .rand()
+        .random_vec(sample_length + 16)
+        .expect("Not enough randomness.");
+    let (key, nonce) = derive(&ciphertext);
+    assert_eq!(key.len(), ciphersuite.aead_key_length());
+    assert_eq!(nonce.len(), ciphersuite.aead_nonce_length());
+
+    // The derivation is deterministic.
+    assert_eq!((key.clone(), nonce.clone()), derive(&ciphertext));
+
+    // Bytes after the sample don't influence the derivation ...
+    let mut tail_modified = ciphertext.clone();
+    *tail_modified.last_mut().unwrap() ^= 0xff;
+    assert_eq!((key.clone(), nonce.clone()), derive(&tail_modified));
+    assert_eq!(
+        (key.clone(), nonce.clone()),
+        derive(&ciphertext[..sample_length])
+    );
+
+    // ... but bytes within the sample do.
+    let mut sample_modified = ciphertext.clone();
+    sample_modified[sample_length - 1] ^= 0xff;
+    let (modified_key, modified_nonce) = derive(&sample_modified);
+    assert_ne!(key, modified_key);
+    assert_ne!(nonce, modified_nonce);
+
+    // A ciphertext shorter than the sample length is used in full.
+    let short_ciphertext = &ciphertext[..sample_length / 2];
+    let (short_key, short_nonce) = derive(short_ciphertext);
+    assert_ne!(key, short_key);
+    assert_ne!(nonce, short_nonce);
+    assert_eq!((short_key, short_nonce), derive(short_ciphertext));
+}
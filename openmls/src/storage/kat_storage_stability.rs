@@ -710,3 +710,67 @@ fn test(ciphersuite: Ciphersuite, provider: &Provider) {
     // there is no pending commit
     assert!(alice_group_pending_proposal.pending_commit().is_none());
 }
+
+#[openmls_test]
+fn migrate_kats(ciphersuite: Ciphersuite, provider: &Provider) {
+    use openmls_traits::storage::migration::{
+        MigrationError, StorageEntry, StorageMigrations, StorageVersion,
+    };
+
+    let base64_engine = base64::engine::GeneralPurpose::new(
+        &base64::alphabet::URL_SAFE,
+        base64::engine::GeneralPurposeConfig::new(),
+    );
+    let mut data: HashMap<Ciphersuite, KatData> = {
+        let file = std::fs::File::open("test_vectors/storage-stability.json").unwrap();
+        serde_json::from_reader(file).unwrap()
+    };
+    let KatData { group_id, storages } = data.remove(&ciphersuite).unwrap();
+
+    // The storage format of the previous version was the same as the current
+    // one.
+    fn from_v0(_entry: &mut StorageEntry) -> Result<(), String> {
+        Ok(())
+    }
+    let migrations =
+        StorageMigrations::new().register(StorageVersion::new(0), StorageVersion::CURRENT, from_v0);
+
+    for storage in storages {
+        let storage = base64_engine.decode(storage).unwrap();
+        let provider = deserialize_provider::<_, Provider>(&mut storage.as_slice(), "alice");
+        let group = MlsGroup::load(provider.storage(), &group_id)
+            .unwrap()
+            .unwrap();
+
+        // The entries of the fixtures are of the current version.
+        assert_eq!(provider.storage().migrate(&StorageMigrations::new()), Ok(0));
+
+        // Rewrite the entries as if they had been written with version 0.
+        let v0_provider = deserialize_provider::<_, Provider>(&mut storage.as_slice(), "alice");
+        {
+            let mut values = v0_provider.storage().values.write().unwrap();
+            *values = std::mem::take(&mut *values)
+                .into_iter()
+                .map(|(mut key, value)| {
+                    let len = key.len();
+                    key[len - 2..].copy_from_slice(&0u16.to_be_bytes());
+                    (key, value)
+                })
+                .collect();
+        }
+        assert!(MlsGroup::load(v0_provider.storage(), &group_id)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            v0_provider.storage().migrate(&StorageMigrations::new()),
+            Err(MigrationError::MissingMigration(StorageVersion::new(0)))
+        );
+
+        let entries = v0_provider.storage().values.read().unwrap().len();
+        assert_eq!(v0_provider.storage().migrate(&migrations), Ok(entries));
+        let migrated_group = MlsGroup::load(v0_provider.storage(), &group_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(group, migrated_group);
+    }
+}
@@ -58,8 +58,7 @@ impl DeserializeBytes for FrankenProposalType {
     {
         let mut bytes_ref = bytes;
         let proposal_type = FrankenProposalType::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[proposal_type.tls_serialized_len()..];
-        Ok((proposal_type, remainder))
+        Ok((proposal_type, bytes_ref))
     }
 }
 
@@ -149,8 +148,7 @@ impl DeserializeBytes for FrankenProposal {
     {
         let mut bytes_ref = bytes;
         let proposal = FrankenProposal::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[proposal.tls_serialized_len()..];
-        Ok((proposal, remainder))
+        Ok((proposal, bytes_ref))
     }
 }
 
@@ -181,8 +179,7 @@ impl DeserializeBytes for FrankenExtensionType {
     {
         let mut bytes_ref = bytes;
         let extension_type = FrankenExtensionType::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[extension_type.tls_serialized_len()..];
-        Ok((extension_type, remainder))
+        Ok((extension_type, bytes_ref))
     }
 }
 
@@ -204,7 +201,7 @@ impl Size for FrankenExtension {
             FrankenExtension::ExternalPub(e) => e.tls_serialized_len(),
             FrankenExtension::ExternalSenders(e) => e.tls_serialized_len(),
             FrankenExtension::LastResort => 0,
-            FrankenExtension::Unknown(_, e) => e.tls_serialized_len(),
+            FrankenExtension::Unknown(_, e) => e.as_slice().len(),
         };
         let vlbytes_len_len = vlbytes_len_len(extension_data_len);
         extension_type_length + vlbytes_len_len + extension_data_len
@@ -275,7 +272,6 @@ impl DeserializeBytes for FrankenExtension {
     {
         let mut bytes_ref = bytes;
         let extension = FrankenExtension::tls_deserialize(&mut bytes_ref)?;
-        let remainder = &bytes[extension.tls_serialized_len()..];
-        Ok((extension, remainder))
+        Ok((extension, bytes_ref))
     }
 }
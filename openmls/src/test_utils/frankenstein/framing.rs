@@ -3,6 +3,7 @@ use tls_codec::*;
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    ciphersuite::hpke,
     extensions::SenderExtensionIndex,
     framing::{
         mls_content::{AuthenticatedContentTbm, FramedContentBody, FramedContentTbs},
@@ -11,13 +12,17 @@ use crate::{
         PublicMessageIn, Sender, WireFormat,
     },
     group::GroupContext,
-    messages::{ConfirmationTag, Welcome},
+    key_packages::KeyPackageBundle,
+    messages::{ConfirmationTag, GroupSecrets, Welcome},
     prelude_test::signable::Signable,
-    schedule::{ConfirmationKey, MembershipKey},
+    schedule::{
+        psk::{PreSharedKeyId, PskSecret},
+        ConfirmationKey, KeySchedule, MembershipKey,
+    },
 };
 
 use super::{
-    commit::FrankenCommit,
+    commit::{FrankenCommit, FrankenHpkeCiphertext},
     compute_membership_tag,
     group_info::{FrankenGroupContext, FrankenGroupInfo},
     sign_with_label, FrankenKeyPackage, FrankenProposal,
@@ -190,6 +195,34 @@ impl FrankenPublicMessage {
             membership_tag,
         }
     }
+
+    /// Recomputes the membership tag of the message for the given
+    /// `group_context` and `membership_key`, e.g. after the signature was
+    /// changed.
+    pub(crate) fn retag(
+        &mut self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        group_context: &FrankenGroupContext,
+        membership_key: &[u8],
+    ) {
+        let tbm = FrankenAuthenticatedContentTbm {
+            content_tbs: FrankenFramedContentTbs {
+                version: 1,     // MLS 1.0
+                wire_format: 1, // PublicMessage
+                content: &self.content,
+                group_context: Some(group_context),
+            },
+            auth: self.auth.clone(),
+        };
+
+        self.membership_tag = Some(compute_membership_tag(
+            crypto,
+            ciphersuite,
+            membership_key,
+            &tbm,
+        ));
+    }
 }
 
 #[derive(
@@ -410,7 +443,7 @@ pub enum FrankenContentType {
 )]
 pub struct FrankenEncryptedGroupSecrets {
     pub new_member: VLBytes,
-    pub encrypted_group_secrets: VLBytes,
+    pub encrypted_group_secrets: FrankenHpkeCiphertext,
 }
 
 impl From<MlsMessageOut> for FrankenMlsMessage {
@@ -458,6 +491,96 @@ impl From<FrankenPrivateMessage> for PrivateMessage {
     }
 }
 
+impl FrankenWelcome {
+    /// Decrypts the GroupInfo of the Welcome with the `key_package_bundle` of
+    /// a new member, lets `modify` change it and encrypts it again. The group
+    /// secrets of the new member are encrypted again for the new encrypted
+    /// GroupInfo, such that the new member can decrypt the modified GroupInfo.
+    ///
+    /// Welcomes that use PSKs are not supported.
+    pub fn modify_group_info(
+        &mut self,
+        provider: &impl crate::storage::OpenMlsProvider,
+        key_package_bundle: &KeyPackageBundle,
+        modify: impl FnOnce(&mut FrankenGroupInfo),
+    ) {
+        let crypto = provider.crypto();
+        let ciphersuite = Ciphersuite::try_from(self.cipher_suite).unwrap();
+        let welcome = Welcome::from(self.clone());
+        let key_package_ref = key_package_bundle.key_package().hash_ref(crypto).unwrap();
+        let encrypted_group_secrets = welcome
+            .find_encrypted_group_secret(key_package_ref.clone())
+            .expect("no group secrets for the key package");
+        let group_secrets = GroupSecrets::try_from_ciphertext(
+            key_package_bundle.init_private_key(),
+            encrypted_group_secrets.encrypted_group_secrets(),
+            welcome.encrypted_group_info(),
+            ciphersuite,
+            crypto,
+        )
+        .unwrap();
+        assert!(group_secrets.psks.is_empty(), "PSKs are not supported");
+
+        let psk_secret =
+            PskSecret::new(crypto, ciphersuite, Vec::<(PreSharedKeyId, _)>::new()).unwrap();
+        let (welcome_key, welcome_nonce) = KeySchedule::init(
+            ciphersuite,
+            crypto,
+            &group_secrets.joiner_secret,
+            psk_secret,
+        )
+        .unwrap()
+        .welcome(crypto, ciphersuite)
+        .unwrap()
+        .derive_welcome_key_nonce(crypto, ciphersuite)
+        .unwrap();
+        let group_info = welcome_key
+            .aead_open(
+                crypto,
+                self.encrypted_group_info.as_slice(),
+                &[],
+                &welcome_nonce,
+            )
+            .unwrap();
+        let mut group_info = FrankenGroupInfo::tls_deserialize_exact(group_info).unwrap();
+        modify(&mut group_info);
+        self.encrypted_group_info = welcome_key
+            .aead_seal(
+                crypto,
+                &group_info.tls_serialize_detached().unwrap(),
+                &[],
+                &welcome_nonce,
+            )
+            .unwrap()
+            .into();
+
+        let group_secrets = GroupSecrets::new_encoded(
+            &group_secrets.joiner_secret,
+            group_secrets.path_secret.as_ref(),
+            &group_secrets.psks,
+        )
+        .unwrap();
+        let encrypted_group_secrets = hpke::encrypt_with_label(
+            key_package_bundle.key_package().hpke_init_key().as_slice(),
+            "Welcome",
+            self.encrypted_group_info.as_slice(),
+            &group_secrets,
+            ciphersuite,
+            crypto,
+        )
+        .unwrap();
+        let secrets = self
+            .secrets
+            .iter_mut()
+            .find(|secrets| secrets.new_member.as_slice() == key_package_ref.as_slice())
+            .unwrap();
+        secrets.encrypted_group_secrets = FrankenHpkeCiphertext {
+            kem_output: encrypted_group_secrets.kem_output,
+            ciphertext: encrypted_group_secrets.ciphertext,
+        };
+    }
+}
+
 impl From<Welcome> for FrankenWelcome {
     fn from(ln: Welcome) -> Self {
         FrankenWelcome::tls_deserialize(&mut ln.tls_serialize_detached().unwrap().as_slice())
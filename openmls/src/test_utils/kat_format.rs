@@ -0,0 +1,104 @@
+//! # Test vector formats
+//!
+//! The format of the test vectors changed between the drafts of MLS, e.g.
+//! fields were renamed or changed their type. Every test vector is tagged with
+//! the [`KatDraft`] it was generated for, which is detected from the fields of
+//! the vector by [`DraftTagged::detect_draft()`]. A test run verifies the
+//! vectors of the drafts a KAT supports and returns the positions and drafts
+//! of the others as skipped, such that files that mix drafts can be read
+//! without sorting them out manually and the tests can assert on what was
+//! skipped. All KATs only support the vectors of RFC 9420 at the moment, the
+//! vectors of older drafts are not converted to the current format.
+//!
+//! A file contains either a list of test vectors or a single test vector, as
+//! the test vectors of some older drafts do.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// The version of MLS a test vector was generated for.
+///
+/// A draft is identified by the last draft that used the format, e.g. the test
+/// vectors of draft 12 are tagged with `Draft(16)` if the format didn't change
+/// until draft 16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KatDraft {
+    /// A draft of the MLS protocol.
+    Draft(u8),
+    /// The final version of RFC 9420.
+    Rfc9420,
+}
+
+impl fmt::Display for KatDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KatDraft::Draft(draft) => write!(f, "draft-{draft}"),
+            KatDraft::Rfc9420 => write!(f, "RFC 9420"),
+        }
+    }
+}
+
+/// A test vector whose draft can be detected from its fields.
+pub trait DraftTagged: DeserializeOwned {
+    /// Detects the draft the JSON `vector` was generated for.
+    fn detect_draft(vector: &Value) -> KatDraft;
+
+    /// Returns `true` if vectors of the `draft` can be verified. Only the
+    /// vectors of RFC 9420 are supported by default.
+    fn supports(draft: KatDraft) -> bool {
+        draft == KatDraft::Rfc9420
+    }
+}
+
+/// A test vector tagged with its draft.
+#[derive(Debug, Clone)]
+pub struct TaggedVector<T> {
+    /// The detected draft.
+    pub draft: KatDraft,
+    /// The test vector.
+    pub vector: T,
+}
+
+/// The test vectors of a file.
+#[derive(Debug, Clone)]
+pub struct TaggedVectors<T> {
+    /// The vectors of supported drafts.
+    pub vectors: Vec<TaggedVector<T>>,
+    /// The positions in the file and the drafts of the skipped vectors.
+    pub skipped: Vec<(usize, KatDraft)>,
+}
+
+impl<T> TaggedVectors<T> {
+    /// Returns the vectors of supported drafts without their tags.
+    pub fn into_vectors(self) -> impl Iterator<Item = T> {
+        self.vectors.into_iter().map(|tagged| tagged.vector)
+    }
+}
+
+/// Parses the test vectors of the JSON `data` and tags them with their draft.
+/// Vectors of unsupported drafts are skipped without parsing them.
+pub fn parse_tagged<T: DraftTagged>(data: &str) -> Result<TaggedVectors<T>, serde_json::Error> {
+    let values = match serde_json::from_str(data)? {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+
+    let mut vectors = TaggedVectors {
+        vectors: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (position, value) in values.into_iter().enumerate() {
+        let draft = T::detect_draft(&value);
+        if T::supports(draft) {
+            vectors.vectors.push(TaggedVector {
+                draft,
+                vector: serde_json::from_value(value)?,
+            });
+        } else {
+            vectors.skipped.push((position, draft));
+        }
+    }
+    Ok(vectors)
+}
@@ -0,0 +1,215 @@
+//! # Message mutator
+//!
+//! This module provides a harness to test how a group handles the messages of
+//! a malicious peer. A [`Mutation`] applies a targeted corruption to a valid
+//! serialized message, and [`assert_rejected()`] checks that processing the
+//! corrupted message fails with the specific error expected for it.
+//!
+//! Mutations change the message such that it only fails the check they
+//! target. For this, they re-sign the message or recompute its membership tag
+//! with a [`Reauthenticator`] where necessary.
+//!
+//! Reusing the generation of a private message is tested with
+//! [`assert_replay_rejected()`], which processes the same message twice.
+
+use openmls_traits::{signatures::Signer, types::Ciphersuite, OpenMlsProvider as _};
+use tls_codec::{Deserialize as _, Serialize as _, VLBytes};
+
+use super::frankenstein::{
+    FrankenFramedContentBody, FrankenGroupContext, FrankenMlsMessage, FrankenMlsMessageBody,
+    FrankenPublicMessage, FrankenSender,
+};
+use crate::{
+    framing::{errors::MessageDecryptionError, MlsMessageIn},
+    group::{
+        errors::{ProcessMessageError, ValidationError},
+        MlsGroup,
+    },
+    storage::OpenMlsProvider,
+};
+
+/// Re-authenticates mutated public messages for the epoch of a group.
+pub struct Reauthenticator<'a, Provider: OpenMlsProvider, S: Signer> {
+    provider: &'a Provider,
+    signer: &'a S,
+    ciphersuite: Ciphersuite,
+    group_context: FrankenGroupContext,
+    membership_key: Vec<u8>,
+}
+
+impl<'a, Provider: OpenMlsProvider, S: Signer> Reauthenticator<'a, Provider, S> {
+    /// Create a reauthenticator that signs with the `signer` and computes
+    /// membership tags for the current epoch of the `group`.
+    ///
+    /// The `group` can be the group of any member that is in the epoch the
+    /// message was sent in, e.g. the group of the receiver before it processes
+    /// the message.
+    pub fn new(provider: &'a Provider, signer: &'a S, group: &MlsGroup) -> Self {
+        Self {
+            provider,
+            signer,
+            ciphersuite: group.ciphersuite(),
+            group_context: group.export_group_context().clone().into(),
+            membership_key: group.message_secrets().membership_key().as_slice().to_vec(),
+        }
+    }
+
+    /// Re-signs the message and recomputes its membership tag.
+    fn resign(&self, message: FrankenPublicMessage) -> FrankenPublicMessage {
+        FrankenPublicMessage::auth(
+            self.provider,
+            self.ciphersuite,
+            self.signer,
+            message.content,
+            Some(&self.group_context),
+            Some(&self.membership_key),
+            message.auth.confirmation_tag,
+        )
+    }
+
+    /// Recomputes the membership tag of the message.
+    fn retag(&self, message: &mut FrankenPublicMessage) {
+        message.retag(
+            self.provider.crypto(),
+            self.ciphersuite,
+            &self.group_context,
+            &self.membership_key,
+        );
+    }
+}
+
+/// A targeted corruption of a valid public message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    /// Flip a bit of the signature. The membership tag is recomputed, such that
+    /// the message fails the signature verification.
+    FlipSignatureBit,
+    /// Flip a bit of the membership tag.
+    FlipMembershipTagBit,
+    /// Replace the sender with the member at the given leaf index. The message
+    /// is re-signed with the key of the original sender, such that it fails the
+    /// signature verification.
+    SwapSender(u32),
+    /// Commit the first proposal of a commit a second time. The message is
+    /// re-signed, such that it fails the validation of the commit.
+    DuplicateProposal,
+}
+
+impl Mutation {
+    /// Applies the mutation to the serialized public `message` and returns the
+    /// serialized mutated message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `message` is not a public message with a member sender,
+    /// or if [`Mutation::DuplicateProposal`] is applied to a message that is
+    /// not a commit with proposals.
+    pub fn apply<Provider: OpenMlsProvider, S: Signer>(
+        &self,
+        message: &[u8],
+        reauthenticator: &Reauthenticator<Provider, S>,
+    ) -> Vec<u8> {
+        let mut franken_message =
+            FrankenMlsMessage::tls_deserialize_exact(message).expect("Invalid message.");
+        let FrankenMlsMessageBody::PublicMessage(public_message) = &mut franken_message.body else {
+            panic!("Only public messages can be mutated.");
+        };
+        assert!(
+            matches!(public_message.content.sender, FrankenSender::Member(_)),
+            "Only messages of members can be mutated."
+        );
+
+        match self {
+            Mutation::FlipSignatureBit => {
+                public_message.auth.signature = flip_bit(&public_message.auth.signature);
+                reauthenticator.retag(public_message);
+            }
+            Mutation::FlipMembershipTagBit => {
+                let membership_tag = public_message
+                    .membership_tag
+                    .as_ref()
+                    .expect("The message has no membership tag.");
+                public_message.membership_tag = Some(flip_bit(membership_tag));
+            }
+            Mutation::SwapSender(leaf_index) => {
+                public_message.content.sender = FrankenSender::Member(*leaf_index);
+                *public_message = reauthenticator.resign(public_message.clone());
+            }
+            Mutation::DuplicateProposal => {
+                let FrankenFramedContentBody::Commit(commit) = &mut public_message.content.body
+                else {
+                    panic!("Only commits can contain duplicate proposals.");
+                };
+                let proposal = commit
+                    .proposals
+                    .first()
+                    .expect("The commit has no proposals.")
+                    .clone();
+                commit.proposals.push(proposal);
+                *public_message = reauthenticator.resign(public_message.clone());
+            }
+        }
+
+        franken_message
+            .tls_serialize_detached()
+            .expect("Could not serialize the mutated message.")
+    }
+}
+
+fn flip_bit(bytes: &VLBytes) -> VLBytes {
+    let mut bytes = bytes.as_slice().to_vec();
+    let last = bytes.last_mut().expect("The value is empty.");
+    *last ^= 1;
+    bytes.into()
+}
+
+/// Processes the serialized `message` with the `group` and asserts that it
+/// fails with the `expected` error without changing the epoch of the group.
+pub fn assert_rejected<Provider: OpenMlsProvider>(
+    group: &mut MlsGroup,
+    provider: &Provider,
+    message: &[u8],
+    expected: ProcessMessageError<Provider::StorageError>,
+) where
+    Provider::StorageError: PartialEq,
+{
+    let epoch = group.epoch();
+    let message = MlsMessageIn::tls_deserialize_exact(message)
+        .expect("Invalid message.")
+        .try_into_protocol_message()
+        .expect("Not a protocol message.");
+
+    let err = group
+        .process_message(provider, message)
+        .expect_err("The mutated message was processed.");
+    assert_eq!(err, expected);
+    assert_eq!(group.epoch(), epoch);
+}
+
+/// Processes the serialized private `message` with the `group` twice and
+/// asserts that the second time fails because the generation was used
+/// before.
+pub fn assert_replay_rejected<Provider: OpenMlsProvider>(
+    group: &mut MlsGroup,
+    provider: &Provider,
+    message: &[u8],
+) where
+    Provider::StorageError: PartialEq,
+{
+    let protocol_message = MlsMessageIn::tls_deserialize_exact(message)
+        .expect("Invalid message.")
+        .try_into_protocol_message()
+        .expect("Not a protocol message.");
+    group
+        .process_message(provider, protocol_message)
+        .expect("Error processing the message.");
+
+    assert_rejected(
+        group,
+        provider,
+        message,
+        ProcessMessageError::ValidationError(ValidationError::UnableToDecrypt(
+            MessageDecryptionError::ReplayedMessage,
+        )),
+    );
+}
@@ -3,12 +3,15 @@
 #![allow(unused_imports)]
 
 use std::{
-    fmt::Write as FmtWrite,
     fs::File,
     io::{BufReader, Write},
 };
 
 use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    types::{CryptoError, HashType, SignatureScheme},
+};
 pub use openmls_traits::{
     storage::StorageProvider as StorageProviderTrait,
     types::{Ciphersuite, HpkeKeyPair},
@@ -22,13 +25,30 @@ pub use crate::utils::*;
 use crate::{
     ciphersuite::{HpkePrivateKey, OpenMlsSignaturePublicKey},
     credentials::{Credential, CredentialType, CredentialWithKey},
-    key_packages::{KeyPackage, KeyPackageBuilder},
+    key_packages::{KeyPackage, KeyPackageBuilder, TimeProvider},
     prelude::KeyPackageBundle,
     treesync::node::encryption_keys::{EncryptionKeyPair, EncryptionPrivateKey},
 };
 
 pub mod frankenstein;
+pub mod kat_format;
+pub mod message_mutator;
+#[cfg(feature = "json")]
+pub mod replay;
 pub mod test_framework;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests;
+
+/// A [`TimeProvider`] that always returns the same time, or no time if it
+/// holds `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedTimeProvider(pub Option<u64>);
+
+impl TimeProvider for FixedTimeProvider {
+    fn now(&self) -> Option<u64> {
+        self.0
+    }
+}
 
 pub(crate) fn write(file_name: &str, obj: impl Serialize) {
     let mut file = match File::create(file_name) {
@@ -52,6 +72,16 @@ macro_rules! read_json {
     }};
 }
 
+// the macro is used in other files, suppress false positive
+#[allow(unused_macros)]
+macro_rules! read_json_tagged {
+    ($file_name:expr) => {{
+        let data = include_str!($file_name);
+        $crate::test_utils::kat_format::parse_tagged(data)
+            .expect(&format!("Error reading file {}", $file_name))
+    }};
+}
+
 pub(crate) fn read<T: DeserializeOwned>(file_name: &str) -> T {
     let file = match File::open(file_name) {
         Ok(f) => f,
@@ -66,23 +96,34 @@ pub(crate) fn read<T: DeserializeOwned>(file_name: &str) -> T {
 
 /// Convert `bytes` to a hex string.
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
-    let mut hex = String::new();
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut hex = Vec::with_capacity(2 * bytes.len());
     for &b in bytes {
-        write!(&mut hex, "{b:02X}").expect("Unable to write to string");
+        hex.push(HEX_DIGITS[usize::from(b >> 4)]);
+        hex.push(HEX_DIGITS[usize::from(b & 0x0f)]);
+    }
+    // The vector only contains ASCII digits.
+    String::from_utf8(hex).expect("An unexpected error occurred.")
+}
+
+/// Returns the value of a single hex digit, independent of its case.
+fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        b'A'..=b'F' => digit - b'A' + 10,
+        _ => panic!("Invalid hex digit {:?}.", char::from(digit)),
     }
-    hex
 }
 
 /// Convert a hex string to a byte vector.
 pub fn hex_to_bytes(hex: &str) -> Vec<u8> {
     assert!(hex.len() % 2 == 0);
-    let mut bytes = Vec::new();
-    for i in 0..(hex.len() / 2) {
-        bytes.push(
-            u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).expect("An unexpected error occurred."),
-        );
-    }
-    bytes
+    hex.as_bytes()
+        .chunks_exact(2)
+        .map(|digits| (hex_digit(digits[0]) << 4) | hex_digit(digits[1]))
+        .collect()
 }
 
 /// Convert a hex string to a byte vector.
@@ -96,6 +137,21 @@ pub fn hex_to_bytes_option(hex: Option<String>) -> Vec<u8> {
 
 // === Convenience functions ===
 
+/// Derive a signature key pair for `signature_scheme` deterministically from
+/// `seed` using HKDF.
+///
+/// Only Ed25519 and ECDSA P-256 are supported.
+pub fn signature_key_pair_from_seed(
+    crypto: &impl OpenMlsCrypto,
+    signature_scheme: SignatureScheme,
+    seed: &[u8],
+) -> Result<SignatureKeyPair, CryptoError> {
+    let prk = crypto.hkdf_extract(HashType::Sha2_256, b"", seed)?;
+    let okm = crypto.hkdf_expand(HashType::Sha2_256, prk.as_slice(), b"signature key", 32)?;
+
+    SignatureKeyPair::from_raw_private_key(signature_scheme, okm.as_slice().to_vec())
+}
+
 #[cfg(test)]
 pub(crate) struct GroupCandidate {
     pub identity: Vec<u8>,
@@ -0,0 +1,135 @@
+//! # Trace replayer
+//!
+//! This module reconstructs the state of a group from a [`GroupTrace`]
+//! recorded with a [`GroupRecorder`](crate::group::GroupRecorder).
+//!
+//! The replayer loads the initial state of the trace and processes all
+//! inbound messages again. It checks that every event happens in the recorded
+//! epoch and that processing a message has the recorded result, such that the
+//! first event at which the replayed group diverges from the recorded one is
+//! reported.
+
+use thiserror::Error;
+
+use crate::{
+    framing::ProcessedMessageContent,
+    group::{
+        errors::{GroupStateError, MergeCommitError},
+        mls_group::trace::process_serialized_message,
+        GroupTrace, MlsGroup, TraceEvent,
+    },
+    storage::OpenMlsProvider,
+};
+
+/// Trace replay error
+#[derive(Error, Debug)]
+pub enum ReplayError<StorageError> {
+    /// Loading a snapshot of the trace failed.
+    #[error("Loading a snapshot of the trace failed: {0}")]
+    GroupState(#[from] GroupStateError<StorageError>),
+    /// The replayed group is in another epoch than the recorded one.
+    #[error("Event {event} was recorded in epoch {recorded}, but replayed in epoch {replayed}.")]
+    EpochMismatch {
+        /// The index of the event.
+        event: usize,
+        /// The recorded epoch.
+        recorded: u64,
+        /// The epoch of the replayed group.
+        replayed: u64,
+    },
+    /// Processing a message had another result than the recorded one.
+    #[error("Event {event} was recorded with {recorded:?}, but replayed with {replayed:?}.")]
+    ResultMismatch {
+        /// The index of the event.
+        event: usize,
+        /// The recorded result.
+        recorded: Result<(), String>,
+        /// The result of the replay.
+        replayed: Result<(), String>,
+    },
+    /// Merging a replayed commit failed.
+    #[error("Merging the commit of event {event} failed: {error}")]
+    MergeCommit {
+        /// The index of the event.
+        event: usize,
+        /// The error of the merge.
+        error: MergeCommitError<StorageError>,
+    },
+    /// Error accessing the storage.
+    #[error("Error accessing the storage.")]
+    StorageError(StorageError),
+}
+
+/// Reconstructs the group state of the `trace` in the storage of the
+/// `provider` and returns the group after the last event.
+///
+/// The storage must not contain the group already.
+pub fn replay<Provider: OpenMlsProvider>(
+    provider: &Provider,
+    trace: &GroupTrace,
+) -> Result<MlsGroup, ReplayError<Provider::StorageError>> {
+    let mut group = MlsGroup::load_state(provider.storage(), &mut trace.initial_state())?;
+
+    for (event, trace_event) in trace.events().iter().enumerate() {
+        match trace_event {
+            TraceEvent::Inbound {
+                epoch,
+                message,
+                result,
+            } => {
+                check_epoch(event, *epoch, &group)?;
+                let processed_message = process_serialized_message(&mut group, provider, message);
+                let replayed = processed_message
+                    .as_ref()
+                    .map(|_| ())
+                    .map_err(|e| e.to_string());
+                if &replayed != result {
+                    return Err(ReplayError::ResultMismatch {
+                        event,
+                        recorded: result.clone(),
+                        replayed,
+                    });
+                }
+                let Ok(processed_message) = processed_message else {
+                    continue;
+                };
+                match processed_message.into_content() {
+                    ProcessedMessageContent::StagedCommitMessage(staged_commit) => group
+                        .merge_staged_commit(provider, *staged_commit)
+                        .map_err(|error| ReplayError::MergeCommit { event, error })?,
+                    ProcessedMessageContent::ProposalMessage(proposal) => group
+                        .store_pending_proposal(provider.storage(), *proposal)
+                        .map_err(ReplayError::StorageError)?,
+                    _ => (),
+                }
+            }
+            TraceEvent::Outbound { epoch, .. } => check_epoch(event, *epoch, &group)?,
+            // The checkpoint may be in a later epoch, e.g. after an own
+            // commit was merged.
+            TraceEvent::Checkpoint { state, .. } => {
+                group
+                    .delete(provider.storage())
+                    .map_err(ReplayError::StorageError)?;
+                group = MlsGroup::load_state(provider.storage(), &mut state.as_slice())?;
+            }
+        }
+    }
+
+    Ok(group)
+}
+
+fn check_epoch<StorageError>(
+    event: usize,
+    recorded: u64,
+    group: &MlsGroup,
+) -> Result<(), ReplayError<StorageError>> {
+    let replayed = group.epoch().as_u64();
+    if recorded != replayed {
+        return Err(ReplayError::EpochMismatch {
+            event,
+            recorded,
+            replayed,
+        });
+    }
+    Ok(())
+}
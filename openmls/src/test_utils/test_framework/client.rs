@@ -6,6 +6,7 @@ use std::{collections::HashMap, sync::RwLock};
 use commit_builder::CommitMessageBundle;
 use openmls_basic_credential::SignatureKeyPair;
 use openmls_traits::{
+    crypto::OpenMlsCrypto,
     types::{Ciphersuite, HpkeKeyPair, SignatureScheme},
     OpenMlsProvider as _,
 };
@@ -15,7 +16,7 @@ use super::OpenMlsRustCrypto;
 
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
-    ciphersuite::hash_ref::KeyPackageRef,
+    ciphersuite::{hash_ref::KeyPackageRef, OpenMlsSignaturePublicKey},
     credentials::*,
     extensions::*,
     framing::*,
@@ -47,6 +48,40 @@ pub struct Client<Provider: OpenMlsProvider> {
     pub groups: RwLock<HashMap<GroupId, MlsGroup>>,
 }
 
+impl<Provider: OpenMlsProvider + Default> Client<Provider> {
+    /// Create a client with the given `identity`, which has a basic
+    /// credential and a signature key pair for every ciphersuite supported by
+    /// its provider.
+    pub fn new(identity: Vec<u8>) -> Self {
+        let provider = Provider::default();
+        let mut credentials = HashMap::new();
+        for ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+            let credential = BasicCredential::new(identity.clone());
+            let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+            signature_keys.store(provider.storage()).unwrap();
+            let signature_key = OpenMlsSignaturePublicKey::new(
+                signature_keys.public().into(),
+                signature_keys.signature_scheme(),
+            )
+            .unwrap();
+
+            credentials.insert(
+                *ciphersuite,
+                CredentialWithKey {
+                    credential: credential.into(),
+                    signature_key: signature_key.into(),
+                },
+            );
+        }
+        Client {
+            identity,
+            credentials,
+            provider,
+            groups: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
 impl<Provider: OpenMlsProvider> Client<Provider> {
     /// Generate a fresh key package and return it.
     /// The first ciphersuite determines the
@@ -18,6 +18,13 @@ pub enum SetupError<StorageError> {
     ClientNotInGroup,
     #[error("")]
     NoFreshKeyPackage,
+    #[error("")]
+    NoQueuedMessage,
+    #[error("")]
+    NoReference,
+    /// See [`ReferenceError`] for more details.
+    #[error(transparent)]
+    ReferenceError(#[from] ReferenceError),
     /// See [`ClientError`] for more details.
     #[error(transparent)]
     ClientError(#[from] ClientError<StorageError>),
@@ -31,6 +38,22 @@ pub enum SetupError<StorageError> {
     Unknown,
 }
 
+/// An error of a [`ReferenceMls`](super::reference::ReferenceMls)
+/// implementation.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+#[error("The reference implementation failed: {0}")]
+pub struct ReferenceError(pub String);
+
+/// Errors of a scripted [`Scenario`](super::scenario::Scenario).
+#[derive(Error, Debug, PartialEq)]
+#[error("Step {step} of the scenario failed: {error}")]
+pub struct ScenarioError<StorageError> {
+    /// The index of the failing step.
+    pub step: usize,
+    /// The error of the failing step.
+    pub error: SetupError<StorageError>,
+}
+
 #[derive(Debug)]
 pub enum SetupGroupError {
     NotEnoughMembers,
@@ -57,7 +80,7 @@ pub enum ClientError<StorageError> {
     TlsCodecError(tls_codec::Error),
     /// See [`ProcessMessageError`] for more details.
     #[error("See ProcessMessageError for more details.")]
-    ProcessMessageError(ProcessMessageError),
+    ProcessMessageError(ProcessMessageError<StorageError>),
     /// See [`AddMembersError`] for more details.
     #[error(transparent)]
     AddMembersError(#[from] AddMembersError<StorageError>),
@@ -47,6 +47,8 @@ use tls_codec::*;
 
 pub mod client;
 pub mod errors;
+pub mod reference;
+pub mod scenario;
 
 use self::client::*;
 use self::errors::*;
@@ -144,33 +146,7 @@ impl<Provider: OpenMlsProvider + Default> MlsGroupTestSetup<Provider> {
         let mut clients = HashMap::new();
         for i in 0..number_of_clients {
             let identity = i.to_be_bytes().to_vec();
-            let provider = Provider::default();
-            let mut credentials = HashMap::new();
-            for ciphersuite in provider.crypto().supported_ciphersuites().iter() {
-                let credential = BasicCredential::new(identity.clone());
-                let signature_keys =
-                    SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
-                signature_keys.store(provider.storage()).unwrap();
-                let signature_key = OpenMlsSignaturePublicKey::new(
-                    signature_keys.public().into(),
-                    signature_keys.signature_scheme(),
-                )
-                .unwrap();
-
-                credentials.insert(
-                    *ciphersuite,
-                    CredentialWithKey {
-                        credential: credential.into(),
-                        signature_key: signature_key.into(),
-                    },
-                );
-            }
-            let client = Client {
-                identity: identity.clone(),
-                credentials,
-                provider,
-                groups: RwLock::new(HashMap::new()),
-            };
+            let client = Client::new(identity.clone());
             clients.insert(identity, RwLock::new(client));
         }
         let groups = RwLock::new(HashMap::new());
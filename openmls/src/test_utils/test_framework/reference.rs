@@ -0,0 +1,54 @@
+//! This module provides the hook for differential testing against another
+//! implementation of MLS.
+//!
+//! A [`ReferenceMls`] is an adapter for a second implementation, e.g. one
+//! that is called through FFI. When a reference is set for a
+//! [`Scenario`](super::scenario::Scenario), it joins the group as a passive
+//! member with [`ScenarioStep::AddReference`](super::scenario::ScenarioStep)
+//! and receives the wire encoding of every message sent by the OpenMLS
+//! clients afterwards. The scenario checks that the reference decrypts the
+//! same application messages and that it agrees with the OpenMLS clients on
+//! the state of the group whenever the scenario asserts convergence.
+//!
+//! All values are passed TLS-serialized, such that the adapter doesn't depend
+//! on the types of OpenMLS.
+
+use openmls_traits::types::Ciphersuite;
+
+use super::errors::ReferenceError;
+
+/// An adapter for a reference implementation of MLS that acts as a single
+/// member of a group.
+pub trait ReferenceMls {
+    /// Returns a fresh serialized `KeyPackage` of the member for the
+    /// `ciphersuite`.
+    fn key_package(&mut self, ciphersuite: Ciphersuite) -> Result<Vec<u8>, ReferenceError>;
+
+    /// Joins the group with the serialized `MLSMessage` containing a
+    /// `Welcome` and the serialized ratchet tree of the group.
+    fn join(&mut self, welcome: &[u8], ratchet_tree: &[u8]) -> Result<(), ReferenceError>;
+
+    /// Processes the serialized `MLSMessage` of another member. Commits are
+    /// merged right away.
+    ///
+    /// Returns the content of application messages and `None` for all other
+    /// messages.
+    fn process_message(&mut self, message: &[u8]) -> Result<Option<Vec<u8>>, ReferenceError>;
+
+    /// Returns the current epoch of the group.
+    fn epoch(&self) -> u64;
+
+    /// Returns the epoch authenticator of the current epoch.
+    fn epoch_authenticator(&self) -> Vec<u8>;
+
+    /// Returns the serialized ratchet tree of the current epoch.
+    fn ratchet_tree(&self) -> Vec<u8>;
+
+    /// Exports a secret of the current epoch.
+    fn export_secret(
+        &self,
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, ReferenceError>;
+}
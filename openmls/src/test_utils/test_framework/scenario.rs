@@ -0,0 +1,519 @@
+//! This module provides scripted multi-client scenarios. A [`Scenario`] runs
+//! a sequence of [`ScenarioStep`]s on a group of in-memory clients.
+//!
+//! In contrast to the `MlsGroupTestSetup`, messages are not distributed to
+//! the members right away. Every message is queued for each of its recipients
+//! and only processed when a delivery step delivers it. This allows scripting
+//! specific interleavings of operations and deliveries, e.g. an application
+//! message that is delivered after a commit of the following epoch.
+//!
+//! Clients are identified by their index. The client with index 0 creates the
+//! group when the scenario is created. The sender of a commit merges it right
+//! away, and new members join with the `Welcome` right away.
+//!
+//! A scenario can also include a [`ReferenceMls`] for differential testing,
+//! see the [`reference`](super::reference) module.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_traits::{types::Ciphersuite, OpenMlsProvider as _};
+use tls_codec::{Deserialize as _, Serialize as _};
+
+use super::{
+    client::Client,
+    errors::{ReferenceError, ScenarioError, SetupError},
+    noop_authentication_service,
+    reference::ReferenceMls,
+    ActionType,
+};
+use crate::{
+    binary_tree::array_representation::LeafNodeIndex,
+    credentials::*,
+    framing::*,
+    group::*,
+    key_packages::KeyPackageIn,
+    storage::OpenMlsProvider,
+    treesync::{LeafNodeParameters, RatchetTree, RatchetTreeIn},
+    versions::ProtocolVersion,
+};
+
+/// A step of a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScenarioStep {
+    /// The `adder` commits adding the `addees`.
+    Add { adder: usize, addees: Vec<usize> },
+    /// The `member` commits an update of its own leaf.
+    Update { member: usize },
+    /// The `remover` commits removing the `targets`.
+    Remove { remover: usize, targets: Vec<usize> },
+    /// The `sender` sends an application message.
+    Message { sender: usize },
+    /// Deliver the oldest queued message of the `recipient`.
+    DeliverNext { recipient: usize },
+    /// Deliver the queued messages of the `recipient` at the positions in
+    /// `order`, in that order. Messages at other positions stay queued.
+    DeliverReordered { recipient: usize, order: Vec<usize> },
+    /// The `adder` commits adding the reference implementation of the
+    /// scenario.
+    AddReference { adder: usize },
+    /// Deliver all queued messages of all clients and of the reference in the
+    /// order they were sent.
+    DeliverAll,
+    /// Assert that all members have received all messages and agree on the
+    /// epoch, the public tree and the exporter secret. If the reference is a
+    /// member, it also has to agree on them and on the epoch authenticator.
+    AssertConverged,
+}
+
+struct QueuedMessage {
+    sender: usize,
+    message: ProtocolMessage,
+}
+
+/// The reference implementation of a scenario together with the messages
+/// queued for it.
+struct Reference {
+    mls: Box<dyn ReferenceMls>,
+    joined: bool,
+    /// The serialized messages together with the content of application
+    /// messages.
+    queue: VecDeque<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+/// A scripted scenario, see the [module documentation](self).
+pub struct Scenario<Provider: OpenMlsProvider> {
+    clients: Vec<Client<Provider>>,
+    group_id: GroupId,
+    join_config: MlsGroupJoinConfig,
+    members: BTreeSet<usize>,
+    queues: Vec<VecDeque<QueuedMessage>>,
+    sent_messages: usize,
+    reference: Option<Reference>,
+}
+
+impl<Provider: OpenMlsProvider + Default> Scenario<Provider> {
+    /// Create a scenario with `number_of_clients` clients, where the client
+    /// with index 0 creates a group with the given `ciphersuite` and
+    /// `create_config`.
+    pub fn new(
+        ciphersuite: Ciphersuite,
+        create_config: MlsGroupCreateConfig,
+        number_of_clients: usize,
+    ) -> Result<Self, SetupError<Provider::StorageError>> {
+        if number_of_clients == 0 {
+            return Err(SetupError::NotEnoughClients);
+        }
+        let clients: Vec<Client<Provider>> = (0..number_of_clients)
+            .map(|i| Client::new(i.to_be_bytes().to_vec()))
+            .collect();
+        let join_config = create_config.join_config().clone();
+        let group_id = clients[0].create_group(create_config, ciphersuite)?;
+
+        Ok(Self {
+            clients,
+            group_id,
+            join_config,
+            members: BTreeSet::from([0]),
+            queues: (0..number_of_clients).map(|_| VecDeque::new()).collect(),
+            sent_messages: 0,
+            reference: None,
+        })
+    }
+}
+
+impl<Provider: OpenMlsProvider> Scenario<Provider> {
+    /// Set the `reference` implementation of the scenario, which joins the
+    /// group with [`ScenarioStep::AddReference`].
+    pub fn with_reference(mut self, reference: impl ReferenceMls + 'static) -> Self {
+        self.reference = Some(Reference {
+            mls: Box::new(reference),
+            joined: false,
+            queue: VecDeque::new(),
+        });
+        self
+    }
+
+    /// Run the `steps` one after the other. Returns the index of the failing
+    /// step together with its error if a step fails.
+    pub fn run(
+        &mut self,
+        steps: &[ScenarioStep],
+    ) -> Result<(), ScenarioError<Provider::StorageError>> {
+        for (step, scenario_step) in steps.iter().enumerate() {
+            self.step(scenario_step)
+                .map_err(|error| ScenarioError { step, error })?;
+        }
+        Ok(())
+    }
+
+    /// Run a single step.
+    ///
+    /// # Panics
+    ///
+    /// [`ScenarioStep::AssertConverged`] panics if the members don't agree on
+    /// the state of the group. Delivering a message to the reference panics if
+    /// it doesn't decrypt the content of an application message that was
+    /// sent.
+    pub fn step(&mut self, step: &ScenarioStep) -> Result<(), SetupError<Provider::StorageError>> {
+        match step {
+            ScenarioStep::Add { adder, addees } => self.add(*adder, addees),
+            ScenarioStep::Update { member } => self.update(*member),
+            ScenarioStep::Remove { remover, targets } => self.remove(*remover, targets),
+            ScenarioStep::Message { sender } => self.message(*sender),
+            ScenarioStep::DeliverNext { recipient } => self.deliver(*recipient, &[0]),
+            ScenarioStep::DeliverReordered { recipient, order } => self.deliver(*recipient, order),
+            ScenarioStep::AddReference { adder } => self.add_reference(*adder),
+            ScenarioStep::DeliverAll => {
+                for recipient in 0..self.clients.len() {
+                    while !self.queues[recipient].is_empty() {
+                        self.deliver(recipient, &[0])?;
+                    }
+                }
+                self.deliver_to_reference()
+            }
+            ScenarioStep::AssertConverged => {
+                self.assert_converged();
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the ID of the group of the scenario.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the indices of the clients that are members of the group.
+    pub fn members(&self) -> impl Iterator<Item = usize> + '_ {
+        self.members.iter().copied()
+    }
+
+    /// Returns the client with the given `index`.
+    pub fn client(&self, index: usize) -> Option<&Client<Provider>> {
+        self.clients.get(index)
+    }
+
+    /// Returns the number of messages queued for the `recipient`.
+    pub fn queued_messages(&self, recipient: usize) -> usize {
+        self.queues.get(recipient).map_or(0, VecDeque::len)
+    }
+
+    fn member(
+        &self,
+        index: usize,
+    ) -> Result<&Client<Provider>, SetupError<Provider::StorageError>> {
+        if !self.members.contains(&index) {
+            return Err(if index < self.clients.len() {
+                SetupError::ClientNotInGroup
+            } else {
+                SetupError::UnknownClientId
+            });
+        }
+        Ok(&self.clients[index])
+    }
+
+    /// Queue the `message` of the `sender` for all other members. The
+    /// `content` of application messages is checked when the message is
+    /// delivered to the reference.
+    fn send(&mut self, sender: usize, message: MlsMessageOut, content: Option<Vec<u8>>) {
+        if let Some(reference) = self.reference.as_mut().filter(|reference| reference.joined) {
+            let serialized = message
+                .tls_serialize_detached()
+                .expect("An unexpected error occurred.");
+            reference.queue.push_back((serialized, content));
+        }
+        let message = MlsMessageIn::from(message)
+            .into_protocol_message()
+            .expect("Unexpected message type.");
+        for &recipient in self.members.iter().filter(|&&member| member != sender) {
+            self.queues[recipient].push_back(QueuedMessage {
+                sender,
+                message: message.clone(),
+            });
+        }
+    }
+
+    fn merge_pending_commit(&self, index: usize) -> Result<(), SetupError<Provider::StorageError>> {
+        let client = &self.clients[index];
+        let mut groups = client
+            .groups
+            .write()
+            .expect("An unexpected error occurred.");
+        let group = groups
+            .get_mut(&self.group_id)
+            .ok_or(SetupError::UnknownGroupId)?;
+        group
+            .merge_pending_commit(&client.provider)
+            .map_err(|e| SetupError::ClientError(e.into()))
+    }
+
+    fn add(
+        &mut self,
+        adder: usize,
+        addees: &[usize],
+    ) -> Result<(), SetupError<Provider::StorageError>> {
+        let adder_client = self.member(adder)?;
+        let ciphersuite = adder_client.groups.read().unwrap()[&self.group_id].ciphersuite();
+        let mut key_packages = Vec::new();
+        for &addee in addees {
+            if self.members.contains(&addee) {
+                return Err(SetupError::ClientAlreadyInGroup);
+            }
+            let addee_client = self.clients.get(addee).ok_or(SetupError::UnknownClientId)?;
+            key_packages.push(addee_client.get_fresh_key_package(ciphersuite)?);
+        }
+
+        let (messages, welcome, _) =
+            adder_client.add_members(ActionType::Commit, &self.group_id, &key_packages)?;
+        self.merge_pending_commit(adder)?;
+        let ratchet_tree =
+            self.clients[adder].groups.read().unwrap()[&self.group_id].export_ratchet_tree();
+
+        for message in messages {
+            self.send(adder, message, None);
+        }
+        let welcome = welcome.ok_or(SetupError::Unknown)?;
+        for &addee in addees {
+            self.clients[addee].join_group(
+                self.join_config.clone(),
+                welcome.clone(),
+                Some(ratchet_tree.clone().into()),
+            )?;
+            self.members.insert(addee);
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, member: usize) -> Result<(), SetupError<Provider::StorageError>> {
+        let (message, _, _) = self.member(member)?.self_update(
+            ActionType::Commit,
+            &self.group_id,
+            LeafNodeParameters::default(),
+        )?;
+        self.merge_pending_commit(member)?;
+        self.send(member, message, None);
+        Ok(())
+    }
+
+    fn remove(
+        &mut self,
+        remover: usize,
+        targets: &[usize],
+    ) -> Result<(), SetupError<Provider::StorageError>> {
+        let remover_client = self.member(remover)?;
+        let leaf_indices = {
+            let groups = remover_client.groups.read().unwrap();
+            let group = &groups[&self.group_id];
+            targets
+                .iter()
+                .map(|&target| {
+                    let identity = target.to_be_bytes();
+                    group
+                        .members()
+                        .find(|Member { credential, .. }| {
+                            credential.serialized_content() == identity
+                        })
+                        .map(|Member { index, .. }| index)
+                        .ok_or(SetupError::ClientNotInGroup)
+                })
+                .collect::<Result<Vec<LeafNodeIndex>, _>>()?
+        };
+
+        let (messages, _, _) =
+            remover_client.remove_members(ActionType::Commit, &self.group_id, &leaf_indices)?;
+        self.merge_pending_commit(remover)?;
+        for message in messages {
+            self.send(remover, message, None);
+        }
+        for target in targets {
+            self.members.remove(target);
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, sender: usize) -> Result<(), SetupError<Provider::StorageError>> {
+        let client = self.member(sender)?;
+        let content = format!("Message {}", self.sent_messages).into_bytes();
+        let message = {
+            let mut groups = client.groups.write().unwrap();
+            let group = groups
+                .get_mut(&self.group_id)
+                .ok_or(SetupError::UnknownGroupId)?;
+            let signature_pk = group.own_leaf().unwrap().signature_key();
+            let signer = SignatureKeyPair::read(
+                client.provider.storage(),
+                signature_pk.as_slice(),
+                group.ciphersuite().signature_algorithm(),
+            )
+            .unwrap();
+            group
+                .create_message(&client.provider, &signer, &content)
+                .map_err(|_| SetupError::Unknown)?
+        };
+        self.sent_messages += 1;
+        self.send(sender, message, Some(content));
+        Ok(())
+    }
+
+    fn add_reference(&mut self, adder: usize) -> Result<(), SetupError<Provider::StorageError>> {
+        self.member(adder)?;
+        let adder_client = &self.clients[adder];
+        let reference = self.reference.as_mut().ok_or(SetupError::NoReference)?;
+        if reference.joined {
+            return Err(SetupError::ClientAlreadyInGroup);
+        }
+        let ciphersuite = adder_client.groups.read().unwrap()[&self.group_id].ciphersuite();
+        let key_package = reference.mls.key_package(ciphersuite)?;
+        let key_package = KeyPackageIn::tls_deserialize_exact(&key_package)
+            .map_err(|e| ReferenceError(e.to_string()))?
+            .validate(adder_client.provider.crypto(), ProtocolVersion::Mls10)
+            .map_err(|e| ReferenceError(e.to_string()))?;
+
+        let (messages, welcome, _) =
+            adder_client.add_members(ActionType::Commit, &self.group_id, &[key_package])?;
+        self.merge_pending_commit(adder)?;
+        for message in messages {
+            self.send(adder, message, None);
+        }
+
+        let ratchet_tree = RatchetTreeIn::from(
+            self.clients[adder].groups.read().unwrap()[&self.group_id].export_ratchet_tree(),
+        );
+        let welcome = MlsMessageOut::from_welcome(
+            welcome.ok_or(SetupError::Unknown)?,
+            ProtocolVersion::Mls10,
+        );
+        let reference = self.reference.as_mut().ok_or(SetupError::NoReference)?;
+        reference.mls.join(
+            &welcome
+                .tls_serialize_detached()
+                .expect("An unexpected error occurred."),
+            &ratchet_tree
+                .tls_serialize_detached()
+                .expect("An unexpected error occurred."),
+        )?;
+        reference.joined = true;
+        Ok(())
+    }
+
+    /// Deliver all queued messages of the reference.
+    fn deliver_to_reference(&mut self) -> Result<(), SetupError<Provider::StorageError>> {
+        let Some(reference) = self.reference.as_mut() else {
+            return Ok(());
+        };
+        while let Some((message, content)) = reference.queue.pop_front() {
+            let processed_content = reference.mls.process_message(&message)?;
+            assert_eq!(
+                processed_content, content,
+                "The reference processed a different message content."
+            );
+        }
+        Ok(())
+    }
+
+    /// Deliver the queued messages of the `recipient` at the positions in
+    /// `order`.
+    fn deliver(
+        &mut self,
+        recipient: usize,
+        order: &[usize],
+    ) -> Result<(), SetupError<Provider::StorageError>> {
+        let queue = self
+            .queues
+            .get_mut(recipient)
+            .ok_or(SetupError::UnknownClientId)?;
+        if order
+            .iter()
+            .enumerate()
+            .any(|(i, position)| *position >= queue.len() || order[..i].contains(position))
+        {
+            return Err(SetupError::NoQueuedMessage);
+        }
+        let mut messages: Vec<Option<QueuedMessage>> = queue.drain(..).map(Some).collect();
+        let delivered: Vec<QueuedMessage> = order
+            .iter()
+            .map(|&position| messages[position].take().unwrap())
+            .collect();
+        queue.extend(messages.into_iter().flatten());
+
+        let client = &self.clients[recipient];
+        for QueuedMessage { sender, message } in delivered {
+            client.receive_messages_for_group(
+                &message,
+                &self.clients[sender].identity,
+                &noop_authentication_service,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn assert_converged(&self) {
+        let mut expected: Option<(GroupEpoch, RatchetTree, Vec<u8>)> = None;
+        let mut epoch_authenticator = None;
+        for &member in &self.members {
+            assert!(
+                self.queues[member].is_empty(),
+                "Client {member} has undelivered messages."
+            );
+            let client = &self.clients[member];
+            let groups = client.groups.read().unwrap();
+            let group = groups
+                .get(&self.group_id)
+                .unwrap_or_else(|| panic!("Client {member} has no group state."));
+            assert!(group.is_active(), "Client {member} is not active.");
+            let state = (
+                group.epoch(),
+                group.export_ratchet_tree(),
+                group
+                    .export_secret(&client.provider, "test", &[], 32)
+                    .expect("An unexpected error occurred."),
+            );
+            match &expected {
+                None => {
+                    epoch_authenticator = Some(group.epoch_authenticator().as_slice().to_vec());
+                    expected = Some(state);
+                }
+                Some(expected) => assert_eq!(
+                    &state, expected,
+                    "Client {member} disagrees on the state of the group."
+                ),
+            }
+        }
+
+        let Some(reference) = self.reference.as_ref().filter(|reference| reference.joined) else {
+            return;
+        };
+        assert!(
+            reference.queue.is_empty(),
+            "The reference has undelivered messages."
+        );
+        let (epoch, ratchet_tree, exported_secret) = expected.expect("The group has no members.");
+        let ratchet_tree = RatchetTreeIn::from(ratchet_tree)
+            .tls_serialize_detached()
+            .expect("An unexpected error occurred.");
+        assert_eq!(
+            reference.mls.epoch(),
+            epoch.as_u64(),
+            "The reference disagrees on the epoch."
+        );
+        assert_eq!(
+            Some(reference.mls.epoch_authenticator()),
+            epoch_authenticator,
+            "The reference disagrees on the epoch authenticator."
+        );
+        assert_eq!(
+            reference.mls.ratchet_tree(),
+            ratchet_tree,
+            "The reference disagrees on the ratchet tree."
+        );
+        assert_eq!(
+            reference
+                .mls
+                .export_secret("test", &[], 32)
+                .expect("The reference failed to export a secret."),
+            exported_secret,
+            "The reference disagrees on the exporter secret."
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Tests of the test utilities.
+
+use proptest::{collection::vec, prelude::*};
+
+use super::{bytes_to_hex, hex_to_bytes};
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn hex_round_trip(bytes in vec(any::<u8>(), 0..1024)) {
+        let hex = bytes_to_hex(&bytes);
+        prop_assert_eq!(hex.len(), 2 * bytes.len());
+        prop_assert_eq!(&hex, &hex::encode_upper(&bytes));
+        prop_assert_eq!(hex_to_bytes(&hex), bytes);
+    }
+
+    #[test]
+    fn hex_to_bytes_ignores_case(bytes in vec(any::<u8>(), 0..1024)) {
+        prop_assert_eq!(hex_to_bytes(&hex::encode(&bytes)), bytes);
+    }
+}
+
+#[test]
+#[should_panic]
+fn hex_to_bytes_invalid_digit() {
+    hex_to_bytes("0G");
+}
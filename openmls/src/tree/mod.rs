@@ -5,6 +5,7 @@ use crate::ciphersuite::*;
 pub mod sender_ratchet;
 
 // Crate
+pub(crate) mod replay_cache;
 pub(crate) mod secret_tree;
 
 #[cfg(any(feature = "test-utils", test))]
@@ -0,0 +1,97 @@
+//! Bounded cache of consumed ciphertext generations.
+//!
+//! The [`ReplayCache`] remembers which generations of which sender ratchet have
+//! already been used to successfully decrypt a [`PrivateMessage`]. Each epoch
+//! has its own cache as part of its message secrets, so that together with the
+//! epoch, the cache tracks `(epoch, sender, generation)` tuples.
+//!
+//! Replays are also caught by the sender ratchets themselves, since key
+//! material is deleted after use. The cache only serves to detect them early
+//! and return a dedicated error.
+//!
+//! [`PrivateMessage`]: crate::framing::PrivateMessage
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binary_tree::LeafNodeIndex,
+    tree::{secret_tree::SecretType, sender_ratchet::Generation},
+};
+
+/// Maximum number of entries kept in a [`ReplayCache`]. Once it is full, the
+/// oldest entries are evicted.
+pub(crate) const REPLAY_CACHE_CAPACITY: usize = 1000;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ReplayCache {
+    entries: VecDeque<(LeafNodeIndex, SecretType, Generation)>,
+}
+
+impl ReplayCache {
+    /// Returns `true` if the given generation of the sender's ratchet was
+    /// already consumed.
+    pub(crate) fn contains(
+        &self,
+        sender: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: Generation,
+    ) -> bool {
+        self.entries.contains(&(sender, secret_type, generation))
+    }
+
+    /// Marks the given generation of the sender's ratchet as consumed.
+    pub(crate) fn insert(
+        &mut self,
+        sender: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: Generation,
+    ) {
+        if self.contains(sender, secret_type, generation) {
+            return;
+        }
+        if self.entries.len() >= REPLAY_CACHE_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((sender, secret_type, generation));
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_cache_is_bounded() {
+        let mut cache = ReplayCache::default();
+        let sender = LeafNodeIndex::new(1);
+
+        cache.insert(sender, SecretType::ApplicationSecret, 0);
+        assert!(cache.contains(sender, SecretType::ApplicationSecret, 0));
+        // The ratchets for handshake and application messages are separate.
+        assert!(!cache.contains(sender, SecretType::HandshakeSecret, 0));
+        assert!(!cache.contains(LeafNodeIndex::new(2), SecretType::ApplicationSecret, 0));
+
+        // Inserting the same entry twice doesn't take up additional space.
+        cache.insert(sender, SecretType::ApplicationSecret, 0);
+        assert_eq!(cache.len(), 1);
+
+        for generation in 1..=REPLAY_CACHE_CAPACITY as Generation {
+            cache.insert(sender, SecretType::ApplicationSecret, generation);
+        }
+        assert_eq!(cache.len(), REPLAY_CACHE_CAPACITY);
+        // The oldest entry was evicted.
+        assert!(!cache.contains(sender, SecretType::ApplicationSecret, 0));
+        assert!(cache.contains(
+            sender,
+            SecretType::ApplicationSecret,
+            REPLAY_CACHE_CAPACITY as Generation
+        ));
+    }
+}
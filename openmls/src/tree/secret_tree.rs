@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::{Ciphersuite, CryptoError};
 use thiserror::Error;
@@ -48,7 +50,7 @@ pub enum SecretTreeError {
     CryptoError(#[from] CryptoError),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum SecretType {
     HandshakeSecret,
     ApplicationSecret,
@@ -112,10 +114,42 @@ pub(crate) struct SecretTreeNode {
     pub(crate) secret: Secret,
 }
 
-#[derive(Serialize, Deserialize)]
+/// The secret tree of an epoch.
+///
+/// Only the nodes that were derived and are still needed are kept, i.e. the
+/// root node until the first sender ratchets are initialized, and afterwards
+/// the copath nodes of the senders whose ratchets were initialized. Parent
+/// nodes are deleted as soon as their children are derived, and leaf nodes as
+/// soon as the sender ratchets of the leaf are initialized. Initializing the
+/// ratchets of a sender thus stores at most `O(log n)` additional nodes.
+#[derive(Deserialize)]
+#[serde(from = "SecretTreeState")]
 #[cfg_attr(any(feature = "test-utils", test), derive(PartialEq, Clone))]
 #[cfg_attr(any(feature = "crypto-debug", test), derive(Debug))]
 pub(crate) struct SecretTree {
+    own_index: LeafNodeIndex,
+    leaf_nodes: BTreeMap<LeafNodeIndex, SecretTreeNode>,
+    parent_nodes: BTreeMap<ParentNodeIndex, SecretTreeNode>,
+    handshake_sender_ratchets: BTreeMap<LeafNodeIndex, SenderRatchet>,
+    application_sender_ratchets: BTreeMap<LeafNodeIndex, SenderRatchet>,
+    size: TreeSize,
+}
+
+/// The serialized form of the [`SecretTree`], which lists the nodes and the
+/// sender ratchets of every index, including the ones that are blank.
+#[derive(Serialize)]
+struct SecretTreeStateRef<'a> {
+    own_index: LeafNodeIndex,
+    leaf_nodes: Vec<Option<&'a SecretTreeNode>>,
+    parent_nodes: Vec<Option<&'a SecretTreeNode>>,
+    handshake_sender_ratchets: Vec<Option<&'a SenderRatchet>>,
+    application_sender_ratchets: Vec<Option<&'a SenderRatchet>>,
+    size: TreeSize,
+}
+
+/// See [`SecretTreeStateRef`].
+#[derive(Deserialize)]
+struct SecretTreeState {
     own_index: LeafNodeIndex,
     leaf_nodes: Vec<Option<SecretTreeNode>>,
     parent_nodes: Vec<Option<SecretTreeNode>>,
@@ -124,6 +158,65 @@ pub(crate) struct SecretTree {
     size: TreeSize,
 }
 
+/// Lists the values of the `map` at the indices `0..count`.
+fn dense<Index: Ord, T>(
+    map: &BTreeMap<Index, T>,
+    count: u32,
+    index: impl Fn(u32) -> Index,
+) -> Vec<Option<&T>> {
+    (0..count).map(|i| map.get(&index(i))).collect()
+}
+
+/// Collects the non-blank values of `values` by their index.
+fn sparse<Index: Ord, T>(
+    values: Vec<Option<T>>,
+    index: impl Fn(u32) -> Index,
+) -> BTreeMap<Index, T> {
+    (0..)
+        .zip(values)
+        .filter_map(|(i, value)| Some((index(i), value?)))
+        .collect()
+}
+
+impl Serialize for SecretTree {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let leaf_count = self.size.leaf_count();
+        SecretTreeStateRef {
+            own_index: self.own_index,
+            leaf_nodes: dense(&self.leaf_nodes, leaf_count, LeafNodeIndex::new),
+            parent_nodes: dense(&self.parent_nodes, leaf_count, ParentNodeIndex::new),
+            handshake_sender_ratchets: dense(
+                &self.handshake_sender_ratchets,
+                leaf_count,
+                LeafNodeIndex::new,
+            ),
+            application_sender_ratchets: dense(
+                &self.application_sender_ratchets,
+                leaf_count,
+                LeafNodeIndex::new,
+            ),
+            size: self.size,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<SecretTreeState> for SecretTree {
+    fn from(state: SecretTreeState) -> Self {
+        Self {
+            own_index: state.own_index,
+            leaf_nodes: sparse(state.leaf_nodes, LeafNodeIndex::new),
+            parent_nodes: sparse(state.parent_nodes, ParentNodeIndex::new),
+            handshake_sender_ratchets: sparse(state.handshake_sender_ratchets, LeafNodeIndex::new),
+            application_sender_ratchets: sparse(
+                state.application_sender_ratchets,
+                LeafNodeIndex::new,
+            ),
+            size: state.size,
+        }
+    }
+}
+
 impl SecretTree {
     /// Creates a new SecretTree based on an `encryption_secret` and group size
     /// `size`. The inner nodes of the tree and the SenderRatchets only get
@@ -134,19 +227,12 @@ impl SecretTree {
         size: TreeSize,
         own_index: LeafNodeIndex,
     ) -> Self {
-        let leaf_count = size.leaf_count() as usize;
-        let leaf_nodes = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let parent_nodes = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let handshake_sender_ratchets = std::iter::repeat_with(|| None).take(leaf_count).collect();
-        let application_sender_ratchets =
-            std::iter::repeat_with(|| None).take(leaf_count).collect();
-
         let mut secret_tree = SecretTree {
             own_index,
-            leaf_nodes,
-            parent_nodes,
-            handshake_sender_ratchets,
-            application_sender_ratchets,
+            leaf_nodes: BTreeMap::new(),
+            parent_nodes: BTreeMap::new(),
+            handshake_sender_ratchets: BTreeMap::new(),
+            application_sender_ratchets: BTreeMap::new(),
             size,
         };
 
@@ -281,14 +367,10 @@ impl SecretTree {
             (handshake_sender_ratchet, application_sender_ratchet)
         };
 
-        *self
-            .handshake_sender_ratchets
-            .get_mut(index.usize())
-            .ok_or(SecretTreeError::IndexOutOfBounds)? = Some(handshake_sender_ratchet);
-        *self
-            .application_sender_ratchets
-            .get_mut(index.usize())
-            .ok_or(SecretTreeError::IndexOutOfBounds)? = Some(application_sender_ratchet);
+        self.handshake_sender_ratchets
+            .insert(index, handshake_sender_ratchet);
+        self.application_sender_ratchets
+            .insert(index, application_sender_ratchet);
 
         // Delete leaf node
         self.set_node(index.into(), None)
@@ -369,8 +451,7 @@ impl SecretTree {
             SecretType::ApplicationSecret => &mut self.application_sender_ratchets,
         };
         sender_ratchets
-            .get_mut(index.usize())
-            .and_then(|r| r.as_mut())
+            .get_mut(&index)
             .ok_or(SecretTreeError::IndexOutOfBounds)
     }
 
@@ -384,10 +465,10 @@ impl SecretTree {
             SecretType::HandshakeSecret => &self.handshake_sender_ratchets,
             SecretType::ApplicationSecret => &self.application_sender_ratchets,
         };
-        match sender_ratchets.get(index.usize()) {
-            Some(sender_ratchet_option) => Ok(sender_ratchet_option.as_ref()),
-            None => Err(SecretTreeError::IndexOutOfBounds),
+        if index.u32() >= self.size.leaf_count() {
+            return Err(SecretTreeError::IndexOutOfBounds);
         }
+        Ok(sender_ratchets.get(&index))
     }
 
     /// Derives the secrets for the child nodes in a SecretTree and blanks the
@@ -451,38 +532,52 @@ impl SecretTree {
         self.set_node(index_in_tree.into(), None)
     }
 
-    fn get_node(&self, index: TreeNodeIndex) -> Result<Option<&SecretTreeNode>, SecretTreeError> {
-        match index {
-            TreeNodeIndex::Leaf(leaf_index) => Ok(self
-                .leaf_nodes
-                .get(leaf_index.usize())
-                .ok_or(SecretTreeError::IndexOutOfBounds)?
-                .as_ref()),
-            TreeNodeIndex::Parent(parent_index) => Ok(self
-                .parent_nodes
-                .get(parent_index.usize())
-                .ok_or(SecretTreeError::IndexOutOfBounds)?
-                .as_ref()),
+    /// Returns the number of leaf and parent nodes that are currently stored.
+    #[cfg(test)]
+    pub(crate) fn stored_nodes(&self) -> usize {
+        self.leaf_nodes.len() + self.parent_nodes.len()
+    }
+
+    /// Returns an error if the node is not in the tree.
+    fn check_bounds(&self, index: TreeNodeIndex) -> Result<(), SecretTreeError> {
+        let index = match index {
+            TreeNodeIndex::Leaf(leaf_index) => leaf_index.u32(),
+            TreeNodeIndex::Parent(parent_index) => parent_index.u32(),
+        };
+        if index >= self.size.leaf_count() {
+            return Err(SecretTreeError::IndexOutOfBounds);
         }
+        Ok(())
     }
 
+    fn get_node(&self, index: TreeNodeIndex) -> Result<Option<&SecretTreeNode>, SecretTreeError> {
+        self.check_bounds(index)?;
+        Ok(match index {
+            TreeNodeIndex::Leaf(leaf_index) => self.leaf_nodes.get(&leaf_index),
+            TreeNodeIndex::Parent(parent_index) => self.parent_nodes.get(&parent_index),
+        })
+    }
+
+    /// Stores the `node` at the `index`, or deletes the node at the `index` if
+    /// `node` is `None`.
     fn set_node(
         &mut self,
         index: TreeNodeIndex,
         node: Option<SecretTreeNode>,
     ) -> Result<(), SecretTreeError> {
-        match index {
-            TreeNodeIndex::Leaf(leaf_index) => {
-                *self
-                    .leaf_nodes
-                    .get_mut(leaf_index.usize())
-                    .ok_or(SecretTreeError::IndexOutOfBounds)? = node;
+        self.check_bounds(index)?;
+        match (index, node) {
+            (TreeNodeIndex::Leaf(leaf_index), Some(node)) => {
+                self.leaf_nodes.insert(leaf_index, node);
+            }
+            (TreeNodeIndex::Leaf(leaf_index), None) => {
+                self.leaf_nodes.remove(&leaf_index);
+            }
+            (TreeNodeIndex::Parent(parent_index), Some(node)) => {
+                self.parent_nodes.insert(parent_index, node);
             }
-            TreeNodeIndex::Parent(parent_index) => {
-                *self
-                    .parent_nodes
-                    .get_mut(parent_index.usize())
-                    .ok_or(SecretTreeError::IndexOutOfBounds)? = node;
+            (TreeNodeIndex::Parent(parent_index), None) => {
+                self.parent_nodes.remove(&parent_index);
             }
         }
         Ok(())
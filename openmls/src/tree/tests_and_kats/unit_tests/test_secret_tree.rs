@@ -221,6 +221,65 @@ fn increment_generation() {
     }
 }
 
+// This tests that only the nodes that are still needed are stored, and that
+// the serialization lists every index of the tree.
+#[openmls_test::openmls_test]
+fn pruned_nodes() {
+    // The tree may have more leaves than requested, so the expected numbers
+    // are derived from its actual leaf count.
+    let size = TreeSize::from_leaf_count(1024);
+    let leaf_count = size.leaf_count();
+
+    let configuration = &SenderRatchetConfiguration::default();
+    let encryption_secret = EncryptionSecret::random(ciphersuite, provider.rand());
+    let mut secret_tree = SecretTree::new(encryption_secret, size, LeafNodeIndex::new(0u32));
+    assert_eq!(secret_tree.stored_nodes(), 1);
+
+    // Only the copath of the sender is left.
+    secret_tree
+        .secret_for_decryption(
+            ciphersuite,
+            provider.crypto(),
+            LeafNodeIndex::new(5u32),
+            SecretType::ApplicationSecret,
+            0,
+            configuration,
+        )
+        .expect("Error getting decryption secret");
+    assert_eq!(secret_tree.stored_nodes(), leaf_count.ilog2() as usize);
+
+    let serialized = serde_json::to_value(&secret_tree).unwrap();
+    assert_eq!(
+        serialized["leaf_nodes"].as_array().unwrap().len(),
+        leaf_count as usize
+    );
+    let deserialized: SecretTree = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized, secret_tree);
+
+    // Nothing is left once the sender ratchets of every leaf are initialized.
+    secret_tree
+        .secret_for_encryption(
+            ciphersuite,
+            provider.crypto(),
+            LeafNodeIndex::new(0u32),
+            SecretType::HandshakeSecret,
+        )
+        .expect("Error getting encryption secret");
+    for i in 1..leaf_count {
+        secret_tree
+            .secret_for_decryption(
+                ciphersuite,
+                provider.crypto(),
+                LeafNodeIndex::new(i),
+                SecretType::HandshakeSecret,
+                0,
+                configuration,
+            )
+            .expect("Error getting decryption secret");
+    }
+    assert_eq!(secret_tree.stored_nodes(), 0);
+}
+
 #[openmls_test::openmls_test]
 fn secret_tree() {
     let leaf_index = 0u32;
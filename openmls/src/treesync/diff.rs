@@ -17,7 +17,7 @@
 //! functions that are not expected to fail and throw an error, will still
 //! return a [`Result`] since they may throw a
 //! [`LibraryError`](TreeSyncDiffError::LibraryError).
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use log::debug;
 use openmls_traits::crypto::OpenMlsCrypto;
@@ -45,7 +45,7 @@ use crate::{
         },
         MlsBinaryTreeDiff, StagedMlsBinaryTreeDiff,
     },
-    ciphersuite::Secret,
+    ciphersuite::{hash_ref::KeyPackageRef, Secret},
     error::LibraryError,
     messages::PathSecret,
     schedule::CommitSecret,
@@ -65,6 +65,8 @@ pub(crate) type UpdatePathResult = (
 pub(crate) struct StagedTreeSyncDiff {
     diff: StagedMlsBinaryTreeDiff<TreeSyncLeafNode, TreeSyncParentNode>,
     new_tree_hash: Vec<u8>,
+    #[serde(default)]
+    key_package_refs: BTreeMap<LeafNodeIndex, KeyPackageRef>,
 }
 
 impl StagedTreeSyncDiff {
@@ -73,8 +75,9 @@ impl StagedTreeSyncDiff {
     ) -> (
         StagedMlsBinaryTreeDiff<TreeSyncLeafNode, TreeSyncParentNode>,
         Vec<u8>,
+        BTreeMap<LeafNodeIndex, KeyPackageRef>,
     ) {
-        (self.diff, self.new_tree_hash)
+        (self.diff, self.new_tree_hash, self.key_package_refs)
     }
 }
 
@@ -86,12 +89,15 @@ impl StagedTreeSyncDiff {
 /// invariants. See [`TreeSync`] for the list of invariants.
 pub(crate) struct TreeSyncDiff<'a> {
     diff: MlsBinaryTreeDiff<'a, TreeSyncLeafNode, TreeSyncParentNode>,
+    /// The references of the key packages of the leaves added in this diff.
+    key_package_refs: BTreeMap<LeafNodeIndex, KeyPackageRef>,
 }
 
 impl<'a> From<&'a TreeSync> for TreeSyncDiff<'a> {
     fn from(tree_sync: &'a TreeSync) -> Self {
         TreeSyncDiff {
             diff: tree_sync.tree.empty_diff(),
+            key_package_refs: BTreeMap::new(),
         }
     }
 }
@@ -249,6 +255,17 @@ impl TreeSyncDiff<'_> {
         Ok(leaf_index)
     }
 
+    /// Record that the leaf at `leaf_index` was added with the key package
+    /// with the given `key_package_ref`. The reference is added to the member
+    /// index of the [`TreeSync`] when this diff is merged.
+    pub(crate) fn set_key_package_ref(
+        &mut self,
+        leaf_index: LeafNodeIndex,
+        key_package_ref: KeyPackageRef,
+    ) {
+        self.key_package_refs.insert(leaf_index, key_package_ref);
+    }
+
     /// Remove a group member by blanking the target leaf and its direct path.
     /// After blanking the leaf and its direct path, the diff is trimmed, i.e.
     /// leaves are removed until the right-most leaf in the tree, as well as its
@@ -256,6 +273,7 @@ impl TreeSyncDiff<'_> {
     ///
     /// Returns an error if the target leaf is outside of the tree.
     pub(crate) fn blank_leaf(&mut self, leaf_index: LeafNodeIndex) {
+        self.key_package_refs.remove(&leaf_index);
         self.diff
             .replace_leaf(leaf_index, TreeSyncLeafNode::blank());
         // This also erases any cached tree hash in the direct path.
@@ -644,19 +662,9 @@ impl TreeSyncDiff<'_> {
                 // of the commit.
                 let exclusion_list = HashSet::from_iter(parent_node.unmerged_leaves().iter());
 
-                // Compute the original tree hash (oth) for the left and right child.
-                let oth_left =
-                    self.compute_tree_hash(crypto, ciphersuite, left_child, &exclusion_list)?;
-
-                let oth_right =
-                    self.compute_tree_hash(crypto, ciphersuite, right_child, &exclusion_list)?;
-
                 // Compute the parent hash for both child roles.
-                let parent_hash_left =
-                    parent_node.compute_parent_hash(crypto, ciphersuite, &oth_right)?;
-
-                let parent_hash_right =
-                    parent_node.compute_parent_hash(crypto, ciphersuite, &oth_left)?;
+                let (parent_hash_left, parent_hash_right) =
+                    self.compute_parent_hashes(crypto, ciphersuite, parent_index, parent_node)?;
 
                 // Compute the resolution for both children.
                 let left_resolution = self.resolution(left_child, &exclusion_list);
@@ -691,6 +699,43 @@ impl TreeSyncDiff<'_> {
         Ok(())
     }
 
+    /// Compute the parent hashes of the given parent node for both child
+    /// roles.
+    ///
+    /// Returns the parent hash as carried by a descendant in the resolution of
+    /// the left child, i.e. computed using the original tree hash of the right
+    /// child, and the parent hash as carried by a descendant in the resolution
+    /// of the right child. The unmerged leaves of the parent node are excluded
+    /// when computing the original tree hashes.
+    pub(super) fn compute_parent_hashes(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        parent_index: ParentNodeIndex,
+        parent_node: &ParentNode,
+    ) -> Result<(Vec<u8>, Vec<u8>), LibraryError> {
+        let exclusion_list = HashSet::from_iter(parent_node.unmerged_leaves().iter());
+
+        // Compute the original tree hash (oth) for the left and right child.
+        let oth_left = self.compute_tree_hash(
+            crypto,
+            ciphersuite,
+            self.diff.left_child(parent_index),
+            &exclusion_list,
+        )?;
+        let oth_right = self.compute_tree_hash(
+            crypto,
+            ciphersuite,
+            self.diff.right_child(parent_index),
+            &exclusion_list,
+        )?;
+
+        let parent_hash_left = parent_node.compute_parent_hash(crypto, ciphersuite, &oth_right)?;
+        let parent_hash_right = parent_node.compute_parent_hash(crypto, ciphersuite, &oth_left)?;
+
+        Ok((parent_hash_left, parent_hash_right))
+    }
+
     /// This turns the diff into a staged diff. In the process, the diff
     /// computes and sets the new tree hash.
     pub(crate) fn into_staged_diff(
@@ -703,6 +748,7 @@ impl TreeSyncDiff<'_> {
         Ok(StagedTreeSyncDiff {
             diff: self.diff.into(),
             new_tree_hash,
+            key_package_refs: self.key_package_refs,
         })
     }
 
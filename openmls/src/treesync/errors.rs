@@ -6,7 +6,12 @@ use thiserror::Error;
 
 use super::*;
 use crate::{
-    binary_tree::MlsBinaryTreeDiffError, ciphersuite::signable::SignatureError, error::LibraryError,
+    binary_tree::MlsBinaryTreeDiffError,
+    ciphersuite::signable::SignatureError,
+    credentials::CredentialType,
+    error::LibraryError,
+    extensions::{errors::ApplicationExtensionError, ExtensionType},
+    messages::proposals::ProposalType,
 };
 
 // === Public errors ===
@@ -242,6 +247,36 @@ pub enum LeafNodeValidationError {
     /// The credential used by a member is not supported by this leaf node.
     #[error("The credential used by a member is not supported by this leaf node.")]
     MemberCredentialNotSupportedByLeafNode,
+    /// The leaf node does not support an extension in the group context.
+    #[error("The leaf node does not support the group context extension {0:?}.")]
+    UnsupportedGroupContextExtension(ExtensionType),
+    /// An application extension of the leaf node is invalid.
+    #[error("An application extension of the leaf node is invalid: {0}")]
+    InvalidApplicationExtension(ApplicationExtensionError),
+}
+
+/// The capabilities a leaf node lacks to join a group.
+///
+/// This lists everything the group requires through its required capabilities
+/// extension or uses in its group context extensions that the leaf node's
+/// capabilities do not support.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MissingCapabilities {
+    /// Extension types that are not supported.
+    pub extension_types: Vec<ExtensionType>,
+    /// Proposal types that are not supported.
+    pub proposal_types: Vec<ProposalType>,
+    /// Credential types that are not supported.
+    pub credential_types: Vec<CredentialType>,
+}
+
+impl MissingCapabilities {
+    /// Returns `true` if no capabilities are missing.
+    pub fn is_empty(&self) -> bool {
+        self.extension_types.is_empty()
+            && self.proposal_types.is_empty()
+            && self.credential_types.is_empty()
+    }
 }
 
 /// Errors that can happen during lifetime validation.
@@ -253,6 +288,12 @@ pub enum LifetimeError {
     /// Lifetime doesn't cover current time.
     #[error("Lifetime doesn't cover current time.")]
     NotCurrent,
+    /// Lifetime ends before the required margin.
+    #[error("Lifetime ends before the required margin.")]
+    ExpiresTooSoon,
+    /// The current time is not available.
+    #[error("The current time is not available.")]
+    TimeUnavailable,
 }
 
 /// Errors that can happen during path validation.
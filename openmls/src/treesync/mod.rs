@@ -18,6 +18,7 @@
 // Finally, this module contains the [`treekem`] module, which allows the
 // encryption and decryption of updates to the tree.
 
+use std::collections::{BTreeMap, HashMap};
 #[cfg(any(feature = "test-utils", test))]
 use std::fmt;
 
@@ -50,7 +51,7 @@ use crate::{
         array_representation::{is_node_in_tree, tree::TreeNode, LeafNodeIndex, TreeSize},
         MlsBinaryTree, MlsBinaryTreeError,
     },
-    ciphersuite::{signable::Verifiable, Secret},
+    ciphersuite::{hash_ref::KeyPackageRef, signable::Verifiable, Secret},
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::Extensions,
@@ -245,6 +246,27 @@ impl RatchetTreeIn {
         Self(nodes)
     }
 
+    /// Returns the members in the leaves of the tree without verifying the
+    /// tree.
+    pub(crate) fn unverified_members(&self) -> Vec<Member> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(node_index, node)| match node {
+                Some(NodeIn::LeafNode(leaf_node)) => Some(
+                    Member::new(
+                        LeafNodeIndex::new(node_index as u32 / 2),
+                        leaf_node.encryption_key().as_slice().to_vec(),
+                        leaf_node.signature_key().as_slice().to_vec(),
+                        leaf_node.credential().clone(),
+                    )
+                    .with_application_id(leaf_node.application_id()),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
+
     #[cfg(test)]
     pub(crate) fn from_nodes(nodes: Vec<Option<NodeIn>>) -> Self {
         Self(nodes)
@@ -372,10 +394,89 @@ impl fmt::Display for RatchetTree {
 /// creating a new instance from an imported set of nodes, as well as when
 /// merging a diff.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "StoredTreeSync")]
 #[cfg_attr(any(test, feature = "test-utils"), derive(PartialEq, Clone))]
 pub(crate) struct TreeSync {
     tree: MlsBinaryTree<TreeSyncLeafNode, TreeSyncParentNode>,
     tree_hash: Vec<u8>,
+    /// The references of the key packages with which members were added to
+    /// the tree. These are only known for members that were added by a commit
+    /// that was merged into this tree.
+    #[serde(default)]
+    key_package_refs: BTreeMap<LeafNodeIndex, KeyPackageRef>,
+    /// Not stored, but rebuilt from the leaves when the tree is loaded.
+    #[serde(skip)]
+    member_index: MemberIndex,
+}
+
+/// The stored state of a [`TreeSync`].
+#[derive(Deserialize)]
+struct StoredTreeSync {
+    tree: MlsBinaryTree<TreeSyncLeafNode, TreeSyncParentNode>,
+    tree_hash: Vec<u8>,
+    #[serde(default)]
+    key_package_refs: BTreeMap<LeafNodeIndex, KeyPackageRef>,
+}
+
+impl From<StoredTreeSync> for TreeSync {
+    fn from(stored: StoredTreeSync) -> Self {
+        let mut member_index = MemberIndex::new(&stored.tree);
+        for (&leaf_index, key_package_ref) in &stored.key_package_refs {
+            member_index
+                .key_package_refs
+                .insert(key_package_ref.clone(), leaf_index);
+        }
+        Self {
+            member_index,
+            tree: stored.tree,
+            tree_hash: stored.tree_hash,
+            key_package_refs: stored.key_package_refs,
+        }
+    }
+}
+
+/// The leaf indices of the members of a [`TreeSync`] by the signature and
+/// encryption keys of their leaf nodes and by the references of the key
+/// packages they were added with, such that members can be found without
+/// iterating over all leaves.
+#[derive(Debug, Default)]
+#[cfg_attr(any(test, feature = "test-utils"), derive(PartialEq, Clone))]
+struct MemberIndex {
+    signature_keys: HashMap<Vec<u8>, LeafNodeIndex>,
+    encryption_keys: HashMap<Vec<u8>, LeafNodeIndex>,
+    key_package_refs: HashMap<KeyPackageRef, LeafNodeIndex>,
+}
+
+impl MemberIndex {
+    fn new(tree: &MlsBinaryTree<TreeSyncLeafNode, TreeSyncParentNode>) -> Self {
+        let mut member_index = Self::default();
+        for (leaf_index, leaf) in tree.leaves() {
+            if let Some(leaf_node) = leaf.node() {
+                member_index.insert(leaf_index, leaf_node);
+            }
+        }
+        member_index
+    }
+
+    fn insert(&mut self, leaf_index: LeafNodeIndex, leaf_node: &LeafNode) {
+        self.signature_keys
+            .insert(leaf_node.signature_key().as_slice().to_vec(), leaf_index);
+        self.encryption_keys
+            .insert(leaf_node.encryption_key().as_slice().to_vec(), leaf_index);
+    }
+
+    /// Removes the keys of the `leaf_node` if they still belong to the leaf at
+    /// `leaf_index`.
+    fn remove(&mut self, leaf_index: LeafNodeIndex, leaf_node: &LeafNode) {
+        let signature_key = leaf_node.signature_key().as_slice();
+        if self.signature_keys.get(signature_key) == Some(&leaf_index) {
+            self.signature_keys.remove(signature_key);
+        }
+        let encryption_key = leaf_node.encryption_key().as_slice();
+        if self.encryption_keys.get(encryption_key) == Some(&leaf_index) {
+            self.encryption_keys.remove(encryption_key);
+        }
+    }
 }
 
 impl TreeSync {
@@ -414,8 +515,10 @@ impl TreeSync {
         let tree = MlsBinaryTree::new(nodes)
             .map_err(|_| LibraryError::custom("Unexpected error creating the binary tree."))?;
         let mut tree_sync = Self {
+            member_index: MemberIndex::new(&tree),
             tree,
             tree_hash: vec![],
+            key_package_refs: BTreeMap::new(),
         };
         // Populate tree hash caches.
         tree_sync.populate_parent_hashes(provider.crypto(), ciphersuite)?;
@@ -429,11 +532,50 @@ impl TreeSync {
     }
 
     /// Merge the given diff into this `TreeSync` instance, refreshing the
-    /// `tree_hash` value in the process.
+    /// `tree_hash` value and the member index in the process.
     pub(crate) fn merge_diff(&mut self, tree_sync_diff: StagedTreeSyncDiff) {
-        let (diff, new_tree_hash) = tree_sync_diff.into_parts();
+        let (diff, new_tree_hash, key_package_refs) = tree_sync_diff.into_parts();
+        let changed_leaves = diff.changed_leaves().collect::<Vec<_>>();
+        for &leaf_index in &changed_leaves {
+            if let Some(leaf_node) = self.tree.leaf(leaf_index).node() {
+                self.member_index.remove(leaf_index, leaf_node);
+            }
+        }
+        // Leaves that are cut off when the tree shrinks are not part of the
+        // changed leaves.
+        let new_leaf_count = diff.tree_size().leaf_count();
+        for leaf_index in new_leaf_count..self.tree.tree_size().leaf_count() {
+            let leaf_index = LeafNodeIndex::new(leaf_index);
+            if let Some(leaf_node) = self.tree.leaf(leaf_index).node() {
+                self.member_index.remove(leaf_index, leaf_node);
+            }
+            self.remove_key_package_ref(leaf_index);
+        }
         self.tree_hash = new_tree_hash;
         self.tree.merge_diff(diff);
+        for leaf_index in changed_leaves {
+            match self.tree.leaf(leaf_index).node() {
+                Some(leaf_node) => self.member_index.insert(leaf_index, leaf_node),
+                // The member was removed, so its key package reference is gone
+                // as well.
+                None => self.remove_key_package_ref(leaf_index),
+            }
+        }
+        for (leaf_index, key_package_ref) in key_package_refs {
+            self.remove_key_package_ref(leaf_index);
+            self.member_index
+                .key_package_refs
+                .insert(key_package_ref.clone(), leaf_index);
+            self.key_package_refs.insert(leaf_index, key_package_ref);
+        }
+    }
+
+    /// Remove the key package reference of the member at `leaf_index` from
+    /// the tree and the member index.
+    fn remove_key_package_ref(&mut self, leaf_index: LeafNodeIndex) {
+        if let Some(key_package_ref) = self.key_package_refs.remove(&leaf_index) {
+            self.member_index.key_package_refs.remove(&key_package_ref);
+        }
     }
 
     /// Create an empty diff based on this [`TreeSync`] instance all operations
@@ -470,8 +612,10 @@ impl TreeSync {
         }
         let tree = MlsBinaryTree::new(ts_nodes).map_err(|_| PublicTreeError::MalformedTree)?;
         let mut tree_sync = Self {
+            member_index: MemberIndex::new(&tree),
             tree,
             tree_hash: vec![],
+            key_package_refs: BTreeMap::new(),
         };
         // Verify all parent hashes.
         tree_sync
@@ -578,6 +722,7 @@ impl TreeSync {
                     leaf_node.signature_key().as_slice().to_vec(),
                     leaf_node.credential().clone(),
                 )
+                .with_application_id(leaf_node.application_id())
             })
     }
 
@@ -638,6 +783,40 @@ impl TreeSync {
         tsn.node().as_ref()
     }
 
+    /// Return the index of the member whose leaf node has the given
+    /// `signature_key`, or `None` if there is no such member.
+    pub(crate) fn leaf_index_by_signature_key(
+        &self,
+        signature_key: &[u8],
+    ) -> Option<LeafNodeIndex> {
+        self.member_index.signature_keys.get(signature_key).copied()
+    }
+
+    /// Return the index of the member whose leaf node has the given
+    /// `encryption_key`, or `None` if there is no such member.
+    pub(crate) fn leaf_index_by_encryption_key(
+        &self,
+        encryption_key: &[u8],
+    ) -> Option<LeafNodeIndex> {
+        self.member_index
+            .encryption_keys
+            .get(encryption_key)
+            .copied()
+    }
+
+    /// Return the index of the member that was added with the key package
+    /// with the given `key_package_ref`, or `None` if there is no such member
+    /// or the member was added before this tree was created.
+    pub(crate) fn leaf_index_by_key_package_ref(
+        &self,
+        key_package_ref: &KeyPackageRef,
+    ) -> Option<LeafNodeIndex> {
+        self.member_index
+            .key_package_refs
+            .get(key_package_ref)
+            .copied()
+    }
+
     /// Returns a [`TreeSyncError`] if the `leaf_index` is not a leaf in this
     /// tree or empty.
     pub(crate) fn is_leaf_in_tree(&self, leaf_index: LeafNodeIndex) -> bool {
@@ -45,16 +45,22 @@ impl EncryptionKey {
         self.key.as_slice()
     }
 
-    /// Encrypt to this HPKE public key.
-    pub(crate) fn encrypt(
-        &self,
+    /// Encrypt the same plaintext to each of the HPKE public keys.
+    ///
+    /// The ciphertexts are returned in the order of the `public_keys`.
+    pub(crate) fn encrypt_to_all(
+        public_keys: &[EncryptionKey],
         crypto: &impl OpenMlsCrypto,
         ciphersuite: Ciphersuite,
         context: &[u8],
         plaintext: &[u8],
-    ) -> Result<HpkeCiphertext, LibraryError> {
-        hpke::encrypt_with_label(
-            self.as_slice(),
+    ) -> Result<Vec<HpkeCiphertext>, LibraryError> {
+        let public_keys = public_keys
+            .iter()
+            .map(|public_key| public_key.as_slice())
+            .collect::<Vec<_>>();
+        hpke::encrypt_with_label_multi(
+            &public_keys,
             "UpdatePathNode",
             context,
             plaintext,
@@ -212,6 +218,15 @@ impl EncryptionKeyPair {
     ) -> Result<Self, LibraryError> {
         let ikm =
             Secret::random(ciphersuite, rand).map_err(LibraryError::unexpected_crypto_error)?;
+        Self::derive(crypto, ciphersuite, &ikm)
+    }
+
+    /// Derive an encryption key pair from the given input keying material.
+    pub(crate) fn derive(
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        ikm: &Secret,
+    ) -> Result<Self, LibraryError> {
         Ok(crypto
             .derive_hpke_keypair(ciphersuite.hpke_config(), ikm.as_slice())
             .map_err(LibraryError::unexpected_crypto_error)?
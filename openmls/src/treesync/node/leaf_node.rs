@@ -93,6 +93,10 @@ impl LeafNodeParameters {
         self.extensions.as_ref()
     }
 
+    pub(crate) fn set_credential_with_key(&mut self, credential_with_key: CredentialWithKey) {
+        self.credential_with_key = Some(credential_with_key);
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.credential_with_key.is_none()
             && self.capabilities.is_none()
@@ -210,6 +214,34 @@ impl LeafNode {
         Ok((leaf_node, encryption_key_pair))
     }
 
+    /// Create a new [`LeafNode`] like [`LeafNode::new()`], but with the given
+    /// encryption key pair instead of a freshly generated one.
+    /// The caller is responsible for storing the private key.
+    pub(crate) fn new_with_encryption_key_pair(
+        signer: &impl Signer,
+        new_leaf_node_params: NewLeafNodeParams,
+        encryption_key_pair: &EncryptionKeyPair,
+    ) -> Result<Self, LibraryError> {
+        let NewLeafNodeParams {
+            ciphersuite: _,
+            credential_with_key,
+            leaf_node_source,
+            capabilities,
+            extensions,
+            tree_info_tbs,
+        } = new_leaf_node_params;
+
+        Self::new_with_key(
+            encryption_key_pair.public_key().clone(),
+            credential_with_key,
+            leaf_node_source,
+            capabilities,
+            extensions,
+            tree_info_tbs,
+            signer,
+        )
+    }
+
     /// Creates a new placeholder [`LeafNode`] that is used to build external
     /// commits.
     ///
@@ -438,6 +470,15 @@ impl LeafNode {
         &self.payload.extensions
     }
 
+    /// Returns the application id of this leaf node if it has an
+    /// [`ApplicationIdExtension`](crate::extensions::ApplicationIdExtension).
+    pub fn application_id(&self) -> Option<&[u8]> {
+        self.payload
+            .extensions
+            .application_id()
+            .map(|extension| extension.as_slice())
+    }
+
     /// Returns `true` if the [`ExtensionType`] is supported by this leaf node.
     pub(crate) fn supports_extension(&self, extension_type: &ExtensionType) -> bool {
         extension_type.is_default()
@@ -753,6 +794,19 @@ impl LeafNodeIn {
     pub fn credential(&self) -> &Credential {
         &self.payload.credential
     }
+
+    /// Returns the `encryption_key`.
+    pub(crate) fn encryption_key(&self) -> &EncryptionKey {
+        &self.payload.encryption_key
+    }
+
+    /// Returns the (unverified) application id.
+    pub(crate) fn application_id(&self) -> Option<&[u8]> {
+        self.payload
+            .extensions
+            .application_id()
+            .map(|extension| extension.as_slice())
+    }
 }
 
 impl From<LeafNode> for LeafNodeIn {
@@ -837,6 +891,7 @@ impl Verifiable for VerifiableKeyPackageLeafNode {
         pk: &crate::ciphersuite::OpenMlsSignaturePublicKey,
     ) -> Result<Self::VerifiedStruct, crate::ciphersuite::signable::SignatureError> {
         self.verify_no_out(crypto, pk)?;
+        self.payload.verify_credential(crypto, pk)?;
         Ok(LeafNode {
             payload: self.payload,
             signature: self.signature,
@@ -846,6 +901,23 @@ impl Verifiable for VerifiableKeyPackageLeafNode {
 
 impl VerifiedStruct for LeafNode {}
 
+impl LeafNodePayload {
+    /// Validate the credential for use with the signature key `pk`, e.g. the
+    /// certificate chain of an X.509 credential.
+    fn verify_credential(
+        &self,
+        crypto: &impl openmls_traits::crypto::OpenMlsCrypto,
+        pk: &crate::ciphersuite::OpenMlsSignaturePublicKey,
+    ) -> Result<(), crate::ciphersuite::signable::SignatureError> {
+        self.credential
+            .validate(crypto, pk.signature_scheme(), pk.as_slice())
+            .map_err(|e| {
+                log::error!("Invalid leaf node credential: {:?}", e);
+                crate::ciphersuite::signable::SignatureError::VerificationError
+            })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct VerifiableUpdateLeafNode {
     payload: LeafNodePayload,
@@ -892,6 +964,7 @@ impl Verifiable for VerifiableUpdateLeafNode {
         pk: &crate::ciphersuite::OpenMlsSignaturePublicKey,
     ) -> Result<Self::VerifiedStruct, crate::ciphersuite::signable::SignatureError> {
         self.verify_no_out(crypto, pk)?;
+        self.payload.verify_credential(crypto, pk)?;
         Ok(LeafNode {
             payload: self.payload,
             signature: self.signature,
@@ -946,6 +1019,7 @@ impl Verifiable for VerifiableCommitLeafNode {
         pk: &crate::ciphersuite::OpenMlsSignaturePublicKey,
     ) -> Result<Self::VerifiedStruct, crate::ciphersuite::signable::SignatureError> {
         self.verify_no_out(crypto, pk)?;
+        self.payload.verify_credential(crypto, pk)?;
         Ok(LeafNode {
             payload: self.payload,
             signature: self.signature,
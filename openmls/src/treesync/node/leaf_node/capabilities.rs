@@ -1,14 +1,19 @@
-use openmls_traits::types::{Ciphersuite, VerifiableCiphersuite};
+use openmls_traits::{
+    random::OpenMlsRand,
+    types::{Ciphersuite, CryptoError, VerifiableCiphersuite},
+};
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSize};
 
 #[cfg(doc)]
 use super::LeafNode;
+#[cfg(doc)]
+use crate::ciphersuite::CiphersuiteRegistry;
 use crate::{
     credentials::CredentialType,
     extensions::{Extension, ExtensionType, Extensions, RequiredCapabilitiesExtension},
     messages::proposals::ProposalType,
-    treesync::errors::LeafNodeValidationError,
+    treesync::errors::{LeafNodeValidationError, MissingCapabilities},
     versions::ProtocolVersion,
 };
 
@@ -47,6 +52,11 @@ impl Capabilities {
     /// Create a new [`Capabilities`] struct with the given configuration.
     /// Any argument that is `None` is filled with the default values from the
     /// global configuration.
+    ///
+    /// The default ciphersuites are all ciphersuites known to OpenMLS,
+    /// independent of whether the crypto provider supports them. Use
+    /// [`CiphersuiteRegistry::default_capabilities()`] to advertise the
+    /// ciphersuites of a provider.
     // TODO(#1232)
     pub fn new(
         versions: Option<&[ProtocolVersion]>,
@@ -173,6 +183,73 @@ impl Capabilities {
         Ok(())
     }
 
+    /// Returns the capabilities that are required by the group, either through
+    /// the `required_capabilities` or through the `group_context_extensions`,
+    /// but not contained in these [`Capabilities`].
+    pub(crate) fn missing_capabilities(
+        &self,
+        required_capabilities: Option<&RequiredCapabilitiesExtension>,
+        group_context_extensions: &Extensions,
+    ) -> MissingCapabilities {
+        let mut missing_capabilities = MissingCapabilities::default();
+
+        let required_extension_types = required_capabilities
+            .map(|rc| rc.extension_types())
+            .unwrap_or_default()
+            .iter()
+            .copied()
+            .chain(
+                group_context_extensions
+                    .iter()
+                    .map(Extension::extension_type),
+            );
+        for extension_type in required_extension_types {
+            if !self.contains_extension(extension_type)
+                && !missing_capabilities
+                    .extension_types
+                    .contains(&extension_type)
+            {
+                missing_capabilities.extension_types.push(extension_type);
+            }
+        }
+
+        if let Some(required_capabilities) = required_capabilities {
+            missing_capabilities.proposal_types = required_capabilities
+                .proposal_types()
+                .iter()
+                .filter(|&p| !self.contains_proposal(*p))
+                .copied()
+                .collect();
+            missing_capabilities.credential_types = required_capabilities
+                .credential_types()
+                .iter()
+                .filter(|&c| !self.contains_credential(*c))
+                .copied()
+                .collect();
+        }
+
+        missing_capabilities
+    }
+
+    /// Check if these [`Capabilities`] support all extensions in the
+    /// `group_context_extensions`. Returns the first unsupported extension type
+    /// otherwise.
+    pub(crate) fn supports_group_context_extensions(
+        &self,
+        group_context_extensions: &Extensions,
+    ) -> Result<(), LeafNodeValidationError> {
+        match group_context_extensions
+            .iter()
+            .map(Extension::extension_type)
+            .find(|&extension_type| !self.contains_extension(extension_type))
+        {
+            Some(extension_type) => Err(LeafNodeValidationError::UnsupportedGroupContextExtension(
+                extension_type,
+            )),
+            None => Ok(()),
+        }
+    }
+
     /// Check if these [`Capabilities`] contain all the extensions.
     pub(crate) fn contains_extensions(&self, extension: &Extensions) -> bool {
         extension
@@ -205,6 +282,146 @@ impl Capabilities {
     pub(crate) fn contains_ciphersuite(&self, ciphersuite: VerifiableCiphersuite) -> bool {
         self.ciphersuites().contains(&ciphersuite)
     }
+
+    /// Add the extension type to these [`Capabilities`] if it is not contained
+    /// yet.
+    pub(crate) fn with_extension(mut self, extension_type: ExtensionType) -> Self {
+        if !self.contains_extension(extension_type) {
+            self.extensions.push(extension_type);
+        }
+        self
+    }
+
+    /// Returns the [`Capabilities`] that are contained in both `self` and
+    /// `other`.
+    ///
+    /// The entries keep the order of `self`, so that the result reflects the
+    /// preferences of `self`. GREASE values are never part of the
+    /// intersection.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            versions: self
+                .versions
+                .iter()
+                .filter(|v| other.versions.contains(v))
+                .copied()
+                .collect(),
+            ciphersuites: self
+                .ciphersuites
+                .iter()
+                .filter(|cs| !is_grease_ciphersuite(**cs) && other.ciphersuites.contains(cs))
+                .copied()
+                .collect(),
+            extensions: self
+                .extensions
+                .iter()
+                .filter(|&&e| !is_grease_value(u16::from(e)) && other.extensions.contains(&e))
+                .copied()
+                .collect(),
+            proposals: self
+                .proposals
+                .iter()
+                .filter(|&&p| !is_grease_value(u16::from(p)) && other.proposals.contains(&p))
+                .copied()
+                .collect(),
+            credentials: self
+                .credentials
+                .iter()
+                .filter(|&&c| !is_grease_value(u16::from(c)) && other.credentials.contains(&c))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Returns the [`Capabilities`] that are contained in all of the given
+    /// [`Capabilities`], or `None` if `capabilities` is empty.
+    ///
+    /// See [`Capabilities::intersection()`] for details.
+    pub fn common<'a>(capabilities: impl IntoIterator<Item = &'a Capabilities>) -> Option<Self> {
+        let mut capabilities = capabilities.into_iter();
+        let first = capabilities.next()?;
+        Some(
+            capabilities.fold(first.intersection(first), |common, capabilities| {
+                common.intersection(capabilities)
+            }),
+        )
+    }
+
+    // ---------------------------------------------------------------------------------------------
+
+    /// Add a randomly chosen GREASE value (see RFC 9420, Section 13.5) to the
+    /// ciphersuites, extensions, proposals and credentials of these
+    /// [`Capabilities`].
+    ///
+    /// Lists that already contain a GREASE value are left untouched, so
+    /// calling this function multiple times has no additional effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CryptoError::InsufficientRandomness`] if the randomness
+    /// source fails.
+    pub fn with_grease(mut self, rand: &impl OpenMlsRand) -> Result<Self, CryptoError> {
+        if !self
+            .ciphersuites
+            .iter()
+            .any(|&cs| is_grease_ciphersuite(cs))
+        {
+            self.ciphersuites
+                .push(VerifiableCiphersuite::new(random_grease_value(rand)?));
+        }
+        if !self
+            .extensions
+            .iter()
+            .any(|&e| is_grease_value(u16::from(e)))
+        {
+            self.extensions
+                .push(ExtensionType::from(random_grease_value(rand)?));
+        }
+        if !self
+            .proposals
+            .iter()
+            .any(|&p| is_grease_value(u16::from(p)))
+        {
+            self.proposals
+                .push(ProposalType::from(random_grease_value(rand)?));
+        }
+        if !self
+            .credentials
+            .iter()
+            .any(|&c| is_grease_value(u16::from(c)))
+        {
+            self.credentials
+                .push(CredentialType::from(random_grease_value(rand)?));
+        }
+        Ok(self)
+    }
+}
+
+/// The GREASE values reserved in all MLS registries that are used in
+/// [`Capabilities`] (RFC 9420, Section 13.5).
+pub(crate) const GREASE_VALUES: [u16; 15] = [
+    0x0A0A, 0x1A1A, 0x2A2A, 0x3A3A, 0x4A4A, 0x5A5A, 0x6A6A, 0x7A7A, 0x8A8A, 0x9A9A, 0xAAAA, 0xBABA,
+    0xCACA, 0xDADA, 0xEAEA,
+];
+
+/// Returns `true` if `value` is one of the reserved GREASE values.
+pub(crate) fn is_grease_value(value: u16) -> bool {
+    GREASE_VALUES.contains(&value)
+}
+
+/// Returns `true` if `ciphersuite` is one of the reserved GREASE values.
+fn is_grease_ciphersuite(ciphersuite: VerifiableCiphersuite) -> bool {
+    GREASE_VALUES
+        .iter()
+        .any(|&v| ciphersuite == VerifiableCiphersuite::new(v))
+}
+
+/// Pick one of the [`GREASE_VALUES`] at random.
+fn random_grease_value(rand: &impl OpenMlsRand) -> Result<u16, CryptoError> {
+    let [index] = rand
+        .random_array::<1>()
+        .map_err(|_| CryptoError::InsufficientRandomness)?;
+    Ok(GREASE_VALUES[index as usize % GREASE_VALUES.len()])
 }
 
 /// A helper for building [`Capabilities`]
@@ -251,6 +468,12 @@ impl CapabilitiesBuilder {
         })
     }
 
+    /// Adds GREASE values to the [`Capabilities`]. See
+    /// [`Capabilities::with_grease`] for details.
+    pub fn with_grease(self, rand: &impl OpenMlsRand) -> Result<Self, CryptoError> {
+        Ok(Self(self.0.with_grease(rand)?))
+    }
+
     /// Builds the [`Capabilities`].
     pub fn build(self) -> Capabilities {
         self.0
@@ -289,6 +512,8 @@ pub(super) fn default_versions() -> Vec<ProtocolVersion> {
     vec![ProtocolVersion::Mls10]
 }
 
+/// The ciphersuites known to OpenMLS. Where a provider is available, the
+/// [`CiphersuiteRegistry`] should be used instead.
 pub(super) fn default_ciphersuites() -> Vec<Ciphersuite> {
     vec![
         Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
@@ -305,10 +530,14 @@ pub(super) fn default_credentials() -> Vec<CredentialType> {
 
 #[cfg(test)]
 mod tests {
-    use openmls_traits::types::{Ciphersuite, VerifiableCiphersuite};
+    use openmls_rust_crypto::OpenMlsRustCrypto;
+    use openmls_traits::{
+        types::{Ciphersuite, VerifiableCiphersuite},
+        OpenMlsProvider,
+    };
     use tls_codec::{Deserialize, Serialize};
 
-    use super::Capabilities;
+    use super::{is_grease_value, Capabilities};
     use crate::{
         credentials::CredentialType, messages::proposals::ProposalType, prelude::ExtensionType,
         versions::ProtocolVersion,
@@ -361,4 +590,105 @@ mod tests {
 
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn grease_values_are_added_once() {
+        let provider = OpenMlsRustCrypto::default();
+        let capabilities = Capabilities::default()
+            .with_grease(provider.rand())
+            .unwrap();
+
+        let grease_ciphersuites = capabilities
+            .ciphersuites()
+            .iter()
+            .filter(|cs| {
+                super::GREASE_VALUES
+                    .iter()
+                    .any(|&v| **cs == VerifiableCiphersuite::new(v))
+            })
+            .count();
+        assert_eq!(grease_ciphersuites, 1);
+        assert_eq!(
+            capabilities
+                .extensions()
+                .iter()
+                .filter(|&&e| is_grease_value(u16::from(e)))
+                .count(),
+            1
+        );
+        assert_eq!(
+            capabilities
+                .proposals()
+                .iter()
+                .filter(|&&p| is_grease_value(u16::from(p)))
+                .count(),
+            1
+        );
+        assert_eq!(
+            capabilities
+                .credentials()
+                .iter()
+                .filter(|&&c| is_grease_value(u16::from(c)))
+                .count(),
+            1
+        );
+
+        // Adding GREASE again doesn't change anything.
+        let greased_twice = capabilities.clone().with_grease(provider.rand()).unwrap();
+        assert_eq!(capabilities, greased_twice);
+
+        // GREASE values survive a serialization round trip.
+        let serialized = capabilities.tls_serialize_detached().unwrap();
+        let deserialized = Capabilities::tls_deserialize_exact(serialized).unwrap();
+        assert_eq!(capabilities, deserialized);
+    }
+
+    #[test]
+    fn intersection() {
+        let provider = OpenMlsRustCrypto::default();
+        let alice = Capabilities::new(
+            None,
+            Some(&[
+                Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
+                Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+            ]),
+            Some(&[ExtensionType::Unknown(0xff00), ExtensionType::LastResort]),
+            Some(&[ProposalType::Custom(0xff00)]),
+            None,
+        )
+        .with_grease(provider.rand())
+        .unwrap();
+        let bob = Capabilities::new(
+            None,
+            Some(&[
+                Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+                Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
+                Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519,
+            ]),
+            Some(&[ExtensionType::LastResort]),
+            None,
+            Some(&[CredentialType::Basic, CredentialType::X509]),
+        );
+        let expected = Capabilities::new(
+            None,
+            Some(&[
+                Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
+                Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+            ]),
+            Some(&[ExtensionType::LastResort]),
+            Some(&[]),
+            None,
+        );
+
+        assert_eq!(alice.intersection(&bob), expected);
+        // GREASE values are dropped even when intersecting with oneself.
+        let common = Capabilities::common([&alice, &alice]).unwrap();
+        assert_eq!(common.ciphersuites().len(), 2);
+        assert_eq!(common.extensions().len(), 2);
+        assert_eq!(common.proposals().len(), 1);
+        assert_eq!(common.credentials(), &[CredentialType::Basic]);
+
+        assert_eq!(Capabilities::common([&alice, &bob]), Some(expected));
+        assert_eq!(Capabilities::common(std::iter::empty()), None);
+    }
 }
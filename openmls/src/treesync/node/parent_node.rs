@@ -3,7 +3,7 @@
 //! [`UpdatePathNode`] instances.
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::types::{Ciphersuite, HpkeCiphertext};
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use thiserror::*;
@@ -67,21 +67,32 @@ impl PlainUpdatePathNode {
         public_keys: &[EncryptionKey],
         group_context: &[u8],
     ) -> Result<UpdatePathNode, LibraryError> {
-        #[cfg(target_arch = "wasm32")]
-        let public_keys = public_keys.iter();
-        #[cfg(not(target_arch = "wasm32"))]
-        let public_keys = public_keys.par_iter();
+        // Large resolutions are split into one batch per thread, such that the
+        // encryptions still run in parallel.
+        #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
+        let batches = std::iter::once(public_keys);
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        let batches = public_keys.par_chunks(
+            public_keys
+                .len()
+                .div_ceil(rayon::current_num_threads())
+                .max(1),
+        );
 
-        public_keys
-            .map(|pk| {
+        let encrypted_path_secrets = batches
+            .map(|batch| {
                 self.path_secret
-                    .encrypt(crypto, ciphersuite, pk, group_context)
-            })
-            .collect::<Result<Vec<HpkeCiphertext>, LibraryError>>()
-            .map(|encrypted_path_secrets| UpdatePathNode {
-                public_key: self.public_key.clone(),
-                encrypted_path_secrets,
+                    .encrypt(crypto, ciphersuite, batch, group_context)
             })
+            .collect::<Result<Vec<Vec<HpkeCiphertext>>, LibraryError>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        Ok(UpdatePathNode {
+            public_key: self.public_key.clone(),
+            encrypted_path_secrets,
+        })
     }
 
     /// Return a reference to the `path_secret` of this node.
@@ -137,9 +148,9 @@ impl ParentNode {
 
         // Iterate over the path secrets and derive a key pair
 
-        #[cfg(not(target_arch = "wasm32"))]
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
         let path_secrets = path_secrets.into_par_iter();
-        #[cfg(target_arch = "wasm32")]
+        #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
         let path_secrets = path_secrets.into_iter();
 
         let (path_with_keypairs, update_path_nodes): PathDerivationResults = path_secrets
@@ -0,0 +1,232 @@
+//! ## Parent Hashes
+//!
+//! This test file generates and reads test vectors for the parent hashes of
+//! the parent nodes in a ratchet tree, such that changes to the tree hashing
+//! can't silently break the parent hash chains.
+//!
+//! The generator grows groups with the test framework and lets members commit
+//! updates and removes, such that the trees contain blanks, unmerged leaves
+//! and parent hash chains that skip blank nodes. The checked-in test vectors
+//! also cover the trees of the tree validation test vectors.
+//!
+//! Parameters:
+//! * Ciphersuite
+//!
+//! Format:
+//! ```text
+//! {
+//!   "cipher_suite": /* uint16 */,
+//!   "tree": /* hex-encoded binary data */,
+//!   "group_id": /* hex-encoded binary data */,
+//!   "parent_hashes": [
+//!     {
+//!       "left": /* hex-encoded binary data */,
+//!       "right": /* hex-encoded binary data */
+//!     },
+//!   ...
+//!   ]
+//! }
+//! ```
+//!
+//! `tree` contains a TLS-serialized ratchet tree, as in the `ratchet_tree`
+//! extension. `parent_hashes[i]` belongs to the parent node with node index
+//! `2 * i + 1` and is `null` if that node is blank. `left` is the parent hash
+//! of the node as carried by a descendant in the resolution of its left child,
+//! i.e. it is computed using the original tree hash of the right child, and
+//! vice versa for `right`. The unmerged leaves of the node are excluded from
+//! the original tree hashes.
+//!
+//! Verification:
+//! * The parent hashes of each parent node in `tree` match `parent_hashes`.
+//! * The parent hashes of `tree` verify as when joining the group.
+
+use ::serde::{Deserialize, Serialize};
+use openmls_traits::{crypto::OpenMlsCrypto, OpenMlsProvider};
+use tls_codec::{Deserialize as _, Serialize as _};
+
+use crate::{
+    binary_tree::{array_representation::TreeNodeIndex, LeafNodeIndex},
+    group::{GroupId, MlsGroupCreateConfig},
+    test_utils::{
+        test_framework::{noop_authentication_service, ActionType, CodecUse, MlsGroupTestSetup},
+        *,
+    },
+    treesync::{LeafNodeParameters, Node, RatchetTree, RatchetTreeIn, TreeSync},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct ParentHashes {
+    #[serde(with = "hex")]
+    left: Vec<u8>,
+    #[serde(with = "hex")]
+    right: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ParentHashesTestVector {
+    cipher_suite: u16,
+    #[serde(with = "hex")]
+    tree: Vec<u8>,
+    #[serde(with = "hex")]
+    group_id: Vec<u8>,
+    parent_hashes: Vec<Option<ParentHashes>>,
+}
+
+/// Compute the parent hashes of all parent nodes in the `ratchet_tree`.
+fn parent_hashes(
+    crypto: &impl OpenMlsCrypto,
+    ciphersuite: Ciphersuite,
+    ratchet_tree: RatchetTree,
+) -> Result<Vec<Option<ParentHashes>>, String> {
+    let nodes = ratchet_tree.0.clone();
+    let treesync = TreeSync::from_ratchet_tree(crypto, ciphersuite, ratchet_tree)
+        .map_err(|e| format!("Error while creating tree sync: {e:?}"))?;
+    let diff = treesync.empty_diff();
+
+    diff.verify_parent_hashes(crypto, ciphersuite)
+        .map_err(|e| format!("Invalid parent hashes: {e:?}"))?;
+
+    nodes
+        .iter()
+        .enumerate()
+        .skip(1)
+        .step_by(2)
+        .map(|(index, node)| {
+            let Some(Node::ParentNode(parent_node)) = node else {
+                return Ok(None);
+            };
+            let TreeNodeIndex::Parent(parent_index) = TreeNodeIndex::test_new(index as u32) else {
+                return Err(format!("Node {index} is not a parent node"));
+            };
+            let (left, right) = diff
+                .compute_parent_hashes(crypto, ciphersuite, parent_index, parent_node)
+                .map_err(|e| format!("Error while computing parent hashes: {e:?}"))?;
+
+            Ok(Some(ParentHashes { left, right }))
+        })
+        .collect()
+}
+
+/// Grow a group of `group_size` members, let some of them commit updates and
+/// then remove the members with the leaf indices `1..=removed`.
+fn generate_test_vector(
+    ciphersuite: Ciphersuite,
+    group_size: usize,
+    removed: u32,
+) -> ParentHashesTestVector {
+    let setup = MlsGroupTestSetup::<OpenMlsRustCrypto>::new(
+        MlsGroupCreateConfig::test_default(ciphersuite),
+        group_size,
+        CodecUse::StructMessages,
+    );
+    let group_id = setup
+        .create_random_group(group_size, ciphersuite, noop_authentication_service)
+        .unwrap();
+    let mut groups = setup.groups.write().unwrap();
+    let group = groups.get_mut(&group_id).unwrap();
+
+    // Committing updates populates the parent nodes on the direct paths of
+    // the committers, while the other parent nodes keep unmerged leaves.
+    for _ in 0..group_size / 2 {
+        let (_, committer_id) = group.random_group_member();
+        setup
+            .self_update(
+                ActionType::Commit,
+                group,
+                &committer_id,
+                LeafNodeParameters::default(),
+                &noop_authentication_service,
+            )
+            .unwrap();
+    }
+
+    if removed > 0 {
+        let (_, remover_id) = group.members().find(|(index, _)| *index == 0).unwrap();
+        let targets = (1..=removed).map(LeafNodeIndex::new).collect::<Vec<_>>();
+        setup
+            .remove_clients(
+                ActionType::Commit,
+                group,
+                &remover_id,
+                &targets,
+                noop_authentication_service,
+            )
+            .unwrap();
+    }
+
+    let provider = OpenMlsRustCrypto::default();
+    let ratchet_tree = group.public_tree.clone();
+    ParentHashesTestVector {
+        cipher_suite: ciphersuite as u16,
+        tree: ratchet_tree.tls_serialize_detached().unwrap(),
+        group_id: group_id.as_slice().to_vec(),
+        parent_hashes: parent_hashes(provider.crypto(), ciphersuite, ratchet_tree).unwrap(),
+    }
+}
+
+#[test]
+fn write_test_vectors() {
+    let mut tests = Vec::new();
+    let provider = OpenMlsRustCrypto::default();
+    for &ciphersuite in provider.crypto().supported_ciphersuites().iter() {
+        for (group_size, removed) in [(2, 0), (5, 0), (8, 0), (8, 2), (11, 3)] {
+            tests.push(generate_test_vector(ciphersuite, group_size, removed));
+        }
+    }
+    write("test_vectors/parent-hashes-new.json", &tests);
+}
+
+fn run_test_vector(
+    test: ParentHashesTestVector,
+    provider: &impl OpenMlsProvider,
+) -> Result<(), String> {
+    let ciphersuite = Ciphersuite::try_from(test.cipher_suite).unwrap();
+    // Skip unsupported ciphersuites.
+    if !provider
+        .crypto()
+        .supported_ciphersuites()
+        .contains(&ciphersuite)
+    {
+        log::debug!("Unsupported ciphersuite {0:?} ...", test.cipher_suite);
+        return Ok(());
+    }
+
+    let group_id = &GroupId::from_slice(test.group_id.as_slice());
+    let ratchet_tree = RatchetTreeIn::tls_deserialize_exact(test.tree)
+        .map_err(|e| format!("Invalid ratchet tree: {e:?}"))?
+        .into_verified(ciphersuite, provider.crypto(), group_id)
+        .map_err(|e| format!("Invalid ratchet tree: {e:?}"))?;
+
+    let parent_hashes = parent_hashes(provider.crypto(), ciphersuite, ratchet_tree)?;
+    if parent_hashes.len() != test.parent_hashes.len() {
+        return Err("Number of parent nodes mismatch".to_string());
+    }
+    for (index, (parent_hashes, expected)) in parent_hashes
+        .iter()
+        .zip(test.parent_hashes.iter())
+        .enumerate()
+    {
+        if parent_hashes != expected {
+            return Err(format!("Parent hash mismatch for node {}", 2 * index + 1));
+        }
+    }
+
+    Ok(())
+}
+
+#[openmls_test::openmls_test]
+fn read_test_vectors_parent_hashes() {
+    let _ = pretty_env_logger::try_init();
+    log::debug!("Reading test vectors ...");
+
+    let tests: Vec<ParentHashesTestVector> =
+        read_json!("../../../../test_vectors/parent-hashes.json");
+
+    for test_vector in tests {
+        match run_test_vector(test_vector, provider) {
+            Ok(_) => {}
+            Err(e) => panic!("Error while checking parent hashes test vector.\n{e:?}"),
+        }
+    }
+    log::trace!("Finished test vector verification");
+}
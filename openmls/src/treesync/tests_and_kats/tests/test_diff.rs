@@ -1,6 +1,7 @@
 use openmls_traits::prelude::*;
 
 use crate::{
+    binary_tree::LeafNodeIndex,
     credentials::test_utils::new_credential,
     key_packages::KeyPackageBundle,
     treesync::{node::Node, RatchetTree, TreeSync},
@@ -48,3 +49,94 @@ fn test_free_leaf_computation() {
 
     assert_eq!(free_leaf_index.u32(), 2u32);
 }
+
+// Verifies that the member index of the tree follows the leaves that are
+// added and blanked when a diff is merged, and that it is rebuilt when the
+// tree is loaded.
+//
+// The key package references are only known for leaves that were added by a
+// merged diff.
+#[openmls_test::openmls_test]
+fn test_member_index() {
+    let (c_0, sk_0) = new_credential(provider, b"leaf0", ciphersuite.signature_algorithm());
+    let kpb_0 = KeyPackageBundle::generate(provider, &sk_0, ciphersuite, c_0);
+    let leaf_0 = kpb_0.key_package().leaf_node().clone();
+
+    let ratchet_tree = RatchetTree::trimmed(vec![Some(Node::LeafNode(leaf_0.clone()))]);
+    let mut tree = TreeSync::from_ratchet_tree(provider.crypto(), ciphersuite, ratchet_tree)
+        .expect("error generating tree");
+    assert_eq!(
+        tree.leaf_index_by_signature_key(leaf_0.signature_key().as_slice()),
+        Some(LeafNodeIndex::new(0))
+    );
+
+    let (c_1, sk_1) = new_credential(provider, b"leaf1", ciphersuite.signature_algorithm());
+    let kpb_1 = KeyPackageBundle::generate(provider, &sk_1, ciphersuite, c_1);
+    let leaf_1 = kpb_1.key_package().leaf_node().clone();
+
+    let kp_ref_1 = kpb_1
+        .key_package()
+        .hash_ref(provider.crypto())
+        .expect("error computing key package ref");
+
+    let mut diff = tree.empty_diff();
+    let added_leaf_index = diff.add_leaf(leaf_1.clone()).expect("error adding leaf");
+    diff.set_key_package_ref(added_leaf_index, kp_ref_1.clone());
+    diff.blank_leaf(LeafNodeIndex::new(0));
+    let staged_diff = diff
+        .into_staged_diff(provider.crypto(), ciphersuite)
+        .expect("error staging diff");
+    tree.merge_diff(staged_diff);
+
+    assert_eq!(
+        tree.leaf_index_by_signature_key(leaf_0.signature_key().as_slice()),
+        None
+    );
+    assert_eq!(
+        tree.leaf_index_by_encryption_key(leaf_0.encryption_key().as_slice()),
+        None
+    );
+    assert_eq!(
+        tree.leaf_index_by_signature_key(leaf_1.signature_key().as_slice()),
+        Some(added_leaf_index)
+    );
+    assert_eq!(
+        tree.leaf_index_by_encryption_key(leaf_1.encryption_key().as_slice()),
+        Some(added_leaf_index)
+    );
+
+    assert_eq!(
+        tree.leaf_index_by_key_package_ref(&kp_ref_1),
+        Some(added_leaf_index)
+    );
+
+    let serialized = serde_json::to_vec(&tree).expect("error serializing tree");
+    let loaded_tree: TreeSync =
+        serde_json::from_slice(&serialized).expect("error deserializing tree");
+    assert_eq!(loaded_tree, tree);
+
+    // Replacing the member in the same leaf replaces its key package ref.
+    let (c_2, sk_2) = new_credential(provider, b"leaf2", ciphersuite.signature_algorithm());
+    let kpb_2 = KeyPackageBundle::generate(provider, &sk_2, ciphersuite, c_2);
+    let kp_ref_2 = kpb_2
+        .key_package()
+        .hash_ref(provider.crypto())
+        .expect("error computing key package ref");
+
+    let mut diff = tree.empty_diff();
+    let leaf_index_2 = diff
+        .add_leaf(kpb_2.key_package().leaf_node().clone())
+        .expect("error adding leaf");
+    diff.set_key_package_ref(leaf_index_2, kp_ref_2.clone());
+    diff.blank_leaf(added_leaf_index);
+    let staged_diff = diff
+        .into_staged_diff(provider.crypto(), ciphersuite)
+        .expect("error staging diff");
+    tree.merge_diff(staged_diff);
+
+    assert_eq!(tree.leaf_index_by_key_package_ref(&kp_ref_1), None);
+    assert_eq!(
+        tree.leaf_index_by_key_package_ref(&kp_ref_2),
+        Some(leaf_index_2)
+    );
+}
@@ -4,13 +4,13 @@
 //!
 //! This module contains structs and functions to encrypt and decrypt path
 //! updates for a [`TreeSyncDiff`] instance.
-use std::collections::HashSet;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use openmls_traits::{
     crypto::OpenMlsCrypto,
     types::{Ciphersuite, HpkeCiphertext},
 };
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSize};
@@ -71,9 +71,9 @@ impl TreeSyncDiff<'_> {
 
         // Encrypt the secrets
 
-        #[cfg(not(target_arch = "wasm32"))]
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
         let resolved_path = path.par_iter().zip(copath_resolutions.par_iter());
-        #[cfg(target_arch = "wasm32")]
+        #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
         let resolved_path = path.iter().zip(copath_resolutions.iter());
 
         resolved_path
@@ -179,52 +179,73 @@ impl TreeSyncDiff<'_> {
         crypto: &impl OpenMlsCrypto,
         encryptor_leaf_index: LeafNodeIndex,
     ) -> Result<Vec<EncryptedGroupSecrets>, LibraryError> {
-        let mut encrypted_group_secrets_vec = vec![];
+        // New members in the same subtree receive the same path secret, so
+        // their group secrets only have to be encoded once. Without a path,
+        // all new members receive the same group secrets.
+        let mut encoded_group_secrets: HashMap<Option<usize>, Vec<u8>> = HashMap::new();
+        let mut recipients = Vec::with_capacity(invited_members.len());
         for (leaf_index, add_proposal) in invited_members {
-            let key_package = add_proposal.key_package;
-
             let direct_path_position = self
                 .subtree_root_position(encryptor_leaf_index, leaf_index)
                 // This can only fail if the nodes are outside the tree or identical
                 .map_err(|_| LibraryError::custom("Unexpected error in subtree_root_position"))?;
-
             // If a plain path was given, there have to be secrets for every new member.
-            let path_secret_option = if let Some(plain_path) = plain_path_option {
-                Some(
-                    plain_path
-                        .get(direct_path_position)
-                        .map(|pupn| pupn.path_secret())
-                        // This only fails if the supplied plain path is invalid
-                        .ok_or_else(|| LibraryError::custom("Invalid plain path"))?,
-                )
-            } else {
-                None
-            };
-
-            // Create the GroupSecrets object for the respective member.
-            let group_secrets_bytes =
-                GroupSecrets::new_encoded(joiner_secret, path_secret_option, presharedkeys)
-                    .map_err(LibraryError::missing_bound_check)?;
-            let ciphertext = hpke::encrypt_with_label(
-                key_package.hpke_init_key().as_slice(),
-                "Welcome",
-                encrypted_group_info,
-                &group_secrets_bytes,
-                key_package.ciphersuite(),
-                crypto,
-            )
-            .map_err(|_| {
-                LibraryError::custom(
-                    "Error while encrypting group secrets. \
-                     This could have really only been a missing bounds check in \
-                     the serialization",
-                )
-            })?;
-            let encrypted_group_secrets =
-                EncryptedGroupSecrets::new(key_package.hash_ref(crypto)?, ciphertext);
-            encrypted_group_secrets_vec.push(encrypted_group_secrets);
+            let direct_path_position = plain_path_option.map(|_| direct_path_position);
+
+            if let Entry::Vacant(entry) = encoded_group_secrets.entry(direct_path_position) {
+                let path_secret_option = match (plain_path_option, direct_path_position) {
+                    (Some(plain_path), Some(direct_path_position)) => Some(
+                        plain_path
+                            .get(direct_path_position)
+                            .map(|pupn| pupn.path_secret())
+                            // This only fails if the supplied plain path is invalid
+                            .ok_or_else(|| LibraryError::custom("Invalid plain path"))?,
+                    ),
+                    _ => None,
+                };
+
+                // Create the GroupSecrets object for the respective member.
+                let group_secrets_bytes =
+                    GroupSecrets::new_encoded(joiner_secret, path_secret_option, presharedkeys)
+                        .map_err(LibraryError::missing_bound_check)?;
+                entry.insert(group_secrets_bytes);
+            }
+
+            recipients.push((add_proposal.key_package, direct_path_position));
         }
-        Ok(encrypted_group_secrets_vec)
+
+        // The HPKE encryption is the expensive part and is done per recipient.
+        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+        let recipients = recipients.par_iter();
+        #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
+        let recipients = recipients.iter();
+
+        recipients
+            .map(|(key_package, direct_path_position)| {
+                let group_secrets_bytes = encoded_group_secrets
+                    .get(direct_path_position)
+                    .ok_or_else(|| LibraryError::custom("Missing encoded group secrets"))?;
+                let ciphertext = hpke::encrypt_with_label(
+                    key_package.hpke_init_key().as_slice(),
+                    "Welcome",
+                    encrypted_group_info,
+                    group_secrets_bytes,
+                    key_package.ciphersuite(),
+                    crypto,
+                )
+                .map_err(|_| {
+                    LibraryError::custom(
+                        "Error while encrypting group secrets. \
+                         This could have really only been a missing bounds check in \
+                         the serialization",
+                    )
+                })?;
+                Ok(EncryptedGroupSecrets::new(
+                    key_package.hash_ref(crypto)?,
+                    ciphertext,
+                ))
+            })
+            .collect()
     }
 }
 
@@ -0,0 +1,180 @@
+//! Group operations with an asynchronous key store.
+#![cfg(feature = "async")]
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use openmls::prelude::{tls_codec::*, *};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test::openmls_test;
+use openmls_traits::key_store_async::OpenMlsKeyStoreAsync;
+
+/// An asynchronous key store that is backed by a map.
+#[derive(Default)]
+struct TestStore {
+    values: RefCell<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl OpenMlsKeyStoreAsync for TestStore {
+    type Error = Infallible;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.values.borrow().get(key).cloned())
+    }
+
+    async fn insert(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.values
+            .borrow_mut()
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<(), Self::Error> {
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+/// Polls the `future` to completion. The futures of the [`TestStore`] are
+/// always ready.
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    // SAFETY: The functions of the vtable don't use the data pointer.
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn credential_with_key(
+    identity: &[u8],
+    ciphersuite: Ciphersuite,
+) -> (CredentialWithKey, SignatureKeyPair) {
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let credential_with_key = CredentialWithKey {
+        credential: BasicCredential::new(identity.to_vec()).into(),
+        signature_key: signer.to_public_vec().into(),
+    };
+    (credential_with_key, signer)
+}
+
+/// Bob keeps his group state in an asynchronous key store. A device without
+/// any local state loads the group from the store and processes messages of
+/// Alice with it, and the changes of the processing are flushed to the store.
+#[openmls_test]
+fn load_and_process_with_async_store() {
+    let alice_provider = &Provider::default();
+    let bob_provider = &Provider::default();
+    let store = TestStore::default();
+
+    let (alice_credential, alice_signer) = credential_with_key(b"Alice", ciphersuite);
+    let (bob_credential, bob_signer) = credential_with_key(b"Bob", ciphersuite);
+    let bob_key_package = KeyPackage::builder()
+        .build(ciphersuite, bob_provider, &bob_signer, bob_credential)
+        .unwrap();
+
+    let mut alice_group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .use_ratchet_tree_extension(true)
+        .build(alice_provider, &alice_signer, alice_credential)
+        .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[bob_key_package.key_package().clone()],
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(alice_provider).unwrap();
+
+    let welcome = MlsMessageIn::tls_deserialize_exact(welcome.tls_serialize_detached().unwrap())
+        .unwrap()
+        .into_welcome()
+        .unwrap();
+    let bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        None,
+    )
+    .unwrap()
+    .into_group(bob_provider)
+    .unwrap();
+    block_on(bob_provider.storage().flush(&store)).unwrap();
+
+    // A device without local state doesn't know the group until it is
+    // fetched from the store.
+    let device_provider = &Provider::default();
+    assert!(
+        MlsGroup::load(device_provider.storage(), bob_group.group_id())
+            .unwrap()
+            .is_none()
+    );
+    let mut device_group = block_on(MlsGroup::load_async(
+        device_provider,
+        &store,
+        bob_group.group_id(),
+    ))
+    .unwrap()
+    .expect("The group is not in the store.");
+    assert_eq!(device_group.epoch(), bob_group.epoch());
+    assert_eq!(
+        device_group.epoch_authenticator(),
+        bob_group.epoch_authenticator()
+    );
+
+    let message = alice_group
+        .create_message(alice_provider, &alice_signer, b"Hello Bob")
+        .unwrap();
+    let message = MlsMessageIn::tls_deserialize_exact(message.tls_serialize_detached().unwrap())
+        .unwrap()
+        .try_into_protocol_message()
+        .unwrap();
+    let processed_message = block_on(device_group.with_async_store(
+        device_provider,
+        &store,
+        |group, provider| group.process_message(provider, message.clone()),
+    ))
+    .unwrap();
+    let ProcessedMessageContent::ApplicationMessage(application_message) =
+        processed_message.into_content()
+    else {
+        panic!("Expected an application message.");
+    };
+    assert_eq!(application_message.into_bytes(), b"Hello Bob");
+
+    // The secrets consumed by the processing were flushed to the store, so
+    // another device that loads the group can't decrypt the message again.
+    let other_device_provider = &Provider::default();
+    let mut other_device_group = block_on(MlsGroup::load_async(
+        other_device_provider,
+        &store,
+        bob_group.group_id(),
+    ))
+    .unwrap()
+    .expect("The group is not in the store.");
+    let result = block_on(other_device_group.with_async_store(
+        other_device_provider,
+        &store,
+        |group, provider| group.process_message(provider, message),
+    ));
+    assert!(matches!(result, Err(AsyncOperationError::Operation(_))));
+}
@@ -0,0 +1,246 @@
+//! Operations that mix ciphersuites fail with the specific error for the
+//! mismatch.
+
+use openmls::{
+    credentials::test_utils::new_credential,
+    prelude::{tls_codec::*, *},
+    test_utils::frankenstein::{FrankenMlsMessage, FrankenMlsMessageBody},
+};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test::openmls_test;
+
+/// Returns another ciphersuite supported by the `provider` that satisfies
+/// `filter`, if there is one.
+fn other_ciphersuite(
+    provider: &impl OpenMlsProvider,
+    ciphersuite: Ciphersuite,
+    filter: impl Fn(Ciphersuite) -> bool,
+) -> Option<Ciphersuite> {
+    provider
+        .crypto()
+        .supported_ciphersuites()
+        .into_iter()
+        .find(|&other| other != ciphersuite && filter(other))
+}
+
+fn create_group(
+    provider: &impl OpenMlsProvider,
+    ciphersuite: Ciphersuite,
+) -> (MlsGroup, SignatureKeyPair) {
+    let (credential_with_key, signer) =
+        new_credential(provider, b"Alice", ciphersuite.signature_algorithm());
+    let group = MlsGroup::builder()
+        .ciphersuite(ciphersuite)
+        .build(provider, &signer, credential_with_key)
+        .expect("Error creating the group.");
+    (group, signer)
+}
+
+fn key_package(provider: &impl OpenMlsProvider, ciphersuite: Ciphersuite) -> KeyPackage {
+    let (credential_with_key, signer) =
+        new_credential(provider, b"Bob", ciphersuite.signature_algorithm());
+    KeyPackage::builder()
+        .build(ciphersuite, provider, &signer, credential_with_key)
+        .expect("Error creating the key package.")
+        .key_package()
+        .clone()
+}
+
+// A key package can't be signed with a signature scheme of another
+// ciphersuite.
+#[openmls_test]
+fn key_package_signer() {
+    let Some(other_ciphersuite) = other_ciphersuite(provider, ciphersuite, |other| {
+        other.signature_algorithm() != ciphersuite.signature_algorithm()
+    }) else {
+        return;
+    };
+
+    let (credential_with_key, signer) =
+        new_credential(provider, b"Bob", other_ciphersuite.signature_algorithm());
+    let err = KeyPackage::builder()
+        .build(ciphersuite, provider, &signer, credential_with_key)
+        .err();
+    assert_eq!(
+        err,
+        Some(KeyPackageNewError::CiphersuiteSignatureSchemeMismatch)
+    );
+}
+
+// A key package of another ciphersuite can't be added to a group.
+#[openmls_test]
+fn add_key_package() {
+    let Some(other_ciphersuite) = other_ciphersuite(provider, ciphersuite, |_| true) else {
+        return;
+    };
+    let (mut group, signer) = create_group(provider, ciphersuite);
+    let key_package = key_package(provider, other_ciphersuite);
+
+    let err = group.add_members(provider, &signer, &[key_package]).err();
+    assert_eq!(
+        err,
+        Some(AddMembersError::CreateCommitError(
+            CreateCommitError::ProposalValidationError(
+                ProposalValidationError::InvalidAddProposalCiphersuiteOrVersion
+            )
+        ))
+    );
+    assert!(group.pending_commit().is_none());
+}
+
+// A proposal to add a key package of another ciphersuite can't be committed,
+// and other members reject it.
+#[openmls_test]
+fn propose_key_package() {
+    let Some(other_ciphersuite) = other_ciphersuite(provider, ciphersuite, |_| true) else {
+        return;
+    };
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+    let (mut alice_group, alice_signer) = create_group(alice_provider, ciphersuite);
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[key_package(bob_provider, ciphersuite)],
+        )
+        .expect("Error adding Bob.");
+    alice_group.merge_pending_commit(alice_provider).unwrap();
+    let welcome = MlsMessageIn::tls_deserialize_exact(welcome.tls_serialize_detached().unwrap())
+        .unwrap()
+        .into_welcome()
+        .unwrap();
+    let mut bob_group = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .and_then(|staged_welcome| staged_welcome.into_group(bob_provider))
+    .expect("Error joining the group.");
+
+    let (proposal, _proposal_ref) = alice_group
+        .propose_add_member(
+            alice_provider,
+            &alice_signer,
+            &key_package(alice_provider, other_ciphersuite),
+        )
+        .expect("Error proposing to add a key package.");
+
+    let err = alice_group
+        .commit_to_pending_proposals(alice_provider, &alice_signer)
+        .err();
+    assert_eq!(
+        err,
+        Some(CommitToPendingProposalsError::CreateCommitError(
+            CreateCommitError::ProposalValidationError(
+                ProposalValidationError::InvalidAddProposalCiphersuiteOrVersion
+            )
+        ))
+    );
+
+    let proposal = MlsMessageIn::tls_deserialize_exact(proposal.tls_serialize_detached().unwrap())
+        .unwrap()
+        .try_into_protocol_message()
+        .unwrap();
+    let err = bob_group.process_message(bob_provider, proposal).err();
+    assert_eq!(
+        err,
+        Some(ProcessMessageError::ValidationError(
+            ValidationError::InvalidAddProposalCiphersuite
+        ))
+    );
+}
+
+// A Welcome of another ciphersuite than the key package can't be joined.
+#[openmls_test]
+fn welcome_ciphersuite() {
+    let Some(other_ciphersuite) = other_ciphersuite(provider, ciphersuite, |_| true) else {
+        return;
+    };
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+    let (mut alice_group, alice_signer) = create_group(alice_provider, ciphersuite);
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[key_package(bob_provider, ciphersuite)],
+        )
+        .expect("Error adding Bob.");
+
+    let mut franken_welcome =
+        FrankenMlsMessage::tls_deserialize_exact(welcome.tls_serialize_detached().unwrap())
+            .unwrap();
+    let FrankenMlsMessageBody::Welcome(welcome) = &mut franken_welcome.body else {
+        panic!("Expected a Welcome.");
+    };
+    welcome.cipher_suite = other_ciphersuite.into();
+    let welcome =
+        MlsMessageIn::tls_deserialize_exact(franken_welcome.tls_serialize_detached().unwrap())
+            .unwrap()
+            .into_welcome()
+            .unwrap();
+
+    let err = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .err();
+    assert_eq!(err, Some(WelcomeError::CiphersuiteMismatch));
+}
+
+// A Welcome with a GroupInfo of another ciphersuite than the Welcome can't be
+// joined.
+#[openmls_test]
+fn welcome_group_info_ciphersuite() {
+    let Some(other_ciphersuite) = other_ciphersuite(provider, ciphersuite, |_| true) else {
+        return;
+    };
+    let alice_provider = provider;
+    let bob_provider = &Provider::default();
+    let (mut alice_group, alice_signer) = create_group(alice_provider, ciphersuite);
+    let (bob_credential_with_key, bob_signer) =
+        new_credential(bob_provider, b"Bob", ciphersuite.signature_algorithm());
+    let bob_key_package_bundle = KeyPackage::builder()
+        .build(
+            ciphersuite,
+            bob_provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("Error creating the key package.");
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            alice_provider,
+            &alice_signer,
+            &[bob_key_package_bundle.key_package().clone()],
+        )
+        .expect("Error adding Bob.");
+
+    let mut franken_welcome =
+        FrankenMlsMessage::tls_deserialize_exact(welcome.tls_serialize_detached().unwrap())
+            .unwrap();
+    let FrankenMlsMessageBody::Welcome(welcome) = &mut franken_welcome.body else {
+        panic!("Expected a Welcome.");
+    };
+    welcome.modify_group_info(bob_provider, &bob_key_package_bundle, |group_info| {
+        group_info.group_context.ciphersuite = other_ciphersuite.into();
+    });
+    let welcome =
+        MlsMessageIn::tls_deserialize_exact(franken_welcome.tls_serialize_detached().unwrap())
+            .unwrap()
+            .into_welcome()
+            .unwrap();
+
+    let err = StagedWelcome::new_from_welcome(
+        bob_provider,
+        &MlsGroupJoinConfig::default(),
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+    )
+    .err();
+    assert_eq!(err, Some(WelcomeError::GroupInfoCiphersuiteMismatch));
+}
@@ -0,0 +1,64 @@
+//! Regression tests for inputs found by the fuzz targets in `fuzz/`.
+//!
+//! Every input in `tests/fuzz_regressions/<target>/` is fed into the same
+//! deserialization as the fuzz target `<target>`. To add a regression test,
+//! copy the crashing input found by `cargo fuzz run <target>` from
+//! `fuzz/artifacts/<target>/` into the directory of the target.
+
+use std::{fs, path::Path};
+
+use openmls::prelude::{tls_codec::*, *};
+use openmls_rust_crypto::OpenMlsRustCrypto;
+
+fn decode(target: &str, data: &[u8]) {
+    match target {
+        "key_package_decode" => {
+            let _ = KeyPackageIn::tls_deserialize(&mut &data[..]);
+        }
+        "mls_message_decode" => {
+            let _ = MlsMessageIn::tls_deserialize_exact(data);
+        }
+        "private_message_decode" => {
+            let _ = PrivateMessageIn::tls_deserialize_exact(data);
+        }
+        "proposal_decode" => {
+            let _ = ProposalIn::tls_deserialize(&mut &data[..]);
+        }
+        "public_message_decode" => {
+            let _ = PublicMessageIn::tls_deserialize_exact(data);
+        }
+        "ratchet_tree_decode" => {
+            if let Ok(ratchet_tree) = RatchetTreeIn::tls_deserialize_exact(data) {
+                let provider = OpenMlsRustCrypto::default();
+                let _ = ratchet_tree.into_verified(
+                    Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+                    provider.crypto(),
+                    &GroupId::from_slice(b"fuzz"),
+                );
+            }
+        }
+        "welcome_decode" => {
+            let _ = Welcome::tls_deserialize(&mut &data[..]);
+        }
+        _ => panic!("Unknown fuzz target {target}"),
+    }
+}
+
+#[test]
+fn fuzz_regressions() {
+    let regressions = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzz_regressions");
+    for target in fs::read_dir(regressions).unwrap() {
+        let target = target.unwrap();
+        let target_name = target.file_name().into_string().unwrap();
+        for input in fs::read_dir(target.path()).unwrap() {
+            let input = input.unwrap().path();
+            let data = fs::read(&input).unwrap();
+            let result = std::panic::catch_unwind(|| decode(&target_name, &data));
+            assert!(
+                result.is_ok(),
+                "The {target_name} regression {} panicked.",
+                input.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use openmls::{
+    prelude::{tls_codec::*, *},
+    test_utils::test_framework::{
+        errors::ReferenceError,
+        reference::ReferenceMls,
+        scenario::{Scenario, ScenarioStep::*},
+    },
+};
+use openmls_basic_credential::SignatureKeyPair;
+use openmls_test::openmls_test;
+
+fn create_config(ciphersuite: Ciphersuite) -> MlsGroupCreateConfig {
+    MlsGroupCreateConfig::builder()
+        .ciphersuite(ciphersuite)
+        .max_past_epochs(2)
+        .build()
+}
+
+// An application message of an epoch is delivered after the commit of the
+// next epoch.
+#[openmls_test]
+fn late_application_message() {
+    let mut scenario = Scenario::<Provider>::new(ciphersuite, create_config(ciphersuite), 3)
+        .expect("Error creating the scenario.");
+
+    scenario
+        .run(&[
+            Add {
+                adder: 0,
+                addees: vec![1, 2],
+            },
+            DeliverAll,
+            Message { sender: 1 },
+            Update { member: 0 },
+            DeliverReordered {
+                recipient: 2,
+                order: vec![1, 0],
+            },
+            DeliverAll,
+            AssertConverged,
+        ])
+        .expect("Error running the scenario.");
+}
+
+// Members that are removed don't receive later messages.
+#[openmls_test]
+fn remove_and_converge() {
+    let mut scenario = Scenario::<Provider>::new(ciphersuite, create_config(ciphersuite), 4)
+        .expect("Error creating the scenario.");
+
+    scenario
+        .run(&[
+            Add {
+                adder: 0,
+                addees: vec![1, 2, 3],
+            },
+            DeliverAll,
+            Remove {
+                remover: 1,
+                targets: vec![2],
+            },
+            Message { sender: 3 },
+            DeliverNext { recipient: 2 },
+            DeliverAll,
+            AssertConverged,
+            Update { member: 3 },
+            DeliverAll,
+            AssertConverged,
+        ])
+        .expect("Error running the scenario.");
+
+    assert_eq!(scenario.members().collect::<Vec<_>>(), vec![0, 1, 3]);
+    assert_eq!(scenario.queued_messages(2), 0);
+}
+
+// Concurrent commits of the same epoch can't both be processed.
+#[openmls_test]
+fn concurrent_commits() {
+    let mut scenario = Scenario::<Provider>::new(ciphersuite, create_config(ciphersuite), 3)
+        .expect("Error creating the scenario.");
+
+    let err = scenario
+        .run(&[
+            Add {
+                adder: 0,
+                addees: vec![1, 2],
+            },
+            DeliverAll,
+            Update { member: 1 },
+            Update { member: 2 },
+            DeliverNext { recipient: 0 },
+            DeliverNext { recipient: 0 },
+        ])
+        .expect_err("Processed two commits of the same epoch.");
+    assert_eq!(err.step, 5);
+}
+
+/// OpenMLS itself as the reference implementation.
+#[derive(Default)]
+struct OpenMlsReference<Provider> {
+    provider: Provider,
+    group: Option<MlsGroup>,
+}
+
+fn reference_error(error: impl std::fmt::Display) -> ReferenceError {
+    ReferenceError(error.to_string())
+}
+
+impl<Provider> OpenMlsReference<Provider> {
+    fn group(&self) -> &MlsGroup {
+        self.group.as_ref().expect("The reference has no group.")
+    }
+}
+
+impl<Provider: OpenMlsProvider> ReferenceMls for OpenMlsReference<Provider> {
+    fn key_package(&mut self, ciphersuite: Ciphersuite) -> Result<Vec<u8>, ReferenceError> {
+        let signer =
+            SignatureKeyPair::new(ciphersuite.signature_algorithm()).map_err(reference_error)?;
+        let credential_with_key = CredentialWithKey {
+            credential: BasicCredential::new(b"Reference".to_vec()).into(),
+            signature_key: signer.to_public_vec().into(),
+        };
+        let key_package_bundle = KeyPackage::builder()
+            .build(ciphersuite, &self.provider, &signer, credential_with_key)
+            .map_err(reference_error)?;
+        key_package_bundle
+            .key_package()
+            .tls_serialize_detached()
+            .map_err(reference_error)
+    }
+
+    fn join(&mut self, welcome: &[u8], ratchet_tree: &[u8]) -> Result<(), ReferenceError> {
+        let MlsMessageBodyIn::Welcome(welcome) = MlsMessageIn::tls_deserialize_exact(welcome)
+            .map_err(reference_error)?
+            .extract()
+        else {
+            return Err(ReferenceError("Not a welcome.".to_string()));
+        };
+        let ratchet_tree =
+            RatchetTreeIn::tls_deserialize_exact(ratchet_tree).map_err(reference_error)?;
+        let join_config = MlsGroupJoinConfig::builder().max_past_epochs(2).build();
+        let group = StagedWelcome::new_from_welcome(
+            &self.provider,
+            &join_config,
+            welcome,
+            Some(ratchet_tree),
+        )
+        .map_err(reference_error)?
+        .into_group(&self.provider)
+        .map_err(reference_error)?;
+        self.group = Some(group);
+        Ok(())
+    }
+
+    fn process_message(&mut self, message: &[u8]) -> Result<Option<Vec<u8>>, ReferenceError> {
+        let message = MlsMessageIn::tls_deserialize_exact(message)
+            .map_err(reference_error)?
+            .try_into_protocol_message()
+            .map_err(reference_error)?;
+        let group = self.group.as_mut().expect("The reference has no group.");
+        let processed_message = group
+            .process_message(&self.provider, message)
+            .map_err(reference_error)?;
+        match processed_message.into_content() {
+            ProcessedMessageContent::ApplicationMessage(message) => Ok(Some(message.into_bytes())),
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                group
+                    .merge_staged_commit(&self.provider, *staged_commit)
+                    .map_err(reference_error)?;
+                Ok(None)
+            }
+            ProcessedMessageContent::ProposalMessage(proposal) => {
+                group
+                    .store_pending_proposal(self.provider.storage(), *proposal)
+                    .map_err(reference_error)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn epoch(&self) -> u64 {
+        self.group().epoch().as_u64()
+    }
+
+    fn epoch_authenticator(&self) -> Vec<u8> {
+        self.group().epoch_authenticator().as_slice().to_vec()
+    }
+
+    fn ratchet_tree(&self) -> Vec<u8> {
+        RatchetTreeIn::from(self.group().export_ratchet_tree())
+            .tls_serialize_detached()
+            .unwrap()
+    }
+
+    fn export_secret(
+        &self,
+        label: &str,
+        context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, ReferenceError> {
+        self.group()
+            .export_secret(&self.provider, label, context, length)
+            .map_err(reference_error)
+    }
+}
+
+// A reference implementation receives all messages after it joined and
+// agrees on the state of the group.
+#[openmls_test]
+fn reference_implementation() {
+    let mut scenario = Scenario::<Provider>::new(ciphersuite, create_config(ciphersuite), 3)
+        .expect("Error creating the scenario.")
+        .with_reference(OpenMlsReference::<Provider>::default());
+
+    scenario
+        .run(&[
+            Add {
+                adder: 0,
+                addees: vec![1],
+            },
+            DeliverAll,
+            AddReference { adder: 1 },
+            DeliverAll,
+            Message { sender: 0 },
+            Update { member: 0 },
+            DeliverAll,
+            Add {
+                adder: 1,
+                addees: vec![2],
+            },
+            Message { sender: 2 },
+            DeliverAll,
+            AssertConverged,
+        ])
+        .expect("Error running the scenario.");
+}
@@ -11,7 +11,7 @@ use hpke::Hpke;
 use hpke_rs_crypto::types as hpke_types;
 use hpke_rs_rust_crypto::HpkeRustCrypto;
 use openmls_traits::{
-    crypto::OpenMlsCrypto,
+    crypto::{HashWriter, OpenMlsCrypto},
     random::OpenMlsRand,
     types::{
         self, AeadType, Ciphersuite, CryptoError, ExporterSecret, HashType, HpkeAeadType,
@@ -26,6 +26,38 @@ use rand::{RngCore, SeedableRng};
 use sha2::{Digest, Sha256, Sha384, Sha512};
 use tls_codec::SecretVLBytes;
 
+/// Incremental SHA-2 hashing for [`OpenMlsCrypto::hash_writer`].
+enum Sha2Writer {
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl std::io::Write for Sha2Writer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Sha2Writer::Sha256(hasher) => hasher.update(buf),
+            Sha2Writer::Sha384(hasher) => hasher.update(buf),
+            Sha2Writer::Sha512(hasher) => hasher.update(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl HashWriter for Sha2Writer {
+    fn finalize(self: Box<Self>) -> Result<Vec<u8>, CryptoError> {
+        Ok(match *self {
+            Sha2Writer::Sha256(hasher) => hasher.finalize().to_vec(),
+            Sha2Writer::Sha384(hasher) => hasher.finalize().to_vec(),
+            Sha2Writer::Sha512(hasher) => hasher.finalize().to_vec(),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct RustCrypto {
     rng: RwLock<rand_chacha::ChaCha20Rng>,
@@ -48,6 +80,20 @@ impl Default for RustCrypto {
     }
 }
 
+#[cfg(feature = "test-utils")]
+impl RustCrypto {
+    /// Create a backend with a random number generator seeded from `seed`,
+    /// such that the random values and signature keys it generates are
+    /// reproducible.
+    ///
+    /// **This must only be used for testing.**
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            rng: RwLock::new(rand_chacha::ChaCha20Rng::from_seed(seed)),
+        }
+    }
+}
+
 #[inline(always)]
 fn kem_mode(kem: HpkeKemType) -> hpke_types::KemAlgorithm {
     match kem {
@@ -159,6 +205,14 @@ impl OpenMlsCrypto for RustCrypto {
         }
     }
 
+    fn hash_writer(&self, hash_type: HashType) -> Result<Box<dyn HashWriter + '_>, CryptoError> {
+        Ok(Box::new(match hash_type {
+            HashType::Sha2_256 => Sha2Writer::Sha256(Sha256::new()),
+            HashType::Sha2_384 => Sha2Writer::Sha384(Sha384::new()),
+            HashType::Sha2_512 => Sha2Writer::Sha512(Sha512::new()),
+        }))
+    }
+
     fn aead_encrypt(
         &self,
         alg: openmls_traits::types::AeadType,
@@ -319,16 +373,24 @@ impl OpenMlsCrypto for RustCrypto {
         aad: &[u8],
         ptxt: &[u8],
     ) -> Result<types::HpkeCiphertext, CryptoError> {
-        let (kem_output, ciphertext) = hpke_from_config(config)
-            .seal(&pk_r.into(), info, aad, ptxt, None, None, None)
-            .map_err(|e| match e {
-                hpke::HpkeError::InvalidInput => CryptoError::InvalidLength,
-                _ => CryptoError::CryptoLibraryError,
-            })?;
-        Ok(HpkeCiphertext {
-            kem_output: kem_output.into(),
-            ciphertext: ciphertext.into(),
-        })
+        hpke_seal(&mut hpke_from_config(config), pk_r, info, aad, ptxt)
+    }
+
+    fn hpke_seal_multi(
+        &self,
+        config: HpkeConfig,
+        pk_rs: &[&[u8]],
+        info: &[u8],
+        aad: &[u8],
+        ptxt: &[u8],
+    ) -> Result<Vec<HpkeCiphertext>, CryptoError> {
+        // Every HPKE instance seeds its own random number generator from the
+        // operating system. One instance is shared by all recipients.
+        let mut hpke = hpke_from_config(config);
+        pk_rs
+            .iter()
+            .map(|pk_r| hpke_seal(&mut hpke, pk_r, info, aad, ptxt))
+            .collect()
     }
 
     fn hpke_open(
@@ -407,6 +469,25 @@ impl OpenMlsCrypto for RustCrypto {
     }
 }
 
+fn hpke_seal(
+    hpke: &mut Hpke<HpkeRustCrypto>,
+    pk_r: &[u8],
+    info: &[u8],
+    aad: &[u8],
+    ptxt: &[u8],
+) -> Result<HpkeCiphertext, CryptoError> {
+    let (kem_output, ciphertext) = hpke
+        .seal(&pk_r.into(), info, aad, ptxt, None, None, None)
+        .map_err(|e| match e {
+            hpke::HpkeError::InvalidInput => CryptoError::InvalidLength,
+            _ => CryptoError::CryptoLibraryError,
+        })?;
+    Ok(HpkeCiphertext {
+        kem_output: kem_output.into(),
+        ciphertext: ciphertext.into(),
+    })
+}
+
 fn hpke_from_config(config: HpkeConfig) -> Hpke<HpkeRustCrypto> {
     Hpke::<HpkeRustCrypto>::new(
         hpke::Mode::Base,
@@ -0,0 +1,439 @@
+//! # OpenMLS Test Crypto Provider
+//!
+//! This is an implementation of the [`OpenMlsProvider`] trait for tests. It
+//! wraps the [`RustCrypto`] backend with
+//! * a seeded random number generator, such that random values and signature
+//!   keys are reproducible,
+//! * call counters for all crypto operations, such that tests can assert that
+//!   a change doesn't increase the number of, e.g., HPKE encryptions,
+//! * an optional [`CertificateValidator`] for the certificate chains of X.509
+//!   credentials, and
+//! * an optional [`TimeProvider`] to test with a fixed time.
+//!
+//! Note that the HPKE encapsulations of the [`RustCrypto`] backend use their
+//! own randomness, such that HPKE ciphertexts are not reproducible.
+//!
+//! ```
+//! use openmls_test_crypto::OpenMlsTestCrypto;
+//! use openmls_traits::{random::OpenMlsRand, OpenMlsProvider};
+//!
+//! let provider = OpenMlsTestCrypto::with_seed([7; 32]);
+//! let other_provider = OpenMlsTestCrypto::with_seed([7; 32]);
+//! assert_eq!(
+//!     provider.rand().random_vec(32).unwrap(),
+//!     other_provider.rand().random_vec(32).unwrap(),
+//! );
+//! ```
+//!
+//! **This provider must only be used for testing.**
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+pub use openmls_memory_storage::{MemoryStorage, MemoryStorageError};
+pub use openmls_rust_crypto::RandError;
+use openmls_rust_crypto::RustCrypto;
+use openmls_traits::{
+    crypto::{CertificateValidator, OpenMlsCrypto, TimeProvider},
+    random::OpenMlsRand,
+    types::{
+        AeadType, Ciphersuite, CryptoError, ExporterSecret, HashType, HpkeCiphertext, HpkeConfig,
+        HpkeKeyPair, KemOutput, SignatureScheme,
+    },
+    OpenMlsProvider,
+};
+use tls_codec::SecretVLBytes;
+
+/// The number of calls of each of the crypto operations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallCounts {
+    pub hkdf_extract: usize,
+    pub hkdf_expand: usize,
+    pub hash: usize,
+    pub aead_encrypt: usize,
+    pub aead_decrypt: usize,
+    pub signature_key_gen: usize,
+    pub sign: usize,
+    pub verify_signature: usize,
+    /// The number of HPKE encryptions, i.e. one per recipient.
+    pub hpke_seal: usize,
+    pub hpke_open: usize,
+    pub hpke_setup_sender_and_export: usize,
+    pub hpke_setup_receiver_and_export: usize,
+    pub derive_hpke_keypair: usize,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hkdf_extract: AtomicUsize,
+    hkdf_expand: AtomicUsize,
+    hash: AtomicUsize,
+    aead_encrypt: AtomicUsize,
+    aead_decrypt: AtomicUsize,
+    signature_key_gen: AtomicUsize,
+    sign: AtomicUsize,
+    verify_signature: AtomicUsize,
+    hpke_seal: AtomicUsize,
+    hpke_open: AtomicUsize,
+    hpke_setup_sender_and_export: AtomicUsize,
+    hpke_setup_receiver_and_export: AtomicUsize,
+    derive_hpke_keypair: AtomicUsize,
+}
+
+fn count(counter: &AtomicUsize) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+fn load(counter: &AtomicUsize) -> usize {
+    counter.load(Ordering::Relaxed)
+}
+
+/// The [`RustCrypto`] backend with a seeded random number generator and call
+/// counters.
+pub struct TestCrypto {
+    crypto: RustCrypto,
+    counters: Counters,
+    certificate_validator: Option<Box<dyn CertificateValidator>>,
+    time_provider: Option<Box<dyn TimeProvider + Send + Sync>>,
+}
+
+impl fmt::Debug for TestCrypto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestCrypto")
+            .field("crypto", &self.crypto)
+            .field("counters", &self.counters)
+            .field(
+                "certificate_validator",
+                &self.certificate_validator.is_some(),
+            )
+            .field("time_provider", &self.time_provider.is_some())
+            .finish()
+    }
+}
+
+impl TestCrypto {
+    /// Create a backend with a random number generator seeded from `seed`.
+    pub fn with_seed(seed: [u8; 32]) -> Self {
+        Self {
+            crypto: RustCrypto::from_seed(seed),
+            counters: Counters::default(),
+            certificate_validator: None,
+            time_provider: None,
+        }
+    }
+
+    /// Set the [`CertificateValidator`] that validates the certificate chains
+    /// of X.509 credentials.
+    pub fn with_certificate_validator(
+        mut self,
+        certificate_validator: impl CertificateValidator + 'static,
+    ) -> Self {
+        self.certificate_validator = Some(Box::new(certificate_validator));
+        self
+    }
+
+    /// Set the [`TimeProvider`] that is used instead of the system clock,
+    /// e.g. to test with a fixed time.
+    pub fn with_time_provider(
+        mut self,
+        time_provider: impl TimeProvider + Send + Sync + 'static,
+    ) -> Self {
+        self.time_provider = Some(Box::new(time_provider));
+        self
+    }
+
+    /// Returns the number of calls of each crypto operation since the
+    /// creation of the backend or the last [`reset_counts`](Self::reset_counts).
+    pub fn call_counts(&self) -> CallCounts {
+        let counters = &self.counters;
+        CallCounts {
+            hkdf_extract: load(&counters.hkdf_extract),
+            hkdf_expand: load(&counters.hkdf_expand),
+            hash: load(&counters.hash),
+            aead_encrypt: load(&counters.aead_encrypt),
+            aead_decrypt: load(&counters.aead_decrypt),
+            signature_key_gen: load(&counters.signature_key_gen),
+            sign: load(&counters.sign),
+            verify_signature: load(&counters.verify_signature),
+            hpke_seal: load(&counters.hpke_seal),
+            hpke_open: load(&counters.hpke_open),
+            hpke_setup_sender_and_export: load(&counters.hpke_setup_sender_and_export),
+            hpke_setup_receiver_and_export: load(&counters.hpke_setup_receiver_and_export),
+            derive_hpke_keypair: load(&counters.derive_hpke_keypair),
+        }
+    }
+
+    /// Reset all call counters to zero.
+    pub fn reset_counts(&self) {
+        let counters = &self.counters;
+        for counter in [
+            &counters.hkdf_extract,
+            &counters.hkdf_expand,
+            &counters.hash,
+            &counters.aead_encrypt,
+            &counters.aead_decrypt,
+            &counters.signature_key_gen,
+            &counters.sign,
+            &counters.verify_signature,
+            &counters.hpke_seal,
+            &counters.hpke_open,
+            &counters.hpke_setup_sender_and_export,
+            &counters.hpke_setup_receiver_and_export,
+            &counters.derive_hpke_keypair,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl OpenMlsCrypto for TestCrypto {
+    fn supports(&self, ciphersuite: Ciphersuite) -> Result<(), CryptoError> {
+        self.crypto.supports(ciphersuite)
+    }
+
+    fn supported_ciphersuites(&self) -> Vec<Ciphersuite> {
+        self.crypto.supported_ciphersuites()
+    }
+
+    fn hkdf_extract(
+        &self,
+        hash_type: HashType,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<SecretVLBytes, CryptoError> {
+        count(&self.counters.hkdf_extract);
+        self.crypto.hkdf_extract(hash_type, salt, ikm)
+    }
+
+    fn hkdf_expand(
+        &self,
+        hash_type: HashType,
+        prk: &[u8],
+        info: &[u8],
+        okm_len: usize,
+    ) -> Result<SecretVLBytes, CryptoError> {
+        count(&self.counters.hkdf_expand);
+        self.crypto.hkdf_expand(hash_type, prk, info, okm_len)
+    }
+
+    fn hash(&self, hash_type: HashType, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        count(&self.counters.hash);
+        self.crypto.hash(hash_type, data)
+    }
+
+    fn aead_encrypt(
+        &self,
+        alg: AeadType,
+        key: &[u8],
+        data: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        count(&self.counters.aead_encrypt);
+        self.crypto.aead_encrypt(alg, key, data, nonce, aad)
+    }
+
+    fn aead_decrypt(
+        &self,
+        alg: AeadType,
+        key: &[u8],
+        ct_tag: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        count(&self.counters.aead_decrypt);
+        self.crypto.aead_decrypt(alg, key, ct_tag, nonce, aad)
+    }
+
+    fn signature_key_gen(&self, alg: SignatureScheme) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        count(&self.counters.signature_key_gen);
+        self.crypto.signature_key_gen(alg)
+    }
+
+    fn verify_signature(
+        &self,
+        alg: SignatureScheme,
+        data: &[u8],
+        pk: &[u8],
+        signature: &[u8],
+    ) -> Result<(), CryptoError> {
+        count(&self.counters.verify_signature);
+        self.crypto.verify_signature(alg, data, pk, signature)
+    }
+
+    fn sign(&self, alg: SignatureScheme, data: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        count(&self.counters.sign);
+        self.crypto.sign(alg, data, key)
+    }
+
+    // The default `hpke_seal_multi` calls `hpke_seal` for each recipient, such
+    // that every encryption is counted.
+    fn hpke_seal(
+        &self,
+        config: HpkeConfig,
+        pk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        ptxt: &[u8],
+    ) -> Result<HpkeCiphertext, CryptoError> {
+        count(&self.counters.hpke_seal);
+        self.crypto.hpke_seal(config, pk_r, info, aad, ptxt)
+    }
+
+    fn hpke_open(
+        &self,
+        config: HpkeConfig,
+        input: &HpkeCiphertext,
+        sk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        count(&self.counters.hpke_open);
+        self.crypto.hpke_open(config, input, sk_r, info, aad)
+    }
+
+    fn hpke_setup_sender_and_export(
+        &self,
+        config: HpkeConfig,
+        pk_r: &[u8],
+        info: &[u8],
+        exporter_context: &[u8],
+        exporter_length: usize,
+    ) -> Result<(KemOutput, ExporterSecret), CryptoError> {
+        count(&self.counters.hpke_setup_sender_and_export);
+        self.crypto.hpke_setup_sender_and_export(
+            config,
+            pk_r,
+            info,
+            exporter_context,
+            exporter_length,
+        )
+    }
+
+    fn hpke_setup_receiver_and_export(
+        &self,
+        config: HpkeConfig,
+        enc: &[u8],
+        sk_r: &[u8],
+        info: &[u8],
+        exporter_context: &[u8],
+        exporter_length: usize,
+    ) -> Result<ExporterSecret, CryptoError> {
+        count(&self.counters.hpke_setup_receiver_and_export);
+        self.crypto.hpke_setup_receiver_and_export(
+            config,
+            enc,
+            sk_r,
+            info,
+            exporter_context,
+            exporter_length,
+        )
+    }
+
+    fn derive_hpke_keypair(
+        &self,
+        config: HpkeConfig,
+        ikm: &[u8],
+    ) -> Result<HpkeKeyPair, CryptoError> {
+        count(&self.counters.derive_hpke_keypair);
+        self.crypto.derive_hpke_keypair(config, ikm)
+    }
+
+    fn certificate_validator(&self) -> Option<&dyn CertificateValidator> {
+        self.certificate_validator.as_deref()
+    }
+
+    fn time_provider(&self) -> Option<&dyn TimeProvider> {
+        self.time_provider
+            .as_deref()
+            .map(|time_provider| time_provider as &dyn TimeProvider)
+    }
+}
+
+impl OpenMlsRand for TestCrypto {
+    type Error = RandError;
+
+    fn random_array<const N: usize>(&self) -> Result<[u8; N], Self::Error> {
+        self.crypto.random_array()
+    }
+
+    fn random_vec(&self, len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.crypto.random_vec(len)
+    }
+}
+
+/// An [`OpenMlsProvider`] with the [`TestCrypto`] backend and an in-memory
+/// storage.
+#[derive(Debug)]
+pub struct OpenMlsTestCrypto {
+    crypto: TestCrypto,
+    key_store: MemoryStorage,
+}
+
+impl OpenMlsTestCrypto {
+    /// Create a provider with a random number generator seeded from `seed`.
+    pub fn with_seed(seed: [u8; 32]) -> Self {
+        Self {
+            crypto: TestCrypto::with_seed(seed),
+            key_store: MemoryStorage::default(),
+        }
+    }
+
+    /// Set the [`CertificateValidator`] of the crypto backend. See
+    /// [`TestCrypto::with_certificate_validator`].
+    pub fn with_certificate_validator(
+        mut self,
+        certificate_validator: impl CertificateValidator + 'static,
+    ) -> Self {
+        self.crypto = self.crypto.with_certificate_validator(certificate_validator);
+        self
+    }
+
+    /// Set the [`TimeProvider`] of the crypto backend. See
+    /// [`TestCrypto::with_time_provider`].
+    pub fn with_time_provider(
+        mut self,
+        time_provider: impl TimeProvider + Send + Sync + 'static,
+    ) -> Self {
+        self.crypto = self.crypto.with_time_provider(time_provider);
+        self
+    }
+
+    /// Returns the number of calls of each crypto operation. See
+    /// [`TestCrypto::call_counts`].
+    pub fn call_counts(&self) -> CallCounts {
+        self.crypto.call_counts()
+    }
+
+    /// Reset all call counters to zero.
+    pub fn reset_counts(&self) {
+        self.crypto.reset_counts()
+    }
+}
+
+impl Default for OpenMlsTestCrypto {
+    /// A provider with an all-zero seed.
+    fn default() -> Self {
+        Self::with_seed([0; 32])
+    }
+}
+
+impl OpenMlsProvider for OpenMlsTestCrypto {
+    type CryptoProvider = TestCrypto;
+    type RandProvider = TestCrypto;
+    type StorageProvider = MemoryStorage;
+
+    fn storage(&self) -> &Self::StorageProvider {
+        &self.key_store
+    }
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.crypto
+    }
+}
@@ -0,0 +1,80 @@
+use openmls_test_crypto::{CallCounts, OpenMlsTestCrypto};
+use openmls_traits::{
+    crypto::OpenMlsCrypto, random::OpenMlsRand, types::Ciphersuite, OpenMlsProvider,
+};
+
+const CIPHERSUITE: Ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+
+/// Providers with the same seed generate the same random values and keys.
+#[test]
+fn deterministic() {
+    let provider = OpenMlsTestCrypto::with_seed([1; 32]);
+    let same_seed = OpenMlsTestCrypto::with_seed([1; 32]);
+    let other_seed = OpenMlsTestCrypto::with_seed([2; 32]);
+
+    let random = provider.rand().random_vec(32).unwrap();
+    assert_eq!(random, same_seed.rand().random_vec(32).unwrap());
+    assert_ne!(random, other_seed.rand().random_vec(32).unwrap());
+
+    let signature_scheme = CIPHERSUITE.signature_algorithm();
+    let key_pair = provider
+        .crypto()
+        .signature_key_gen(signature_scheme)
+        .unwrap();
+    assert_eq!(
+        key_pair,
+        same_seed
+            .crypto()
+            .signature_key_gen(signature_scheme)
+            .unwrap()
+    );
+}
+
+/// The crypto operations are counted.
+#[test]
+fn call_counts() {
+    let provider = OpenMlsTestCrypto::default();
+    let crypto = provider.crypto();
+    assert_eq!(provider.call_counts(), CallCounts::default());
+
+    let key_pairs = [[1u8; 32], [2u8; 32]].map(|ikm| {
+        crypto
+            .derive_hpke_keypair(CIPHERSUITE.hpke_config(), &ikm)
+            .unwrap()
+    });
+    let public_keys = key_pairs
+        .iter()
+        .map(|key_pair| key_pair.public.as_slice())
+        .collect::<Vec<_>>();
+    let ciphertexts = crypto
+        .hpke_seal_multi(
+            CIPHERSUITE.hpke_config(),
+            &public_keys,
+            b"info",
+            b"",
+            b"ptxt",
+        )
+        .unwrap();
+    crypto
+        .hpke_open(
+            CIPHERSUITE.hpke_config(),
+            &ciphertexts[0],
+            &key_pairs[0].private,
+            b"info",
+            b"",
+        )
+        .unwrap();
+
+    assert_eq!(
+        provider.call_counts(),
+        CallCounts {
+            derive_hpke_keypair: 2,
+            hpke_seal: 2,
+            hpke_open: 1,
+            ..Default::default()
+        }
+    );
+
+    provider.reset_counts();
+    assert_eq!(provider.call_counts(), CallCounts::default());
+}
@@ -0,0 +1,997 @@
+//! # OpenMLS SQLite Storage
+//!
+//! An implementation of the [`StorageProvider`] trait on SQLite.
+//!
+//! ## Schema
+//!
+//! All values are stored in a single table. The `PRAGMA user_version` of the
+//! database holds the [`SCHEMA_VERSION`].
+//!
+//! ```sql
+//! CREATE TABLE openmls_storage (
+//!     label   TEXT    NOT NULL,
+//!     key     BLOB    NOT NULL,
+//!     version INTEGER NOT NULL,
+//!     value   BLOB    NOT NULL,
+//!     PRIMARY KEY (label, key, version)
+//! ) WITHOUT ROWID;
+//! ```
+//!
+//! * `label` is the kind of the value, e.g. `Tree` or `EncryptionKeyPair`.
+//! * `key` is the JSON serialization of the key, e.g. the group ID or a public
+//!   key. Compound keys, e.g. of queued proposals, are serialized as JSON
+//!   arrays.
+//! * `version` is the storage version of OpenMLS, i.e. [`CURRENT_VERSION`].
+//! * `value` is the JSON serialization of the value. Lists, e.g. the own leaf
+//!   nodes of a group, are stored as one JSON array.
+//!
+//! ## Transactions
+//!
+//! OpenMLS writes the state of a group with several calls to the storage, e.g.
+//! when merging a commit. The storage implements the transactions of the
+//! [`StorageProvider`] with SQLite savepoints, such that either the complete
+//! new epoch or the old one is persisted, even if the application crashes in
+//! between. Other operations that belong together can be run in
+//! [`SqliteStorage::transaction()`].
+//!
+//! A transaction belongs to the thread that began it. Other threads that use
+//! the storage meanwhile wait until the transaction is committed or rolled
+//! back, such that their writes don't become part of it.
+//!
+//! ```
+//! use openmls_sqlite_storage::{SqliteStorage, SqliteStorageError};
+//!
+//! let storage = SqliteStorage::open_in_memory().unwrap();
+//! storage
+//!     .transaction(|storage| {
+//!         // e.g. group.merge_staged_commit(&provider, staged_commit)
+//!         Ok::<_, SqliteStorageError>(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use std::{
+    path::Path,
+    sync::{Condvar, Mutex, MutexGuard},
+    thread::{self, ThreadId},
+};
+
+use openmls_traits::storage::{
+    migration::{MigrationError, StorageEntry, StorageMigrations},
+    *,
+};
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The version of the database schema created by this crate.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS openmls_storage (
+    label   TEXT    NOT NULL,
+    key     BLOB    NOT NULL,
+    version INTEGER NOT NULL,
+    value   BLOB    NOT NULL,
+    PRIMARY KEY (label, key, version)
+) WITHOUT ROWID;";
+
+/// A [`StorageProvider`] storing all values in a SQLite database.
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+    /// The open transaction. The transaction mutex is always locked before
+    /// the connection mutex.
+    transaction: Mutex<Transaction>,
+    /// Notified when the open transaction ends.
+    transaction_ended: Condvar,
+}
+
+/// The thread that owns the open transaction, and the number of nested
+/// savepoints of the transaction.
+#[derive(Debug, Default)]
+struct Transaction {
+    owner: Option<ThreadId>,
+    depth: usize,
+}
+
+impl SqliteStorage {
+    /// Open or create the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteStorageError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Open a new in-memory database.
+    pub fn open_in_memory() -> Result<Self, SqliteStorageError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Use an open `connection`, e.g. to a database that also holds the
+    /// application's data. The table of this storage is created if it doesn't
+    /// exist.
+    ///
+    /// Returns [`SqliteStorageError::UnsupportedSchemaVersion`] if the
+    /// database was created by a newer version of this crate.
+    pub fn from_connection(connection: Connection) -> Result<Self, SqliteStorageError> {
+        let schema_version: u32 =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        match schema_version {
+            0 => {
+                connection.execute_batch(SCHEMA)?;
+                connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            }
+            SCHEMA_VERSION => {}
+            _ => return Err(SqliteStorageError::UnsupportedSchemaVersion(schema_version)),
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            transaction: Mutex::default(),
+            transaction_ended: Condvar::new(),
+        })
+    }
+
+    /// Run `f` in a transaction. All writes to the storage in `f` are rolled
+    /// back if `f` returns an error or panics.
+    ///
+    /// Transactions can be nested. Other threads that use the storage while `f`
+    /// runs wait until the transaction has ended.
+    pub fn transaction<T, E: From<SqliteStorageError>>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let savepoint = Savepoint::new(self)?;
+        let value = f(self)?;
+        savepoint.release()?;
+
+        Ok(value)
+    }
+
+    /// Migrates all values of older storage versions to the
+    /// [`CURRENT_VERSION`] with the `migrations`, and returns the number of
+    /// migrated values.
+    ///
+    /// The values are migrated in one transaction, such that either all or
+    /// none of them are migrated.
+    pub fn migrate(&self, migrations: &StorageMigrations) -> Result<usize, SqliteStorageError> {
+        self.transaction(|storage| {
+            let rows = {
+                let connection = storage.connection();
+                let mut statement = connection.prepare(
+                    "SELECT label, key, version, value FROM openmls_storage
+                    WHERE version != ?1",
+                )?;
+                let rows = statement
+                    .query_map(params![CURRENT_VERSION], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, Vec<u8>>(1)?,
+                            row.get::<_, u16>(2)?,
+                            row.get::<_, Vec<u8>>(3)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                rows
+            };
+
+            let count = rows.len();
+            for (label, key, version, value) in rows {
+                let mut entry = StorageEntry {
+                    label: label.clone().into_bytes(),
+                    key: key.clone(),
+                    value,
+                };
+                migrations.migrate(version.into(), &mut entry)?;
+                let new_label =
+                    String::from_utf8(entry.label).map_err(|_| MigrationError::Failed {
+                        version: version.into(),
+                        reason: "The label is not valid UTF-8".to_string(),
+                    })?;
+
+                let connection = storage.connection();
+                connection.execute(
+                    "DELETE FROM openmls_storage WHERE label = ?1 AND key = ?2 AND version = ?3",
+                    params![label, key, version],
+                )?;
+                // A value that already exists in the current version, e.g.
+                // because it was written after a partial earlier migration,
+                // must not be overwritten with an older one.
+                match connection.execute(
+                    "INSERT INTO openmls_storage (label, key, version, value)
+                    VALUES (?1, ?2, ?3, ?4)",
+                    params![new_label, entry.key, CURRENT_VERSION, entry.value],
+                ) {
+                    Err(rusqlite::Error::SqliteFailure(e, _))
+                        if e.code == ErrorCode::ConstraintViolation =>
+                    {
+                        return Err(SqliteStorageError::MigrationConflict);
+                    }
+                    result => result?,
+                };
+            }
+
+            Ok(count)
+        })
+    }
+
+    /// Waits until no other thread has an open transaction and returns the
+    /// connection.
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        let transaction = self.wait_for_transaction();
+        let connection = self.lock_connection();
+        drop(transaction);
+        connection
+    }
+
+    fn lock_connection(&self) -> MutexGuard<'_, Connection> {
+        // The connection stays usable if a thread panicked while holding it.
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Waits until the open transaction, if any, belongs to the current
+    /// thread.
+    fn wait_for_transaction(&self) -> MutexGuard<'_, Transaction> {
+        let current = thread::current().id();
+        let transaction = self
+            .transaction
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.transaction_ended
+            .wait_while(transaction, |transaction| {
+                transaction.owner.is_some_and(|owner| owner != current)
+            })
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Ends the innermost savepoint of the current thread's transaction with
+    /// the `statement`.
+    fn end_transaction(&self, statement: &str) -> Result<(), SqliteStorageError> {
+        let mut transaction = self.wait_for_transaction();
+        if transaction.owner.is_none() {
+            return Err(SqliteStorageError::NoTransaction);
+        }
+        self.lock_connection().execute_batch(statement)?;
+        transaction.depth -= 1;
+        if transaction.depth == 0 {
+            transaction.owner = None;
+            self.transaction_ended.notify_all();
+        }
+        Ok(())
+    }
+
+    /// Internal helper to abstract write operations.
+    fn write(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+        value: &(impl Serialize + ?Sized),
+    ) -> Result<(), SqliteStorageError> {
+        let key = serde_json::to_vec(key)?;
+        let value = serde_json::to_vec(value)?;
+        self.connection().execute(
+            "INSERT OR REPLACE INTO openmls_storage (label, key, version, value)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![label, key, CURRENT_VERSION, value],
+        )?;
+
+        Ok(())
+    }
+
+    /// Internal helper to abstract read operations.
+    fn read<V: DeserializeOwned>(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+    ) -> Result<Option<V>, SqliteStorageError> {
+        let key = serde_json::to_vec(key)?;
+        let value: Option<Vec<u8>> = self
+            .connection()
+            .query_row(
+                "SELECT value FROM openmls_storage
+                WHERE label = ?1 AND key = ?2 AND version = ?3",
+                params![label, key, CURRENT_VERSION],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(value
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()?)
+    }
+
+    /// Internal helper to read lists. Returns an empty list if it doesn't
+    /// exist.
+    fn read_list<V: DeserializeOwned>(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+    ) -> Result<Vec<V>, SqliteStorageError> {
+        Ok(self.read(label, key)?.unwrap_or_default())
+    }
+
+    /// Internal helper to append an item to a list.
+    fn append(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+        item: &impl Serialize,
+    ) -> Result<(), SqliteStorageError> {
+        self.transaction(|storage| {
+            let mut list: Vec<serde_json::Value> = storage.read_list(label, key)?;
+            list.push(serde_json::to_value(item)?);
+            storage.write(label, key, &list)
+        })
+    }
+
+    /// Internal helper to remove the first occurrence of an item from a list.
+    fn remove_item(
+        &self,
+        label: &str,
+        key: &impl Serialize,
+        item: &impl Serialize,
+    ) -> Result<(), SqliteStorageError> {
+        self.transaction(|storage| {
+            let mut list: Vec<serde_json::Value> = storage.read_list(label, key)?;
+            let item = serde_json::to_value(item)?;
+            if let Some(position) = list.iter().position(|stored_item| stored_item == &item) {
+                list.remove(position);
+            }
+            storage.write(label, key, &list)
+        })
+    }
+
+    /// Internal helper to abstract delete operations.
+    fn delete(&self, label: &str, key: &impl Serialize) -> Result<(), SqliteStorageError> {
+        let key = serde_json::to_vec(key)?;
+        self.connection().execute(
+            "DELETE FROM openmls_storage WHERE label = ?1 AND key = ?2 AND version = ?3",
+            params![label, key, CURRENT_VERSION],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// An open savepoint, which is rolled back when dropped without being
+/// released.
+struct Savepoint<'a> {
+    storage: &'a SqliteStorage,
+    released: bool,
+}
+
+impl<'a> Savepoint<'a> {
+    fn new(storage: &'a SqliteStorage) -> Result<Self, SqliteStorageError> {
+        storage.begin_transaction()?;
+
+        Ok(Self {
+            storage,
+            released: false,
+        })
+    }
+
+    fn release(mut self) -> Result<(), SqliteStorageError> {
+        self.storage.commit_transaction()?;
+        self.released = true;
+
+        Ok(())
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.storage.rollback_transaction();
+        }
+    }
+}
+
+/// Errors thrown by the SQLite storage.
+#[derive(thiserror::Error, Debug)]
+pub enum SqliteStorageError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Error serializing value: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("The database schema version {0} is not supported.")]
+    UnsupportedSchemaVersion(u32),
+    #[error("A value referenced by another value does not exist.")]
+    MissingValue,
+    #[error("Error migrating a value: {0}")]
+    Migration(#[from] MigrationError),
+    #[error("A migrated value already exists in the current storage version.")]
+    MigrationConflict,
+    #[error("There is no open transaction.")]
+    NoTransaction,
+}
+
+const KEY_PACKAGE_LABEL: &str = "KeyPackage";
+const PSK_LABEL: &str = "Psk";
+const ENCRYPTION_KEY_PAIR_LABEL: &str = "EncryptionKeyPair";
+const SIGNATURE_KEY_PAIR_LABEL: &str = "SignatureKeyPair";
+const EPOCH_KEY_PAIRS_LABEL: &str = "EpochKeyPairs";
+
+// related to PublicGroup
+const TREE_LABEL: &str = "Tree";
+const GROUP_CONTEXT_LABEL: &str = "GroupContext";
+const INTERIM_TRANSCRIPT_HASH_LABEL: &str = "InterimTranscriptHash";
+const CONFIRMATION_TAG_LABEL: &str = "ConfirmationTag";
+
+// related to MlsGroup
+const JOIN_CONFIG_LABEL: &str = "MlsGroupJoinConfig";
+const OWN_LEAF_NODES_LABEL: &str = "OwnLeafNodes";
+const GROUP_STATE_LABEL: &str = "GroupState";
+const QUEUED_PROPOSAL_LABEL: &str = "QueuedProposal";
+const PROPOSAL_QUEUE_REFS_LABEL: &str = "ProposalQueueRefs";
+const OWN_LEAF_NODE_INDEX_LABEL: &str = "OwnLeafNodeIndex";
+const EPOCH_SECRETS_LABEL: &str = "EpochSecrets";
+const RESUMPTION_PSK_STORE_LABEL: &str = "ResumptionPsk";
+const MESSAGE_SECRETS_LABEL: &str = "MessageSecrets";
+
+impl StorageProvider<CURRENT_VERSION> for SqliteStorage {
+    type Error = SqliteStorageError;
+
+    fn queue_proposal<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+        QueuedProposal: traits::QueuedProposal<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        proposal_ref: &ProposalRef,
+        proposal: &QueuedProposal,
+    ) -> Result<(), Self::Error> {
+        self.transaction(|storage| {
+            storage.write(QUEUED_PROPOSAL_LABEL, &(group_id, proposal_ref), proposal)?;
+            storage.append(PROPOSAL_QUEUE_REFS_LABEL, group_id, proposal_ref)
+        })
+    }
+
+    fn write_tree<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        TreeSync: traits::TreeSync<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        tree: &TreeSync,
+    ) -> Result<(), Self::Error> {
+        self.write(TREE_LABEL, group_id, tree)
+    }
+
+    fn write_interim_transcript_hash<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        InterimTranscriptHash: traits::InterimTranscriptHash<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        interim_transcript_hash: &InterimTranscriptHash,
+    ) -> Result<(), Self::Error> {
+        self.write(
+            INTERIM_TRANSCRIPT_HASH_LABEL,
+            group_id,
+            interim_transcript_hash,
+        )
+    }
+
+    fn write_context<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupContext: traits::GroupContext<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_context: &GroupContext,
+    ) -> Result<(), Self::Error> {
+        self.write(GROUP_CONTEXT_LABEL, group_id, group_context)
+    }
+
+    fn write_confirmation_tag<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ConfirmationTag: traits::ConfirmationTag<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        confirmation_tag: &ConfirmationTag,
+    ) -> Result<(), Self::Error> {
+        self.write(CONFIRMATION_TAG_LABEL, group_id, confirmation_tag)
+    }
+
+    fn write_signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+        SignatureKeyPair: traits::SignatureKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+        signature_key_pair: &SignatureKeyPair,
+    ) -> Result<(), Self::Error> {
+        self.write(SIGNATURE_KEY_PAIR_LABEL, public_key, signature_key_pair)
+    }
+
+    fn queued_proposal_refs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<ProposalRef>, Self::Error> {
+        self.read_list(PROPOSAL_QUEUE_REFS_LABEL, group_id)
+    }
+
+    fn queued_proposals<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+        QueuedProposal: traits::QueuedProposal<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<(ProposalRef, QueuedProposal)>, Self::Error> {
+        let refs: Vec<ProposalRef> = self.read_list(PROPOSAL_QUEUE_REFS_LABEL, group_id)?;
+
+        refs.into_iter()
+            .map(|proposal_ref| -> Result<_, SqliteStorageError> {
+                let proposal = self
+                    .read(QUEUED_PROPOSAL_LABEL, &(group_id, &proposal_ref))?
+                    .ok_or(SqliteStorageError::MissingValue)?;
+                Ok((proposal_ref, proposal))
+            })
+            .collect()
+    }
+
+    fn tree<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        TreeSync: traits::TreeSync<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<TreeSync>, Self::Error> {
+        self.read(TREE_LABEL, group_id)
+    }
+
+    fn group_context<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupContext: traits::GroupContext<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupContext>, Self::Error> {
+        self.read(GROUP_CONTEXT_LABEL, group_id)
+    }
+
+    fn interim_transcript_hash<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        InterimTranscriptHash: traits::InterimTranscriptHash<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<InterimTranscriptHash>, Self::Error> {
+        self.read(INTERIM_TRANSCRIPT_HASH_LABEL, group_id)
+    }
+
+    fn confirmation_tag<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ConfirmationTag: traits::ConfirmationTag<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<ConfirmationTag>, Self::Error> {
+        self.read(CONFIRMATION_TAG_LABEL, group_id)
+    }
+
+    fn signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+        SignatureKeyPair: traits::SignatureKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<Option<SignatureKeyPair>, Self::Error> {
+        self.read(SIGNATURE_KEY_PAIR_LABEL, public_key)
+    }
+
+    fn write_key_package<
+        HashReference: traits::HashReference<CURRENT_VERSION>,
+        KeyPackage: traits::KeyPackage<CURRENT_VERSION>,
+    >(
+        &self,
+        hash_ref: &HashReference,
+        key_package: &KeyPackage,
+    ) -> Result<(), Self::Error> {
+        self.write(KEY_PACKAGE_LABEL, hash_ref, key_package)
+    }
+
+    fn write_psk<
+        PskId: traits::PskId<CURRENT_VERSION>,
+        PskBundle: traits::PskBundle<CURRENT_VERSION>,
+    >(
+        &self,
+        psk_id: &PskId,
+        psk: &PskBundle,
+    ) -> Result<(), Self::Error> {
+        self.write(PSK_LABEL, psk_id, psk)
+    }
+
+    fn write_encryption_key_pair<
+        EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &EncryptionKey,
+        key_pair: &HpkeKeyPair,
+    ) -> Result<(), Self::Error> {
+        self.write(ENCRYPTION_KEY_PAIR_LABEL, public_key, key_pair)
+    }
+
+    fn key_package<
+        KeyPackageRef: traits::HashReference<CURRENT_VERSION>,
+        KeyPackage: traits::KeyPackage<CURRENT_VERSION>,
+    >(
+        &self,
+        hash_ref: &KeyPackageRef,
+    ) -> Result<Option<KeyPackage>, Self::Error> {
+        self.read(KEY_PACKAGE_LABEL, hash_ref)
+    }
+
+    fn psk<PskBundle: traits::PskBundle<CURRENT_VERSION>, PskId: traits::PskId<CURRENT_VERSION>>(
+        &self,
+        psk_id: &PskId,
+    ) -> Result<Option<PskBundle>, Self::Error> {
+        self.read(PSK_LABEL, psk_id)
+    }
+
+    fn encryption_key_pair<
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+        EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &EncryptionKey,
+    ) -> Result<Option<HpkeKeyPair>, Self::Error> {
+        self.read(ENCRYPTION_KEY_PAIR_LABEL, public_key)
+    }
+
+    fn delete_signature_key_pair<
+        SignaturePublicKey: traits::SignaturePublicKey<CURRENT_VERSION>,
+    >(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<(), Self::Error> {
+        self.delete(SIGNATURE_KEY_PAIR_LABEL, public_key)
+    }
+
+    fn delete_encryption_key_pair<EncryptionKey: traits::EncryptionKey<CURRENT_VERSION>>(
+        &self,
+        public_key: &EncryptionKey,
+    ) -> Result<(), Self::Error> {
+        self.delete(ENCRYPTION_KEY_PAIR_LABEL, public_key)
+    }
+
+    fn delete_key_package<KeyPackageRef: traits::HashReference<CURRENT_VERSION>>(
+        &self,
+        hash_ref: &KeyPackageRef,
+    ) -> Result<(), Self::Error> {
+        self.delete(KEY_PACKAGE_LABEL, hash_ref)
+    }
+
+    fn delete_psk<PskKey: traits::PskId<CURRENT_VERSION>>(
+        &self,
+        psk_id: &PskKey,
+    ) -> Result<(), Self::Error> {
+        self.delete(PSK_LABEL, psk_id)
+    }
+
+    fn group_state<
+        GroupState: traits::GroupState<CURRENT_VERSION>,
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupState>, Self::Error> {
+        self.read(GROUP_STATE_LABEL, group_id)
+    }
+
+    fn write_group_state<
+        GroupState: traits::GroupState<CURRENT_VERSION>,
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_state: &GroupState,
+    ) -> Result<(), Self::Error> {
+        self.write(GROUP_STATE_LABEL, group_id, group_state)
+    }
+
+    fn delete_group_state<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(GROUP_STATE_LABEL, group_id)
+    }
+
+    fn message_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MessageSecrets: traits::MessageSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<MessageSecrets>, Self::Error> {
+        self.read(MESSAGE_SECRETS_LABEL, group_id)
+    }
+
+    fn write_message_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MessageSecrets: traits::MessageSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        message_secrets: &MessageSecrets,
+    ) -> Result<(), Self::Error> {
+        self.write(MESSAGE_SECRETS_LABEL, group_id, message_secrets)
+    }
+
+    fn delete_message_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(MESSAGE_SECRETS_LABEL, group_id)
+    }
+
+    fn resumption_psk_store<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ResumptionPskStore: traits::ResumptionPskStore<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<ResumptionPskStore>, Self::Error> {
+        self.read(RESUMPTION_PSK_STORE_LABEL, group_id)
+    }
+
+    fn write_resumption_psk_store<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ResumptionPskStore: traits::ResumptionPskStore<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        resumption_psk_store: &ResumptionPskStore,
+    ) -> Result<(), Self::Error> {
+        self.write(RESUMPTION_PSK_STORE_LABEL, group_id, resumption_psk_store)
+    }
+
+    fn delete_all_resumption_psk_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(RESUMPTION_PSK_STORE_LABEL, group_id)
+    }
+
+    fn own_leaf_index<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNodeIndex: traits::LeafNodeIndex<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<LeafNodeIndex>, Self::Error> {
+        self.read(OWN_LEAF_NODE_INDEX_LABEL, group_id)
+    }
+
+    fn write_own_leaf_index<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNodeIndex: traits::LeafNodeIndex<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        own_leaf_index: &LeafNodeIndex,
+    ) -> Result<(), Self::Error> {
+        self.write(OWN_LEAF_NODE_INDEX_LABEL, group_id, own_leaf_index)
+    }
+
+    fn delete_own_leaf_index<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(OWN_LEAF_NODE_INDEX_LABEL, group_id)
+    }
+
+    fn group_epoch_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupEpochSecrets: traits::GroupEpochSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<GroupEpochSecrets>, Self::Error> {
+        self.read(EPOCH_SECRETS_LABEL, group_id)
+    }
+
+    fn write_group_epoch_secrets<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        GroupEpochSecrets: traits::GroupEpochSecrets<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        group_epoch_secrets: &GroupEpochSecrets,
+    ) -> Result<(), Self::Error> {
+        self.write(EPOCH_SECRETS_LABEL, group_id, group_epoch_secrets)
+    }
+
+    fn delete_group_epoch_secrets<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(EPOCH_SECRETS_LABEL, group_id)
+    }
+
+    fn write_encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+        key_pairs: &[HpkeKeyPair],
+    ) -> Result<(), Self::Error> {
+        self.write(
+            EPOCH_KEY_PAIRS_LABEL,
+            &(group_id, epoch, leaf_index),
+            key_pairs,
+        )
+    }
+
+    fn encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+        HpkeKeyPair: traits::HpkeKeyPair<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+    ) -> Result<Vec<HpkeKeyPair>, Self::Error> {
+        Ok(self
+            .read(EPOCH_KEY_PAIRS_LABEL, &(group_id, epoch, leaf_index))?
+            .unwrap_or_default())
+    }
+
+    fn delete_encryption_epoch_key_pairs<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        EpochKey: traits::EpochKey<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        epoch: &EpochKey,
+        leaf_index: u32,
+    ) -> Result<(), Self::Error> {
+        self.delete(EPOCH_KEY_PAIRS_LABEL, &(group_id, epoch, leaf_index))
+    }
+
+    fn clear_proposal_queue<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.transaction(|storage| {
+            let proposal_refs: Vec<ProposalRef> =
+                storage.read_list(PROPOSAL_QUEUE_REFS_LABEL, group_id)?;
+            for proposal_ref in proposal_refs {
+                storage.delete(QUEUED_PROPOSAL_LABEL, &(group_id, proposal_ref))?;
+            }
+            storage.delete(PROPOSAL_QUEUE_REFS_LABEL, group_id)
+        })
+    }
+
+    fn mls_group_join_config<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MlsGroupJoinConfig: traits::MlsGroupJoinConfig<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Option<MlsGroupJoinConfig>, Self::Error> {
+        self.read(JOIN_CONFIG_LABEL, group_id)
+    }
+
+    fn write_mls_join_config<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        MlsGroupJoinConfig: traits::MlsGroupJoinConfig<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        config: &MlsGroupJoinConfig,
+    ) -> Result<(), Self::Error> {
+        self.write(JOIN_CONFIG_LABEL, group_id, config)
+    }
+
+    fn own_leaf_nodes<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNode: traits::LeafNode<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<LeafNode>, Self::Error> {
+        self.read_list(OWN_LEAF_NODES_LABEL, group_id)
+    }
+
+    fn append_own_leaf_node<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        LeafNode: traits::LeafNode<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        leaf_node: &LeafNode,
+    ) -> Result<(), Self::Error> {
+        self.append(OWN_LEAF_NODES_LABEL, group_id, leaf_node)
+    }
+
+    fn delete_own_leaf_nodes<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(OWN_LEAF_NODES_LABEL, group_id)
+    }
+
+    fn delete_group_config<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(JOIN_CONFIG_LABEL, group_id)
+    }
+
+    fn delete_tree<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(TREE_LABEL, group_id)
+    }
+
+    fn delete_confirmation_tag<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(CONFIRMATION_TAG_LABEL, group_id)
+    }
+
+    fn delete_context<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(GROUP_CONTEXT_LABEL, group_id)
+    }
+
+    fn delete_interim_transcript_hash<GroupId: traits::GroupId<CURRENT_VERSION>>(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<(), Self::Error> {
+        self.delete(INTERIM_TRANSCRIPT_HASH_LABEL, group_id)
+    }
+
+    fn remove_proposal<
+        GroupId: traits::GroupId<CURRENT_VERSION>,
+        ProposalRef: traits::ProposalRef<CURRENT_VERSION>,
+    >(
+        &self,
+        group_id: &GroupId,
+        proposal_ref: &ProposalRef,
+    ) -> Result<(), Self::Error> {
+        self.transaction(|storage| {
+            storage.remove_item(PROPOSAL_QUEUE_REFS_LABEL, group_id, proposal_ref)?;
+            storage.delete(QUEUED_PROPOSAL_LABEL, &(group_id, proposal_ref))
+        })
+    }
+
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        let mut transaction = self.wait_for_transaction();
+        self.lock_connection().execute_batch("SAVEPOINT openmls")?;
+        transaction.owner = Some(thread::current().id());
+        transaction.depth += 1;
+        Ok(())
+    }
+
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        self.end_transaction("RELEASE openmls")
+    }
+
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        self.end_transaction("ROLLBACK TO openmls; RELEASE openmls")
+    }
+}
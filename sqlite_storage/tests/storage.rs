@@ -0,0 +1,278 @@
+use openmls_sqlite_storage::{SqliteStorage, SqliteStorageError, SCHEMA_VERSION};
+use openmls_traits::storage::{
+    migration::{StorageEntry, StorageMigrations, StorageVersion},
+    traits::{self},
+    Entity, Key, StorageProvider, CURRENT_VERSION,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+// Test types
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TestGroupId(Vec<u8>);
+impl traits::GroupId<CURRENT_VERSION> for TestGroupId {}
+impl Key<CURRENT_VERSION> for TestGroupId {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+struct ProposalRef(usize);
+impl traits::ProposalRef<CURRENT_VERSION> for ProposalRef {}
+impl Key<CURRENT_VERSION> for ProposalRef {}
+impl Entity<CURRENT_VERSION> for ProposalRef {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct Proposal(Vec<u8>);
+impl traits::QueuedProposal<CURRENT_VERSION> for Proposal {}
+impl Entity<CURRENT_VERSION> for Proposal {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct TreeSync(Vec<u8>);
+impl traits::TreeSync<CURRENT_VERSION> for TreeSync {}
+impl Entity<CURRENT_VERSION> for TreeSync {}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct GroupContext(u64);
+impl traits::GroupContext<CURRENT_VERSION> for GroupContext {}
+impl Entity<CURRENT_VERSION> for GroupContext {}
+
+/// Write and read some proposals
+#[test]
+fn read_write_delete() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let proposals = (0..10)
+        .map(|i| Proposal(format!("TestProposal{i}").as_bytes().to_vec()))
+        .collect::<Vec<_>>();
+    let storage = SqliteStorage::open_in_memory().unwrap();
+
+    // Store proposals
+    for (i, proposal) in proposals.iter().enumerate() {
+        storage
+            .queue_proposal(&group_id, &ProposalRef(i), proposal)
+            .unwrap();
+    }
+
+    // Read proposal refs
+    let proposal_refs_read: Vec<ProposalRef> = storage.queued_proposal_refs(&group_id).unwrap();
+    assert_eq!(
+        (0..10).map(ProposalRef).collect::<Vec<_>>(),
+        proposal_refs_read
+    );
+
+    // Read proposals
+    let proposals_read: Vec<(ProposalRef, Proposal)> = storage.queued_proposals(&group_id).unwrap();
+    let proposals_expected: Vec<(ProposalRef, Proposal)> =
+        (0..10).map(ProposalRef).zip(proposals.clone()).collect();
+    assert_eq!(proposals_expected, proposals_read);
+
+    // Remove proposal 5
+    storage.remove_proposal(&group_id, &ProposalRef(5)).unwrap();
+
+    let proposals_read: Vec<(ProposalRef, Proposal)> = storage.queued_proposals(&group_id).unwrap();
+    let mut proposals_expected: Vec<(ProposalRef, Proposal)> =
+        (0..10).map(ProposalRef).zip(proposals.clone()).collect();
+    proposals_expected.remove(5);
+    assert_eq!(proposals_expected, proposals_read);
+
+    // Clear all proposals
+    storage
+        .clear_proposal_queue::<TestGroupId, ProposalRef>(&group_id)
+        .unwrap();
+    let proposals_read: Vec<(ProposalRef, Proposal)> = storage.queued_proposals(&group_id).unwrap();
+    assert!(proposals_read.is_empty());
+}
+
+/// Writes in a failed transaction are rolled back.
+#[test]
+fn transaction_rollback() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = SqliteStorage::open_in_memory().unwrap();
+    storage.write_tree(&group_id, &TreeSync(vec![0])).unwrap();
+    storage.write_context(&group_id, &GroupContext(0)).unwrap();
+
+    let result = storage.transaction(|storage| {
+        storage.write_tree(&group_id, &TreeSync(vec![1]))?;
+        storage.write_context(&group_id, &GroupContext(1))?;
+        Err::<(), _>(SqliteStorageError::MissingValue)
+    });
+    assert!(matches!(result, Err(SqliteStorageError::MissingValue)));
+
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    let context: Option<GroupContext> = storage.group_context(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![0])));
+    assert_eq!(context, Some(GroupContext(0)));
+
+    // A successful transaction is committed.
+    storage
+        .transaction(|storage| {
+            storage.write_tree(&group_id, &TreeSync(vec![1]))?;
+            storage.write_context(&group_id, &GroupContext(1))
+        })
+        .unwrap();
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    let context: Option<GroupContext> = storage.group_context(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![1])));
+    assert_eq!(context, Some(GroupContext(1)));
+}
+
+/// Writes of other threads wait for an open transaction instead of becoming
+/// part of it.
+#[test]
+fn transaction_of_other_thread() {
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+    let storage = SqliteStorage::open_in_memory().unwrap();
+
+    std::thread::scope(|scope| {
+        storage.begin_transaction().unwrap();
+        storage.write_tree(&group_id, &TreeSync(vec![1])).unwrap();
+        let writer = scope.spawn(|| storage.write_context(&group_id, &GroupContext(1)));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let context: Option<GroupContext> = storage.group_context(&group_id).unwrap();
+        assert_eq!(context, None);
+
+        // Rolling back the transaction doesn't roll back the write of the
+        // other thread, which happens afterwards.
+        storage.rollback_transaction().unwrap();
+        writer.join().unwrap().unwrap();
+    });
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    let context: Option<GroupContext> = storage.group_context(&group_id).unwrap();
+    assert_eq!(tree, None);
+    assert_eq!(context, Some(GroupContext(1)));
+
+    // A transaction can't be ended without beginning it.
+    assert!(matches!(
+        storage.commit_transaction(),
+        Err(SqliteStorageError::NoTransaction)
+    ));
+}
+
+/// Values are still there after reopening the database.
+#[test]
+fn reopen() {
+    let path = std::env::temp_dir().join(format!(
+        "openmls_sqlite_storage_test_{}.db",
+        std::process::id()
+    ));
+    let group_id = TestGroupId(b"TestGroupId".to_vec());
+
+    {
+        let storage = SqliteStorage::open(&path).unwrap();
+        storage
+            .write_tree(&group_id, &TreeSync(vec![1, 2, 3]))
+            .unwrap();
+    }
+
+    let storage = SqliteStorage::open(&path).unwrap();
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![1, 2, 3])));
+
+    storage.delete_tree(&group_id).unwrap();
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, None);
+
+    drop(storage);
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Databases of newer schema versions are rejected.
+#[test]
+fn newer_schema_version() {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+
+    let err = SqliteStorage::from_connection(connection).unwrap_err();
+    assert!(matches!(
+        err,
+        SqliteStorageError::UnsupportedSchemaVersion(version) if version == SCHEMA_VERSION + 1
+    ));
+}
+
+/// Values of older storage versions are migrated with the registered
+/// migrations.
+#[test]
+fn migrate() {
+    // A database with a tree written by a release with storage version 0,
+    // which stored it under the label `RatchetTree`.
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE openmls_storage (
+                label   TEXT    NOT NULL,
+                key     BLOB    NOT NULL,
+                version INTEGER NOT NULL,
+                value   BLOB    NOT NULL,
+                PRIMARY KEY (label, key, version)
+            ) WITHOUT ROWID;
+            INSERT INTO openmls_storage VALUES ('RatchetTree', x'5b312c322c335d', 0, x'5b345d');",
+        )
+        .unwrap();
+    connection
+        .pragma_update(None, "user_version", SCHEMA_VERSION)
+        .unwrap();
+    let storage = SqliteStorage::from_connection(connection).unwrap();
+    let group_id = TestGroupId(vec![1, 2, 3]);
+
+    fn from_v0(entry: &mut StorageEntry) -> Result<(), String> {
+        if entry.label == b"RatchetTree" {
+            entry.label = b"Tree".to_vec();
+        }
+        Ok(())
+    }
+    let migrations =
+        StorageMigrations::new().register(StorageVersion::new(0), StorageVersion::CURRENT, from_v0);
+
+    // Without the migration no value is migrated.
+    let err = storage.migrate(&StorageMigrations::new()).unwrap_err();
+    assert!(matches!(err, SqliteStorageError::Migration(_)));
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, None);
+
+    assert_eq!(storage.migrate(&migrations).unwrap(), 1);
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![4])));
+    assert_eq!(storage.migrate(&migrations).unwrap(), 0);
+}
+
+/// An old value isn't migrated over a value that already exists in the
+/// current version.
+#[test]
+fn migrate_conflict() {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .execute_batch(&format!(
+            "CREATE TABLE openmls_storage (
+                label   TEXT    NOT NULL,
+                key     BLOB    NOT NULL,
+                version INTEGER NOT NULL,
+                value   BLOB    NOT NULL,
+                PRIMARY KEY (label, key, version)
+            ) WITHOUT ROWID;
+            INSERT INTO openmls_storage VALUES ('RatchetTree', x'5b312c322c335d', 0, x'5b345d');
+            INSERT INTO openmls_storage VALUES ('Tree', x'5b312c322c335d', {CURRENT_VERSION}, x'5b355d');",
+        ))
+        .unwrap();
+    connection
+        .pragma_update(None, "user_version", SCHEMA_VERSION)
+        .unwrap();
+    let storage = SqliteStorage::from_connection(connection).unwrap();
+    let group_id = TestGroupId(vec![1, 2, 3]);
+
+    fn from_v0(entry: &mut StorageEntry) -> Result<(), String> {
+        if entry.label == b"RatchetTree" {
+            entry.label = b"Tree".to_vec();
+        }
+        Ok(())
+    }
+    let migrations =
+        StorageMigrations::new().register(StorageVersion::new(0), StorageVersion::CURRENT, from_v0);
+
+    let err = storage.migrate(&migrations).unwrap_err();
+    assert!(matches!(err, SqliteStorageError::MigrationConflict));
+    let tree: Option<TreeSync> = storage.tree(&group_id).unwrap();
+    assert_eq!(tree, Some(TreeSync(vec![5])));
+
+    // The old value is kept, so the migration fails again.
+    let err = storage.migrate(&migrations).unwrap_err();
+    assert!(matches!(err, SqliteStorageError::MigrationConflict));
+}
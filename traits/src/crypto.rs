@@ -6,7 +6,7 @@ use tls_codec::SecretVLBytes;
 
 use crate::types::{
     AeadType, Ciphersuite, CryptoError, ExporterSecret, HashType, HpkeCiphertext, HpkeConfig,
-    HpkeKeyPair, KemOutput, SignatureScheme,
+    HpkeKeyPair, KemOutput, SignatureScheme, SupportedAlgorithms,
 };
 
 pub trait OpenMlsCrypto: Send + Sync {
@@ -18,6 +18,14 @@ pub trait OpenMlsCrypto: Send + Sync {
     /// Returns the list of supported [`Ciphersuite`]s.
     fn supported_ciphersuites(&self) -> Vec<Ciphersuite>;
 
+    /// Returns the [`SupportedAlgorithms`] of the backend.
+    ///
+    /// The default implementation derives them from the
+    /// [`supported_ciphersuites`](Self::supported_ciphersuites).
+    fn supported_algorithms(&self) -> SupportedAlgorithms {
+        SupportedAlgorithms::new(self.supported_ciphersuites())
+    }
+
     /// HKDF extract.
     ///
     /// Returns an error if the [`HashType`] is not supported.
@@ -45,6 +53,21 @@ pub trait OpenMlsCrypto: Send + Sync {
     /// Returns an error if the [`HashType`] is not supported.
     fn hash(&self, hash_type: HashType, data: &[u8]) -> Result<Vec<u8>, CryptoError>;
 
+    /// Returns a [`HashWriter`] to hash data that is written in parts, e.g.
+    /// while it is serialized, without collecting it in a single buffer first.
+    ///
+    /// Returns an error if the [`HashType`] is not supported. The default
+    /// implementation buffers the written data and calls [`hash`](Self::hash)
+    /// when the writer is finalized. Backends with incremental hash functions
+    /// should override it.
+    fn hash_writer(&self, hash_type: HashType) -> Result<Box<dyn HashWriter + '_>, CryptoError> {
+        Ok(Box::new(BufferedHashWriter {
+            crypto: self,
+            hash_type,
+            buffer: Vec::new(),
+        }))
+    }
+
     /// AEAD encrypt with the given parameters.
     ///
     /// Returns an error if the [`AeadType`] is not supported or an encryption
@@ -107,6 +130,29 @@ pub trait OpenMlsCrypto: Send + Sync {
         ptxt: &[u8],
     ) -> Result<HpkeCiphertext, CryptoError>;
 
+    /// HPKE single-shot encryption of the same `ptxt` to each of the `pk_rs`,
+    /// using `info` and `aad`.
+    ///
+    /// Returns the ciphertexts in the order of the `pk_rs`. Each recipient
+    /// requires its own encapsulation, such that the KEM costs the same as
+    /// separate calls of [`hpke_seal`](Self::hpke_seal). Backends can override
+    /// this to share the setup that doesn't depend on the recipient. The
+    /// default implementation calls [`hpke_seal`](Self::hpke_seal) for each
+    /// recipient.
+    fn hpke_seal_multi(
+        &self,
+        config: HpkeConfig,
+        pk_rs: &[&[u8]],
+        info: &[u8],
+        aad: &[u8],
+        ptxt: &[u8],
+    ) -> Result<Vec<HpkeCiphertext>, CryptoError> {
+        pk_rs
+            .iter()
+            .map(|pk_r| self.hpke_seal(config, pk_r, info, aad, ptxt))
+            .collect()
+    }
+
     /// HPKE single-shot decryption of `input` with `sk_r`, using `info` and
     /// `aad`.
     fn hpke_open(
@@ -149,4 +195,85 @@ pub trait OpenMlsCrypto: Send + Sync {
         config: HpkeConfig,
         ikm: &[u8],
     ) -> Result<HpkeKeyPair, CryptoError>;
+
+    /// Returns the [`CertificateValidator`] that is used to validate the
+    /// certificate chains of X.509 credentials.
+    ///
+    /// Returns `None` by default, in which case X.509 credentials are
+    /// rejected.
+    fn certificate_validator(&self) -> Option<&dyn CertificateValidator> {
+        None
+    }
+
+    /// Returns the [`TimeProvider`] that is used to create and validate the
+    /// lifetimes of key packages and leaf nodes.
+    ///
+    /// Returns `None` by default, in which case the system clock is used.
+    fn time_provider(&self) -> Option<&dyn TimeProvider> {
+        None
+    }
+}
+
+/// An incremental hash computation, see [`OpenMlsCrypto::hash_writer`].
+///
+/// The data to hash is written with [`std::io::Write`].
+pub trait HashWriter: std::io::Write {
+    /// Finish the computation and return the hash of all written data.
+    fn finalize(self: Box<Self>) -> Result<Vec<u8>, CryptoError>;
+}
+
+/// The [`HashWriter`] used by the default implementation of
+/// [`OpenMlsCrypto::hash_writer`].
+struct BufferedHashWriter<'a, Crypto: OpenMlsCrypto + ?Sized> {
+    crypto: &'a Crypto,
+    hash_type: HashType,
+    buffer: Vec<u8>,
+}
+
+impl<Crypto: OpenMlsCrypto + ?Sized> std::io::Write for BufferedHashWriter<'_, Crypto> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<Crypto: OpenMlsCrypto + ?Sized> HashWriter for BufferedHashWriter<'_, Crypto> {
+    fn finalize(self: Box<Self>) -> Result<Vec<u8>, CryptoError> {
+        self.crypto.hash(self.hash_type, &self.buffer)
+    }
+}
+
+/// A validator for the X.509 certificate chains in `x509` credentials.
+pub trait CertificateValidator: Send + Sync {
+    /// Validate the DER encoded certificate `chain`, which starts with the
+    /// end-entity certificate.
+    ///
+    /// Returns the public key of the end-entity certificate, encoded like
+    /// signature public keys for `signature_scheme`.
+    ///
+    /// Returns a [`CryptoError::InvalidCertificate`] if the chain is invalid,
+    /// e.g. because a certificate is malformed, expired or not trusted, or
+    /// because the key doesn't match the `signature_scheme`. Other errors,
+    /// e.g. a [`CryptoError::UnsupportedSignatureScheme`], mean that the chain
+    /// could not be validated.
+    fn validate_certificate_chain(
+        &self,
+        chain: &[&[u8]],
+        signature_scheme: SignatureScheme,
+    ) -> Result<Vec<u8>, CryptoError>;
+}
+
+/// A source of the current time, used to create and validate the lifetimes of
+/// key packages and leaf nodes.
+///
+/// Targets without a reliable system clock, e.g. embedded ones, can implement
+/// this trait to supply their own time.
+pub trait TimeProvider {
+    /// Returns the current time in seconds since the Unix epoch, or `None` if
+    /// the current time is not available.
+    fn now(&self) -> Option<u64>;
 }
@@ -0,0 +1,40 @@
+//! # Asynchronous key store
+//!
+//! The [`StorageProvider`](crate::storage::StorageProvider) is synchronous,
+//! such that backends that can only be reached asynchronously, e.g. a remote
+//! KMS, IndexedDB in the browser or a network HSM, would have to block the
+//! executor in every call.
+//!
+//! Such backends implement the [`OpenMlsKeyStoreAsync`] instead, which only
+//! stores opaque values under opaque keys. OpenMLS runs the group operations
+//! on an in-memory storage, which fetches the values needed by an operation
+//! from the asynchronous key store before and flushes the changed values to it
+//! after the operation.
+
+use std::{fmt::Debug, future::Future};
+
+/// An asynchronous key/value store for the values of the OpenMLS storage.
+///
+/// The keys and values are opaque byte strings. Implementations must not
+/// modify them, and a [`get`](Self::get) has to return the value of the last
+/// [`insert`](Self::insert) under the same key, unless it has been
+/// [`delete`](Self::delete)d.
+///
+/// The futures are not required to be [`Send`], such that single-threaded
+/// backends like IndexedDB can be used.
+// ANCHOR: openmls_key_store_async
+pub trait OpenMlsKeyStoreAsync {
+    /// The error type returned by the key store.
+    type Error: std::error::Error + Debug;
+
+    /// Get the value stored under `key`, or `None` if there is none.
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<Vec<u8>>, Self::Error>>;
+
+    /// Store the `value` under `key`, replacing a previous value.
+    fn insert(&self, key: &[u8], value: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Delete the value stored under `key`. Deleting a key that doesn't exist
+    /// is not an error.
+    fn delete(&self, key: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+}
+// ANCHOR_END: openmls_key_store_async
@@ -2,12 +2,22 @@ use crate::types::{CryptoError, SignatureScheme};
 /// Trait errors.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum SignerError {
+    /// An error occurred in the underlying crypto library.
     CryptoError(CryptoError),
+    /// The produced signature is invalid.
     InvalidSignature,
+    /// The payload could not be signed, e.g. because an external key store
+    /// was not available.
     SigningError,
 }
 
 /// Sign the provided payload and return a signature.
+///
+/// All signatures created by OpenMLS, e.g. on leaf nodes, key packages,
+/// framed content and group infos, are created through this trait. OpenMLS
+/// never needs access to the private signature key itself. Implementations
+/// can therefore keep the key in a hardware security module, a secure
+/// enclave, or an OS keystore and only hand out signatures.
 pub trait Signer {
     /// Sign the provided payload.
     ///
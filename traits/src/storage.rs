@@ -4,6 +4,9 @@
 //! also a few methods that append to lists (which behave similar to setters).
 
 use serde::{de::DeserializeOwned, Serialize};
+
+pub mod migration;
+
 /// The storage version used by OpenMLS
 pub const CURRENT_VERSION: u16 = 1;
 
@@ -551,6 +554,44 @@ pub trait StorageProvider<const VERSION: u16> {
         &self,
         psk_id: &PskKey,
     ) -> Result<(), Self::Error>;
+
+    //
+    //    ---   transactions  ---
+    //
+
+    /// Begins a transaction. All writes and deletes until the matching
+    /// [`commit_transaction`](Self::commit_transaction) or
+    /// [`rollback_transaction`](Self::rollback_transaction) must be applied
+    /// atomically. Transactions may be nested.
+    ///
+    /// OpenMLS uses transactions where several values have to change
+    /// together, e.g. when merging a commit, such that a crash can't leave a
+    /// group between two epochs.
+    ///
+    /// The default implementation does nothing, i.e. the writes are applied
+    /// one by one.
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Returns `true` if the storage implements the transaction functions.
+    /// Operations that can't be done safely without transactions, like
+    /// restoring a group snapshot, fail otherwise.
+    ///
+    /// The default implementation returns `false`.
+    fn supports_transactions(&self) -> bool {
+        false
+    }
+
+    /// Commits the innermost transaction.
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Rolls back all writes and deletes of the innermost transaction.
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 // base traits for keys and values
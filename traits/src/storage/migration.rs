@@ -0,0 +1,200 @@
+//! # Storage migrations
+//!
+//! Every entry persisted by a [`StorageProvider`](super::StorageProvider) is
+//! stored together with the [`StorageVersion`] it was written with. When a
+//! release of OpenMLS changes the format of the persisted entries, it
+//! increases [`CURRENT_VERSION`] and ships a [`Migration`] from the previous
+//! version. Storage providers apply the registered [`StorageMigrations`] to all
+//! entries of older versions, such that groups persisted by older releases can
+//! still be loaded after upgrading.
+//!
+//! ```
+//! use openmls_traits::storage::migration::*;
+//!
+//! // Version 0 stored the tree under another label.
+//! fn rename_tree(entry: &mut StorageEntry) -> Result<(), String> {
+//!     if entry.label == b"RatchetTree" {
+//!         entry.label = b"Tree".to_vec();
+//!     }
+//!     Ok(())
+//! }
+//!
+//! let migrations = StorageMigrations::new().register(
+//!     StorageVersion::new(0),
+//!     StorageVersion::CURRENT,
+//!     rename_tree,
+//! );
+//!
+//! let mut entry = StorageEntry {
+//!     label: b"RatchetTree".to_vec(),
+//!     key: b"[1,2,3]".to_vec(),
+//!     value: b"{}".to_vec(),
+//! };
+//! migrations
+//!     .migrate(StorageVersion::new(0), &mut entry)
+//!     .unwrap();
+//! assert_eq!(entry.label, b"Tree");
+//! ```
+
+use std::collections::BTreeMap;
+
+use super::CURRENT_VERSION;
+
+/// The version of the storage format an entry was written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StorageVersion(u16);
+
+impl StorageVersion {
+    /// The storage version used by OpenMLS, i.e. [`CURRENT_VERSION`].
+    pub const CURRENT: Self = Self(CURRENT_VERSION);
+
+    /// Create a storage version.
+    pub const fn new(version: u16) -> Self {
+        Self(version)
+    }
+
+    /// Returns the version number.
+    pub const fn value(self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for StorageVersion {
+    fn from(version: u16) -> Self {
+        Self(version)
+    }
+}
+
+impl std::fmt::Display for StorageVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An entry of the storage as it is passed to a [`Migration`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageEntry {
+    /// The label of the kind of the entry, e.g. `Tree`.
+    pub label: Vec<u8>,
+    /// The serialized key of the entry, e.g. the group ID.
+    pub key: Vec<u8>,
+    /// The serialized value of the entry.
+    pub value: Vec<u8>,
+}
+
+/// A function that migrates an entry from one storage version to the next.
+///
+/// The migration may change the label, key and value of the entry. Entries
+/// that the migration doesn't know have to be left unchanged.
+pub type Migration = fn(&mut StorageEntry) -> Result<(), String>;
+
+/// The migrations between storage versions.
+#[derive(Debug, Clone, Default)]
+pub struct StorageMigrations {
+    /// The migrations by the version they migrate from, together with the
+    /// version they migrate to.
+    migrations: BTreeMap<StorageVersion, (StorageVersion, Migration)>,
+}
+
+impl StorageMigrations {
+    /// Create an empty registry, which only accepts entries of the
+    /// [`StorageVersion::CURRENT`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the `migration` of entries from the version `from` to the
+    /// newer version `to`. A migration registered before for `from` is
+    /// replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to` is not newer than `from`.
+    pub fn register(
+        mut self,
+        from: StorageVersion,
+        to: StorageVersion,
+        migration: Migration,
+    ) -> Self {
+        assert!(to > from, "a migration must migrate to a newer version");
+        self.migrations.insert(from, (to, migration));
+        self
+    }
+
+    /// Returns `true` if entries of the `version` can be migrated to the
+    /// [`StorageVersion::CURRENT`].
+    pub fn supports(&self, mut version: StorageVersion) -> bool {
+        while version < StorageVersion::CURRENT {
+            match self.migrations.get(&version) {
+                Some((to, _)) => version = *to,
+                None => return false,
+            }
+        }
+        version == StorageVersion::CURRENT
+    }
+
+    /// Migrates the `entry` written with the `version` to the
+    /// [`StorageVersion::CURRENT`] by applying the registered migrations one
+    /// after the other. Entries of the current version are left unchanged.
+    ///
+    /// The `entry` may be partially migrated if an error is returned.
+    pub fn migrate(
+        &self,
+        mut version: StorageVersion,
+        entry: &mut StorageEntry,
+    ) -> Result<(), MigrationError> {
+        while version != StorageVersion::CURRENT {
+            if version > StorageVersion::CURRENT {
+                return Err(MigrationError::NewerVersion(version));
+            }
+            let (to, migration) = self
+                .migrations
+                .get(&version)
+                .ok_or(MigrationError::MissingMigration(version))?;
+            migration(entry).map_err(|reason| MigrationError::Failed { version, reason })?;
+            version = *to;
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors thrown when migrating storage entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The entry was written by a newer release with the version.
+    NewerVersion(StorageVersion),
+    /// No migration is registered from the version.
+    MissingMigration(StorageVersion),
+    /// The migration from the version failed.
+    Failed {
+        /// The version the failing migration migrates from.
+        version: StorageVersion,
+        /// The reason returned by the migration.
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NewerVersion(version) => {
+                write!(
+                    f,
+                    "The storage version {version} is newer than the supported one."
+                )
+            }
+            Self::MissingMigration(version) => {
+                write!(f, "No migration from the storage version {version}.")
+            }
+            Self::Failed { version, reason } => {
+                write!(
+                    f,
+                    "The migration from the storage version {version} failed: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
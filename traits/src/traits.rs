@@ -4,6 +4,8 @@
 //! API of OpenMLS.
 
 pub mod crypto;
+#[cfg(feature = "async")]
+pub mod key_store_async;
 pub mod public_storage;
 pub mod random;
 pub mod signatures;
@@ -145,6 +145,8 @@ pub enum CryptoError {
     TooMuchData,
     SigningError,
     InvalidPublicKey,
+    InvalidCertificate,
+    KeyDecodingError,
 }
 
 impl std::fmt::Display for CryptoError {
@@ -158,7 +160,7 @@ impl std::error::Error for CryptoError {}
 // === HPKE === //
 
 /// Convenience tuple struct for an HPKE configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct HpkeConfig(pub HpkeKemType, pub HpkeKdfType, pub HpkeAeadType);
 
 /// KEM Types for HPKE
@@ -373,6 +375,9 @@ pub enum Ciphersuite {
     MLS_256_DHKEMP384_AES256GCM_SHA384_P384 = 0x0007,
 
     /// X-WING KEM draft-01 | Chacha20Poly1305 | SHA2-256 | Ed25519
+    ///
+    /// Experimental post-quantum hybrid ciphersuite. X-Wing combines X25519
+    /// and ML-KEM-768. Only implemented by the libcrux provider.
     MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519 = 0x004D,
 }
 
@@ -606,3 +611,75 @@ impl Ciphersuite {
         self.aead_algorithm().nonce_size()
     }
 }
+
+/// The algorithms implemented by a crypto provider, as returned by
+/// [`OpenMlsCrypto::supported_algorithms`](crate::crypto::OpenMlsCrypto::supported_algorithms).
+///
+/// The primitives are derived from the supported ciphersuites, such that a
+/// primitive is listed if it is used by at least one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedAlgorithms {
+    ciphersuites: Vec<Ciphersuite>,
+    signature_schemes: Vec<SignatureScheme>,
+    hpke_kems: Vec<HpkeKemType>,
+    aeads: Vec<AeadType>,
+    hashes: Vec<HashType>,
+}
+
+impl SupportedAlgorithms {
+    /// Collect the algorithms used by the `ciphersuites`.
+    pub fn new(ciphersuites: Vec<Ciphersuite>) -> Self {
+        fn dedup<T: PartialEq>(values: impl Iterator<Item = T>) -> Vec<T> {
+            let mut deduped = Vec::new();
+            for value in values {
+                if !deduped.contains(&value) {
+                    deduped.push(value);
+                }
+            }
+            deduped
+        }
+
+        Self {
+            signature_schemes: dedup(ciphersuites.iter().map(|c| c.signature_algorithm())),
+            hpke_kems: dedup(ciphersuites.iter().map(|c| c.hpke_kem_algorithm())),
+            aeads: dedup(ciphersuites.iter().map(|c| c.aead_algorithm())),
+            hashes: dedup(ciphersuites.iter().map(|c| c.hash_algorithm())),
+            ciphersuites,
+        }
+    }
+
+    /// Returns the supported ciphersuites.
+    pub fn ciphersuites(&self) -> &[Ciphersuite] {
+        &self.ciphersuites
+    }
+
+    /// Returns the supported signature schemes.
+    pub fn signature_schemes(&self) -> &[SignatureScheme] {
+        &self.signature_schemes
+    }
+
+    /// Returns the supported HPKE KEMs.
+    pub fn hpke_kems(&self) -> &[HpkeKemType] {
+        &self.hpke_kems
+    }
+
+    /// Returns the supported AEADs.
+    pub fn aeads(&self) -> &[AeadType] {
+        &self.aeads
+    }
+
+    /// Returns the supported hash functions.
+    pub fn hashes(&self) -> &[HashType] {
+        &self.hashes
+    }
+
+    /// Returns `true` if the `ciphersuite` is supported.
+    pub fn supports_ciphersuite(&self, ciphersuite: Ciphersuite) -> bool {
+        self.ciphersuites.contains(&ciphersuite)
+    }
+
+    /// Returns `true` if the `signature_scheme` is supported.
+    pub fn supports_signature_scheme(&self, signature_scheme: SignatureScheme) -> bool {
+        self.signature_schemes.contains(&signature_scheme)
+    }
+}